@@ -0,0 +1,80 @@
+//! Whether a session looks open in another terminal. Resuming one that is forks it in
+//! confusing ways, so the TUI asks first and `recall --last` wants `--force`. The check is a
+//! heuristic: the session file changed recently, and (where the process list can be read) its
+//! agent is running in the session's folder or with its ID. It gives up on the process list
+//! after 100 ms rather than hold up a resume.
+
+use crate::session::Session;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// A session changed this recently, with its agent running, looks active
+const RECENT: Duration = Duration::from_secs(10 * 60);
+
+/// Without a process list to confirm it (other platforms, remote sessions), only a change
+/// this recent counts
+const RECENT_UNCONFIRMED: Duration = Duration::from_secs(60);
+
+/// Longest the process list is searched
+#[cfg(target_os = "linux")]
+const BUDGET: Duration = Duration::from_millis(100);
+
+/// Whether `session` looks like it's open somewhere else right now
+pub fn looks_active(session: &Session) -> bool {
+    let Some(age) = std::fs::metadata(crate::parser::backing_file(&session.file_path))
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| SystemTime::now().duration_since(t).ok())
+    else {
+        return false;
+    };
+    if age > RECENT {
+        return false;
+    }
+    let running = if session.remote_host().is_some() {
+        None
+    } else {
+        agent_running(session.source.program(), &session.id, Path::new(&session.cwd))
+    };
+    running.unwrap_or(age <= RECENT_UNCONFIRMED)
+}
+
+/// Whether a process named `program` runs in `cwd` or was given `id`, or None if the
+/// process list can't be read in time
+#[cfg(target_os = "linux")]
+fn agent_running(program: &str, id: &str, cwd: &Path) -> Option<bool> {
+    use std::time::Instant;
+
+    let deadline = Instant::now() + BUDGET;
+    let cwd = cwd.canonicalize().unwrap_or_else(|_| cwd.to_path_buf());
+    for entry in std::fs::read_dir("/proc").ok()?.flatten() {
+        if Instant::now() > deadline {
+            return None;
+        }
+        let Ok(cmdline) = std::fs::read(entry.path().join("cmdline")) else {
+            continue;
+        };
+        let args: Vec<String> = cmdline
+            .split(|&b| b == 0)
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect();
+        // The program itself, or the script an interpreter (node, python) runs
+        let named = args
+            .iter()
+            .take(2)
+            .any(|arg| Path::new(arg).file_name().is_some_and(|name| name == program));
+        if !named {
+            continue;
+        }
+        let in_cwd = std::fs::read_link(entry.path().join("cwd")).is_ok_and(|dir| dir == cwd);
+        if in_cwd || args.iter().any(|arg| arg == id) {
+            return Some(true);
+        }
+    }
+    Some(false)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn agent_running(_program: &str, _id: &str, _cwd: &Path) -> Option<bool> {
+    None
+}
@@ -1,21 +1,37 @@
-use crate::index::{discover_and_sort_files, index_files, IndexProgress, IndexState, SessionIndex};
+use crate::config::{Config, DefaultScope, OpenConfig, PathMap, PreviewConfig};
+use crate::history::JumpList;
+use crate::index::{
+    cache, discover_and_sort_files, expired_files, files_to_index, index_files, prune_files,
+    purge_files, retained_files, stale_files, IndexProgress, IndexState, SessionIndex,
+};
 use crate::parser;
-use crate::session::{SearchResult, Session};
+use crate::session::{MatchHit, SearchResult, Session, SessionSource};
+use crate::{bookmarks, notes, tags, titles};
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread;
 use std::time::{Duration, Instant};
 
-/// Debounce delay for search (avoid searching on every keystroke during fast typing/paste)
-const SEARCH_DEBOUNCE: Duration = Duration::from_millis(50);
-
 /// Messages from the indexing thread
 pub enum IndexMsg {
     Progress { indexed: usize, total: usize },
-    Done { total_sessions: usize },
+    /// `skipped` counts empty and unparseable files; `failures` lists the latter with the reason.
+    /// `pruned` sessions were removed for being older than `retention`.
+    Done {
+        total_sessions: usize,
+        skipped: usize,
+        pruned: usize,
+        failures: Vec<(PathBuf, String)>,
+    },
     NeedsReload,
+    /// `[[remote]]` hosts that couldn't be synced, with why; indexing goes on without them
+    RemoteFailed(Vec<(String, String)>),
+    /// Settings changed in a way that needs `--reindex` to apply (see
+    /// [`IndexState::reindex_hint`])
+    ReindexHint(String),
     Error(String),
 }
 
@@ -26,6 +42,151 @@ pub enum SearchScope {
     Everything,
     /// Search only conversations from a specific folder
     Folder(String),
+    /// Search conversations from a repository root or anywhere beneath it
+    Repo(String),
+}
+
+impl SearchScope {
+    /// Whether a session in `cwd` falls inside this scope
+    pub fn contains(&self, cwd: &str) -> bool {
+        match self {
+            SearchScope::Everything => true,
+            SearchScope::Folder(path) => cwd == path,
+            SearchScope::Repo(root) => in_scope(cwd, Path::new(root)),
+        }
+    }
+}
+
+/// A one-line input in the status bar, editing the selected session
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub input: String,
+}
+
+/// What a [`Prompt`] edits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptKind {
+    /// Comma-separated tags (Ctrl+T)
+    Tags,
+    /// A title in place of the derived one, blank to go back to it (F2)
+    Title,
+}
+
+impl PromptKind {
+    /// Label shown before the input
+    pub fn label(self) -> &'static str {
+        match self {
+            PromptKind::Tags => "Tags",
+            PromptKind::Title => "Title",
+        }
+    }
+
+    /// What to type, shown after the input
+    pub fn hint(self) -> &'static str {
+        match self {
+            PromptKind::Tags => "comma-separated, Enter to save, Esc to cancel",
+            PromptKind::Title => "empty for the original, Enter to save, Esc to cancel",
+        }
+    }
+}
+
+/// A session's folder and the command that opens it (Ctrl+O)
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenFolder {
+    pub cwd: String,
+    pub program: String,
+    pub args: Vec<String>,
+    /// The program runs in this terminal, so the TUI has to step aside until it exits
+    pub terminal: bool,
+}
+
+/// Query that lists bookmarked messages (Ctrl+B bookmarks one)
+pub const BOOKMARKS_QUERY: &str = "is:bookmarked";
+
+/// Longest scope label shown in full, in terminal columns
+const MAX_SCOPE_WIDTH: usize = 25;
+
+/// How long a toast stays in the status bar
+const TOAST_DURATION: Duration = Duration::from_secs(2);
+
+/// How long the selection has to rest on a session before it goes into the jump list
+const SETTLE_TIME: Duration = Duration::from_secs(1);
+
+/// Columns Left/Right move an unwrapped message
+const HSCROLL_STEP: usize = 8;
+
+/// A visited session and how it was being viewed, for Alt+←/→
+#[derive(Debug, Clone)]
+struct Jump {
+    session_id: String,
+    file_path: PathBuf,
+    query: String,
+    scope: SearchScope,
+    preview_scroll: usize,
+    focused_message: Option<usize>,
+}
+
+/// `path` with `home` shown as `~`, cut down to the last folder when it's wider than
+/// [`MAX_SCOPE_WIDTH`]. Both `/` and `\` separate components, so Windows paths work too.
+fn compact_path(path: &str, home: &str) -> String {
+    let is_sep = |c: char| c == '/' || c == '\\';
+    let home = home.trim_end_matches(is_sep);
+
+    // Only a whole-component prefix counts: /home/al is not the home of /home/alice
+    let display_path = match path.strip_prefix(home) {
+        Some(rest) if !home.is_empty() && (rest.is_empty() || rest.starts_with(is_sep)) => {
+            format!("~{}", rest)
+        }
+        _ => path.to_string(),
+    };
+    if ratatui::text::Span::raw(display_path.as_str()).width() <= MAX_SCOPE_WIDTH {
+        return display_path;
+    }
+
+    let trimmed = path.trim_end_matches(is_sep);
+    let last_component = trimmed.rsplit(is_sep).next().unwrap_or(trimmed);
+    let sep = if path.contains('/') { '/' } else { '\\' };
+    let prefix = if display_path.starts_with('~') { "~" } else { "" };
+    format!("{prefix}{sep}...{sep}{last_component}")
+}
+
+/// The enclosing git repository of `dir` if there is one, otherwise `dir` itself
+pub fn scope_root(dir: &Path) -> PathBuf {
+    dir.ancestors()
+        .find(|d| d.join(".git").exists())
+        .unwrap_or(dir)
+        .to_path_buf()
+}
+
+/// `--cwd` the way sessions record folders: absolute with symlinks resolved, or if it isn't
+/// a folder here (sessions from another machine), as given without a trailing `/`
+fn scope_dir(cwd: &str) -> String {
+    match Path::new(cwd).canonicalize() {
+        Ok(dir) => dir.to_string_lossy().to_string(),
+        Err(_) if cwd.len() > 1 => cwd.trim_end_matches('/').to_string(),
+        Err(_) => cwd.to_string(),
+    }
+}
+
+/// Whether a session's cwd is `root` or somewhere beneath it
+pub fn in_scope(cwd: &str, root: &Path) -> bool {
+    !cwd.is_empty() && Path::new(cwd).starts_with(root)
+}
+
+/// How the TUI starts, from command-line flags
+#[derive(Debug, Clone, Default)]
+pub struct AppOptions {
+    /// Initial search query
+    pub query: String,
+    /// Start searching everything, whatever the configured default scope
+    pub everywhere: bool,
+    /// Scope to this folder instead of the launch folder
+    pub cwd: Option<String>,
+    /// Only show sessions from this source
+    pub source: Option<SessionSource>,
+    /// Only show sessions after this time
+    pub since: Option<DateTime<Utc>>,
 }
 
 pub struct App {
@@ -37,6 +198,12 @@ pub struct App {
     pub results: Vec<SearchResult>,
     /// Selected result index
     pub selected: usize,
+    /// Session whose result shows its other matches beneath it (Right)
+    pub expanded_result: Option<String>,
+    /// Which of the expanded result's other matches is selected (None for the result itself)
+    pub selected_match: Option<usize>,
+    /// Where the selected result's "+N more matches" line was drawn (x, y, width), for clicks
+    pub more_matches_line: Option<(u16, u16, u16)>,
     /// Results list scroll offset
     pub list_scroll: usize,
     /// Preview scroll offset
@@ -45,6 +212,13 @@ pub struct App {
     pub focused_message: Option<usize>,
     /// Set of expanded message indices (shown in full, not truncated)
     pub expanded_messages: HashSet<usize>,
+    /// Messages whose long lines are cut off at the edge instead of wrapped (Ctrl+W)
+    pub unwrapped_messages: HashSet<usize>,
+    /// Columns the focused message is scrolled right by, while it's unwrapped
+    pub preview_hscroll: usize,
+    /// Furthest `preview_hscroll` goes: how far the focused message's longest line overflows
+    /// the preview (set by render_preview)
+    pub preview_max_hscroll: usize,
     /// Total message count in current preview (for navigation bounds)
     pub preview_message_count: usize,
     /// Whether the focused message can be expanded/collapsed
@@ -61,70 +235,154 @@ pub struct App {
     pub should_quit: bool,
     /// Should execute resume (set on Enter)
     pub should_resume: Option<Session>,
+    /// A session that looks open elsewhere, waiting for y/n before it's resumed
+    pub confirm_resume: Option<Session>,
     /// Session ID to copy (set on Tab)
     pub should_copy: Option<String>,
+    /// Text to copy without leaving the TUI (set on Ctrl+X)
+    pub clipboard: Option<String>,
+    /// Folder to open without leaving the TUI (set on Ctrl+O)
+    pub should_open: Option<OpenFolder>,
+    /// Whether Enter selects the session instead of resuming it (`--select`)
+    pub select_mode: bool,
+    /// Session chosen in select mode (set on Enter)
+    pub should_select: Option<Session>,
     /// Index for searching
     index: SessionIndex,
+    /// The index's state file, or None when the index is in memory
+    state_path: Option<PathBuf>,
     /// Status message (for indexing progress, etc.)
     pub status: Option<String>,
+    /// Short-lived message shown in place of the status bar, and when it was shown
+    toast: Option<(String, Instant)>,
     /// Total sessions indexed
     pub total_sessions: usize,
+    /// Discovered files that aren't searchable: empty, or failed to parse
+    pub skipped_sessions: usize,
+    /// Sessions removed from the index this run for being older than `retention`
+    pub pruned_sessions: usize,
+    /// Files that failed to index this run, with the reason (shown on exit)
+    pub index_failures: Vec<(PathBuf, String)>,
+    /// Remote hosts that couldn't be synced this run, with the reason (shown on exit)
+    pub remote_failures: Vec<(String, String)>,
     /// Channel to receive indexing updates
     index_rx: Option<Receiver<IndexMsg>>,
     /// Is indexing in progress
     pub indexing: bool,
     /// Current search scope
     pub search_scope: SearchScope,
+    /// The scope `/` toggles back to from everything
+    local_scope: SearchScope,
     /// Launch directory (for folder-scoped search)
     pub launch_cwd: String,
+    /// Only show sessions from this source (`--source`)
+    pub source_filter: Option<SessionSource>,
+    /// Only show sessions after this time (`--since`)
+    pub since_filter: Option<DateTime<Utc>>,
+    /// Debounce delay for search (avoid searching on every keystroke during fast typing/paste)
+    debounce: Duration,
+    /// Maximum number of results shown
+    max_results: usize,
+    /// How long preview messages are collapsed
+    pub preview: PreviewConfig,
+    /// How Ctrl+O opens a session's folder
+    open: OpenConfig,
+    /// Where folders recorded elsewhere are on this machine, for scoping and opening
+    path_map: PathMap,
+    /// strftime format for dates older than a few weeks
+    pub date_format: Option<String>,
+    /// Draw plain labeled text for screen readers (`--accessible`)
+    pub accessible: bool,
     /// Whether a search is pending (for debouncing)
     search_pending: bool,
     /// When the last input occurred (for debouncing)
     last_input: Instant,
     /// Error from indexing thread (shown on exit)
     pub index_error: Option<String>,
+    /// Sessions visited, for Alt+←/→
+    jumps: JumpList<Jump>,
+    /// The selected session and when it was selected, until it goes into `jumps`
+    settling: Option<(String, Instant)>,
+    /// The tag or title prompt while it's open
+    pub prompt: Option<Prompt>,
+    /// The note overlay's text while it's open (Ctrl+N)
+    pub note_input: Option<String>,
+    /// Files whose tags, note, or title changed while the indexer was busy, reindexed once it's
+    /// done
+    pending_reindex: Vec<PathBuf>,
 }
 
 impl App {
     pub fn new(initial_query: String) -> Result<Self> {
-        // Allow override for testing
-        let cache_dir = std::env::var("RECALL_HOME_OVERRIDE")
-            .map(|h| PathBuf::from(h).join(".cache").join("recall"))
-            .unwrap_or_else(|_| {
-                dirs::cache_dir()
-                    .unwrap_or_else(|| PathBuf::from("."))
-                    .join("recall")
-            });
-
-        let index_path = cache_dir.join("index");
-        let state_path = cache_dir.join("state.json");
+        Self::with_options(AppOptions {
+            query: initial_query,
+            ..Default::default()
+        })
+    }
 
-        let index = SessionIndex::open_or_create(&index_path)?;
+    pub fn with_options(options: AppOptions) -> Result<Self> {
+        Self::with_config(options, &Config::load()?)
+    }
 
-        // Get launch directory (override for tests)
-        let launch_cwd = std::env::var("RECALL_CWD_OVERRIDE").unwrap_or_else(|_| {
-            std::env::current_dir()
-                .map(|p| p.to_string_lossy().to_string())
-                .unwrap_or_default()
+    pub fn with_config(options: AppOptions, config: &Config) -> Result<Self> {
+        // With privacy.ephemeral the index is built in memory and dropped on exit
+        let index_path = (!config.privacy.ephemeral).then(|| cache::dir().join("index"));
+        let mut index = match &index_path {
+            Some(path) => SessionIndex::open_or_create(path)?,
+            None => SessionIndex::in_memory()?,
+        };
+        index.set_snippet_chars(config.search.snippet_chars);
+        index.set_memory(&config.index);
+
+        // Get launch directory (override for tests); --cwd stands in for it
+        let launch_cwd = options.cwd.as_deref().map(scope_dir).unwrap_or_else(|| {
+            std::env::var("RECALL_CWD_OVERRIDE").unwrap_or_else(|_| {
+                std::env::current_dir()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            })
         });
+        // --cwd and --everywhere win over the configured default
+        let local_scope = match config.default_scope {
+            DefaultScope::Repo if options.cwd.is_none() => SearchScope::Repo(
+                scope_root(Path::new(&launch_cwd)).to_string_lossy().to_string(),
+            ),
+            _ => SearchScope::Folder(launch_cwd.clone()),
+        };
+        let search_scope = if options.everywhere
+            || (options.cwd.is_none() && config.default_scope == DefaultScope::Everywhere)
+        {
+            SearchScope::Everything
+        } else {
+            local_scope.clone()
+        };
+
+        let state_path = index_path.as_deref().map(cache::state_path);
 
         // Start background indexing
         let (tx, rx) = mpsc::channel();
-        let index_path_clone = index_path.clone();
+        let background = index.clone();
+        let cutoff = config.retention_cutoff();
         thread::spawn(move || {
-            background_index(index_path_clone, state_path, tx);
+            background_index(background, index_path, cutoff, tx);
         });
 
-        let initial_cursor = initial_query.chars().count();
+        let initial_cursor = options.query.chars().count();
         let mut app = Self {
-            query: initial_query,
+            query: options.query,
             cursor: initial_cursor,
             results: Vec::new(),
             selected: 0,
+            expanded_result: None,
+            selected_match: None,
+            more_matches_line: None,
             list_scroll: 0,
             preview_scroll: 0,
             focused_message: None,
             expanded_messages: HashSet::new(),
+            unwrapped_messages: HashSet::new(),
+            preview_hscroll: 0,
+            preview_max_hscroll: 0,
             preview_message_count: 0,
             focused_message_expandable: false,
             message_line_ranges: Vec::new(),
@@ -133,17 +391,43 @@ impl App {
             preview_scrollable: false,
             should_quit: false,
             should_resume: None,
+            confirm_resume: None,
             should_copy: None,
+            clipboard: None,
+            should_open: None,
+            select_mode: false,
+            should_select: None,
             index,
+            state_path,
             status: None,
+            toast: None,
             total_sessions: 0,
+            skipped_sessions: 0,
+            pruned_sessions: 0,
+            index_failures: Vec::new(),
+            remote_failures: Vec::new(),
             index_rx: Some(rx),
             indexing: true,
-            search_scope: SearchScope::Folder(launch_cwd.clone()),
+            search_scope,
+            local_scope,
             launch_cwd,
+            source_filter: options.source,
+            since_filter: options.since,
+            debounce: Duration::from_millis(config.search.debounce_ms),
+            max_results: config.search.max_results,
+            preview: config.preview.clone(),
+            open: config.open.clone(),
+            path_map: config.path_map.clone(),
+            date_format: config.date_format.clone(),
+            accessible: config.accessible,
             search_pending: false,
             last_input: Instant::now(),
             index_error: None,
+            jumps: JumpList::default(),
+            settling: None,
+            prompt: None,
+            note_input: None,
+            pending_reindex: Vec::new(),
         };
 
         // If there's an initial query, run the search immediately
@@ -190,14 +474,24 @@ impl App {
                     needs_reload = true;
                     needs_search = true;
                 }
-                IndexMsg::Done { total_sessions } => {
+                IndexMsg::Done { total_sessions, skipped, pruned, failures } => {
                     self.total_sessions = total_sessions;
+                    self.skipped_sessions = skipped;
+                    self.pruned_sessions = pruned;
+                    self.index_failures = failures;
                     self.status = None;
                     self.indexing = false;
                     should_close_rx = true;
                     needs_reload = true;
                     needs_search = true;
                 }
+                IndexMsg::RemoteFailed(failures) => {
+                    let hosts: Vec<&str> = failures.iter().map(|(h, _)| h.as_str()).collect();
+                    let hosts = hosts.join(", ");
+                    self.show_toast(format!("Couldn't sync {} • using the last copy", hosts));
+                    self.remote_failures = failures;
+                }
+                IndexMsg::ReindexHint(hint) => self.show_toast(hint),
                 IndexMsg::Error(err) => {
                     self.index_error = Some(err);
                     self.status = Some("Index error • Ctrl+C for details".to_string());
@@ -215,6 +509,13 @@ impl App {
             should_close_rx = true;
         }
 
+        if !self.indexing && !self.pending_reindex.is_empty() {
+            let files = std::mem::take(&mut self.pending_reindex);
+            if let Err(e) = self.reindex_files(&files) {
+                self.show_toast(format!("Couldn't reindex: {:#}", e));
+            }
+            needs_search = true;
+        }
         if needs_reload {
             let _ = self.index.reload();
         }
@@ -232,14 +533,20 @@ impl App {
         let selected_session_id = self.results.get(self.selected).map(|r| r.session.id.clone());
 
         let mut results = if self.query.is_empty() {
-            self.index.recent(50)?
+            self.index.recent(self.max_results)?
+        } else if self.bookmark_view() {
+            bookmarks::results()
         } else {
-            self.index.search(&self.query, 50)?
+            self.index.search(&self.query, self.max_results)?
         };
 
-        // Filter by scope if searching within a folder
-        if let SearchScope::Folder(ref cwd) = self.search_scope {
-            results.retain(|r| r.session.cwd == *cwd);
+        // Filter by scope if searching within a folder or repo
+        results.retain(|r| self.search_scope.contains(&self.path_map.apply(&r.session.cwd)));
+        if let Some(source) = self.source_filter {
+            results.retain(|r| r.session.source == source);
+        }
+        if let Some(since) = self.since_filter {
+            results.retain(|r| r.session.timestamp >= since);
         }
 
         self.results = results;
@@ -258,16 +565,46 @@ impl App {
             self.selected = 0;
             self.list_scroll = 0;
         }
+        // Other matches stay open only while the session is still there and still has them
+        let expanded = self.expanded_result.take();
+        self.expanded_result = expanded.filter(|id| {
+            self.results.iter().any(|r| &r.session.id == id && !r.more_matches.is_empty())
+        });
+        let shown = self.expanded_matches().map_or(0, <[MatchHit]>::len);
+        self.selected_match = self.selected_match.filter(|&m| m < shown);
         self.update_preview_scroll();
 
         Ok(())
     }
 
-    /// Toggle search scope between everything and current folder
+    /// Indexing problems to print after the TUI exits (empty if there were none)
+    pub fn exit_diagnostics(&self) -> String {
+        let mut out = String::new();
+        if let Some(ref err) = self.index_error {
+            out.push_str(&format!("\nIndexing error:\n  {}\n\nTry: recall --reindex\n\n", err));
+        }
+        if !self.index_failures.is_empty() {
+            out.push_str(&format!(
+                "\nSkipped {} session file{} that couldn't be read:\n",
+                self.index_failures.len(),
+                if self.index_failures.len() == 1 { "" } else { "s" }
+            ));
+            for (path, err) in &self.index_failures {
+                out.push_str(&format!("  {}: {}\n", path.display(), err));
+            }
+            out.push('\n');
+        }
+        for (host, err) in &self.remote_failures {
+            out.push_str(&format!("\nCouldn't sync remote {}:\n  {}\n", host, err));
+        }
+        out
+    }
+
+    /// Toggle search scope between everything and the current folder (or repo)
     pub fn toggle_scope(&mut self) {
         self.search_scope = match self.search_scope {
-            SearchScope::Everything => SearchScope::Folder(self.launch_cwd.clone()),
-            SearchScope::Folder(_) => SearchScope::Everything,
+            SearchScope::Everything => self.local_scope.clone(),
+            SearchScope::Folder(_) | SearchScope::Repo(_) => SearchScope::Everything,
         };
         let _ = self.search();
     }
@@ -276,7 +613,7 @@ impl App {
     pub fn scope_folder_name(&self) -> Option<&str> {
         match &self.search_scope {
             SearchScope::Everything => None,
-            SearchScope::Folder(path) => {
+            SearchScope::Folder(path) | SearchScope::Repo(path) => {
                 path.rsplit(std::path::MAIN_SEPARATOR).next()
             }
         }
@@ -289,29 +626,14 @@ impl App {
     pub fn scope_display_path(&self) -> Option<String> {
         let path = match &self.search_scope {
             SearchScope::Everything => return None,
-            SearchScope::Folder(path) => path.as_str(),
+            SearchScope::Folder(path) | SearchScope::Repo(path) => path.as_str(),
         };
 
-        // Replace home dir with ~ (HOME on Unix, USERPROFILE on Windows)
+        // HOME on Unix, USERPROFILE on Windows
         let home = std::env::var("HOME")
             .or_else(|_| std::env::var("USERPROFILE"))
             .unwrap_or_default();
-        let display_path = if !home.is_empty() && path.starts_with(&home) {
-            format!("~{}", &path[home.len()..])
-        } else {
-            path.to_string()
-        };
-
-        // If short enough, show full path
-        const MAX_LEN: usize = 25;
-        if display_path.len() <= MAX_LEN {
-            return Some(display_path);
-        }
-
-        // Otherwise show prefix/.../<last_dir>
-        let last_component = path.rsplit(std::path::MAIN_SEPARATOR).next().unwrap_or(path);
-        let prefix = if display_path.starts_with('~') { "~" } else { "" };
-        Some(format!("{}/.../{}", prefix, last_component))
+        Some(compact_path(path, &home))
     }
 
     /// Handle character input
@@ -354,16 +676,68 @@ impl App {
         }
     }
 
-    /// Move cursor left
+    /// Move cursor left, or at the end of the query, fold the selected result's other matches
+    /// back up
     pub fn on_left(&mut self) {
+        if self.focused_unwrapped() {
+            self.preview_hscroll = self.preview_hscroll.saturating_sub(HSCROLL_STEP);
+            return;
+        }
+        let at_end = self.cursor == self.query.chars().count();
+        if at_end && self.expanded_matches().is_some() {
+            self.collapse_result();
+            return;
+        }
         self.cursor = self.cursor.saturating_sub(1);
     }
 
-    /// Move cursor right
+    /// Move cursor right, or at the end of the query, show the selected result's other matches
     pub fn on_right(&mut self) {
+        if self.focused_unwrapped() {
+            let hscroll = self.preview_hscroll + HSCROLL_STEP;
+            self.preview_hscroll = hscroll.min(self.preview_max_hscroll);
+            return;
+        }
         let char_count = self.query.chars().count();
         if self.cursor < char_count {
             self.cursor += 1;
+        } else {
+            self.expand_result();
+        }
+    }
+
+    /// Show the selected result's other matches beneath it, each selectable on its own
+    pub fn expand_result(&mut self) {
+        if let Some(result) = self.selected_result() {
+            if !result.more_matches.is_empty() {
+                self.expanded_result = Some(result.session.id.clone());
+            }
+        }
+    }
+
+    /// Hide the expanded result's other matches, selecting the result itself again
+    fn collapse_result(&mut self) {
+        self.expanded_result = None;
+        if self.selected_match.take().is_some() {
+            self.update_preview_scroll();
+        }
+    }
+
+    /// The selected result's other matches if it's expanded
+    fn expanded_matches(&self) -> Option<&[MatchHit]> {
+        let result = self.selected_result()?;
+        (self.expanded_result.as_ref() == Some(&result.session.id))
+            .then_some(result.more_matches.as_slice())
+    }
+
+    /// Handle a click in the results list: on the "+N more matches" line, expand the result
+    pub fn click_results_list(&mut self, x: u16, y: u16) -> bool {
+        match self.more_matches_line {
+            Some((lx, ly, width)) if y == ly && x >= lx && x < lx + width => {
+                self.expand_result();
+                true
+            }
+            _ => false,
         }
     }
 
@@ -393,7 +767,7 @@ impl App {
 
     /// Check if debounce period has elapsed and trigger search if needed
     pub fn maybe_search(&mut self) {
-        if self.search_pending && self.last_input.elapsed() >= SEARCH_DEBOUNCE {
+        if self.search_pending && self.last_input.elapsed() >= self.debounce {
             self.search_pending = false;
             let _ = self.search();
         }
@@ -407,18 +781,30 @@ impl App {
         }
     }
 
-    /// Move selection up
+    /// Move selection up, through the other matches of an expanded result
     pub fn on_up(&mut self) {
-        if !self.results.is_empty() {
+        if let Some(m) = self.selected_match {
+            self.selected_match = m.checked_sub(1);
+            self.update_preview_scroll();
+        } else if !self.results.is_empty() {
             self.selected = self.selected.saturating_sub(1);
+            // Coming up into an expanded result lands on its last match
+            self.selected_match = self.expanded_matches().and_then(|m| m.len().checked_sub(1));
             self.update_preview_scroll();
         }
     }
 
-    /// Move selection down
+    /// Move selection down, through the other matches of an expanded result
     pub fn on_down(&mut self) {
-        if !self.results.is_empty() {
-            self.selected = (self.selected + 1).min(self.results.len() - 1);
+        let next_match = self.selected_match.map_or(0, |m| m + 1);
+        if self.expanded_matches().is_some_and(|m| next_match < m.len()) {
+            self.selected_match = Some(next_match);
+            self.update_preview_scroll();
+        } else if !self.results.is_empty() {
+            if self.selected + 1 < self.results.len() {
+                self.selected += 1;
+                self.selected_match = None;
+            }
             self.update_preview_scroll();
         }
     }
@@ -430,10 +816,280 @@ impl App {
         }
     }
 
+    /// Handle Ctrl+X - copy the selected session's file path
+    pub fn on_copy_path(&mut self) {
+        if let Some(result) = self.results.get(self.selected) {
+            self.clipboard = Some(result.session.file_path.to_string_lossy().into_owned());
+        }
+    }
+
+    /// Ctrl+O: open the selected session's folder, in the platform's file manager or with
+    /// `[open] command`
+    pub fn on_open_folder(&mut self) {
+        let Some(session) = self.selected_result().map(|r| r.session.clone()) else {
+            return;
+        };
+        let cwd = self.path_map.apply(&session.cwd).into_owned();
+        if cwd.is_empty() || !Path::new(&cwd).is_dir() {
+            self.show_toast(format!("Folder not found: {}", cwd));
+            return;
+        }
+        let (program, args) = session.open_folder_command_with(&self.open, &self.path_map);
+        self.should_open = Some(OpenFolder { cwd, program, args, terminal: self.open.terminal });
+    }
+
+    /// Whether the query lists bookmarked messages instead of searching
+    pub fn bookmark_view(&self) -> bool {
+        self.query.trim() == BOOKMARKS_QUERY
+    }
+
+    /// Ctrl+B: bookmark the focused message in the preview, or remove its bookmark
+    pub fn toggle_bookmark(&mut self) {
+        let Some(result) = self.selected_result() else {
+            return;
+        };
+        let index = self.focused_message.unwrap_or(result.matched_message_index);
+        let Ok(session) = parser::parse_session_file(&result.session.file_path) else {
+            return;
+        };
+        match bookmarks::toggle(&session, index) {
+            Ok(true) => self.show_toast(format!("Bookmarked message {}", index + 1)),
+            Ok(false) => self.show_toast("Bookmark removed"),
+            Err(e) => self.show_toast(format!("Couldn't save bookmark: {:#}", e)),
+        }
+        if self.bookmark_view() {
+            let _ = self.search();
+        }
+    }
+
+    /// Ctrl+T: open the tag prompt for the selected session, filled with its tags
+    pub fn on_edit_tags(&mut self) {
+        if let Some(result) = self.selected_result() {
+            let input = tags::get(&result.session.id).join(", ");
+            self.prompt = Some(Prompt { kind: PromptKind::Tags, input });
+        }
+    }
+
+    /// F2: open the title prompt for the selected session, filled with its title if renamed
+    pub fn on_rename(&mut self) {
+        if let Some(result) = self.selected_result() {
+            let input = titles::get(&result.session.id).unwrap_or_default();
+            self.prompt = Some(Prompt { kind: PromptKind::Title, input });
+        }
+    }
+
+    /// Type into the prompt
+    pub fn on_prompt_char(&mut self, c: char) {
+        if let Some(prompt) = &mut self.prompt {
+            prompt.input.push(c);
+        }
+    }
+
+    /// Delete the last character of the prompt
+    pub fn on_prompt_backspace(&mut self) {
+        if let Some(prompt) = &mut self.prompt {
+            prompt.input.pop();
+        }
+    }
+
+    /// Close the prompt without saving
+    pub fn cancel_prompt(&mut self) {
+        self.prompt = None;
+    }
+
+    /// Close the prompt, saving what it edits on the selected session and reindexing the
+    /// session so searches see it (after the indexer finishes, if it's running)
+    pub fn submit_prompt(&mut self) {
+        let Some(prompt) = self.prompt.take() else {
+            return;
+        };
+        let Some(session) = self.selected_result().map(|r| r.session.clone()) else {
+            return;
+        };
+        match prompt.kind {
+            PromptKind::Tags => self.save_tags(&session, &prompt.input),
+            PromptKind::Title => self.save_title(&session, &prompt.input),
+        }
+    }
+
+    fn save_tags(&mut self, session: &Session, input: &str) {
+        let new_tags = tags::parse(input);
+        match tags::set(&session.id, &session.file_path, new_tags.clone()) {
+            Ok(false) => return,
+            Ok(true) => {}
+            Err(e) => {
+                self.show_toast(format!("Couldn't save tags: {:#}", e));
+                return;
+            }
+        }
+
+        if self.reindex_edited(session.file_path.clone()) {
+            self.show_toast(if new_tags.is_empty() {
+                "Tags cleared".to_string()
+            } else {
+                format!("Tagged {}", new_tags.join(", "))
+            });
+        }
+    }
+
+    fn save_title(&mut self, session: &Session, input: &str) {
+        match titles::set(&session.id, &session.file_path, input) {
+            Ok(false) => return,
+            Ok(true) => {}
+            Err(e) => {
+                self.show_toast(format!("Couldn't save title: {:#}", e));
+                return;
+            }
+        }
+
+        if self.reindex_edited(session.file_path.clone()) {
+            self.show_toast(match titles::get(&session.id) {
+                Some(title) => format!("Renamed to {}", title),
+                None => "Title reset".to_string(),
+            });
+        }
+    }
+
+    /// Ctrl+N: open the note overlay for the selected session, filled with its note
+    pub fn on_edit_note(&mut self) {
+        if let Some(result) = self.selected_result() {
+            self.note_input = Some(notes::get(&result.session.id).unwrap_or_default());
+        }
+    }
+
+    /// Type into the note overlay
+    pub fn on_note_char(&mut self, c: char) {
+        if let Some(input) = &mut self.note_input {
+            input.push(c);
+        }
+    }
+
+    /// Enter in the note overlay: start a new line, up to the note's line limit
+    pub fn on_note_newline(&mut self) {
+        if let Some(input) = &mut self.note_input {
+            if input.split('\n').count() < notes::MAX_LINES {
+                input.push('\n');
+            }
+        }
+    }
+
+    /// Delete the last character of the note overlay (joining lines at a line start)
+    pub fn on_note_backspace(&mut self) {
+        if let Some(input) = &mut self.note_input {
+            input.pop();
+        }
+    }
+
+    /// Close the note overlay without saving
+    pub fn cancel_note(&mut self) {
+        self.note_input = None;
+    }
+
+    /// Close the note overlay, saving the note on the selected session and reindexing it so
+    /// searches find the note (after the indexer finishes, if it's running)
+    pub fn save_note(&mut self) {
+        let Some(input) = self.note_input.take() else {
+            return;
+        };
+        let Some(session) = self.selected_result().map(|r| r.session.clone()) else {
+            return;
+        };
+        match notes::set(&session.id, &session.file_path, &input) {
+            Ok(false) => return,
+            Ok(true) => {}
+            Err(e) => {
+                self.show_toast(format!("Couldn't save note: {:#}", e));
+                return;
+            }
+        }
+
+        if self.reindex_edited(session.file_path) {
+            let cleared = notes::get(&session.id).is_none();
+            self.show_toast(if cleared { "Note removed" } else { "Note saved" });
+        }
+    }
+
+    /// Reindex a session whose tags, note, or title changed: now, or once the indexer is done if
+    /// it's running. Returns false (after saying why) if reindexing failed.
+    fn reindex_edited(&mut self, path: PathBuf) -> bool {
+        if self.indexing {
+            self.pending_reindex.push(path);
+            return true;
+        }
+        if let Err(e) = self.reindex_files(&[path]) {
+            self.show_toast(format!("Couldn't reindex: {:#}", e));
+            return false;
+        }
+        let _ = self.search();
+        true
+    }
+
+    /// Index `files` again now, recording them in the state file
+    fn reindex_files(&self, files: &[PathBuf]) -> Result<()> {
+        let mut state = match &self.state_path {
+            Some(path) => IndexState::load(path)?,
+            None => IndexState::fresh(),
+        };
+        let mut writer = self.index.writer()?;
+        index_files(&self.index, &mut writer, &mut state, files, None, None, None)?;
+        if let Some(path) = &self.state_path {
+            state.save(path)?;
+        }
+        self.index.reload()
+    }
+
+    /// Show a message in the status bar for a couple of seconds
+    pub fn show_toast(&mut self, message: impl Into<String>) {
+        self.toast = Some((message.into(), Instant::now()));
+    }
+
+    /// The toast to show, if it hasn't expired
+    pub fn toast(&self) -> Option<&str> {
+        self.toast
+            .as_ref()
+            .filter(|(_, shown)| shown.elapsed() < TOAST_DURATION)
+            .map(|(message, _)| message.as_str())
+    }
+
     /// Handle Enter key - open conversation
     pub fn on_enter(&mut self) {
         if let Some(result) = self.results.get(self.selected) {
+            // In the bookmarks, the first Enter goes to the bookmarked message
+            let index = result.matched_message_index;
+            if self.bookmark_view() && self.focused_message != Some(index) {
+                self.focused_message = Some(index);
+                self.expanded_messages.insert(index);
+                self.pending_auto_scroll = true;
+                self.record_jump();
+                return;
+            }
+            if self.select_mode {
+                self.should_select = Some(result.session.clone());
+                return;
+            }
+            let source = result.session.source;
+            if !result.session.resumable() {
+                let chats = match result.session.is_subagent() {
+                    true => "Subagent transcripts".to_string(),
+                    false => format!("{} chats", source.display_name()),
+                };
+                self.show_toast(format!("{} can't be resumed; Tab copies the ID", chats));
+                return;
+            }
             if let Ok(session) = parser::parse_session_file(&result.session.file_path) {
+                if crate::active::looks_active(&session) {
+                    self.confirm_resume = Some(session);
+                } else {
+                    self.should_resume = Some(session);
+                }
+            }
+        }
+    }
+
+    /// Answer the question asked when the session to resume looked open elsewhere
+    pub fn confirm_resume(&mut self, resume: bool) {
+        if let Some(session) = self.confirm_resume.take() {
+            if resume {
                 self.should_resume = Some(session);
             }
         }
@@ -449,16 +1105,94 @@ impl App {
         // Reset focus and expansions when switching sessions
         self.focused_message = None;
         self.expanded_messages.clear();
+        self.unwrapped_messages.clear();
+        self.preview_hscroll = 0;
     }
 
     /// Scroll preview up
     pub fn scroll_preview_up(&mut self, lines: usize) {
         self.preview_scroll = self.preview_scroll.saturating_sub(lines);
+        self.record_jump();
     }
 
     /// Scroll preview down
     pub fn scroll_preview_down(&mut self, lines: usize) {
         self.preview_scroll = self.preview_scroll.saturating_add(lines);
+        self.record_jump();
+    }
+
+    /// Add the selected session to the jump list once the selection has rested on it for a
+    /// moment (call this in the main loop)
+    pub fn maybe_record_jump(&mut self) {
+        let Some(id) = self.selected_result().map(|r| r.session.id.clone()) else {
+            self.settling = None;
+            return;
+        };
+        if self.jumps.current().is_some_and(|jump| jump.session_id == id) {
+            self.settling = None;
+            return;
+        }
+        match &self.settling {
+            Some((settling, since)) if *settling == id => {
+                if since.elapsed() >= SETTLE_TIME {
+                    self.record_jump();
+                }
+            }
+            _ => self.settling = Some((id, Instant::now())),
+        }
+    }
+
+    /// Put the selected session, as it's being viewed now, in the jump list. Viewing the
+    /// current entry again just updates it.
+    fn record_jump(&mut self) {
+        let Some(result) = self.selected_result() else {
+            return;
+        };
+        let jump = Jump {
+            session_id: result.session.id.clone(),
+            file_path: result.session.file_path.clone(),
+            query: self.query.clone(),
+            scope: self.search_scope.clone(),
+            preview_scroll: self.preview_scroll,
+            focused_message: self.focused_message,
+        };
+        self.jumps.push(jump, |a, b| a.session_id == b.session_id);
+        self.settling = None;
+    }
+
+    /// Alt+Left: go back to the previously visited session, skipping deleted ones
+    pub fn jump_back(&mut self) {
+        self.record_jump();
+        if let Some(jump) = self.jumps.back(|jump| jump.file_path.exists()).cloned() {
+            self.restore_jump(jump);
+        }
+    }
+
+    /// Alt+Right: undo `jump_back`
+    pub fn jump_forward(&mut self) {
+        self.record_jump();
+        if let Some(jump) = self.jumps.forward(|jump| jump.file_path.exists()).cloned() {
+            self.restore_jump(jump);
+        }
+    }
+
+    /// Show `jump`'s session the way it was being viewed
+    fn restore_jump(&mut self, jump: Jump) {
+        self.cursor = jump.query.chars().count();
+        self.query = jump.query;
+        self.search_scope = jump.scope;
+        self.search_pending = false;
+        let _ = self.search();
+
+        if let Some(pos) = self.results.iter().position(|r| r.session.id == jump.session_id) {
+            self.selected = pos;
+            self.list_scroll = pos;
+        }
+        self.selected_match = None;
+        self.preview_scroll = jump.preview_scroll;
+        self.focused_message = jump.focused_message;
+        self.pending_auto_scroll = false;
+        self.settling = None;
     }
 
     /// Navigate to previous message in preview
@@ -466,14 +1200,13 @@ impl App {
         if self.preview_message_count == 0 {
             return;
         }
-        let matched_idx = self
-            .selected_result()
-            .map(|r| r.matched_message_index)
-            .unwrap_or(0);
+        let matched_idx = self.selected_match().map(|(index, _)| index).unwrap_or(0);
         let current = self.focused_message.unwrap_or(matched_idx);
         if current > 0 {
             self.focused_message = Some(current - 1);
+            self.preview_hscroll = 0;
             self.pending_auto_scroll = true;
+            self.record_jump();
         }
     }
 
@@ -482,14 +1215,13 @@ impl App {
         if self.preview_message_count == 0 {
             return;
         }
-        let matched_idx = self
-            .selected_result()
-            .map(|r| r.matched_message_index)
-            .unwrap_or(0);
+        let matched_idx = self.selected_match().map(|(index, _)| index).unwrap_or(0);
         let current = self.focused_message.unwrap_or(matched_idx);
         if current + 1 < self.preview_message_count {
             self.focused_message = Some(current + 1);
+            self.preview_hscroll = 0;
             self.pending_auto_scroll = true;
+            self.record_jump();
         }
     }
 
@@ -498,16 +1230,37 @@ impl App {
         if self.preview_message_count == 0 {
             return;
         }
-        let matched_idx = self
-            .selected_result()
-            .map(|r| r.matched_message_index)
-            .unwrap_or(0);
+        let matched_idx = self.selected_match().map(|(index, _)| index).unwrap_or(0);
         let focused = self.focused_message.unwrap_or(matched_idx);
         if self.expanded_messages.contains(&focused) {
             self.expanded_messages.remove(&focused);
         } else {
             self.expanded_messages.insert(focused);
         }
+        self.record_jump();
+    }
+
+    /// Toggle wrapping of the focused message's long lines; unwrapped, Left/Right scroll them
+    pub fn toggle_focused_wrap(&mut self) {
+        if self.preview_message_count == 0 {
+            return;
+        }
+        let focused = self.focused_index();
+        if !self.unwrapped_messages.remove(&focused) {
+            self.unwrapped_messages.insert(focused);
+        }
+        self.preview_hscroll = 0;
+    }
+
+    /// Whether the focused message is unwrapped, so Left/Right scroll it
+    pub fn focused_unwrapped(&self) -> bool {
+        self.preview_message_count > 0 && self.unwrapped_messages.contains(&self.focused_index())
+    }
+
+    /// The focused message, which is the matched one until another is picked
+    fn focused_index(&self) -> usize {
+        let matched_idx = self.selected_match().map(|(index, _)| index).unwrap_or(0);
+        self.focused_message.unwrap_or(matched_idx)
     }
 
     /// Get the currently selected result
@@ -515,6 +1268,16 @@ impl App {
         self.results.get(self.selected)
     }
 
+    /// The matched message the preview centers on, with its Tantivy fragment: the selected one
+    /// of an expanded result's other matches, or else the result's own
+    pub fn selected_match(&self) -> Option<(usize, &str)> {
+        let result = self.selected_result()?;
+        match self.selected_match.and_then(|m| self.expanded_matches()?.get(m)) {
+            Some(hit) => Some((hit.message_index, &hit.match_fragment)),
+            None => Some((result.matched_message_index, &result.match_fragment)),
+        }
+    }
+
     /// Handle mouse click in preview area - returns true if a message was clicked
     pub fn click_preview_message(&mut self, x: u16, y: u16) -> bool {
         let (px, py, pw, ph) = self.preview_area;
@@ -531,6 +1294,7 @@ impl App {
         for (msg_idx, &(start, end)) in self.message_line_ranges.iter().enumerate() {
             if clicked_line >= start && clicked_line < end {
                 self.focused_message = Some(msg_idx);
+                self.record_jump();
                 return true;
             }
         }
@@ -539,35 +1303,60 @@ impl App {
     }
 }
 
-/// Background indexing function
-fn background_index(index_path: PathBuf, state_path: PathBuf, tx: Sender<IndexMsg>) {
-    let index = match SessionIndex::open_or_create(&index_path) {
-        Ok(idx) => idx,
-        Err(e) => {
-            let _ = tx.send(IndexMsg::Error(format!("Failed to open index: {}", e)));
-            return;
-        }
+/// Background indexing function. `index_path` is None for an in-memory index, which starts
+/// empty and keeps no state. Sessions last active before `cutoff` are left out and pruned.
+fn background_index(
+    index: SessionIndex,
+    index_path: Option<PathBuf>,
+    cutoff: Option<i64>,
+    tx: Sender<IndexMsg>,
+) {
+    let state_path = index_path.as_deref().map(cache::state_path);
+    let loaded = match &state_path {
+        Some(path) => IndexState::load(path),
+        None => Ok(IndexState::fresh()),
     };
-    let mut state = match IndexState::load(&state_path) {
+    let mut state = match loaded {
         Ok(s) => s,
         Err(e) => {
             let _ = tx.send(IndexMsg::Error(format!("Failed to load index state: {}", e)));
             return;
         }
     };
+    if let Some(hint) = state.reindex_hint() {
+        let _ = tx.send(IndexMsg::ReindexHint(hint));
+    }
+
+    // Update the remotes' mirrors first; one that can't be reached keeps its last copy
+    let remote_failures = crate::remote::sync_all();
+    if !remote_failures.is_empty() {
+        let _ = tx.send(IndexMsg::RemoteFailed(remote_failures));
+    }
 
-    // Discover and sort files by mtime (most recent first)
-    let files = discover_and_sort_files();
+    // Discover and sort files by mtime (most recent first), minus those too old to keep
+    let files = retained_files(discover_and_sort_files(), cutoff);
 
-    let files_to_index: Vec<_> = files
-        .iter()
-        .filter(|f| state.needs_reindex(f))
-        .cloned()
-        .collect();
+    let files_to_index = files_to_index(&state, &files, cutoff);
 
-    if files_to_index.is_empty() {
+    let stale = stale_files(&state, &files);
+    let expired = match expired_files(&index, cutoff) {
+        Ok(expired) => expired,
+        Err(e) => {
+            let _ = tx.send(IndexMsg::Error(format!("Failed to read index: {}", e)));
+            return;
+        }
+    };
+
+    if files_to_index.is_empty() && stale.is_empty() && expired.is_empty() {
+        let empty = state.empty_count(&files);
         let _ = tx.send(IndexMsg::Done {
-            total_sessions: files.len(),
+            total_sessions: files.len()
+                - empty
+                - state.expired_count(&files)
+                - state.duplicate_count(&files),
+            skipped: empty,
+            pruned: 0,
+            failures: Vec::new(),
         });
         return;
     }
@@ -580,6 +1369,9 @@ fn background_index(index_path: PathBuf, state_path: PathBuf, tx: Sender<IndexMs
         }
     };
 
+    purge_files(&index, &mut writer, &mut state, &stale);
+    prune_files(&index, &mut writer, &mut state, &expired);
+
     // Progress callback sends to channel
     let tx_progress = tx.clone();
     let on_progress = Box::new(move |p: IndexProgress| {
@@ -600,19 +1392,34 @@ fn background_index(index_path: PathBuf, state_path: PathBuf, tx: Sender<IndexMs
         &mut writer,
         &mut state,
         &files_to_index,
+        cutoff,
         Some(on_progress),
         Some(on_reload),
     );
 
-    if let Err(e) = result {
-        let _ = tx.send(IndexMsg::Error(format!("Indexing failed: {}", e)));
-        return;
-    }
+    let report = match result {
+        Ok(report) => report,
+        Err(e) => {
+            let _ = tx.send(IndexMsg::Error(format!("Indexing failed: {}", e)));
+            return;
+        }
+    };
 
-    let _ = state.save(&state_path);
+    if let (Some(index_path), Some(state_path)) = (&index_path, &state_path) {
+        let _ = state.save(state_path);
+        let _ = cache::restrict_files(index_path);
+    }
 
+    // Failed files aren't in the state, so count them from this run's report
+    let skipped = state.empty_count(&files) + report.failed.len();
     let _ = tx.send(IndexMsg::Done {
-        total_sessions: files.len(),
+        total_sessions: files.len()
+            - skipped
+            - state.expired_count(&files)
+            - state.duplicate_count(&files),
+        skipped,
+        pruned: expired.len(),
+        failures: report.failed,
     });
 }
 
@@ -633,10 +1440,16 @@ mod tests {
             cursor: 0,
             results: Vec::new(),
             selected: 0,
+            expanded_result: None,
+            selected_match: None,
+            more_matches_line: None,
             list_scroll: 0,
             preview_scroll: 0,
             focused_message: None,
             expanded_messages: HashSet::new(),
+            unwrapped_messages: HashSet::new(),
+            preview_hscroll: 0,
+            preview_max_hscroll: 0,
             preview_message_count: 0,
             focused_message_expandable: false,
             message_line_ranges: Vec::new(),
@@ -645,20 +1458,112 @@ mod tests {
             preview_scrollable: false,
             should_quit: false,
             should_resume: None,
+            confirm_resume: None,
             should_copy: None,
+            clipboard: None,
+            should_open: None,
+            select_mode: false,
+            should_select: None,
             index: SessionIndex::open_or_create(&index_path).unwrap(),
+            state_path: None,
             status: None,
+            toast: None,
             total_sessions: 0,
+            skipped_sessions: 0,
+            pruned_sessions: 0,
+            index_failures: Vec::new(),
+            remote_failures: Vec::new(),
             index_rx: None,
             indexing: false,
             search_scope: SearchScope::Everything,
+            local_scope: SearchScope::Folder(String::new()),
             launch_cwd: String::new(),
+            source_filter: None,
+            since_filter: None,
+            debounce: Duration::from_millis(50),
+            max_results: 50,
+            preview: PreviewConfig::default(),
+            open: OpenConfig::default(),
+            path_map: PathMap::default(),
+            date_format: None,
+            accessible: false,
             search_pending: false,
             last_input: Instant::now(),
             index_error: None,
+            jumps: JumpList::default(),
+            settling: None,
+            prompt: None,
+            note_input: None,
+            pending_reindex: Vec::new(),
         }
     }
 
+    #[test]
+    fn test_scope_contains() {
+        assert!(SearchScope::Everything.contains(""));
+        let folder = SearchScope::Folder("/work/app".to_string());
+        assert!(folder.contains("/work/app"));
+        assert!(!folder.contains("/work/app/src"));
+        let repo = SearchScope::Repo("/work/app".to_string());
+        assert!(repo.contains("/work/app/src"));
+        assert!(!repo.contains("/work/app2"));
+    }
+
+    #[test]
+    fn test_compact_path() {
+        // Cyrillic username: the byte after the home prefix is mid-character in a naive slice
+        let home = "/home/алексей";
+        assert_eq!(compact_path("/home/алексей/код", home), "~/код");
+        assert_eq!(compact_path("/home/алексей", home), "~");
+        assert_eq!(compact_path("/home/алексей/", "/home/алексей/"), "~/");
+        assert_eq!(compact_path("/home/алексейка/код", home), "/home/алексейка/код");
+        assert_eq!(
+            compact_path("/home/алексей/проекты/бухгалтерия/сервер", home),
+            "~/.../сервер"
+        );
+
+        // Trailing separator on home, and an empty home
+        assert_eq!(compact_path("/home/me/app", "/home/me/"), "~/app");
+        assert_eq!(compact_path("/srv/app", ""), "/srv/app");
+        assert_eq!(compact_path("/srv/applications/internal/billing", ""), "/.../billing");
+
+        // Windows-style paths
+        let home = r"C:\Users\Me";
+        assert_eq!(compact_path(r"C:\Users\Me\src\app", home), r"~\src\app");
+        assert_eq!(compact_path(r"C:\Users\Me", home), "~");
+        assert_eq!(
+            compact_path(r"C:\Users\Me\Documents\Projects\billing", home),
+            r"~\...\billing"
+        );
+
+        // Width counts columns, and each of these is two: 25 columns fit, 27 don't
+        let wide = "漢".repeat(12);
+        assert_eq!(compact_path(&format!("/{}", wide), ""), format!("/{}", wide));
+        assert_eq!(compact_path(&format!("/a/{}", wide), ""), format!("/.../{}", wide));
+    }
+
+    #[test]
+    fn test_in_scope() {
+        let root = Path::new("/work/app");
+        assert!(in_scope("/work/app", root));
+        assert!(in_scope("/work/app/src", root));
+        assert!(!in_scope("/work/app2", root));
+        assert!(!in_scope("/work", root));
+        assert!(!in_scope("", root));
+    }
+
+    #[test]
+    fn test_scope_root_finds_repo() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let repo = temp.path().join("repo");
+        let nested = repo.join("src/deep");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::create_dir(repo.join(".git")).unwrap();
+
+        assert_eq!(scope_root(&nested), repo);
+        assert_eq!(scope_root(temp.path()), temp.path());
+    }
+
     // ==================== focus_prev_message tests ====================
 
     #[test]
@@ -810,6 +1715,47 @@ mod tests {
         assert_eq!(app.expanded_messages.len(), 2);
     }
 
+    // ==================== horizontal scroll tests ====================
+
+    #[test]
+    fn test_hscroll_clamps_to_longest_line() {
+        let mut app = test_app();
+        app.preview_message_count = 3;
+        app.focused_message = Some(1);
+        app.query = "q".to_string();
+        app.cursor = 0;
+
+        // Wrapped, Left/Right edit the query
+        app.on_right();
+        assert_eq!((app.cursor, app.preview_hscroll), (1, 0));
+
+        app.toggle_focused_wrap();
+        assert!(app.focused_unwrapped());
+        app.preview_max_hscroll = 20;
+        app.on_right();
+        assert_eq!(app.preview_hscroll, HSCROLL_STEP);
+        for _ in 0..5 {
+            app.on_right();
+        }
+        assert_eq!(app.preview_hscroll, 20);
+        app.on_left();
+        assert_eq!(app.preview_hscroll, 20 - HSCROLL_STEP);
+        for _ in 0..5 {
+            app.on_left();
+        }
+        assert_eq!(app.preview_hscroll, 0);
+        assert_eq!(app.cursor, 1);
+
+        // Another message starts at the left edge, and wraps
+        app.preview_hscroll = 10;
+        app.focus_next_message();
+        assert_eq!(app.preview_hscroll, 0);
+        assert!(!app.focused_unwrapped());
+        app.focus_prev_message();
+        app.toggle_focused_wrap();
+        assert!(!app.focused_unwrapped());
+    }
+
     // ==================== click_preview_message tests ====================
 
     #[test]
@@ -891,4 +1837,74 @@ mod tests {
 
         assert!(app.pending_auto_scroll);
     }
+
+    // ==================== Select mode tests ====================
+
+    fn push_result(app: &mut App, id: &str) {
+        app.results.push(SearchResult {
+            session: Session {
+                id: id.to_string(),
+                source: crate::session::SessionSource::ClaudeCode,
+                file_path: PathBuf::from(format!("/nonexistent/{}.jsonl", id)),
+                cwd: "/test/project".to_string(),
+                git_branch: None,
+                timestamp: chrono::Utc::now(),
+                messages: vec![],
+                files_touched: vec![],
+                usage: vec![],
+                title: None,
+            },
+            score: 1.0,
+            matched_message_index: 0,
+            snippet: String::new(),
+            match_spans: vec![],
+            match_fragment: String::new(),
+            more_matches: Vec::new(),
+        });
+    }
+
+    #[test]
+    fn test_enter_in_select_mode_sets_should_select() {
+        let mut app = test_app();
+        app.select_mode = true;
+        push_result(&mut app, "first");
+        push_result(&mut app, "second");
+        app.selected = 1;
+
+        app.on_enter();
+
+        assert_eq!(app.should_select.as_ref().map(|s| s.id.as_str()), Some("second"));
+        assert!(app.should_resume.is_none());
+    }
+
+    #[test]
+    fn test_enter_without_select_mode_does_not_select() {
+        let mut app = test_app();
+        push_result(&mut app, "first");
+
+        app.on_enter();
+
+        assert!(app.should_select.is_none());
+    }
+
+    #[test]
+    fn test_enter_does_not_resume_subagents() {
+        let mut app = test_app();
+        push_result(&mut app, "agent-a1b2c3");
+
+        app.on_enter();
+
+        assert!(app.should_resume.is_none());
+        assert_eq!(app.toast(), Some("Subagent transcripts can't be resumed; Tab copies the ID"));
+    }
+
+    #[test]
+    fn test_enter_in_select_mode_with_no_results_noop() {
+        let mut app = test_app();
+        app.select_mode = true;
+
+        app.on_enter();
+
+        assert!(app.should_select.is_none());
+    }
 }
@@ -0,0 +1,163 @@
+//! Bookmarked messages (a great prompt, a config snippet, a decision), kept in a sidecar file
+//! like [`crate::tags`]. A bookmark points at a message by index, but indices can shift when a
+//! session is parsed differently (joined messages, a changed format), so it also keeps a hash
+//! of the message and finds it again by that when the index no longer matches.
+
+use crate::parser;
+use crate::session::{Message, SearchResult, Session};
+use crate::sidecar::Sidecar;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Length of the excerpt kept with a bookmark, in characters
+const EXCERPT_CHARS: usize = 200;
+
+/// A bookmarked message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    /// Where the message was when it was bookmarked
+    pub message_index: usize,
+    /// The start of the message, on one line
+    pub excerpt: String,
+    /// Hash of the message's content, to find it again if it moved
+    pub hash: String,
+    /// When the bookmark was made
+    pub created: DateTime<Utc>,
+}
+
+static BOOKMARKS: Sidecar<Vec<Bookmark>> = Sidecar::new("bookmarks.json");
+
+impl Bookmark {
+    /// A bookmark on `messages[index]`
+    fn new(messages: &[Message], index: usize) -> Option<Self> {
+        let message = messages.get(index)?;
+        let excerpt = message.content.split_whitespace().collect::<Vec<_>>().join(" ");
+        Some(Self {
+            message_index: index,
+            excerpt: excerpt.chars().take(EXCERPT_CHARS).collect(),
+            hash: hash(&message.content),
+            created: Utc::now(),
+        })
+    }
+
+    /// Where the bookmarked message is in `messages` now: at its index if that still holds
+    /// the same content, or else the copy nearest to it. None if it's gone.
+    pub fn resolve(&self, messages: &[Message]) -> Option<usize> {
+        let matches = |i: &usize| hash(&messages[*i].content) == self.hash;
+        if self.message_index < messages.len() && matches(&self.message_index) {
+            return Some(self.message_index);
+        }
+        (0..messages.len()).filter(matches).min_by_key(|i| i.abs_diff(self.message_index))
+    }
+}
+
+/// FNV-1a of `content`, in hex (stable across builds, unlike std's hasher)
+fn hash(content: &str) -> String {
+    let hash = content.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// Bookmarks in the session with this ID
+pub fn for_session(session_id: &str) -> Vec<Bookmark> {
+    BOOKMARKS.get(session_id).unwrap_or_default()
+}
+
+/// Indices of the bookmarked messages in `session`, as they are now
+pub fn bookmarked(session: &Session) -> Vec<usize> {
+    for_session(&session.id)
+        .iter()
+        .filter_map(|b| b.resolve(&session.messages))
+        .collect()
+}
+
+/// Bookmark `session`'s message at `index`, or remove the bookmark it has. Returns whether
+/// the message is bookmarked now.
+pub fn toggle(session: &Session, index: usize) -> Result<bool> {
+    let mut bookmarks = for_session(&session.id);
+    let before = bookmarks.len();
+    bookmarks.retain(|b| b.resolve(&session.messages) != Some(index));
+    let added = bookmarks.len() == before;
+    if added {
+        bookmarks.extend(Bookmark::new(&session.messages, index));
+    }
+    let value = (!bookmarks.is_empty()).then_some(bookmarks);
+    BOOKMARKS.set(&session.id, &session.file_path, value)?;
+    Ok(added)
+}
+
+/// Every bookmark whose message can still be found, newest first, with its session (read from
+/// the session file) and the message's current index
+pub fn all() -> Vec<(Session, usize, Bookmark)> {
+    let mut found = Vec::new();
+    for (_, path, bookmarks) in BOOKMARKS.all() {
+        let Ok(session) = parser::parse_session_file(&path) else {
+            continue;
+        };
+        for bookmark in bookmarks {
+            if let Some(index) = bookmark.resolve(&session.messages) {
+                found.push((session.clone(), index, bookmark));
+            }
+        }
+    }
+    found.sort_by_key(|(_, _, bookmark)| std::cmp::Reverse(bookmark.created));
+    found
+}
+
+/// Bookmarks as search results (the `is:bookmarked` query), pointing at their messages
+pub fn results() -> Vec<SearchResult> {
+    all()
+        .into_iter()
+        .map(|(session, index, bookmark)| SearchResult {
+            session: Session { messages: Vec::new(), ..session },
+            score: 0.0,
+            matched_message_index: index,
+            snippet: bookmark.excerpt,
+            match_spans: Vec::new(),
+            match_fragment: String::new(),
+            more_matches: Vec::new(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::Role;
+
+    fn messages(contents: &[&str]) -> Vec<Message> {
+        contents
+            .iter()
+            .map(|c| Message {
+                role: Role::User,
+                content: c.to_string(),
+                timestamp: Utc::now(),
+                tool_calls: Vec::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve_follows_moved_message() {
+        let bookmark = Bookmark::new(&messages(&["a", "use sqlite", "c"]), 1).unwrap();
+        assert_eq!(bookmark.excerpt, "use sqlite");
+        assert_eq!(bookmark.resolve(&messages(&["a", "use sqlite", "c", "d"])), Some(1));
+
+        // Messages inserted before it
+        let moved = messages(&["x", "y", "a", "use sqlite", "c"]);
+        assert_eq!(bookmark.resolve(&moved), Some(3));
+
+        // The nearest of several copies, and nothing once it's gone
+        let copies = messages(&["use sqlite", "b", "c", "use sqlite"]);
+        assert_eq!(bookmark.resolve(&copies), Some(0));
+        assert_eq!(bookmark.resolve(&messages(&["a", "b"])), None);
+    }
+
+    #[test]
+    fn test_hash_is_stable() {
+        assert_eq!(hash(""), "cbf29ce484222325");
+        assert_eq!(hash("a"), "af63dc4c8601ec8c");
+    }
+}
@@ -0,0 +1,398 @@
+//! `recall backup` - copy every discovered session file into a .tar.gz archive
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use recall::{
+    parser::{self, OpenCodeParser, SessionParser},
+    session::SessionSource,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::{Component, Path, PathBuf};
+
+/// Name of the manifest entry inside the archive
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+const MANIFEST_VERSION: u32 = 1;
+
+/// Describes the sessions stored in a backup archive
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub created: DateTime<Utc>,
+    pub sessions: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// None if the file couldn't be parsed when it was backed up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<SessionSource>,
+    pub id: String,
+    pub original_path: PathBuf,
+    /// Path of the session file inside the archive
+    pub archive_path: String,
+    pub mtime: DateTime<Utc>,
+    /// Other files the session needs (OpenCode message/part trees)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_files: Vec<String>,
+}
+
+/// A file to put in the archive
+struct PlannedFile {
+    path: PathBuf,
+    archive_path: String,
+}
+
+/// Run the backup subcommand
+pub fn run_backup(output: &Path, incremental: bool) -> Result<()> {
+    let home = std::env::var("RECALL_HOME_OVERRIDE")
+        .map(PathBuf::from)
+        .ok()
+        .or_else(dirs::home_dir)
+        .unwrap_or_else(|| PathBuf::from("/"));
+
+    // Plan: every session file plus the files it depends on
+    let mut sessions: Vec<(PlannedFile, Vec<PlannedFile>)> = Vec::new();
+    for path in parser::discover_session_files() {
+        let extras = if OpenCodeParser::can_parse(&path) {
+            opencode_extra_files(&path)
+        } else {
+            Vec::new()
+        };
+        let plan = |path: PathBuf| PlannedFile {
+            archive_path: archive_path(&path, &home),
+            path,
+        };
+        sessions.push((plan(path), extras.into_iter().map(plan).collect()));
+    }
+    let planned: HashMap<&str, &Path> = sessions
+        .iter()
+        .flat_map(|(file, extras)| std::iter::once(file).chain(extras))
+        .map(|f| (f.archive_path.as_str(), f.path.as_path()))
+        .collect();
+
+    // Write to a temp file first so an incremental run can read the previous archive, and
+    // remove it if the run fails so a half-written archive isn't left behind
+    let tmp_path = output.with_extension("tmp");
+    let tmp = File::create(&tmp_path)
+        .with_context(|| format!("Failed to create {}", tmp_path.display()))?;
+    let written = write_archive(tmp, output, incremental, &sessions, &planned).and_then(|report| {
+        std::fs::rename(&tmp_path, output)
+            .with_context(|| format!("Failed to write {}", output.display()))?;
+        Ok(report)
+    });
+    let report = match written {
+        Ok(report) => report,
+        Err(e) => {
+            let _ = std::fs::remove_file(&tmp_path);
+            return Err(e);
+        }
+    };
+
+    let sessions = report.manifest.sessions.len();
+    println!(
+        "Backed up {} session{} to {} ({} files added, {} unchanged{})",
+        sessions,
+        if sessions == 1 { "" } else { "s" },
+        output.display(),
+        report.added,
+        report.unchanged,
+        match report.kept {
+            0 => String::new(),
+            kept => format!(", {} kept though no longer on disk", kept),
+        }
+    );
+    if report.missing > 0 {
+        let plural = if report.missing == 1 { "" } else { "s" };
+        eprintln!("{} file{} disappeared during backup", report.missing, plural);
+    }
+
+    Ok(())
+}
+
+/// What [`write_archive`] wrote
+struct BackupReport {
+    manifest: Manifest,
+    added: usize,
+    unchanged: usize,
+    /// Sessions from the previous archive whose files are gone, carried forward as they were
+    kept: usize,
+    missing: usize,
+}
+
+/// Write the archive for `sessions` to `tmp`. An incremental run carries forward every entry
+/// of the archive at `output` that a changed file doesn't replace, including those whose
+/// files are gone: agents prune old sessions, and keeping them is what the backup is for.
+fn write_archive(
+    tmp: File,
+    output: &Path,
+    incremental: bool,
+    sessions: &[(PlannedFile, Vec<PlannedFile>)],
+    planned: &HashMap<&str, &Path>,
+) -> Result<BackupReport> {
+    let mut builder = tar::Builder::new(GzEncoder::new(tmp, Compression::default()));
+
+    let mut carried: HashSet<String> = HashSet::new();
+    let mut previous: HashMap<String, ManifestEntry> = HashMap::new();
+    let mut unchanged = 0;
+
+    if incremental && output.exists() {
+        let file = File::open(output).context("Failed to open existing archive")?;
+        let mut archive = tar::Archive::new(GzDecoder::new(file));
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let name = entry.path()?.to_string_lossy().to_string();
+
+            if name == MANIFEST_NAME {
+                let manifest: Manifest = serde_json::from_reader(&mut entry)
+                    .context("Existing archive has an invalid manifest")?;
+                previous.extend(
+                    manifest
+                        .sessions
+                        .into_iter()
+                        .map(|e| (e.archive_path.clone(), e)),
+                );
+                continue;
+            }
+
+            // Carry over entries whose source file hasn't changed, or is gone
+            let mut header = entry.header().clone();
+            let (mtime, size) = (header.mtime()?, header.size()?);
+            let replaced = planned
+                .get(name.as_str())
+                .is_some_and(|path| !file_matches(path, mtime, size));
+            if !replaced {
+                builder.append_data(&mut header, &name, &mut entry)?;
+                carried.insert(name);
+                unchanged += 1;
+            }
+        }
+    }
+
+    let mut added = 0;
+    let mut missing = 0;
+    let mut manifest = Manifest {
+        version: MANIFEST_VERSION,
+        created: Utc::now(),
+        sessions: Vec::new(),
+    };
+
+    for (file, extras) in sessions {
+        let carried_over = carried.contains(&file.archive_path);
+        if !carried_over {
+            if !append_file(&mut builder, file)? {
+                missing += 1;
+                continue;
+            }
+            added += 1;
+        }
+
+        let mut extra_files = Vec::new();
+        for extra in extras {
+            if !carried.contains(&extra.archive_path) {
+                if !append_file(&mut builder, extra)? {
+                    missing += 1;
+                    continue;
+                }
+                added += 1;
+            }
+            extra_files.push(extra.archive_path.clone());
+        }
+
+        // Unchanged files keep their manifest entry; everything else is parsed for its ID
+        let entry = match previous.remove(&file.archive_path).filter(|_| carried_over) {
+            Some(entry) => entry,
+            None => {
+                let parsed = parser::parse_session_file(&file.path).ok();
+                ManifestEntry {
+                    source: parsed.as_ref().map(|s| s.source),
+                    id: parsed.map(|s| s.id).unwrap_or_else(|| file_stem(&file.path)),
+                    original_path: file.path.clone(),
+                    archive_path: file.archive_path.clone(),
+                    mtime: modified(&file.path).unwrap_or_else(Utc::now),
+                    extra_files: Vec::new(),
+                }
+            }
+        };
+        manifest.sessions.push(ManifestEntry { extra_files, ..entry });
+    }
+
+    // Sessions no longer discovered keep their entry, as long as their file was carried
+    let mut kept: Vec<ManifestEntry> = previous
+        .into_values()
+        .filter(|entry| carried.contains(&entry.archive_path))
+        .collect();
+    kept.sort_by(|a, b| a.archive_path.cmp(&b.archive_path));
+    let kept_count = kept.len();
+    manifest.sessions.extend(kept);
+
+    let json = serde_json::to_vec_pretty(&manifest)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(json.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(manifest.created.timestamp() as u64);
+    builder.append_data(&mut header, MANIFEST_NAME, json.as_slice())?;
+    builder.into_inner()?.finish()?;
+
+    Ok(BackupReport { manifest, added, unchanged, kept: kept_count, missing })
+}
+
+/// Append a file to the archive. Returns false if it vanished or became unreadable.
+fn append_file<W: std::io::Write>(builder: &mut tar::Builder<W>, file: &PlannedFile) -> Result<bool> {
+    let mut f = match File::open(&file.path) {
+        Ok(f) => f,
+        Err(e) => {
+            eprintln!("Skipping {}: {}", file.path.display(), e);
+            return Ok(false);
+        }
+    };
+    // A partially appended entry would corrupt the archive, so failures past this point are fatal
+    builder
+        .append_file(&file.archive_path, &mut f)
+        .with_context(|| format!("Failed to archive {}", file.path.display()))?;
+    Ok(true)
+}
+
+/// Whether a file on disk still matches an archived entry's mtime and size
+fn file_matches(path: &Path, mtime: u64, size: u64) -> bool {
+    std::fs::metadata(path)
+        .ok()
+        .and_then(|m| {
+            let secs = m
+                .modified()
+                .ok()?
+                .duration_since(std::time::UNIX_EPOCH)
+                .ok()?
+                .as_secs();
+            Some(secs == mtime && m.len() == size)
+        })
+        .unwrap_or(false)
+}
+
+fn modified(path: &Path) -> Option<DateTime<Utc>> {
+    std::fs::metadata(path).ok()?.modified().ok().map(DateTime::from)
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Archive path for a file: relative to home, so a restore can lay it out the same way
+fn archive_path(path: &Path, home: &Path) -> String {
+    let relative = path.strip_prefix(home).unwrap_or(path);
+    relative
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(s) => Some(s.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// OpenCode keeps messages and parts outside the session file:
+/// storage/message/<session_id>/*.json and storage/part/<message_id>/*.json
+fn opencode_extra_files(session_path: &Path) -> Vec<PathBuf> {
+    let Some(storage) = session_path.ancestors().nth(3) else {
+        return Vec::new();
+    };
+    let session_id = file_stem(session_path);
+
+    let mut files = Vec::new();
+    for message in json_files(&storage.join("message").join(&session_id)) {
+        let message_id = file_stem(&message);
+        files.push(message);
+        files.extend(json_files(&storage.join("part").join(message_id)));
+    }
+    files
+}
+
+fn json_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().map(|e| e == "json").unwrap_or(false))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// Whether a path looks like a backup archive rather than an export
+pub fn is_archive(path: &Path) -> bool {
+    let name = path.to_string_lossy();
+    name.ends_with(".tar.gz") || name.ends_with(".tgz")
+}
+
+/// Extract a backup archive into `dest`, returning its manifest
+pub fn extract(archive_path: &Path, dest: &Path) -> Result<Manifest> {
+    let file = File::open(archive_path)
+        .with_context(|| format!("Failed to open {}", archive_path.display()))?;
+    // unpack() refuses entries that would escape dest
+    tar::Archive::new(GzDecoder::new(file))
+        .unpack(dest)
+        .context("Failed to extract archive")?;
+
+    let manifest = File::open(dest.join(MANIFEST_NAME)).context("Archive has no manifest")?;
+    serde_json::from_reader(manifest).context("Archive has an invalid manifest")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_path_relative_to_home() {
+        let home = Path::new("/home/user");
+        assert_eq!(
+            archive_path(Path::new("/home/user/.claude/projects/p/s.jsonl"), home),
+            ".claude/projects/p/s.jsonl"
+        );
+        assert_eq!(
+            archive_path(Path::new("/data/recall/imported/claude/x.json"), home),
+            "data/recall/imported/claude/x.json"
+        );
+    }
+
+    #[test]
+    fn test_is_archive() {
+        assert!(is_archive(Path::new("backup.tar.gz")));
+        assert!(is_archive(Path::new("/tmp/backup.tgz")));
+        assert!(!is_archive(Path::new("export/session.json")));
+    }
+
+    #[test]
+    fn test_opencode_extra_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = dir.path().join("storage");
+        let session = storage.join("session/proj/ses_1.json");
+        for path in [
+            &session,
+            &storage.join("message/ses_1/msg_a.json"),
+            &storage.join("part/msg_a/prt_1.json"),
+            &storage.join("part/msg_a/prt_2.json"),
+            &storage.join("part/msg_other/prt_3.json"),
+        ] {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "{}").unwrap();
+        }
+
+        let extras = opencode_extra_files(&session);
+        let names: Vec<_> = extras
+            .iter()
+            .map(|p| p.strip_prefix(&storage).unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(
+            names,
+            ["message/ses_1/msg_a.json", "part/msg_a/prt_1.json", "part/msg_a/prt_2.json"]
+        );
+    }
+}
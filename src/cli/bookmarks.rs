@@ -0,0 +1,44 @@
+//! `recall bookmarks` - list bookmarked messages as JSON
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use recall::session::{Role, SessionSource};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct BookmarkOutput {
+    pub session_id: String,
+    pub source: SessionSource,
+    pub cwd: String,
+    /// Where the message is in the session now (see `recall print <id>:N`)
+    pub message_index: usize,
+    pub role: Role,
+    pub excerpt: String,
+    pub created: DateTime<Utc>,
+    pub resume_command: String,
+}
+
+/// Bookmarks whose messages can still be found, newest first
+pub fn bookmarks() -> Vec<BookmarkOutput> {
+    recall::bookmarks::all()
+        .into_iter()
+        .map(|(session, index, bookmark)| {
+            BookmarkOutput {
+                role: session.messages[index].role,
+                resume_command: session.resume_command_line(),
+                session_id: session.id,
+                source: session.source,
+                cwd: session.cwd,
+                message_index: index,
+                excerpt: bookmark.excerpt,
+                created: bookmark.created,
+            }
+        })
+        .collect()
+}
+
+/// Run the bookmarks subcommand
+pub fn run_bookmarks() -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(&bookmarks())?);
+    Ok(())
+}
@@ -0,0 +1,270 @@
+//! `recall context` - a compact Markdown digest of past sessions to paste into a new one
+
+use super::{filters, score_messages};
+use anyhow::Result;
+use recall::{
+    engine::fetch_filtered,
+    fold,
+    index::{ensure_index_fresh, SessionIndex},
+    parser,
+    session::{Role, Session, SessionSource},
+};
+use std::collections::HashMap;
+
+/// Excerpts shorter than this aren't worth including
+const MIN_EXCERPT_CHARS: usize = 40;
+
+/// No single message may take more than this share of the budget
+const MAX_MESSAGE_SHARE: usize = 4;
+
+pub struct ContextParams {
+    pub query: String,
+    /// Maximum output size in characters
+    pub budget: usize,
+    /// Number of sessions to draw from
+    pub sessions: usize,
+    pub source: Option<SessionSource>,
+    pub cwd: Option<String>,
+}
+
+/// A message chosen for the pack, and how much it matters
+struct Pick {
+    session: usize,
+    message: usize,
+    priority: f64,
+}
+
+/// Run the context subcommand
+pub fn run_context(params: &ContextParams) -> Result<()> {
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+
+    print!("{}", context(&index, params)?);
+    Ok(())
+}
+
+/// Build the Markdown context pack for a query, at most `params.budget` characters long
+pub fn context(index: &SessionIndex, params: &ContextParams) -> Result<String> {
+    let query = params.query.as_str();
+    let filters = filters(params.source, &None, &None, &params.cwd, &[])?;
+    let results = fetch_filtered(|n| index.search(query, n), &filters, params.sessions)?;
+
+    let sessions: Vec<Session> = results
+        .into_iter()
+        .take(params.sessions)
+        .map(|r| parser::parse_session_file(&r.session.file_path).unwrap_or(r.session))
+        .collect();
+
+    Ok(assemble(&sessions, query, params.budget))
+}
+
+/// Lay out sessions (best first) within the budget, filling it with the highest-priority messages
+fn assemble(sessions: &[Session], query: &str, budget: usize) -> String {
+    let title = format!("# Context from past sessions: \"{}\"\n\n", query);
+
+    // Drop the weakest sessions until their headers and footnotes fit
+    let mut sessions = sessions;
+    let overhead = |sessions: &[Session]| -> usize {
+        char_len(&title)
+            + sessions
+                .iter()
+                .enumerate()
+                .map(|(i, s)| char_len(&header(s, i)) + char_len(&footnote(s, i)))
+                .sum::<usize>()
+            + FOOTER_RULE.len()
+    };
+    while !sessions.is_empty() && overhead(sessions) > budget {
+        sessions = &sessions[..sessions.len() - 1];
+    }
+    if sessions.is_empty() {
+        return String::new();
+    }
+
+    let mut remaining = budget - overhead(sessions);
+    let cap = (budget / MAX_MESSAGE_SHARE).max(MIN_EXCERPT_CHARS);
+    let mut chosen: HashMap<(usize, usize), String> = HashMap::new();
+
+    for pick in rank_messages(sessions, query) {
+        let message = &sessions[pick.session].messages[pick.message];
+        let label = label(message.role);
+        let framing = char_len(label) + 2; // blank line after each message
+        let room = remaining.min(cap).saturating_sub(framing);
+        if room < MIN_EXCERPT_CHARS {
+            continue;
+        }
+
+        let content = message.content.trim();
+        let text = excerpt(content, room, focus(content, query));
+        remaining -= framing + char_len(&text);
+        chosen.insert((pick.session, pick.message), text);
+    }
+
+    let mut out = title;
+    let mut footnotes = String::new();
+    for (i, session) in sessions.iter().enumerate() {
+        let mut picked: Vec<_> = chosen.iter().filter(|((s, _), _)| *s == i).collect();
+        if picked.is_empty() {
+            continue;
+        }
+        picked.sort_by_key(|((_, m), _)| *m);
+
+        out.push_str(&header(session, i));
+        for ((_, m), text) in picked {
+            out.push_str(label(session.messages[*m].role));
+            out.push_str(text);
+            out.push_str("\n\n");
+        }
+        footnotes.push_str(&footnote(session, i));
+    }
+    out.push_str(FOOTER_RULE);
+    out.push_str(&footnotes);
+    out
+}
+
+const FOOTER_RULE: &str = "---\n";
+
+/// Order candidate messages by how much they belong in the pack: query matches first
+/// (weighted toward the end of a session, where conclusions are), then each match's
+/// reply or prompt, then each session's final answer. Better-ranked sessions win ties.
+fn rank_messages(sessions: &[Session], query: &str) -> Vec<Pick> {
+    let mut priorities: HashMap<(usize, usize), f64> = HashMap::new();
+    let mut bump = |key: (usize, usize), priority: f64| {
+        let entry = priorities.entry(key).or_insert(0.0);
+        *entry = entry.max(priority);
+    };
+
+    for (s, session) in sessions.iter().enumerate() {
+        let len = session.messages.len().max(1) as f64;
+        let session_weight = 1.0 / (s + 1) as f64;
+
+        for (m, score, message) in score_messages(&session.messages, query) {
+            let lateness = 1.0 + m as f64 / len;
+            let priority = score as f64 * lateness * session_weight;
+            bump((s, m), priority);
+
+            // The other half of the exchange
+            let partner = match message.role {
+                Role::User => m + 1,
+                Role::Assistant => m.wrapping_sub(1),
+                Role::Context => continue,
+            };
+            if partner < session.messages.len() {
+                bump((s, partner), priority / 2.0);
+            }
+        }
+
+        if let Some(last) = session.messages.iter().rposition(|m| m.role == Role::Assistant) {
+            bump((s, last), 0.5 * session_weight);
+        }
+    }
+
+    let mut picks: Vec<Pick> = priorities
+        .into_iter()
+        .map(|((session, message), priority)| Pick { session, message, priority })
+        .collect();
+    picks.sort_by(|a, b| {
+        b.priority
+            .total_cmp(&a.priority)
+            .then_with(|| a.session.cmp(&b.session))
+            .then_with(|| a.message.cmp(&b.message))
+    });
+    picks
+}
+
+fn header(session: &Session, i: usize) -> String {
+    format!(
+        "## {} ({}, {}) [^{}]\n\n",
+        session.project_name(),
+        session.source.display_name(),
+        session.timestamp.format("%Y-%m-%d"),
+        i + 1
+    )
+}
+
+fn footnote(session: &Session, i: usize) -> String {
+    format!("[^{}]: `{}` in {}\n", i + 1, session.resume_command_line(), session.cwd)
+}
+
+fn label(role: Role) -> &'static str {
+    match role {
+        Role::User => "**User:** ",
+        Role::Assistant => "**Assistant:** ",
+        Role::Context => "**Earlier context:** ",
+    }
+}
+
+fn char_len(s: &str) -> usize {
+    s.chars().count()
+}
+
+/// Character offset of the first query term in `text` (0 if none)
+fn focus(text: &str, query: &str) -> usize {
+    query
+        .split_whitespace()
+        .filter_map(|term| fold::find_all(text, term).first().map(|r| r.start))
+        .min()
+        .map(|byte| char_len(&text[..byte]))
+        .unwrap_or(0)
+}
+
+/// Cut `text` to at most `max_chars`, keeping the part around `focus` and marking cuts with …
+fn excerpt(text: &str, max_chars: usize, focus: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() <= max_chars {
+        return text.to_string();
+    }
+
+    // Focus near the start: keep the beginning, cut the end
+    let head = max_chars.saturating_sub(1);
+    if focus < head * 2 / 3 {
+        let mut out: String = chars[..head].iter().collect();
+        out.push('…');
+        return out;
+    }
+
+    // Otherwise cut both ends, leaving room for an ellipsis at each
+    let room = max_chars.saturating_sub(2);
+    let start = focus.saturating_sub(room / 3).min(chars.len() - room);
+    let end = start + room;
+
+    let mut out = String::new();
+    if start > 0 {
+        out.push('…');
+    }
+    out.extend(&chars[start..end]);
+    if end < chars.len() {
+        out.push('…');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_excerpt_short_text_unchanged() {
+        assert_eq!(excerpt("hello", 10, 0), "hello");
+    }
+
+    #[test]
+    fn test_excerpt_keeps_focus() {
+        let text = format!("{}NEEDLE{}", "a".repeat(100), "b".repeat(100));
+        let cut = excerpt(&text, 30, 100);
+        assert!(cut.chars().count() <= 30);
+        assert!(cut.contains("NEEDLE"));
+        assert!(cut.starts_with('…') && cut.ends_with('…'));
+    }
+
+    #[test]
+    fn test_excerpt_at_start() {
+        let cut = excerpt("needle and a long tail of text", 12, 0);
+        assert_eq!(cut, "needle and …");
+    }
+
+    #[test]
+    fn test_focus() {
+        assert_eq!(focus("Über the Pool", "pool"), 9);
+        assert_eq!(focus("nothing here", "pool"), 0);
+    }
+}
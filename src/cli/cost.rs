@@ -0,0 +1,298 @@
+//! `recall cost` - token usage and estimated cost, grouped by project, source, or day
+
+use super::csv::push_row;
+use anyhow::Result;
+use recall::{
+    index::{ensure_index_fresh, SessionIndex},
+    pricing,
+    session::SessionUsage,
+    time::parse_time,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// What to group costs by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CostGroup {
+    Project,
+    Source,
+    Day,
+}
+
+/// Output format for `recall cost`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CostFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+/// Output format for `recall cost --format json`
+#[derive(Debug, Serialize)]
+pub struct CostOutput {
+    pub groups: Vec<CostRow>,
+    pub total: CostRow,
+}
+
+/// Usage and cost summed over a group of sessions
+#[derive(Debug, Default, Serialize)]
+pub struct CostRow {
+    pub key: String,
+    pub sessions: usize,
+    /// Sessions whose cost is included in `cost_usd` (the rest lack usage data or a known price)
+    pub priced_sessions: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+    /// Estimated USD for the priced sessions; None when no session could be priced
+    pub cost_usd: Option<f64>,
+}
+
+impl CostRow {
+    fn add(&mut self, session: &SessionUsage) {
+        self.sessions += 1;
+        for usage in &session.usage {
+            self.input_tokens += usage.input_tokens;
+            self.output_tokens += usage.output_tokens;
+            self.cache_read_tokens += usage.cache_read_tokens;
+            self.cache_write_tokens += usage.cache_write_tokens;
+        }
+        if let Some(cost) = pricing::cost(&session.usage) {
+            self.priced_sessions += 1;
+            *self.cost_usd.get_or_insert(0.0) += cost;
+        }
+    }
+
+    /// Cost for display: "n/a" when nothing was priced, marked with * when only partly priced
+    fn cost_display(&self) -> String {
+        match self.cost_usd {
+            None => "n/a".to_string(),
+            Some(cost) if self.priced_sessions < self.sessions => format!("${:.2}*", cost),
+            Some(cost) => format!("${:.2}", cost),
+        }
+    }
+}
+
+fn group_key(session: &SessionUsage, by: CostGroup) -> String {
+    match by {
+        CostGroup::Project => {
+            let cwd = recall::config::get().path_map.apply(&session.cwd);
+            std::path::Path::new(&*cwd)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or(&cwd)
+                .to_string()
+        }
+        CostGroup::Source => session.source.as_str().to_string(),
+        CostGroup::Day => session.timestamp.format("%Y-%m-%d").to_string(),
+    }
+}
+
+/// Sum usage and cost per group. Days are listed newest first, other groups by cost.
+pub fn aggregate(sessions: &[SessionUsage], by: CostGroup) -> CostOutput {
+    let mut groups: BTreeMap<String, CostRow> = BTreeMap::new();
+    let mut total = CostRow {
+        key: "total".to_string(),
+        ..Default::default()
+    };
+
+    for session in sessions {
+        let key = group_key(session, by);
+        groups
+            .entry(key.clone())
+            .or_insert_with(|| CostRow { key, ..Default::default() })
+            .add(session);
+        total.add(session);
+    }
+
+    let mut groups: Vec<CostRow> = groups.into_values().collect();
+    match by {
+        CostGroup::Day => groups.reverse(),
+        CostGroup::Project | CostGroup::Source => groups.sort_by(|a, b| {
+            b.cost_usd
+                .unwrap_or(-1.0)
+                .total_cmp(&a.cost_usd.unwrap_or(-1.0))
+                .then_with(|| a.key.cmp(&b.key))
+        }),
+    }
+
+    CostOutput { groups, total }
+}
+
+/// Run the cost subcommand
+pub fn run_cost(since: &Option<String>, by: CostGroup, format: CostFormat) -> Result<()> {
+    let since = since.as_ref().map(|s| parse_time(s)).transpose()?;
+
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+
+    let output = aggregate(&index.usage(since.map(|t| t.timestamp()))?, by);
+
+    match format {
+        CostFormat::Json => println!("{}", serde_json::to_string_pretty(&output)?),
+        CostFormat::Csv => print!("{}", to_csv(&output)),
+        CostFormat::Text => print!("{}", to_table(&output, by)),
+    }
+    Ok(())
+}
+
+fn to_csv(output: &CostOutput) -> String {
+    let mut csv = String::new();
+    push_row(
+        &mut csv,
+        [
+            "key",
+            "sessions",
+            "priced_sessions",
+            "input_tokens",
+            "output_tokens",
+            "cache_read_tokens",
+            "cache_write_tokens",
+            "cost_usd",
+        ]
+        .into_iter()
+        .map(String::from),
+    );
+    for row in output.groups.iter().chain([&output.total]) {
+        push_row(
+            &mut csv,
+            [
+                row.key.clone(),
+                row.sessions.to_string(),
+                row.priced_sessions.to_string(),
+                row.input_tokens.to_string(),
+                row.output_tokens.to_string(),
+                row.cache_read_tokens.to_string(),
+                row.cache_write_tokens.to_string(),
+                row.cost_usd.map(|c| format!("{:.4}", c)).unwrap_or_default(),
+            ]
+            .into_iter(),
+        );
+    }
+    csv
+}
+
+fn to_table(output: &CostOutput, by: CostGroup) -> String {
+    let heading = match by {
+        CostGroup::Project => "PROJECT",
+        CostGroup::Source => "SOURCE",
+        CostGroup::Day => "DAY",
+    };
+    let width = output
+        .groups
+        .iter()
+        .map(|r| r.key.chars().count())
+        .chain([heading.len(), output.total.key.len()])
+        .max()
+        .unwrap_or(0);
+
+    let line = |key: &str, cells: [String; 6]| {
+        format!(
+            "{:<width$}  {:>8}  {:>12}  {:>12}  {:>12}  {:>12}  {:>10}\n",
+            key,
+            cells[0],
+            cells[1],
+            cells[2],
+            cells[3],
+            cells[4],
+            cells[5],
+            width = width
+        )
+    };
+    let row_line = |row: &CostRow| {
+        line(
+            &row.key,
+            [
+                row.sessions.to_string(),
+                row.input_tokens.to_string(),
+                row.output_tokens.to_string(),
+                row.cache_read_tokens.to_string(),
+                row.cache_write_tokens.to_string(),
+                row.cost_display(),
+            ],
+        )
+    };
+
+    let mut out = line(
+        heading,
+        ["SESSIONS", "INPUT", "OUTPUT", "CACHE READ", "CACHE WRITE", "COST"].map(String::from),
+    );
+    for row in &output.groups {
+        out.push_str(&row_line(row));
+    }
+    out.push_str(&row_line(&output.total));
+
+    let unpriced = output.total.sessions - output.total.priced_sessions;
+    if unpriced > 0 {
+        out.push_str(&format!(
+            "\n* excludes {} session{} without usage data or a known model price\n",
+            unpriced,
+            if unpriced == 1 { "" } else { "s" }
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use recall::session::{ModelUsage, SessionSource};
+
+    fn session(id: &str, cwd: &str, day: u32, usage: Vec<ModelUsage>) -> SessionUsage {
+        SessionUsage {
+            session_id: id.to_string(),
+            source: SessionSource::ClaudeCode,
+            cwd: cwd.to_string(),
+            timestamp: Utc.with_ymd_and_hms(2025, 3, day, 12, 0, 0).unwrap(),
+            usage,
+        }
+    }
+
+    fn sonnet(input: u64, output: u64) -> Vec<ModelUsage> {
+        vec![ModelUsage {
+            model: "claude-sonnet-4-5".to_string(),
+            input_tokens: input,
+            output_tokens: output,
+            ..Default::default()
+        }]
+    }
+
+    #[test]
+    fn test_aggregate_by_project() {
+        let sessions = [
+            session("a", "/w/shop", 1, sonnet(1_000_000, 0)),
+            session("b", "/w/shop", 2, sonnet(0, 1_000_000)),
+            session("c", "/w/blog", 2, sonnet(1_000_000, 0)),
+            session("d", "/w/blog", 3, vec![]),
+        ];
+        let output = aggregate(&sessions, CostGroup::Project);
+
+        assert_eq!(output.groups.len(), 2);
+        let shop = &output.groups[0];
+        assert_eq!(shop.key, "shop");
+        assert_eq!(shop.sessions, 2);
+        assert_eq!(shop.input_tokens, 1_000_000);
+        assert!((shop.cost_usd.unwrap() - 18.0).abs() < 1e-9);
+
+        let blog = &output.groups[1];
+        assert_eq!((blog.sessions, blog.priced_sessions), (2, 1));
+        assert_eq!(blog.cost_display(), "$3.00*");
+
+        assert_eq!(output.total.sessions, 4);
+        assert!((output.total.cost_usd.unwrap() - 21.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_aggregate_by_day_newest_first() {
+        let sessions = [
+            session("a", "/w/shop", 1, sonnet(1, 1)),
+            session("b", "/w/shop", 3, vec![]),
+        ];
+        let output = aggregate(&sessions, CostGroup::Day);
+        let keys: Vec<_> = output.groups.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(keys, ["2025-03-03", "2025-03-01"]);
+        assert_eq!(output.groups[0].cost_display(), "n/a");
+    }
+}
@@ -0,0 +1,73 @@
+//! CSV output for `recall list --format csv` and `recall cost --format csv`
+
+use super::fields::FieldSelection;
+use anyhow::Result;
+use recall::session::ListOutput;
+use serde_json::Value;
+
+/// Render sessions as CSV: a header row of field names, then one row per session
+pub fn list_to_csv(output: &ListOutput, fields: &FieldSelection) -> Result<String> {
+    let mut csv = String::new();
+    push_row(&mut csv, fields.names().map(String::from));
+
+    for session in &output.sessions {
+        let item = serde_json::to_value(session)?;
+        push_row(
+            &mut csv,
+            fields
+                .names()
+                .map(|name| cell(fields.lookup(&item, name))),
+        );
+    }
+
+    Ok(csv)
+}
+
+pub(super) fn cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+pub(super) fn push_row(csv: &mut String, cells: impl Iterator<Item = String>) {
+    let row: Vec<String> = cells.map(|c| escape(&c)).collect();
+    csv.push_str(&row.join(","));
+    csv.push('\n');
+}
+
+/// Quote a cell if it contains a delimiter, quote, or line break (RFC 4180)
+fn escape(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_plain() {
+        assert_eq!(escape("hello world"), "hello world");
+        assert_eq!(escape(""), "");
+    }
+
+    #[test]
+    fn test_escape_special_characters() {
+        assert_eq!(escape("a,b"), "\"a,b\"");
+        assert_eq!(escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape("line1\nline2"), "\"line1\nline2\"");
+    }
+
+    #[test]
+    fn test_cell_formats_values() {
+        assert_eq!(cell(None), "");
+        assert_eq!(cell(Some(&Value::Null)), "");
+        assert_eq!(cell(Some(&serde_json::json!(42))), "42");
+        assert_eq!(cell(Some(&serde_json::json!("x"))), "x");
+    }
+}
@@ -0,0 +1,124 @@
+//! `recall diff` - compare two sessions message by message
+
+use anyhow::Result;
+use recall::{
+    diff::{self, Alignment, DiffEntry, DiffSummary},
+    index::{ensure_index_fresh, SessionIndex},
+    parser,
+    session::{Session, SessionSource},
+};
+use serde::Serialize;
+
+/// Output format for `recall diff`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DiffFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiffSide {
+    pub session_id: String,
+    pub source: SessionSource,
+    pub cwd: String,
+    pub message_count: usize,
+}
+
+/// Output format for `recall diff --format json`
+#[derive(Debug, Serialize)]
+pub struct DiffOutput {
+    pub left: DiffSide,
+    pub right: DiffSide,
+    pub summary: DiffSummary,
+    pub entries: Vec<DiffEntry>,
+}
+
+fn side(session: &Session) -> DiffSide {
+    DiffSide {
+        session_id: session.id.clone(),
+        source: session.source,
+        cwd: session.cwd.clone(),
+        message_count: session.messages.len(),
+    }
+}
+
+fn load(index: &SessionIndex, session_id: &str) -> Result<Session> {
+    let file_path = index
+        .get_by_id(session_id)?
+        .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session_id))?;
+    parser::parse_session_file(&file_path)
+}
+
+/// Run the diff subcommand
+pub fn run_diff(left: &str, right: &str, alignment: Alignment, format: DiffFormat) -> Result<()> {
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+
+    let left = load(&index, left)?;
+    let right = load(&index, right)?;
+    let entries = diff::align(&left.messages, &right.messages, alignment);
+
+    match format {
+        DiffFormat::Json => {
+            let output = DiffOutput {
+                left: side(&left),
+                right: side(&right),
+                summary: diff::summarize(&entries),
+                entries,
+            };
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        DiffFormat::Text => print!("{}", to_text(&left, &right, &entries)),
+    }
+    Ok(())
+}
+
+/// Unified-diff style rendering: changed messages show their lines, messages in only one
+/// session are shown in full, unchanged ones get a single line
+fn to_text(left: &Session, right: &Session, entries: &[DiffEntry]) -> String {
+    let mut out = format!(
+        "--- {} ({})\n+++ {} ({})\n",
+        left.id,
+        left.source.display_name(),
+        right.id,
+        right.source.display_name()
+    );
+    let body = |out: &mut String, op: char, content: &str| {
+        for line in content.lines() {
+            out.push(op);
+            out.push_str(line);
+            out.push('\n');
+        }
+    };
+
+    for entry in entries {
+        match entry {
+            DiffEntry::Same { left: i, right: j, role } => {
+                out.push_str(&format!("  [{}] = [{}] {}\n", i, j, role.as_str()));
+            }
+            DiffEntry::Changed { left: i, right: j, role, lines } => {
+                out.push_str(&format!("@@ [{}] ~ [{}] {} @@\n", i, j, role.as_str()));
+                for line in lines {
+                    out.push(line.op);
+                    out.push_str(&line.text);
+                    out.push('\n');
+                }
+            }
+            DiffEntry::Removed { left: i, role } => {
+                out.push_str(&format!("@@ -[{}] {} (only in {}) @@\n", i, role.as_str(), left.id));
+                body(&mut out, '-', &left.messages[*i].content);
+            }
+            DiffEntry::Added { right: j, role } => {
+                out.push_str(&format!("@@ +[{}] {} (only in {}) @@\n", j, role.as_str(), right.id));
+                body(&mut out, '+', &right.messages[*j].content);
+            }
+        }
+    }
+
+    let DiffSummary { added, removed, changed, unchanged } = diff::summarize(entries);
+    out.push_str(&format!(
+        "\n{} added, {} removed, {} changed, {} unchanged\n",
+        added, removed, changed, unchanged
+    ));
+    out
+}
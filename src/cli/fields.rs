@@ -0,0 +1,139 @@
+//! `--fields` selection of which keys appear in JSON output (and columns in CSV output)
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Fields accepted by `--fields`, and the JSON keys each one keeps
+/// (Not every output has every key: `list` has no messages, `search` has no title.)
+const FIELDS: &[(&str, &[&str])] = &[
+    ("id", &["session_id"]),
+    ("source", &["source"]),
+    ("project", &["project"]),
+    ("cwd", &["cwd"]),
+    ("branch", &["git_branch"]),
+    ("timestamp", &["timestamp"]),
+    ("message_count", &["message_count"]),
+    ("title", &["title"]),
+    ("resume_command", &["resume_command"]),
+    ("messages", &["relevant_messages", "messages"]),
+    ("origin", &["origin"]),
+];
+
+/// Columns written by `recall list --format csv` (and `--null`) when `--fields` isn't given
+pub const DEFAULT_CSV_FIELDS: &str = "id,source,project,cwd,branch,timestamp,message_count,title";
+
+/// Fields to keep on each result, parsed from a comma-separated `--fields` value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSelection {
+    fields: Vec<(&'static str, &'static [&'static str])>,
+}
+
+impl FieldSelection {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut fields = Vec::new();
+        for name in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let field = FIELDS
+                .iter()
+                .find(|(field, _)| *field == name)
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Unknown field '{}'. Valid fields: {}", name, valid_fields())
+                })?;
+            fields.push(*field);
+        }
+        if fields.is_empty() {
+            anyhow::bail!("No fields given. Valid fields: {}", valid_fields());
+        }
+        Ok(Self { fields })
+    }
+
+    /// Selected field names, in the order given
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.fields.iter().map(|(name, _)| *name)
+    }
+
+    /// The first of a field's keys present in `item`
+    pub fn lookup<'v>(&self, item: &'v Value, name: &str) -> Option<&'v Value> {
+        let (_, keys) = self.fields.iter().find(|(n, _)| *n == name)?;
+        keys.iter().find_map(|key| item.get(*key))
+    }
+
+    fn keeps(&self, key: &str) -> bool {
+        self.fields.iter().any(|(_, keys)| keys.contains(&key))
+    }
+
+    /// Serialize `output`, keeping only the selected keys on each item of the `items` array
+    pub fn apply(&self, output: &impl Serialize, items: &str) -> Result<Value> {
+        let mut value = serde_json::to_value(output)?;
+        if let Some(Value::Array(entries)) = value.get_mut(items) {
+            for entry in entries {
+                if let Value::Object(map) = entry {
+                    map.retain(|key, _| self.keeps(key));
+                }
+            }
+        }
+        Ok(value)
+    }
+}
+
+fn valid_fields() -> String {
+    FIELDS
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Serialize `output` as pretty JSON, honoring `--fields` if given
+pub fn to_json_pretty(
+    output: &impl Serialize,
+    items: &str,
+    fields: Option<&FieldSelection>,
+) -> Result<String> {
+    Ok(match fields {
+        Some(fields) => serde_json::to_string_pretty(&fields.apply(output, items)?)?,
+        None => serde_json::to_string_pretty(output)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_fields() {
+        let fields = FieldSelection::parse("timestamp, id,messages").unwrap();
+        assert_eq!(fields.names().collect::<Vec<_>>(), ["timestamp", "id", "messages"]);
+        assert!(fields.keeps("session_id"));
+        assert!(fields.keeps("relevant_messages"));
+        assert!(!fields.keeps("cwd"));
+    }
+
+    #[test]
+    fn test_parse_unknown_field_lists_valid_ones() {
+        let err = FieldSelection::parse("id,bogus").unwrap_err().to_string();
+        assert!(err.contains("'bogus'"));
+        assert!(err.contains("id, source, project, cwd, branch, timestamp"));
+    }
+
+    #[test]
+    fn test_parse_default_csv_fields() {
+        assert!(FieldSelection::parse(DEFAULT_CSV_FIELDS).is_ok());
+    }
+
+    #[test]
+    fn test_parse_empty() {
+        assert!(FieldSelection::parse(" , ").is_err());
+    }
+
+    #[test]
+    fn test_apply_prunes_items_only() {
+        let output = json!({
+            "query": "q",
+            "results": [{"session_id": "a", "cwd": "/x", "relevant_messages": []}],
+        });
+        let value = FieldSelection::parse("id").unwrap().apply(&output, "results").unwrap();
+        assert_eq!(value, json!({"query": "q", "results": [{"session_id": "a"}]}));
+    }
+}
@@ -0,0 +1,202 @@
+//! `recall files` - files a session read or wrote, and sessions that touched a file
+
+use super::null;
+use anyhow::Result;
+use recall::{
+    index::{ensure_index_fresh, SessionIndex},
+    parser,
+    session::{FileOp, FileTouch, SessionSummary},
+};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+
+/// Output format for `recall files <session-id>`
+#[derive(Debug, Serialize)]
+pub struct FilesOutput {
+    pub session_id: String,
+    pub files: Vec<TouchedFile>,
+}
+
+/// A file touched during a session, with every way it was touched
+#[derive(Debug, Serialize)]
+pub struct TouchedFile {
+    pub path: String,
+    pub operations: BTreeSet<FileOp>,
+    pub message_indices: BTreeSet<usize>,
+}
+
+/// Output format for `recall files --path <path>`
+#[derive(Debug, Serialize)]
+pub struct TouchingOutput {
+    pub path: String,
+    pub sessions: Vec<SessionSummary>,
+}
+
+/// Group touches by path, keeping first-touch order
+pub fn group_touches<'a>(touches: impl IntoIterator<Item = &'a FileTouch>) -> Vec<TouchedFile> {
+    let mut files: Vec<TouchedFile> = Vec::new();
+    for touch in touches {
+        let file = match files.iter_mut().position(|f| f.path == touch.path) {
+            Some(i) => &mut files[i],
+            None => {
+                files.push(TouchedFile {
+                    path: touch.path.clone(),
+                    operations: BTreeSet::new(),
+                    message_indices: BTreeSet::new(),
+                });
+                files.last_mut().unwrap()
+            }
+        };
+        file.operations.insert(touch.op);
+        file.message_indices.insert(touch.message_index);
+    }
+    files
+}
+
+/// List the files a session touched
+pub fn files(index: &SessionIndex, session_id: &str, edited_only: bool) -> Result<FilesOutput> {
+    let file_path = index
+        .get_by_id(session_id)?
+        .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session_id))?;
+    let session = parser::parse_session_file(&file_path)?;
+
+    let touches = session
+        .files_touched
+        .iter()
+        .filter(|t| !edited_only || t.op.is_write());
+
+    Ok(FilesOutput {
+        session_id: session.id.clone(),
+        files: group_touches(touches),
+    })
+}
+
+/// List sessions whose tool calls touched `path`, most recent first
+pub fn touching(index: &SessionIndex, path: &Path, edited_only: bool) -> Result<TouchingOutput> {
+    let path = absolute_path(path).to_string_lossy().to_string();
+
+    let mut sessions: Vec<_> = index
+        .sessions_touching(&path)?
+        .iter()
+        .filter_map(|file| parser::parse_session_file(file).ok())
+        .filter(|session| {
+            session
+                .files_touched
+                .iter()
+                .any(|t| t.path == path && (!edited_only || t.op.is_write()))
+        })
+        .collect();
+    sessions.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then_with(|| a.id.cmp(&b.id)));
+
+    Ok(TouchingOutput {
+        path,
+        sessions: sessions.iter().map(|s| s.to_summary()).collect(),
+    })
+}
+
+/// Resolve a path against the current directory without touching the filesystem
+/// (the file may no longer exist)
+fn absolute_path(path: &Path) -> PathBuf {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir().unwrap_or_default().join(path)
+    };
+
+    let mut resolved = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            other => resolved.push(other),
+        }
+    }
+    resolved
+}
+
+/// Run the files subcommand
+pub fn run_files(
+    session_id: Option<&str>,
+    path: Option<&Path>,
+    edited_only: bool,
+    json: bool,
+    null: bool,
+) -> Result<()> {
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+
+    if let Some(path) = path {
+        let output = touching(&index, path, edited_only)?;
+        if json {
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        } else {
+            for session in &output.sessions {
+                let timestamp = session.timestamp.format("%Y-%m-%d %H:%M").to_string();
+                let source = session.source.as_str();
+                if null {
+                    print!(
+                        "{}",
+                        null::record([timestamp.as_str(), source, &session.session_id, &session.title])
+                    );
+                } else {
+                    println!(
+                        "{}  {:<8} {}  {}",
+                        timestamp, source, session.session_id, session.title
+                    );
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    let session_id = session_id.ok_or_else(|| anyhow::anyhow!("Give a session ID or --path"))?;
+    let output = files(&index, session_id, edited_only)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        for file in &output.files {
+            let ops: Vec<_> = file.operations.iter().map(|op| op.as_str()).collect();
+            let indices: Vec<_> = file.message_indices.iter().map(|i| i.to_string()).collect();
+            if null {
+                print!("{}", null::record([&file.path, &ops.join(","), &indices.join(",")]));
+            } else {
+                println!("{}  [{}]  messages {}", file.path, ops.join(","), indices.join(","));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(path: &str, op: FileOp, message_index: usize) -> FileTouch {
+        FileTouch { path: path.to_string(), op, message_index }
+    }
+
+    #[test]
+    fn test_group_touches() {
+        let touches = [
+            touch("/p/b.rs", FileOp::Read, 1),
+            touch("/p/a.rs", FileOp::Read, 1),
+            touch("/p/b.rs", FileOp::Edit, 3),
+            touch("/p/b.rs", FileOp::Edit, 3),
+        ];
+        let files = group_touches(&touches);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "/p/b.rs");
+        assert_eq!(files[0].operations, BTreeSet::from([FileOp::Read, FileOp::Edit]));
+        assert_eq!(files[0].message_indices, BTreeSet::from([1, 3]));
+        assert_eq!(files[1].path, "/p/a.rs");
+    }
+
+    #[test]
+    fn test_absolute_path() {
+        assert_eq!(absolute_path(Path::new("/p/./src/../app.rs")), PathBuf::from("/p/app.rs"));
+        assert!(absolute_path(Path::new("src/app.rs")).is_absolute());
+    }
+}
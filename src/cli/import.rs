@@ -0,0 +1,211 @@
+//! `recall import` - bring exported or backed up sessions back into the index
+
+use super::backup;
+use anyhow::{Context, Result};
+use recall::{
+    index::{ensure_index_fresh, SessionIndex},
+    parser::{self, imported_dir},
+    session::{ReadOutput, SessionSource},
+};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Run the import subcommand
+pub fn run_import(path: &Path, overwrite: bool) -> Result<()> {
+    if !path.exists() {
+        anyhow::bail!("No such file or directory: {}", path.display());
+    }
+
+    let exports = if backup::is_archive(path) {
+        read_archive(path)?
+    } else {
+        export_files(path)
+            .into_iter()
+            .map(|file| (file.display().to_string(), read_export(&file)))
+            .collect()
+    };
+
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+
+    let dest_root = imported_dir();
+    let mut imported = 0;
+    let mut skipped: Vec<(String, String)> = Vec::new();
+    let mut stale: Vec<PathBuf> = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (label, export) in exports {
+        let export = match export {
+            Ok(export) => export,
+            Err(e) => {
+                skipped.push((label, format!("{:#}", e)));
+                continue;
+            }
+        };
+
+        let id = export.session_id.clone();
+        if !seen.insert(id.clone()) {
+            skipped.push((id, "duplicate in import".to_string()));
+            continue;
+        }
+
+        // Conflicts are decided by what the index already knows about this id
+        if let Some(existing) = index.get_by_id(&id)? {
+            if !existing.exists() {
+                // Original was pruned since it was indexed - the import replaces it
+                stale.push(existing);
+            } else if !existing.starts_with(&dest_root) {
+                skipped.push((id, format!("original still present at {}", existing.display())));
+                continue;
+            } else if !overwrite {
+                skipped.push((id, "already imported (use --overwrite)".to_string()));
+                continue;
+            }
+        }
+
+        let dest = dest_root
+            .join(export.source.as_str())
+            .join(format!("{}.json", sanitize_file_name(&id)));
+        write_export(&dest, &export)?;
+        imported += 1;
+    }
+
+    // Drop index entries pointing at deleted originals so lookups find the import
+    if !stale.is_empty() {
+        let mut writer = index.writer()?;
+        for path in &stale {
+            index.delete_session(&mut writer, path);
+        }
+        writer.commit()?;
+        index.reload()?;
+    }
+
+    ensure_index_fresh(&index)?;
+
+    println!(
+        "Imported {} session{} into {}",
+        imported,
+        if imported == 1 { "" } else { "s" },
+        dest_root.display()
+    );
+    if !skipped.is_empty() {
+        println!("Skipped {}:", skipped.len());
+        for (what, reason) in &skipped {
+            println!("  {}: {}", what, reason);
+        }
+    }
+
+    Ok(())
+}
+
+/// Run `import chatgpt`: file each conversation in a ChatGPT export as an imported session.
+/// A conversation imported before is replaced by its copy in the export, so importing a newer
+/// export brings in new messages without duplicating anything.
+pub fn run_import_chatgpt(path: &Path) -> Result<()> {
+    if !path.is_file() {
+        anyhow::bail!("No such file: {}", path.display());
+    }
+    let sessions = parser::read_chatgpt_export(path)?;
+
+    let dest_dir = imported_dir().join(SessionSource::ChatGpt.as_str());
+    let (mut imported, mut updated, mut unchanged) = (0, 0, 0);
+    for session in &sessions {
+        let dest = dest_dir.join(format!("{}.json", sanitize_file_name(&session.id)));
+        let export = session.to_read_output();
+        match read_export(&dest) {
+            // Left alone, so the index has nothing to redo
+            Ok(existing) if existing.messages == export.messages => {
+                unchanged += 1;
+                continue;
+            }
+            Ok(_) => updated += 1,
+            Err(_) => imported += 1,
+        }
+        write_export(&dest, &export)?;
+    }
+
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+
+    println!(
+        "Imported {} conversation{} into {} ({} updated, {} unchanged)",
+        imported,
+        if imported == 1 { "" } else { "s" },
+        dest_dir.display(),
+        updated,
+        unchanged
+    );
+    Ok(())
+}
+
+/// Collect exported session files: a single JSON file, or every JSON file under a directory
+fn export_files(path: &Path) -> Vec<PathBuf> {
+    if path.is_file() {
+        return vec![path.to_path_buf()];
+    }
+
+    let mut files: Vec<PathBuf> = walkdir::WalkDir::new(path)
+        .into_iter()
+        .flatten()
+        .map(|e| e.into_path())
+        .filter(|p| p.is_file() && p.extension().map(|e| e == "json").unwrap_or(false))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Parse every session in a `recall backup` archive
+fn read_archive(path: &Path) -> Result<Vec<(String, Result<ReadOutput>)>> {
+    let tmp = std::env::temp_dir().join(format!("recall-import-{}", std::process::id()));
+    let manifest = backup::extract(path, &tmp);
+    let exports = manifest.map(|manifest| {
+        manifest
+            .sessions
+            .into_iter()
+            .map(|entry| {
+                let file = tmp.join(&entry.archive_path);
+                // Archives hold the tools' native files, plus any previously imported exports
+                let export = parser::parse_session_file(&file)
+                    .map(|session| session.to_read_output())
+                    .or_else(|_| read_export(&file));
+                (entry.id, export)
+            })
+            .collect()
+    });
+    let _ = std::fs::remove_dir_all(&tmp);
+    exports
+}
+
+fn read_export(path: &Path) -> Result<ReadOutput> {
+    let contents = std::fs::read_to_string(path).context("Failed to read file")?;
+    serde_json::from_str(&contents).context("Not a recall session export")
+}
+
+fn write_export(dest: &Path, export: &ReadOutput) -> Result<()> {
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(export)?;
+    std::fs::write(dest, json).with_context(|| format!("Failed to write {}", dest.display()))
+}
+
+/// Make a session ID safe to use as a file name
+fn sanitize_file_name(id: &str) -> String {
+    id.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '\0' => '_',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("ses_abc123"), "ses_abc123");
+        assert_eq!(sanitize_file_name("a/b\\c:d"), "a_b_c_d");
+    }
+}
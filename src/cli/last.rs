@@ -0,0 +1,41 @@
+//! `recall last` - pick the most recent session for the current directory
+
+use super::filters;
+use anyhow::Result;
+use recall::{
+    app::{in_scope, scope_root},
+    engine::fetch_filtered,
+    index::SessionIndex,
+    session::{SearchResult, SessionSource},
+};
+use std::path::Path;
+
+/// The `pick`-th most recent session (1-based) under `dir`'s scope
+pub fn last(
+    index: &SessionIndex,
+    dir: &Path,
+    source: Option<SessionSource>,
+    pick: usize,
+) -> Result<Option<SearchResult>> {
+    let root = scope_root(dir);
+    let filters = filters(source, &None, &None, &None, &[])?;
+
+    // The cwd filter isn't part of Filters (it's exact-match there), so keep fetching
+    // until enough sessions survive both
+    let mut wanted = pick.max(1) * 8;
+    loop {
+        let results = fetch_filtered(|n| index.recent(n), &filters, wanted)?;
+        let exhausted = results.len() <= wanted;
+        let mut matching: Vec<_> = results
+            .into_iter()
+            .filter(|r| in_scope(&r.session.local_cwd(), &root))
+            // Sessions with nothing to resume them in aren't the one to jump back into
+            .filter(|r| r.session.resumable())
+            .collect();
+
+        if matching.len() >= pick || exhausted {
+            return Ok((pick >= 1 && matching.len() >= pick).then(|| matching.remove(pick - 1)));
+        }
+        wanted *= 4;
+    }
+}
@@ -0,0 +1,452 @@
+//! CLI subcommands for non-interactive mode (JSON output for agents)
+
+pub mod backup;
+pub mod bookmarks;
+pub mod context;
+pub mod cost;
+mod csv;
+pub mod diff;
+pub mod fields;
+pub mod files;
+pub mod import;
+pub mod last;
+pub mod null;
+pub mod print;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod sources;
+pub mod summary;
+pub mod watch;
+
+use anyhow::Result;
+use fields::{to_json_pretty, FieldSelection};
+use recall::{
+    engine::{fetch_filtered, SearchOptions},
+    fold,
+    index::{ensure_index_fresh, SessionIndex},
+    parser,
+    session::{
+        ListOutput, Message, ReadOutput, Role, SearchOutput, SearchResultOutput, Session,
+        SessionSource,
+    },
+    time::parse_time,
+};
+
+const DEFAULT_MESSAGES_PER_SESSION: usize = 5;
+
+/// Search parameters shared by `recall search` and `recall serve`
+pub struct SearchParams {
+    pub query: String,
+    pub source: Option<SessionSource>,
+    /// Search within a specific session
+    pub session_id: Option<String>,
+    pub limit: usize,
+    /// Number of results to skip (for paging)
+    pub offset: usize,
+    /// Number of context messages around each match
+    pub context: usize,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub cwd: Option<String>,
+    /// Only sessions with all of these tags
+    pub tags: Vec<String>,
+}
+
+/// List parameters shared by `recall list` and `recall serve`
+pub struct ListParams {
+    pub limit: usize,
+    /// Number of sessions to skip (for paging)
+    pub offset: usize,
+    pub source: Option<SessionSource>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub cwd: Option<String>,
+    /// Only sessions with all of these tags
+    pub tags: Vec<String>,
+}
+
+/// Session filters applied after the index query, from the CLI's arguments
+fn filters(
+    source: Option<SessionSource>,
+    since: &Option<String>,
+    until: &Option<String>,
+    cwd: &Option<String>,
+    tags: &[String],
+) -> Result<SearchOptions> {
+    Ok(SearchOptions {
+        source,
+        since: since.as_ref().map(|s| parse_time(s)).transpose()?,
+        until: until.as_ref().map(|s| parse_time(s)).transpose()?,
+        cwd: cwd.clone(),
+        tags: recall::tags::parse(&tags.join(",")),
+        ..Default::default()
+    })
+}
+
+/// Run the search subcommand
+pub fn run_search(params: &SearchParams, fields: Option<&FieldSelection>) -> Result<()> {
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+
+    let output = search(&index, params)?;
+
+    println!("{}", to_json_pretty(&output, "results", fields)?);
+    Ok(())
+}
+
+/// Search the index and build the JSON output
+pub fn search(index: &SessionIndex, params: &SearchParams) -> Result<SearchOutput> {
+    let query = params.query.as_str();
+
+    // If searching within a specific session, handle separately
+    if let Some(sid) = &params.session_id {
+        return search_in_session(index, query, sid, params.context);
+    }
+
+    let filters = filters(params.source, &params.since, &params.until, &params.cwd, &params.tags)?;
+    let results = fetch_filtered(
+        |n| index.search(query, n),
+        &filters,
+        params.offset + params.limit,
+    )?;
+    let total_estimate = results.len();
+
+    // Convert to output format
+    let output = SearchOutput {
+        query: query.to_string(),
+        total_estimate,
+        offset: params.offset,
+        limit: params.limit,
+        results: results
+            .into_iter()
+            .skip(params.offset)
+            .take(params.limit)
+            .map(|r| {
+                // Load full session to get messages
+                let session = parser::parse_session_file(&r.session.file_path)
+                    .unwrap_or(r.session.clone());
+
+                let scored_messages = score_messages(&session.messages, query);
+
+                // Get top N messages, with context if requested
+                let relevant_messages = if params.context > 0 {
+                    // Convert to format expected by collect_with_context
+                    let for_context: Vec<(usize, &Message)> = scored_messages
+                        .iter()
+                        .map(|(idx, _, m)| (*idx, *m))
+                        .collect();
+                    collect_with_context(&session.messages, &for_context, params.context)
+                } else {
+                    scored_messages
+                        .into_iter()
+                        .take(DEFAULT_MESSAGES_PER_SESSION)
+                        .map(|(_, _, m)| m.clone())
+                        .collect()
+                };
+
+                let resume_command = r.session.resume_command_line();
+
+                SearchResultOutput {
+                    session_id: r.session.id,
+                    source: r.session.source,
+                    cwd: r.session.cwd,
+                    timestamp: r.session.timestamp,
+                    relevant_messages,
+                    resume_command,
+                }
+            })
+            .collect(),
+    };
+
+    Ok(output)
+}
+
+/// Search within a specific session (returns all matches)
+fn search_in_session(
+    index: &SessionIndex,
+    query: &str,
+    session_id: &str,
+    context: usize,
+) -> Result<SearchOutput> {
+    let file_path = index
+        .get_by_id(session_id)?
+        .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session_id))?;
+
+    let session = parser::parse_session_file(&file_path)?;
+
+    let scored_messages = score_messages(&session.messages, query);
+
+    // Return all matches (no limit for single session search)
+    let relevant_messages = if context > 0 {
+        let for_context: Vec<(usize, &Message)> = scored_messages
+            .iter()
+            .map(|(idx, _, m)| (*idx, *m))
+            .collect();
+        collect_with_context(&session.messages, &for_context, context)
+    } else {
+        scored_messages
+            .into_iter()
+            .map(|(_, _, m)| m.clone())
+            .collect()
+    };
+
+    let resume_command = session.resume_command_line();
+
+    let output = SearchOutput {
+        query: query.to_string(),
+        total_estimate: 1,
+        offset: 0,
+        limit: 1,
+        results: vec![SearchResultOutput {
+            session_id: session.id,
+            source: session.source,
+            cwd: session.cwd,
+            timestamp: session.timestamp,
+            relevant_messages,
+            resume_command,
+        }],
+    };
+
+    Ok(output)
+}
+
+/// Messages containing query terms as (index, score, message), best first, leaving out
+/// compacted context as the index does.
+/// Score is the number of term occurrences; ties go to the more recent message.
+fn score_messages<'m>(messages: &'m [Message], query: &str) -> Vec<(usize, usize, &'m Message)> {
+    let query_folded = fold::fold(query);
+    let query_terms: Vec<&str> = query_folded.split_whitespace().collect();
+
+    // Filter and score messages in one pass (avoids repeated folding in sort)
+    let mut scored_messages: Vec<(usize, usize, &Message)> = messages
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| m.role != Role::Context)
+        .filter_map(|(idx, m)| {
+            let content_folded = fold::fold(&m.content);
+            let score: usize = query_terms
+                .iter()
+                .map(|t| content_folded.matches(t).count())
+                .sum();
+            if score > 0 {
+                Some((idx, score, m))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    // Sort by pre-computed score (higher first), then recency (higher index first)
+    scored_messages.sort_by(|(idx_a, score_a, _), (idx_b, score_b, _)| {
+        score_b.cmp(score_a).then_with(|| idx_b.cmp(idx_a))
+    });
+
+    scored_messages
+}
+
+/// Collect messages with context around matches, deduplicating overlaps
+fn collect_with_context(
+    all_messages: &[Message],
+    scored: &[(usize, &Message)],
+    context: usize,
+) -> Vec<Message> {
+    let mut indices: std::collections::BTreeSet<usize> = std::collections::BTreeSet::new();
+
+    for (idx, _) in scored.iter().take(DEFAULT_MESSAGES_PER_SESSION) {
+        let start = idx.saturating_sub(context);
+        let end = (*idx + context + 1).min(all_messages.len());
+        for i in start..end {
+            indices.insert(i);
+        }
+    }
+
+    indices
+        .into_iter()
+        .map(|i| all_messages[i].clone())
+        .collect()
+}
+
+/// Output format for `recall list`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ListFormat {
+    Json,
+    Csv,
+}
+
+/// Run the list subcommand (`null` writes NUL-delimited records instead of `format`)
+pub fn run_list(
+    params: &ListParams,
+    fields: Option<&FieldSelection>,
+    format: ListFormat,
+    null: bool,
+) -> Result<()> {
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+
+    let output = list(&index, params)?;
+
+    if format == ListFormat::Json && !null {
+        println!("{}", to_json_pretty(&output, "sessions", fields)?);
+        return Ok(());
+    }
+
+    let default_fields;
+    let fields = match fields {
+        Some(fields) => fields,
+        None => {
+            default_fields = FieldSelection::parse(fields::DEFAULT_CSV_FIELDS)?;
+            &default_fields
+        }
+    };
+    if null {
+        print!("{}", null::list_to_records(&output, fields)?);
+    } else {
+        print!("{}", csv::list_to_csv(&output, fields)?);
+    }
+    Ok(())
+}
+
+/// List recent sessions and build the JSON output
+pub fn list(index: &SessionIndex, params: &ListParams) -> Result<ListOutput> {
+    let filters = filters(params.source, &params.since, &params.until, &params.cwd, &params.tags)?;
+    let results = fetch_filtered(
+        |n| index.recent(n),
+        &filters,
+        params.offset + params.limit,
+    )?;
+
+    Ok(ListOutput {
+        total_estimate: results.len(),
+        offset: params.offset,
+        limit: params.limit,
+        sessions: results
+            .iter()
+            .skip(params.offset)
+            .take(params.limit)
+            .map(|r| {
+                // Load full session for message count and title
+                parser::parse_session_file(&r.session.file_path)
+                    .unwrap_or(r.session.clone())
+                    .to_summary()
+            })
+            .collect(),
+    })
+}
+
+/// Run the read subcommand
+pub fn run_read(selector: &str) -> Result<()> {
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+
+    let output = read(&index, selector)?;
+
+    println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+/// Load a full conversation by session ID, or only the messages a selector picks from it
+/// (`ID:3..8`, `ID:errors`; see [`print::Selector`])
+pub fn read(index: &SessionIndex, selector: &str) -> Result<ReadOutput> {
+    let selector = print::Selector::parse(selector)?;
+    // Find the session by ID
+    let file_path = index
+        .get_by_id(&selector.session_id)?
+        .ok_or_else(|| anyhow::anyhow!("Session not found: {}", selector.session_id))?;
+
+    // Parse full session
+    let session = parser::parse_session_file(&file_path)?;
+    let mut output = session.to_read_output();
+    output.messages = selector
+        .select(&session)
+        .into_iter()
+        .map(|i| session.messages[i].clone())
+        .collect();
+    Ok(output)
+}
+
+/// Session field printed by `recall --select`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SelectField {
+    Id,
+    Cwd,
+    Path,
+    ResumeCmd,
+}
+
+impl SelectField {
+    /// Format the field for printing to stdout
+    pub fn format(self, session: &Session) -> String {
+        match self {
+            SelectField::Id => session.id.clone(),
+            SelectField::Cwd => session.cwd.clone(),
+            SelectField::Path => session.file_path.to_string_lossy().to_string(),
+            SelectField::ResumeCmd => session.resume_command_line(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn select_session() -> Session {
+        Session {
+            id: "abc-123".to_string(),
+            source: SessionSource::ClaudeCode,
+            file_path: std::path::PathBuf::from("/home/user/.claude/projects/p/abc-123.jsonl"),
+            cwd: "/home/user/project".to_string(),
+            git_branch: None,
+            timestamp: Utc::now(),
+            messages: vec![],
+            files_touched: vec![],
+            usage: vec![],
+            title: None,
+        }
+    }
+
+    #[test]
+    fn test_select_field_format() {
+        let session = select_session();
+        assert_eq!(SelectField::Id.format(&session), "abc-123");
+        assert_eq!(SelectField::Cwd.format(&session), "/home/user/project");
+        assert_eq!(
+            SelectField::Path.format(&session),
+            "/home/user/.claude/projects/p/abc-123.jsonl"
+        );
+    }
+
+    #[test]
+    fn test_select_field_resume_cmd() {
+        // An argument with a space stays one argument when the line is run by a shell
+        let session = Session { id: "abc 123".to_string(), ..select_session() };
+        let line = SelectField::ResumeCmd.format(&session);
+        let (cmd, args) = session.resume_command();
+        assert!(args.contains(&"abc 123".to_string()));
+        assert_eq!(shell_words::split(&line).unwrap(), [vec![cmd], args].concat());
+        assert!(line.contains("'abc 123'"), "{}", line);
+    }
+
+    #[test]
+    fn test_score_messages_folds_case() {
+        let message = |content: &str| Message {
+            role: Role::User,
+            content: content.to_string(),
+            timestamp: Utc::now(),
+            tool_calls: Vec::new(),
+        };
+        let messages = [
+            message("Die Straße ist lang"),
+            message("no match here"),
+            message("STRASSE, strasse"),
+            message("İstanbul"),
+        ];
+        let scores = |query| -> Vec<(usize, usize)> {
+            score_messages(&messages, query).into_iter().map(|(i, s, _)| (i, s)).collect()
+        };
+        assert_eq!(scores("straße"), [(2, 2), (0, 1)]);
+        assert_eq!(scores("STRASSE"), [(2, 2), (0, 1)]);
+        assert_eq!(scores("istanbul"), [(3, 1)]);
+        assert_eq!(scores("İSTANBUL"), [(3, 1)]);
+    }
+}
@@ -0,0 +1,40 @@
+//! NUL-delimited output for `-0/--null`, like `find -print0`: one record per NUL,
+//! fields separated by tabs
+
+use super::{csv::cell, fields::FieldSelection};
+use anyhow::Result;
+use recall::session::ListOutput;
+
+/// A record of tab-separated fields, NUL-terminated. Tabs and NULs inside a field become spaces
+/// so they can't be mistaken for separators; newlines are kept.
+pub fn record<S: AsRef<str>>(fields: impl IntoIterator<Item = S>) -> String {
+    let fields: Vec<String> = fields
+        .into_iter()
+        .map(|f| f.as_ref().replace(['\t', '\0'], " "))
+        .collect();
+    let mut record = fields.join("\t");
+    record.push('\0');
+    record
+}
+
+/// One record per session with the selected fields (no header row)
+pub fn list_to_records(output: &ListOutput, fields: &FieldSelection) -> Result<String> {
+    let mut out = String::new();
+    for session in &output.sessions {
+        let item = serde_json::to_value(session)?;
+        out.push_str(&record(fields.names().map(|name| cell(fields.lookup(&item, name)))));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        assert_eq!(record(["a b", "c"]), "a b\tc\0");
+        assert_eq!(record(["tab\there", "line\nbreak"]), "tab here\tline\nbreak\0");
+        assert_eq!(record(["only"]), "only\0");
+    }
+}
@@ -0,0 +1,406 @@
+//! `recall print` - a readable transcript of a session for the terminal
+
+use anyhow::{Context, Result};
+use ratatui::style::Color;
+use recall::{
+    index::{ensure_index_fresh, SessionIndex},
+    parser,
+    session::{Role, Session, ToolCall, ToolStatus},
+    theme::Theme,
+    ui::wrap_text,
+};
+use std::io::{IsTerminal, Write};
+use std::ops::Range;
+
+/// A session and optionally a slice of its messages: `ID`, `ID:N`, `ID:A..B`, `ID:A..`, or `ID:..B`
+/// (0-based message indices, end exclusive), or `ID:errors` for the messages a tool call failed
+/// after
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selector {
+    pub session_id: String,
+    pub start: Option<usize>,
+    pub end: Option<usize>,
+    /// Only messages followed by a failed tool call
+    pub errors: bool,
+}
+
+impl Selector {
+    pub fn parse(s: &str) -> Result<Self> {
+        // Only treat the suffix as a slice if it parses as one (IDs may contain ':')
+        if let Some((id, slice)) = s.rsplit_once(':') {
+            let errors = slice == "errors";
+            let bounds = if errors { Some((None, None)) } else { parse_slice(slice)? };
+            if let Some((start, end)) = bounds {
+                if id.is_empty() {
+                    anyhow::bail!("Missing session ID in '{}'", s);
+                }
+                return Ok(Self {
+                    session_id: id.to_string(),
+                    start,
+                    end,
+                    errors,
+                });
+            }
+        }
+        Ok(Self {
+            session_id: s.to_string(),
+            start: None,
+            end: None,
+            errors: false,
+        })
+    }
+
+    /// Message indices selected from a session with `len` messages
+    pub fn range(&self, len: usize) -> Range<usize> {
+        let start = self.start.unwrap_or(0).min(len);
+        let end = self.end.unwrap_or(len).clamp(start, len);
+        start..end
+    }
+
+    /// Indices of the messages selected from `session`
+    pub fn select(&self, session: &Session) -> Vec<usize> {
+        let failed = |i: &usize| session.messages[*i].tool_calls.iter().any(ToolCall::is_error);
+        self.range(session.messages.len())
+            .filter(|i| !self.errors || failed(i))
+            .collect()
+    }
+}
+
+/// Parse `N`, `A..B`, `A..`, or `..B`, or None if `slice` isn't one
+fn parse_slice(slice: &str) -> Result<Option<(Option<usize>, Option<usize>)>> {
+    match slice.split_once("..") {
+        Some((a, b)) => {
+            let bound = |s: &str| -> Option<Option<usize>> {
+                if s.is_empty() {
+                    Some(None)
+                } else {
+                    s.parse().ok().map(Some)
+                }
+            };
+            Ok(bound(a).zip(bound(b)))
+        }
+        None => {
+            let Ok(n) = slice.parse::<usize>() else {
+                return Ok(None);
+            };
+            let end = n
+                .checked_add(1)
+                .with_context(|| format!("Message index {} is out of range", n))?;
+            Ok(Some((Some(n), Some(end))))
+        }
+    }
+}
+
+/// Run the print subcommand
+pub fn run_print(selector: &str, theme: &str) -> Result<()> {
+    let selector = Selector::parse(selector)?;
+
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+
+    let file_path = index
+        .get_by_id(&selector.session_id)?
+        .ok_or_else(|| anyhow::anyhow!("Session not found: {}", selector.session_id))?;
+    let session = parser::parse_session_file(&file_path)?;
+
+    let stdout = std::io::stdout();
+    if !stdout.is_terminal() {
+        print!("{}", render(&session, &selector, None));
+        return Ok(());
+    }
+
+    let (width, height) = crossterm::terminal::size().unwrap_or((80, 24));
+    let style = Style {
+        theme: Theme::select(theme)?,
+        width: width as usize,
+    };
+    let transcript = render(&session, &selector, Some(&style));
+
+    if transcript.lines().count() >= height as usize && page(&transcript).is_ok() {
+        return Ok(());
+    }
+    stdout.lock().write_all(transcript.as_bytes())?;
+    Ok(())
+}
+
+/// Pipe text through `$PAGER` (default `less -R`)
+fn page(text: &str) -> Result<()> {
+    let pager = std::env::var("PAGER")
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| "less -R".to_string());
+    let mut parts = pager.split_whitespace();
+    let program = parts.next().context("Empty PAGER")?;
+
+    let mut child = std::process::Command::new(program)
+        .args(parts)
+        .stdin(std::process::Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run pager '{}'", pager))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The user quitting the pager early closes the pipe; that's fine
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    child.wait()?;
+    Ok(())
+}
+
+/// Colors and width for terminal output
+pub struct Style {
+    pub theme: Theme,
+    pub width: usize,
+}
+
+/// Render the selected messages as a transcript. Without a style, output is plain text
+/// with message bodies verbatim; with one, it's wrapped to the terminal width and colored
+/// unless the theme is monochrome.
+pub fn render(session: &Session, selector: &Selector, style: Option<&Style>) -> String {
+    let paint = |color: Color, text: &str| match style {
+        Some(s) if !s.theme.monochrome => format!("{}{}\x1b[0m", ansi_fg(color), text),
+        _ => text.to_string(),
+    };
+    let dim = |text: &str| match style {
+        Some(s) => paint(s.theme.dim_fg, text),
+        None => text.to_string(),
+    };
+
+    let mut out = String::new();
+
+    // Header: source, id, where, when, how to resume
+    let source_name = session.source.display_name();
+    out.push_str(&match style {
+        Some(s) => paint(
+            s.theme.source_color(session.source),
+            &format!("{} {}", s.theme.icon(session.source), source_name),
+        ),
+        None => source_name.to_string(),
+    });
+    out.push_str(&format!(" session {}\n", session.id));
+
+    let mut location = session.local_cwd().into_owned();
+    if let Some(branch) = &session.git_branch {
+        location.push_str(&format!(" ({})", branch));
+    }
+    out.push_str(&dim(&format!(
+        "{} · {}\n",
+        location,
+        session.timestamp.format("%Y-%m-%d %H:%M UTC")
+    )));
+    out.push_str(&dim(&format!("resume: {}\n", session.resume_command_line())));
+
+    let selected = selector.select(session);
+    if selected.is_empty() {
+        out.push_str(&match selector.errors {
+            true => "\n(no tool calls failed in the session)\n".to_string(),
+            false => format!(
+                "\n(no messages in range; the session has {})\n",
+                session.messages.len()
+            ),
+        });
+        return out;
+    }
+
+    for i in selected {
+        let message = &session.messages[i];
+        let label = message.role.label(session.source);
+
+        out.push('\n');
+        let heading = format!("[{}] {}", i, label);
+        out.push_str(&match style {
+            Some(s) if !s.theme.monochrome => {
+                let color = match message.role {
+                    Role::User => s.theme.user_label,
+                    Role::Assistant => s.theme.source_color(session.source),
+                    Role::Context => s.theme.dim_fg,
+                };
+                format!("\x1b[1m{}", paint(color, &heading))
+            }
+            _ => heading,
+        });
+        out.push_str(&dim(&format!(" · {}\n", message.timestamp.format("%Y-%m-%d %H:%M:%S"))));
+
+        match style {
+            Some(s) => {
+                for line in wrap_text(&message.content, s.width.saturating_sub(2).max(20)) {
+                    out.push_str("  ");
+                    out.push_str(&line);
+                    out.push('\n');
+                }
+            }
+            None => {
+                out.push_str(message.content.trim_end());
+                out.push('\n');
+            }
+        }
+
+        for call in &message.tool_calls {
+            out.push_str(&tool_call_lines(call, &dim));
+        }
+
+        // Files touched by the tool calls that followed this message
+        let touches: Vec<String> = session
+            .files_touched
+            .iter()
+            .filter(|t| t.message_index == i)
+            .map(|t| format!("{} {}", t.op.as_str(), t.path))
+            .collect();
+        if !touches.is_empty() {
+            out.push_str(&dim(&format!("  ↳ {}\n", touches.join(", "))));
+        }
+    }
+
+    out
+}
+
+/// A tool call as a line, with the start of its output when it failed
+fn tool_call_lines(call: &ToolCall, dim: &dyn Fn(&str) -> String) -> String {
+    const OUTPUT_LINES: usize = 3;
+
+    let command = call.input.lines().next().unwrap_or_default();
+    let line = format!("{}: {}", call.name, command);
+    match call.status {
+        ToolStatus::Ok => dim(&format!("  ↳ {}\n", line)),
+        ToolStatus::Pending => dim(&format!("  ↳ {} (no result)\n", line)),
+        ToolStatus::Error => {
+            let mut out = match call.exit_code {
+                Some(code) => format!("  ✗ {} (exit {})\n", line, code),
+                None => format!("  ✗ {}\n", line),
+            };
+            let output = call.output.as_deref().unwrap_or_default();
+            for line in output.lines().filter(|l| !l.trim().is_empty()).take(OUTPUT_LINES) {
+                out.push_str(&dim(&format!("    {}\n", line)));
+            }
+            out
+        }
+    }
+}
+
+/// ANSI escape setting the foreground color
+fn ansi_fg(color: Color) -> String {
+    match color {
+        Color::Rgb(r, g, b) => format!("\x1b[38;2;{};{};{}m", r, g, b),
+        Color::Indexed(i) => format!("\x1b[38;5;{}m", i),
+        Color::Black => "\x1b[30m".to_string(),
+        Color::Red => "\x1b[31m".to_string(),
+        Color::Green => "\x1b[32m".to_string(),
+        Color::Yellow => "\x1b[33m".to_string(),
+        Color::Blue => "\x1b[34m".to_string(),
+        Color::Magenta => "\x1b[35m".to_string(),
+        Color::Cyan => "\x1b[36m".to_string(),
+        Color::Gray => "\x1b[37m".to_string(),
+        Color::DarkGray => "\x1b[90m".to_string(),
+        Color::LightRed => "\x1b[91m".to_string(),
+        Color::LightGreen => "\x1b[92m".to_string(),
+        Color::LightYellow => "\x1b[93m".to_string(),
+        Color::LightBlue => "\x1b[94m".to_string(),
+        Color::LightMagenta => "\x1b[95m".to_string(),
+        Color::LightCyan => "\x1b[96m".to_string(),
+        Color::White => "\x1b[97m".to_string(),
+        Color::Reset => "\x1b[39m".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use recall::session::{Message, SessionSource};
+
+    #[test]
+    fn test_selector_parse() {
+        let sel = |start, end| (start, end);
+        let parse = |s: &str| {
+            let p = Selector::parse(s).unwrap();
+            (p.session_id, sel(p.start, p.end))
+        };
+        assert_eq!(parse("abc"), ("abc".to_string(), (None, None)));
+        assert_eq!(parse("abc:3"), ("abc".to_string(), (Some(3), Some(4))));
+        assert_eq!(parse("abc:2..5"), ("abc".to_string(), (Some(2), Some(5))));
+        assert_eq!(parse("abc:2.."), ("abc".to_string(), (Some(2), None)));
+        assert_eq!(parse("abc:..2"), ("abc".to_string(), (None, Some(2))));
+        // Not a slice: part of the ID
+        assert_eq!(parse("ns:abc"), ("ns:abc".to_string(), (None, None)));
+        assert!(Selector::parse(":3").is_err());
+        assert!(Selector::parse(&format!("abc:{}", usize::MAX)).is_err());
+
+        let errors = Selector::parse("abc:errors").unwrap();
+        assert_eq!((errors.session_id.as_str(), errors.errors), ("abc", true));
+        assert!(!Selector::parse("abc:2").unwrap().errors);
+    }
+
+    #[test]
+    fn test_selector_range_clamps() {
+        let sel = Selector::parse("x:2..10").unwrap();
+        assert_eq!(sel.range(5), 2..5);
+        assert_eq!(sel.range(1), 1..1);
+        assert_eq!(Selector::parse("x").unwrap().range(3), 0..3);
+    }
+
+    #[test]
+    fn test_errors_selects_messages_with_failed_tool_calls() {
+        let message = |status| Message {
+            role: Role::Assistant,
+            content: "Running it.".to_string(),
+            timestamp: Utc::now(),
+            tool_calls: vec![ToolCall {
+                name: "shell".to_string(),
+                input: "make".to_string(),
+                output: Some("make: *** No rule to make target".to_string()),
+                exit_code: Some(2),
+                status,
+            }],
+        };
+        let session = Session {
+            id: "s1".to_string(),
+            source: SessionSource::CodexCli,
+            file_path: "/x.jsonl".into(),
+            cwd: "/p".to_string(),
+            git_branch: None,
+            timestamp: Utc::now(),
+            messages: vec![message(ToolStatus::Ok), message(ToolStatus::Error)],
+            files_touched: vec![],
+            usage: vec![],
+            title: None,
+        };
+        let selector = Selector::parse("s1:errors").unwrap();
+        assert_eq!(selector.select(&session), [1]);
+
+        let out = render(&session, &selector, None);
+        assert!(!out.contains("[0]"));
+        assert!(out.contains("[1] Codex"));
+        assert!(out.contains("  ✗ shell: make (exit 2)\n    make: *** No rule to make target\n"));
+    }
+
+    #[test]
+    fn test_render_styled_wraps_and_colors() {
+        let session = Session {
+            id: "s1".to_string(),
+            source: SessionSource::ClaudeCode,
+            file_path: "/x.jsonl".into(),
+            cwd: "/p".to_string(),
+            git_branch: None,
+            timestamp: Utc::now(),
+            messages: vec![Message {
+                role: Role::User,
+                content: "one two three four five six seven eight nine ten".to_string(),
+                timestamp: Utc::now(),
+                tool_calls: Vec::new(),
+            }],
+            files_touched: vec![],
+            usage: vec![],
+            title: None,
+        };
+        let style = Style { theme: Theme::detect(), width: 22 };
+        let out = render(&session, &Selector::parse("s1").unwrap(), Some(&style));
+
+        assert!(out.contains("\x1b["));
+        assert!(out.contains("  one two three four\n  five six seven eight\n  nine ten\n"));
+
+        // NO_COLOR keeps the wrapping but drops every escape
+        let style = Style { theme: Theme::monochrome(), width: 22 };
+        let out = render(&session, &Selector::parse("s1").unwrap(), Some(&style));
+        assert!(!out.contains('\x1b'));
+        assert!(out.starts_with("* Claude session"));
+        assert!(out.contains("  one two three four\n  five six seven eight\n  nine ten\n"));
+    }
+}
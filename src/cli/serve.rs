@@ -0,0 +1,264 @@
+//! `recall serve` - a small local HTTP API over the session index
+
+use super::{list, read, search, ListParams, SearchParams};
+use anyhow::{Context, Result};
+use recall::{
+    index::{ensure_index_fresh, SessionIndex},
+    session::SessionSource,
+};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Minimum time between index freshness checks (each check stats every session file)
+const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+const DEFAULT_RECENT_LIMIT: usize = 20;
+
+/// Run the serve subcommand (blocks until the process is killed)
+pub fn run_serve(addr: &str, allow_origin: Option<String>) -> Result<()> {
+    let addr: SocketAddr = addr
+        .parse()
+        .with_context(|| format!("Invalid address: {}", addr))?;
+
+    // Session history is private - never expose it beyond this machine
+    if !addr.ip().is_loopback() {
+        anyhow::bail!(
+            "Refusing to bind to {}: recall serve only listens on localhost",
+            addr
+        );
+    }
+
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+    let mut last_refresh = Instant::now();
+
+    let server = Server::http(addr).map_err(|e| anyhow::anyhow!("Failed to bind {}: {}", addr, e))?;
+    let bound = server.server_addr().to_ip().unwrap_or(addr);
+    eprintln!("Listening on http://{}", bound);
+
+    for request in server.incoming_requests() {
+        if last_refresh.elapsed() >= REFRESH_INTERVAL {
+            if let Err(e) = ensure_index_fresh(&index) {
+                eprintln!("Index refresh failed: {}", e);
+            }
+            last_refresh = Instant::now();
+        }
+
+        let (status, body) = match handle(&index, &request, bound.port()) {
+            Ok(body) => (200, body),
+            Err(ApiError(status, msg)) => (status, serde_json::json!({ "error": msg }).to_string()),
+        };
+        respond(request, status, body, allow_origin.as_deref());
+    }
+
+    Ok(())
+}
+
+/// An error response: HTTP status code and message
+struct ApiError(u16, String);
+
+impl ApiError {
+    fn bad_request(msg: impl Into<String>) -> Self {
+        Self(400, msg.into())
+    }
+
+    fn internal(err: anyhow::Error) -> Self {
+        Self(500, err.to_string())
+    }
+}
+
+/// Route a request and return the JSON body
+fn handle(index: &SessionIndex, request: &Request, port: u16) -> Result<String, ApiError> {
+    // A web page can point its own hostname at 127.0.0.1 (DNS rebinding); its requests still
+    // name that hostname, so only requests addressed to this machine are answered
+    let host = request.headers().iter().find(|h| h.field.equiv("Host"));
+    if !host.is_some_and(|h| host_allowed(h.value.as_str(), port)) {
+        return Err(ApiError(403, "Host not allowed".to_string()));
+    }
+
+    if *request.method() != Method::Get {
+        return Err(ApiError(405, "Only GET is supported".to_string()));
+    }
+
+    let (path, query) = match request.url().split_once('?') {
+        Some((path, query)) => (path, parse_query(query)),
+        None => (request.url(), HashMap::new()),
+    };
+
+    let json = match path.trim_end_matches('/') {
+        "/search" => {
+            let q = query
+                .get("q")
+                .filter(|q| !q.trim().is_empty())
+                .ok_or_else(|| ApiError::bad_request("Missing query parameter 'q'"))?;
+            let params = SearchParams {
+                query: q.clone(),
+                source: parse_source(&query)?,
+                session_id: None,
+                limit: parse_number(&query, "limit", DEFAULT_SEARCH_LIMIT)?,
+                offset: parse_number(&query, "offset", 0)?,
+                context: 0,
+                since: None,
+                until: None,
+                cwd: None,
+                tags: Vec::new(),
+            };
+            let output = search(index, &params).map_err(ApiError::internal)?;
+            serde_json::to_string(&output)
+        }
+        "/recent" => {
+            let params = ListParams {
+                limit: parse_number(&query, "limit", DEFAULT_RECENT_LIMIT)?,
+                offset: parse_number(&query, "offset", 0)?,
+                source: parse_source(&query)?,
+                since: None,
+                until: None,
+                cwd: None,
+                tags: Vec::new(),
+            };
+            let output = list(index, &params).map_err(ApiError::internal)?;
+            serde_json::to_string(&output)
+        }
+        _ => match path.strip_prefix("/sessions/") {
+            Some(id) if !id.is_empty() => {
+                let id = percent_decode(id);
+                let output = read(index, &id).map_err(|e| ApiError(404, e.to_string()))?;
+                serde_json::to_string(&output)
+            }
+            _ => return Err(ApiError(404, format!("Not found: {}", path))),
+        },
+    };
+
+    json.map_err(|e| ApiError::internal(e.into()))
+}
+
+fn parse_source(query: &HashMap<String, String>) -> Result<Option<SessionSource>, ApiError> {
+    match query.get("source") {
+        Some(s) => SessionSource::parse(s)
+            .map(Some)
+            .ok_or_else(|| ApiError::bad_request(format!("Invalid source '{}'", s))),
+        None => Ok(None),
+    }
+}
+
+fn parse_number(
+    query: &HashMap<String, String>,
+    key: &str,
+    default: usize,
+) -> Result<usize, ApiError> {
+    match query.get(key) {
+        Some(s) => s
+            .parse()
+            .map_err(|_| ApiError::bad_request(format!("Invalid {} '{}'", key, s))),
+        None => Ok(default),
+    }
+}
+
+/// Whether a Host header names this machine's loopback address and the port served on (the
+/// port may be left out only when it's 80)
+fn host_allowed(host: &str, port: u16) -> bool {
+    let (name, host_port) = match host.rsplit_once(':') {
+        Some((name, p)) if !p.contains(']') => (name, p.parse().ok()),
+        _ => (host, Some(80)),
+    };
+    host_port == Some(port)
+        && (name == "127.0.0.1" || name == "[::1]" || name.eq_ignore_ascii_case("localhost"))
+}
+
+fn respond(request: Request, status: u16, body: String, allow_origin: Option<&str>) {
+    let mut response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(header("Content-Type", "application/json"));
+    // CORS is off unless explicitly allowed for a given origin
+    if let Some(origin) = allow_origin {
+        response = response.with_header(header("Access-Control-Allow-Origin", origin));
+    }
+    let _ = request.respond(response);
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("valid header")
+}
+
+/// Parse a URL query string (`a=1&b=two+words`) into a map
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+/// Decode `%XX` escapes and `+` as space (invalid escapes are kept verbatim)
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' => match bytes.get(i + 1..i + 3).and_then(decode_hex_pair) {
+                Some(b) => {
+                    out.push(b);
+                    i += 2;
+                }
+                None => out.push(b'%'),
+            },
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn decode_hex_pair(pair: &[u8]) -> Option<u8> {
+    let hex = std::str::from_utf8(pair).ok()?;
+    u8::from_str_radix(hex, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query() {
+        let q = parse_query("q=hello+world&limit=5&source=claude");
+        assert_eq!(q["q"], "hello world");
+        assert_eq!(q["limit"], "5");
+        assert_eq!(q["source"], "claude");
+    }
+
+    #[test]
+    fn test_parse_query_empty_value() {
+        let q = parse_query("q=&flag");
+        assert_eq!(q["q"], "");
+        assert_eq!(q["flag"], "");
+    }
+
+    #[test]
+    fn test_host_allowed() {
+        assert!(host_allowed("127.0.0.1:7777", 7777));
+        assert!(host_allowed("LOCALHOST:7777", 7777));
+        assert!(host_allowed("[::1]:7777", 7777));
+        assert!(host_allowed("localhost", 80));
+        assert!(!host_allowed("localhost", 7777));
+        assert!(!host_allowed("127.0.0.1:8080", 7777));
+        assert!(!host_allowed("attacker.example:7777", 7777));
+        assert!(!host_allowed("localhost.attacker.example:7777", 7777));
+        assert!(!host_allowed("[::1]", 7777));
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("caf%C3%A9"), "café");
+        assert_eq!(percent_decode("a%2Fb"), "a/b");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+}
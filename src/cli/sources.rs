@@ -0,0 +1,89 @@
+//! `recall sources` - show where recall looks for sessions and what it found
+
+use anyhow::Result;
+use recall::index::{cache, SessionIndex};
+use recall::parser::{self, DiscoveryPass};
+use serde::Serialize;
+use std::path::PathBuf;
+
+#[derive(Debug, Serialize)]
+pub struct SourcesOutput {
+    pub sources: Vec<SourceInfo>,
+    pub index: IndexInfo,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IndexInfo {
+    /// The cache directory, or None when the index is kept in memory (`privacy.ephemeral`)
+    pub dir: Option<PathBuf>,
+    /// Its permission bits on Unix, like "0700"
+    pub mode: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SourceInfo {
+    pub source: &'static str,
+    pub dir: PathBuf,
+    pub exists: bool,
+    /// False when the source is turned off in the config or with --disable-source
+    pub enabled: bool,
+    /// Session files found on disk
+    pub files: usize,
+    /// Sessions from this directory in the index (as of the last index update)
+    pub indexed: usize,
+}
+
+/// Collect discovery roots with their file and index counts
+pub fn sources() -> Result<SourcesOutput> {
+    let index = SessionIndex::open_default()?;
+    let indexed = index.indexed_file_paths()?;
+
+    let pass = DiscoveryPass::default();
+    let sources = parser::discovery_roots()
+        .into_iter()
+        .map(|root| SourceInfo {
+            source: root.name(),
+            exists: root.dir.exists(),
+            enabled: root.enabled(),
+            files: root.discover_files_in(&pass).len(),
+            indexed: indexed.iter().filter(|p| root.contains(p)).count(),
+            dir: root.dir,
+        })
+        .collect();
+
+    let dir = (!cache::ephemeral()).then(cache::dir);
+    let index = IndexInfo { mode: dir.as_deref().and_then(cache::mode), dir };
+
+    Ok(SourcesOutput { sources, index })
+}
+
+/// Run the sources subcommand
+pub fn run_sources(json: bool) -> Result<()> {
+    let output = sources()?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!("{:<10} {:>7} {:>8}  DIRECTORY", "SOURCE", "FILES", "INDEXED");
+    for info in &output.sources {
+        println!(
+            "{:<10} {:>7} {:>8}  {}{}",
+            info.source,
+            info.files,
+            info.indexed,
+            info.dir.display(),
+            if !info.enabled {
+                " (disabled)"
+            } else if !info.exists {
+                " (not found)"
+            } else {
+                ""
+            }
+        );
+    }
+    println!("\nIndex: {}", cache::describe(&cache::dir()));
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+//! `recall summary` - mechanical one-paragraph session summaries
+
+use super::filters;
+use anyhow::Result;
+use recall::{
+    engine::fetch_filtered,
+    index::{ensure_index_fresh, SessionIndex},
+    parser,
+    session::SessionDigest,
+};
+use serde::Serialize;
+
+/// Output format for `recall summary --all`
+#[derive(Debug, Serialize)]
+pub struct DigestOutput {
+    pub sessions: Vec<SessionDigest>,
+}
+
+/// Digest a single session by ID
+pub fn summary(index: &SessionIndex, session_id: &str) -> Result<SessionDigest> {
+    let file_path = index
+        .get_by_id(session_id)?
+        .ok_or_else(|| anyhow::anyhow!("Session not found: {}", session_id))?;
+    Ok(parser::parse_session_file(&file_path)?.digest())
+}
+
+/// Digest recent sessions, most recent first
+pub fn summaries(index: &SessionIndex, since: &Option<String>, limit: usize) -> Result<DigestOutput> {
+    let filters = filters(None, since, &None, &None, &[])?;
+    let results = fetch_filtered(|n| index.recent(n), &filters, limit)?;
+
+    Ok(DigestOutput {
+        sessions: results
+            .iter()
+            .take(limit)
+            .map(|r| {
+                parser::parse_session_file(&r.session.file_path)
+                    .unwrap_or(r.session.clone())
+                    .digest()
+            })
+            .collect(),
+    })
+}
+
+/// Run the summary subcommand
+pub fn run_summary(
+    session_id: Option<&str>,
+    since: &Option<String>,
+    limit: usize,
+    json: bool,
+) -> Result<()> {
+    let index = SessionIndex::open_default()?;
+    ensure_index_fresh(&index)?;
+
+    let digests = match session_id {
+        Some(id) => {
+            let digest = summary(&index, id)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&digest)?);
+                return Ok(());
+            }
+            vec![digest]
+        }
+        None => {
+            let output = summaries(&index, since, limit)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&output)?);
+                return Ok(());
+            }
+            output.sessions
+        }
+    };
+
+    let ascii = recall::theme::no_color();
+    for (i, digest) in digests.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!(
+            "{} {} · {} · {}",
+            if ascii { digest.source.ascii_icon() } else { digest.source.icon() },
+            digest.source.display_name(),
+            digest.project,
+            digest.session_id
+        );
+        println!("{}", digest.paragraph());
+    }
+    Ok(())
+}
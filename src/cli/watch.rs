@@ -0,0 +1,218 @@
+//! `recall watch` - stream session changes as NDJSON
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use recall::{
+    index::IndexState,
+    parser,
+    session::{Message, Session, SessionSource},
+};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum EventKind {
+    #[serde(rename = "session_created")]
+    Created,
+    #[serde(rename = "session_updated")]
+    Updated,
+    #[serde(rename = "session_deleted")]
+    Deleted,
+}
+
+/// One line of `recall watch` output
+#[derive(Debug, Serialize)]
+pub struct WatchEvent {
+    pub event: EventKind,
+    pub session_id: String,
+    pub source: SessionSource,
+    pub cwd: String,
+    pub file_path: PathBuf,
+    /// Latest message time (absent for deletions)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_count: Option<usize>,
+    /// Index of the first message in `messages`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_message_index: Option<usize>,
+    /// Messages added since the previous event (with --include-messages)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub messages: Option<Vec<Message>>,
+}
+
+/// What we remember about a session between scans
+struct Seen {
+    session_id: String,
+    source: SessionSource,
+    cwd: String,
+    message_count: usize,
+    /// Length of the last message, which grows when new output is joined onto it
+    last_message_len: usize,
+}
+
+/// Polls the discovery roots and turns file changes into events
+pub struct Watcher {
+    files: IndexState,
+    seen: HashMap<PathBuf, Seen>,
+    source: Option<SessionSource>,
+    cwd: Option<String>,
+    include_messages: bool,
+    /// False during the first scan, which only takes stock
+    primed: bool,
+}
+
+impl Watcher {
+    /// Start watching, treating every session that exists now as already seen
+    pub fn new(source: Option<SessionSource>, cwd: Option<String>, include_messages: bool) -> Self {
+        let mut watcher = Self {
+            files: IndexState::default(),
+            seen: HashMap::new(),
+            source,
+            cwd,
+            include_messages,
+            primed: false,
+        };
+        watcher.scan();
+        watcher.primed = true;
+        watcher
+    }
+
+    /// Number of sessions currently known
+    pub fn session_count(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Look for created, changed, and removed session files since the last scan
+    pub fn scan(&mut self) -> Vec<WatchEvent> {
+        let mut events = Vec::new();
+        let files: HashSet<PathBuf> = parser::discover_session_files().into_iter().collect();
+
+        for path in &files {
+            if !self.files.needs_reindex(path) {
+                continue;
+            }
+            self.files.mark_indexed(path);
+            let Ok(session) = parser::parse_session_file(path) else {
+                continue;
+            };
+            if parser::is_excluded_by_project(&session.cwd) {
+                continue;
+            }
+            if let Some(event) = self.observe(path, session) {
+                events.push(event);
+            }
+        }
+
+        let removed: Vec<PathBuf> = self
+            .seen
+            .keys()
+            .filter(|path| !files.contains(*path))
+            .cloned()
+            .collect();
+        for path in removed {
+            self.files.remove(&path);
+            if let Some(seen) = self.seen.remove(&path) {
+                if self.matches(seen.source, &seen.cwd) {
+                    events.push(WatchEvent {
+                        event: EventKind::Deleted,
+                        session_id: seen.session_id,
+                        source: seen.source,
+                        cwd: seen.cwd,
+                        file_path: path,
+                        timestamp: None,
+                        message_count: None,
+                        first_message_index: None,
+                        messages: None,
+                    });
+                }
+            }
+        }
+
+        events
+    }
+
+    fn matches(&self, source: SessionSource, cwd: &str) -> bool {
+        self.source.is_none_or(|s| s == source) && self.cwd.as_deref().is_none_or(|c| c == cwd)
+    }
+
+    /// Record a parsed session; returns an event if it's new or gained messages
+    fn observe(&mut self, path: &Path, session: Session) -> Option<WatchEvent> {
+        let last_message_len = session.messages.last().map_or(0, |m| m.content.len());
+        let previous = self.seen.insert(
+            path.to_path_buf(),
+            Seen {
+                session_id: session.id.clone(),
+                source: session.source,
+                cwd: session.cwd.clone(),
+                message_count: session.messages.len(),
+                last_message_len,
+            },
+        );
+
+        if !self.primed {
+            return None;
+        }
+
+        let (kind, first_new) = match previous {
+            None => (EventKind::Created, 0),
+            Some(prev) => {
+                // A message that kept growing is sent again in full
+                let grew = prev.message_count > 0
+                    && session.messages.get(prev.message_count - 1).map(|m| m.content.len())
+                        != Some(prev.last_message_len);
+                let first_new = if grew { prev.message_count - 1 } else { prev.message_count };
+                if first_new >= session.messages.len() {
+                    return None;
+                }
+                (EventKind::Updated, first_new)
+            }
+        };
+
+        if !self.matches(session.source, &session.cwd) {
+            return None;
+        }
+
+        Some(WatchEvent {
+            event: kind,
+            message_count: Some(session.messages.len()),
+            first_message_index: self.include_messages.then_some(first_new),
+            messages: self
+                .include_messages
+                .then(|| session.messages[first_new..].to_vec()),
+            session_id: session.id,
+            source: session.source,
+            cwd: session.cwd,
+            file_path: path.to_path_buf(),
+            timestamp: Some(session.timestamp),
+        })
+    }
+}
+
+/// Run the watch subcommand: print one JSON line per event until interrupted
+pub fn run_watch(
+    source: Option<SessionSource>,
+    cwd: Option<String>,
+    include_messages: bool,
+    interval: Duration,
+) -> Result<()> {
+    let mut watcher = Watcher::new(source, cwd, include_messages);
+    eprintln!("Watching {} sessions (Ctrl+C to stop)", watcher.session_count());
+
+    let stdout = std::io::stdout();
+    loop {
+        std::thread::sleep(interval);
+        let events = watcher.scan();
+        if events.is_empty() {
+            continue;
+        }
+        let mut out = stdout.lock();
+        for event in events {
+            writeln!(out, "{}", serde_json::to_string(&event)?)?;
+        }
+        out.flush()?;
+    }
+}
@@ -0,0 +1,1164 @@
+//! User configuration from `~/.config/recall/config.toml`
+//!
+//! Every key is optional and defaults to recall's built-in behavior, so a missing file
+//! and an empty file mean the same thing. A `.recall.toml` in the launch directory or one
+//! of its parents overrides the global file for that invocation.
+
+use crate::pricing::Price;
+use crate::session::SessionSource;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Color theme: "auto" (follow the terminal) or a preset name from `recall themes`
+    pub theme: String,
+    /// Where the TUI starts searching; `--everywhere` and `--cwd` override it
+    pub default_scope: DefaultScope,
+    /// When consecutive messages from the same role are shown as one. Indexed sessions keep
+    /// the old joining until `recall --reindex`.
+    pub join_messages: MessageJoin,
+    /// Keep the assistants' thinking (Claude's thinking blocks, Codex's reasoning summaries) in
+    /// messages, marked `[thinking]`, so it's shown and searched (or `--include-thinking`).
+    /// Indexed sessions keep the old content until `recall --reindex`.
+    pub include_thinking: bool,
+    /// Index the transcripts of Claude's subagents (`agent-*.jsonl`), which can't be resumed
+    /// themselves (or `--include-subagents`). `RECALL_INCLUDE_SUBAGENTS` (1 or 0) overrides it.
+    pub include_subagents: bool,
+    /// strftime format for dates in the TUI, in local time; unset shows "Jan 12", with the
+    /// year added when it isn't the current one
+    pub date_format: Option<String>,
+    /// Interactive search
+    pub search: SearchConfig,
+    /// Message bubbles in the TUI preview
+    pub preview: PreviewConfig,
+    /// Defaults for the JSON subcommands
+    pub cli: CliConfig,
+    /// Resume command per source
+    pub resume: ResumeConfig,
+    /// How the TUI opens a session's folder
+    pub open: OpenConfig,
+    /// Which sources are discovered, indexed, and shown
+    pub sources: SourcesConfig,
+    /// Session formats described here rather than built in (`[[custom_source]]`)
+    #[serde(rename = "custom_source")]
+    pub custom_sources: Vec<CustomSource>,
+    /// Where folders recorded somewhere else (a devcontainer, a remote machine) are on this one
+    pub path_map: PathMap,
+    /// Machines whose sessions are mirrored over SSH and searched with these (`[[remote]]`)
+    #[serde(rename = "remote")]
+    pub remotes: Vec<RemoteConfig>,
+    /// More home directories to scan for sessions, laid out like the real one
+    /// (`.claude/projects`, `.codex/sessions`, …)
+    pub extra_homes: Vec<PathBuf>,
+    /// Folders with an Aider chat history, besides those other agents' sessions ran in
+    pub aider_projects: Vec<PathBuf>,
+    /// Project folders to look in for histories kept in the project (Aider's, Crush's),
+    /// besides those other agents' sessions ran in. `RECALL_EXTRA_ROOTS` adds more.
+    pub extra_roots: Vec<PathBuf>,
+    /// Glob patterns for sessions never to index, matched against the session file's path
+    /// and the session's working directory
+    pub exclude: Vec<String>,
+    /// Mask API keys, tokens, and passwords in indexed content and CLI output. Session files
+    /// are left as they are.
+    pub redact: bool,
+    /// More regexes to redact when `redact` is on; a `secret` group limits the mask to itself
+    pub redact_patterns: Vec<String>,
+    /// Keep only sessions active within this long in the index, like "180d" or "6 months"
+    /// (`--max-age` overrides it, `--no-retention` searches everything)
+    pub retention: Option<String>,
+    /// Language whose word forms match each other in search ("parsers" finds "parser"), or
+    /// "off". Changing it rebuilds the index.
+    pub stemming: String,
+    /// What's indexed, and the memory used while indexing
+    pub index: IndexConfig,
+    /// Model prices for `recall cost`, by model-name prefix like the built-in ones, which
+    /// they're checked before
+    pub pricing: BTreeMap<String, Price>,
+    /// Where the index is kept
+    pub privacy: PrivacyConfig,
+    /// Draw the TUI as plain labeled text for screen readers (or `--accessible`)
+    pub accessible: bool,
+    /// Set by `--no-color`; not read from the file
+    #[serde(skip)]
+    pub no_color: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: "auto".to_string(),
+            default_scope: DefaultScope::default(),
+            join_messages: MessageJoin::default(),
+            include_thinking: false,
+            include_subagents: false,
+            date_format: None,
+            search: SearchConfig::default(),
+            preview: PreviewConfig::default(),
+            cli: CliConfig::default(),
+            resume: ResumeConfig::default(),
+            open: OpenConfig::default(),
+            sources: SourcesConfig::default(),
+            custom_sources: Vec::new(),
+            path_map: PathMap::default(),
+            remotes: Vec::new(),
+            extra_homes: Vec::new(),
+            aider_projects: Vec::new(),
+            extra_roots: Vec::new(),
+            exclude: Vec::new(),
+            redact: false,
+            redact_patterns: Vec::new(),
+            retention: None,
+            stemming: "english".to_string(),
+            index: IndexConfig::default(),
+            pricing: BTreeMap::new(),
+            privacy: PrivacyConfig::default(),
+            accessible: false,
+            no_color: false,
+        }
+    }
+}
+
+impl Config {
+    /// `extra_homes` with a leading `~` expanded
+    pub fn extra_homes(&self) -> Vec<PathBuf> {
+        self.extra_homes.iter().map(|dir| expand_tilde(dir)).collect()
+    }
+
+    /// `aider_projects` with a leading `~` expanded
+    pub fn aider_projects(&self) -> BTreeSet<PathBuf> {
+        self.aider_projects.iter().map(|dir| expand_tilde(dir)).collect()
+    }
+
+    /// `extra_roots` with a leading `~` expanded, and those in `RECALL_EXTRA_ROOTS` (a list
+    /// like `PATH`)
+    pub fn extra_roots(&self) -> BTreeSet<PathBuf> {
+        let from_env = std::env::var_os("RECALL_EXTRA_ROOTS")
+            .map(|roots| std::env::split_paths(&roots).collect::<Vec<_>>())
+            .unwrap_or_default();
+        self.extra_roots
+            .iter()
+            .chain(&from_env)
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(|dir| expand_tilde(dir))
+            .collect()
+    }
+
+    /// The `[[custom_source]]` named `name`
+    pub fn custom_source(&self, name: &str) -> Option<&CustomSource> {
+        self.custom_sources.iter().find(|c| c.name == name)
+    }
+
+    /// The oldest session time kept in the index under `retention`, in Unix seconds
+    pub fn retention_cutoff(&self) -> Option<i64> {
+        let age = self.retention.as_deref()?;
+        crate::time::parse_time(age).ok().map(|cutoff| cutoff.timestamp())
+    }
+}
+
+/// `path` with a leading `~` replaced by the home directory
+fn expand_tilde(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Folder prefix rewrites, like `"/workspaces/recall" = "~/code/recall"`. Sessions keep the
+/// folder they recorded; the rewritten one is used to scope, show, and open them.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct PathMap(BTreeMap<String, String>);
+
+impl PathMap {
+    /// `path` with the longest matching prefix rewritten. Prefixes match whole components, so
+    /// `/workspaces/app` leaves `/workspaces/app2` alone.
+    pub fn apply<'a>(&self, path: &'a str) -> Cow<'a, str> {
+        let longest = self
+            .0
+            .iter()
+            .map(|(from, to)| (from.trim_end_matches('/'), to))
+            .filter(|(from, _)| {
+                path.strip_prefix(from).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+            .max_by_key(|(from, _)| from.len());
+        match longest {
+            Some((from, to)) => {
+                let to = expand_tilde(Path::new(to));
+                let to = to.to_string_lossy();
+                Cow::Owned(format!("{}{}", to.trim_end_matches('/'), &path[from.len()..]))
+            }
+            None => Cow::Borrowed(path),
+        }
+    }
+}
+
+/// A session directory on another machine, reached with `ssh <host>`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RemoteConfig {
+    /// Host as ssh takes it: an alias from `~/.ssh/config`, or `user@host`
+    pub host: String,
+    /// Directory on the remote, relative to its home (a leading `~/` is allowed)
+    #[serde(default = "default_remote_path")]
+    pub path: String,
+}
+
+fn default_remote_path() -> String {
+    "~/.claude/projects".to_string()
+}
+
+impl RemoteConfig {
+    /// `path` relative to the remote home, without a trailing `/`
+    pub fn dir(&self) -> &str {
+        let path = self.path.strip_prefix("~/").unwrap_or(&self.path);
+        path.trim_end_matches('/')
+    }
+
+    /// Reject hosts that ssh would read as an option or that can't name a directory, and
+    /// paths outside the remote home
+    fn validate(&self) -> Result<()> {
+        let allowed = |c: char| c.is_ascii_alphanumeric() || "-_.@".contains(c);
+        if self.host.is_empty() || self.host.starts_with('-') || !self.host.chars().all(allowed) {
+            anyhow::bail!("Invalid remote host '{}'", self.host);
+        }
+        let inside_home = Path::new(self.dir())
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_)));
+        if self.dir().is_empty() || !inside_home {
+            anyhow::bail!(
+                "Invalid remote path '{}' for {}: use a directory under the remote's home",
+                self.path,
+                self.host
+            );
+        }
+        Ok(())
+    }
+}
+
+/// The TUI's starting scope, and the one `/` toggles back to from everywhere
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DefaultScope {
+    /// Sessions from all folders
+    Everywhere,
+    /// Sessions started in the launch folder
+    #[default]
+    Folder,
+    /// Sessions started anywhere in the launch folder's git repository
+    Repo,
+}
+
+/// Whether consecutive same-role messages are merged into one: "off", "always", or
+/// "gap:<minutes>" to merge only messages sent within that many minutes of each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum MessageJoin {
+    Off,
+    Always,
+    Gap(u32),
+}
+
+impl Default for MessageJoin {
+    fn default() -> Self {
+        MessageJoin::Gap(15)
+    }
+}
+
+impl TryFrom<String> for MessageJoin {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, String> {
+        match value.as_str() {
+            "off" => Ok(MessageJoin::Off),
+            "always" => Ok(MessageJoin::Always),
+            _ => value
+                .strip_prefix("gap:")
+                .and_then(|minutes| minutes.trim().parse().ok())
+                .map(MessageJoin::Gap)
+                .ok_or_else(|| {
+                    format!("invalid join_messages '{}', expected off, always, or gap:<minutes>", value)
+                }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SearchConfig {
+    /// Wait this long after the last keystroke before searching
+    pub debounce_ms: u64,
+    /// Maximum sessions listed in the TUI
+    pub max_results: usize,
+    /// Length of the matched-text snippet shown for each result
+    pub snippet_chars: usize,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            debounce_ms: 50,
+            max_results: 50,
+            snippet_chars: 200,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PreviewConfig {
+    /// Collapse messages longer than this many lines (0 never collapses)
+    pub collapse_lines: usize,
+    /// Lines kept from the start of a collapsed message
+    pub head_lines: usize,
+    /// Lines kept from the end of a collapsed message
+    pub tail_lines: usize,
+}
+
+impl Default for PreviewConfig {
+    fn default() -> Self {
+        Self {
+            collapse_lines: 12,
+            head_lines: 6,
+            tail_lines: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CliConfig {
+    /// Default `--limit` for `recall search`
+    pub search_limit: usize,
+    /// Default `--limit` for `recall list`
+    pub list_limit: usize,
+}
+
+impl Default for CliConfig {
+    fn default() -> Self {
+        Self {
+            search_limit: 10,
+            list_limit: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SourcesConfig {
+    pub claude: bool,
+    pub codex: bool,
+    pub factory: bool,
+    pub opencode: bool,
+    pub aider: bool,
+    pub gemini: bool,
+    pub goose: bool,
+    pub cline: bool,
+    pub copilot: bool,
+    pub amp: bool,
+    pub cursor: bool,
+    pub r#continue: bool,
+    pub openhands: bool,
+    pub amazonq: bool,
+    pub crush: bool,
+    pub chatgpt: bool,
+    pub llm: bool,
+    pub zed: bool,
+    pub windsurf: bool,
+    pub claudedesktop: bool,
+    pub plandex: bool,
+    pub roocode: bool,
+    pub warp: bool,
+    /// Custom sources turned off with `--disable-source`
+    #[serde(skip)]
+    pub disabled_custom: Vec<String>,
+}
+
+impl Default for SourcesConfig {
+    fn default() -> Self {
+        Self {
+            claude: true,
+            codex: true,
+            factory: true,
+            opencode: true,
+            aider: true,
+            gemini: true,
+            goose: true,
+            cline: true,
+            copilot: true,
+            amp: true,
+            cursor: true,
+            r#continue: true,
+            openhands: true,
+            amazonq: true,
+            crush: true,
+            chatgpt: true,
+            llm: true,
+            zed: true,
+            windsurf: true,
+            claudedesktop: true,
+            plandex: true,
+            roocode: true,
+            warp: true,
+            disabled_custom: Vec::new(),
+        }
+    }
+}
+
+impl SourcesConfig {
+    pub fn enabled(&self, source: SessionSource) -> bool {
+        match source {
+            SessionSource::ClaudeCode => self.claude,
+            SessionSource::CodexCli => self.codex,
+            SessionSource::Factory => self.factory,
+            SessionSource::OpenCode => self.opencode,
+            SessionSource::Aider => self.aider,
+            SessionSource::Gemini => self.gemini,
+            SessionSource::Goose => self.goose,
+            SessionSource::Cline => self.cline,
+            SessionSource::Copilot => self.copilot,
+            SessionSource::Amp => self.amp,
+            SessionSource::Cursor => self.cursor,
+            SessionSource::Continue => self.r#continue,
+            SessionSource::OpenHands => self.openhands,
+            SessionSource::AmazonQ => self.amazonq,
+            SessionSource::Crush => self.crush,
+            SessionSource::ChatGpt => self.chatgpt,
+            SessionSource::Llm => self.llm,
+            SessionSource::Zed => self.zed,
+            SessionSource::Windsurf => self.windsurf,
+            SessionSource::ClaudeDesktop => self.claudedesktop,
+            SessionSource::Plandex => self.plandex,
+            SessionSource::RooCode => self.roocode,
+            SessionSource::Warp => self.warp,
+            SessionSource::Custom(name) => !self.disabled_custom.iter().any(|n| n == name),
+        }
+    }
+
+    pub fn disable(&mut self, source: SessionSource) {
+        let flag = match source {
+            SessionSource::ClaudeCode => &mut self.claude,
+            SessionSource::CodexCli => &mut self.codex,
+            SessionSource::Factory => &mut self.factory,
+            SessionSource::OpenCode => &mut self.opencode,
+            SessionSource::Aider => &mut self.aider,
+            SessionSource::Gemini => &mut self.gemini,
+            SessionSource::Goose => &mut self.goose,
+            SessionSource::Cline => &mut self.cline,
+            SessionSource::Copilot => &mut self.copilot,
+            SessionSource::Amp => &mut self.amp,
+            SessionSource::Cursor => &mut self.cursor,
+            SessionSource::Continue => &mut self.r#continue,
+            SessionSource::OpenHands => &mut self.openhands,
+            SessionSource::AmazonQ => &mut self.amazonq,
+            SessionSource::Crush => &mut self.crush,
+            SessionSource::ChatGpt => &mut self.chatgpt,
+            SessionSource::Llm => &mut self.llm,
+            SessionSource::Zed => &mut self.zed,
+            SessionSource::Windsurf => &mut self.windsurf,
+            SessionSource::ClaudeDesktop => &mut self.claudedesktop,
+            SessionSource::Plandex => &mut self.plandex,
+            SessionSource::RooCode => &mut self.roocode,
+            SessionSource::Warp => &mut self.warp,
+            SessionSource::Custom(name) => {
+                self.disabled_custom.push(name.to_string());
+                return;
+            }
+        };
+        *flag = false;
+    }
+
+    /// Sources turned off in the config or on the command line
+    pub fn disabled(&self) -> Vec<SessionSource> {
+        SessionSource::ALL
+            .into_iter()
+            .filter(|s| !self.enabled(*s))
+            .chain(self.disabled_custom.iter().map(|name| SessionSource::custom(name)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct IndexConfig {
+    /// Buffer for the index writer, in MB (at least 15); more means fewer, larger segments
+    pub writer_heap_mb: usize,
+    /// While indexing, commit (making new sessions searchable) at least this often
+    pub commit_interval_ms: u64,
+    /// ...or once this much message content, in MB, has been indexed since the last commit
+    pub commit_mb: usize,
+    /// Index with as little memory as possible (`--low-memory`): one writer thread with the
+    /// smallest buffer, more frequent commits, and long messages indexed only in part
+    pub low_memory: bool,
+    /// Search tool calls' commands and output along with the message they followed. Indexed
+    /// sessions keep the old content until `recall --reindex`.
+    pub tool_output: bool,
+    /// Search what a compacted conversation was summed up as, which is otherwise only shown,
+    /// collapsed. Indexed sessions keep the old content until `recall --reindex`.
+    pub compacted_context: bool,
+}
+
+impl Default for IndexConfig {
+    fn default() -> Self {
+        Self {
+            writer_heap_mb: 50,
+            commit_interval_ms: 2000,
+            commit_mb: 32,
+            low_memory: false,
+            tool_output: false,
+            compacted_context: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PrivacyConfig {
+    /// Build the index in memory for each run instead of caching it on disk (`--no-persist`).
+    /// Every launch reindexes everything.
+    pub ephemeral: bool,
+}
+
+/// Resume command templates, keyed by source name. `{id}` and `{cwd}` are replaced in each
+/// argument; unset sources use the built-in command.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ResumeConfig {
+    pub claude: Option<CommandTemplate>,
+    pub codex: Option<CommandTemplate>,
+    pub factory: Option<CommandTemplate>,
+    pub opencode: Option<CommandTemplate>,
+    pub aider: Option<CommandTemplate>,
+    pub gemini: Option<CommandTemplate>,
+    pub goose: Option<CommandTemplate>,
+    pub cline: Option<CommandTemplate>,
+    pub copilot: Option<CommandTemplate>,
+    pub amp: Option<CommandTemplate>,
+    pub cursor: Option<CommandTemplate>,
+    pub r#continue: Option<CommandTemplate>,
+    pub openhands: Option<CommandTemplate>,
+    pub amazonq: Option<CommandTemplate>,
+    pub crush: Option<CommandTemplate>,
+    pub chatgpt: Option<CommandTemplate>,
+    pub llm: Option<CommandTemplate>,
+    pub zed: Option<CommandTemplate>,
+    pub windsurf: Option<CommandTemplate>,
+    pub claudedesktop: Option<CommandTemplate>,
+    pub plandex: Option<CommandTemplate>,
+    pub roocode: Option<CommandTemplate>,
+    pub warp: Option<CommandTemplate>,
+}
+
+impl ResumeConfig {
+    pub fn template(&self, source: SessionSource) -> Option<&CommandTemplate> {
+        match source {
+            SessionSource::ClaudeCode => self.claude.as_ref(),
+            SessionSource::CodexCli => self.codex.as_ref(),
+            SessionSource::Factory => self.factory.as_ref(),
+            SessionSource::OpenCode => self.opencode.as_ref(),
+            SessionSource::Aider => self.aider.as_ref(),
+            SessionSource::Gemini => self.gemini.as_ref(),
+            SessionSource::Goose => self.goose.as_ref(),
+            SessionSource::Cline => self.cline.as_ref(),
+            SessionSource::Copilot => self.copilot.as_ref(),
+            SessionSource::Amp => self.amp.as_ref(),
+            SessionSource::Cursor => self.cursor.as_ref(),
+            SessionSource::Continue => self.r#continue.as_ref(),
+            SessionSource::OpenHands => self.openhands.as_ref(),
+            SessionSource::AmazonQ => self.amazonq.as_ref(),
+            SessionSource::Crush => self.crush.as_ref(),
+            SessionSource::ChatGpt => self.chatgpt.as_ref(),
+            SessionSource::Llm => self.llm.as_ref(),
+            SessionSource::Zed => self.zed.as_ref(),
+            SessionSource::Windsurf => self.windsurf.as_ref(),
+            SessionSource::ClaudeDesktop => self.claudedesktop.as_ref(),
+            SessionSource::Plandex => self.plandex.as_ref(),
+            SessionSource::RooCode => self.roocode.as_ref(),
+            SessionSource::Warp => self.warp.as_ref(),
+            // Set in the source's own `[[custom_source]]`
+            SessionSource::Custom(_) => None,
+        }
+    }
+}
+
+/// A session format recall doesn't know, described by where its files are and where each
+/// record keeps its fields (JSON pointers like `/message/role`)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CustomSource {
+    /// Name for `--source`, the index, and the TUI: lowercase letters, digits, `-` and `_`
+    pub name: String,
+    /// Glob for the session files, relative to the home directory unless it's absolute
+    /// (a leading `~/` is the home directory too)
+    pub glob: String,
+    /// One JSON record per line, or one JSON document holding an array of them
+    #[serde(default)]
+    pub format: RecordFormat,
+    /// With `format = "json"`, where the array of records is (the document itself if empty)
+    #[serde(default)]
+    pub records: String,
+    /// The record's role, matched against `user_roles` and `assistant_roles`; records with
+    /// other roles are skipped
+    pub role: String,
+    /// The message text: a string, or an array of strings or `{"text": ...}` parts
+    pub content: String,
+    /// RFC 3339 time or Unix seconds (milliseconds if that's too big to be seconds)
+    pub timestamp: Option<String>,
+    /// The session ID, from the first record that has one (the file name if none do)
+    pub session_id: Option<String>,
+    /// The working directory, from the first record that has one
+    pub cwd: Option<String>,
+    #[serde(default = "default_user_roles")]
+    pub user_roles: Vec<String>,
+    #[serde(default = "default_assistant_roles")]
+    pub assistant_roles: Vec<String>,
+    /// Resume command template (`{id}` and `{cwd}` are replaced); unset runs `<name> <id>`
+    pub resume: Option<CommandTemplate>,
+}
+
+fn default_user_roles() -> Vec<String> {
+    vec!["user".to_string(), "human".to_string()]
+}
+
+fn default_assistant_roles() -> Vec<String> {
+    ["assistant", "ai", "model", "bot"].iter().map(|r| r.to_string()).collect()
+}
+
+/// How a custom source's file holds its records
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordFormat {
+    #[default]
+    Jsonl,
+    Json,
+}
+
+impl CustomSource {
+    /// Reject names that clash with a built-in source or can't be typed in a query, bad
+    /// globs, and pointers that don't start with `/`
+    fn validate(&self) -> Result<()> {
+        let allowed = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || "-_".contains(c);
+        let name_ok = !self.name.is_empty() && self.name.chars().all(allowed);
+        if !name_ok {
+            anyhow::bail!(
+                "Invalid custom_source name '{}': use lowercase letters, digits, '-' and '_'",
+                self.name
+            );
+        }
+        if SessionSource::ALL.iter().any(|s| s.as_str() == self.name) || self.name == "imported" {
+            anyhow::bail!("custom_source name '{}' is taken by a built-in source", self.name);
+        }
+        globset::Glob::new(&self.glob).with_context(|| {
+            format!("Invalid glob '{}' in custom_source {}", self.glob, self.name)
+        })?;
+        let pointers = [Some(&self.role), Some(&self.content)]
+            .into_iter()
+            .chain([self.timestamp.as_ref(), self.session_id.as_ref(), self.cwd.as_ref()])
+            .flatten()
+            .chain((!self.records.is_empty()).then_some(&self.records));
+        for pointer in pointers {
+            if !pointer.starts_with('/') {
+                anyhow::bail!(
+                    "Invalid pointer '{}' in custom_source {}: JSON pointers start with '/'",
+                    pointer,
+                    self.name
+                );
+            }
+        }
+        if let Some(template) = &self.resume {
+            template
+                .expand("", "")
+                .with_context(|| format!("Invalid resume in custom_source {}", self.name))?;
+        }
+        Ok(())
+    }
+}
+
+/// The command Ctrl+O opens a session's folder with
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct OpenConfig {
+    /// Command template (`{cwd}` is replaced); unset uses the platform's opener
+    pub command: Option<CommandTemplate>,
+    /// The command runs in this terminal, like a shell: the TUI steps aside until it exits
+    pub terminal: bool,
+}
+
+/// A command as an argument list, or as one string split with shell quoting rules
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum CommandTemplate {
+    Args(Vec<String>),
+    Line(String),
+}
+
+impl CommandTemplate {
+    /// The template's arguments, with `{id}` and `{cwd}` filled in
+    pub fn expand(&self, id: &str, cwd: &str) -> Result<Vec<String>> {
+        let args = match self {
+            CommandTemplate::Args(args) => args.clone(),
+            CommandTemplate::Line(line) => shell_words::split(line)
+                .with_context(|| format!("Can't parse command `{}`", line))?,
+        };
+        Ok(args
+            .iter()
+            .map(|arg| arg.replace("{id}", id).replace("{cwd}", cwd))
+            .collect())
+    }
+}
+
+/// Keys a `.recall.toml` can't override for the whole invocation, because they change what
+/// goes into the shared index. A project's `exclude` applies to that project's sessions instead.
+const SHARED_INDEX_KEYS: &[&str] = &[
+    "aider_projects",
+    "custom_source",
+    "exclude",
+    "extra_homes",
+    "extra_roots",
+    "redact",
+    "redact_patterns",
+    "remote",
+    "retention",
+    "stemming",
+];
+
+impl Config {
+    /// Load the config file if there is one, with the launch directory's `.recall.toml` on top.
+    /// Unknown keys are reported on stderr.
+    pub fn load() -> Result<Self> {
+        let mut table = match config_path() {
+            Some(path) if path.exists() || std::env::var_os("RECALL_CONFIG").is_some() => {
+                read_table(&path)?
+            }
+            _ => toml::Table::new(),
+        };
+        if let Some(path) = launch_dir().as_deref().and_then(project_config_path) {
+            let mut project = read_table(&path)?;
+            for key in SHARED_INDEX_KEYS {
+                project.remove(*key);
+            }
+            merge(&mut table, project);
+        }
+        let (mut config, _) = Self::parse(&table.to_string())?;
+        if let Some(on) = env_flag("RECALL_INCLUDE_SUBAGENTS") {
+            config.include_subagents = on;
+        }
+        Ok(config)
+    }
+
+    /// Load a specific config file
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config {}", path.display()))?;
+        let (config, unknown) =
+            Self::parse(&text).with_context(|| format!("Invalid config {}", path.display()))?;
+        for key in unknown {
+            eprintln!("Ignoring unknown config key '{}' in {}", key, path.display());
+        }
+        Ok(config)
+    }
+
+    /// Parse config text, returning the config and the keys that were not recognized
+    pub fn parse(text: &str) -> Result<(Self, Vec<String>)> {
+        let mut unknown = Vec::new();
+        let deserializer = toml::Deserializer::new(text);
+        let config: Self =
+            serde_ignored::deserialize(deserializer, |path| unknown.push(path.to_string()))?;
+
+        if !crate::theme::is_known(&config.theme) {
+            anyhow::bail!(
+                "Unknown theme '{}'. Valid: auto, {}",
+                config.theme,
+                crate::theme::preset_names().join(", ")
+            );
+        }
+        if let Some(format) = &config.date_format {
+            crate::time::validate_format(format)?;
+        }
+        for pattern in &config.exclude {
+            globset::Glob::new(pattern)
+                .with_context(|| format!("Invalid exclude pattern '{}'", pattern))?;
+        }
+        crate::redact::Redactor::new(&config.redact_patterns)?;
+        if config.index.writer_heap_mb < 15 {
+            anyhow::bail!(
+                "index.writer_heap_mb must be at least 15, got {}",
+                config.index.writer_heap_mb
+            );
+        }
+        if let Some(age) = &config.retention {
+            crate::time::parse_time(age).with_context(|| format!("Invalid retention '{}'", age))?;
+        }
+        crate::index::stem_language(&config.stemming)?;
+        for source in SessionSource::ALL {
+            if let Some(template) = config.resume.template(source) {
+                template
+                    .expand("", "")
+                    .with_context(|| format!("Invalid resume.{}", source.as_str()))?;
+            }
+        }
+        if let Some(template) = &config.open.command {
+            template.expand("", "").context("Invalid open.command")?;
+        }
+        for (i, custom) in config.custom_sources.iter().enumerate() {
+            custom.validate()?;
+            if config.custom_sources[..i].iter().any(|c| c.name == custom.name) {
+                anyhow::bail!("Duplicate custom_source name '{}'", custom.name);
+            }
+        }
+        for remote in &config.remotes {
+            remote.validate()?;
+        }
+        Ok((config, unknown))
+    }
+}
+
+/// Validate a config file (reporting unknown keys) and return its raw table
+fn read_table(path: &Path) -> Result<toml::Table> {
+    Config::load_from(path)?;
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config {}", path.display()))?;
+    text.parse()
+        .with_context(|| format!("Invalid config {}", path.display()))
+}
+
+/// Overlay `top` onto `base`, merging tables key by key
+fn merge(base: &mut toml::Table, top: toml::Table) {
+    for (key, value) in top {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(top)) => merge(base, top),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// A yes/no environment variable: `1`, `true`, or `yes` (in any case) for yes, anything else
+/// for no, and `None` when it's unset
+fn env_flag(name: &str) -> Option<bool> {
+    let value = std::env::var(name).ok()?;
+    Some(["1", "true", "yes"].iter().any(|yes| value.trim().eq_ignore_ascii_case(yes)))
+}
+
+/// Where recall was launched (RECALL_CWD_OVERRIDE in tests)
+fn launch_dir() -> Option<PathBuf> {
+    std::env::var_os("RECALL_CWD_OVERRIDE")
+        .map(PathBuf::from)
+        .or_else(|| std::env::current_dir().ok())
+}
+
+/// The nearest `.recall.toml` in `dir` or its parents
+pub fn project_config_path(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join(".recall.toml"))
+        .find(|path| path.is_file())
+}
+
+static GLOBAL: OnceLock<Config> = OnceLock::new();
+
+/// Make `config` the one returned by [`get`] for the rest of the process
+pub fn init(config: Config) -> &'static Config {
+    GLOBAL.get_or_init(|| config)
+}
+
+/// The config passed to [`init`], or the defaults if it was never called
+pub fn get() -> &'static Config {
+    GLOBAL.get_or_init(Config::default)
+}
+
+/// Where the config file lives: `$RECALL_CONFIG`, else `recall/config.toml` under
+/// `$XDG_CONFIG_HOME` or `~/.config`
+pub fn config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("RECALL_CONFIG") {
+        return Some(PathBuf::from(path));
+    }
+    // Tests point the home directory elsewhere; don't let a real XDG dir leak in
+    if let Ok(home) = std::env::var("RECALL_HOME_OVERRIDE") {
+        return Some(PathBuf::from(home).join(".config/recall/config.toml"));
+    }
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".config")))?;
+    Some(config_dir.join("recall").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_config_is_default() {
+        let (config, unknown) = Config::parse("").unwrap();
+        assert_eq!(config, Config::default());
+        assert!(unknown.is_empty());
+        assert_eq!(config.search.debounce_ms, 50);
+        assert_eq!(config.cli.search_limit, 10);
+        assert_eq!(config.preview, PreviewConfig { collapse_lines: 12, head_lines: 6, tail_lines: 5 });
+    }
+
+    #[test]
+    fn test_parse_sections() {
+        let text = r#"
+theme = "gruvbox"
+
+[search]
+debounce_ms = 120
+
+[cli]
+list_limit = 100
+"#;
+        let (config, unknown) = Config::parse(text).unwrap();
+        assert!(unknown.is_empty());
+        assert_eq!(config.theme, "gruvbox");
+        assert_eq!(config.search.debounce_ms, 120);
+        // Unset keys in a present section keep their defaults
+        assert_eq!(config.search.max_results, 50);
+        assert_eq!(config.cli.list_limit, 100);
+        assert_eq!(config.cli.search_limit, 10);
+    }
+
+    #[test]
+    fn test_unknown_keys_are_reported_not_fatal() {
+        let text = r#"
+colour = "blue"
+
+[search]
+debounce_ms = 10
+typo_ms = 5
+"#;
+        let (config, unknown) = Config::parse(text).unwrap();
+        assert_eq!(config.search.debounce_ms, 10);
+        assert_eq!(unknown, ["colour", "search.typo_ms"]);
+    }
+
+    #[test]
+    fn test_bad_values_fail() {
+        assert!(Config::parse("theme = \"purple\"").is_err());
+        assert!(Config::parse("[search]\ndebounce_ms = \"fast\"").is_err());
+        assert!(Config::parse("date_format = \"%Y-%\"").is_err());
+    }
+
+    #[test]
+    fn test_default_scope() {
+        assert_eq!(Config::default().default_scope, DefaultScope::Folder);
+        let (config, _) = Config::parse("default_scope = \"repo\"").unwrap();
+        assert_eq!(config.default_scope, DefaultScope::Repo);
+        assert!(Config::parse("default_scope = \"home\"").is_err());
+    }
+
+    #[test]
+    fn test_join_messages() {
+        assert_eq!(Config::default().join_messages, MessageJoin::Gap(15));
+        let parse = |text: &str| Config::parse(text).map(|(config, _)| config.join_messages);
+        assert_eq!(parse("join_messages = \"off\"").unwrap(), MessageJoin::Off);
+        assert_eq!(parse("join_messages = \"always\"").unwrap(), MessageJoin::Always);
+        assert_eq!(parse("join_messages = \"gap:60\"").unwrap(), MessageJoin::Gap(60));
+        assert!(parse("join_messages = \"gap:soon\"").is_err());
+        assert!(parse("join_messages = \"sometimes\"").is_err());
+    }
+
+    #[test]
+    fn test_resume_templates() {
+        let text = r#"
+[resume]
+claude = 'claude --resume {id} --append-system-prompt "be brief"'
+codex = ["codex", "resume", "{id}", "--cd={cwd}"]
+"#;
+        let (config, _) = Config::parse(text).unwrap();
+
+        let claude = config.resume.template(SessionSource::ClaudeCode).unwrap();
+        assert_eq!(
+            claude.expand("abc", "/w").unwrap(),
+            ["claude", "--resume", "abc", "--append-system-prompt", "be brief"]
+        );
+
+        // Placeholders are replaced inside arguments, and values are never re-split
+        let codex = config.resume.template(SessionSource::CodexCli).unwrap();
+        assert_eq!(
+            codex.expand("abc", "/my projects/app").unwrap(),
+            ["codex", "resume", "abc", "--cd=/my projects/app"]
+        );
+
+        assert!(config.resume.template(SessionSource::Factory).is_none());
+    }
+
+    #[test]
+    fn test_empty_and_invalid_resume_templates() {
+        assert!(CommandTemplate::Line(String::new()).expand("x", "").unwrap().is_empty());
+        assert!(CommandTemplate::Args(vec![]).expand("x", "").unwrap().is_empty());
+
+        let err = Config::parse("[resume]\nclaude = 'claude \"unclosed'").unwrap_err();
+        assert!(format!("{:#}", err).contains("resume.claude"));
+    }
+
+    #[test]
+    fn test_open_command() {
+        let (config, _) = Config::parse(
+            "[open]\ncommand = [\"wezterm\", \"start\", \"--cwd\", \"{cwd}\"]\nterminal = true\n",
+        )
+        .unwrap();
+        let command = config.open.command.unwrap();
+        assert_eq!(command.expand("", "/w").unwrap(), ["wezterm", "start", "--cwd", "/w"]);
+        assert!(config.open.terminal);
+        assert_eq!(Config::default().open, OpenConfig::default());
+
+        let err = Config::parse("[open]\ncommand = 'kitty \"unclosed'").unwrap_err();
+        assert!(format!("{:#}", err).contains("open.command"));
+    }
+
+    #[test]
+    fn test_sources() {
+        let (mut config, _) = Config::parse("[sources]\nfactory = false\n").unwrap();
+        assert!(!config.sources.enabled(SessionSource::Factory));
+        assert!(config.sources.enabled(SessionSource::ClaudeCode));
+        let (continue_off, _) = Config::parse("[sources]\ncontinue = false\n").unwrap();
+        assert!(!continue_off.sources.enabled(SessionSource::Continue));
+
+        config.sources.disable(SessionSource::CodexCli);
+        assert_eq!(config.sources.disabled(), [SessionSource::CodexCli, SessionSource::Factory]);
+    }
+
+    #[test]
+    fn test_extra_homes() {
+        let (config, _) = Config::parse("extra_homes = [\"/mnt/old\", \"~/shared\"]").unwrap();
+        let homes = config.extra_homes();
+        assert_eq!(homes[0], PathBuf::from("/mnt/old"));
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(homes[1], home.join("shared"));
+        }
+    }
+
+    #[test]
+    fn test_path_map_longest_prefix_first() {
+        let text = r#"
+[path_map]
+"/workspaces" = "/srv/containers"
+"/workspaces/recall/" = "/home/me/code/recall"
+"/home/vscode/project" = "/home/me/code/other"
+"#;
+        let (config, unknown) = Config::parse(text).unwrap();
+        assert!(unknown.is_empty());
+        let map = &config.path_map;
+        assert_eq!(map.apply("/workspaces/recall"), "/home/me/code/recall");
+        assert_eq!(map.apply("/workspaces/recall/src"), "/home/me/code/recall/src");
+        assert_eq!(map.apply("/workspaces/recall2"), "/srv/containers/recall2");
+        assert_eq!(map.apply("/home/vscode/project"), "/home/me/code/other");
+        // Whole components only, and unmapped paths stay borrowed
+        assert_eq!(map.apply("/home/vscode/projects"), "/home/vscode/projects");
+        assert!(matches!(map.apply("/tmp"), Cow::Borrowed("/tmp")));
+
+        if let Some(home) = dirs::home_dir() {
+            let (config, _) = Config::parse("[path_map]
+\"/w\" = \"~/code\"").unwrap();
+            assert_eq!(config.path_map.apply("/w/app"), home.join("code/app").to_string_lossy());
+        }
+    }
+
+    #[test]
+    fn test_remotes() {
+        let text = r#"
+[[remote]]
+host = "dev"
+
+[[remote]]
+host = "me@build.local"
+path = "~/.codex/sessions/"
+"#;
+        let (config, _) = Config::parse(text).unwrap();
+        let dirs: Vec<_> = config.remotes.iter().map(|r| (r.host.as_str(), r.dir())).collect();
+        assert_eq!(dirs, [("dev", ".claude/projects"), ("me@build.local", ".codex/sessions")]);
+
+        for bad in ["host = \"-oProxyCommand=x\"", "host = \"a/b\"", "host = \"\""] {
+            assert!(Config::parse(&format!("[[remote]]\n{}", bad)).is_err(), "{}", bad);
+        }
+        for bad in ["/etc", "~/../x", "~/"] {
+            let text = format!("[[remote]]\nhost = \"dev\"\npath = \"{}\"", bad);
+            assert!(Config::parse(&text).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_exclude_patterns() {
+        let (config, _) = Config::parse("exclude = [\"**/scratch/**\"]").unwrap();
+        assert_eq!(config.exclude, ["**/scratch/**"]);
+        let err = Config::parse("exclude = [\"a/[b\"]").unwrap_err();
+        assert!(format!("{:#}", err).contains("Invalid exclude pattern 'a/[b'"));
+    }
+
+    #[test]
+    fn test_redact_patterns() {
+        let (config, _) = Config::parse("redact = true\nredact_patterns = ['ACME-\\d+']").unwrap();
+        assert!(config.redact);
+        assert_eq!(config.redact_patterns, [r"ACME-\d+"]);
+        let err = Config::parse("redact_patterns = ['(open']").unwrap_err();
+        assert!(format!("{:#}", err).contains("Invalid redact pattern '(open'"));
+    }
+
+    #[test]
+    fn test_index_memory() {
+        assert_eq!(Config::default().index.writer_heap_mb, 50);
+        let (config, _) = Config::parse("[index]\nwriter_heap_mb = 20\nlow_memory = true").unwrap();
+        assert!(config.index.low_memory);
+        assert_eq!(config.index.writer_heap_mb, 20);
+        assert_eq!(config.index.commit_interval_ms, 2000);
+        assert!(Config::parse("[index]\nwriter_heap_mb = 4").is_err());
+    }
+
+    #[test]
+    fn test_retention() {
+        assert_eq!(Config::default().retention_cutoff(), None);
+        let (config, _) = Config::parse("retention = \"180d\"").unwrap();
+        let cutoff = config.retention_cutoff().unwrap();
+        let expected = (chrono::Utc::now() - chrono::Duration::days(180)).timestamp();
+        assert!((cutoff - expected).abs() < 60, "{} vs {}", cutoff, expected);
+        let err = Config::parse("retention = \"forever\"").unwrap_err();
+        assert!(format!("{:#}", err).contains("Invalid retention 'forever'"));
+    }
+
+    #[test]
+    fn test_stemming() {
+        assert_eq!(Config::default().stemming, "english");
+        assert_eq!(Config::parse("stemming = \"off\"").unwrap().0.stemming, "off");
+        assert_eq!(Config::parse("stemming = \"german\"").unwrap().0.stemming, "german");
+        let err = Config::parse("stemming = \"klingon\"").unwrap_err();
+        assert!(format!("{:#}", err).contains("Unknown stemming 'klingon'"));
+    }
+
+    #[test]
+    fn test_merge_overlays_tables() {
+        let mut base: toml::Table = "theme = \"nord\"\n[cli]\nsearch_limit = 5\nlist_limit = 7\n"
+            .parse()
+            .unwrap();
+        let top: toml::Table = "[cli]\nlist_limit = 1\n[search]\nmax_results = 3\n".parse().unwrap();
+        merge(&mut base, top);
+        let (config, _) = Config::parse(&base.to_string()).unwrap();
+        assert_eq!(config.theme, "nord");
+        assert_eq!((config.cli.search_limit, config.cli.list_limit), (5, 1));
+        assert_eq!(config.search.max_results, 3);
+    }
+
+    #[test]
+    fn test_project_config_path_walks_up() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let nested = temp.path().join("repo/crates/core");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(project_config_path(&nested), None);
+
+        std::fs::write(temp.path().join("repo/.recall.toml"), "").unwrap();
+        assert_eq!(project_config_path(&nested), Some(temp.path().join("repo/.recall.toml")));
+    }
+
+    #[test]
+    fn test_load_from_file() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("config.toml");
+        std::fs::write(&path, "theme = \"dark\"\n").unwrap();
+        assert_eq!(Config::load_from(&path).unwrap().theme, "dark");
+        assert!(Config::load_from(&temp.path().join("missing.toml")).is_err());
+    }
+}
@@ -0,0 +1,272 @@
+//! Align the messages of two sessions and diff the ones that changed
+
+use crate::session::{Message, Role};
+use serde::Serialize;
+
+/// How messages are paired between the two sessions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Alignment {
+    /// Pair identical messages by longest common subsequence, then pair what's left
+    /// between them by position (good for forks and redone tasks)
+    #[default]
+    Lcs,
+    /// Pair message N with message N
+    Index,
+}
+
+/// One step of the alignment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DiffEntry {
+    Same { left: usize, right: usize, role: Role },
+    Changed { left: usize, right: usize, role: Role, lines: Vec<LineChange> },
+    /// Only in the left session
+    Removed { left: usize, role: Role },
+    /// Only in the right session
+    Added { right: usize, role: Role },
+}
+
+/// A line of a unified diff
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LineChange {
+    /// ' ' (context), '-' (left only), or '+' (right only)
+    pub op: char,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DiffSummary {
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+    pub unchanged: usize,
+}
+
+/// Past this many cells, line diffs give up on alignment and replace the whole message
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// Align two message lists
+pub fn align(left: &[Message], right: &[Message], alignment: Alignment) -> Vec<DiffEntry> {
+    match alignment {
+        Alignment::Index => {
+            let mut entries = Vec::new();
+            for i in 0..left.len().max(right.len()) {
+                match (left.get(i), right.get(i)) {
+                    (Some(l), Some(r)) if l.role == r.role => entries.push(compare(i, l, i, r)),
+                    (l, r) => {
+                        if let Some(l) = l {
+                            entries.push(DiffEntry::Removed { left: i, role: l.role });
+                        }
+                        if let Some(r) = r {
+                            entries.push(DiffEntry::Added { right: i, role: r.role });
+                        }
+                    }
+                }
+            }
+            entries
+        }
+        Alignment::Lcs => {
+            let pairs = lcs(left.len(), right.len(), |i, j| same_message(&left[i], &right[j]));
+            let mut entries = Vec::new();
+            let (mut i, mut j) = (0, 0);
+            for (a, b) in pairs.into_iter().chain([(left.len(), right.len())]) {
+                pair_gap(left, right, i..a, j..b, &mut entries);
+                if a < left.len() && b < right.len() {
+                    entries.push(DiffEntry::Same { left: a, right: b, role: left[a].role });
+                }
+                (i, j) = (a + 1, b + 1);
+            }
+            entries
+        }
+    }
+}
+
+/// Count each kind of entry
+pub fn summarize(entries: &[DiffEntry]) -> DiffSummary {
+    let mut summary = DiffSummary::default();
+    for entry in entries {
+        match entry {
+            DiffEntry::Same { .. } => summary.unchanged += 1,
+            DiffEntry::Changed { .. } => summary.changed += 1,
+            DiffEntry::Removed { .. } => summary.removed += 1,
+            DiffEntry::Added { .. } => summary.added += 1,
+        }
+    }
+    summary
+}
+
+/// Line-by-line diff of two texts: every line of both, marked ' ', '-', or '+'
+pub fn diff_lines(left: &str, right: &str) -> Vec<LineChange> {
+    let a: Vec<&str> = left.lines().collect();
+    let b: Vec<&str> = right.lines().collect();
+    let line = |op, text: &str| LineChange { op, text: text.to_string() };
+
+    if a.len() * b.len() > MAX_LCS_CELLS {
+        return a.iter().map(|t| line('-', t)).chain(b.iter().map(|t| line('+', t))).collect();
+    }
+
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let pairs = lcs(a.len(), b.len(), |x, y| a[x] == b[y]);
+    for (x, y) in pairs.into_iter().chain([(a.len(), b.len())]) {
+        changes.extend(a[i..x].iter().map(|t| line('-', t)));
+        changes.extend(b[j..y].iter().map(|t| line('+', t)));
+        if x < a.len() && y < b.len() {
+            changes.push(line(' ', a[x]));
+        }
+        (i, j) = (x + 1, y + 1);
+    }
+    changes
+}
+
+fn same_message(a: &Message, b: &Message) -> bool {
+    a.role == b.role && a.content.trim() == b.content.trim()
+}
+
+fn compare(i: usize, l: &Message, j: usize, r: &Message) -> DiffEntry {
+    if same_message(l, r) {
+        DiffEntry::Same { left: i, right: j, role: l.role }
+    } else {
+        DiffEntry::Changed {
+            left: i,
+            right: j,
+            role: l.role,
+            lines: diff_lines(&l.content, &r.content),
+        }
+    }
+}
+
+/// Pair the unmatched messages between two anchors: same-role messages in order become
+/// changes, the rest are removals and additions
+fn pair_gap(
+    left: &[Message],
+    right: &[Message],
+    lefts: std::ops::Range<usize>,
+    rights: std::ops::Range<usize>,
+    entries: &mut Vec<DiffEntry>,
+) {
+    let mut j = rights.start;
+    for i in lefts {
+        match (j..rights.end).find(|&k| right[k].role == left[i].role) {
+            Some(k) => {
+                entries.extend((j..k).map(|k| DiffEntry::Added { right: k, role: right[k].role }));
+                entries.push(compare(i, &left[i], k, &right[k]));
+                j = k + 1;
+            }
+            None => entries.push(DiffEntry::Removed { left: i, role: left[i].role }),
+        }
+    }
+    entries.extend((j..rights.end).map(|k| DiffEntry::Added { right: k, role: right[k].role }));
+}
+
+/// Index pairs (i, j) of a longest common subsequence, in order
+fn lcs(n: usize, m: usize, eq: impl Fn(usize, usize) -> bool) -> Vec<(usize, usize)> {
+    // lengths[i][j] = LCS length of the suffixes starting at i and j
+    let mut lengths = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if eq(i, j) {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if eq(i, j) {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn messages(spec: &[(Role, &str)]) -> Vec<Message> {
+        spec.iter()
+            .map(|(role, content)| Message {
+                role: *role,
+                content: content.to_string(),
+                timestamp: Utc::now(),
+                tool_calls: Vec::new(),
+            })
+            .collect()
+    }
+
+    use Role::{Assistant as A, User as U};
+
+    #[test]
+    fn test_fork_is_strict_prefix() {
+        let base = messages(&[(U, "fix the bug"), (A, "fixed"), (U, "add a test")]);
+        let mut fork = base.clone();
+        fork.extend(messages(&[(A, "test added"), (U, "thanks")]));
+
+        let entries = align(&base, &fork, Alignment::Lcs);
+        assert_eq!(
+            summarize(&entries),
+            DiffSummary { added: 2, removed: 0, changed: 0, unchanged: 3 }
+        );
+        assert_eq!(entries[3], DiffEntry::Added { right: 3, role: A });
+        assert_eq!(entries[4], DiffEntry::Added { right: 4, role: U });
+    }
+
+    #[test]
+    fn test_redone_task_pairs_changed_answers() {
+        let claude = messages(&[(U, "rename foo"), (A, "Renamed foo to bar."), (U, "run tests")]);
+        let codex = messages(&[(U, "rename foo"), (A, "Renamed foo to baz."), (U, "run tests")]);
+
+        let entries = align(&claude, &codex, Alignment::Lcs);
+        assert_eq!(summarize(&entries).changed, 1);
+        let DiffEntry::Changed { left, right, lines, .. } = &entries[1] else {
+            panic!("expected a change, got {:?}", entries[1]);
+        };
+        assert_eq!((*left, *right), (1, 1));
+        assert_eq!(lines[0], LineChange { op: '-', text: "Renamed foo to bar.".to_string() });
+        assert_eq!(lines[1], LineChange { op: '+', text: "Renamed foo to baz.".to_string() });
+    }
+
+    #[test]
+    fn test_lcs_realigns_after_insertion() {
+        let left = messages(&[(U, "one"), (A, "a"), (U, "two"), (A, "b")]);
+        let right = messages(&[(U, "zero"), (A, "z"), (U, "one"), (A, "a"), (U, "two"), (A, "b")]);
+
+        let entries = align(&left, &right, Alignment::Lcs);
+        assert_eq!(
+            summarize(&entries),
+            DiffSummary { added: 2, removed: 0, changed: 0, unchanged: 4 }
+        );
+
+        // By index, the shift makes everything look changed
+        let by_index = summarize(&align(&left, &right, Alignment::Index));
+        assert_eq!((by_index.changed, by_index.added), (4, 2));
+    }
+
+    #[test]
+    fn test_removed_only_in_left() {
+        let left = messages(&[(U, "q"), (A, "a"), (U, "dropped")]);
+        let right = messages(&[(U, "q"), (A, "a")]);
+        let entries = align(&left, &right, Alignment::Lcs);
+        assert_eq!(entries.last(), Some(&DiffEntry::Removed { left: 2, role: U }));
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let lines = diff_lines("a\nb\nc", "a\nc\nd");
+        let ops: String = lines.iter().map(|l| l.op).collect();
+        let texts: Vec<&str> = lines.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(ops, " - +");
+        assert_eq!(texts, ["a", "b", "c", "d"]);
+    }
+}
@@ -0,0 +1,239 @@
+//! Embedding recall in other tools: discovery, indexing, and search behind one entry type,
+//! configured explicitly rather than from environment variables.
+//!
+//! ```
+//! use recall::{Recall, RecallConfig, SearchOptions};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! # let temp = tempfile::TempDir::new()?;
+//! # let home = temp.path().join("home");
+//! # let project = home.join(".claude/projects/demo");
+//! # std::fs::create_dir_all(&project)?;
+//! # std::fs::write(
+//! #     project.join("s1.jsonl"),
+//! #     concat!(
+//! #         r#"{"type":"user","sessionId":"s1","cwd":"/w","timestamp":"2025-01-01T00:00:00Z","#,
+//! #         r#""message":{"role":"user","content":"fix the flaky login test"}}"#,
+//! #     ),
+//! # )?;
+//! let recall = Recall::open(RecallConfig::new(home, temp.path().join("cache")))?;
+//! recall.index()?;
+//!
+//! let results = recall.search("flaky login", &SearchOptions::default())?;
+//! assert_eq!(results[0].session.id, "s1");
+//! let session = recall.get_session("s1")?.unwrap();
+//! assert_eq!(session.messages[0].content, "fix the flaky login test");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Stability
+//!
+//! [`Recall`], [`RecallConfig`], [`SearchOptions`], [`fetch_filtered`], and the
+//! [`crate::session`] types they return are the supported library API. While recall is 0.x,
+//! breaking changes to them come with a minor version bump, never a patch release. New
+//! fields may be added to the option structs in a minor release, so build them with
+//! [`RecallConfig::new`] and `..Default::default()`. The other modules serve the `recall`
+//! binary and can change in any release.
+
+use crate::index::{
+    files_to_index, index_files, purge_files, stale_files, IndexReport, IndexState, SessionIndex,
+};
+use crate::parser::{self, DiscoveryPass, DiscoveryRoot};
+use crate::session::{SearchResult, Session, SessionSource};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+
+/// Where a [`Recall`] finds sessions and keeps its index
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecallConfig {
+    /// Home directory holding `.claude/projects`, `.codex/sessions`, and the other sources
+    pub home: PathBuf,
+    /// Sessions brought in by `recall import`
+    pub imported_dir: PathBuf,
+    /// Directory for the index and its state (created if missing)
+    pub cache_dir: PathBuf,
+    /// Sources to discover and index
+    pub sources: Vec<SessionSource>,
+}
+
+impl RecallConfig {
+    /// Every source under `home`, indexed into `cache_dir`
+    pub fn new(home: impl Into<PathBuf>, cache_dir: impl Into<PathBuf>) -> Self {
+        let home = home.into();
+        Self {
+            imported_dir: home.join(".local/share/recall/imported"),
+            home,
+            cache_dir: cache_dir.into(),
+            sources: SessionSource::ALL.to_vec(),
+        }
+    }
+
+    /// The locations the `recall` binary uses: the user's home and cache directory, or
+    /// `RECALL_HOME_OVERRIDE` in tests
+    pub fn from_env() -> Self {
+        Self {
+            home: parser::default_home().unwrap_or_default(),
+            imported_dir: parser::imported_dir(),
+            cache_dir: crate::index::cache::dir(),
+            sources: SessionSource::ALL.to_vec(),
+        }
+    }
+}
+
+/// Which sessions [`Recall::search`] and [`Recall::recent`] return
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchOptions {
+    /// Most sessions to return
+    pub limit: usize,
+    /// Only sessions from this source
+    pub source: Option<SessionSource>,
+    /// Only sessions started in exactly this directory (as recorded, or after `[path_map]`)
+    pub cwd: Option<String>,
+    /// Only sessions active at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only sessions active at or before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Only sessions with all of these tags (see [`crate::tags`])
+    pub tags: Vec<String>,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self { limit: 10, source: None, cwd: None, since: None, until: None, tags: Vec::new() }
+    }
+}
+
+impl SearchOptions {
+    /// Whether `session` passes the filters
+    pub fn matches(&self, session: &Session) -> bool {
+        self.matches_in(session, &crate::index::cache::dir())
+    }
+
+    /// Whether `session` passes the filters, with its tags read from the sidecar in `dir`
+    pub fn matches_in(&self, session: &Session, dir: &Path) -> bool {
+        self.source.is_none_or(|s| session.source == s)
+            && self.since.is_none_or(|t| session.timestamp >= t)
+            && self.until.is_none_or(|t| session.timestamp <= t)
+            && self.cwd.as_deref().is_none_or(|c| session.cwd == c || session.local_cwd() == c)
+            && (self.tags.is_empty() || {
+                let tags = crate::tags::get_in(dir, &session.id);
+                self.tags.iter().all(|t| tags.contains(t))
+            })
+    }
+}
+
+/// Query the index until more than `wanted` results pass `options`' filters (or the index runs
+/// out), growing the fetch size as needed. More than `wanted` results means there's another page.
+pub fn fetch_filtered(
+    fetch: impl Fn(usize) -> Result<Vec<SearchResult>>,
+    options: &SearchOptions,
+    wanted: usize,
+) -> Result<Vec<SearchResult>> {
+    fetch_matching(fetch, |session| options.matches(session), wanted)
+}
+
+/// [`fetch_filtered`], keeping the results whose session passes `keep`
+fn fetch_matching(
+    fetch: impl Fn(usize) -> Result<Vec<SearchResult>>,
+    keep: impl Fn(&Session) -> bool,
+    wanted: usize,
+) -> Result<Vec<SearchResult>> {
+    let mut fetch_size = wanted.max(1) * 2;
+    let mut last_fetched = None;
+    loop {
+        let results = fetch(fetch_size)?;
+        let fetched = results.len();
+        let filtered: Vec<_> = results.into_iter().filter(|r| keep(&r.session)).collect();
+
+        // A bigger fetch finding nothing new means the index is exhausted
+        if filtered.len() > wanted || last_fetched == Some(fetched) {
+            return Ok(filtered);
+        }
+        last_fetched = Some(fetched);
+        fetch_size *= 4;
+    }
+}
+
+/// Sessions from one home directory, searchable through an index of its own
+pub struct Recall {
+    config: RecallConfig,
+    index: SessionIndex,
+}
+
+impl Recall {
+    /// Open the index in `config.cache_dir`, creating it if needed. Call [`Recall::index`] to
+    /// bring it up to date with the session files.
+    pub fn open(config: RecallConfig) -> Result<Self> {
+        let mut index = SessionIndex::open_or_create(&config.cache_dir.join("index"))?;
+        index.set_sidecar_dir(&config.cache_dir);
+        Ok(Self { config, index })
+    }
+
+    /// Index new and changed session files and drop deleted ones
+    pub fn index(&self) -> Result<IndexReport> {
+        let state_path = self.config.cache_dir.join("state.json");
+        let mut state = IndexState::load(&state_path)?;
+        state.set_sidecar_dir(&self.config.cache_dir);
+        let files = self.discover_files();
+        let files_to_index = files_to_index(&state, &files, None);
+        let stale = stale_files(&state, &files);
+        if files_to_index.is_empty() && stale.is_empty() {
+            return Ok(IndexReport::default());
+        }
+
+        let mut writer = self.index.writer()?;
+        purge_files(&self.index, &mut writer, &mut state, &stale);
+        let report =
+            index_files(&self.index, &mut writer, &mut state, &files_to_index, None, None, None)?;
+        state.save(&state_path)?;
+        self.index.reload()?;
+        Ok(report)
+    }
+
+    /// Sessions matching `query`, best first, one result per session
+    pub fn search(&self, query: &str, options: &SearchOptions) -> Result<Vec<SearchResult>> {
+        let keep = |session: &Session| options.matches_in(session, &self.config.cache_dir);
+        let mut results = fetch_matching(|n| self.index.search(query, n), keep, options.limit)?;
+        results.truncate(options.limit);
+        Ok(results)
+    }
+
+    /// The most recently active sessions, newest first
+    pub fn recent(&self, options: &SearchOptions) -> Result<Vec<SearchResult>> {
+        let keep = |session: &Session| options.matches_in(session, &self.config.cache_dir);
+        let mut results = fetch_matching(|n| self.index.recent(n), keep, options.limit)?;
+        results.truncate(options.limit);
+        Ok(results)
+    }
+
+    /// A session with all its messages, read from its file, or None if `id` isn't indexed
+    pub fn get_session(&self, id: &str) -> Result<Option<Session>> {
+        match self.index.get_by_id(id)? {
+            Some(path) => parser::parse_session_file(&path).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Session files of the configured sources, minus the excluded ones
+    fn discover_files(&self) -> Vec<PathBuf> {
+        let config = &self.config;
+        let mut roots = parser::source_roots(&config.home, None);
+        roots.retain(|root| root.source.is_some_and(|s| config.sources.contains(&s)));
+        roots.push(DiscoveryRoot { source: None, dir: config.imported_dir.clone(), origin: None });
+
+        let pass = DiscoveryPass::default();
+        roots
+            .iter()
+            .flat_map(|root| {
+                root.discover_files_in(&pass).into_iter().filter(|path| {
+                    // Imported sessions are filed by source
+                    root.source.is_some()
+                        || parser::imported_source(path).is_none_or(|s| config.sources.contains(&s))
+                })
+            })
+            .filter(|path| !parser::is_excluded(path))
+            .collect()
+    }
+}
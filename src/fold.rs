@@ -0,0 +1,83 @@
+//! Case-insensitive matching, shared by the TUI's highlighting and the CLI's message scoring
+//! so both agree on what matches
+
+use caseless::Caseless;
+use std::ops::Range;
+
+fn fold_char(c: char, out: &mut String) {
+    match c {
+        // Full folding turns İ into i + a combining dot and leaves ı alone; treat both as i so
+        // Turkish text matches whichever way it was typed
+        'İ' | 'ı' => out.push('i'),
+        c => out.extend(std::iter::once(c).default_case_fold()),
+    }
+}
+
+/// Unicode case folding of `text` (ß and SS both fold to ss)
+pub fn fold(text: &str) -> String {
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars() {
+        fold_char(c, &mut folded);
+    }
+    folded
+}
+
+/// Non-overlapping occurrences of `needle` in `text`, ignoring case, as byte ranges of `text`.
+/// A match that covers part of a char's folding (the "s" of ß) takes the whole char.
+pub fn find_all(text: &str, needle: &str) -> Vec<Range<usize>> {
+    let needle = fold(needle);
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    // The start of the original char behind each folded byte
+    let mut folded = String::with_capacity(text.len());
+    let mut origins = Vec::with_capacity(text.len());
+    for (i, c) in text.char_indices() {
+        fold_char(c, &mut folded);
+        origins.resize(folded.len(), i);
+    }
+
+    let char_end = |i: usize| i + text[i..].chars().next().map_or(0, char::len_utf8);
+    let mut ranges: Vec<Range<usize>> = folded
+        .match_indices(&needle)
+        .map(|(start, matched)| origins[start]..char_end(origins[start + matched.len() - 1]))
+        .collect();
+    // Both halves of one folded char land on the same range
+    ranges.dedup();
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The parts of `text` that match `needle`
+    fn found<'t>(text: &'t str, needle: &str) -> Vec<&'t str> {
+        find_all(text, needle).into_iter().map(|r| &text[r]).collect()
+    }
+
+    #[test]
+    fn test_fold_sharp_s() {
+        assert_eq!(fold("Straße"), fold("STRASSE"));
+        assert_eq!(found("Die Straße ist lang", "strasse"), ["Straße"]);
+        assert_eq!(found("STRASSE", "straße"), ["STRASSE"]);
+    }
+
+    #[test]
+    fn test_fold_turkish_i() {
+        assert_eq!(fold("İstanbul"), "istanbul");
+        assert_eq!(found("İstanbul'da", "istanbul"), ["İstanbul"]);
+        assert_eq!(found("ıslak", "ISLAK"), ["ıslak"]);
+        assert_eq!(found("Istanbul", "İSTANBUL"), ["Istanbul"]);
+    }
+
+    #[test]
+    fn test_find_all_maps_back_to_text() {
+        assert_eq!(found("Ärger, ärger, ÄRGER", "ärger"), ["Ärger", "ärger", "ÄRGER"]);
+
+        // Part of a folded char highlights the whole char, once
+        assert_eq!(found("groß", "s"), ["ß"]);
+        assert!(find_all("anything", "").is_empty());
+    }
+}
@@ -0,0 +1,151 @@
+//! Back/forward navigation through visited sessions, like a browser's history
+
+/// Most entries kept; the oldest are dropped first
+const MAX_ENTRIES: usize = 100;
+
+/// Visited places, oldest first, with a cursor on the current one. Pushing after going back
+/// drops everything ahead of the cursor.
+#[derive(Debug, Clone)]
+pub struct JumpList<T> {
+    entries: Vec<T>,
+    /// Index of the current entry (meaningless while `entries` is empty)
+    current: usize,
+}
+
+impl<T> Default for JumpList<T> {
+    fn default() -> Self {
+        Self { entries: Vec::new(), current: 0 }
+    }
+}
+
+impl<T> JumpList<T> {
+    /// Record `entry` as the current place. If `same_place` says it's where the cursor already
+    /// is, the current entry is updated instead, leaving the forward entries alone.
+    pub fn push(&mut self, entry: T, same_place: impl Fn(&T, &T) -> bool) {
+        if let Some(current) = self.entries.get_mut(self.current) {
+            if same_place(current, &entry) {
+                *current = entry;
+                return;
+            }
+            self.entries.truncate(self.current + 1);
+        }
+        self.entries.push(entry);
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.current = self.entries.len() - 1;
+    }
+
+    /// Step back to the nearest earlier entry that `valid` accepts
+    pub fn back(&mut self, valid: impl Fn(&T) -> bool) -> Option<&T> {
+        let found = (0..self.current).rev().find(|&i| valid(&self.entries[i]))?;
+        self.current = found;
+        self.entries.get(found)
+    }
+
+    /// Step forward to the nearest later entry that `valid` accepts
+    pub fn forward(&mut self, valid: impl Fn(&T) -> bool) -> Option<&T> {
+        let found = (self.current + 1..self.entries.len()).find(|&i| valid(&self.entries[i]))?;
+        self.current = found;
+        self.entries.get(found)
+    }
+
+    /// The entry the cursor is on
+    pub fn current(&self) -> Option<&T> {
+        self.entries.get(self.current)
+    }
+
+    /// Number of entries, behind and ahead of the cursor
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A session name and its preview scroll
+    type Entry = (&'static str, u32);
+
+    fn same(a: &Entry, b: &Entry) -> bool {
+        a.0 == b.0
+    }
+
+    fn any(_: &Entry) -> bool {
+        true
+    }
+
+    fn list(names: &[&'static str]) -> JumpList<Entry> {
+        let mut jumps = JumpList::default();
+        for &name in names {
+            jumps.push((name, 0), same);
+        }
+        jumps
+    }
+
+    #[test]
+    fn test_back_and_forward() {
+        let mut jumps = list(&["a", "b", "c"]);
+        assert_eq!(jumps.back(any).map(|e| e.0), Some("b"));
+        assert_eq!(jumps.back(any).map(|e| e.0), Some("a"));
+        assert_eq!(jumps.back(any), None);
+        assert_eq!(jumps.current().map(|e| e.0), Some("a"));
+        assert_eq!(jumps.forward(any).map(|e| e.0), Some("b"));
+        assert_eq!(jumps.forward(any).map(|e| e.0), Some("c"));
+        assert_eq!(jumps.forward(any), None);
+        assert!(JumpList::<u32>::default().back(|_| true).is_none());
+    }
+
+    #[test]
+    fn test_push_after_back_truncates_forward() {
+        let mut jumps = list(&["a", "b", "c"]);
+        jumps.back(any);
+        jumps.back(any);
+        jumps.push(("d", 0), same);
+        assert_eq!(jumps.len(), 2);
+        assert_eq!(jumps.forward(any), None);
+        assert_eq!(jumps.back(any).map(|e| e.0), Some("a"));
+    }
+
+    #[test]
+    fn test_same_place_updates_in_place() {
+        let mut jumps = list(&["a", "b", "c"]);
+        jumps.back(any);
+        // Scrolling the restored entry keeps the way forward
+        jumps.push(("b", 7), same);
+        assert_eq!(jumps.len(), 3);
+        assert_eq!(jumps.current(), Some(&("b", 7)));
+        assert_eq!(jumps.forward(any).map(|e| e.0), Some("c"));
+        assert_eq!(jumps.back(any), Some(&("b", 7)));
+    }
+
+    #[test]
+    fn test_skips_invalid_entries() {
+        let mut jumps = list(&["a", "gone", "c"]);
+        let exists = |e: &Entry| e.0 != "gone";
+        assert_eq!(jumps.back(exists).map(|e| e.0), Some("a"));
+        assert_eq!(jumps.forward(exists).map(|e| e.0), Some("c"));
+
+        // Nothing valid behind: stay put
+        let mut jumps = list(&["gone", "c"]);
+        assert_eq!(jumps.back(exists), None);
+        assert_eq!(jumps.current().map(|e| e.0), Some("c"));
+    }
+
+    #[test]
+    fn test_capped() {
+        let mut jumps = JumpList::default();
+        for i in 0..MAX_ENTRIES as u32 + 10 {
+            jumps.push(("x", i), |a, b| a.1 == b.1);
+        }
+        assert_eq!(jumps.len(), MAX_ENTRIES);
+        assert_eq!(jumps.current(), Some(&("x", MAX_ENTRIES as u32 + 9)));
+        while jumps.back(any).is_some() {}
+        assert_eq!(jumps.current(), Some(&("x", 10)));
+    }
+}
@@ -0,0 +1,119 @@
+//! Where the index lives, and keeping it private. The index holds a full-text copy of every
+//! conversation, so on Unix its directories are 0700 and its files 0600. With
+//! `privacy.ephemeral` (or `--no-persist`) nothing is written at all.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The cache directory holding the index and its state
+pub fn dir() -> PathBuf {
+    std::env::var("RECALL_HOME_OVERRIDE")
+        .map(|h| PathBuf::from(h).join(".cache").join("recall"))
+        .unwrap_or_else(|_| {
+            dirs::cache_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("recall")
+        })
+}
+
+/// The index's state file, next to the index directory
+pub fn state_path(index_path: &Path) -> PathBuf {
+    index_path
+        .parent()
+        .map(|p| p.join("state.json"))
+        .unwrap_or_else(|| index_path.join("state.json"))
+}
+
+/// Whether the index is kept in memory for this process only
+pub fn ephemeral() -> bool {
+    crate::config::get().privacy.ephemeral
+}
+
+/// Create `path` and its missing parents, making `path` itself 0700
+pub fn create_private_dir(path: &Path) -> io::Result<()> {
+    std::fs::create_dir_all(path)?;
+    set_mode(path, 0o700)
+}
+
+/// Write `contents` to a file only the owner can read
+pub fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    io::Write::write_all(&mut options.open(path)?, contents)?;
+    // The mode above only applies to new files
+    set_mode(path, 0o600)
+}
+
+/// Make the files directly in `dir` 0600. Tantivy creates its segment files with the
+/// process umask, so this runs after every commit.
+pub fn restrict_files(dir: &Path) -> io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            set_mode(&entry.path(), 0o600)?;
+        }
+    }
+    Ok(())
+}
+
+/// Permission bits of `path` as octal ("0700"), or None if it's missing or not on Unix
+pub fn mode(path: &Path) -> Option<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(path).ok()?.permissions().mode();
+        Some(format!("{:04o}", mode & 0o777))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+/// A line describing where the index lives and who can read it
+pub fn describe(dir: &Path) -> String {
+    if ephemeral() {
+        return "in memory (not persisted)".to_string();
+    }
+    match mode(dir) {
+        Some(mode) => format!("{} (mode {})", dir.display(), mode),
+        None => dir.display().to_string(),
+    }
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_private_modes() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let dir = temp.path().join("recall").join("index");
+        create_private_dir(&dir).unwrap();
+        assert_eq!(mode(&dir).as_deref(), Some("0700"));
+
+        // An existing file is tightened too
+        let state = temp.path().join("recall").join("state.json");
+        std::fs::write(&state, "{}").unwrap();
+        write_private(&state, b"{}").unwrap();
+        assert_eq!(mode(&state).as_deref(), Some("0600"));
+
+        std::fs::write(dir.join("meta.json"), "{}").unwrap();
+        restrict_files(&dir).unwrap();
+        assert_eq!(mode(&dir.join("meta.json")).as_deref(), Some("0600"));
+    }
+}
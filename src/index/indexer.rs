@@ -3,8 +3,10 @@
 use super::state::IndexState;
 use super::SessionIndex;
 use crate::parser;
+use crate::session::SessionSource;
 use anyhow::Result;
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
 use tantivy::IndexWriter;
 
 /// Progress information during indexing
@@ -13,6 +15,23 @@ pub struct IndexProgress {
     pub total: usize,
 }
 
+/// What happened to the files passed to [`index_files`]
+#[derive(Debug, Default)]
+pub struct IndexReport {
+    /// Files with at least one message, now searchable
+    pub indexed: usize,
+    /// Files that parsed but had no messages
+    pub empty: usize,
+    /// Files that couldn't be parsed or indexed, with the reason (retried next run)
+    pub failed: Vec<(PathBuf, String)>,
+    /// Files whose session is older than the retention cutoff, left out
+    pub expired: usize,
+    /// Files holding a session another file holds more of, left out
+    pub duplicates: usize,
+    /// Files whose project's `.recall.toml` excludes them, left out
+    pub excluded: usize,
+}
+
 /// Callback for reporting indexing progress
 pub type ProgressCallback = Box<dyn FnMut(IndexProgress) + Send>;
 
@@ -23,10 +42,10 @@ pub type ReloadCallback = Box<dyn FnMut() + Send>;
 pub fn discover_and_sort_files() -> Vec<PathBuf> {
     let mut files = parser::discover_session_files();
     files.sort_by(|a, b| {
-        let mtime_a = std::fs::metadata(a)
+        let mtime_a = std::fs::metadata(parser::backing_file(a))
             .and_then(|m| m.modified())
             .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
-        let mtime_b = std::fs::metadata(b)
+        let mtime_b = std::fs::metadata(parser::backing_file(b))
             .and_then(|m| m.modified())
             .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
         mtime_b.cmp(&mtime_a) // Descending (most recent first)
@@ -34,41 +53,195 @@ pub fn discover_and_sort_files() -> Vec<PathBuf> {
     files
 }
 
+/// `files` last modified at or after `cutoff`. An older file can't hold a newer session, so
+/// retention skips it without parsing.
+pub fn retained_files(files: Vec<PathBuf>, cutoff: Option<i64>) -> Vec<PathBuf> {
+    let Some(cutoff) = cutoff else {
+        return files;
+    };
+    files
+        .into_iter()
+        .filter(|path| {
+            std::fs::metadata(parser::backing_file(path))
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .is_some_and(|t| t.as_secs() as i64 >= cutoff)
+        })
+        .collect()
+}
+
+/// Files to (re)index: new or changed since the last run, or left out by retention and
+/// within `cutoff` now
+pub fn files_to_index(state: &IndexState, files: &[PathBuf], cutoff: Option<i64>) -> Vec<PathBuf> {
+    files
+        .iter()
+        .filter(|f| state.needs_reindex(f) || state.revived(f, cutoff))
+        .cloned()
+        .collect()
+}
+
+/// Indexed sessions whose latest message is before `cutoff`, with that time
+pub fn expired_files(index: &SessionIndex, cutoff: Option<i64>) -> Result<Vec<(PathBuf, i64)>> {
+    match cutoff {
+        Some(cutoff) => index.sessions_before(cutoff),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Remove sessions past the retention cutoff from the index, remembering their time in the
+/// state so they come back if the cutoff moves
+pub fn prune_files(
+    index: &SessionIndex,
+    writer: &mut IndexWriter,
+    state: &mut IndexState,
+    files: &[(PathBuf, i64)],
+) {
+    for (file_path, timestamp) in files {
+        index.delete_session(writer, file_path);
+        state.mark_expired(file_path, *timestamp);
+    }
+}
+
+/// Indexed files to drop: deleted, or excluded (or subagent transcripts no longer included)
+/// since they were indexed. Files of disabled sources stay (they're hidden at query time). A
+/// session kept in a database never "exists" as a file, so it's dropped once it's no longer
+/// discovered in the database.
+pub fn stale_files(state: &IndexState, discovered: &[PathBuf]) -> Vec<PathBuf> {
+    let subagents = crate::config::get().include_subagents;
+    state
+        .undiscovered_files(discovered)
+        .into_iter()
+        .filter(|path| {
+            !path.exists()
+                || parser::is_excluded(path)
+                || (!subagents && parser::is_subagent_transcript(path))
+        })
+        .collect()
+}
+
+/// Remove files from the index and the state
+pub fn purge_files(
+    index: &SessionIndex,
+    writer: &mut IndexWriter,
+    state: &mut IndexState,
+    files: &[PathBuf],
+) {
+    for file_path in files {
+        index.delete_session(writer, file_path);
+        state.remove(file_path);
+    }
+}
+
+/// When to commit while indexing: once `interval` has passed since the last commit, so new
+/// results show up steadily even while giant sessions are being indexed, or once `max_bytes`
+/// of content is buffered, so runs of small files don't commit needlessly often
+pub struct CommitCadence<C: Fn() -> Instant = fn() -> Instant> {
+    interval: Duration,
+    max_bytes: usize,
+    bytes: usize,
+    last_commit: Instant,
+    clock: C,
+}
+
+impl CommitCadence {
+    pub fn new(interval: Duration, max_bytes: usize) -> Self {
+        Self::with_clock(interval, max_bytes, Instant::now)
+    }
+}
+
+impl<C: Fn() -> Instant> CommitCadence<C> {
+    /// A cadence that reads the time from `clock`
+    pub fn with_clock(interval: Duration, max_bytes: usize, clock: C) -> Self {
+        let last_commit = clock();
+        Self { interval, max_bytes, bytes: 0, last_commit, clock }
+    }
+
+    /// Count `bytes` more of buffered content, returning whether it's time to commit
+    pub fn add(&mut self, bytes: usize) -> bool {
+        self.bytes += bytes;
+        self.bytes >= self.max_bytes || (self.clock)() - self.last_commit >= self.interval
+    }
+
+    /// Start counting again after a commit
+    pub fn committed(&mut self) {
+        self.bytes = 0;
+        self.last_commit = (self.clock)();
+    }
+}
+
 /// Index a batch of files, calling progress callbacks as work proceeds.
 ///
 /// - `on_progress`: Called every 50 files with current progress
-/// - `on_reload`: Called every 200 files after a commit (for incremental updates)
+/// - `on_reload`: Called after each commit along the way (see [`CommitCadence`]), for
+///   incremental updates
+///
+/// Sessions last active before `cutoff` are left out (see [`retained_files`]).
 ///
-/// Returns the number of files successfully indexed.
+/// Returns how many files were indexed, empty, expired, or failed.
 pub fn index_files(
     index: &SessionIndex,
     writer: &mut IndexWriter,
     state: &mut IndexState,
     files: &[PathBuf],
+    cutoff: Option<i64>,
     mut on_progress: Option<ProgressCallback>,
     mut on_reload: Option<ReloadCallback>,
-) -> Result<usize> {
+) -> Result<IndexReport> {
     let total = files.len();
-    let mut indexed = 0;
+    let mut report = IndexReport::default();
+    let (interval, max_bytes) = index.commit_cadence();
+    let mut cadence = CommitCadence::new(interval, max_bytes);
 
     for (i, file_path) in files.iter().enumerate() {
         // Delete existing documents for this file (in case of update)
         index.delete_session(writer, file_path);
+        let mut bytes = 0;
 
         // Parse and index
         match parser::parse_session_file(file_path) {
+            // Mark as indexed even if empty (so we don't reprocess it)
+            Ok(session) if session.messages.is_empty() => {
+                state.mark_empty(file_path);
+                report.empty += 1;
+            }
+            Ok(session) if parser::is_excluded_by_project(&session.cwd) => {
+                state.mark_excluded(file_path);
+                report.excluded += 1;
+            }
+            Ok(session) if cutoff.is_some_and(|c| session.timestamp.timestamp() < c) => {
+                state.mark_expired(file_path, session.timestamp.timestamp());
+                report.expired += 1;
+            }
             Ok(session) => {
-                if !session.messages.is_empty() {
-                    let _ = index.index_session(writer, &session);
+                // Resuming a Claude session can copy it into a new file under the same ID: only
+                // the fuller copy is indexed, so the session shows up once
+                let copied = session.source == SessionSource::ClaudeCode;
+                let copy = copied.then(|| state.indexed_copy(&session.id, file_path)).flatten();
+                match copy {
+                    Some(copy) if !state.fuller_than(file_path, &copy) => {
+                        state.mark_duplicate(file_path, &session.id, &copy);
+                        report.duplicates += 1;
+                    }
+                    copy => match index.index_session(writer, &session) {
+                        Ok(()) => {
+                            if let Some(copy) = copy {
+                                index.delete_session(writer, &copy);
+                                state.mark_duplicate(&copy, &session.id, file_path);
+                            }
+                            match copied {
+                                true => state.mark_indexed_session(file_path, &session.id),
+                                false => state.mark_indexed(file_path),
+                            }
+                            report.indexed += 1;
+                            bytes = session.messages.iter().map(|m| m.content.len()).sum();
+                        }
+                        Err(e) => report.failed.push((file_path.clone(), format!("{:#}", e))),
+                    },
                 }
-                // Mark as indexed even if empty (so we don't reprocess it)
-                state.mark_indexed(file_path);
-                indexed += 1;
-            }
-            Err(_) => {
-                // Skip failed files (they might be incomplete/corrupted)
-                // Don't mark as indexed so we retry next time
             }
+            // Likely incomplete or corrupted; don't mark as indexed so we retry next time
+            Err(e) => report.failed.push((file_path.clone(), format!("{:#}", e))),
         }
 
         // Progress update every 50 files or at the end
@@ -81,9 +254,10 @@ pub fn index_files(
             }
         }
 
-        // Commit and notify for reload every 200 files
-        if (i + 1) % 200 == 0 {
+        // Commit and notify for reload as time passes and content piles up
+        if cadence.add(bytes) && i + 1 < total {
             writer.commit()?;
+            cadence.committed();
             if let Some(ref mut callback) = on_reload {
                 callback();
             }
@@ -93,5 +267,40 @@ pub fn index_files(
     // Final commit
     writer.commit()?;
 
-    Ok(indexed)
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_commit_cadence() {
+        let start = Instant::now();
+        let elapsed = Cell::new(Duration::ZERO);
+        let mut cadence =
+            CommitCadence::with_clock(Duration::from_secs(2), 1000, || start + elapsed.get());
+
+        // Small files, quickly: no commit until the time is up
+        assert!(!cadence.add(10));
+        elapsed.set(Duration::from_millis(1900));
+        assert!(!cadence.add(10));
+        elapsed.set(Duration::from_millis(2000));
+        assert!(cadence.add(10));
+        cadence.committed();
+
+        // Lots of content commits early
+        elapsed.set(Duration::from_millis(2100));
+        assert!(!cadence.add(600));
+        assert!(cadence.add(600));
+        cadence.committed();
+        assert!(!cadence.add(0));
+
+        // The interval counts from the last commit
+        elapsed.set(Duration::from_millis(4000));
+        assert!(!cadence.add(0));
+        elapsed.set(Duration::from_millis(4100));
+        assert!(cadence.add(0));
+    }
 }
@@ -1,9 +1,13 @@
+pub mod cache;
 mod indexer;
 mod schema;
 mod state;
 mod sync;
 
-pub use indexer::{discover_and_sort_files, index_files, IndexProgress};
-pub use schema::SessionIndex;
+pub use indexer::{
+    discover_and_sort_files, expired_files, files_to_index, index_files, prune_files, purge_files,
+    retained_files, stale_files, IndexProgress, IndexReport,
+};
+pub use schema::{stem_language, SessionIndex};
 pub use state::IndexState;
 pub use sync::ensure_index_fresh;
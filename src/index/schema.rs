@@ -1,25 +1,83 @@
-use crate::session::{SearchResult, Session, SessionSource};
+use crate::config::IndexConfig;
+use crate::redact::Redactor;
+use crate::session::{
+    MatchHit, Message, ModelUsage, Role, SearchResult, Session, SessionSource, SessionUsage,
+};
 use anyhow::{Context, Result};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use tantivy::collector::TopDocs;
-use tantivy::query::{BooleanQuery, BoostQuery, Occur, PhraseQuery, Query, QueryParser, TermQuery};
+use tantivy::query::{
+    BooleanQuery, BoostQuery, Occur, PhraseQuery, Query, QueryParser, RangeQuery, TermQuery,
+};
 use tantivy::schema::*;
 use tantivy::snippet::SnippetGenerator;
-use tantivy::{doc, Index, IndexReader, IndexWriter, ReloadPolicy};
+use tantivy::tokenizer::{
+    Language, LowerCaser, RawTokenizer, RemoveLongFilter, SimpleTokenizer, Stemmer, TextAnalyzer,
+};
+use tantivy::{doc, DocSet, Index, IndexReader, IndexWriter, ReloadPolicy, TERMINATED};
 
 /// Get the default cache directory for the index
 pub fn default_index_path() -> PathBuf {
-    std::env::var("RECALL_HOME_OVERRIDE")
-        .map(|h| PathBuf::from(h).join(".cache").join("recall").join("index"))
-        .unwrap_or_else(|_| {
-            dirs::cache_dir()
-                .unwrap_or_else(|| PathBuf::from("."))
-                .join("recall")
-                .join("index")
-        })
+    super::cache::dir().join("index")
+}
+
+/// The writer buffer in low-memory mode: the least Tantivy accepts for a thread
+const LOW_MEMORY_HEAP: usize = 15_000_000;
+
+/// Most content buffered between commits in low-memory mode
+const LOW_MEMORY_COMMIT: usize = 8_000_000;
+
+/// How much of each message is indexed in low-memory mode
+const LOW_MEMORY_CONTENT: usize = 64 * 1024;
+
+/// How much more a match in a session's title counts than one in its messages
+const TITLE_BOOST: f32 = 3.0;
+
+/// Languages `stemming` accepts, by config name
+const STEM_LANGUAGES: [(&str, Language); 18] = [
+    ("arabic", Language::Arabic),
+    ("danish", Language::Danish),
+    ("dutch", Language::Dutch),
+    ("english", Language::English),
+    ("finnish", Language::Finnish),
+    ("french", Language::French),
+    ("german", Language::German),
+    ("greek", Language::Greek),
+    ("hungarian", Language::Hungarian),
+    ("italian", Language::Italian),
+    ("norwegian", Language::Norwegian),
+    ("portuguese", Language::Portuguese),
+    ("romanian", Language::Romanian),
+    ("russian", Language::Russian),
+    ("spanish", Language::Spanish),
+    ("swedish", Language::Swedish),
+    ("tamil", Language::Tamil),
+    ("turkish", Language::Turkish),
+];
+
+/// The language named by `stemming` in the config, or None for "off"
+pub fn stem_language(name: &str) -> Result<Option<Language>> {
+    if name == "off" {
+        return Ok(None);
+    }
+    match STEM_LANGUAGES.iter().find(|(n, _)| *n == name) {
+        Some(&(_, language)) => Ok(Some(language)),
+        None => {
+            let names: Vec<&str> = STEM_LANGUAGES.iter().map(|(n, _)| *n).collect();
+            anyhow::bail!("Unknown stemming '{}'. Valid: off, {}", name, names.join(", "))
+        }
+    }
+}
+
+/// Name the content tokenizer for `language` is registered under
+fn stem_tokenizer(name: &str) -> String {
+    format!("stem_{}", name)
 }
 
-/// Wrapper around Tantivy index for session search
+/// Wrapper around Tantivy index for session search. Clones share the same index.
+#[derive(Clone)]
 pub struct SessionIndex {
     index: Index,
     reader: IndexReader,
@@ -34,27 +92,82 @@ pub struct SessionIndex {
     timestamp: Field,
     content: Field,
     message_index: Field,
+    files: Field,
+    usage: Field,
+    tag: Field,
+    note: Field,
+    title: Field,
+    heading: Field,
+    /// Maximum length of result snippets
+    snippet_chars: usize,
+    /// Masks secrets in content before it's written (`redact` in the config)
+    redactor: Option<&'static Redactor>,
+    /// Writer buffer size, commit cadence, and low-memory mode (`[index]` in the config)
+    memory: IndexConfig,
+    /// Where the tags, notes, and titles indexed with each session are kept
+    sidecar_dir: PathBuf,
 }
 
 impl SessionIndex {
-    /// Open existing index or create a new one at the default path
+    /// Open existing index or create a new one at the default path, or an empty in-memory
+    /// one with `privacy.ephemeral`
     pub fn open_default() -> Result<Self> {
+        if super::cache::ephemeral() {
+            return Self::in_memory();
+        }
         Self::open_or_create(&default_index_path())
     }
 
+    /// An empty index that lives only as long as this process
+    pub fn in_memory() -> Result<Self> {
+        Self::from_index(Index::create_in_ram(Self::build_schema(&Self::stemming())))
+    }
+
     /// Open existing index or create a new one
     pub fn open_or_create(index_path: &Path) -> Result<Self> {
-        std::fs::create_dir_all(index_path)?;
+        if let Some(parent) = index_path.parent() {
+            super::cache::create_private_dir(parent)?;
+        }
+        super::cache::create_private_dir(index_path)?;
 
-        let schema = Self::build_schema();
+        let schema = Self::build_schema(&Self::stemming());
 
-        let index = if index_path.join("meta.json").exists() {
-            Index::open_in_dir(index_path).context("Failed to open existing index")?
-        } else {
-            Index::create_in_dir(index_path, schema.clone())
-                .context("Failed to create new index")?
+        let mut index = None;
+        if index_path.join("meta.json").exists() {
+            let existing = Index::open_in_dir(index_path).context("Failed to open existing index")?;
+            if existing.schema() == schema {
+                index = Some(existing);
+            } else {
+                // Built by an older version or with other stemming: start over (IndexState's
+                // version and stemming checks reindex every file)
+                drop(existing);
+                std::fs::remove_dir_all(index_path).context("Failed to remove outdated index")?;
+                super::cache::create_private_dir(index_path)?;
+            }
+        }
+        let index = match index {
+            Some(index) => index,
+            None => Index::create_in_dir(index_path, schema.clone())
+                .context("Failed to create new index")?,
         };
+        // Files from before the cache was made private
+        super::cache::restrict_files(index_path)?;
+        Self::from_index(index)
+    }
 
+    fn from_index(index: Index) -> Result<Self> {
+        for &(name, language) in &STEM_LANGUAGES {
+            let analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+                .filter(RemoveLongFilter::limit(40))
+                .filter(LowerCaser)
+                .filter(Stemmer::new(language))
+                .build();
+            index.tokenizers().register(&stem_tokenizer(name), analyzer);
+        }
+        // Whole tags, so `tag:Billing-Bug` finds exactly "billing-bug"
+        let tag = TextAnalyzer::builder(RawTokenizer::default()).filter(LowerCaser).build();
+        index.tokenizers().register("tag", tag);
+        let schema = index.schema();
         let reader = index
             .reader_builder()
             .reload_policy(ReloadPolicy::Manual)
@@ -72,15 +185,61 @@ impl SessionIndex {
             timestamp: schema.get_field("timestamp").unwrap(),
             content: schema.get_field("content").unwrap(),
             message_index: schema.get_field("message_index").unwrap(),
+            files: schema.get_field("files").unwrap(),
+            usage: schema.get_field("usage").unwrap(),
+            tag: schema.get_field("tag").unwrap(),
+            note: schema.get_field("note").unwrap(),
+            title: schema.get_field("title").unwrap(),
+            heading: schema.get_field("heading").unwrap(),
+            snippet_chars: crate::config::get().search.snippet_chars,
+            redactor: crate::redact::configured(),
+            memory: crate::config::get().index.clone(),
+            sidecar_dir: super::cache::dir(),
             schema,
         })
     }
 
-    fn build_schema() -> Schema {
+    /// Make result snippets up to `chars` long (`search.snippet_chars` by default)
+    pub fn set_snippet_chars(&mut self, chars: usize) {
+        self.snippet_chars = chars;
+    }
+
+    /// Mask secrets with `redactor` when indexing (the config's redactor by default)
+    pub fn set_redactor(&mut self, redactor: Option<&'static Redactor>) {
+        self.redactor = redactor;
+    }
+
+    /// Read tags, notes, and titles from the sidecars in `dir` (the cache dir by default)
+    pub fn set_sidecar_dir(&mut self, dir: &Path) {
+        self.sidecar_dir = dir.to_path_buf();
+    }
+
+    /// Use `config`'s indexing settings (`[index]` by default)
+    pub fn set_memory(&mut self, config: &IndexConfig) {
+        self.memory = config.clone();
+    }
+
+    /// How long and how much content to buffer between commits while indexing. Low-memory
+    /// mode commits after at most 8 MB.
+    pub fn commit_cadence(&self) -> (std::time::Duration, usize) {
+        let mut max_bytes = self.memory.commit_mb * 1_000_000;
+        if self.memory.low_memory {
+            max_bytes = max_bytes.min(LOW_MEMORY_COMMIT);
+        }
+        (std::time::Duration::from_millis(self.memory.commit_interval_ms), max_bytes)
+    }
+
+    /// The `stemming` setting the index is built with
+    fn stemming() -> String {
+        crate::config::get().stemming.clone()
+    }
+
+    /// The schema for content stemmed per `stemming` ("off" keeps Tantivy's default tokenizer)
+    fn build_schema(stemming: &str) -> Schema {
         let mut builder = Schema::builder();
 
-        // Stored metadata fields
-        builder.add_text_field("session_id", STRING | STORED);
+        // Stored metadata fields (session_id is also fast, to group messages by session)
+        builder.add_text_field("session_id", STRING | STORED | FAST);
         builder.add_text_field("source", STRING | STORED);
         builder.add_text_field("file_path", STRING | STORED);
         builder.add_text_field("cwd", STRING | STORED);
@@ -89,20 +248,53 @@ impl SessionIndex {
         // Timestamp for recency sorting (stored as i64 unix timestamp)
         builder.add_i64_field("timestamp", INDEXED | STORED | FAST);
 
-        // Message index within the session (for match-recency)
-        builder.add_u64_field("message_index", STORED);
+        // Message index within the session (for match-recency; indexed to find each
+        // session's first message)
+        builder.add_u64_field("message_index", INDEXED | STORED);
+
+        // Searchable content field, stemmed so "indexing" finds "indexed"
+        let content = match stemming {
+            "off" => TEXT | STORED,
+            language => {
+                let indexing = TextFieldIndexing::default()
+                    .set_tokenizer(&stem_tokenizer(language))
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+                TextOptions::default().set_indexing_options(indexing).set_stored()
+            }
+        };
+        builder.add_text_field("content", content.clone());
+
+        // The session's note and its title (the one it was renamed to, or the agent's), on its
+        // first message only, searched along with content
+        builder.add_text_field("note", content.clone());
+        builder.add_text_field("title", content);
+
+        // The title shown in results (see `Session::title`) on every message
+        builder.add_text_field("heading", STORED);
+
+        // Paths of files touched by tool calls after this message (for reverse lookup)
+        builder.add_text_field("files", STRING);
 
-        // Searchable content field
-        builder.add_text_field("content", TEXT | STORED);
+        // Token usage per model as JSON, on each session's first message only
+        builder.add_text_field("usage", STORED);
+
+        // The session's tags on every message, for `tag:name` queries
+        let tag = TextFieldIndexing::default()
+            .set_tokenizer("tag")
+            .set_index_option(IndexRecordOption::Basic);
+        builder.add_text_field("tag", TextOptions::default().set_indexing_options(tag));
 
         builder.build()
     }
 
     /// Get a writer for indexing operations
     pub fn writer(&self) -> Result<IndexWriter> {
-        self.index
-            .writer(50_000_000) // 50MB heap
-            .context("Failed to create index writer")
+        let writer = if self.memory.low_memory {
+            self.index.writer_with_num_threads(1, LOW_MEMORY_HEAP)
+        } else {
+            self.index.writer(self.memory.writer_heap_mb * 1_000_000)
+        };
+        writer.context("Failed to create index writer")
     }
 
     /// Index a single session (all its messages)
@@ -110,8 +302,14 @@ impl SessionIndex {
         let timestamp_secs = session.timestamp.timestamp();
 
         // Index each message separately for match-recency ranking
+        let last = session.messages.len().saturating_sub(1);
+        let tags = crate::tags::get_in(&self.sidecar_dir, &session.id);
+        let note = crate::notes::get_in(&self.sidecar_dir, &session.id);
+        let rename = crate::titles::get_in(&self.sidecar_dir, &session.id);
+        let heading = session.title_renamed(rename.clone());
+        let title = rename.or_else(|| session.title.clone());
         for (idx, message) in session.messages.iter().enumerate() {
-            let doc = doc!(
+            let mut doc = doc!(
                 self.session_id => session.id.clone(),
                 self.source => session.source.as_str(),
                 self.file_path => session.file_path.to_string_lossy().to_string(),
@@ -119,14 +317,74 @@ impl SessionIndex {
                 self.git_branch => session.git_branch.clone().unwrap_or_default(),
                 self.timestamp => timestamp_secs,
                 self.message_index => idx as u64,
-                self.content => message.content.clone(),
+                self.heading => heading.clone(),
+                self.content => {
+                    let text = self.indexed_text(message);
+                    let content = self.indexed_part(&text);
+                    match self.redactor {
+                        Some(redactor) => redactor.redact(content).into_owned(),
+                        None => content.to_string(),
+                    }
+                },
             );
+            for touch in &session.files_touched {
+                if touch.message_index.min(last) == idx {
+                    doc.add_text(self.files, &touch.path);
+                }
+            }
+            for tag in &tags {
+                doc.add_text(self.tag, tag);
+            }
+            if let (0, Some(note)) = (idx, &note) {
+                doc.add_text(self.note, note);
+            }
+            if let (0, Some(title)) = (idx, &title) {
+                doc.add_text(self.title, title);
+            }
+            if idx == 0 && !session.usage.is_empty() {
+                doc.add_text(self.usage, serde_json::to_string(&session.usage)?);
+            }
             writer.add_document(doc)?;
         }
 
         Ok(())
     }
 
+    /// A message's content, followed by its tool calls' commands and output when
+    /// `index.tool_output` is on. Compacted context is left out unless
+    /// `index.compacted_context` is on.
+    fn indexed_text<'m>(&self, message: &'m Message) -> Cow<'m, str> {
+        if message.role == Role::Context && !self.memory.compacted_context {
+            return Cow::Borrowed("");
+        }
+        if !self.memory.tool_output || message.tool_calls.is_empty() {
+            return Cow::Borrowed(&message.content);
+        }
+        let mut text = message.content.clone();
+        for call in &message.tool_calls {
+            for part in std::iter::once(&call.input).chain(&call.output) {
+                if !text.is_empty() {
+                    text.push_str("\n\n");
+                }
+                text.push_str(part);
+            }
+        }
+        Cow::Owned(text)
+    }
+
+    /// The part of a message's content that's indexed: all of it, or the first 64 KB in
+    /// low-memory mode
+    fn indexed_part<'c>(&self, content: &'c str) -> &'c str {
+        if !self.memory.low_memory || content.len() <= LOW_MEMORY_CONTENT {
+            return content;
+        }
+        let mut end = LOW_MEMORY_CONTENT;
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+        &content[..end]
+    }
+
     /// Delete all documents for a session (by file path)
     pub fn delete_session(&self, writer: &mut IndexWriter, file_path: &Path) {
         let term = tantivy::Term::from_field_text(
@@ -149,7 +407,10 @@ impl SessionIndex {
         }
 
         let searcher = self.reader.searcher();
-        let query_parser = QueryParser::for_index(&self.index, vec![self.content]);
+        let mut query_parser =
+            QueryParser::for_index(&self.index, vec![self.content, self.note, self.title]);
+        // A title says what the whole session is about
+        query_parser.set_field_boost(self.title, TITLE_BOOST);
 
         let base_query = query_parser
             .parse_query(query_str)
@@ -157,7 +418,8 @@ impl SessionIndex {
 
         // Boost exact phrase matches for multi-word queries
         // Use the same tokenizer that indexed the content to tokenize the query
-        let query: Box<dyn Query> = if let Some(mut tokenizer) = self.index.tokenizers().get("default") {
+        let tokenizer = self.index.tokenizer_for_field(self.content).ok();
+        let query: Box<dyn Query> = if let Some(mut tokenizer) = tokenizer {
             let mut terms: Vec<(usize, tantivy::Term)> = Vec::new();
             let mut token_stream = tokenizer.token_stream(query_str);
             token_stream.process(&mut |token| {
@@ -184,14 +446,21 @@ impl SessionIndex {
         // Create snippet generator from the query - Tantivy knows what terms matched
         let mut snippet_generator =
             SnippetGenerator::create(&searcher, &*query, self.content)?;
-        snippet_generator.set_max_num_chars(200);
+        snippet_generator.set_max_num_chars(self.snippet_chars);
+        // For sessions found by their note or title alone
+        let mut extra_snippets = Vec::new();
+        for field in [self.note, self.title] {
+            let mut generator = SnippetGenerator::create(&searcher, &*query, field)?;
+            generator.set_max_num_chars(self.snippet_chars);
+            extra_snippets.push((field, generator));
+        }
 
         // Get more results than limit to group by session
+        let query = self.without_disabled_sources(query);
         let top_docs = searcher.search(&query, &TopDocs::with_limit(limit * 10))?;
 
-        // Group by session, keeping track of the highest-scoring message per session
-        let mut session_results: std::collections::HashMap<String, (f32, SearchResult)> =
-            std::collections::HashMap::new();
+        // Group the matched messages by session
+        let mut session_hits: HashMap<String, Vec<SearchResult>> = HashMap::new();
 
         for (score, doc_addr) in top_docs {
             let doc: tantivy::TantivyDocument = searcher.doc(doc_addr)?;
@@ -227,6 +496,12 @@ impl SessionIndex {
                 .map(|s| s.to_string())
                 .filter(|s| !s.is_empty());
 
+            let title = doc
+                .get_first(self.heading)
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .filter(|s| !s.is_empty());
+
             let timestamp_secs = doc
                 .get_first(self.timestamp)
                 .and_then(|v| v.as_i64())
@@ -238,17 +513,34 @@ impl SessionIndex {
                 .unwrap_or(0) as usize;
 
             // Use Tantivy's SnippetGenerator for accurate snippet with highlights
-            let tantivy_snippet = snippet_generator.snippet_from_doc(&doc);
+            let mut tantivy_snippet = snippet_generator.snippet_from_doc(&doc);
+            let mut content = doc.get_first(self.content).and_then(|v| v.as_str()).unwrap_or("");
+            // Found by its note or title alone: show that
+            for (field, generator) in &extra_snippets {
+                if !tantivy_snippet.fragment().is_empty() {
+                    break;
+                }
+                tantivy_snippet = generator.snippet_from_doc(&doc);
+                content = doc.get_first(*field).and_then(|v| v.as_str()).unwrap_or("");
+            }
             let fragment = tantivy_snippet.fragment();
             let highlighted = tantivy_snippet.highlighted();
 
             // Store original fragment for finding match in wrapped text
             let match_fragment = fragment.to_string();
-            let snippet = fragment.replace('\n', " ");
-            let match_spans: Vec<(usize, usize)> = highlighted
+            let spans: Vec<(usize, usize)> = highlighted
                 .iter()
                 .map(|r| (r.start, r.end))
                 .collect();
+            let (snippet, match_spans) = match content.find(fragment) {
+                Some(start) if !fragment.is_empty() => tidy_snippet(
+                    content,
+                    start..start + fragment.len(),
+                    &spans,
+                    self.snippet_chars,
+                ),
+                _ => (fragment.replace('\n', " "), spans),
+            };
 
             let result = SearchResult {
                 session: Session {
@@ -260,27 +552,19 @@ impl SessionIndex {
                     timestamp: chrono::DateTime::from_timestamp(timestamp_secs, 0)
                         .unwrap_or_default(),
                     messages: Vec::new(), // We don't load all messages for search results
+                    files_touched: Vec::new(),
+                    usage: Vec::new(),
+                    title,
                 },
                 score,
                 matched_message_index: message_index,
                 snippet,
                 match_spans,
                 match_fragment,
+                more_matches: Vec::new(),
             };
 
-            // Keep the highest-scoring result for each session
-            // But prefer more recent message indices (higher = more recent)
-            session_results
-                .entry(session_id)
-                .and_modify(|(existing_score, existing_result)| {
-                    // Prefer higher message index (more recent) if scores are similar
-                    let recency_bonus = (message_index as f32) * 0.01;
-                    if score + recency_bonus > *existing_score {
-                        *existing_score = score + recency_bonus;
-                        *existing_result = result.clone();
-                    }
-                })
-                .or_insert((score, result));
+            session_hits.entry(session_id).or_default().push(result);
         }
 
         // Sort by combined relevance + recency score
@@ -288,7 +572,7 @@ impl SessionIndex {
         let now = chrono::Utc::now().timestamp() as f64;
         let half_life_secs = 7.0 * 24.0 * 3600.0; // 7 days
 
-        let mut results: Vec<_> = session_results.into_values().map(|(_, r)| r).collect();
+        let mut results: Vec<_> = session_hits.into_values().map(best_hit).collect();
         results.sort_by(|a, b| {
             let age_a = (now - a.session.timestamp.timestamp() as f64).max(0.0);
             let age_b = (now - b.session.timestamp.timestamp() as f64).max(0.0);
@@ -300,7 +584,11 @@ impl SessionIndex {
             let final_a = (a.score as f64) * recency_a;
             let final_b = (b.score as f64) * recency_b;
 
-            final_b.partial_cmp(&final_a).unwrap_or(std::cmp::Ordering::Equal)
+            final_b
+                .partial_cmp(&final_a)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                // Stable order for ties so paged queries agree with each other
+                .then_with(|| a.session.id.cmp(&b.session.id))
         });
         results.truncate(limit);
 
@@ -309,36 +597,25 @@ impl SessionIndex {
 
     /// Get recent sessions sorted by timestamp (most recent first)
     pub fn recent(&self, limit: usize) -> Result<Vec<SearchResult>> {
-        use tantivy::collector::TopDocs;
         use tantivy::query::AllQuery;
 
         let searcher = self.reader.searcher();
 
-        // Get all docs sorted by timestamp descending
-        // Fetch many more docs since each session has multiple messages indexed
-        let top_docs = searcher.search(
-            &AllQuery,
-            &TopDocs::with_limit(limit * 100).order_by_fast_field::<i64>("timestamp", tantivy::Order::Desc),
-        )?;
-
-        // Group by session, keeping only the most recent per session
-        let mut session_results: std::collections::HashMap<String, SearchResult> =
-            std::collections::HashMap::new();
-
-        for (_score, doc_addr) in top_docs {
+        // Each session's latest message, from the fast fields alone so that long sessions
+        // can't crowd shorter ones out
+        let mut latest: Vec<(String, i64, tantivy::DocAddress)> = searcher
+            .search(&self.without_disabled_sources(Box::new(AllQuery)), &LatestPerSession)?
+            .into_iter()
+            .map(|(id, (timestamp, addr))| (id, timestamp, addr))
+            .collect();
+        // Ties by ID so paged queries agree with each other
+        latest.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        latest.truncate(limit);
+
+        let mut results = Vec::with_capacity(latest.len());
+        for (session_id, timestamp_secs, doc_addr) in latest {
             let doc: tantivy::TantivyDocument = searcher.doc(doc_addr)?;
 
-            let session_id = doc
-                .get_first(self.session_id)
-                .and_then(|v| v.as_str())
-                .unwrap_or("")
-                .to_string();
-
-            // Skip if we already have this session
-            if session_results.contains_key(&session_id) {
-                continue;
-            }
-
             let source_str = doc
                 .get_first(self.source)
                 .and_then(|v| v.as_str())
@@ -364,10 +641,11 @@ impl SessionIndex {
                 .map(|s| s.to_string())
                 .filter(|s| !s.is_empty());
 
-            let timestamp_secs = doc
-                .get_first(self.timestamp)
-                .and_then(|v| v.as_i64())
-                .unwrap_or(0);
+            let title = doc
+                .get_first(self.heading)
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+                .filter(|s| !s.is_empty());
 
             let content = doc
                 .get_first(self.content)
@@ -375,13 +653,14 @@ impl SessionIndex {
                 .unwrap_or("")
                 .to_string();
 
-            // Use first part of content as snippet
-            let snippet: String = content.chars().take(200).collect();
+            // Use the session's note, or else the first part of content, as snippet
+            let content = crate::notes::get_in(&self.sidecar_dir, &session_id).unwrap_or(content);
+            let snippet: String = content.chars().take(self.snippet_chars).collect();
             let snippet = snippet.replace('\n', " ");
 
             let result = SearchResult {
                 session: Session {
-                    id: session_id.clone(),
+                    id: session_id,
                     source,
                     file_path: std::path::PathBuf::from(&file_path),
                     cwd,
@@ -389,27 +668,163 @@ impl SessionIndex {
                     timestamp: chrono::DateTime::from_timestamp(timestamp_secs, 0)
                         .unwrap_or_default(),
                     messages: Vec::new(),
+                    files_touched: Vec::new(),
+                    usage: Vec::new(),
+                    title,
                 },
                 score: 0.0,
                 matched_message_index: 0,
                 snippet,
                 match_spans: Vec::new(),
                 match_fragment: String::new(),
+                more_matches: Vec::new(),
             };
 
-            session_results.insert(session_id, result);
+            results.push(result);
+        }
+
+        Ok(results)
+    }
 
-            if session_results.len() >= limit {
-                break;
+    /// File paths and times of sessions whose latest message is before `cutoff` (Unix seconds)
+    pub fn sessions_before(&self, cutoff: i64) -> Result<Vec<(PathBuf, i64)>> {
+        let searcher = self.reader.searcher();
+        let latest = searcher.search(&tantivy::query::AllQuery, &LatestPerSession)?;
+        let mut sessions = Vec::new();
+        for (timestamp, doc_addr) in latest.into_values() {
+            if timestamp >= cutoff {
+                continue;
+            }
+            let doc: tantivy::TantivyDocument = searcher.doc(doc_addr)?;
+            if let Some(file_path) = doc.get_first(self.file_path).and_then(|v| v.as_str()) {
+                sessions.push((PathBuf::from(file_path), timestamp));
             }
         }
+        Ok(sessions)
+    }
 
-        // Sort by timestamp descending
-        let mut results: Vec<_> = session_results.into_values().collect();
-        results.sort_by(|a, b| b.session.timestamp.cmp(&a.session.timestamp));
-        results.truncate(limit);
+    /// Token usage of every session active since `since` (all sessions if None), read from
+    /// the index without parsing session files
+    pub fn usage(&self, since: Option<i64>) -> Result<Vec<SessionUsage>> {
+        let searcher = self.reader.searcher();
 
-        Ok(results)
+        let first_message = TermQuery::new(
+            tantivy::Term::from_field_u64(self.message_index, 0),
+            IndexRecordOption::Basic,
+        );
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![(Occur::Must, Box::new(first_message))];
+        if let Some(since) = since {
+            let range = RangeQuery::new_i64_bounds(
+                "timestamp".to_string(),
+                std::ops::Bound::Included(since),
+                std::ops::Bound::Unbounded,
+            );
+            clauses.push((Occur::Must, Box::new(range)));
+        }
+        let query = self.without_disabled_sources(Box::new(BooleanQuery::new(clauses)));
+
+        let mut sessions = Vec::new();
+        for doc_addr in searcher.search(&query, &tantivy::collector::DocSetCollector)? {
+            let doc: tantivy::TantivyDocument = searcher.doc(doc_addr)?;
+            let text = |field: Field| {
+                doc.get_first(field)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string()
+            };
+
+            let usage: Vec<ModelUsage> = match doc.get_first(self.usage).and_then(|v| v.as_str()) {
+                Some(json) => serde_json::from_str(json).unwrap_or_default(),
+                None => Vec::new(),
+            };
+            let timestamp_secs = doc
+                .get_first(self.timestamp)
+                .and_then(|v| v.as_i64())
+                .unwrap_or(0);
+
+            sessions.push(SessionUsage {
+                session_id: text(self.session_id),
+                source: SessionSource::parse(&text(self.source)).unwrap_or(SessionSource::ClaudeCode),
+                cwd: text(self.cwd),
+                timestamp: chrono::DateTime::from_timestamp(timestamp_secs, 0).unwrap_or_default(),
+                usage,
+            });
+        }
+
+        sessions.sort_by(|a, b| {
+            b.timestamp
+                .cmp(&a.timestamp)
+                .then_with(|| a.session_id.cmp(&b.session_id))
+        });
+        Ok(sessions)
+    }
+
+    /// File paths of sessions whose tool calls touched `path`
+    pub fn sessions_touching(&self, path: &str) -> Result<Vec<PathBuf>> {
+        let searcher = self.reader.searcher();
+        let term = tantivy::Term::from_field_text(self.files, path);
+        let query =
+            self.without_disabled_sources(Box::new(TermQuery::new(term, IndexRecordOption::Basic)));
+
+        let doc_addrs = searcher.search(&query, &tantivy::collector::DocSetCollector)?;
+
+        let mut paths = HashSet::new();
+        for doc_addr in doc_addrs {
+            let doc: tantivy::TantivyDocument = searcher.doc(doc_addr)?;
+            if let Some(file_path) = doc.get_first(self.file_path).and_then(|v| v.as_str()) {
+                paths.insert(PathBuf::from(file_path));
+            }
+        }
+
+        Ok(paths.into_iter().collect())
+    }
+
+    /// File paths of every session in the index (read from the term dictionary, skipping deleted docs)
+    pub fn indexed_file_paths(&self) -> Result<HashSet<PathBuf>> {
+        let searcher = self.reader.searcher();
+        let mut paths = HashSet::new();
+
+        for segment in searcher.segment_readers() {
+            let inverted = segment.inverted_index(self.file_path)?;
+            let alive = segment.alive_bitset();
+            let mut terms = inverted.terms().stream()?;
+
+            while terms.advance() {
+                // A deleted session keeps its term until segments merge
+                if let Some(alive) = alive {
+                    let mut postings =
+                        inverted.read_postings_from_terminfo(terms.value(), IndexRecordOption::Basic)?;
+                    let mut doc = postings.doc();
+                    while doc != TERMINATED && !alive.is_alive(doc) {
+                        doc = postings.advance();
+                    }
+                    if doc == TERMINATED {
+                        continue;
+                    }
+                }
+                paths.insert(PathBuf::from(String::from_utf8_lossy(terms.key()).into_owned()));
+            }
+        }
+
+        Ok(paths)
+    }
+
+    /// Exclude documents from sources disabled in the config, which stay in the index
+    /// until the next reindex
+    fn without_disabled_sources(&self, query: Box<dyn Query>) -> Box<dyn Query> {
+        let disabled = crate::config::get().sources.disabled();
+        if disabled.is_empty() {
+            return query;
+        }
+        let mut clauses = vec![(Occur::Must, query)];
+        for source in disabled {
+            let term = tantivy::Term::from_field_text(self.source, source.as_str());
+            clauses.push((
+                Occur::MustNot,
+                Box::new(TermQuery::new(term, IndexRecordOption::Basic)) as Box<dyn Query>,
+            ));
+        }
+        Box::new(BooleanQuery::new(clauses))
     }
 
     /// Look up a session by ID and return its file path
@@ -436,3 +851,534 @@ impl SessionIndex {
     }
 }
 
+/// Matched messages are near-duplicates when this much of the smaller fragment's word trigrams
+/// are in the other's too (so a paragraph quoted with a preamble still counts)
+const DUPLICATE_SIMILARITY: f64 = 0.8;
+
+/// Most matched messages kept per result besides the one it shows
+const MORE_MATCHES: usize = 5;
+
+/// Pick the message a session's result shows. Near-identical matches (the same paragraph quoted
+/// again after compaction or a retry) count once, as their latest copy with their best score.
+/// Among the rest the best score wins, nudged toward later messages, and exact ties go to the
+/// later one, so the pick doesn't depend on the order Tantivy returns hits in. The runners-up
+/// are kept as the result's `more_matches`.
+fn best_hit(mut hits: Vec<SearchResult>) -> SearchResult {
+    hits.sort_by_key(|hit| std::cmp::Reverse(hit.matched_message_index));
+
+    // Latest first, so each group's representative is its latest message
+    let mut groups: Vec<(HashSet<String>, SearchResult)> = Vec::new();
+    for hit in hits {
+        let shingles = shingles(&hit.match_fragment);
+        let duplicate_of = groups.iter_mut().find(|(group, _)| {
+            let shared = group.intersection(&shingles).count();
+            let smaller = group.len().min(shingles.len());
+            smaller > 0 && shared as f64 / smaller as f64 >= DUPLICATE_SIMILARITY
+        });
+        match duplicate_of {
+            Some((_, latest)) => latest.score = latest.score.max(hit.score),
+            None => groups.push((shingles, hit)),
+        }
+    }
+
+    let rank = |r: &SearchResult| r.score + r.matched_message_index as f32 * 0.01;
+    let mut picks: Vec<SearchResult> = groups.into_iter().map(|(_, hit)| hit).collect();
+    // Stable, so exact ties keep the later message first
+    picks.sort_by(|a, b| rank(b).total_cmp(&rank(a)));
+    let mut picks = picks.into_iter();
+    let mut best = picks.next().expect("a session has at least one hit");
+    best.more_matches = picks.take(MORE_MATCHES).map(MatchHit::from).collect();
+    best
+}
+
+/// Word trigrams of `text`, case-insensitive (the words themselves when there are fewer than 3)
+fn shingles(text: &str) -> HashSet<String> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if words.len() < 3 {
+        return words.into_iter().collect();
+    }
+    words.windows(3).map(|w| w.join(" ")).collect()
+}
+
+/// How far (in chars) a snippet edge may move to reach a sentence or line boundary
+const BOUNDARY_WINDOW: usize = 40;
+
+/// Move the edges of `text[range]`, a Tantivy fragment with highlights at `spans` (relative to
+/// the fragment), to nearby sentence or line boundaries, or at least off mid-word cuts, without
+/// cutting into a highlight or growing past `max_chars`. Returns the snippet (newlines as
+/// spaces, "…" where text was cut) and the spans re-based onto it.
+fn tidy_snippet(
+    text: &str,
+    range: std::ops::Range<usize>,
+    spans: &[(usize, usize)],
+    max_chars: usize,
+) -> (String, Vec<(usize, usize)>) {
+    let first = spans.iter().map(|s| range.start + s.0).min().unwrap_or(range.start);
+    let last = spans.iter().map(|s| range.start + s.1).max().unwrap_or(range.start);
+
+    // Prefer more context; if that's over budget, only shrink the fragment, which keeps it
+    // no longer than Tantivy made it
+    let fits = |(start, end): (usize, usize)| {
+        let cut = |s: &str| usize::from(!s.trim().is_empty());
+        let markers = cut(&text[..start]) + cut(&text[end..]);
+        text[start..end].chars().count() + markers <= max_chars
+    };
+    let grown = snippet_edges(text, &range, first, last, true);
+    let (start, end) =
+        if fits(grown) { grown } else { snippet_edges(text, &range, first, last, false) };
+
+    let mut snippet = String::new();
+    if !text[..start].trim().is_empty() {
+        snippet.push('…');
+    }
+    let offset = snippet.len();
+    snippet.push_str(&text[start..end].replace('\n', " "));
+    if !text[end..].trim().is_empty() {
+        snippet.push('…');
+    }
+    let spans = spans
+        .iter()
+        .map(|&(s, e)| (range.start + s - start + offset, range.start + e - start + offset))
+        .collect();
+    (snippet, spans)
+}
+
+/// The snippet's byte range: each edge moved to the nearest sentence or line boundary within
+/// `BOUNDARY_WINDOW` (outward only if `grow`), else off a partial word, then trimmed
+fn snippet_edges(
+    text: &str,
+    range: &std::ops::Range<usize>,
+    first: usize,
+    last: usize,
+    grow: bool,
+) -> (usize, usize) {
+    let nearest = |from: usize, to: usize, target: usize, boundary: &dyn Fn(usize) -> bool| {
+        (from..=to)
+            .filter(|&i| text.is_char_boundary(i) && boundary(i))
+            .min_by_key(|&i| i.abs_diff(target))
+    };
+    let window_before = |pos: usize| {
+        text[..pos].char_indices().rev().nth(BOUNDARY_WINDOW - 1).map_or(0, |(i, _)| i)
+    };
+    let window_after = |pos: usize| {
+        text[pos..].char_indices().nth(BOUNDARY_WINDOW).map_or(text.len(), |(i, _)| pos + i)
+    };
+
+    let lo = if grow { window_before(range.start) } else { range.start };
+    let start = nearest(lo, window_after(range.start).min(first), range.start, &|i| {
+        starts_sentence(text, i)
+    })
+    .unwrap_or_else(|| {
+        // Skip a partial word, unless that would drop into a highlight
+        let next_space = text[range.start..].find(char::is_whitespace).map(|i| range.start + i);
+        match next_space {
+            Some(space) if in_word(text, range.start) && space < first => space,
+            _ => range.start,
+        }
+    });
+
+    let hi = if grow { window_after(range.end) } else { range.end };
+    let end = nearest(window_before(range.end).max(last), hi, range.end, &|i| {
+        ends_sentence(text, i)
+    })
+    .unwrap_or_else(|| {
+        let prev_space = text[..range.end].rfind(char::is_whitespace);
+        match prev_space {
+            Some(space) if in_word(text, range.end) && space >= last => space,
+            _ => range.end,
+        }
+    });
+
+    let start = start + (text[start..end].len() - text[start..end].trim_start().len());
+    let end = start + text[start..end].trim_end().len();
+    (start, end.max(start))
+}
+
+/// Whether a sentence or line starts at `pos` (a char boundary)
+fn starts_sentence(text: &str, pos: usize) -> bool {
+    if text[pos..].chars().next().is_none_or(char::is_whitespace) {
+        return false;
+    }
+    let mut before = text[..pos].chars().rev();
+    match before.next() {
+        None | Some('\n') => true,
+        Some(' ' | '\t') => before
+            .find(|&c| c != ' ' && c != '\t')
+            .is_some_and(|c| matches!(c, '.' | '!' | '?' | '\n')),
+        _ => false,
+    }
+}
+
+/// Whether a sentence or line ends at `pos` (a char boundary)
+fn ends_sentence(text: &str, pos: usize) -> bool {
+    match (text[..pos].chars().next_back(), text[pos..].chars().next()) {
+        (None, _) => false,
+        (Some(prev), _) if prev.is_whitespace() => false,
+        (_, None | Some('\n')) => true,
+        (Some(prev), Some(next)) => matches!(prev, '.' | '!' | '?') && next.is_whitespace(),
+    }
+}
+
+/// Whether `pos` falls between two letters or digits of the same word
+fn in_word(text: &str, pos: usize) -> bool {
+    let prev = text[..pos].chars().next_back();
+    let next = text[pos..].chars().next();
+    prev.is_some_and(char::is_alphanumeric) && next.is_some_and(char::is_alphanumeric)
+}
+
+/// Collects the latest message of every session: session ID -> (timestamp, document)
+struct LatestPerSession;
+
+type Latest = HashMap<String, (i64, tantivy::DocAddress)>;
+
+/// Keep whichever of the two is newer
+fn keep_latest(latest: &mut Latest, session_id: String, entry: (i64, tantivy::DocAddress)) {
+    let slot = latest.entry(session_id).or_insert(entry);
+    if entry.0 > slot.0 {
+        *slot = entry;
+    }
+}
+
+impl tantivy::collector::Collector for LatestPerSession {
+    type Fruit = Latest;
+    type Child = LatestPerSessionSegment;
+
+    fn for_segment(
+        &self,
+        segment_ord: tantivy::SegmentOrdinal,
+        reader: &tantivy::SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        let fast_fields = reader.fast_fields();
+        Ok(LatestPerSessionSegment {
+            segment_ord,
+            session_ids: fast_fields.str("session_id")?,
+            timestamps: fast_fields.i64("timestamp")?,
+            latest: HashMap::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, fruits: Vec<Latest>) -> tantivy::Result<Latest> {
+        let mut merged = Latest::new();
+        for (session_id, entry) in fruits.into_iter().flatten() {
+            keep_latest(&mut merged, session_id, entry);
+        }
+        Ok(merged)
+    }
+}
+
+struct LatestPerSessionSegment {
+    segment_ord: tantivy::SegmentOrdinal,
+    session_ids: Option<tantivy::columnar::StrColumn>,
+    timestamps: tantivy::columnar::Column<i64>,
+    /// Session ID term ordinal -> (timestamp, doc); ordinals are only valid in this segment
+    latest: HashMap<u64, (i64, tantivy::DocId)>,
+}
+
+impl tantivy::collector::SegmentCollector for LatestPerSessionSegment {
+    type Fruit = Latest;
+
+    fn collect(&mut self, doc: tantivy::DocId, _score: tantivy::Score) {
+        let Some(ord) = self.session_ids.as_ref().and_then(|ids| ids.term_ords(doc).next()) else {
+            return;
+        };
+        let timestamp = self.timestamps.first(doc).unwrap_or(0);
+        let slot = self.latest.entry(ord).or_insert((timestamp, doc));
+        if timestamp > slot.0 {
+            *slot = (timestamp, doc);
+        }
+    }
+
+    fn harvest(self) -> Latest {
+        let mut latest = Latest::new();
+        let Some(session_ids) = self.session_ids else {
+            return latest;
+        };
+        let mut session_id = String::new();
+        for (ord, (timestamp, doc)) in self.latest {
+            if session_ids.ord_to_str(ord, &mut session_id).unwrap_or(false) {
+                let addr = tantivy::DocAddress::new(self.segment_ord, doc);
+                keep_latest(&mut latest, session_id.clone(), (timestamp, addr));
+            }
+        }
+        latest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{ToolCall, ToolStatus};
+
+    /// Tidy the fragment `text[from..to]` highlighting `word`
+    fn tidy(text: &str, from: &str, to: &str, word: &str, max_chars: usize) -> (String, String) {
+        let start = text.find(from).unwrap();
+        let end = text.find(to).unwrap() + to.len();
+        let hit = text[start..].find(word).unwrap();
+        let spans = [(hit, hit + word.len())];
+        let (snippet, spans) = tidy_snippet(text, start..end, &spans, max_chars);
+        assert_eq!(spans.len(), 1);
+        let highlighted = snippet[spans[0].0..spans[0].1].to_string();
+        (snippet, highlighted)
+    }
+
+    #[test]
+    fn test_snippet_snaps_to_sentences() {
+        let text = "We tried a few things first. The parser failed because the tokenizer \
+                    dropped quotes. Then the next test hit something else entirely.";
+
+        // Cut mid-word on both sides: grows to the surrounding sentence
+        let (snippet, highlighted) = tidy(text, "rser failed", "the tokeni", "failed", 200);
+        assert_eq!(snippet, "…The parser failed because the tokenizer dropped quotes.…");
+        assert_eq!(highlighted, "failed");
+
+        // No room to grow: drops the partial words instead
+        let (snippet, highlighted) = tidy(text, "rser failed", "the tokeni", "failed", 30);
+        assert_eq!(snippet, "…failed because the…");
+        assert_eq!(highlighted, "failed");
+        assert!(snippet.chars().count() <= 30);
+
+        // Never cuts into a highlight
+        let (snippet, highlighted) = tidy(text, "rser failed", "the tokeni", "rser", 30);
+        assert_eq!(snippet, "…rser failed because the…");
+        assert_eq!(highlighted, "rser");
+    }
+
+    #[test]
+    fn test_snippet_line_boundaries_and_ellipses() {
+        // Nothing cut: no ellipses, and newlines become spaces
+        let text = "fix the flaky\nlogin test";
+        let (snippet, highlighted) = tidy(text, "fix", "test", "login", 200);
+        assert_eq!(snippet, "fix the flaky login test");
+        assert_eq!(highlighted, "login");
+
+        // Lines count as boundaries, and multibyte text keeps offsets on char boundaries
+        let text = "première ligne\nle café était froid aujourd'hui\ntroisième ligne ici";
+        let (snippet, highlighted) = tidy(text, "afé", "aujourd", "était", 200);
+        assert_eq!(snippet, "…le café était froid aujourd'hui…");
+        assert_eq!(highlighted, "était");
+    }
+
+    fn index_messages(index: &SessionIndex, id: &str, messages: &[&str]) {
+        let session = Session {
+            id: id.to_string(),
+            source: SessionSource::ClaudeCode,
+            file_path: PathBuf::from(format!("/sessions/{}.jsonl", id)),
+            cwd: "/w".to_string(),
+            git_branch: None,
+            timestamp: chrono::Utc::now(),
+            messages: messages
+                .iter()
+                .map(|content| Message {
+                    role: Role::User,
+                    content: content.to_string(),
+                    timestamp: chrono::Utc::now(),
+                    tool_calls: Vec::new(),
+                })
+                .collect(),
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: None,
+        };
+        let mut writer = index.writer().unwrap();
+        index.index_session(&mut writer, &session).unwrap();
+        writer.commit().unwrap();
+        index.reload().unwrap();
+    }
+
+    fn ids(results: &[SearchResult]) -> Vec<&str> {
+        let mut ids: Vec<&str> = results.iter().map(|r| r.session.id.as_str()).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_stemmed_search() {
+        let index = SessionIndex::from_index(Index::create_in_ram(SessionIndex::build_schema(
+            "english",
+        )))
+        .unwrap();
+        index_messages(&index, "a", &["We indexed the parsers overnight"]);
+        index_messages(&index, "b", &["the parser indexes slowly"]);
+
+        assert_eq!(ids(&index.search("indexing", 10).unwrap()), ["a", "b"]);
+        assert_eq!(ids(&index.search("parser", 10).unwrap()), ["a", "b"]);
+
+        // Exact phrases still need their words in order
+        assert_eq!(ids(&index.search("\"indexed the parsers\"", 10).unwrap()), ["a"]);
+        let results = index.search("parser indexes", 10).unwrap();
+        assert_eq!(results[0].session.id, "b", "the phrase match ranks first");
+
+        // Highlights mark the words as written
+        let result = &index.search("indexing", 10).unwrap()[0];
+        let highlighted: Vec<&str> =
+            result.match_spans.iter().map(|&(s, e)| &result.snippet[s..e]).collect();
+        assert!(["indexed", "indexes"].contains(&highlighted[0]), "{:?}", highlighted);
+
+        // Without stemming only the exact form matches
+        let plain =
+            SessionIndex::from_index(Index::create_in_ram(SessionIndex::build_schema("off")))
+                .unwrap();
+        index_messages(&plain, "a", &["We indexed the parsers overnight"]);
+        assert!(plain.search("indexing", 10).unwrap().is_empty());
+        assert_eq!(ids(&plain.search("indexed", 10).unwrap()), ["a"]);
+    }
+
+    #[test]
+    fn test_repeated_paragraph_picks_latest_copy() {
+        let index = SessionIndex::in_memory().unwrap();
+        let paragraph = "The compaction step merges small segments into larger ones so search \
+                         stays fast, and it runs after every hundred commits.";
+        index_messages(
+            &index,
+            "s1",
+            &[
+                "How do we keep the index quick?",
+                paragraph,
+                "Unrelated: make the theme colors darker",
+                &format!("Summary of the conversation so far: {}", paragraph),
+                "thanks",
+                &format!("Continuing where we left off. {} Next up: tests.", paragraph),
+                "Done with the theme too",
+            ],
+        );
+
+        for query in ["compaction", "compaction step", "segments merges", "compaction hundred"] {
+            for _ in 0..3 {
+                let results = index.search(query, 10).unwrap();
+                assert_eq!(results.len(), 1);
+                assert_eq!(results[0].matched_message_index, 5, "query {:?}", query);
+            }
+        }
+
+        // A distinct message still wins on its own merits
+        let results = index.search("theme colors darker", 10).unwrap();
+        assert_eq!(results[0].matched_message_index, 2);
+    }
+
+    #[test]
+    fn test_keeps_other_matches_without_duplicates() {
+        let index = SessionIndex::in_memory().unwrap();
+        let paragraph = "The retry loop backs off exponentially and gives up after five tries.";
+        index_messages(
+            &index,
+            "s1",
+            &[
+                "Why does the retry fire twice?",
+                paragraph,
+                "Unrelated: rename the config struct",
+                &format!("Quoting the earlier answer: {}", paragraph),
+                "Make the retry delay configurable",
+            ],
+        );
+
+        let results = index.search("retry", 10).unwrap();
+        assert_eq!(results.len(), 1);
+        let mut shown: Vec<usize> =
+            results[0].more_matches.iter().map(|hit| hit.message_index).collect();
+        shown.push(results[0].matched_message_index);
+        shown.sort();
+        // The quoted paragraph counts once, as its latest copy
+        assert_eq!(shown, [0, 3, 4]);
+        for hit in &results[0].more_matches {
+            assert!(hit.snippet.to_lowercase().contains("retry"), "{:?}", hit.snippet);
+            assert!(!hit.match_spans.is_empty());
+        }
+
+        // A single match has nothing more to show
+        let results = index.search("rename", 10).unwrap();
+        assert!(results[0].more_matches.is_empty());
+    }
+
+    #[test]
+    fn test_redacted_content_never_reaches_the_index() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut index = SessionIndex::open_or_create(dir.path()).unwrap();
+        let redactor = Box::leak(Box::new(Redactor::new(&[]).unwrap()));
+        index.set_redactor(Some(redactor));
+
+        let secret = "sk-ant-REDACTED";
+        let session = Session {
+            id: "s1".to_string(),
+            source: SessionSource::ClaudeCode,
+            file_path: dir.path().join("s1.jsonl"),
+            cwd: "/w".to_string(),
+            git_branch: None,
+            timestamp: chrono::Utc::now(),
+            messages: vec![Message {
+                role: Role::User,
+                content: format!("use ANTHROPIC_API_KEY={} please", secret),
+                timestamp: chrono::Utc::now(),
+                tool_calls: Vec::new(),
+            }],
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: None,
+        };
+        let mut writer = index.writer().unwrap();
+        index.index_session(&mut writer, &session).unwrap();
+        writer.commit().unwrap();
+        index.reload().unwrap();
+
+        let searcher = index.reader.searcher();
+        for segment in searcher.segment_readers() {
+            let store = segment.get_store_reader(1).unwrap();
+            for doc in store.iter::<tantivy::TantivyDocument>(segment.alive_bitset()) {
+                let doc = doc.unwrap();
+                let content = doc.get_first(index.content).and_then(|v| v.as_str()).unwrap();
+                assert!(!content.contains(secret), "stored: {}", content);
+                assert!(content.contains(crate::redact::REDACTED));
+            }
+            // Nor is it a searchable term
+            let terms = segment.inverted_index(index.content).unwrap();
+            assert!(terms.terms().get("0123456789abcdefghijklmnop").unwrap().is_none());
+        }
+        assert!(index.search("0123456789abcdefghijklmnop", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tool_output_searched_only_when_opted_in() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut index = SessionIndex::open_or_create(dir.path()).unwrap();
+        let session = |id: &str| Session {
+            id: id.to_string(),
+            source: SessionSource::CodexCli,
+            file_path: dir.path().join(format!("{}.jsonl", id)),
+            cwd: "/w".to_string(),
+            git_branch: None,
+            timestamp: chrono::Utc::now(),
+            messages: vec![Message {
+                role: Role::Assistant,
+                content: "Running the tests.".to_string(),
+                timestamp: chrono::Utc::now(),
+                tool_calls: vec![ToolCall {
+                    name: "shell".to_string(),
+                    input: "cargo test".to_string(),
+                    output: Some("thread 'main' panicked at src/lib.rs".to_string()),
+                    exit_code: Some(101),
+                    status: ToolStatus::Error,
+                }],
+            }],
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: None,
+        };
+
+        let mut writer = index.writer().unwrap();
+        index.index_session(&mut writer, &session("off")).unwrap();
+        index.set_memory(&IndexConfig { tool_output: true, ..Default::default() });
+        index.index_session(&mut writer, &session("on")).unwrap();
+        writer.commit().unwrap();
+        index.reload().unwrap();
+
+        assert_eq!(ids(&index.search("panicked", 10).unwrap()), ["on"]);
+        assert_eq!(ids(&index.search("tests", 10).unwrap()), ["off", "on"]);
+    }
+}
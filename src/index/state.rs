@@ -1,24 +1,92 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
+/// The settings that change what's read of each message: their name in the state file, and
+/// their key in the config
+const CONTENT_SETTINGS: [(&str, &str); 3] = [
+    ("thinking", "include_thinking"),
+    ("tool_output", "index.tool_output"),
+    ("compacted_context", "index.compacted_context"),
+];
+
+/// The content settings that are on, like "thinking,tool_output"
+fn content_settings() -> String {
+    let config = crate::config::get();
+    let on = [
+        config.include_thinking,
+        config.index.tool_output,
+        config.index.compacted_context,
+    ];
+    CONTENT_SETTINGS
+        .iter()
+        .zip(on)
+        .filter(|(_, on)| *on)
+        .map(|((name, _), _)| *name)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn has_setting(settings: &str, name: &str) -> bool {
+    settings.split(',').any(|setting| setting == name)
+}
+
 /// Tracks which files have been indexed and their modification times
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct IndexState {
     pub indexed_files: HashMap<PathBuf, FileState>,
     pub version: u32,
+    /// The redaction settings the index was built with (see [`crate::redact::fingerprint`])
+    #[serde(default)]
+    pub redaction: String,
+    /// The `stemming` language the index was built with
+    #[serde(default)]
+    pub stemming: String,
+    /// The settings the indexed content was read with (see [`content_settings`])
+    #[serde(default)]
+    pub content: String,
+    /// Directory of the tags, notes, and titles sidecars files are compared against (the
+    /// cache dir when unset)
+    #[serde(skip)]
+    sidecar_dir: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileState {
     pub mtime: u64,
     pub size: u64,
+    /// Parsed fine but had no messages, so nothing was indexed
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub empty: bool,
+    /// Left out of the index for being older than the retention cutoff: the session's time,
+    /// so it's indexed again once the cutoff no longer excludes it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expired: Option<i64>,
+    /// Left out by the `exclude` of its project's `.recall.toml`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub excluded: bool,
+    /// The tags (see [`crate::tags`]) the session was indexed with
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// The note (see [`crate::notes`]) the session was indexed with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// The title (see [`crate::titles`]) the session was indexed with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The ID of the session in the file, for sessions that can be copied into another file
+    /// under the same ID (see [`IndexState::indexed_copy`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Left out of the index for holding less of its session than this file, which is indexed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<PathBuf>,
 }
 
 impl IndexState {
-    const CURRENT_VERSION: u32 = 1;
+    const CURRENT_VERSION: u32 = 10;
 
     /// Load state from disk or create new
     pub fn load(state_path: &Path) -> Result<Self> {
@@ -27,23 +95,68 @@ impl IndexState {
                 .context("Failed to read state file")?;
             let state: Self = serde_json::from_str(&content)
                 .context("Failed to parse state file")?;
+            // A new version or stemming means the index schema changed, and new redaction
+            // settings mean indexed content is masked differently: reindex everything
+            if state.version != Self::CURRENT_VERSION
+                || state.redaction != crate::redact::fingerprint()
+                || state.stemming != crate::config::get().stemming
+            {
+                return Ok(Self::fresh());
+            }
             Ok(state)
         } else {
-            Ok(Self {
-                indexed_files: HashMap::new(),
-                version: Self::CURRENT_VERSION,
-            })
+            Ok(Self::fresh())
+        }
+    }
+
+    /// State for an index with nothing in it
+    pub fn fresh() -> Self {
+        Self {
+            indexed_files: HashMap::new(),
+            version: Self::CURRENT_VERSION,
+            redaction: crate::redact::fingerprint(),
+            stemming: crate::config::get().stemming.clone(),
+            content: content_settings(),
+            sidecar_dir: None,
+        }
+    }
+
+    /// Compare files against the tags, notes, and titles in `dir` rather than the cache dir
+    pub fn set_sidecar_dir(&mut self, dir: &Path) {
+        self.sidecar_dir = Some(dir.to_path_buf());
+    }
+
+    fn sidecar_dir(&self) -> PathBuf {
+        self.sidecar_dir.clone().unwrap_or_else(super::cache::dir)
+    }
+
+    /// A suggestion to reindex when the settings that change what's read of each message
+    /// aren't the ones the index was built with. Unlike redaction and stemming, they don't
+    /// rebuild the index themselves: sessions already indexed keep their content.
+    pub fn reindex_hint(&self) -> Option<String> {
+        let current = content_settings();
+        if self.content == current || self.indexed_files.is_empty() {
+            return None;
         }
+        let changed: Vec<&str> = CONTENT_SETTINGS
+            .iter()
+            .filter(|(name, _)| has_setting(&self.content, name) != has_setting(&current, name))
+            .map(|(_, key)| *key)
+            .collect();
+        Some(format!(
+            "{} changed since sessions were indexed; `recall --reindex` applies it to them",
+            changed.join(" and ")
+        ))
     }
 
     /// Save state to disk
     pub fn save(&self, state_path: &Path) -> Result<()> {
         if let Some(parent) = state_path.parent() {
-            std::fs::create_dir_all(parent)?;
+            super::cache::create_private_dir(parent)?;
         }
         let content = serde_json::to_string_pretty(self)
             .context("Failed to serialize state")?;
-        std::fs::write(state_path, content)
+        super::cache::write_private(state_path, content.as_bytes())
             .context("Failed to write state file")?;
         Ok(())
     }
@@ -55,37 +168,187 @@ impl IndexState {
         };
 
         match self.indexed_files.get(path) {
+            // A duplicate is compared again when it changes, or the file it lost to is gone or
+            // no longer the one its session is indexed from
+            Some(indexed) if indexed.duplicate_of.is_some() => {
+                let session_id = indexed.session_id.as_deref().unwrap_or_default();
+                let kept = self.indexed_copy(session_id, path).filter(|kept| kept.exists());
+                indexed.mtime != current_state.mtime
+                    || indexed.size != current_state.size
+                    || kept != indexed.duplicate_of
+            }
             Some(indexed) => {
-                // Reindex if mtime or size changed
-                indexed.mtime != current_state.mtime || indexed.size != current_state.size
+                // Reindex if mtime or size changed, or the session's tags, note, or title did
+                let dir = self.sidecar_dir();
+                indexed.mtime != current_state.mtime
+                    || indexed.size != current_state.size
+                    || (indexed.expired.is_none()
+                        && !indexed.empty
+                        && !indexed.excluded
+                        && (indexed.tags != crate::tags::for_file_in(&dir, path)
+                            || indexed.note != crate::notes::for_file_in(&dir, path)
+                            || indexed.title != crate::titles::for_file_in(&dir, path)))
             }
             None => true, // Not indexed yet
         }
     }
 
-    /// Mark a file as indexed
+    /// Mark a file as indexed, with the tags, note, and title it was given
     pub fn mark_indexed(&mut self, path: &Path) {
         if let Some(state) = get_file_state(path) {
+            let dir = self.sidecar_dir();
+            let state = FileState {
+                tags: crate::tags::for_file_in(&dir, path),
+                note: crate::notes::for_file_in(&dir, path),
+                title: crate::titles::for_file_in(&dir, path),
+                ..state
+            };
             self.indexed_files.insert(path.to_path_buf(), state);
         }
     }
 
+    /// Mark a file as indexed holding the session `session_id`
+    pub fn mark_indexed_session(&mut self, path: &Path, session_id: &str) {
+        self.mark_indexed(path);
+        if let Some(state) = self.indexed_files.get_mut(path) {
+            state.session_id = Some(session_id.to_string());
+        }
+    }
+
+    /// Mark a file as left out for holding less of the session `session_id` than `kept`
+    pub fn mark_duplicate(&mut self, path: &Path, session_id: &str, kept: &Path) {
+        match get_file_state(path) {
+            Some(state) => {
+                let state = FileState {
+                    session_id: Some(session_id.to_string()),
+                    duplicate_of: Some(kept.to_path_buf()),
+                    ..state
+                };
+                self.indexed_files.insert(path.to_path_buf(), state);
+            }
+            None => self.remove(path),
+        }
+    }
+
+    /// The file other than `path` that the session `session_id` is indexed from, if any
+    pub fn indexed_copy(&self, session_id: &str, path: &Path) -> Option<PathBuf> {
+        self.indexed_files
+            .iter()
+            .find(|(other, state)| {
+                *other != path
+                    && state.duplicate_of.is_none()
+                    && state.session_id.as_deref() == Some(session_id)
+            })
+            .map(|(other, _)| other.clone())
+    }
+
+    /// Whether the file at `path` holds more of its session than the indexed `copy`: it's
+    /// bigger, or as big and changed later
+    pub fn fuller_than(&self, path: &Path, copy: &Path) -> bool {
+        let (Some(file), Some(copy)) = (get_file_state(path), self.indexed_files.get(copy)) else {
+            return true;
+        };
+        (file.size, file.mtime) > (copy.size, copy.mtime)
+    }
+
+    /// Mark a file as indexed, remembering that it had no messages
+    pub fn mark_empty(&mut self, path: &Path) {
+        if let Some(state) = get_file_state(path) {
+            self.indexed_files.insert(path.to_path_buf(), FileState { empty: true, ..state });
+        }
+    }
+
+    /// Mark a file as left out by its project's `exclude`
+    pub fn mark_excluded(&mut self, path: &Path) {
+        if let Some(state) = get_file_state(path) {
+            self.indexed_files.insert(path.to_path_buf(), FileState { excluded: true, ..state });
+        }
+    }
+
+    /// Mark a file as left out by retention, its session last active at `timestamp`
+    pub fn mark_expired(&mut self, path: &Path, timestamp: i64) {
+        if let Some(state) = get_file_state(path) {
+            let state = FileState { expired: Some(timestamp), ..state };
+            self.indexed_files.insert(path.to_path_buf(), state);
+        }
+    }
+
+    /// Whether a file left out by retention is within `cutoff` now (or retention is off)
+    pub fn revived(&self, path: &Path, cutoff: Option<i64>) -> bool {
+        self.indexed_files
+            .get(path)
+            .and_then(|s| s.expired)
+            .is_some_and(|timestamp| cutoff.is_none_or(|cutoff| timestamp >= cutoff))
+    }
+
+    /// How many of `files` are left out by retention
+    pub fn expired_count(&self, files: &[PathBuf]) -> usize {
+        files
+            .iter()
+            .filter(|path| self.indexed_files.get(*path).is_some_and(|s| s.expired.is_some()))
+            .count()
+    }
+
+    /// How many of `files` are left out for holding a session another file holds more of
+    pub fn duplicate_count(&self, files: &[PathBuf]) -> usize {
+        files
+            .iter()
+            .filter(|path| self.indexed_files.get(*path).is_some_and(|s| s.duplicate_of.is_some()))
+            .count()
+    }
+
+    /// How many of `files` were indexed without any messages
+    pub fn empty_count(&self, files: &[PathBuf]) -> usize {
+        files
+            .iter()
+            .filter(|path| self.indexed_files.get(*path).is_some_and(|s| s.empty))
+            .count()
+    }
+
     /// Remove a file from the index state
     pub fn remove(&mut self, path: &Path) {
         self.indexed_files.remove(path);
     }
+
+    /// Indexed files that aren't among `discovered`
+    pub fn undiscovered_files(&self, discovered: &[PathBuf]) -> Vec<PathBuf> {
+        let discovered: HashSet<&PathBuf> = discovered.iter().collect();
+        self.indexed_files
+            .keys()
+            .filter(|path| !discovered.contains(path))
+            .cloned()
+            .collect()
+    }
 }
 
-/// Get the current file state (mtime and size)
+/// Get the current file state (mtime and size), of the database for a session kept in one,
+/// with the writes still in its WAL
 fn get_file_state(path: &Path) -> Option<FileState> {
-    let metadata = std::fs::metadata(path).ok()?;
-    let mtime = metadata
-        .modified()
-        .ok()?
+    let database = crate::parser::backing_file(path);
+    let metadata = std::fs::metadata(database).ok()?;
+    let wal = (database != path)
+        .then(|| std::fs::metadata(crate::parser::wal_path(database)).ok())
+        .flatten();
+    let modified = metadata.modified().ok()?;
+    let mtime = wal
+        .as_ref()
+        .and_then(|wal| wal.modified().ok())
+        .map_or(modified, |wal| wal.max(modified))
         .duration_since(SystemTime::UNIX_EPOCH)
         .ok()?
         .as_secs();
-    let size = metadata.len();
+    let size = metadata.len() + wal.map_or(0, |wal| wal.len());
 
-    Some(FileState { mtime, size })
+    Some(FileState {
+        mtime,
+        size,
+        empty: false,
+        expired: None,
+        excluded: false,
+        tags: Vec::new(),
+        note: None,
+        title: None,
+        session_id: None,
+        duplicate_of: None,
+    })
 }
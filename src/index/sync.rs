@@ -1,6 +1,10 @@
 //! Synchronous indexing for CLI mode
 
-use super::indexer::{discover_and_sort_files, index_files, IndexProgress};
+use super::indexer::{
+    discover_and_sort_files, expired_files, files_to_index, index_files, prune_files, purge_files,
+    retained_files, stale_files, IndexProgress,
+};
+use super::cache;
 use super::schema::default_index_path;
 use super::state::IndexState;
 use super::SessionIndex;
@@ -11,62 +15,105 @@ use std::io::Write;
 /// Discovers new/modified session files and indexes them synchronously.
 /// Progress is printed to stderr.
 pub fn ensure_index_fresh(index: &SessionIndex) -> Result<()> {
-    // state.json lives alongside the index directory
+    // An ephemeral index starts empty every time and keeps no state on disk
     let index_path = default_index_path();
-    let state_path = index_path
-        .parent()
-        .map(|p| p.join("state.json"))
-        .unwrap_or_else(|| index_path.join("state.json"));
+    let state_path = (!cache::ephemeral()).then(|| cache::state_path(&index_path));
 
-    let mut state = IndexState::load(&state_path)?;
+    let mut state = match &state_path {
+        Some(path) => IndexState::load(path)?,
+        None => IndexState::fresh(),
+    };
+    if let Some(hint) = state.reindex_hint() {
+        eprintln!("{}", hint);
+    }
+    let save = |state: &IndexState| -> Result<()> {
+        if let Some(path) = &state_path {
+            state.save(path)?;
+            cache::restrict_files(&index_path)?;
+        }
+        Ok(())
+    };
+
+    // Update the remotes' mirrors first; one that can't be reached keeps its last copy
+    for (host, err) in crate::remote::sync_all() {
+        eprintln!("Couldn't sync remote {} (searching its last copy): {}", host, err);
+    }
 
-    // Discover all session files
-    let files = discover_and_sort_files();
+    // Discover all session files, minus those too old for `retention`
+    let config = crate::config::get();
+    let cutoff = config.retention_cutoff();
+    let files = retained_files(discover_and_sort_files(), cutoff);
 
     // Find files that need indexing
-    let files_to_index: Vec<_> = files
-        .iter()
-        .filter(|f| state.needs_reindex(f))
-        .cloned()
-        .collect();
+    let files_to_index = files_to_index(&state, &files, cutoff);
+
+    let stale = stale_files(&state, &files);
+    let expired = expired_files(index, cutoff)?;
 
     let total = files_to_index.len();
-    if total == 0 {
+    if total == 0 && stale.is_empty() && expired.is_empty() {
         // Nothing to index, we're fresh
         return Ok(());
     }
 
+    let mut writer = index.writer()?;
+    purge_files(index, &mut writer, &mut state, &stale);
+    prune_files(index, &mut writer, &mut state, &expired);
+    if !expired.is_empty() {
+        eprintln!(
+            "Pruned {} session{} older than {}.",
+            expired.len(),
+            if expired.len() == 1 { "" } else { "s" },
+            config.retention.as_deref().unwrap_or_default()
+        );
+    }
+
+    if total == 0 {
+        writer.commit()?;
+        save(&state)?;
+        index.reload()?;
+        return Ok(());
+    }
+
     eprintln!(
         "Indexing {} session{}...",
         total,
         if total == 1 { "" } else { "s" }
     );
 
-    let mut writer = index.writer()?;
-
     // Progress callback prints to stderr
     let on_progress = Box::new(|p: IndexProgress| {
         eprint!("\rIndexing {}/{}...", p.indexed, p.total);
         let _ = std::io::stderr().flush();
     });
 
-    index_files(
+    let report = index_files(
         index,
         &mut writer,
         &mut state,
         &files_to_index,
+        cutoff,
         Some(on_progress),
         None, // No reload callback for sync mode
     )?;
 
-    state.save(&state_path)?;
+    save(&state)?;
 
     // Clear progress line and print completion
+    let skipped = report.empty
+        + report.expired
+        + report.duplicates
+        + report.excluded
+        + report.failed.len();
     eprintln!(
-        "\rIndexed {} session{}.    ",
-        total,
-        if total == 1 { "" } else { "s" }
+        "\rIndexed {} session{}{}.    ",
+        report.indexed,
+        if report.indexed == 1 { "" } else { "s" },
+        if skipped > 0 { format!(" ({} skipped)", skipped) } else { String::new() }
     );
+    for (path, err) in &report.failed {
+        eprintln!("  Couldn't index {}: {}", path.display(), err);
+    }
 
     // Reload index to see new data
     index.reload()?;
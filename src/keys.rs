@@ -0,0 +1,98 @@
+//! Key events as the TUI's handlers see them. Terminals that speak the kitty keyboard protocol
+//! (enabled by [`crate::tui::init`] when they do) report chords legacy input can't tell apart,
+//! like Ctrl+Enter from Enter or Ctrl+I from Tab, plus key releases and repeats. Legacy
+//! terminals send the same keys the old way, so every binding keeps a legacy spelling.
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+/// Turns raw key events into key presses
+#[derive(Debug, Default)]
+pub struct Keys {
+    /// A press has come through, so releases are just the other half of a keystroke
+    seen_press: bool,
+}
+
+impl Keys {
+    /// The key press `event` stands for, or None if it isn't one. Held keys repeat. Releases
+    /// are ignored, except from terminals that only ever send releases.
+    pub fn translate(&mut self, event: KeyEvent) -> Option<KeyEvent> {
+        match event.kind {
+            KeyEventKind::Press => self.seen_press = true,
+            KeyEventKind::Repeat => {}
+            KeyEventKind::Release if self.seen_press => return None,
+            KeyEventKind::Release => {}
+        }
+        Some(KeyEvent::new(event.code, event.modifiers))
+    }
+}
+
+/// Whether `key` is `c` with Ctrl held
+fn ctrl(key: &KeyEvent, c: char) -> bool {
+    key.code == KeyCode::Char(c) && key.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+/// Whether `key` saves the note being edited: Ctrl+S, or Ctrl+Enter where the terminal can
+/// tell it from Enter (which adds a line)
+pub fn saves_note(key: &KeyEvent) -> bool {
+    ctrl(key, 's') || (key.code == KeyCode::Enter && key.modifiers.contains(KeyModifiers::CONTROL))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(code: KeyCode, modifiers: KeyModifiers, kind: KeyEventKind) -> KeyEvent {
+        KeyEvent::new_with_kind(code, modifiers, kind)
+    }
+
+    #[test]
+    fn test_enhanced_events() {
+        let mut keys = Keys::default();
+        let press = event(KeyCode::Enter, KeyModifiers::CONTROL, KeyEventKind::Press);
+        let release = event(KeyCode::Enter, KeyModifiers::CONTROL, KeyEventKind::Release);
+        let ctrl_enter = keys.translate(press).unwrap();
+        assert!(saves_note(&ctrl_enter));
+        assert_eq!(keys.translate(release), None);
+
+        // Shift+Enter is still a new line
+        let shift_enter = event(KeyCode::Enter, KeyModifiers::SHIFT, KeyEventKind::Press);
+        assert!(!saves_note(&keys.translate(shift_enter).unwrap()));
+
+        // Held arrows repeat
+        let repeat = event(KeyCode::Down, KeyModifiers::NONE, KeyEventKind::Repeat);
+        assert_eq!(keys.translate(repeat).map(|k| k.kind), Some(KeyEventKind::Press));
+
+        // Ctrl+I is its own chord, not Tab
+        let ctrl_i = event(KeyCode::Char('i'), KeyModifiers::CONTROL, KeyEventKind::Press);
+        let ctrl_i = keys.translate(ctrl_i).unwrap();
+        assert!(ctrl(&ctrl_i, 'i'));
+        assert_ne!(ctrl_i.code, KeyCode::Tab);
+    }
+
+    #[test]
+    fn test_legacy_events() {
+        let mut keys = Keys::default();
+        // Ctrl+Enter arrives as plain Enter, and Ctrl+I as Tab
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(keys.translate(enter), Some(enter));
+        assert!(!saves_note(&enter));
+        let tab = KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE);
+        assert_eq!(keys.translate(tab), Some(tab));
+
+        let ctrl_s = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL);
+        assert!(saves_note(&keys.translate(ctrl_s).unwrap()));
+    }
+
+    #[test]
+    fn test_release_only_terminal() {
+        let mut keys = Keys::default();
+        let release = event(KeyCode::Char('a'), KeyModifiers::NONE, KeyEventKind::Release);
+        assert_eq!(keys.translate(release).map(|k| k.kind), Some(KeyEventKind::Press));
+        assert!(keys.translate(release).is_some());
+
+        // Once a press shows up, releases are the other half of it
+        let press = event(KeyCode::Char('a'), KeyModifiers::NONE, KeyEventKind::Press);
+        assert!(keys.translate(press).is_some());
+        assert_eq!(keys.translate(release), None);
+    }
+}
@@ -1,13 +1,30 @@
+pub mod active;
 pub mod app;
+pub mod bookmarks;
+pub mod config;
+pub mod diff;
+pub mod engine;
+pub mod fold;
+pub mod history;
 pub mod index;
+pub mod keys;
+pub mod notes;
 pub mod parser;
+pub mod pricing;
+pub mod redact;
+pub mod remote;
 pub mod session;
+pub mod sidecar;
+pub mod tags;
 pub mod theme;
+pub mod time;
+pub mod titles;
 pub mod tui;
 pub mod ui;
 
-pub use app::{App, SearchScope};
+pub use app::{App, AppOptions, SearchScope};
+pub use engine::{Recall, RecallConfig, SearchOptions};
 pub use session::{
-    ListOutput, Message, ReadOutput, Role, SearchOutput, SearchResult, SearchResultOutput,
-    Session, SessionSource, SessionSummary,
+    ListOutput, MatchHit, Message, ReadOutput, Role, SearchOutput, SearchResult, SearchResultOutput,
+    Session, SessionSource, SessionSummary, ToolCall, ToolStatus,
 };
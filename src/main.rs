@@ -1,7 +1,14 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
-use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers, MouseEventKind};
-use recall::{app::App, session, session::SessionSource, tui, ui};
+use crossterm::event::{self, Event, KeyCode, KeyModifiers, MouseEventKind};
+use recall::{
+    app::{App, AppOptions, OpenFolder},
+    config::Config,
+    session,
+    session::SessionSource,
+    theme::Theme,
+    tui, ui,
+};
 use std::time::Duration;
 
 mod cli;
@@ -17,6 +24,91 @@ struct Cli {
     #[arg(long, global = true)]
     reindex: bool,
 
+    /// Ignore sessions from this source (repeatable; adds to `[sources]` in the config)
+    #[arg(long, global = true, value_name = "SOURCE")]
+    disable_source: Vec<String>,
+
+    /// Length of result snippets (overrides `search.snippet_chars` in the config)
+    #[arg(long, global = true, value_name = "N")]
+    snippet_chars: Option<usize>,
+
+    /// Turn off colors and Unicode icons (same as setting NO_COLOR)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Only index sessions active within this long, like "180d" (overrides `retention`)
+    #[arg(long, global = true, value_name = "AGE")]
+    max_age: Option<String>,
+
+    /// Search everything, including sessions past `retention`, with a temporary in-memory index
+    #[arg(long, global = true, conflicts_with = "max_age")]
+    no_retention: bool,
+
+    /// Index with less memory, for small machines (same as `index.low_memory` in the config)
+    ///
+    /// Uses one writer thread with a 15 MB buffer and commits after every 8 MB of content.
+    /// Indexing is slower, and messages longer than 64 KB are only searchable in their first
+    /// 64 KB until the next --reindex without this flag.
+    #[arg(long, global = true)]
+    low_memory: bool,
+
+    /// Keep the assistants' thinking in messages (same as `include_thinking` in the config)
+    #[arg(long, global = true)]
+    include_thinking: bool,
+
+    /// Index Claude's subagent transcripts too (same as `include_subagents` in the config)
+    #[arg(long, global = true)]
+    include_subagents: bool,
+
+    /// Keep the index in memory for this run only (same as `privacy.ephemeral` in the config)
+    #[arg(long, global = true)]
+    no_persist: bool,
+
+    /// Print the chosen session to stdout instead of resuming it (Esc exits 130)
+    #[arg(long)]
+    select: bool,
+
+    /// Field to print in --select mode
+    #[arg(long, value_enum, default_value = "id", requires = "select")]
+    print: cli::SelectField,
+
+    /// Resume the most recent session for the current directory
+    #[arg(long, conflicts_with = "select")]
+    last: bool,
+
+    /// With --last, resume the Nth most recent session instead
+    #[arg(long, default_value = "1", requires = "last")]
+    pick: usize,
+
+    /// With --last, resume even if the session looks open in another terminal
+    #[arg(long, requires = "last")]
+    force: bool,
+
+    /// Only show sessions from this source (claude, codex, ...; `recall sources` lists them)
+    #[arg(long, short)]
+    source: Option<String>,
+
+    /// Start searching all folders instead of the current one
+    #[arg(long, conflicts_with_all = ["cwd", "last"])]
+    everywhere: bool,
+
+    /// Start scoped to this folder instead of the current one
+    #[arg(long)]
+    cwd: Option<String>,
+
+    /// Draw in this many rows (or percent, like 40%) below the prompt instead of the whole
+    /// screen, leaving the scrollback as it was
+    #[arg(long, value_name = "HEIGHT", conflicts_with = "last")]
+    height: Option<tui::Height>,
+
+    /// Only show sessions after this time (e.g., "1 week ago", "2025-12-01")
+    #[arg(long, conflicts_with = "last")]
+    since: Option<String>,
+
+    /// Draw plain, labeled text for screen readers (same as `accessible` in the config)
+    #[arg(long, conflicts_with = "last")]
+    accessible: bool,
+
     /// Initial search query (for interactive TUI mode)
     #[arg(trailing_var_arg = true)]
     query: Vec<String>,
@@ -30,7 +122,7 @@ enum Command {
         #[arg(required = true)]
         query: Vec<String>,
 
-        /// Filter by source (claude, codex, factory, opencode)
+        /// Filter by source (claude, codex, ...; `recall sources` lists them)
         #[arg(long, short)]
         source: Option<String>,
 
@@ -38,9 +130,18 @@ enum Command {
         #[arg(long)]
         session: Option<String>,
 
-        /// Maximum number of results
-        #[arg(long, short, default_value = "10")]
-        limit: usize,
+        /// Maximum number of results [default: 10, or cli.search_limit in config]
+        #[arg(long, short)]
+        limit: Option<usize>,
+
+        /// Skip this many results (for paging)
+        #[arg(long, default_value = "0")]
+        offset: usize,
+
+        /// Comma-separated fields to include per result: id, source, project, cwd, branch,
+        /// timestamp, message_count, title, resume_command, messages, origin
+        #[arg(long, value_parser = cli::fields::FieldSelection::parse)]
+        fields: Option<cli::fields::FieldSelection>,
 
         /// Number of context messages around each match
         #[arg(short = 'C', long = "context", default_value = "0")]
@@ -57,15 +158,36 @@ enum Command {
         /// Filter by working directory (exact match)
         #[arg(long)]
         cwd: Option<String>,
+
+        /// Only include sessions with this tag (repeatable; `tag:name` in the query works too)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
     },
 
     /// List recent sessions and output JSON
     List {
-        /// Maximum number of sessions
-        #[arg(long, short, default_value = "20")]
-        limit: usize,
+        /// Maximum number of sessions [default: 20, or cli.list_limit in config]
+        #[arg(long, short)]
+        limit: Option<usize>,
+
+        /// Skip this many sessions (for paging)
+        #[arg(long, default_value = "0")]
+        offset: usize,
+
+        /// Comma-separated fields to include per result: id, source, project, cwd, branch,
+        /// timestamp, message_count, title, resume_command, messages, origin
+        #[arg(long, value_parser = cli::fields::FieldSelection::parse)]
+        fields: Option<cli::fields::FieldSelection>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "json")]
+        format: cli::ListFormat,
+
+        /// Write NUL-terminated records of tab-separated fields (for `xargs -0`)
+        #[arg(short = '0', long, conflicts_with = "format")]
+        null: bool,
 
-        /// Filter by source (claude, codex, factory, opencode)
+        /// Filter by source (claude, codex, ...; `recall sources` lists them)
         #[arg(long, short)]
         source: Option<String>,
 
@@ -80,20 +202,259 @@ enum Command {
         /// Filter by working directory (exact match)
         #[arg(long)]
         cwd: Option<String>,
+
+        /// Only include sessions with this tag (repeatable)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
     },
 
     /// Read a full conversation by session ID and output JSON
     Read {
-        /// Session ID to read
-        session_id: String,
+        /// Session ID to read, optionally with a message slice or :errors, as for print
+        selector: String,
+    },
+
+    /// Resume the most recent session for the current directory
+    Last {
+        /// Resume the Nth most recent session instead
+        #[arg(long, default_value = "1")]
+        pick: usize,
+
+        /// Only consider this source (claude, codex, ...; `recall sources` lists them)
+        #[arg(long, short)]
+        source: Option<String>,
+
+        /// Resume even if the session looks open in another terminal
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Print a session as a readable transcript (paged when it doesn't fit the screen)
+    Print {
+        /// Session ID, optionally with a message slice: ID:N, ID:A..B, ID:A.., ID:..B, or
+        /// ID:errors for the messages a tool call failed after
+        selector: String,
+    },
+
+    /// Summarize a session (or recent sessions with --all) without calling any model
+    Summary {
+        /// Session ID to summarize
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        session_id: Option<String>,
+
+        /// Summarize every recent session instead
+        #[arg(long)]
+        all: bool,
+
+        /// With --all, only include sessions after this time (e.g., "1day", "2025-12-01")
+        #[arg(long, requires = "all")]
+        since: Option<String>,
+
+        /// With --all, maximum number of sessions
+        #[arg(long, short, default_value = "50", requires = "all")]
+        limit: usize,
+
+        /// Output JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Build a Markdown digest of matching sessions to paste into a new agent session
+    Context {
+        /// Search query
+        #[arg(required = true)]
+        query: Vec<String>,
+
+        /// Maximum output size in characters (roughly 4 per token)
+        #[arg(long, default_value = "4000")]
+        budget: usize,
+
+        /// Number of sessions to draw from
+        #[arg(long, default_value = "3")]
+        sessions: usize,
+
+        /// Filter by source (claude, codex, ...; `recall sources` lists them)
+        #[arg(long, short)]
+        source: Option<String>,
+
+        /// Filter by working directory (exact match)
+        #[arg(long)]
+        cwd: Option<String>,
+    },
+
+    /// Report token usage and estimated cost
+    Cost {
+        /// Only include sessions after this time (e.g., "1month", "2025-12-01")
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Group by project, source, or day
+        #[arg(long, value_enum, default_value = "project")]
+        by: cli::cost::CostGroup,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: cli::cost::CostFormat,
+    },
+
+    /// Compare two sessions message by message
+    Diff {
+        /// Session ID shown as "-"
+        left: String,
+
+        /// Session ID shown as "+"
+        right: String,
+
+        /// Pair messages by longest common subsequence (lcs) or by position (index)
+        #[arg(long, value_enum, default_value = "lcs")]
+        align: recall::diff::Alignment,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: cli::diff::DiffFormat,
+    },
+
+    /// Print a JSON line whenever a session is created, updated, or deleted (until interrupted)
+    Watch {
+        /// Only report sessions from this source (claude, codex, ...; `recall sources` lists them)
+        #[arg(long, short)]
+        source: Option<String>,
+
+        /// Only report sessions in this working directory (exact match)
+        #[arg(long)]
+        cwd: Option<String>,
+
+        /// Include the new messages in each event
+        #[arg(long)]
+        include_messages: bool,
+
+        /// How often to check for changes, in milliseconds
+        #[arg(long, default_value = "1000")]
+        interval_ms: u64,
+    },
+
+    /// Import sessions from a `recall backup` archive or `recall read` exports, or chats
+    /// exported from ChatGPT (`recall import chatgpt conversations.json`)
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+    Import {
+        #[command(subcommand)]
+        from: Option<ImportFrom>,
+
+        /// Backup archive (.tar.gz), exported session file, or directory of exports
+        #[arg(required = true)]
+        path: Option<std::path::PathBuf>,
+
+        /// Replace sessions that were already imported
+        #[arg(long)]
+        overwrite: bool,
+    },
+
+    /// Back up every session file into a .tar.gz archive
+    Backup {
+        /// Archive to write
+        output: std::path::PathBuf,
+
+        /// Reuse unchanged files from an existing archive at the same path
+        #[arg(long)]
+        incremental: bool,
+    },
+
+    /// List files a session read or wrote, or sessions that touched a file
+    Files {
+        /// Session ID to list files for
+        #[arg(required_unless_present = "path", conflicts_with = "path")]
+        session_id: Option<String>,
+
+        /// List sessions that touched this file instead
+        #[arg(long)]
+        path: Option<std::path::PathBuf>,
+
+        /// Only include edits and file creations
+        #[arg(long)]
+        edited_only: bool,
+
+        /// Output JSON instead of text
+        #[arg(long)]
+        json: bool,
+
+        /// Write NUL-terminated records of tab-separated fields (for `xargs -0`)
+        #[arg(short = '0', long, conflicts_with = "json")]
+        null: bool,
+    },
+
+    /// List bookmarked messages (Ctrl+B in the TUI) as JSON
+    Bookmarks,
+
+    /// Show where sessions are discovered and how many are indexed
+    Sources {
+        /// Output JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// List the color themes accepted by `theme` in the config and RECALL_THEME
+    #[command(hide = true)]
+    Themes,
+
+    /// Serve search, list, and read as a local HTTP API
+    #[cfg(feature = "serve")]
+    Serve {
+        /// Address to listen on (must be a loopback address)
+        #[arg(long, default_value = "127.0.0.1:7878")]
+        addr: String,
+
+        /// Send Access-Control-Allow-Origin with this value (CORS is off by default)
+        #[arg(long)]
+        allow_origin: Option<String>,
+    },
+}
+
+/// Chat apps whose exports `recall import` reads
+#[derive(Subcommand)]
+enum ImportFrom {
+    /// Import the conversations in a ChatGPT data export, updating ones imported before
+    Chatgpt {
+        /// The export's conversations.json
+        path: std::path::PathBuf,
     },
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let mut config = Config::load()?;
+    for name in &cli.disable_source {
+        // Custom sources are looked up in this config, not the global one (not set yet)
+        let source = match config.custom_source(name) {
+            Some(custom) => Some(SessionSource::custom(&custom.name)),
+            None => parse_source(&Some(name.clone()))?,
+        };
+        if let Some(source) = source {
+            config.sources.disable(source);
+        }
+    }
+    config.no_color = cli.no_color;
+    config.accessible |= cli.accessible;
+    config.privacy.ephemeral |= cli.no_persist;
+    config.index.low_memory |= cli.low_memory;
+    config.include_thinking |= cli.include_thinking;
+    config.include_subagents |= cli.include_subagents;
+    if let Some(age) = cli.max_age {
+        recall::time::parse_time(&age)
+            .with_context(|| format!("Invalid --max-age '{}'", age))?;
+        config.retention = Some(age);
+    }
+    if cli.no_retention {
+        // Index everything for this run only, leaving the pruned cache as it is
+        config.retention = None;
+        config.privacy.ephemeral = true;
+    }
+    if let Some(chars) = cli.snippet_chars {
+        config.search.snippet_chars = chars;
+    }
+    let config = recall::config::init(config);
 
-    // Handle --reindex
-    if cli.reindex {
+    // Handle --reindex (an in-memory index is rebuilt every run anyway)
+    if cli.reindex && !config.privacy.ephemeral {
         clear_index_cache();
     }
 
@@ -104,38 +465,127 @@ fn main() -> Result<()> {
             source,
             session,
             limit,
+            offset,
+            fields,
             context,
             since,
             until,
             cwd,
+            tags,
         }) => {
-            let source = parse_source(&source)?;
-            cli::run_search(
-                &query.join(" "),
-                source,
-                session,
-                limit,
+            let params = cli::SearchParams {
+                query: query.join(" "),
+                source: parse_source(&source)?,
+                session_id: session,
+                limit: limit.unwrap_or(config.cli.search_limit),
+                offset,
                 context,
                 since,
                 until,
                 cwd,
-            )
+                tags,
+            };
+            cli::run_search(&params, fields.as_ref())
         }
         Some(Command::List {
             limit,
+            offset,
+            fields,
+            format,
+            null,
             source,
             since,
             until,
             cwd,
+            tags,
         }) => {
-            let source = parse_source(&source)?;
-            cli::run_list(limit, source, since, until, cwd)
+            let params = cli::ListParams {
+                limit: limit.unwrap_or(config.cli.list_limit),
+                offset,
+                source: parse_source(&source)?,
+                since,
+                until,
+                cwd,
+                tags,
+            };
+            cli::run_list(&params, fields.as_ref(), format, null)
+        }
+        Some(Command::Read { selector }) => cli::run_read(&selector),
+        Some(Command::Summary {
+            session_id,
+            all: _,
+            since,
+            limit,
+            json,
+        }) => cli::summary::run_summary(session_id.as_deref(), &since, limit, json),
+        Some(Command::Last { pick, source, force }) => {
+            run_last(parse_source(&source)?, pick, None, force)
+        }
+        Some(Command::Print { selector }) => cli::print::run_print(&selector, &config.theme),
+        Some(Command::Context {
+            query,
+            budget,
+            sessions,
+            source,
+            cwd,
+        }) => cli::context::run_context(&cli::context::ContextParams {
+            query: query.join(" "),
+            budget,
+            sessions,
+            source: parse_source(&source)?,
+            cwd,
+        }),
+        Some(Command::Cost { since, by, format }) => cli::cost::run_cost(&since, by, format),
+        Some(Command::Diff { left, right, align, format }) => {
+            cli::diff::run_diff(&left, &right, align, format)
         }
-        Some(Command::Read { session_id }) => cli::run_read(&session_id),
+        Some(Command::Watch {
+            source,
+            cwd,
+            include_messages,
+            interval_ms,
+        }) => cli::watch::run_watch(
+            parse_source(&source)?,
+            cwd,
+            include_messages,
+            Duration::from_millis(interval_ms),
+        ),
+        Some(Command::Backup { output, incremental }) => {
+            cli::backup::run_backup(&output, incremental)
+        }
+        Some(Command::Import { from, path, overwrite }) => match from {
+            Some(ImportFrom::Chatgpt { path }) => cli::import::run_import_chatgpt(&path),
+            None => cli::import::run_import(&path.context("No file to import")?, overwrite),
+        },
+        Some(Command::Files {
+            session_id,
+            path,
+            edited_only,
+            json,
+            null,
+        }) => cli::files::run_files(session_id.as_deref(), path.as_deref(), edited_only, json, null),
+        Some(Command::Bookmarks) => cli::bookmarks::run_bookmarks(),
+        Some(Command::Sources { json }) => cli::sources::run_sources(json),
+        Some(Command::Themes) => {
+            for name in std::iter::once("auto").chain(recall::theme::preset_names()) {
+                println!("{}", name);
+            }
+            Ok(())
+        }
+        #[cfg(feature = "serve")]
+        Some(Command::Serve { addr, allow_origin }) => cli::serve::run_serve(&addr, allow_origin),
+        None if cli.last => run_last(parse_source(&cli.source)?, cli.pick, cli.cwd, cli.force),
         None => {
             // Interactive TUI mode
-            let initial_query = cli.query.join(" ");
-            run_tui(initial_query)
+            let options = AppOptions {
+                query: cli.query.join(" "),
+                everywhere: cli.everywhere,
+                cwd: cli.cwd,
+                source: parse_source(&cli.source)?,
+                since: cli.since.as_deref().map(recall::time::parse_time).transpose()?,
+            };
+            let select = cli.select.then_some(cli.print);
+            run_tui(options, select, cli.height, config)
         }
     }
 }
@@ -143,32 +593,108 @@ fn main() -> Result<()> {
 fn parse_source(source: &Option<String>) -> Result<Option<SessionSource>> {
     match source {
         Some(s) => SessionSource::parse(s)
-            .ok_or_else(|| anyhow::anyhow!("Invalid source '{}'. Valid: claude, codex, factory, opencode", s))
+            .ok_or_else(|| {
+                let custom = recall::config::get().custom_sources.iter().map(|c| c.name.as_str());
+                let valid: Vec<&str> =
+                    SessionSource::ALL.iter().map(|s| s.as_str()).chain(custom).collect();
+                anyhow::anyhow!("Invalid source '{}'. Valid: {}", s, valid.join(", "))
+            })
             .map(Some),
         None => Ok(None),
     }
 }
 
-fn run_tui(initial_query: String) -> Result<()> {
+/// Resume the most recent session for the current directory, or exit 1 if there is none (or
+/// it looks open elsewhere and `force` isn't set)
+fn run_last(
+    source: Option<SessionSource>,
+    pick: usize,
+    cwd: Option<String>,
+    force: bool,
+) -> Result<()> {
+    let index = recall::index::SessionIndex::open_default()?;
+    recall::index::ensure_index_fresh(&index)?;
+
+    let dir = match cwd.or_else(|| std::env::var("RECALL_CWD_OVERRIDE").ok()) {
+        Some(dir) => std::path::PathBuf::from(dir),
+        None => std::env::current_dir()?,
+    };
+
+    match cli::last::last(&index, &dir, source, pick)? {
+        Some(result) => {
+            let session = result.session;
+            if !force && recall::active::looks_active(&session) {
+                eprintln!(
+                    "Session {} looks active in another terminal; resuming it would fork it",
+                    session.id
+                );
+                eprintln!("Pass --force to resume it anyway");
+                std::process::exit(1);
+            }
+            eprintln!(
+                "Resuming {} ({}, {})",
+                session.id,
+                session.source.display_name(),
+                session.cwd
+            );
+            resume_session(&session)
+        }
+        None => {
+            eprintln!("No matching session found for {}", dir.display());
+            eprintln!("Run `recall` to search all sessions");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run_tui(
+    options: AppOptions,
+    select: Option<cli::SelectField>,
+    height: Option<tui::Height>,
+    config: &Config,
+) -> Result<()> {
+    ui::set_theme(Theme::select(&config.theme)?);
+
     // Initialize app (starts background indexing automatically)
-    let mut app = App::new(initial_query)?;
+    let mut app = App::with_config(options, config)?;
+    app.select_mode = select.is_some();
+
+    // In select mode stdout carries the selection, so draw on stderr
+    let output = if app.select_mode {
+        tui::Output::Stderr
+    } else {
+        tui::Output::Stdout
+    };
 
     // Initialize terminal
-    let mut terminal = tui::init()?;
+    let mut terminal = tui::init(output, height)?;
 
     // Main event loop
-    let result = run(&mut terminal, &mut app);
+    let result = run(&mut terminal, output, height, &mut app);
 
     // Restore terminal
-    tui::restore()?;
+    tui::restore(&mut terminal, output)?;
 
-    // Print any indexing error
-    if let Some(ref err) = app.index_error {
-        eprintln!("\nIndexing error:\n  {}\n", err);
-        eprintln!("Try: recall --reindex\n");
-    }
+    // Print any indexing error or skipped files
+    eprint!("{}", app.exit_diagnostics());
 
     // Handle post-exit actions
+    if let Some(field) = select {
+        result?;
+        if let Some(session_id) = app.should_copy {
+            copy_to_clipboard(&session_id)?;
+            eprintln!("Copied session ID: {}", session_id);
+        }
+        match app.should_select {
+            Some(session) => {
+                println!("{}", field.format(&session));
+                return Ok(());
+            }
+            // Cancelled - same exit code as fzf
+            None => std::process::exit(130),
+        }
+    }
+
     if let Some(session) = app.should_resume {
         resume_session(&session)?;
     } else if let Some(session_id) = app.should_copy {
@@ -179,10 +705,16 @@ fn run_tui(initial_query: String) -> Result<()> {
     result
 }
 
-fn run(terminal: &mut tui::Tui, app: &mut App) -> Result<()> {
+fn run(
+    terminal: &mut tui::Tui,
+    output: tui::Output,
+    height: Option<tui::Height>,
+    app: &mut App,
+) -> Result<()> {
     // Track last click for double-click detection
     let mut last_click: Option<(std::time::Instant, u16, u16)> = None;
     const DOUBLE_CLICK_MS: u128 = 400;
+    let mut keys = recall::keys::Keys::default();
 
     loop {
         // Poll for indexing updates
@@ -190,21 +722,67 @@ fn run(terminal: &mut tui::Tui, app: &mut App) -> Result<()> {
 
         // Check for debounced search
         app.maybe_search();
+        app.maybe_record_jump();
 
         // Render
         terminal.draw(|frame| ui::render(frame, app))?;
 
         // Check for exit conditions
-        if app.should_quit || app.should_resume.is_some() || app.should_copy.is_some() {
+        if app.should_quit
+            || app.should_resume.is_some()
+            || app.should_select.is_some()
+            || app.should_copy.is_some()
+        {
             break;
         }
 
         // Handle all pending events (drain queue to prevent mouse event flooding)
         while event::poll(Duration::from_millis(0))? {
-            match event::read()? {
-                // On Windows, crossterm sends both Press and Release events.
-                // Only handle Press to avoid double input.
-                Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+            // Windows and the kitty protocol send releases too; only presses are handled
+            let event = match event::read()? {
+                Event::Key(key) => match keys.translate(key) {
+                    Some(key) => Event::Key(key),
+                    None => continue,
+                },
+                event => event,
+            };
+            match event {
+                // The prompt and note overlay take all typing while they're open
+                Event::Key(key) if app.note_input.is_some() => {
+                    match key.code {
+                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.should_quit = true;
+                        }
+                        _ if recall::keys::saves_note(&key) => app.save_note(),
+                        KeyCode::Esc => app.cancel_note(),
+                        KeyCode::Enter => app.on_note_newline(),
+                        KeyCode::Backspace => app.on_note_backspace(),
+                        KeyCode::Char(c) => app.on_note_char(c),
+                        _ => {}
+                    }
+                }
+                Event::Key(key) if app.confirm_resume.is_some() => {
+                    match key.code {
+                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.should_quit = true;
+                        }
+                        KeyCode::Char('y') | KeyCode::Char('Y') => app.confirm_resume(true),
+                        _ => app.confirm_resume(false),
+                    }
+                }
+                Event::Key(key) if app.prompt.is_some() => {
+                    match key.code {
+                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                            app.should_quit = true;
+                        }
+                        KeyCode::Esc => app.cancel_prompt(),
+                        KeyCode::Enter => app.submit_prompt(),
+                        KeyCode::Backspace => app.on_prompt_backspace(),
+                        KeyCode::Char(c) => app.on_prompt_char(c),
+                        _ => {}
+                    }
+                }
+                Event::Key(key) => match key.code {
                     KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                         app.should_quit = true;
                     }
@@ -213,6 +791,10 @@ fn run(terminal: &mut tui::Tui, app: &mut App) -> Result<()> {
                     KeyCode::Tab => app.on_tab(),
                     KeyCode::Up => app.on_up(),
                     KeyCode::Down => app.on_down(),
+                    KeyCode::Left if key.modifiers.contains(KeyModifiers::ALT) => app.jump_back(),
+                    KeyCode::Right if key.modifiers.contains(KeyModifiers::ALT) => {
+                        app.jump_forward();
+                    }
                     KeyCode::Left => app.on_left(),
                     KeyCode::Right => app.on_right(),
                     KeyCode::Home => app.on_home(),
@@ -224,6 +806,25 @@ fn run(terminal: &mut tui::Tui, app: &mut App) -> Result<()> {
                     KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                         app.toggle_focused_expansion();
                     }
+                    KeyCode::Char('w') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.toggle_focused_wrap();
+                    }
+                    KeyCode::Char('x') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.on_copy_path();
+                    }
+                    KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.on_edit_tags();
+                    }
+                    KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.on_edit_note();
+                    }
+                    KeyCode::F(2) => app.on_rename(),
+                    KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.toggle_bookmark();
+                    }
+                    KeyCode::Char('o') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        app.on_open_folder();
+                    }
                     KeyCode::Char('/') => app.toggle_scope(),
                     KeyCode::Char(c) => app.on_char(c),
                     _ => {}
@@ -243,7 +844,9 @@ fn run(terminal: &mut tui::Tui, app: &mut App) -> Result<()> {
                             false
                         };
 
-                        if app.click_preview_message(x, y) {
+                        if app.click_results_list(x, y) {
+                            last_click = None;
+                        } else if app.click_preview_message(x, y) {
                             if is_double_click {
                                 app.toggle_focused_expansion();
                                 last_click = None; // Reset after double-click
@@ -258,6 +861,21 @@ fn run(terminal: &mut tui::Tui, app: &mut App) -> Result<()> {
             }
         }
 
+        // Copy without leaving the TUI
+        if let Some(text) = app.clipboard.take() {
+            match copy_to_clipboard(&text) {
+                Ok(()) => app.show_toast(format!("Copied {}", text)),
+                Err(e) => app.show_toast(format!("Couldn't copy: {}", e)),
+            }
+        }
+        if let Some(open) = app.should_open.take() {
+            match open_folder(terminal, output, height, &open) {
+                Ok(()) if open.terminal => {}
+                Ok(()) => app.show_toast(format!("Opened {}", open.cwd)),
+                Err(e) => app.show_toast(format!("Couldn't open {}: {:#}", open.cwd, e)),
+            }
+        }
+
         // Small sleep to prevent busy loop
         std::thread::sleep(Duration::from_millis(16));
     }
@@ -265,13 +883,48 @@ fn run(terminal: &mut tui::Tui, app: &mut App) -> Result<()> {
     Ok(())
 }
 
+/// Run the command that opens a session's folder. A terminal program gets the terminal until
+/// it exits; anything else is started in the background.
+fn open_folder(
+    terminal: &mut tui::Tui,
+    output: tui::Output,
+    height: Option<tui::Height>,
+    open: &OpenFolder,
+) -> Result<()> {
+    use std::process::{Command, Stdio};
+
+    let mut command = Command::new(&open.program);
+    command.args(&open.args).current_dir(&open.cwd);
+    if !open.terminal {
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Failed to run {}", open.program))?;
+        // Reap it whenever it exits
+        std::thread::spawn(move || child.wait());
+        return Ok(());
+    }
+
+    tui::restore(terminal, output)?;
+    let status = command.status();
+    *terminal = tui::init(output, height)?;
+    terminal.clear()?;
+    let status = status.with_context(|| format!("Failed to run {}", open.program))?;
+    if !status.success() {
+        anyhow::bail!("{} exited with {}", open.program, status);
+    }
+    Ok(())
+}
+
 /// Resume a session by exec'ing into the appropriate CLI
 #[cfg(unix)]
 fn resume_session(session: &session::Session) -> Result<()> {
     use std::os::unix::process::CommandExt;
 
-    // Change to conversation's working directory
-    if !session.cwd.is_empty() {
+    // Change to conversation's working directory (a remote session's is on the remote)
+    if !session.cwd.is_empty() && session.remote_host().is_none() {
         let _ = std::env::set_current_dir(&session.cwd);
     }
 
@@ -286,8 +939,8 @@ fn resume_session(session: &session::Session) -> Result<()> {
 
 #[cfg(not(unix))]
 fn resume_session(session: &session::Session) -> Result<()> {
-    // Change to conversation's working directory
-    if !session.cwd.is_empty() {
+    // Change to conversation's working directory (a remote session's is on the remote)
+    if !session.cwd.is_empty() && session.remote_host().is_none() {
         let _ = std::env::set_current_dir(&session.cwd);
     }
 
@@ -301,7 +954,7 @@ fn resume_session(session: &session::Session) -> Result<()> {
     Ok(())
 }
 
-/// Copy session ID to clipboard
+/// Copy text to the clipboard
 fn copy_to_clipboard(text: &str) -> Result<()> {
     use arboard::Clipboard;
     let mut clipboard = Clipboard::new()?;
@@ -309,17 +962,15 @@ fn copy_to_clipboard(text: &str) -> Result<()> {
     Ok(())
 }
 
-/// Clear the index cache directory
+/// Clear the index and its state from the cache directory, saying which one and who could
+/// read it. Tags, notes, and titles the user gave sessions are kept.
 fn clear_index_cache() {
-    let cache_dir = std::env::var("RECALL_HOME_OVERRIDE")
-        .map(|h| std::path::PathBuf::from(h).join(".cache").join("recall"))
-        .unwrap_or_else(|_| {
-            dirs::cache_dir()
-                .unwrap_or_else(|| std::path::PathBuf::from("."))
-                .join("recall")
-        });
+    let cache_dir = recall::index::cache::dir();
 
     if cache_dir.exists() {
-        let _ = std::fs::remove_dir_all(&cache_dir);
+        eprintln!("Clearing {}", recall::index::cache::describe(&cache_dir));
+        let index_path = cache_dir.join("index");
+        let _ = std::fs::remove_dir_all(&index_path);
+        let _ = std::fs::remove_file(recall::index::cache::state_path(&index_path));
     }
 }
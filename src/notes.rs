@@ -0,0 +1,62 @@
+//! A few lines the user writes about a session ("this is where we chose sqlite over sled"),
+//! kept in a sidecar file like [`crate::tags`]. They head the session's preview, stand in for
+//! its snippet in the recent list, and are indexed so searching finds them.
+
+use crate::sidecar::Sidecar;
+use anyhow::Result;
+use std::path::Path;
+
+/// Most lines a note keeps
+pub const MAX_LINES: usize = 3;
+
+static NOTES: Sidecar<String> = Sidecar::new("notes.json");
+
+/// The note on the session with this ID
+pub fn get(session_id: &str) -> Option<String> {
+    NOTES.get(session_id)
+}
+
+/// The note on the session with this ID, from the sidecar in `dir` rather than the cache dir
+pub fn get_in(dir: &Path, session_id: &str) -> Option<String> {
+    NOTES.get_in(dir, session_id)
+}
+
+/// The note on the session stored in `path`
+pub fn for_file(path: &Path) -> Option<String> {
+    NOTES.for_file(path)
+}
+
+/// The note on the session stored in `path`, from the sidecar in `dir`
+pub fn for_file_in(dir: &Path, path: &Path) -> Option<String> {
+    NOTES.for_file_in(dir, path)
+}
+
+/// Replace a session's note (an empty one removes it) and save the sidecar, unless the index
+/// is kept in memory. Returns whether anything changed.
+pub fn set(session_id: &str, path: &Path, note: &str) -> Result<bool> {
+    let note = clean(note);
+    NOTES.set(session_id, path, (!note.is_empty()).then_some(note))
+}
+
+/// A note as typed, without trailing whitespace, blank lines, or lines past [`MAX_LINES`]
+pub fn clean(input: &str) -> String {
+    input
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty())
+        .take(MAX_LINES)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean() {
+        assert_eq!(clean("chose sqlite  \n\n  over sled\n"), "chose sqlite\n  over sled");
+        assert_eq!(clean("a\nb\nc\nd"), "a\nb\nc");
+        assert_eq!(clean(" \n\t"), "");
+    }
+}
@@ -0,0 +1,275 @@
+//! Aider keeps one chat history per project, in `.aider.chat.history.md` at its root: each
+//! run starts with a `# aider chat started at` heading, prompts are `#### ` lines, tool output
+//! is quoted with `> `, and everything else is the model's reply. Prompts are dated from
+//! `.aider.input.history` beside it where they can be matched up.
+
+use crate::config;
+use crate::session::{Message, Role, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use std::path::{Path, PathBuf};
+
+use super::{
+    join_consecutive_messages, timeline::Timeline, DiscoveryPass, DiscoveryRoot, SessionParser,
+};
+
+/// The chat history file in a project's root
+pub const HISTORY_FILE: &str = ".aider.chat.history.md";
+
+/// Prompts as typed, each under a `# <time>` line, one `+` line per line of input
+const INPUT_HISTORY_FILE: &str = ".aider.input.history";
+
+const RUN_HEADING: &str = "# aider chat started at ";
+
+pub struct AiderParser;
+
+impl SessionParser for AiderParser {
+    fn can_parse(path: &Path) -> bool {
+        is_history(path)
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        let text = std::fs::read_to_string(path).context("Failed to read file")?;
+        let project = path.parent().unwrap_or(Path::new("."));
+        let inputs = std::fs::read_to_string(project.join(INPUT_HISTORY_FILE))
+            .map(|text| parse_inputs(&text))
+            .unwrap_or_default();
+
+        let mut chat = Chat { inputs, ..Default::default() };
+        for line in text.lines() {
+            if let Some(started) = line.strip_prefix(RUN_HEADING) {
+                chat.flush();
+                chat.timeline.observe(parse_local_time(started.trim()));
+            } else if let Some(prompt) = line.strip_prefix("####") {
+                chat.push(Role::User, prompt.strip_prefix(' ').unwrap_or(prompt));
+            } else if line.starts_with('>') {
+                // Tool output ("> Applied edit to app.py") ends a prompt but isn't a message
+                if chat.role == Some(Role::User) {
+                    chat.flush();
+                }
+            } else if chat.role == Some(Role::User) && line.trim().is_empty() {
+                // The blank line after a prompt
+                chat.flush();
+            } else if chat.role.is_some() || !line.trim().is_empty() {
+                chat.push(Role::Assistant, line);
+            }
+        }
+        chat.flush();
+
+        let Chat { mut messages, timeline, .. } = chat;
+        // Runs are dated when they start, so the file's mtime says when the last one ended
+        let mtime = std::fs::metadata(path).and_then(|m| m.modified()).ok().map(DateTime::from);
+        let timestamp = timeline.finish(&mut messages, path).max(mtime.unwrap_or_default());
+
+        Ok(Session {
+            id: session_id(project),
+            source: SessionSource::Aider,
+            file_path: path.to_path_buf(),
+            cwd: project.display().to_string(),
+            git_branch: None,
+            timestamp,
+            messages: join_consecutive_messages(messages, config::get().join_messages),
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: None,
+        })
+    }
+}
+
+/// Whether `path` is an Aider chat history
+pub(super) fn is_history(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| name == HISTORY_FILE)
+}
+
+/// Messages being read out of the history, line by line
+#[derive(Default)]
+struct Chat {
+    messages: Vec<Message>,
+    timeline: Timeline,
+    /// Role and lines of the message being read
+    role: Option<Role>,
+    lines: Vec<String>,
+    /// Dated prompts from the input history, and how many have been matched to messages
+    inputs: Vec<(DateTime<Utc>, String)>,
+    matched_inputs: usize,
+}
+
+impl Chat {
+    /// Add a line to the message being read, first finishing it if `role` starts another
+    fn push(&mut self, role: Role, line: &str) {
+        if self.role != Some(role) {
+            self.flush();
+            self.role = Some(role);
+        }
+        // Prompt lines end in a Markdown line break
+        self.lines.push(line.trim_end().to_string());
+    }
+
+    /// Finish the message being read
+    fn flush(&mut self) {
+        let Some(role) = self.role.take() else {
+            return;
+        };
+        let content = std::mem::take(&mut self.lines).join("\n").trim().to_string();
+        if content.is_empty() {
+            return;
+        }
+        if role == Role::User {
+            // The prompt's entry in the input history, skipping ones that never made it here
+            let unmatched = &self.inputs[self.matched_inputs..];
+            if let Some(i) = unmatched.iter().position(|(_, input)| *input == content) {
+                self.timeline.observe(Some(unmatched[i].0));
+                self.matched_inputs += i + 1;
+            }
+        }
+        let timestamp = self.timeline.stamp(&self.messages);
+        self.messages.push(Message { role, content, timestamp, tool_calls: Vec::new() });
+    }
+}
+
+/// Prompts from `.aider.input.history`, in the order they were typed
+fn parse_inputs(text: &str) -> Vec<(DateTime<Utc>, String)> {
+    let mut inputs: Vec<(DateTime<Utc>, String)> = Vec::new();
+    let mut time = None;
+    for line in text.lines() {
+        if let Some(stamp) = line.strip_prefix("# ") {
+            time = parse_local_time(stamp.trim());
+        } else if let (Some(input), Some(time)) = (line.strip_prefix('+'), time) {
+            match inputs.last_mut() {
+                Some((t, text)) if *t == time => {
+                    text.push('\n');
+                    text.push_str(input);
+                }
+                _ => inputs.push((time, input.to_string())),
+            }
+        }
+    }
+    for (_, text) in &mut inputs {
+        *text = text.trim().to_string();
+    }
+    inputs
+}
+
+/// A time Aider wrote in local time, like `2025-05-01 10:00:00` (fractional seconds allowed)
+fn parse_local_time(text: &str) -> Option<DateTime<Utc>> {
+    let naive = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f").ok()?;
+    naive.and_local_timezone(Local).earliest().map(|t| t.with_timezone(&Utc))
+}
+
+/// Aider has no session IDs, so the project's history gets one from its folder
+fn session_id(project: &Path) -> String {
+    // FNV-1a, to stay the same across runs and Rust versions
+    let hash = project.to_string_lossy().bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("aider-{:016x}", hash)
+}
+
+/// Chat histories in the folders sessions from the other sources under this home ran in,
+/// and in the configured `aider_projects` and `extra_roots`
+pub(super) fn discover(root: &DiscoveryRoot, pass: &DiscoveryPass) -> Vec<PathBuf> {
+    let mut projects = pass.project_folders(root);
+    projects.extend(config::get().aider_projects());
+    projects
+        .into_iter()
+        .map(|project| project.join(HISTORY_FILE))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HISTORY: &str = "
+# aider chat started at 2025-05-01 10:00:00
+
+> Aider v0.82.0
+> Main model: sonnet with diff edit format
+
+#### add a greet function
+#### that takes a name
+
+Here's the change:
+
+```python
+def greet(name):
+    return f\"hi {name}\"
+```
+
+> Applied edit to app.py
+> Commit 1a2b3c4 feat: Add greet function
+
+#### /undo
+
+> Removed: 1a2b3c4 feat: Add greet function
+
+# aider chat started at 2025-05-02 09:30:00
+
+#### why is the build red?
+
+The test imports `greet`, which no longer exists.
+";
+
+    const INPUTS: &str = "
+# 2025-05-01 10:00:05.123456
++add a greet function
++that takes a name
+
+# 2025-05-01 10:01:00.000000
++/undo
+
+# 2025-05-02 09:31:00.000000
++why is the build red?
+";
+
+    #[test]
+    fn test_parse_history() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join(HISTORY_FILE);
+        std::fs::write(&path, HISTORY).unwrap();
+        std::fs::write(temp.path().join(INPUT_HISTORY_FILE), INPUTS).unwrap();
+
+        let session = AiderParser::parse_file(&path).unwrap();
+        assert_eq!(session.source, SessionSource::Aider);
+        assert_eq!(session.cwd, temp.path().display().to_string());
+        assert_eq!(session.id, session_id(temp.path()));
+        let code = "def greet(name):\n    return f\"hi {name}\"";
+        let contents: Vec<_> = session
+            .messages
+            .iter()
+            .map(|m| (m.role, m.content.replace(code, "{CODE}")))
+            .collect();
+        assert_eq!(
+            contents,
+            [
+                (Role::User, "add a greet function\nthat takes a name"),
+                (Role::Assistant, "Here's the change:\n\n```python\n{CODE}\n```"),
+                (Role::User, "/undo"),
+                (Role::User, "why is the build red?"),
+                (Role::Assistant, "The test imports `greet`, which no longer exists."),
+            ]
+            .map(|(role, content)| (role, content.to_string()))
+        );
+
+        // Prompts are dated from the input history, replies like the prompt before them
+        let time = |text| parse_local_time(text).unwrap();
+        let stamps: Vec<_> = session.messages.iter().map(|m| m.timestamp).collect();
+        assert_eq!(stamps[0], time("2025-05-01 10:00:05.123456"));
+        assert_eq!(stamps[1], stamps[0]);
+        assert_eq!(stamps[4], time("2025-05-02 09:31:00"));
+    }
+
+    #[test]
+    fn test_parse_history_without_inputs() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join(HISTORY_FILE);
+        std::fs::write(&path, HISTORY).unwrap();
+
+        // Each run's messages are dated when it started
+        let session = AiderParser::parse_file(&path).unwrap();
+        let started = parse_local_time("2025-05-02 09:30:00").unwrap();
+        assert_eq!(session.messages.last().unwrap().timestamp, started);
+        assert!(session.timestamp > started);
+    }
+}
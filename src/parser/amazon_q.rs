@@ -0,0 +1,162 @@
+//! Amazon Q Developer CLI (`q chat`) keeps one conversation per directory in a SQLite
+//! database: the `conversations` table maps the directory to the conversation's state as
+//! JSON. Its `history` pairs each user message (a prompt, or tool results sent back) with the
+//! assistant's reply (text, or text and tool calls). Each row is a session, addressed as
+//! `data.sqlite3/<directory>` (see [`super::entry_path`]).
+
+use crate::config;
+use crate::session::{Message, Role, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use super::{join_consecutive_messages, sqlite, timeline::Timeline, SessionParser};
+
+/// The data folder, under the home
+#[cfg(target_os = "macos")]
+pub const DATA_DIR: &str = "Library/Application Support/amazon-q";
+#[cfg(not(target_os = "macos"))]
+pub const DATA_DIR: &str = ".local/share/amazon-q";
+
+/// The database in the data folder
+pub const DATABASE_FILE: &str = "data.sqlite3";
+
+const TABLE: &str = "conversations";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Conversation {
+    conversation_id: Option<String>,
+    /// `{user, assistant}` objects, or `[user, assistant]` pairs in older versions
+    history: Vec<Value>,
+}
+
+pub struct AmazonQParser;
+
+impl SessionParser for AmazonQParser {
+    fn can_parse(path: &Path) -> bool {
+        path.parent().is_some_and(|database| {
+            database.file_name().is_some_and(|name| name == DATABASE_FILE)
+                && database.parent().is_some_and(|dir| dir.ends_with("amazon-q"))
+        })
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        let (database, directory) =
+            super::split_entry(path).context("Not a conversation in a database")?;
+        let table = sqlite::read_table(database, TABLE)?;
+        let json = table
+            .rows
+            .iter()
+            .find(|row| table.get(row, "key").and_then(|key| key.as_text()) == Some(&directory))
+            .and_then(|row| table.get(row, "value")?.as_text())
+            .context("Conversation is no longer in the database")?;
+        let conversation: Conversation =
+            serde_json::from_str(json).context("Failed to parse Amazon Q conversation")?;
+
+        let mut timeline = Timeline::default();
+        let mut messages: Vec<Message> = Vec::new();
+        for entry in &conversation.history {
+            let (user, assistant) = match entry {
+                Value::Array(pair) => (pair.first(), pair.get(1)),
+                entry => (entry.get("user"), entry.get("assistant")),
+            };
+            let sent = user.and_then(|u| u.get("timestamp")).and_then(Value::as_str);
+            timeline.observe(sent.and_then(parse_time));
+            let texts = [
+                (Role::User, user.map(user_text).unwrap_or_default()),
+                (Role::Assistant, assistant.map(assistant_text).unwrap_or_default()),
+            ];
+            for (role, content) in texts {
+                if !content.is_empty() {
+                    let timestamp = timeline.stamp(&messages);
+                    messages.push(Message { role, content, timestamp, tool_calls: Vec::new() });
+                }
+            }
+        }
+
+        let session_id = conversation.conversation_id.unwrap_or_else(|| directory.clone());
+        let timestamp = timeline.finish(&mut messages, database);
+
+        Ok(Session {
+            id: session_id,
+            source: SessionSource::AmazonQ,
+            file_path: path.to_path_buf(),
+            cwd: directory,
+            git_branch: None,
+            timestamp,
+            messages: join_consecutive_messages(messages, config::get().join_messages),
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: None,
+        })
+    }
+}
+
+/// A session for each conversation in the database in `dir`. A database that can't be read
+/// right now yields none this time.
+pub(super) fn discover(dir: &Path) -> Vec<PathBuf> {
+    let database = dir.join(DATABASE_FILE);
+    let Ok(table) = sqlite::read_table(&database, TABLE) else {
+        return Vec::new();
+    };
+    table
+        .rows
+        .iter()
+        .filter_map(|row| table.get(row, "key")?.as_text())
+        .map(|directory| super::entry_path(&database, directory))
+        .collect()
+}
+
+/// What the user typed. Tool results sent back on their behalf aren't part of the
+/// conversation, like Claude's `tool_result` blocks.
+fn user_text(message: &Value) -> String {
+    ["/content/Prompt/prompt", "/content/CancelledToolUses/prompt"]
+        .iter()
+        .find_map(|pointer| message.pointer(pointer).and_then(Value::as_str))
+        .map(|text| text.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// The assistant's text; the calls of a tool-use reply are skipped, like Claude's `tool_use`
+/// blocks
+fn assistant_text(message: &Value) -> String {
+    ["/Response/content", "/ToolUse/content"]
+        .iter()
+        .find_map(|pointer| message.pointer(pointer).and_then(Value::as_str))
+        .map(|text| text.trim().to_string())
+        .unwrap_or_default()
+}
+
+fn parse_time(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text).ok().map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_message_texts() {
+        let prompt = json!({"content": {"Prompt": {"prompt": " why is the build red? "}}});
+        assert_eq!(user_text(&prompt), "why is the build red?");
+        let results = json!({"content": {"ToolUseResults": {"tool_use_results": []}}});
+        assert_eq!(user_text(&results), "");
+
+        let tool_use = json!({"ToolUse": {"message_id": "m1", "content": "Checking the logs.",
+                                          "tool_uses": [{"id": "t1", "name": "fs_read"}]}});
+        assert_eq!(assistant_text(&tool_use), "Checking the logs.");
+        let response = json!({"Response": {"message_id": "m2", "content": "A flaky test."}});
+        assert_eq!(assistant_text(&response), "A flaky test.");
+    }
+
+    #[test]
+    fn test_can_parse() {
+        let database = Path::new("/h/.local/share/amazon-q/data.sqlite3");
+        assert!(AmazonQParser::can_parse(&database.join("%2Fw%2Fshop")));
+        assert!(!AmazonQParser::can_parse(database));
+    }
+}
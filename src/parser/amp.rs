@@ -0,0 +1,184 @@
+//! Amp threads: `~/.local/share/amp/threads/T-<id>.json`, one document per thread with its
+//! `messages` (content blocks as in the Anthropic API, plus `meta.sentAt` times in
+//! milliseconds) and the workspace it started in under `env.initial.trees`.
+
+use crate::config;
+use crate::session::{Message, Role, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::DateTime;
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use super::{join_consecutive_messages, timeline::Timeline, SessionParser};
+
+#[derive(Debug, Deserialize)]
+struct AmpThread {
+    id: Option<String>,
+    /// Unix milliseconds
+    created: Option<i64>,
+    #[serde(default)]
+    messages: Vec<AmpMessage>,
+    #[serde(default)]
+    env: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct AmpMessage {
+    role: String,
+    #[serde(default)]
+    content: Value,
+    #[serde(default)]
+    meta: Value,
+}
+
+pub struct AmpParser;
+
+impl SessionParser for AmpParser {
+    fn can_parse(path: &Path) -> bool {
+        path.to_str()
+            .map(|s| s.contains("amp/threads") || s.contains("amp\\threads"))
+            .unwrap_or(false)
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        let file = File::open(path).context("Failed to open file")?;
+        let thread: AmpThread = serde_json::from_reader(BufReader::new(file))
+            .context("Failed to parse Amp thread")?;
+
+        let mut timeline = Timeline::default();
+        timeline.observe(thread.created.and_then(DateTime::from_timestamp_millis));
+        let mut messages: Vec<Message> = Vec::new();
+
+        for msg in &thread.messages {
+            let sent_at = msg.meta.get("sentAt").and_then(Value::as_i64);
+            timeline.observe(sent_at.and_then(DateTime::from_timestamp_millis));
+
+            let role = match msg.role.as_str() {
+                "user" => Role::User,
+                "assistant" => Role::Assistant,
+                _ => continue,
+            };
+
+            let content = extract_content(&msg.content);
+            if !content.is_empty() {
+                let timestamp = timeline.stamp(&messages);
+                messages.push(Message { role, content, timestamp, tool_calls: Vec::new() });
+            }
+        }
+
+        let session_id = thread.id.unwrap_or_else(|| {
+            path.file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("unknown")
+                .to_string()
+        });
+
+        let timestamp = timeline.finish(&mut messages, path);
+
+        Ok(Session {
+            id: session_id,
+            source: SessionSource::Amp,
+            file_path: path.to_path_buf(),
+            cwd: workspace(&thread.env).unwrap_or_else(|| ".".to_string()),
+            git_branch: None,
+            timestamp,
+            messages: join_consecutive_messages(messages, config::get().join_messages),
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: None,
+        })
+    }
+}
+
+/// Extract text content from an Amp message's content field.
+/// Content is a string or an array of {type, text} blocks; tool_use, tool_result and
+/// thinking blocks are skipped, as are system-reminder blocks Amp adds to user messages.
+fn extract_content(content: &Value) -> String {
+    let blocks = match content {
+        Value::String(text) => return text.trim().to_string(),
+        Value::Array(blocks) => blocks,
+        _ => return String::new(),
+    };
+
+    let mut texts = Vec::new();
+    for block in blocks {
+        if block.get("type").and_then(Value::as_str) != Some("text") {
+            continue;
+        }
+        if let Some(text) = block.get("text").and_then(Value::as_str) {
+            let trimmed = text.trim();
+            if trimmed.is_empty()
+                || (trimmed.starts_with("<system-reminder>")
+                    && trimmed.ends_with("</system-reminder>"))
+            {
+                continue;
+            }
+            texts.push(text.to_string());
+        }
+    }
+    texts.join("\n")
+}
+
+/// The first workspace folder the thread started in (`file://` URIs)
+fn workspace(env: &Value) -> Option<String> {
+    let trees = env.pointer("/initial/trees")?.as_array()?;
+    trees
+        .iter()
+        .filter_map(|tree| tree.get("uri").and_then(Value::as_str))
+        .find_map(|uri| uri.strip_prefix("file://"))
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_thread() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let dir = temp.path().join(".local/share/amp/threads");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("T-7a1b.json");
+        let thread = json!({
+            "v": 42,
+            "id": "T-7a1b",
+            "created": 1748772000000i64,
+            "env": {"initial": {"trees": [{"displayName": "api", "uri": "file:///w/api"}]}},
+            "messages": [
+                {"role": "user", "meta": {"sentAt": 1748772001000i64}, "content": [
+                    {"type": "text", "text": "add rate limiting"},
+                    {"type": "text", "text": "<system-reminder>Todos are empty</system-reminder>"}
+                ]},
+                {"role": "assistant", "content": [
+                    {"type": "thinking", "thinking": "Middleware, probably."},
+                    {"type": "text", "text": "I'll add a middleware."},
+                    {"type": "tool_use", "id": "t1", "name": "edit_file", "input": {}}
+                ]},
+                {"role": "user", "content": [
+                    {"type": "tool_result", "toolUseID": "t1", "run": {"status": "done"}}
+                ]},
+                {"role": "assistant", "content": [{"type": "text", "text": "Added."}]}
+            ]
+        });
+        std::fs::write(&path, thread.to_string()).unwrap();
+        assert!(AmpParser::can_parse(&path));
+
+        let session = AmpParser::parse_file(&path).unwrap();
+        assert_eq!(session.id, "T-7a1b");
+        assert_eq!(session.source, SessionSource::Amp);
+        assert_eq!(session.cwd, "/w/api");
+        // The tool result between the replies isn't a message, so they're joined
+        assert_eq!(session.messages.len(), 2);
+        assert_eq!(session.messages[0].content, "add rate limiting");
+        assert_eq!(
+            session.messages[0].timestamp,
+            DateTime::from_timestamp_millis(1748772001000).unwrap()
+        );
+        assert!(session.messages[1].content.starts_with("I'll add a middleware."));
+        assert!(session.messages[1].content.ends_with("Added."));
+    }
+}
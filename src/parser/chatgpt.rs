@@ -0,0 +1,203 @@
+//! ChatGPT's data export (Settings → Data controls → Export) has every conversation in
+//! `conversations.json`. Each conversation's messages are a tree, `mapping`, since editing a
+//! message or regenerating a reply branches it; the branch shown last is the path from
+//! `current_node` back to the root. There's nothing to discover: `recall import chatgpt`
+//! converts an export into imported sessions.
+
+use crate::config;
+use crate::session::{Message, Role, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use super::{join_consecutive_messages, timeline::Timeline};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Conversation {
+    /// Set in newer exports; `id` in older ones
+    conversation_id: Option<String>,
+    id: Option<String>,
+    /// Unix seconds, with a fraction
+    create_time: Option<f64>,
+    update_time: Option<f64>,
+    mapping: HashMap<String, Node>,
+    current_node: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Node {
+    parent: Option<String>,
+    message: Option<ChatMessage>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ChatMessage {
+    author: Author,
+    create_time: Option<f64>,
+    content: Value,
+    metadata: Value,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Author {
+    role: String,
+}
+
+/// The conversations in a ChatGPT export's `conversations.json`, as sessions filed under
+/// the export. Conversations without an id are skipped.
+pub fn read_chatgpt_export(path: &Path) -> Result<Vec<Session>> {
+    let file = File::open(path).context("Failed to open file")?;
+    let conversations: Vec<Conversation> = serde_json::from_reader(BufReader::new(file))
+        .context("Not a ChatGPT conversations.json")?;
+    Ok(conversations
+        .into_iter()
+        .filter_map(|conversation| to_session(conversation, path))
+        .collect())
+}
+
+fn to_session(conversation: Conversation, path: &Path) -> Option<Session> {
+    let id = conversation.conversation_id.clone().or(conversation.id.clone())?;
+
+    let mut timeline = Timeline::default();
+    timeline.observe(conversation.create_time.and_then(unix_time));
+    let mut messages: Vec<Message> = Vec::new();
+    for message in branch(&conversation) {
+        timeline.observe(message.create_time.and_then(unix_time));
+        // System prompts and tool output aren't part of the conversation
+        let role = match message.author.role.as_str() {
+            "user" => Role::User,
+            "assistant" => Role::Assistant,
+            _ => continue,
+        };
+        let hidden = message.metadata.get("is_visually_hidden_from_conversation");
+        if hidden.and_then(Value::as_bool) == Some(true) {
+            continue;
+        }
+        let content = extract_content(&message.content);
+        if !content.is_empty() {
+            let timestamp = timeline.stamp(&messages);
+            messages.push(Message { role, content, timestamp, tool_calls: Vec::new() });
+        }
+    }
+    timeline.observe(conversation.update_time.and_then(unix_time));
+    let timestamp = timeline.finish(&mut messages, path);
+
+    Some(Session {
+        id,
+        source: SessionSource::ChatGpt,
+        file_path: path.to_path_buf(),
+        // Chats aren't tied to a folder
+        cwd: ".".to_string(),
+        git_branch: None,
+        timestamp,
+        messages: join_consecutive_messages(messages, config::get().join_messages),
+        files_touched: Vec::new(),
+        usage: Vec::new(),
+        title: None,
+    })
+}
+
+/// The messages on the branch ending at `current_node`, oldest first
+fn branch(conversation: &Conversation) -> Vec<&ChatMessage> {
+    let mut messages = Vec::new();
+    let mut seen = HashSet::new();
+    let mut next = conversation.current_node.as_deref();
+    // A malformed export could link a node back to itself
+    while let Some(id) = next.filter(|id| seen.insert(*id)) {
+        let Some(node) = conversation.mapping.get(id) else {
+            break;
+        };
+        messages.extend(node.message.as_ref());
+        next = node.parent.as_deref();
+    }
+    messages.reverse();
+    messages
+}
+
+/// The text of a message: the string `parts` of `text` and `multimodal_text` content
+/// (images and other attachments are dropped). Code the model ran and its results are skipped.
+fn extract_content(content: &Value) -> String {
+    let kind = content.get("content_type").and_then(Value::as_str);
+    if !matches!(kind, Some("text" | "multimodal_text")) {
+        return String::new();
+    }
+    content
+        .get("parts")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn unix_time(seconds: f64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp_millis((seconds * 1000.0) as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_read_export() {
+        let export = json!([{
+            "title": "Regex help",
+            "create_time": 1748772000.5,
+            "update_time": 1748772300.0,
+            "conversation_id": "6842a1b0-77c4-8000-9e1d-2f3c4b5a6d7e",
+            "current_node": "a2",
+            "mapping": {
+                "root": {"id": "root", "message": null, "parent": null, "children": ["sys"]},
+                "sys": {"id": "sys", "parent": "root", "children": ["u1"], "message": {
+                    "author": {"role": "system"}, "create_time": null,
+                    "content": {"content_type": "text", "parts": [""]},
+                    "metadata": {"is_visually_hidden_from_conversation": true}}},
+                "u1": {"id": "u1", "parent": "sys", "children": ["a1", "a2"], "message": {
+                    "author": {"role": "user"}, "create_time": 1748772060.0,
+                    "content": {"content_type": "multimodal_text", "parts": [
+                        {"content_type": "image_asset_pointer", "asset_pointer": "file://x"},
+                        "match an email address"]}}},
+                "a1": {"id": "a1", "parent": "u1", "children": [], "message": {
+                    "author": {"role": "assistant"}, "create_time": 1748772090.0,
+                    "content": {"content_type": "text", "parts": ["A regenerated reply."]}}},
+                "a2": {"id": "a2", "parent": "u1", "children": [], "message": {
+                    "author": {"role": "assistant"}, "create_time": 1748772120.0,
+                    "content": {"content_type": "text", "parts": ["Try \\S+@\\S+."]}}}
+            }
+        }, {"title": "No id", "mapping": {}}]);
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("conversations.json");
+        std::fs::write(&path, export.to_string()).unwrap();
+
+        let sessions = read_chatgpt_export(&path).unwrap();
+        assert_eq!(sessions.len(), 1);
+        let session = &sessions[0];
+        assert_eq!(session.id, "6842a1b0-77c4-8000-9e1d-2f3c4b5a6d7e");
+        assert_eq!(session.source, SessionSource::ChatGpt);
+        let contents: Vec<_> =
+            session.messages.iter().map(|m| (m.role, m.content.as_str())).collect();
+        // Only the branch that ends at current_node
+        assert_eq!(
+            contents,
+            [(Role::User, "match an email address"), (Role::Assistant, "Try \\S+@\\S+.")]
+        );
+        assert_eq!(session.messages[1].timestamp, unix_time(1748772120.0).unwrap());
+        assert_eq!(session.timestamp, unix_time(1748772300.0).unwrap());
+
+        std::fs::write(&path, "{}").unwrap();
+        assert!(read_chatgpt_export(&path).is_err());
+    }
+}
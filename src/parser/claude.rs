@@ -1,12 +1,14 @@
-use crate::session::{Message, Role, Session, SessionSource};
+use crate::config;
+use crate::session::{FileTouch, Message, ModelUsage, Role, Session, SessionSource};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use std::fs::File;
+use std::collections::HashMap;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
-use super::{join_consecutive_messages, SessionParser};
+use super::{files, join_consecutive_messages, timeline::Timeline, tools::OpenCalls, SessionParser};
 
 #[derive(Debug, Deserialize)]
 struct ClaudeLine {
@@ -19,6 +21,8 @@ struct ClaudeLine {
     git_branch: Option<String>,
     timestamp: Option<String>,
     message: Option<ClaudeMessage>,
+    /// The conversation's title, on `summary` lines
+    summary: Option<String>,
     /// Compaction summary flag (v2.0.56+)
     #[serde(rename = "isCompactSummary")]
     is_compact_summary: Option<bool>,
@@ -34,10 +38,34 @@ struct ClaudeLine {
 struct ClaudeMessage {
     role: String,
     content: serde_json::Value,
+    /// API message ID (repeated on every line of a multi-block response)
+    id: Option<String>,
+    model: Option<String>,
+    usage: Option<ClaudeUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaudeUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    cache_creation_input_tokens: u64,
+    #[serde(default)]
+    cache_read_input_tokens: u64,
 }
 
 pub struct ClaudeParser;
 
+/// Whether `path` is a subagent's transcript (`agent-<id>.jsonl`), which sits with the
+/// sessions but can't be resumed on its own
+pub fn is_subagent_transcript(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("agent-"))
+}
+
 impl SessionParser for ClaudeParser {
     fn can_parse(path: &Path) -> bool {
         // Claude Code sessions are in ~/.claude/projects/
@@ -53,8 +81,14 @@ impl SessionParser for ClaudeParser {
         let mut session_id: Option<String> = None;
         let mut cwd: Option<String> = None;
         let mut git_branch: Option<String> = None;
-        let mut latest_timestamp: Option<DateTime<Utc>> = None;
+        let mut timeline = Timeline::default();
         let mut messages: Vec<Message> = Vec::new();
+        let mut files_touched: Vec<FileTouch> = Vec::new();
+        // Usage by API message ID: each content block of a response repeats it
+        let mut usage_by_id: HashMap<String, ModelUsage> = HashMap::new();
+        let mut usage: Vec<ModelUsage> = Vec::new();
+        let mut title: Option<String> = None;
+        let mut calls = OpenCalls::default();
 
         for line in reader.lines() {
             let line = line.context("Failed to read line")?;
@@ -67,20 +101,25 @@ impl SessionParser for ClaudeParser {
                 Err(_) => continue, // Skip malformed lines
             };
 
+            // The latest summary titles the conversation
+            if entry.entry_type == "summary" {
+                title = entry.summary.filter(|s| !s.trim().is_empty()).or(title);
+                continue;
+            }
+
             // Skip non-message entries
             if entry.entry_type != "user" && entry.entry_type != "assistant" {
                 continue;
             }
 
-            // Skip synthetic messages (not actual user input):
-            // - Compaction summaries (v2.0.56+ isCompactSummary, v2.0.55 isVisibleInTranscriptOnly)
-            // - Slash command prompt expansions (isMeta)
-            if entry.is_compact_summary == Some(true)
-                || entry.is_visible_in_transcript_only == Some(true)
-                || entry.is_meta == Some(true)
-            {
+            // Skip slash command prompt expansions (isMeta), which aren't actual user input
+            if entry.is_meta == Some(true) {
                 continue;
             }
+            // A compaction summary (v2.0.56+ isCompactSummary, v2.0.55 isVisibleInTranscriptOnly)
+            // stands in for the conversation before it, so it's kept apart as context
+            let compacted = entry.is_compact_summary == Some(true)
+                || entry.is_visible_in_transcript_only == Some(true);
 
             // Extract session metadata from the first valid message
             if session_id.is_none() {
@@ -93,49 +132,81 @@ impl SessionParser for ClaudeParser {
                 git_branch = entry.git_branch.clone();
             }
 
-            // Parse timestamp
-            let timestamp = entry
-                .timestamp
-                .as_ref()
-                .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
-                .map(|dt| dt.with_timezone(&Utc))
-                .unwrap_or_else(Utc::now);
-
-            // Update latest timestamp
-            if latest_timestamp.is_none() || timestamp > latest_timestamp.unwrap() {
-                latest_timestamp = Some(timestamp);
-            }
+            timeline.observe(
+                entry
+                    .timestamp
+                    .as_ref()
+                    .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+                    .map(|dt| dt.with_timezone(&Utc)),
+            );
 
             // Extract message content
             if let Some(msg) = &entry.message {
                 let role = match msg.role.as_str() {
+                    _ if compacted => Role::Context,
                     "user" => Role::User,
                     "assistant" => Role::Assistant,
                     _ => continue,
                 };
 
-                let content = extract_content(&msg.content);
-                if content.is_empty() {
-                    continue;
+                let content = extract_content(&msg.content, config::get().include_thinking);
+
+                if !content.is_empty() {
+                    let timestamp = timeline.stamp(&messages);
+                    messages.push(Message {
+                        role,
+                        content,
+                        timestamp,
+                        tool_calls: Vec::new(),
+                    });
                 }
 
-                // Skip slash command expansions (internal Claude Code messages)
-                let trimmed = content.trim_start();
-                if trimmed.starts_with("<command-message>")
-                    || trimmed.starts_with("<command-name>")
-                {
-                    continue;
+                if let (Some(model), Some(u)) = (&msg.model, &msg.usage) {
+                    // "<synthetic>" marks locally generated messages (errors, interruptions)
+                    if model != "<synthetic>" {
+                        let model_usage = ModelUsage {
+                            model: model.clone(),
+                            input_tokens: u.input_tokens,
+                            output_tokens: u.output_tokens,
+                            cache_read_tokens: u.cache_read_input_tokens,
+                            cache_write_tokens: u.cache_creation_input_tokens,
+                        };
+                        match &msg.id {
+                            Some(id) => {
+                                usage_by_id.insert(id.clone(), model_usage);
+                            }
+                            None => ModelUsage::accumulate(&mut usage, model_usage),
+                        }
+                    }
                 }
 
-                messages.push(Message {
-                    role,
-                    content,
-                    timestamp,
-                });
+                // Tool calls are attributed to the message they followed, and their results
+                // come back in the next user entry, apart from any text the user added
+                calls.record_tool_results(&msg.content, &mut messages);
+                calls.push_tool_uses(&msg.content, &mut messages);
+                for (op, path) in files::tool_use_file_ops(&msg.content) {
+                    files_touched.push(FileTouch {
+                        path,
+                        op,
+                        message_index: files::latest_index(&messages),
+                    });
+                }
             }
         }
 
-        // Fall back to filename for session ID if not found
+        let cwd = cwd.unwrap_or_else(|| ".".to_string());
+        files::resolve_paths(&mut files_touched, &cwd);
+
+        let mut by_id: Vec<_> = usage_by_id.into_iter().collect();
+        by_id.sort_by(|a, b| a.0.cmp(&b.0));
+        for (_, model_usage) in by_id {
+            ModelUsage::accumulate(&mut usage, model_usage);
+        }
+        usage.sort_by(|a, b| a.model.cmp(&b.model));
+
+        // Fall back to filename for session ID if not found. A subagent's transcript carries the
+        // ID of the session that started it, so it goes by its file's name, as a session apart.
+        let session_id = session_id.filter(|_| !is_subagent_transcript(path));
         let session_id = session_id.unwrap_or_else(|| {
             path.file_stem()
                 .and_then(|s| s.to_str())
@@ -143,37 +214,59 @@ impl SessionParser for ClaudeParser {
                 .to_string()
         });
 
+        let timestamp = timeline.finish(&mut messages, path);
+        let join = config::get().join_messages;
+        files::rejoin(&mut files_touched, &messages, join);
+
         Ok(Session {
             id: session_id,
             source: SessionSource::ClaudeCode,
             file_path: path.to_path_buf(),
-            cwd: cwd.unwrap_or_else(|| ".".to_string()),
+            cwd,
             git_branch,
-            timestamp: latest_timestamp.unwrap_or_else(Utc::now),
-            messages: join_consecutive_messages(messages),
+            timestamp,
+            messages: join_consecutive_messages(messages, join),
+            files_touched,
+            usage,
+            title,
         })
     }
 }
 
 /// Extract text content from Claude's message content field.
 /// - User messages: content is a plain string
-/// - Assistant messages: content is an array of {type, text} objects
-fn extract_content(content: &serde_json::Value) -> String {
+/// - Assistant messages: content is an array of {type, text} objects, and of thinking
+///   blocks, kept marked `[thinking]` when `thinking` is on
+/// - User messages with tool results: an array of tool_result blocks (recorded on their tool
+///   calls instead), and of text blocks when the user added a follow-up
+fn extract_content(content: &serde_json::Value, thinking: bool) -> String {
     match content {
         // Direct string (user messages)
-        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::String(s) => strip_command_wrappers(s),
 
         // Array of content blocks (assistant messages)
         serde_json::Value::Array(arr) => {
             let mut texts = Vec::new();
             for item in arr {
-                if let Some(obj) = item.as_object() {
-                    // Only extract "text" type blocks, skip tool_use, thinking, etc.
-                    if obj.get("type").and_then(|v| v.as_str()) == Some("text") {
-                        if let Some(text) = obj.get("text").and_then(|v| v.as_str()) {
-                            texts.push(text.to_string());
+                let Some(obj) = item.as_object() else {
+                    continue;
+                };
+                // Skip tool_use, tool_result, redacted_thinking, etc.
+                match obj.get("type").and_then(|v| v.as_str()) {
+                    Some("text") => {
+                        let text = obj.get("text").and_then(|v| v.as_str()).unwrap_or("");
+                        let text = strip_command_wrappers(text);
+                        if !text.is_empty() {
+                            texts.push(text);
+                        }
+                    }
+                    Some("thinking") if thinking => {
+                        let text = obj.get("thinking").and_then(|v| v.as_str()).unwrap_or("");
+                        if !text.trim().is_empty() {
+                            texts.push(format!("[thinking] {}", text.trim()));
                         }
                     }
+                    _ => {}
                 }
             }
             texts.join("\n")
@@ -183,6 +276,40 @@ fn extract_content(content: &serde_json::Value) -> String {
     }
 }
 
+/// Blocks Claude Code wraps slash commands and their local output in, added to the user's
+/// side of the conversation without being part of it
+const COMMAND_TAGS: [&str; 6] = [
+    "command-message",
+    "command-name",
+    "command-args",
+    "local-command-stdout",
+    "local-command-stderr",
+    "local-command-caveat",
+];
+
+/// `text` without its slash command blocks. Like Factory's system reminders, a block is only
+/// removed with both its tags, so a message that mentions one is kept as it is.
+fn strip_command_wrappers(text: &str) -> String {
+    let mut text = text.to_string();
+    let mut stripped = false;
+    for tag in COMMAND_TAGS {
+        let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+        let mut from = 0;
+        while let Some(start) = text[from..].find(&open).map(|i| from + i) {
+            let Some(end) = text[start..].find(&close).map(|i| start + i + close.len()) else {
+                break;
+            };
+            text.replace_range(start..end, "");
+            stripped = true;
+            from = start;
+        }
+    }
+    match stripped {
+        true => text.trim().to_string(),
+        false => text,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -190,7 +317,7 @@ mod tests {
     #[test]
     fn test_extract_content_string() {
         let content = serde_json::json!("Hello, world!");
-        assert_eq!(extract_content(&content), "Hello, world!");
+        assert_eq!(extract_content(&content, false), "Hello, world!");
     }
 
     #[test]
@@ -200,7 +327,49 @@ mod tests {
             {"type": "tool_use", "name": "Read"},
             {"type": "text", "text": "World"}
         ]);
-        assert_eq!(extract_content(&content), "Hello\nWorld");
+        assert_eq!(extract_content(&content, false), "Hello\nWorld");
+    }
+
+    #[test]
+    fn test_extract_content_tool_results() {
+        let content = serde_json::json!([
+            {"type": "tool_result", "tool_use_id": "t1", "content": "src/\ntests/"},
+            {"type": "text", "text": "also check the migrations"}
+        ]);
+        assert_eq!(extract_content(&content, false), "also check the migrations");
+    }
+
+    #[test]
+    fn test_strip_command_wrappers() {
+        let invocation = "<command-message>review is running…</command-message>\n\
+            <command-name>/review</command-name>\n<command-args>auth</command-args>";
+        assert_eq!(strip_command_wrappers(invocation), "");
+        let output = "<local-command-stdout>Set model to opus</local-command-stdout>";
+        assert_eq!(strip_command_wrappers(output), "");
+        let mixed = "<command-name>/clear</command-name>\nnow fix the login redirect";
+        assert_eq!(strip_command_wrappers(mixed), "now fix the login redirect");
+        let quoted = "why does <command-name> show up in my transcript?";
+        assert_eq!(strip_command_wrappers(quoted), quoted);
+
+        let blocks = serde_json::json!([
+            {"type": "text", "text": "<local-command-stdout></local-command-stdout>"},
+            {"type": "text", "text": "what did that print?"}
+        ]);
+        assert_eq!(extract_content(&blocks, false), "what did that print?");
+    }
+
+    #[test]
+    fn test_extract_content_thinking() {
+        let content = serde_json::json!([
+            {"type": "thinking", "thinking": "The config is read once.\n", "signature": "x"},
+            {"type": "redacted_thinking", "data": "opaque"},
+            {"type": "text", "text": "It's cached in a OnceLock."}
+        ]);
+        assert_eq!(extract_content(&content, false), "It's cached in a OnceLock.");
+        assert_eq!(
+            extract_content(&content, true),
+            "[thinking] The config is read once.\nIt's cached in a OnceLock."
+        );
     }
 
 }
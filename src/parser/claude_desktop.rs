@@ -0,0 +1,299 @@
+//! The Claude desktop app is a Chromium shell around claude.ai, and keeps the conversations
+//! it has loaded in Chromium's Local Storage: a LevelDB store in its data folder (see
+//! [`super::leveldb`]). Each value is a string, UTF-16 or Latin-1 after a marker byte; the
+//! ones holding JSON are searched for conversations in the claude.ai API's shape, a `uuid`
+//! with `chat_messages`. Each conversation is a session titled with its name, addressed as
+//! `<newest log>/<conversation uuid>` (see [`super::entry_path`]) so it's dated by the
+//! store's last write. Conversations aren't tied to a folder, so their cwd is empty and
+//! they only show when searching everywhere; the app has no way to reopen one from outside.
+
+use crate::config;
+use crate::session::{Message, Role, Session, SessionSource};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::{join_consecutive_messages, leveldb, timeline::Timeline, SessionParser};
+
+/// The Local Storage store, under the home
+#[cfg(target_os = "macos")]
+pub const STORE_DIR: &str = "Library/Application Support/Claude/Local Storage/leveldb";
+#[cfg(windows)]
+pub const STORE_DIR: &str = "AppData/Roaming/Claude/Local Storage/leveldb";
+#[cfg(not(any(target_os = "macos", windows)))]
+pub const STORE_DIR: &str = ".config/Claude/Local Storage/leveldb";
+
+/// The store, in the app's data folder
+const STORE: &str = "Local Storage/leveldb";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Conversation {
+    uuid: String,
+    /// The conversation's title
+    name: String,
+    /// RFC 3339, as are the messages'
+    created_at: Option<String>,
+    updated_at: Option<String>,
+    chat_messages: Vec<ChatMessage>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ChatMessage {
+    /// `human` or `assistant`
+    sender: String,
+    /// The message's text, in older responses; `content` blocks in newer ones
+    text: String,
+    content: Vec<Value>,
+    created_at: Option<String>,
+}
+
+pub struct ClaudeDesktopParser;
+
+impl SessionParser for ClaudeDesktopParser {
+    fn can_parse(path: &Path) -> bool {
+        // A conversation is <store>/<log>/<uuid>; the store itself stands in for them when it
+        // can't be read
+        let store = match path.ends_with(STORE) {
+            true => Some(path),
+            false => path.parent().and_then(Path::parent),
+        };
+        store.is_some_and(|store| {
+            store.ends_with(STORE)
+                && store.parent().and_then(|p| p.parent()).is_some_and(|app| {
+                    app.file_name().is_some_and(|name| name == "Claude")
+                })
+        })
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        if path.is_dir() {
+            read_conversations(path)?;
+            bail!("Claude Desktop's store can be read again; indexed on the next pass");
+        }
+        let (log, uuid) = super::split_entry(path).context("Not a conversation in the store")?;
+        let store = log.parent().context("Not a conversation in the store")?;
+        let conversations = read_conversations(store)?;
+        let conversation =
+            conversations.get(&uuid).context("Conversation is no longer in the store")?;
+
+        let mut timeline = Timeline::default();
+        timeline.observe(conversation.created_at.as_deref().and_then(parse_time));
+        let mut messages: Vec<Message> = Vec::new();
+        for message in &conversation.chat_messages {
+            timeline.observe(message.created_at.as_deref().and_then(parse_time));
+            let role = match message.sender.as_str() {
+                "human" => Role::User,
+                "assistant" => Role::Assistant,
+                _ => continue,
+            };
+            let content = extract_text(message);
+            if !content.is_empty() {
+                let timestamp = timeline.stamp(&messages);
+                messages.push(Message { role, content, timestamp, tool_calls: Vec::new() });
+            }
+        }
+        timeline.observe(conversation.updated_at.as_deref().and_then(parse_time));
+        let timestamp = timeline.finish(&mut messages, log);
+
+        Ok(Session {
+            id: uuid,
+            source: SessionSource::ClaudeDesktop,
+            file_path: path.to_path_buf(),
+            // Not tied to a folder: shown only when searching everywhere
+            cwd: String::new(),
+            git_branch: None,
+            timestamp,
+            messages: join_consecutive_messages(messages, config::get().join_messages),
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: Some(conversation.name.trim().to_string()).filter(|name| !name.is_empty()),
+        })
+    }
+}
+
+/// A session for each conversation in the store at `dir`. A store that can't be read (the
+/// app may hold it locked) is returned itself, so that indexing reports it.
+pub(super) fn discover(dir: &Path) -> Vec<PathBuf> {
+    if !dir.is_dir() {
+        return Vec::new();
+    }
+    let Ok(conversations) = read_conversations(dir) else {
+        return vec![dir.to_path_buf()];
+    };
+    let Some(anchor) = newest_log(dir) else {
+        return Vec::new();
+    };
+    let mut uuids: Vec<&String> = conversations.keys().collect();
+    uuids.sort();
+    uuids.into_iter().map(|uuid| super::entry_path(&anchor, uuid)).collect()
+}
+
+/// The store's write-ahead log with the highest number, the one written to last; `CURRENT`
+/// for a store that has none
+fn newest_log(dir: &Path) -> Option<PathBuf> {
+    let logs = std::fs::read_dir(dir).ok()?.flatten().filter_map(|entry| {
+        let name = entry.file_name().to_str()?.to_string();
+        let number: u64 = name.strip_suffix(".log")?.parse().ok()?;
+        Some((number, entry.path()))
+    });
+    match logs.max() {
+        Some((_, log)) => Some(log),
+        None => Some(dir.join("CURRENT")).filter(|current| current.is_file()),
+    }
+}
+
+/// Conversations by uuid
+type Conversations = HashMap<String, Conversation>;
+
+/// The conversations found in the store read last, while it is the same read
+struct ReadConversations {
+    store: Arc<leveldb::Store>,
+    conversations: Arc<Conversations>,
+}
+
+/// Each conversation is parsed from the same store, so its values are decoded once for all
+/// of them rather than once each. The store's own read is reused until its files change.
+static LAST_READ: Mutex<Option<ReadConversations>> = Mutex::new(None);
+
+/// Every conversation in the store, by uuid. A conversation stored more than once (the
+/// app caches lists as well as open chats) keeps its copy with the most messages.
+fn read_conversations(dir: &Path) -> Result<Arc<Conversations>> {
+    let store = leveldb::read_store(dir).with_context(|| {
+        format!(
+            "Claude Desktop's conversation store couldn't be read; is the app holding it \
+             locked? ({})",
+            dir.display()
+        )
+    })?;
+    let mut last = LAST_READ.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(read) = last.as_ref().filter(|read| Arc::ptr_eq(&read.store, &store)) {
+        return Ok(Arc::clone(&read.conversations));
+    }
+
+    let mut conversations: Conversations = HashMap::new();
+    for value in store.values() {
+        let Some(json) = decode_string(value).and_then(|s| serde_json::from_str(&s).ok()) else {
+            continue;
+        };
+        let mut found = Vec::new();
+        find_conversations(json, &mut found);
+        for conversation in found {
+            let kept = conversations.get(&conversation.uuid);
+            if kept.is_none_or(|kept| kept.chat_messages.len() < conversation.chat_messages.len())
+            {
+                conversations.insert(conversation.uuid.clone(), conversation);
+            }
+        }
+    }
+    let conversations = Arc::new(conversations);
+    *last = Some(ReadConversations { store, conversations: Arc::clone(&conversations) });
+    Ok(conversations)
+}
+
+/// A Local Storage value: a marker byte, then UTF-16LE (0) or Latin-1 (1)
+fn decode_string(value: &[u8]) -> Option<String> {
+    let (marker, rest) = value.split_first()?;
+    match marker {
+        0 => {
+            let units: Vec<u16> = rest
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16(&units).ok()
+        }
+        1 => Some(rest.iter().map(|&b| b as char).collect()),
+        _ => None,
+    }
+}
+
+/// The conversations anywhere in `value`, wherever the app nested them
+fn find_conversations(value: Value, found: &mut Vec<Conversation>) {
+    match value {
+        Value::Object(object) => {
+            let is_conversation = object.get("uuid").is_some_and(Value::is_string)
+                && object.get("chat_messages").is_some_and(Value::is_array);
+            if is_conversation {
+                if let Ok(conversation) = serde_json::from_value(Value::Object(object)) {
+                    found.push(conversation);
+                }
+                return;
+            }
+            for value in object.into_iter().map(|(_, v)| v) {
+                find_conversations(value, found);
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                find_conversations(value, found);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A message's text blocks; tool use and attachments are dropped
+fn extract_text(message: &ChatMessage) -> String {
+    let blocks: Vec<&str> = message
+        .content
+        .iter()
+        .filter(|block| block.get("type").and_then(Value::as_str) == Some("text"))
+        .filter_map(|block| block.get("text").and_then(Value::as_str))
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .collect();
+    if blocks.is_empty() {
+        message.text.trim().to_string()
+    } else {
+        blocks.join("\n")
+    }
+}
+
+fn parse_time(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text).ok().map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_decode_string() {
+        assert_eq!(decode_string(b"\x01caf\xe9").as_deref(), Some("café"));
+        assert_eq!(decode_string(b"\x00h\x00i\x00").as_deref(), Some("hi"));
+        assert_eq!(decode_string(b"\x02hi"), None);
+    }
+
+    #[test]
+    fn test_find_conversations() {
+        let cache = json!({"state": {"conversations": [
+            {"uuid": "c1", "name": "Regex help", "chat_messages": [
+                {"sender": "human", "text": "match digits"},
+                {"sender": "assistant", "text": "", "content": [
+                    {"type": "text", "text": "Use \\d+."}, {"type": "tool_use", "name": "x"}]}
+            ]},
+            {"uuid": "c2", "name": "Listed only"}
+        ]}});
+        let mut found = Vec::new();
+        find_conversations(cache, &mut found);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].uuid, "c1");
+        let texts: Vec<_> = found[0].chat_messages.iter().map(extract_text).collect();
+        assert_eq!(texts, ["match digits", "Use \\d+."]);
+    }
+
+    #[test]
+    fn test_can_parse() {
+        let store = Path::new("/h/.config/Claude/Local Storage/leveldb");
+        assert!(ClaudeDesktopParser::can_parse(&store.join("000003.log/c1")));
+        assert!(ClaudeDesktopParser::can_parse(store));
+        let other = Path::new("/h/.config/Slack/Local Storage/leveldb");
+        assert!(!ClaudeDesktopParser::can_parse(&other.join("000003.log/c1")));
+    }
+}
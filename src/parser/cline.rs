@@ -0,0 +1,266 @@
+//! Cline, the VS Code extension, keeps each task in VS Code's global storage:
+//! `tasks/<id>/api_conversation_history.json` is the conversation as sent to the model
+//! (Anthropic-style messages, without times), and `ui_messages.json` beside it is what the
+//! extension showed, each with a `ts` in milliseconds. Roo Code, a fork, keeps its tasks the
+//! same way under its own extension id, so both are read by [`parse_task`].
+
+use crate::config;
+use crate::session::{Message, Role, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::DateTime;
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use super::{join_consecutive_messages, timeline::Timeline, SessionParser};
+
+/// The task folders, under the home
+#[cfg(target_os = "macos")]
+pub const TASKS_DIR: &str =
+    "Library/Application Support/Code/User/globalStorage/saoudrizwan.claude-dev/tasks";
+#[cfg(windows)]
+pub const TASKS_DIR: &str =
+    "AppData/Roaming/Code/User/globalStorage/saoudrizwan.claude-dev/tasks";
+#[cfg(not(any(target_os = "macos", windows)))]
+pub const TASKS_DIR: &str = ".config/Code/User/globalStorage/saoudrizwan.claude-dev/tasks";
+
+/// The conversation in each task folder
+pub const HISTORY_FILE: &str = "api_conversation_history.json";
+
+const UI_MESSAGES_FILE: &str = "ui_messages.json";
+
+/// Tags Cline wraps what the user typed in
+const USER_TAGS: [&str; 4] = ["task", "feedback", "answer", "user_message"];
+
+#[derive(Debug, Deserialize)]
+struct ApiMessage {
+    role: String,
+    #[serde(default)]
+    content: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct UiMessage {
+    ts: i64,
+    #[serde(default)]
+    say: Option<String>,
+    #[serde(default)]
+    text: Option<String>,
+}
+
+pub struct ClineParser;
+
+impl SessionParser for ClineParser {
+    fn can_parse(path: &Path) -> bool {
+        path.to_str().is_some_and(|s| s.contains("saoudrizwan.claude-dev"))
+            && path.file_name().is_some_and(|name| name == HISTORY_FILE)
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        parse_task(path, SessionSource::Cline)
+    }
+}
+
+/// A task's conversation, as a session from `source`: Cline, or a fork that keeps its tasks
+/// the same way
+pub(super) fn parse_task(path: &Path, source: SessionSource) -> Result<Session> {
+    let file = File::open(path).context("Failed to open file")?;
+    let history: Vec<ApiMessage> = serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("Failed to parse {} conversation", source.display_name()))?;
+    let task = path.parent().unwrap_or(Path::new("."));
+    let ui = read_ui_messages(&task.join(UI_MESSAGES_FILE));
+
+    // What the user typed and the model said, as shown, to date messages by
+    let mut shown: Vec<(i64, String)> = ui
+        .iter()
+        .filter(|m| matches!(m.say.as_deref(), Some("task" | "text" | "user_feedback")))
+        .filter_map(|m| Some((m.ts, m.text.as_deref()?.trim().to_string())))
+        .collect();
+    shown.reverse();
+
+    let mut timeline = Timeline::default();
+    timeline.observe(ui.first().and_then(|m| DateTime::from_timestamp_millis(m.ts)));
+    let mut messages: Vec<Message> = Vec::new();
+    let mut cwd: Option<String> = None;
+
+    for entry in &history {
+        let role = match entry.role.as_str() {
+            "user" => Role::User,
+            "assistant" => Role::Assistant,
+            _ => continue,
+        };
+        let mut texts = Vec::new();
+        let mut tool_output = false;
+        for text in text_blocks(&entry.content) {
+            if cwd.is_none() {
+                cwd = working_dir(text);
+            }
+            // A tool result is a `[tool_name ...] Result:` block, then the output
+            if std::mem::take(&mut tool_output) {
+                continue;
+            }
+            let text = match role {
+                Role::User if is_tool_result(text) => {
+                    tool_output = true;
+                    continue;
+                }
+                Role::User => user_text(text),
+                Role::Assistant | Role::Context => text.trim().to_string(),
+            };
+            if !text.is_empty() {
+                texts.push(text);
+            }
+        }
+        let content = texts.join("\n");
+        if content.is_empty() {
+            continue;
+        }
+
+        // Shown entries are matched in order, skipping ones that never reached the model
+        if let Some(i) = shown.iter().rposition(|(_, text)| *text == content) {
+            timeline.observe(DateTime::from_timestamp_millis(shown[i].0));
+            shown.truncate(i);
+        }
+        let timestamp = timeline.stamp(&messages);
+        messages.push(Message { role, content, timestamp, tool_calls: Vec::new() });
+    }
+    timeline.observe(ui.last().and_then(|m| DateTime::from_timestamp_millis(m.ts)));
+
+    let session_id = task
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let timestamp = timeline.finish(&mut messages, path);
+
+    Ok(Session {
+        id: session_id,
+        source,
+        file_path: path.to_path_buf(),
+        cwd: cwd.unwrap_or_else(|| ".".to_string()),
+        git_branch: None,
+        timestamp,
+        messages: join_consecutive_messages(messages, config::get().join_messages),
+        files_touched: Vec::new(),
+        usage: Vec::new(),
+        title: None,
+    })
+}
+
+/// The task's UI messages, or none if they're missing or unreadable
+fn read_ui_messages(path: &Path) -> Vec<UiMessage> {
+    File::open(path)
+        .ok()
+        .and_then(|file| serde_json::from_reader(BufReader::new(file)).ok())
+        .unwrap_or_default()
+}
+
+/// The text blocks of a message's content, which is a string or an array of blocks
+fn text_blocks(content: &Value) -> Vec<&str> {
+    match content {
+        Value::String(text) => vec![text.as_str()],
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter(|b| b.get("type").and_then(Value::as_str) == Some("text"))
+            .filter_map(|b| b.get("text").and_then(Value::as_str))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Whether a text block Cline sent for the user heads a tool's result
+fn is_tool_result(text: &str) -> bool {
+    let first_line = text.trim_start().lines().next().unwrap_or_default();
+    first_line.starts_with('[') && first_line.contains("] Result:")
+}
+
+/// What the user typed, out of a text block Cline sent for them: their words are wrapped in a
+/// tag like `<task>`, and the editor's state is appended in `<environment_details>`
+fn user_text(text: &str) -> String {
+    let text = match text.find("<environment_details>") {
+        Some(start) => &text[..start],
+        None => text,
+    };
+    let text = text.trim();
+    for tag in USER_TAGS {
+        let close = format!("</{}>", tag);
+        if let Some(inner) = text.strip_prefix(&format!("<{}>", tag)) {
+            return inner.split(&close).next().unwrap_or_default().trim().to_string();
+        }
+    }
+    text.to_string()
+}
+
+/// The workspace folder, from the environment details Cline sends with the task
+fn working_dir(text: &str) -> Option<String> {
+    let line = text
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("# Current Working Directory ("))?;
+    let dir = line.trim_end().trim_end_matches("Files").trim_end().strip_suffix(')')?;
+    Some(dir.to_string()).filter(|d| !d.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_task() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let task = temp.path().join("saoudrizwan.claude-dev/tasks/1748772000000");
+        std::fs::create_dir_all(&task).unwrap();
+        let env = "<environment_details>\n# VSCode Visible Files\nsrc/app.ts\n\n\
+                   # Current Working Directory (/w/my app) Files\nsrc/\n</environment_details>";
+        let history = json!([
+            {"role": "user", "content": [
+                {"type": "text", "text": "<task>\nrename the app\n</task>"},
+                {"type": "text", "text": env}
+            ]},
+            {"role": "assistant", "content": [{"type": "text", "text": "Renaming it."}]},
+            {"role": "user", "content": [
+                {"type": "text", "text": "[replace_in_file for 'src/app.ts'] Result:"},
+                {"type": "text", "text": "Done"}
+            ]},
+            {"role": "user", "content": [
+                {"type": "text", "text": "<feedback>\nkeep the old name in the README\n</feedback>"}
+            ]},
+            {"role": "assistant", "content": "Kept it."}
+        ]);
+        let ui = json!([
+            {"ts": 1748772000000i64, "type": "say", "say": "task", "text": "rename the app"},
+            {"ts": 1748772005000i64, "type": "say", "say": "text", "text": "Renaming it."},
+            {"ts": 1748772060000i64, "type": "say", "say": "user_feedback",
+             "text": "keep the old name in the README"},
+            {"ts": 1748772065000i64, "type": "say", "say": "text", "text": "Kept it."},
+            {"ts": 1748772070000i64, "type": "say", "say": "completion_result", "text": ""}
+        ]);
+        let path = task.join(HISTORY_FILE);
+        std::fs::write(&path, history.to_string()).unwrap();
+        std::fs::write(task.join(UI_MESSAGES_FILE), ui.to_string()).unwrap();
+        assert!(ClineParser::can_parse(&path));
+
+        let session = ClineParser::parse_file(&path).unwrap();
+        assert_eq!(session.id, "1748772000000");
+        assert_eq!(session.source, SessionSource::Cline);
+        assert_eq!(session.cwd, "/w/my app");
+        let contents: Vec<_> =
+            session.messages.iter().map(|m| (m.role, m.content.as_str())).collect();
+        assert_eq!(
+            contents,
+            [
+                (Role::User, "rename the app"),
+                (Role::Assistant, "Renaming it."),
+                (Role::User, "keep the old name in the README"),
+                (Role::Assistant, "Kept it."),
+            ]
+        );
+        let time = |ms| DateTime::from_timestamp_millis(ms).unwrap();
+        assert_eq!(session.messages[1].timestamp, time(1748772005000));
+        assert_eq!(session.messages[3].timestamp, time(1748772065000));
+        assert_eq!(session.timestamp, time(1748772070000));
+    }
+}
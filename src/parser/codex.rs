@@ -1,12 +1,14 @@
-use crate::session::{Message, Role, Session, SessionSource};
+use crate::config;
+use crate::session::{Message, ModelUsage, Role, Session, SessionSource, ToolCall, ToolStatus};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
+use serde_json::Value;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
-use super::{join_consecutive_messages, SessionParser};
+use super::{join_consecutive_messages, timeline::Timeline, tools::OpenCalls, SessionParser};
 
 #[derive(Debug, Deserialize)]
 struct CodexLine {
@@ -34,6 +36,17 @@ struct ResponseItem {
     content: Option<Vec<ContentBlock>>,
 }
 
+/// Cumulative token counts from a `token_count` event
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+struct TokenUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    cached_input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+}
+
 #[derive(Debug, Deserialize)]
 struct ContentBlock {
     #[serde(rename = "type")]
@@ -58,8 +71,14 @@ impl SessionParser for CodexParser {
         let mut session_id: Option<String> = None;
         let mut cwd: Option<String> = None;
         let mut git_branch: Option<String> = None;
-        let mut latest_timestamp: Option<DateTime<Utc>> = None;
+        let mut timeline = Timeline::default();
         let mut messages: Vec<Message> = Vec::new();
+        let mut model = String::from("unknown");
+        let mut usage_total = TokenUsage::default();
+        let mut usage: Vec<ModelUsage> = Vec::new();
+        let mut calls = OpenCalls::default();
+        // Whether the latest message is an assistant turn that began with reasoning
+        let mut reasoning_turn = false;
 
         for line in reader.lines() {
             let line = line.context("Failed to read line")?;
@@ -72,13 +91,13 @@ impl SessionParser for CodexParser {
                 Err(_) => continue,
             };
 
-            // Parse timestamp from entry
-            let timestamp = entry
-                .timestamp
-                .as_ref()
-                .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
-                .map(|dt| dt.with_timezone(&Utc))
-                .unwrap_or_else(Utc::now);
+            timeline.observe(
+                entry
+                    .timestamp
+                    .as_ref()
+                    .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+                    .map(|dt| dt.with_timezone(&Utc)),
+            );
 
             match entry.entry_type.as_str() {
                 "session_meta" => {
@@ -99,6 +118,19 @@ impl SessionParser for CodexParser {
                 }
                 "response_item" => {
                     if let Some(payload) = &entry.payload {
+                        if record_tool_item(payload, &mut messages, &mut calls) {
+                            continue;
+                        }
+                        if payload.get("type").and_then(|t| t.as_str()) == Some("reasoning") {
+                            let summary = reasoning_text(payload);
+                            if config::get().include_thinking && !summary.is_empty() {
+                                let content = format!("[thinking] {}", summary);
+                                let (role, turn) = (Role::Assistant, reasoning_turn);
+                                push_message(&mut messages, &mut timeline, role, content, turn);
+                                reasoning_turn = true;
+                            }
+                            continue;
+                        }
                         if let Ok(item) = serde_json::from_value::<ResponseItem>(payload.clone()) {
                             let role = match item.role.as_deref() {
                                 Some("user") => Role::User,
@@ -123,23 +155,49 @@ impl SessionParser for CodexParser {
                             };
 
                             let content = extract_codex_content(&item);
+                            reasoning_turn &= role == Role::Assistant;
                             if !content.is_empty() {
-                                messages.push(Message {
-                                    role,
-                                    content,
-                                    timestamp,
-                                });
-
-                                // Update latest timestamp
-                                if latest_timestamp.is_none()
-                                    || timestamp > latest_timestamp.unwrap()
-                                {
-                                    latest_timestamp = Some(timestamp);
-                                }
+                                let turn = reasoning_turn;
+                                push_message(&mut messages, &mut timeline, role, content, turn);
                             }
                         }
                     }
                 }
+                "turn_context" => {
+                    let turn_model = entry
+                        .payload
+                        .as_ref()
+                        .and_then(|p| p.get("model"))
+                        .and_then(|m| m.as_str());
+                    if let Some(m) = turn_model {
+                        model = m.to_string();
+                    }
+                }
+                "event_msg" => {
+                    // Token counts are running totals: charge the increase to the current model
+                    let total = entry
+                        .payload
+                        .as_ref()
+                        .filter(|p| p.get("type").and_then(|t| t.as_str()) == Some("token_count"))
+                        .and_then(|p| p.pointer("/info/total_token_usage"))
+                        .and_then(|u| serde_json::from_value::<TokenUsage>(u.clone()).ok());
+                    if let Some(total) = total {
+                        let delta = |now: u64, before: u64| now.saturating_sub(before);
+                        let input = delta(total.input_tokens, usage_total.input_tokens);
+                        let cached = delta(total.cached_input_tokens, usage_total.cached_input_tokens);
+                        ModelUsage::accumulate(
+                            &mut usage,
+                            ModelUsage {
+                                model: model.clone(),
+                                input_tokens: input.saturating_sub(cached),
+                                output_tokens: delta(total.output_tokens, usage_total.output_tokens),
+                                cache_read_tokens: cached,
+                                cache_write_tokens: 0,
+                            },
+                        );
+                        usage_total = total;
+                    }
+                }
                 _ => {}
             }
         }
@@ -152,18 +210,169 @@ impl SessionParser for CodexParser {
                 .to_string()
         });
 
+        let timestamp = timeline.finish(&mut messages, path);
+
         Ok(Session {
             id: session_id,
             source: SessionSource::CodexCli,
             file_path: path.to_path_buf(),
             cwd: cwd.unwrap_or_else(|| ".".to_string()),
             git_branch,
-            timestamp: latest_timestamp.unwrap_or_else(Utc::now),
-            messages: join_consecutive_messages(messages),
+            timestamp,
+            messages: join_consecutive_messages(messages, config::get().join_messages),
+            files_touched: Vec::new(),
+            usage,
+            title: None,
         })
     }
 }
 
+/// Add a message to the session, or to the latest message when that's the reasoning that began
+/// the assistant's turn (`reasoning_turn`), whatever `join_messages` is, so that a turn doesn't
+/// fragment into a bubble for each step. A retried turn repeats the user's message, which is
+/// kept once.
+fn push_message(
+    messages: &mut Vec<Message>,
+    timeline: &mut Timeline,
+    role: Role,
+    content: String,
+    reasoning_turn: bool,
+) {
+    if let Some(last) = messages.last_mut().filter(|_| reasoning_turn) {
+        last.content = format!("{}\n{}", last.content, content);
+        return;
+    }
+    let retried = |last: &Message| last.role == Role::User && last.content == content;
+    if role == Role::User && messages.last().is_some_and(retried) {
+        return;
+    }
+    let timestamp = timeline.stamp(messages);
+    messages.push(Message { role, content, timestamp, tool_calls: Vec::new() });
+}
+
+/// The summary of a reasoning item, or the reasoning itself when it isn't summarized
+fn reasoning_text(payload: &Value) -> String {
+    let parts = |key: &str, kind: &str| -> Vec<String> {
+        payload
+            .get(key)
+            .and_then(|parts| parts.as_array())
+            .into_iter()
+            .flatten()
+            .filter(|part| part.get("type").and_then(|t| t.as_str()) == Some(kind))
+            .filter_map(|part| part.get("text")?.as_str())
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+            .collect()
+    };
+    let summary = parts("summary", "summary_text");
+    let parts = match summary.is_empty() {
+        true => parts("content", "reasoning_text"),
+        false => summary,
+    };
+    parts.join("\n")
+}
+
+/// Record a tool call on the latest message, the one it followed, or a call's output on the
+/// call. Returns whether `payload` was either.
+fn record_tool_item(payload: &Value, messages: &mut [Message], calls: &mut OpenCalls) -> bool {
+    match payload.get("type").and_then(|t| t.as_str()) {
+        Some("function_call" | "custom_tool_call" | "local_shell_call") => {
+            if let Some((call_id, call)) = tool_call(payload) {
+                calls.push(&call_id, call, messages);
+            }
+            true
+        }
+        Some("function_call_output" | "custom_tool_call_output") => {
+            let call_id = payload.get("call_id").and_then(|id| id.as_str()).unwrap_or_default();
+            let call = calls.get(call_id, messages);
+            if let (Some(call), Some(output)) = (call, payload.get("output")) {
+                apply_output(call, output);
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+/// A call and its call ID. Shell commands are the command, other tools their arguments.
+fn tool_call(payload: &Value) -> Option<(String, ToolCall)> {
+    let call_id = payload.get("call_id").or_else(|| payload.get("id"))?.as_str()?;
+    let (name, input) = match payload.get("type")?.as_str()? {
+        "local_shell_call" => ("shell", shell_command(payload.pointer("/action/command")?)),
+        _ => {
+            let input = match (payload.get("arguments"), payload.get("input")) {
+                (Some(Value::String(arguments)), _) => arguments_input(arguments),
+                (_, Some(Value::String(input))) => input.clone(),
+                _ => String::new(),
+            };
+            (payload.get("name")?.as_str()?, input)
+        }
+    };
+    Some((call_id.to_string(), ToolCall::pending(name, input)))
+}
+
+/// The command of a shell call's JSON arguments, or else the arguments as they are
+fn arguments_input(arguments: &str) -> String {
+    let arguments_json = serde_json::from_str::<Value>(arguments).ok();
+    match arguments_json.as_ref().and_then(|json| json.get("command")) {
+        Some(command) => shell_command(command),
+        None => arguments.to_string(),
+    }
+}
+
+/// A command as it would be typed: the script of `bash -lc <script>`, else its words
+fn shell_command(command: &Value) -> String {
+    match command {
+        Value::String(command) => command.clone(),
+        Value::Array(words) => {
+            let words: Vec<&str> = words.iter().filter_map(|w| w.as_str()).collect();
+            match words.as_slice() {
+                [shell, "-lc" | "-c", script] if shell.ends_with("sh") => script.to_string(),
+                _ => words.join(" "),
+            }
+        }
+        _ => String::new(),
+    }
+}
+
+/// Fill in what a call returned. Codex has written it as `{output, metadata: {exit_code}}`
+/// encoded as a string, as text headed `Exit code: N`, and as `{content, success}`.
+fn apply_output(call: &mut ToolCall, output: &Value) {
+    let (text, exit_code, success) = match output {
+        Value::String(text) => match serde_json::from_str::<Value>(text) {
+            Ok(json) if json.get("output").is_some() => (
+                json["output"].as_str().unwrap_or_default().to_string(),
+                json.pointer("/metadata/exit_code").and_then(|code| code.as_i64()),
+                None,
+            ),
+            _ => {
+                let (exit_code, text) = split_exit_code(text);
+                (text.to_string(), exit_code, None)
+            }
+        },
+        Value::Object(_) => (
+            output.get("content").and_then(|c| c.as_str()).unwrap_or_default().to_string(),
+            None,
+            output.get("success").and_then(|s| s.as_bool()),
+        ),
+        _ => return,
+    };
+    let failed = exit_code.is_some_and(|code| code != 0) || success == Some(false);
+    call.status = if failed { ToolStatus::Error } else { ToolStatus::Ok };
+    call.exit_code = exit_code;
+    call.output = Some(text);
+}
+
+/// The exit code of output headed `Exit code: N`, and the output after the header
+fn split_exit_code(text: &str) -> (Option<i64>, &str) {
+    let Some(code) = text.strip_prefix("Exit code: ") else {
+        return (None, text);
+    };
+    let code = code.lines().next().and_then(|line| line.trim().parse().ok());
+    let output = text.split_once("Output:\n").map_or(text, |(_, output)| output);
+    (code, output)
+}
+
 /// Extract text content from a Codex response item.
 /// Filters out CLI-injected blocks (AGENTS.md instructions, environment_context).
 fn extract_codex_content(item: &ResponseItem) -> String {
@@ -256,4 +465,61 @@ mod tests {
             "<environment_context> what is this?"
         );
     }
+
+    #[test]
+    fn test_reasoning_text() {
+        let summarized = serde_json::json!({"type": "reasoning", "summary": [
+            {"type": "summary_text", "text": "**Checking the tests**"},
+            {"type": "summary_text", "text": " They pass. "}
+        ], "content": [{"type": "reasoning_text", "text": "hidden"}]});
+        assert_eq!(reasoning_text(&summarized), "**Checking the tests**\nThey pass.");
+        let unsummarized = serde_json::json!({"type": "reasoning", "summary": [],
+            "content": [{"type": "reasoning_text", "text": "Run them first."}]});
+        assert_eq!(reasoning_text(&unsummarized), "Run them first.");
+        let encrypted = serde_json::json!({"type": "reasoning", "encrypted_content": "gAAAA"});
+        assert_eq!(reasoning_text(&encrypted), "");
+    }
+
+    #[test]
+    fn test_tool_call_input() {
+        let call = |payload: Value| tool_call(&payload).unwrap().1.input;
+        let arguments = r#"{"command": ["bash", "-lc", "cargo test"], "workdir": "/w"}"#;
+        assert_eq!(
+            call(serde_json::json!({"type": "function_call", "name": "shell",
+                                    "arguments": arguments, "call_id": "c1"})),
+            "cargo test"
+        );
+        assert_eq!(
+            call(serde_json::json!({"type": "local_shell_call", "call_id": "c2",
+                                    "action": {"command": ["git", "status"]}})),
+            "git status"
+        );
+        let plan = r#"{"plan": []}"#;
+        assert_eq!(
+            call(serde_json::json!({"type": "function_call", "name": "update_plan",
+                                    "arguments": plan, "call_id": "c3"})),
+            plan
+        );
+    }
+
+    #[test]
+    fn test_apply_output() {
+        let output = |value: Value| {
+            let mut call = tool_call(&serde_json::json!({
+                "type": "function_call", "name": "shell", "arguments": "{}", "call_id": "c"
+            }))
+            .unwrap()
+            .1;
+            assert_eq!(call.status, ToolStatus::Pending);
+            apply_output(&mut call, &value);
+            (call.status, call.exit_code, call.output.unwrap_or_default())
+        };
+        let encoded = r#"{"output": "ok\n", "metadata": {"exit_code": 0}}"#;
+        assert_eq!(output(encoded.into()), (ToolStatus::Ok, Some(0), "ok\n".to_string()));
+        let text = "Exit code: 2\nWall time: 0.1 seconds\nOutput:\nno such file";
+        assert_eq!(output(text.into()), (ToolStatus::Error, Some(2), "no such file".to_string()));
+        let object = serde_json::json!({"content": "patch rejected", "success": false});
+        assert_eq!(output(object), (ToolStatus::Error, None, "patch rejected".to_string()));
+        assert_eq!(output("Done".into()), (ToolStatus::Ok, None, "Done".to_string()));
+    }
 }
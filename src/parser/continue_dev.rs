@@ -0,0 +1,192 @@
+//! Continue, the IDE extension, saves each session as `~/.continue/sessions/<id>.json`: its
+//! `history` is a list of items that each wrap a `message` with a `role` and `content`. The
+//! messages aren't dated. `sessions.json` beside them indexes the sessions with their
+//! titles and the workspace each was started in.
+
+use crate::config;
+use crate::session::{Message, Role, Session, SessionSource};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use super::{join_consecutive_messages, timeline::Timeline, SessionParser};
+
+/// The index of the sessions, in the sessions folder
+pub const INDEX_FILE: &str = "sessions.json";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ContinueSession {
+    session_id: Option<String>,
+    workspace_directory: Option<String>,
+    history: Vec<HistoryItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HistoryItem {
+    message: Option<ChatMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatMessage {
+    role: String,
+    #[serde(default)]
+    content: Value,
+}
+
+/// A session's entry in `sessions.json`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct IndexEntry {
+    session_id: String,
+    #[allow(dead_code)]
+    title: Option<String>,
+    workspace_directory: Option<String>,
+}
+
+pub struct ContinueParser;
+
+impl SessionParser for ContinueParser {
+    fn can_parse(path: &Path) -> bool {
+        let in_continue = path.to_str().is_some_and(|s| {
+            s.contains(".continue/sessions") || s.contains(".continue\\sessions")
+        });
+        in_continue && path.file_name().is_some_and(|name| name != INDEX_FILE)
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        let file = File::open(path).context("Failed to open file")?;
+        let session: ContinueSession = serde_json::from_reader(BufReader::new(file))
+            .context("Failed to parse Continue session")?;
+
+        // Nothing is dated, so the messages all take the file's mtime
+        let mut timeline = Timeline::default();
+        let mut messages: Vec<Message> = Vec::new();
+        for message in session.history.iter().filter_map(|item| item.message.as_ref()) {
+            // System prompts, thinking, and tool output aren't part of the conversation
+            let role = match message.role.as_str() {
+                "user" => Role::User,
+                "assistant" => Role::Assistant,
+                _ => continue,
+            };
+            let content = extract_content(&message.content);
+            if !content.is_empty() {
+                let timestamp = timeline.stamp(&messages);
+                messages.push(Message { role, content, timestamp, tool_calls: Vec::new() });
+            }
+        }
+
+        let session_id = session.session_id.unwrap_or_else(|| {
+            path.file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("unknown")
+                .to_string()
+        });
+
+        let entry = path.parent().and_then(|dir| index_entry(dir, &session_id));
+        let cwd = entry
+            .and_then(|entry| entry.workspace_directory)
+            .or(session.workspace_directory)
+            .filter(|dir| !dir.is_empty())
+            .map(|dir| dir.strip_prefix("file://").map(String::from).unwrap_or(dir));
+
+        let timestamp = timeline.finish(&mut messages, path);
+
+        Ok(Session {
+            id: session_id,
+            source: SessionSource::Continue,
+            file_path: path.to_path_buf(),
+            cwd: cwd.unwrap_or_else(|| ".".to_string()),
+            git_branch: None,
+            timestamp,
+            messages: join_consecutive_messages(messages, config::get().join_messages),
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: None,
+        })
+    }
+}
+
+/// The session's entry in the index in `dir`, if the index is there and lists it
+fn index_entry(dir: &Path, session_id: &str) -> Option<IndexEntry> {
+    let file = File::open(dir.join(INDEX_FILE)).ok()?;
+    let entries: Vec<IndexEntry> = serde_json::from_reader(BufReader::new(file)).ok()?;
+    entries.into_iter().find(|entry| entry.session_id == session_id)
+}
+
+/// Extract text content from a Continue message's content field.
+/// Content is a string or an array of parts, of which only `text` parts are kept (images
+/// are dropped).
+fn extract_content(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.trim().to_string(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter(|part| part.get("type").and_then(Value::as_str) == Some("text"))
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_session() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let dir = temp.path().join(".continue/sessions");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("3b9e1f.json");
+        let session = json!({
+            "sessionId": "3b9e1f",
+            "title": "New Session",
+            "workspaceDirectory": "",
+            "history": [
+                {"message": {"role": "user", "content": [
+                    {"type": "text", "text": "why does the build fail?"},
+                    {"type": "imageUrl", "imageUrl": {"url": "data:image/png;base64,AAAA"}}
+                ]}, "contextItems": []},
+                {"message": {"role": "thinking", "content": "Checking the log."}},
+                {"message": {"role": "assistant", "content": "A missing semicolon."}},
+                {"message": {"role": "tool", "content": "ok", "toolCallId": "t1"}},
+                {"message": {"role": "user", "content": "fix it"}}
+            ]
+        });
+        let index = json!([
+            {"sessionId": "0a0a", "title": "Other", "workspaceDirectory": "/w/other"},
+            {"sessionId": "3b9e1f", "title": "Build failure", "dateCreated": "1748772000000",
+             "workspaceDirectory": "file:///w/site"}
+        ]);
+        std::fs::write(&path, session.to_string()).unwrap();
+        std::fs::write(dir.join(INDEX_FILE), index.to_string()).unwrap();
+        assert!(ContinueParser::can_parse(&path));
+        assert!(!ContinueParser::can_parse(&dir.join(INDEX_FILE)));
+
+        let session = ContinueParser::parse_file(&path).unwrap();
+        assert_eq!(session.id, "3b9e1f");
+        assert_eq!(session.source, SessionSource::Continue);
+        assert_eq!(session.cwd, "/w/site");
+        let contents: Vec<_> =
+            session.messages.iter().map(|m| (m.role, m.content.as_str())).collect();
+        assert_eq!(
+            contents,
+            [
+                (Role::User, "why does the build fail?"),
+                (Role::Assistant, "A missing semicolon."),
+                (Role::User, "fix it"),
+            ]
+        );
+        let mtime = std::fs::metadata(&path).unwrap().modified().unwrap();
+        assert_eq!(session.timestamp, chrono::DateTime::<chrono::Utc>::from(mtime));
+        assert_eq!(session.messages[0].timestamp, session.timestamp);
+    }
+}
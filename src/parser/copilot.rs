@@ -0,0 +1,189 @@
+//! GitHub Copilot CLI sessions: one JSON file per session under
+//! `~/.copilot/history-session-state/`, holding its `turns`, each a `request` and the
+//! `response` to it (older files have `chatMessages` with a `role` each instead).
+
+use crate::config;
+use crate::session::{Message, Role, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use super::{join_consecutive_messages, timeline::Timeline, SessionParser};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct CopilotSession {
+    session_id: Option<String>,
+    start_time: Option<String>,
+    #[serde(alias = "workingDirectory")]
+    cwd: Option<String>,
+    turns: Vec<Turn>,
+    chat_messages: Vec<ChatMessage>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Turn {
+    request: Value,
+    response: Value,
+    timestamp: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatMessage {
+    role: String,
+    #[serde(default)]
+    content: Value,
+}
+
+pub struct CopilotParser;
+
+impl SessionParser for CopilotParser {
+    fn can_parse(path: &Path) -> bool {
+        path.to_str()
+            .map(|s| {
+                s.contains(".copilot/history-session-state")
+                    || s.contains(".copilot\\history-session-state")
+            })
+            .unwrap_or(false)
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        let file = File::open(path).context("Failed to open file")?;
+        let state: CopilotSession = serde_json::from_reader(BufReader::new(file))
+            .context("Failed to parse Copilot session")?;
+
+        let mut timeline = Timeline::default();
+        timeline.observe(state.start_time.as_deref().and_then(parse_time));
+        let mut messages: Vec<Message> = Vec::new();
+        let mut push = |timeline: &mut Timeline, role, content: String| {
+            if !content.trim().is_empty() {
+                let timestamp = timeline.stamp(&messages);
+                messages.push(Message { role, content, timestamp, tool_calls: Vec::new() });
+            }
+        };
+
+        for turn in &state.turns {
+            timeline.observe(turn.timestamp.as_deref().and_then(parse_time));
+            push(&mut timeline, Role::User, text(&turn.request));
+            push(&mut timeline, Role::Assistant, text(&turn.response));
+        }
+        for message in &state.chat_messages {
+            // System prompts and tool results aren't part of the conversation
+            let role = match message.role.as_str() {
+                "user" => Role::User,
+                "assistant" => Role::Assistant,
+                _ => continue,
+            };
+            push(&mut timeline, role, text(&message.content));
+        }
+
+        let session_id = state.session_id.unwrap_or_else(|| {
+            path.file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("unknown")
+                .to_string()
+        });
+
+        let timestamp = timeline.finish(&mut messages, path);
+
+        Ok(Session {
+            id: session_id,
+            source: SessionSource::Copilot,
+            file_path: path.to_path_buf(),
+            cwd: state.cwd.unwrap_or_else(|| ".".to_string()),
+            git_branch: None,
+            timestamp,
+            messages: join_consecutive_messages(messages, config::get().join_messages),
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: None,
+        })
+    }
+}
+
+/// The text of a request, response, or message: a string, an object with its `text` or
+/// `content`, or an array of those
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => {
+            parts.iter().map(text).filter(|t| !t.is_empty()).collect::<Vec<_>>().join("\n")
+        }
+        Value::Object(object) => {
+            object.get("text").or_else(|| object.get("content")).map(text).unwrap_or_default()
+        }
+        _ => String::new(),
+    }
+}
+
+fn parse_time(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text).ok().map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(value: Value) -> Session {
+        let temp = tempfile::TempDir::new().unwrap();
+        let dir = temp.path().join(".copilot/history-session-state");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("session_1.json");
+        std::fs::write(&path, value.to_string()).unwrap();
+        assert!(CopilotParser::can_parse(&path));
+        CopilotParser::parse_file(&path).unwrap()
+    }
+
+    #[test]
+    fn test_parse_turns() {
+        let session = parse(json!({
+            "sessionId": "5e0c",
+            "startTime": "2025-06-01T09:00:00Z",
+            "cwd": "/w/shop",
+            "turns": [
+                {"request": "list open PRs", "response": {"text": "There are two."},
+                 "timestamp": "2025-06-01T09:01:00Z"},
+                {"request": {"content": "merge the first"},
+                 "response": [{"text": "Merged #12."}], "timestamp": "2025-06-01T09:03:00Z"}
+            ]
+        }));
+        assert_eq!(session.id, "5e0c");
+        assert_eq!(session.source, SessionSource::Copilot);
+        assert_eq!(session.cwd, "/w/shop");
+        let contents: Vec<_> =
+            session.messages.iter().map(|m| (m.role, m.content.as_str())).collect();
+        assert_eq!(
+            contents,
+            [
+                (Role::User, "list open PRs"),
+                (Role::Assistant, "There are two."),
+                (Role::User, "merge the first"),
+                (Role::Assistant, "Merged #12."),
+            ]
+        );
+        assert_eq!(session.messages[2].timestamp, parse_time("2025-06-01T09:03:00Z").unwrap());
+    }
+
+    #[test]
+    fn test_parse_chat_messages() {
+        let session = parse(json!({
+            "startTime": "2025-06-01T09:00:00Z",
+            "chatMessages": [
+                {"role": "system", "content": "You are Copilot."},
+                {"role": "user", "content": "why is CI slow?"},
+                {"role": "tool", "content": "..."},
+                {"role": "assistant", "content": "The cache key changes every run."}
+            ]
+        }));
+        assert_eq!(session.id, "session_1");
+        assert_eq!(session.messages.len(), 2);
+        assert_eq!(session.messages[1].content, "The cache key changes every run.");
+        assert_eq!(session.timestamp, parse_time("2025-06-01T09:00:00Z").unwrap());
+    }
+}
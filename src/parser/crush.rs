@@ -0,0 +1,174 @@
+//! Crush keeps each project's sessions in a SQLite database in the project,
+//! `.crush/crush.db`: a `sessions` table, and a `messages` table whose `parts` column holds
+//! each message's content as JSON (`{type, data}` parts). Each session is addressed as
+//! `crush.db/<session id>` (see [`super::entry_path`]); the sessions Crush starts for its own
+//! sub-tasks (with a `parent_session_id`) are left out.
+
+use crate::config;
+use crate::session::{Message, Role, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use super::sqlite::{self, Table};
+use super::{
+    join_consecutive_messages, timeline::Timeline, DiscoveryPass, DiscoveryRoot, SessionParser,
+};
+
+/// The folder in a project's root that holds the database
+pub const DATA_DIR: &str = ".crush";
+
+/// The database in the data folder
+pub const DATABASE_FILE: &str = "crush.db";
+
+pub struct CrushParser;
+
+impl SessionParser for CrushParser {
+    fn can_parse(path: &Path) -> bool {
+        is_session(path)
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        let (database, session_id) =
+            super::split_entry(path).context("Not a session in a database")?;
+        let messages_table = sqlite::read_table(database, "messages")?;
+        let mut rows: Vec<_> = messages_table
+            .rows
+            .iter()
+            .filter(|row| text(&messages_table, row, "session_id") == Some(&session_id))
+            .collect();
+        rows.sort_by_key(|row| integer(&messages_table, row, "created_at"));
+
+        let mut timeline = Timeline::default();
+        let mut messages: Vec<Message> = Vec::new();
+        for row in rows {
+            timeline.observe(integer(&messages_table, row, "created_at").and_then(unix_time));
+            let role = match text(&messages_table, row, "role") {
+                Some("user") => Role::User,
+                Some("assistant") => Role::Assistant,
+                _ => continue,
+            };
+            let content = text(&messages_table, row, "parts").map(parts_text).unwrap_or_default();
+            if !content.is_empty() {
+                let timestamp = timeline.stamp(&messages);
+                messages.push(Message { role, content, timestamp, tool_calls: Vec::new() });
+            }
+        }
+
+        let project = database.parent().and_then(Path::parent).unwrap_or(Path::new("."));
+        let timestamp = timeline.finish(&mut messages, database);
+
+        Ok(Session {
+            id: session_id,
+            source: SessionSource::Crush,
+            file_path: path.to_path_buf(),
+            cwd: project.display().to_string(),
+            git_branch: None,
+            timestamp,
+            messages: join_consecutive_messages(messages, config::get().join_messages),
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: None,
+        })
+    }
+}
+
+/// Whether `path` addresses a session in a project's Crush database
+pub(super) fn is_session(path: &Path) -> bool {
+    path.parent().is_some_and(|database| {
+        database.file_name().is_some_and(|name| name == DATABASE_FILE)
+            && database.parent().is_some_and(|dir| dir.ends_with(DATA_DIR))
+    })
+}
+
+/// The sessions in the Crush databases of the folders sessions from the other sources under
+/// this home ran in, and of the configured `extra_roots`
+pub(super) fn discover(root: &DiscoveryRoot, pass: &DiscoveryPass) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for project in pass.project_folders(root) {
+        let database = project.join(DATA_DIR).join(DATABASE_FILE);
+        if !database.is_file() {
+            continue;
+        }
+        // A database that can't be read right now is tried again next time
+        let Ok(sessions) = sqlite::read_table(&database, "sessions") else {
+            continue;
+        };
+        for row in &sessions.rows {
+            let sub_task = text(&sessions, row, "parent_session_id").is_some_and(|p| !p.is_empty());
+            if let Some(id) = text(&sessions, row, "id").filter(|_| !sub_task) {
+                files.push(super::entry_path(&database, id));
+            }
+        }
+    }
+    files
+}
+
+fn text<'a>(table: &Table, row: &'a sqlite::Row, column: &str) -> Option<&'a str> {
+    table.get(row, column)?.as_text()
+}
+
+fn integer(table: &Table, row: &sqlite::Row, column: &str) -> Option<i64> {
+    match table.get(row, column)? {
+        sqlite::Value::Integer(n) => Some(*n),
+        sqlite::Value::Real(n) => Some(*n as i64),
+        _ => None,
+    }
+}
+
+/// A time Crush stored in Unix seconds (or milliseconds, in some versions)
+fn unix_time(time: i64) -> Option<DateTime<Utc>> {
+    if time > 100_000_000_000 {
+        DateTime::from_timestamp_millis(time)
+    } else {
+        DateTime::from_timestamp(time, 0)
+    }
+}
+
+/// The text parts of a message; reasoning, tool calls and results, and images are skipped
+fn parts_text(parts: &str) -> String {
+    let Ok(Value::Array(parts)) = serde_json::from_str::<Value>(parts) else {
+        return String::new();
+    };
+    parts
+        .iter()
+        .filter(|part| part.get("type").and_then(Value::as_str) == Some("text"))
+        .filter_map(|part| part.pointer("/data/text").and_then(Value::as_str))
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parts_text() {
+        let parts = r#"[
+            {"type": "reasoning", "data": {"thinking": "The user wants a test."}},
+            {"type": "text", "data": {"text": "Adding one."}},
+            {"type": "tool_call", "data": {"id": "t1", "name": "edit", "input": "{}"}},
+            {"type": "finish", "data": {"reason": "tool_use", "time": 1748772000}}
+        ]"#;
+        assert_eq!(parts_text(parts), "Adding one.");
+        assert_eq!(parts_text("not json"), "");
+    }
+
+    #[test]
+    fn test_unix_time() {
+        let time = DateTime::from_timestamp(1748772000, 0);
+        assert_eq!(unix_time(1748772000), time);
+        assert_eq!(unix_time(1748772000000), time);
+    }
+
+    #[test]
+    fn test_is_session() {
+        let database = Path::new("/w/shop/.crush/crush.db");
+        assert!(is_session(&database.join("4c1e")));
+        assert!(!is_session(database));
+        assert!(!is_session(Path::new("/w/shop/other.db/4c1e")));
+    }
+}
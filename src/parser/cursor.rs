@@ -0,0 +1,141 @@
+//! Cursor keeps each workspace's state in a SQLite database,
+//! `User/workspaceStorage/<hash>/state.vscdb` in its data folder, next to a `workspace.json`
+//! naming the folder. The AI pane's chats are one JSON value in its `ItemTable`: tabs of
+//! `bubbles`, each from the user or the AI. A workspace's chats are indexed as one session,
+//! since Cursor has no way to reopen a particular chat from outside.
+
+use crate::config;
+use crate::session::{Message, Role, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::DateTime;
+use serde::Deserialize;
+use std::path::Path;
+
+use super::sqlite::Database;
+use super::{join_consecutive_messages, timeline::Timeline, SessionParser};
+
+/// The workspace folders, under the home
+#[cfg(target_os = "macos")]
+pub const WORKSPACES_DIR: &str = "Library/Application Support/Cursor/User/workspaceStorage";
+#[cfg(windows)]
+pub const WORKSPACES_DIR: &str = "AppData/Roaming/Cursor/User/workspaceStorage";
+#[cfg(not(any(target_os = "macos", windows)))]
+pub const WORKSPACES_DIR: &str = ".config/Cursor/User/workspaceStorage";
+
+/// Each workspace's database
+pub const DATABASE_FILE: &str = "state.vscdb";
+
+/// The `ItemTable` key the chats are under
+const CHAT_KEY: &str = "workbench.panel.aichat.view.aichat.chatdata";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ChatData {
+    tabs: Vec<ChatTab>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ChatTab {
+    /// Unix milliseconds
+    last_send_time: Option<i64>,
+    bubbles: Vec<Bubble>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Bubble {
+    #[serde(rename = "type")]
+    kind: String,
+    text: Option<String>,
+    raw_text: Option<String>,
+}
+
+/// The workspace the database belongs to
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Workspace {
+    folder: Option<String>,
+}
+
+pub struct CursorParser;
+
+impl SessionParser for CursorParser {
+    fn can_parse(path: &Path) -> bool {
+        let in_cursor = path.to_str().is_some_and(|s| {
+            s.contains("Cursor/User/workspaceStorage")
+                || s.contains("Cursor\\User\\workspaceStorage")
+        });
+        in_cursor && path.file_name().is_some_and(|name| name == DATABASE_FILE)
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        let db = Database::open(path)?;
+        let items = db.table("ItemTable")?;
+        let chats = items
+            .rows
+            .iter()
+            .find(|row| items.get(row, "key").and_then(|key| key.as_text()) == Some(CHAT_KEY))
+            .and_then(|row| Some(items.get(row, "value")?.as_text()?.to_string()));
+        let mut data: ChatData = match chats {
+            Some(json) => serde_json::from_str(&json).context("Failed to parse Cursor chats")?,
+            None => ChatData::default(),
+        };
+        data.tabs.sort_by_key(|tab| tab.last_send_time);
+
+        let mut timeline = Timeline::default();
+        let mut messages: Vec<Message> = Vec::new();
+        for tab in &data.tabs {
+            // Bubbles aren't dated, so a tab's are dated when it was last sent to
+            timeline.observe(tab.last_send_time.and_then(DateTime::from_timestamp_millis));
+            for bubble in &tab.bubbles {
+                let role = match bubble.kind.as_str() {
+                    "user" => Role::User,
+                    "ai" => Role::Assistant,
+                    _ => continue,
+                };
+                let text = bubble.text.as_deref().filter(|t| !t.trim().is_empty());
+                let content = text.or(bubble.raw_text.as_deref()).unwrap_or_default().trim();
+                if !content.is_empty() {
+                    let timestamp = timeline.stamp(&messages);
+                    messages.push(Message {
+                        role,
+                        content: content.to_string(),
+                        timestamp,
+                        tool_calls: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let session_id = dir
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let timestamp = timeline.finish(&mut messages, path);
+
+        Ok(Session {
+            id: session_id,
+            source: SessionSource::Cursor,
+            file_path: path.to_path_buf(),
+            cwd: workspace_folder(dir).unwrap_or_else(|| ".".to_string()),
+            git_branch: None,
+            timestamp,
+            messages: join_consecutive_messages(messages, config::get().join_messages),
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: None,
+        })
+    }
+}
+
+/// The folder open in the workspace, from the `file://` URI in its `workspace.json`
+pub(super) fn workspace_folder(dir: &Path) -> Option<String> {
+    let text = std::fs::read_to_string(dir.join("workspace.json")).ok()?;
+    let workspace: Workspace = serde_json::from_str(&text).ok()?;
+    let path = workspace.folder?.strip_prefix("file://")?.to_string();
+    Some(super::percent_decode(&path))
+}
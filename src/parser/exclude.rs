@@ -0,0 +1,191 @@
+//! Keep sessions matching `exclude` patterns out: the config's at discovery, and those in
+//! the `.recall.toml` of each session's project once the session is read for indexing (its
+//! folder is only known then, and probing every file for it would cost each discovery a read
+//! of the whole corpus)
+
+use crate::config::{project_config_path, Config};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+use super::SessionParser;
+
+/// JSONL lines read looking for a working directory before giving up
+const PROBE_LINES: usize = 50;
+
+/// Bytes read from the start of a JSON session looking for its working directory
+const PROBE_BYTES: u64 = 64 * 1024;
+
+/// Compile glob patterns, skipping invalid ones (the config rejects them when loaded)
+pub fn compile(patterns: &[String]) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        if let Ok(glob) = Glob::new(pattern) {
+            builder.add(glob);
+        }
+    }
+    builder.build().unwrap_or_else(|_| GlobSet::empty())
+}
+
+fn patterns() -> &'static GlobSet {
+    static PATTERNS: OnceLock<GlobSet> = OnceLock::new();
+    PATTERNS.get_or_init(|| compile(&crate::config::get().exclude))
+}
+
+/// A `.recall.toml`'s patterns, as of the modification time they were read at
+type ProjectPatterns = (SystemTime, Option<Arc<GlobSet>>);
+
+/// Patterns from the `.recall.toml` governing `cwd`, cached per file and read again when it
+/// changes, so a long-running `watch` or `serve` sees edits
+fn project_patterns(cwd: &str) -> Option<Arc<GlobSet>> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, ProjectPatterns>>> = OnceLock::new();
+    let path = project_config_path(Path::new(cwd))?;
+    let mtime = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+    let mut cache = CACHE.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((_, patterns)) = cache.get(&path).filter(|(read_at, _)| *read_at == mtime) {
+        return patterns.clone();
+    }
+    let patterns = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|text| Config::parse(&text).ok())
+        .map(|(config, _)| Arc::new(compile(&config.exclude)))
+        .filter(|set| !set.is_empty());
+    cache.insert(path, (mtime, patterns.clone()));
+    patterns
+}
+
+/// Whether a session file is excluded by the config, by its own path or its session's cwd.
+/// Files are only read for their cwd when there are patterns to match it against.
+pub fn is_excluded(path: &Path) -> bool {
+    let patterns = patterns();
+    if patterns.is_empty() {
+        return false;
+    }
+    if patterns.is_match(path) {
+        return true;
+    }
+    probe_cwd(path).is_some_and(|cwd| cwd_matches(patterns, &cwd))
+}
+
+/// Whether the `.recall.toml` of the project a session was recorded in excludes it
+pub fn is_excluded_by_project(cwd: &str) -> bool {
+    !cwd.is_empty() && project_patterns(cwd).is_some_and(|p| cwd_matches(&p, cwd))
+}
+
+/// Match a working directory as a directory, so `**/scratch/**` covers `/w/scratch` itself
+fn cwd_matches(patterns: &GlobSet, cwd: &str) -> bool {
+    patterns.is_match(cwd) || patterns.is_match(format!("{}/", cwd.trim_end_matches('/')))
+}
+
+/// The session's working directory, read from the start of the file without parsing it
+pub fn probe_cwd(path: &Path) -> Option<String> {
+    if super::aider::is_history(path) {
+        // Aider's history is in the project's root
+        return path.parent().map(|dir| dir.display().to_string());
+    }
+    if super::AmazonQParser::can_parse(path) {
+        // Amazon Q keys its conversations by directory
+        return super::split_entry(path).map(|(_, directory)| directory);
+    }
+    let file = std::fs::File::open(path).ok()?;
+    if path.extension().is_some_and(|e| e == "json") {
+        // OpenCode session info (`directory`) and imported sessions (`cwd`) name it near the
+        // start, so only that much is read, and the first string under either key is taken
+        let mut start = Vec::new();
+        file.take(PROBE_BYTES).read_to_end(&mut start).ok()?;
+        let text = String::from_utf8_lossy(&start);
+        return ["cwd", "directory"].iter().find_map(|key| json_string_after(&text, key));
+    }
+    BufReader::new(file)
+        .lines()
+        .take(PROBE_LINES)
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(&line).ok())
+        .find_map(|entry| {
+            // Claude and Factory put cwd on entries, Codex in the session_meta payload
+            entry
+                .get("cwd")
+                .or_else(|| entry.get("payload").and_then(|p| p.get("cwd")))
+                .and_then(|v| v.as_str())
+                .map(String::from)
+        })
+}
+
+/// The string value of the first `"key":` in `text`, which may be cut off after it
+fn json_string_after(text: &str, key: &str) -> Option<String> {
+    let quoted = format!("\"{}\"", key);
+    text.match_indices(&quoted).find_map(|(at, _)| {
+        let rest = text[at + quoted.len()..].trim_start().strip_prefix(':')?;
+        serde_json::Deserializer::from_str(rest).into_iter::<String>().next()?.ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cwd_matches() {
+        let patterns = compile(&["**/scratch/**".to_string(), "**/*client-x*".to_string()]);
+        assert!(cwd_matches(&patterns, "/home/me/scratch"));
+        assert!(cwd_matches(&patterns, "/home/me/scratch/tmp"));
+        assert!(cwd_matches(&patterns, "/work/client-x-portal/api"));
+        assert!(!cwd_matches(&patterns, "/work/shop"));
+        assert!(patterns.is_match("/h/.claude/projects/-work-client-x/1.jsonl"));
+
+        // A project file excluding everything
+        assert!(cwd_matches(&compile(&["**".to_string()]), "/work/client"));
+        assert!(!cwd_matches(&compile(&[]), "/home/me/scratch"));
+    }
+
+    #[test]
+    fn test_project_patterns() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let repo = temp.path().join("client");
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+        std::fs::write(repo.join(".recall.toml"), "exclude = [\"**\"]\n").unwrap();
+
+        let cwd = repo.join("src").display().to_string();
+        let patterns = project_patterns(&cwd).unwrap();
+        assert!(cwd_matches(&patterns, &cwd));
+        assert!(is_excluded_by_project(&cwd));
+        assert!(project_patterns(&temp.path().display().to_string()).is_none());
+
+        // An edit is seen once the file's modification time moves
+        let config = std::fs::File::options().write(true).open(repo.join(".recall.toml")).unwrap();
+        std::fs::write(repo.join(".recall.toml"), "exclude = [\"**/scratch/**\"]\n").unwrap();
+        config.set_modified(SystemTime::now() + std::time::Duration::from_secs(5)).unwrap();
+        assert!(!is_excluded_by_project(&cwd));
+        assert!(is_excluded_by_project(&repo.join("scratch").display().to_string()));
+    }
+
+    #[test]
+    fn test_probe_cwd() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let codex = temp.path().join("rollout.jsonl");
+        std::fs::write(
+            &codex,
+            r#"{"type":"session_meta","payload":{"id":"x","cwd":"/projects/webapp"}}"#,
+        )
+        .unwrap();
+        assert_eq!(probe_cwd(&codex).as_deref(), Some("/projects/webapp"));
+
+        let opencode = temp.path().join("ses_1.json");
+        std::fs::write(&opencode, r#"{"id":"ses_1","directory":"/w/app"}"#).unwrap();
+        assert_eq!(probe_cwd(&opencode).as_deref(), Some("/w/app"));
+
+        // Only the start of a large file is read, even cut off mid-document
+        let imported = temp.path().join("imported.json");
+        let messages = format!("[\"{}\"]", "x".repeat(PROBE_BYTES as usize));
+        let text = format!(r#"{{"session_id":"s","cwd":"/w/big","messages":{}}}"#, messages);
+        std::fs::write(&imported, text).unwrap();
+        assert_eq!(probe_cwd(&imported).as_deref(), Some("/w/big"));
+        let cut = r#"{"cwd" : "/w/a\"b", "messages": ["#;
+        assert_eq!(json_string_after(cut, "cwd").as_deref(), Some("/w/a\"b"));
+
+        assert_eq!(probe_cwd(&temp.path().join("missing.jsonl")), None);
+    }
+}
@@ -1,4 +1,5 @@
-use crate::session::{Message, Role, Session, SessionSource};
+use crate::config;
+use crate::session::{FileTouch, Message, Role, Session, SessionSource};
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
@@ -6,7 +7,9 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
-use super::{join_consecutive_messages, SessionParser};
+use super::{
+    files, join_consecutive_messages, timeline::Timeline, tools::OpenCalls, SessionParser,
+};
 
 #[derive(Debug, Deserialize)]
 struct FactoryLine {
@@ -16,6 +19,9 @@ struct FactoryLine {
     #[allow(dead_code)]
     title: Option<String>,
     cwd: Option<String>,
+    /// The branch checked out when the session started, on `session_start`
+    #[serde(rename = "gitBranch", alias = "branch")]
+    git_branch: Option<String>,
     timestamp: Option<String>,
     message: Option<FactoryMessage>,
 }
@@ -42,8 +48,11 @@ impl SessionParser for FactoryParser {
 
         let mut session_id: Option<String> = None;
         let mut cwd: Option<String> = None;
-        let mut latest_timestamp: Option<DateTime<Utc>> = None;
+        let mut git_branch: Option<String> = None;
+        let mut timeline = Timeline::default();
         let mut messages: Vec<Message> = Vec::new();
+        let mut files_touched: Vec<FileTouch> = Vec::new();
+        let mut calls = OpenCalls::default();
 
         for line in reader.lines() {
             let line = line.context("Failed to read line")?;
@@ -65,20 +74,18 @@ impl SessionParser for FactoryParser {
                     if cwd.is_none() {
                         cwd = entry.cwd.clone();
                     }
+                    if git_branch.is_none() {
+                        git_branch = entry.git_branch.clone().filter(|b| !b.is_empty());
+                    }
                 }
                 "message" => {
-                    // Parse timestamp
-                    let timestamp = entry
-                        .timestamp
-                        .as_ref()
-                        .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(Utc::now);
-
-                    // Update latest timestamp
-                    if latest_timestamp.is_none() || timestamp > latest_timestamp.unwrap() {
-                        latest_timestamp = Some(timestamp);
-                    }
+                    timeline.observe(
+                        entry
+                            .timestamp
+                            .as_ref()
+                            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+                            .map(|dt| dt.with_timezone(&Utc)),
+                    );
 
                     // Extract message content
                     if let Some(msg) = &entry.message {
@@ -90,10 +97,24 @@ impl SessionParser for FactoryParser {
 
                         let content = extract_content(&msg.content);
                         if !content.is_empty() {
+                            let timestamp = timeline.stamp(&messages);
                             messages.push(Message {
                                 role,
                                 content,
                                 timestamp,
+                                tool_calls: Vec::new(),
+                            });
+                        }
+
+                        // Tool calls are attributed to the message they followed, and their
+                        // results come back in the next user entry
+                        calls.record_tool_results(&msg.content, &mut messages);
+                        calls.push_tool_uses(&msg.content, &mut messages);
+                        for (op, path) in files::tool_use_file_ops(&msg.content) {
+                            files_touched.push(FileTouch {
+                                path,
+                                op,
+                                message_index: files::latest_index(&messages),
                             });
                         }
                     }
@@ -107,6 +128,10 @@ impl SessionParser for FactoryParser {
             cwd = extract_cwd_from_path(path);
         }
 
+        let cwd = cwd.unwrap_or_else(|| ".".to_string());
+        files::resolve_paths(&mut files_touched, &cwd);
+        let git_branch = git_branch.or_else(|| super::git::head_branch(&cwd, path));
+
         // Fall back to filename for session ID if not found
         let session_id = session_id.unwrap_or_else(|| {
             path.file_stem()
@@ -115,14 +140,21 @@ impl SessionParser for FactoryParser {
                 .to_string()
         });
 
+        let timestamp = timeline.finish(&mut messages, path);
+        let join = config::get().join_messages;
+        files::rejoin(&mut files_touched, &messages, join);
+
         Ok(Session {
             id: session_id,
             source: SessionSource::Factory,
             file_path: path.to_path_buf(),
-            cwd: cwd.unwrap_or_else(|| ".".to_string()),
-            git_branch: None,
-            timestamp: latest_timestamp.unwrap_or_else(Utc::now),
-            messages: join_consecutive_messages(messages),
+            cwd,
+            git_branch,
+            timestamp,
+            messages: join_consecutive_messages(messages, join),
+            files_touched,
+            usage: Vec::new(),
+            title: None,
         })
     }
 }
@@ -0,0 +1,130 @@
+//! Files touched by tool calls (shared by parsers whose logs record tool_use blocks)
+
+use crate::config::MessageJoin;
+use crate::session::{FileOp, FileTouch, Message};
+use std::path::Path;
+
+/// Map a tool name to the file operation it performs.
+/// Claude Code and Factory use the same names for their file tools.
+fn tool_file_op(name: &str) -> Option<FileOp> {
+    match name {
+        "Read" | "NotebookRead" => Some(FileOp::Read),
+        "Edit" | "MultiEdit" | "NotebookEdit" => Some(FileOp::Edit),
+        "Write" | "Create" => Some(FileOp::Create),
+        _ => None,
+    }
+}
+
+/// Extract file operations from the tool_use blocks of a message content array
+pub(crate) fn tool_use_file_ops(content: &serde_json::Value) -> Vec<(FileOp, String)> {
+    let Some(blocks) = content.as_array() else {
+        return Vec::new();
+    };
+
+    blocks
+        .iter()
+        .filter(|block| block.get("type").and_then(|v| v.as_str()) == Some("tool_use"))
+        .filter_map(|block| {
+            let op = tool_file_op(block.get("name")?.as_str()?)?;
+            let input = block.get("input")?;
+            let path = ["file_path", "notebook_path", "path"]
+                .iter()
+                .find_map(|key| input.get(*key)?.as_str())?;
+            Some((op, path.to_string()))
+        })
+        .collect()
+}
+
+/// Index of the latest message, before consecutive messages are joined (see [`rejoin`])
+pub(crate) fn latest_index(messages: &[Message]) -> usize {
+    messages.len().saturating_sub(1)
+}
+
+/// Point touches recorded against `messages` (see [`latest_index`]) at the message each one's
+/// message becomes part of once they're joined as far as `join` allows
+pub(crate) fn rejoin(touches: &mut [FileTouch], messages: &[Message], join: MessageJoin) {
+    let joined = super::joined_indices(messages, join);
+    for touch in touches {
+        touch.message_index = joined.get(touch.message_index).copied().unwrap_or(0);
+    }
+}
+
+/// Make relative paths absolute against the session's working directory
+pub(crate) fn resolve_paths(touches: &mut [FileTouch], cwd: &str) {
+    if !Path::new(cwd).is_absolute() {
+        return;
+    }
+    for touch in touches {
+        if !Path::new(&touch.path).is_absolute() {
+            touch.path = Path::new(cwd).join(&touch.path).to_string_lossy().to_string();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::Role;
+    use chrono::Utc;
+
+    #[test]
+    fn test_tool_use_file_ops() {
+        let content = serde_json::json!([
+            {"type": "text", "text": "Let me look"},
+            {"type": "tool_use", "name": "Read", "input": {"file_path": "/p/src/main.rs"}},
+            {"type": "tool_use", "name": "Bash", "input": {"command": "ls"}},
+            {"type": "tool_use", "name": "MultiEdit", "input": {"file_path": "/p/src/app.rs"}},
+            {"type": "tool_use", "name": "Write", "input": {"file_path": "/p/NEW.md"}}
+        ]);
+        assert_eq!(
+            tool_use_file_ops(&content),
+            [
+                (FileOp::Read, "/p/src/main.rs".to_string()),
+                (FileOp::Edit, "/p/src/app.rs".to_string()),
+                (FileOp::Create, "/p/NEW.md".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rejoin() {
+        let start = Utc::now();
+        let msg = |role, minutes| Message {
+            role,
+            content: "x".to_string(),
+            timestamp: start + chrono::Duration::minutes(minutes),
+            tool_calls: Vec::new(),
+        };
+        // The second reply comes after a gap wider than the window, so it stays apart
+        let messages = [
+            msg(Role::User, 0),
+            msg(Role::Assistant, 1),
+            msg(Role::Assistant, 2),
+            msg(Role::Assistant, 30),
+        ];
+        let touch = |message_index| FileTouch {
+            path: "/p/src/lib.rs".to_string(),
+            op: FileOp::Read,
+            message_index,
+        };
+        let rejoined = |join| {
+            let mut touches: Vec<_> = (0..4).map(touch).collect();
+            rejoin(&mut touches, &messages, join);
+            touches.iter().map(|t| t.message_index).collect::<Vec<_>>()
+        };
+        assert_eq!(rejoined(MessageJoin::Gap(15)), [0, 1, 1, 2]);
+        assert_eq!(rejoined(MessageJoin::Always), [0, 1, 1, 1]);
+        assert_eq!(rejoined(MessageJoin::Off), [0, 1, 2, 3]);
+        assert_eq!(latest_index(&messages), 3);
+        assert_eq!(latest_index(&[]), 0);
+    }
+
+    #[test]
+    fn test_resolve_paths() {
+        let touch = |path: &str| FileTouch { path: path.to_string(), op: FileOp::Read, message_index: 0 };
+        let mut touches = vec![touch("src/lib.rs"), touch("/abs/file.rs")];
+        resolve_paths(&mut touches, "/home/u/proj");
+        assert_eq!(touches[0].path, "/home/u/proj/src/lib.rs");
+        assert_eq!(touches[1].path, "/abs/file.rs");
+    }
+}
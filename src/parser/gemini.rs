@@ -0,0 +1,231 @@
+//! Gemini CLI conversations under `~/.gemini/tmp/<project hash>/`: checkpoints saved with
+//! `/chat save <tag>` (`checkpoint-<tag>.json`, a `history` of `{role, parts}` contents, bare
+//! or with metadata around it), and recorded chats (`chats/session-*.json`, a list of
+//! `messages`).
+
+use crate::config;
+use crate::session::{Message, Role, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use super::{join_consecutive_messages, timeline::Timeline, SessionParser};
+
+/// How the context Gemini CLI opens every chat with says where it runs
+const WORKING_DIR_LINE: &str = "I'm currently working in the directory: ";
+
+/// A saved conversation, with or without metadata
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum GeminiFile {
+    Saved(GeminiChat),
+    Bare(Vec<Content>),
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct GeminiChat {
+    #[serde(alias = "id")]
+    session_id: Option<String>,
+    #[serde(alias = "projectRoot", alias = "directory")]
+    cwd: Option<String>,
+    last_updated: Option<String>,
+    history: Vec<Content>,
+    messages: Vec<ChatMessage>,
+}
+
+/// A turn as the Gemini API has it
+#[derive(Debug, Deserialize)]
+struct Content {
+    role: String,
+    #[serde(default)]
+    parts: Vec<Value>,
+}
+
+/// A turn as recorded chats have it
+#[derive(Debug, Deserialize)]
+struct ChatMessage {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    content: Value,
+    timestamp: Option<String>,
+}
+
+pub struct GeminiParser;
+
+impl SessionParser for GeminiParser {
+    fn can_parse(path: &Path) -> bool {
+        path.to_str()
+            .map(|s| s.contains(".gemini/tmp") || s.contains(".gemini\\tmp"))
+            .unwrap_or(false)
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        let file = File::open(path).context("Failed to open file")?;
+        let chat = match serde_json::from_reader(BufReader::new(file))
+            .context("Failed to parse Gemini chat")?
+        {
+            GeminiFile::Saved(chat) => chat,
+            GeminiFile::Bare(history) => GeminiChat { history, ..Default::default() },
+        };
+
+        let mut timeline = Timeline::default();
+        let mut messages: Vec<Message> = Vec::new();
+        let mut cwd = chat.cwd;
+
+        let turns = chat.history.iter().map(|c| (c.role.as_str(), parts_text(&c.parts), None));
+        let recorded = chat.messages.iter().map(|m| {
+            let text = match &m.content {
+                Value::Array(parts) => parts_text(parts),
+                content => content.as_str().unwrap_or_default().to_string(),
+            };
+            (m.kind.as_str(), text, m.timestamp.as_deref())
+        });
+        let mut in_setup = false;
+        for (role, content, timestamp) in turns.chain(recorded) {
+            timeline.observe(timestamp.and_then(parse_time));
+            let role = match role {
+                "user" => Role::User,
+                "model" | "gemini" => Role::Assistant,
+                _ => continue,
+            };
+            // The context the CLI opens with, and the model's acknowledgment of it
+            if let Some(dir) = setup_working_dir(&content) {
+                cwd.get_or_insert(dir);
+                in_setup = true;
+                continue;
+            }
+            if std::mem::take(&mut in_setup) && role == Role::Assistant {
+                continue;
+            }
+            if !content.trim().is_empty() {
+                let timestamp = timeline.stamp(&messages);
+                messages.push(Message { role, content, timestamp, tool_calls: Vec::new() });
+            }
+        }
+        timeline.observe(chat.last_updated.as_deref().and_then(parse_time));
+
+        // Checkpoints are named for their tag, which is what resumes them
+        let session_id = chat.session_id.unwrap_or_else(|| {
+            let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("unknown");
+            stem.strip_prefix("checkpoint-").unwrap_or(stem).to_string()
+        });
+
+        let timestamp = timeline.finish(&mut messages, path);
+
+        Ok(Session {
+            id: session_id,
+            source: SessionSource::Gemini,
+            file_path: path.to_path_buf(),
+            cwd: cwd.unwrap_or_else(|| ".".to_string()),
+            git_branch: None,
+            timestamp,
+            messages: join_consecutive_messages(messages, config::get().join_messages),
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: None,
+        })
+    }
+}
+
+/// The text of a turn's parts, leaving out thoughts and function calls and responses
+fn parts_text(parts: &[Value]) -> String {
+    parts
+        .iter()
+        .filter(|part| part.get("thought").and_then(Value::as_bool) != Some(true))
+        .filter_map(|part| part.get("text").and_then(Value::as_str))
+        .collect()
+}
+
+/// The working directory in the context message Gemini CLI opens chats with
+fn setup_working_dir(content: &str) -> Option<String> {
+    if !content.starts_with("This is the Gemini CLI.") {
+        return None;
+    }
+    let dir = content.lines().find_map(|line| line.trim().strip_prefix(WORKING_DIR_LINE));
+    Some(dir.unwrap_or_default().trim_end_matches('.').trim().to_string()).filter(|d| !d.is_empty())
+}
+
+fn parse_time(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text).ok().map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn parse(dir: &Path, name: &str, value: Value) -> Session {
+        let path = dir.join(".gemini/tmp/3f2a").join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, value.to_string()).unwrap();
+        GeminiParser::parse_file(&path).unwrap()
+    }
+
+    #[test]
+    fn test_parse_bare_checkpoint() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let setup = "This is the Gemini CLI. We are setting up the context for our chat.\n\
+                     Today's date is Monday.\n\
+                     I'm currently working in the directory: /w/api\n\
+                     Here is the folder structure: ...";
+        let session = parse(
+            temp.path(),
+            "checkpoint-auth.json",
+            json!([
+                {"role": "user", "parts": [{"text": setup}]},
+                {"role": "model", "parts": [{"text": "Got it. Thanks for the context!"}]},
+                {"role": "user", "parts": [{"text": "why does login "}, {"text": "time out?"}]},
+                {"role": "model", "parts": [
+                    {"text": "Checking the handler.", "thought": true},
+                    {"functionCall": {"name": "read_file", "args": {"path": "auth.ts"}}}
+                ]},
+                {"role": "user", "parts": [{"functionResponse": {"name": "read_file"}}]},
+                {"role": "model", "parts": [{"text": "The token refresh never resolves."}]}
+            ]),
+        );
+        assert_eq!(session.id, "auth");
+        assert_eq!(session.source, SessionSource::Gemini);
+        assert_eq!(session.cwd, "/w/api");
+        let contents: Vec<_> =
+            session.messages.iter().map(|m| (m.role, m.content.as_str())).collect();
+        assert_eq!(
+            contents,
+            [
+                (Role::User, "why does login time out?"),
+                (Role::Assistant, "The token refresh never resolves."),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_recorded_chat() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let session = parse(
+            temp.path(),
+            "session-2025-06-01.json",
+            json!({
+                "sessionId": "b1d6",
+                "projectRoot": "/w/site",
+                "lastUpdated": "2025-06-01T10:05:00Z",
+                "messages": [
+                    {"type": "user", "content": "add dark mode",
+                     "timestamp": "2025-06-01T10:00:00Z"},
+                    {"type": "gemini", "content": "Added a toggle.",
+                     "timestamp": "2025-06-01T10:01:00Z"},
+                    {"type": "info", "content": "Saved", "timestamp": "2025-06-01T10:02:00Z"}
+                ]
+            }),
+        );
+        assert_eq!((session.id.as_str(), session.cwd.as_str()), ("b1d6", "/w/site"));
+        assert_eq!(session.messages.len(), 2);
+        assert_eq!(session.messages[1].role, Role::Assistant);
+        assert_eq!(session.messages[1].timestamp, parse_time("2025-06-01T10:01:00Z").unwrap());
+        assert_eq!(session.timestamp, parse_time("2025-06-01T10:05:00Z").unwrap());
+    }
+}
@@ -0,0 +1,345 @@
+//! Sessions in formats recall has no parser for, described by a `[[custom_source]]` in the
+//! config: a glob for the files, and JSON pointers to the fields of each record.
+
+use crate::config::{self, CustomSource, RecordFormat};
+use crate::session::{Message, Role, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use super::{join_consecutive_messages, timeline::Timeline, DiscoveryRoot, SessionParser};
+
+pub struct GenericParser;
+
+impl SessionParser for GenericParser {
+    fn can_parse(path: &Path) -> bool {
+        spec_for(path).is_some()
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        let spec = spec_for(path).context("No custom_source matches this file")?;
+        GenericParser::parse_with(spec, path)
+    }
+}
+
+impl GenericParser {
+    /// Parse `path` the way `spec` describes it
+    pub fn parse_with(spec: &CustomSource, path: &Path) -> Result<Session> {
+        let records = read_records(spec, path)?;
+
+        let mut session_id: Option<String> = None;
+        let mut cwd: Option<String> = None;
+        let mut timeline = Timeline::default();
+        let mut messages: Vec<Message> = Vec::new();
+
+        for record in &records {
+            if session_id.is_none() {
+                session_id = spec.session_id.as_deref().and_then(|p| text_at(record, p));
+            }
+            if cwd.is_none() {
+                cwd = spec.cwd.as_deref().and_then(|p| text_at(record, p));
+            }
+            timeline.observe(spec.timestamp.as_deref().and_then(|p| time_at(record, p)));
+
+            let role = record.pointer(&spec.role).and_then(Value::as_str).and_then(|role| {
+                if spec.user_roles.iter().any(|r| r == role) {
+                    Some(Role::User)
+                } else if spec.assistant_roles.iter().any(|r| r == role) {
+                    Some(Role::Assistant)
+                } else {
+                    None
+                }
+            });
+            let Some(role) = role else {
+                continue;
+            };
+            let content = record.pointer(&spec.content).map(extract_content).unwrap_or_default();
+            if !content.trim().is_empty() {
+                let timestamp = timeline.stamp(&messages);
+                messages.push(Message { role, content, timestamp, tool_calls: Vec::new() });
+            }
+        }
+
+        // Fall back to filename for session ID if not found
+        let session_id = session_id.unwrap_or_else(|| {
+            path.file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("unknown")
+                .to_string()
+        });
+
+        let timestamp = timeline.finish(&mut messages, path);
+
+        Ok(Session {
+            id: session_id,
+            source: SessionSource::custom(&spec.name),
+            file_path: path.to_path_buf(),
+            cwd: cwd.unwrap_or_else(|| ".".to_string()),
+            git_branch: None,
+            timestamp,
+            messages: join_consecutive_messages(messages, config::get().join_messages),
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: None,
+        })
+    }
+}
+
+/// The file's records: each line of a JSONL file (malformed ones skipped), or the array
+/// `spec.records` points to in a JSON file
+fn read_records(spec: &CustomSource, path: &Path) -> Result<Vec<Value>> {
+    let file = File::open(path).context("Failed to open file")?;
+    let reader = BufReader::with_capacity(64 * 1024, file);
+    match spec.format {
+        RecordFormat::Jsonl => {
+            let mut records = Vec::new();
+            for line in reader.lines() {
+                let line = line.context("Failed to read line")?;
+                if let Ok(record) = serde_json::from_str(&line) {
+                    records.push(record);
+                }
+            }
+            Ok(records)
+        }
+        RecordFormat::Json => {
+            let mut document: Value =
+                serde_json::from_reader(reader).context("Failed to parse session JSON")?;
+            match document.pointer_mut(&spec.records).map(Value::take) {
+                Some(Value::Array(records)) => Ok(records),
+                _ => anyhow::bail!("No array of records at '{}'", spec.records),
+            }
+        }
+    }
+}
+
+/// A string or number at `pointer`, as text (None if it's empty)
+fn text_at(record: &Value, pointer: &str) -> Option<String> {
+    let text = match record.pointer(pointer)? {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        _ => return None,
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+/// A time at `pointer`: RFC 3339, or Unix seconds, or milliseconds when the number is too
+/// big to be seconds
+fn time_at(record: &Value, pointer: &str) -> Option<DateTime<Utc>> {
+    match record.pointer(pointer)? {
+        Value::String(s) => DateTime::parse_from_rfc3339(s).ok().map(|t| t.with_timezone(&Utc)),
+        Value::Number(n) => {
+            let n = n.as_f64()?;
+            // Seconds won't reach 1e11 until the year 5138
+            let millis = if n.abs() >= 1e11 { n } else { n * 1000.0 };
+            DateTime::from_timestamp_millis(millis as i64)
+        }
+        _ => None,
+    }
+}
+
+/// Message text from a string, or an array of strings and `{"text": ...}` parts
+fn extract_content(content: &Value) -> String {
+    match content {
+        Value::String(s) => s.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| match part {
+                Value::String(s) => Some(s.as_str()),
+                _ => part.get("text").and_then(Value::as_str),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// `spec`'s glob as an absolute pattern under `home`. An absolute glob only applies to the
+/// user's own home, so it's None for an extra one.
+fn absolute_glob(spec: &CustomSource, home: &Path, extra_home: bool) -> Option<String> {
+    let relative = match spec.glob.strip_prefix("~/") {
+        Some(rest) => rest,
+        None if Path::new(&spec.glob).is_absolute() => {
+            return (!extra_home).then(|| spec.glob.clone());
+        }
+        None => &spec.glob,
+    };
+    let home = globset::escape(&home.to_string_lossy());
+    Some(format!("{}/{}", home.trim_end_matches('/'), relative))
+}
+
+/// A matcher where `*` stays within one directory and `**` crosses them
+fn matcher(glob: &str) -> Option<globset::GlobMatcher> {
+    let glob = globset::GlobBuilder::new(glob).literal_separator(true).build().ok()?;
+    Some(glob.compile_matcher())
+}
+
+/// The directory a glob starts from: everything before its first wildcard
+fn glob_base(glob: &str) -> PathBuf {
+    Path::new(glob)
+        .components()
+        .take_while(|c| !c.as_os_str().to_string_lossy().contains(['*', '?', '[', '{']))
+        .collect()
+}
+
+/// A discovery root per custom source in the config, under `home`
+pub(crate) fn roots(home: &Path, origin: Option<&Path>) -> Vec<DiscoveryRoot> {
+    config::get()
+        .custom_sources
+        .iter()
+        .filter_map(|spec| {
+            let glob = absolute_glob(spec, home, origin.is_some())?;
+            Some(DiscoveryRoot {
+                source: Some(SessionSource::custom(&spec.name)),
+                dir: glob_base(&glob),
+                origin: origin.map(Path::to_path_buf),
+            })
+        })
+        .collect()
+}
+
+/// The files under `root` (one of [`roots`]) that its source's glob matches
+pub(crate) fn discover(root: &DiscoveryRoot, name: &str) -> Vec<PathBuf> {
+    let Some(spec) = config::get().custom_source(name) else {
+        return Vec::new();
+    };
+    let home = root.origin.clone().or_else(super::default_home).unwrap_or_default();
+    let Some(matcher) = absolute_glob(spec, &home, root.origin.is_some()).and_then(|g| matcher(&g))
+    else {
+        return Vec::new();
+    };
+    walkdir::WalkDir::new(&root.dir)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.file_type().is_file() && matcher.is_match(entry.path()))
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// The custom source whose glob matches `path`, under the user's home or an extra one
+fn spec_for(path: &Path) -> Option<&'static CustomSource> {
+    let config = config::get();
+    if config.custom_sources.is_empty() {
+        return None;
+    }
+    let homes: Vec<(PathBuf, bool)> = super::default_home()
+        .map(|home| (home, false))
+        .into_iter()
+        .chain(config.extra_homes().into_iter().map(|home| (home, true)))
+        .collect();
+    config.custom_sources.iter().find(|spec| {
+        homes.iter().any(|(home, extra)| {
+            absolute_glob(spec, home, *extra)
+                .and_then(|glob| matcher(&glob))
+                .is_some_and(|m| m.is_match(path))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn spec(toml: &str) -> CustomSource {
+        let text = format!("[[custom_source]]\nname = \"toy\"\nglob = \"toy/*.json\"\n{}", toml);
+        let (config, unknown) = config::Config::parse(&text).unwrap();
+        assert!(unknown.is_empty(), "{:?}", unknown);
+        config.custom_sources[0].clone()
+    }
+
+    fn write(text: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_json_array_with_content_parts() {
+        let spec = spec(
+            r#"
+format = "json"
+records = "/chat/turns"
+role = "/who"
+content = "/body"
+timestamp = "/at"
+session_id = "/conversation"
+user_roles = ["me"]
+assistant_roles = ["toy"]
+"#,
+        );
+        let file = write(
+            r#"{"chat": {"turns": [
+                {"who": "me", "body": "what's a monad", "at": 1736935200, "conversation": 42},
+                {"who": "system", "body": "ignored"},
+                {"who": "toy", "body": ["A monoid", {"text": "in endofunctors"}, {"n": 1}],
+                 "at": 1736935260000}
+            ]}}"#,
+        );
+
+        let session = GenericParser::parse_with(&spec, file.path()).unwrap();
+        assert_eq!(session.id, "42");
+        assert_eq!(session.source, SessionSource::Custom("toy"));
+        assert_eq!(session.cwd, ".");
+        assert_eq!(session.messages.len(), 2);
+        assert_eq!(session.messages[0].role, Role::User);
+        assert_eq!(session.messages[1].content, "A monoid\nin endofunctors");
+        // Seconds and milliseconds both read as times
+        assert_eq!(session.messages[0].timestamp.to_rfc3339(), "2025-01-15T10:00:00+00:00");
+        assert_eq!(session.timestamp.to_rfc3339(), "2025-01-15T10:01:00+00:00");
+    }
+
+    #[test]
+    fn test_jsonl_skips_malformed_lines() {
+        let spec = spec("role = \"/r\"\ncontent = \"/c\"\ncwd = \"/dir\"\n");
+        let file = write(concat!(
+            r#"{"r": "user", "c": "hi", "dir": "/w"}"#,
+            "\nnot json\n",
+            r#"{"r": "ai", "c": "hey"}"#,
+        ));
+        let session = GenericParser::parse_with(&spec, file.path()).unwrap();
+        assert_eq!(session.cwd, "/w");
+        assert_eq!(session.messages.len(), 2);
+        // No ID in the records: the file name
+        assert_eq!(session.id, file.path().file_stem().unwrap().to_str().unwrap());
+
+        let missing = GenericParser::parse_with(
+            &CustomSource { format: RecordFormat::Json, ..spec },
+            file.path(),
+        );
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_globs() {
+        let spec = spec("role = \"/r\"\ncontent = \"/c\"\n");
+        let home = Path::new("/home/me");
+        let glob = absolute_glob(&spec, home, false).unwrap();
+        assert_eq!(glob, "/home/me/toy/*.json");
+        assert_eq!(glob_base(&glob), Path::new("/home/me/toy"));
+        let matcher = matcher(&glob).unwrap();
+        assert!(matcher.is_match("/home/me/toy/a.json"));
+        assert!(!matcher.is_match("/home/me/toy/nested/a.json"));
+
+        let absolute = CustomSource { glob: "/var/toy/**/*.json".to_string(), ..spec };
+        assert_eq!(absolute_glob(&absolute, home, false).unwrap(), "/var/toy/**/*.json");
+        assert_eq!(absolute_glob(&absolute, home, true), None);
+        assert_eq!(glob_base("/var/toy/**/*.json"), Path::new("/var/toy"));
+    }
+
+    #[test]
+    fn test_invalid_specs() {
+        let parse = |toml: &str| {
+            let text = format!("[[custom_source]]\nrole = \"/r\"\ncontent = \"/c\"\n{}", toml);
+            config::Config::parse(&text)
+        };
+        assert!(parse("name = \"toy\"\nglob = \"toy/*.json\"").is_ok());
+        assert!(parse("name = \"Toy Bot\"\nglob = \"toy/*.json\"").is_err());
+        assert!(parse("name = \"claude\"\nglob = \"toy/*.json\"").is_err());
+        assert!(parse("name = \"toy\"\nglob = \"toy/[.json\"").is_err());
+        assert!(parse("name = \"toy\"\nglob = \"toy/*.json\"\ntimestamp = \"at\"").is_err());
+        assert!(parse("name = \"toy\"\nglob = \"*.json\"\nformat = \"xml\"").is_err());
+    }
+}
@@ -0,0 +1,58 @@
+//! The branch checked out in a session's folder, for sources that don't record one. It's the
+//! branch now rather than when the session ran, so parsers only fall back to it.
+
+use std::path::Path;
+
+/// The branch checked out in the repository at `cwd`, when this machine's `cwd` is the one the
+/// session at `session_file` ran in. Only `cwd` itself is looked at, not the folders above it,
+/// and only its `HEAD`, so this stays one or two small reads per session.
+pub(crate) fn head_branch(cwd: &str, session_file: &Path) -> Option<String> {
+    let cwd = Path::new(cwd);
+    // Sessions from another home or machine ran in folders that aren't these
+    if !cwd.is_absolute()
+        || super::origin_of(session_file).is_some()
+        || crate::remote::host_of(session_file).is_some()
+    {
+        return None;
+    }
+    read_head(cwd)
+}
+
+fn read_head(cwd: &Path) -> Option<String> {
+    let git = cwd.join(".git");
+    // A worktree's `.git` is a file pointing at its git folder
+    let git_dir = match std::fs::read_to_string(&git) {
+        Ok(pointer) => cwd.join(pointer.strip_prefix("gitdir:")?.trim()),
+        Err(_) => git,
+    };
+    let head = std::fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    // A detached HEAD is a commit, not a branch
+    let branch = head.trim().strip_prefix("ref: refs/heads/")?;
+    (!branch.is_empty()).then(|| branch.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_head() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let repo = temp.path().join("repo");
+        std::fs::create_dir_all(repo.join(".git")).unwrap();
+        std::fs::write(repo.join(".git/HEAD"), "ref: refs/heads/feature/upload\n").unwrap();
+        assert_eq!(read_head(&repo).as_deref(), Some("feature/upload"));
+
+        let worktree = temp.path().join("worktree");
+        let git_dir = repo.join(".git/worktrees/worktree");
+        std::fs::create_dir_all(&git_dir).unwrap();
+        std::fs::create_dir_all(&worktree).unwrap();
+        std::fs::write(worktree.join(".git"), format!("gitdir: {}\n", git_dir.display())).unwrap();
+        std::fs::write(git_dir.join("HEAD"), "ref: refs/heads/hotfix\n").unwrap();
+        assert_eq!(read_head(&worktree).as_deref(), Some("hotfix"));
+
+        std::fs::write(repo.join(".git/HEAD"), "3f2a9c1d\n").unwrap();
+        assert_eq!(read_head(&repo), None);
+        assert_eq!(read_head(temp.path()), None);
+    }
+}
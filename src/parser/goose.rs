@@ -0,0 +1,159 @@
+//! Goose sessions: `~/.local/share/goose/sessions/<name>.jsonl`, a metadata line
+//! (`working_dir`, `description`) followed by one message per line, each with a `role` and
+//! an array of `content` blocks.
+
+use crate::config;
+use crate::session::{Message, Role, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::DateTime;
+use serde::Deserialize;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::{join_consecutive_messages, timeline::Timeline, SessionParser};
+
+/// The first line
+#[derive(Debug, Deserialize)]
+struct GooseMetadata {
+    working_dir: Option<String>,
+    #[allow(dead_code)]
+    description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GooseMessage {
+    role: String,
+    /// Unix seconds
+    created: Option<i64>,
+    #[serde(default)]
+    content: serde_json::Value,
+}
+
+pub struct GooseParser;
+
+impl SessionParser for GooseParser {
+    fn can_parse(path: &Path) -> bool {
+        path.to_str()
+            .map(|s| s.contains("goose/sessions") || s.contains("goose\\sessions"))
+            .unwrap_or(false)
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        let file = File::open(path).context("Failed to open file")?;
+        let reader = BufReader::with_capacity(64 * 1024, file);
+
+        let mut cwd: Option<String> = None;
+        let mut timeline = Timeline::default();
+        let mut messages: Vec<Message> = Vec::new();
+
+        for (i, line) in reader.lines().enumerate() {
+            let line = line.context("Failed to read line")?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if i == 0 {
+                if let Ok(metadata) = serde_json::from_str::<GooseMetadata>(&line) {
+                    cwd = metadata.working_dir;
+                    continue;
+                }
+            }
+
+            let entry: GooseMessage = match serde_json::from_str(&line) {
+                Ok(e) => e,
+                Err(_) => continue, // Skip malformed lines
+            };
+            timeline.observe(entry.created.and_then(|secs| DateTime::from_timestamp(secs, 0)));
+
+            let role = match entry.role.as_str() {
+                "user" => Role::User,
+                "assistant" => Role::Assistant,
+                _ => continue,
+            };
+
+            // Tool responses come back as user messages with no text, and are skipped
+            let content = extract_content(&entry.content);
+            if !content.is_empty() {
+                let timestamp = timeline.stamp(&messages);
+                messages.push(Message { role, content, timestamp, tool_calls: Vec::new() });
+            }
+        }
+
+        // Sessions are resumed by name, which is the file's
+        let session_id = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let timestamp = timeline.finish(&mut messages, path);
+
+        Ok(Session {
+            id: session_id,
+            source: SessionSource::Goose,
+            file_path: path.to_path_buf(),
+            cwd: cwd.unwrap_or_else(|| ".".to_string()),
+            git_branch: None,
+            timestamp,
+            messages: join_consecutive_messages(messages, config::get().join_messages),
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: None,
+        })
+    }
+}
+
+/// Extract text content from a Goose message's content blocks.
+/// Only `text` blocks are kept; `toolRequest` and `toolResponse` blocks are skipped.
+fn extract_content(content: &serde_json::Value) -> String {
+    let serde_json::Value::Array(arr) = content else {
+        return String::new();
+    };
+
+    let mut texts = Vec::new();
+    for item in arr {
+        if item.get("type").and_then(|v| v.as_str()) == Some("text") {
+            if let Some(text) = item.get("text").and_then(|v| v.as_str()) {
+                if !text.trim().is_empty() {
+                    texts.push(text.to_string());
+                }
+            }
+        }
+    }
+    texts.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION: &str = r#"{"working_dir":"/w/cli","description":"Fix flaky test","message_count":4}
+{"id":"m1","role":"user","created":1748772000,"content":[{"type":"text","text":"why is test_sync flaky?"}]}
+{"id":"m2","role":"assistant","created":1748772010,"content":[{"type":"text","text":"Let me run it."},{"type":"toolRequest","id":"t1","toolCall":{"status":"success","value":{"name":"developer__shell","arguments":{"command":"cargo test test_sync"}}}}]}
+{"id":"m3","role":"user","created":1748772020,"content":[{"type":"toolResponse","id":"t1","toolResult":{"status":"success","value":[{"type":"text","text":"ok"}]}}]}
+{"id":"m4","role":"assistant","created":1748772030,"content":[{"type":"text","text":"It races the file watcher."}]}
+"#;
+
+    #[test]
+    fn test_parse_session() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let dir = temp.path().join(".local/share/goose/sessions");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("20250601_1.jsonl");
+        std::fs::write(&path, SESSION).unwrap();
+        assert!(GooseParser::can_parse(&path));
+
+        let session = GooseParser::parse_file(&path).unwrap();
+        assert_eq!(session.id, "20250601_1");
+        assert_eq!(session.source, SessionSource::Goose);
+        assert_eq!(session.cwd, "/w/cli");
+        // The tool response between the replies isn't a message, so they're joined
+        assert_eq!(session.messages.len(), 2);
+        assert_eq!(session.messages[0].content, "why is test_sync flaky?");
+        assert_eq!(session.messages[1].role, Role::Assistant);
+        assert!(session.messages[1].content.starts_with("Let me run it."));
+        assert!(session.messages[1].content.ends_with("It races the file watcher."));
+        assert_eq!(session.timestamp, DateTime::from_timestamp(1748772030, 0).unwrap());
+    }
+}
@@ -0,0 +1,101 @@
+use crate::session::{ReadOutput, Session};
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use super::SessionParser;
+
+/// Directory holding sessions brought in by `recall import`
+/// (~/.local/share/recall/imported/<source>/<id>.json)
+pub fn imported_dir() -> PathBuf {
+    std::env::var("RECALL_HOME_OVERRIDE")
+        .map(|h| PathBuf::from(h).join(".local/share/recall/imported"))
+        .unwrap_or_else(|_| {
+            dirs::data_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("recall")
+                .join("imported")
+        })
+}
+
+/// Imported sessions directories of the extra homes from the config
+pub fn extra_imported_dirs() -> Vec<PathBuf> {
+    crate::config::get()
+        .extra_homes()
+        .into_iter()
+        .map(|home| home.join(".local/share/recall/imported"))
+        .collect()
+}
+
+/// Parser for imported sessions, stored in the `recall read` JSON format
+pub struct ImportedParser;
+
+impl SessionParser for ImportedParser {
+    fn can_parse(path: &Path) -> bool {
+        std::iter::once(imported_dir())
+            .chain(extra_imported_dirs())
+            .any(|dir| path.starts_with(dir))
+            && path.extension().map(|e| e == "json").unwrap_or(false)
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        let file = File::open(path).context("Failed to open imported session")?;
+        let output: ReadOutput = serde_json::from_reader(BufReader::new(file))
+            .context("Failed to parse imported session JSON")?;
+        Ok(output.into_session(path.to_path_buf()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{Message, Role, SessionSource};
+    use chrono::Utc;
+    use std::io::Write;
+
+    #[test]
+    fn test_imported_round_trip() {
+        let now = Utc::now();
+        let session = Session {
+            id: "round-trip-1".to_string(),
+            source: SessionSource::CodexCli,
+            file_path: PathBuf::from("/original/path.jsonl"),
+            cwd: "/test/project".to_string(),
+            git_branch: Some("main".to_string()),
+            timestamp: now,
+            messages: vec![
+                Message {
+                    role: Role::User,
+                    content: "Hello".to_string(),
+                    timestamp: now,
+                    tool_calls: Vec::new(),
+                },
+                Message {
+                    role: Role::Assistant,
+                    content: "Hi".to_string(),
+                    timestamp: now,
+                    tool_calls: Vec::new(),
+                },
+            ],
+            files_touched: vec![],
+            usage: vec![],
+            title: None,
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("round-trip-1.json");
+        let mut file = File::create(&path).unwrap();
+        write!(file, "{}", serde_json::to_string(&session.to_read_output()).unwrap()).unwrap();
+
+        let parsed = ImportedParser::parse_file(&path).unwrap();
+        assert_eq!(parsed.id, session.id);
+        assert_eq!(parsed.source, session.source);
+        assert_eq!(parsed.cwd, session.cwd);
+        assert_eq!(parsed.git_branch, session.git_branch);
+        assert_eq!(parsed.timestamp, session.timestamp);
+        assert_eq!(parsed.messages.len(), 2);
+        assert_eq!(parsed.messages[1].content, "Hi");
+        assert_eq!(parsed.file_path, path);
+    }
+}
@@ -0,0 +1,362 @@
+//! Just enough of LevelDB to read every live key out of a store some other program keeps
+//! its state in (Chromium's Local Storage, in Claude Desktop). It reads the write-ahead logs
+//! and the table files, Snappy-compressed blocks included, and keeps each key's newest value;
+//! the manifest is skipped, since the newest sequence number wins either way. Checksums
+//! aren't verified: a log's torn tail is simply where reading stops.
+
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Logs are written in blocks of this size, which records never straddle the header of
+const LOG_BLOCK_SIZE: usize = 32768;
+const LOG_HEADER: usize = 7;
+
+/// Log record types
+const FULL: u8 = 1;
+const FIRST: u8 = 2;
+const MIDDLE: u8 = 3;
+const LAST: u8 = 4;
+
+/// The magic number at the end of a table file
+const TABLE_MAGIC: u64 = 0xdb4775248b80fb57;
+const FOOTER_SIZE: usize = 48;
+
+/// Block compression types
+const NO_COMPRESSION: u8 = 0;
+const SNAPPY: u8 = 1;
+
+/// A store's live keys and their values
+pub type Store = HashMap<Vec<u8>, Vec<u8>>;
+
+/// The store read last, while its files are unchanged
+struct ReadStore {
+    dir: PathBuf,
+    files: Vec<(PathBuf, u64, SystemTime)>,
+    store: Arc<Store>,
+}
+
+/// Stores kept in many sessions are parsed one session after another from the same read
+static LAST_READ: Mutex<Option<ReadStore>> = Mutex::new(None);
+
+/// The store in `dir`, reusing the last read while none of its files have changed
+pub fn read_store(dir: &Path) -> Result<Arc<Store>> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).context("Failed to read store")? {
+        let path = entry?.path();
+        let metadata = std::fs::metadata(&path)?;
+        files.push((path, metadata.len(), metadata.modified()?));
+    }
+    files.sort();
+
+    let mut last = LAST_READ.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(read) = last.as_ref().filter(|read| read.dir == dir && read.files == files) {
+        return Ok(Arc::clone(&read.store));
+    }
+
+    // Each key's newest entry: its sequence number, and its value (None once deleted)
+    let mut entries: HashMap<Vec<u8>, (u64, Option<Vec<u8>>)> = HashMap::new();
+    let mut add = |key: Vec<u8>, sequence: u64, value: Option<Vec<u8>>| {
+        if entries.get(&key).is_none_or(|(newest, _)| sequence >= *newest) {
+            entries.insert(key, (sequence, value));
+        }
+    };
+    for (path, _, _) in &files {
+        let extension = path.extension().and_then(|e| e.to_str());
+        if !matches!(extension, Some("log" | "ldb" | "sst")) {
+            continue;
+        }
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        // Chromium's own logs (LOG, LOG.old) aren't part of the data
+        if !name.starts_with(|c: char| c.is_ascii_digit()) {
+            continue;
+        }
+        let data = std::fs::read(path).with_context(|| format!("Failed to read {}", name))?;
+        if extension == Some("log") {
+            for batch in log_records(&data) {
+                for_each_in_batch(&batch, &mut add)?;
+            }
+        } else {
+            for (key, value) in table_entries(&data).with_context(|| format!("In {}", name))? {
+                let Some(split) = key.len().checked_sub(8) else {
+                    continue;
+                };
+                let trailer = u64::from_le_bytes(key[split..].try_into()?);
+                let value = (trailer & 0xff == 1).then_some(value);
+                add(key[..split].to_vec(), trailer >> 8, value);
+            }
+        }
+    }
+
+    let store: Arc<Store> = Arc::new(
+        entries
+            .into_iter()
+            .filter_map(|(key, (_, value))| Some((key, value?)))
+            .collect(),
+    );
+    *last = Some(ReadStore { dir: dir.to_path_buf(), files, store: Arc::clone(&store) });
+    Ok(store)
+}
+
+/// The records in a log: write batches, put back together from their fragments
+fn log_records(data: &[u8]) -> Vec<Vec<u8>> {
+    let mut records = Vec::new();
+    let mut record: Option<Vec<u8>> = None;
+    let mut at = 0;
+    while at + LOG_HEADER <= data.len() {
+        let left_in_block = LOG_BLOCK_SIZE - at % LOG_BLOCK_SIZE;
+        if left_in_block < LOG_HEADER {
+            at += left_in_block;
+            continue;
+        }
+        let length = u16::from_le_bytes([data[at + 4], data[at + 5]]) as usize;
+        let kind = data[at + 6];
+        let Some(fragment) = data.get(at + LOG_HEADER..at + LOG_HEADER + length) else {
+            break;
+        };
+        match kind {
+            FULL => records.push(fragment.to_vec()),
+            FIRST => record = Some(fragment.to_vec()),
+            MIDDLE => {
+                if let Some(record) = &mut record {
+                    record.extend_from_slice(fragment);
+                }
+            }
+            LAST => {
+                if let Some(mut record) = record.take() {
+                    record.extend_from_slice(fragment);
+                    records.push(record);
+                }
+            }
+            // Zeroes where the file was preallocated
+            _ => {
+                at += left_in_block;
+                continue;
+            }
+        }
+        at += LOG_HEADER + length;
+    }
+    records
+}
+
+/// Every put (with its value) and delete (None) in a write batch
+fn for_each_in_batch(
+    batch: &[u8],
+    add: &mut impl FnMut(Vec<u8>, u64, Option<Vec<u8>>),
+) -> Result<()> {
+    let sequence = u64::from_le_bytes(slice(batch, 0, 8)?.try_into()?);
+    let count = u32::from_le_bytes(slice(batch, 8, 4)?.try_into()?) as u64;
+    let mut at = 12;
+    for i in 0..count {
+        let kind = *batch.get(at).context("Truncated batch")?;
+        let key = read_prefixed(batch, &mut at, 1)?;
+        let value = match kind {
+            1 => Some(read_prefixed(batch, &mut at, 0)?),
+            0 => None,
+            _ => bail!("Unknown batch entry type {}", kind),
+        };
+        add(key, sequence + i, value);
+    }
+    Ok(())
+}
+
+/// A varint-length-prefixed slice `skip` bytes past `at`, moving `at` past it
+fn read_prefixed(data: &[u8], at: &mut usize, skip: usize) -> Result<Vec<u8>> {
+    let (len, n) = read_varint(data, *at + skip)?;
+    let start = *at + skip + n;
+    let bytes = slice(data, start, len as usize)?.to_vec();
+    *at = start + len as usize;
+    Ok(bytes)
+}
+
+/// The internal keys and values in a table file
+fn table_entries(data: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+    let footer_start = data.len().checked_sub(FOOTER_SIZE).context("Not a table file")?;
+    let magic = u64::from_le_bytes(data[data.len() - 8..].try_into()?);
+    if magic != TABLE_MAGIC {
+        bail!("Not a table file");
+    }
+    // The metaindex block's handle, then the index block's
+    let mut at = footer_start;
+    read_handle(data, &mut at)?;
+    let index = read_block(data, read_handle(data, &mut at)?)?;
+
+    let mut entries = Vec::new();
+    for (_, handle) in block_entries(&index)? {
+        let block = read_block(data, read_handle(&handle, &mut 0)?)?;
+        entries.extend(block_entries(&block)?);
+    }
+    Ok(entries)
+}
+
+/// A block handle: its offset and size
+fn read_handle(data: &[u8], at: &mut usize) -> Result<(usize, usize)> {
+    let (offset, n) = read_varint(data, *at)?;
+    let (size, m) = read_varint(data, *at + n)?;
+    *at += n + m;
+    Ok((offset as usize, size as usize))
+}
+
+/// A block's contents, uncompressed
+fn read_block(data: &[u8], (offset, size): (usize, usize)) -> Result<Vec<u8>> {
+    let contents = slice(data, offset, size)?;
+    match *data.get(offset + size).context("Block has no trailer")? {
+        NO_COMPRESSION => Ok(contents.to_vec()),
+        SNAPPY => snappy_decompress(contents),
+        kind => bail!("Unknown block compression {}", kind),
+    }
+}
+
+/// A block's keys (each sharing a prefix with the one before) and values
+fn block_entries(block: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+    let restarts = u32::from_le_bytes(slice(block, block.len().saturating_sub(4), 4)?.try_into()?);
+    let end = (block.len() - 4)
+        .checked_sub(4 * restarts as usize)
+        .context("Corrupt block")?;
+    let mut entries: Vec<(Vec<u8>, Vec<u8>)> = Vec::new();
+    let mut at = 0;
+    while at < end {
+        let (shared, n) = read_varint(block, at)?;
+        let (unshared, m) = read_varint(block, at + n)?;
+        let (value_len, o) = read_varint(block, at + n + m)?;
+        at += n + m + o;
+        let previous = entries.last().map(|(key, _)| key.as_slice()).unwrap_or_default();
+        let mut key = slice(previous, 0, shared as usize)?.to_vec();
+        key.extend_from_slice(slice(block, at, unshared as usize)?);
+        at += unshared as usize;
+        let value = slice(block, at, value_len as usize)?.to_vec();
+        at += value_len as usize;
+        entries.push((key, value));
+    }
+    Ok(entries)
+}
+
+/// Undo Snappy's raw format: the uncompressed length, then literals and back-references
+fn snappy_decompress(data: &[u8]) -> Result<Vec<u8>> {
+    let (len, mut at) = read_varint(data, 0)?;
+    let mut out: Vec<u8> = Vec::with_capacity(len as usize);
+    while at < data.len() {
+        let tag = data[at];
+        at += 1;
+        let (length, offset) = match tag & 3 {
+            0 => {
+                let mut length = (tag >> 2) as usize + 1;
+                if length > 60 {
+                    // The length is in the next 1-4 bytes
+                    let bytes = length - 60;
+                    length = slice(data, at, bytes)?
+                        .iter()
+                        .rev()
+                        .fold(0, |acc, b| (acc << 8) | *b as usize)
+                        + 1;
+                    at += bytes;
+                }
+                out.extend_from_slice(slice(data, at, length)?);
+                at += length;
+                continue;
+            }
+            1 => {
+                let low = *data.get(at).context("Truncated copy")? as usize;
+                at += 1;
+                (((tag >> 2) & 7) as usize + 4, ((tag as usize >> 5) << 8) | low)
+            }
+            2 => {
+                let offset = u16::from_le_bytes(slice(data, at, 2)?.try_into()?) as usize;
+                at += 2;
+                ((tag >> 2) as usize + 1, offset)
+            }
+            _ => {
+                let offset = u32::from_le_bytes(slice(data, at, 4)?.try_into()?) as usize;
+                at += 4;
+                ((tag >> 2) as usize + 1, offset)
+            }
+        };
+        let start = out.len().checked_sub(offset).filter(|_| offset > 0);
+        let start = start.context("Copy from before the start")?;
+        // The copy can overlap what it's writing
+        for i in 0..length {
+            out.push(out[start + i]);
+        }
+    }
+    if out.len() != len as usize {
+        bail!("Decompressed {} bytes, expected {}", out.len(), len);
+    }
+    Ok(out)
+}
+
+fn slice(data: &[u8], start: usize, len: usize) -> Result<&[u8]> {
+    data.get(start..start + len).context("Read past the end of a block")
+}
+
+/// A LevelDB varint and its length: little-endian 7-bit groups
+fn read_varint(data: &[u8], at: usize) -> Result<(u64, usize)> {
+    let mut value = 0u64;
+    for i in 0..10 {
+        let byte = *data.get(at + i).context("Truncated varint")?;
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    bail!("Varint is too long")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varint() {
+        assert_eq!(read_varint(&[0x05], 0).unwrap(), (5, 1));
+        assert_eq!(read_varint(&[0x80, 0x01], 0).unwrap(), (128, 2));
+        assert!(read_varint(&[0x80], 0).is_err());
+    }
+
+    #[test]
+    fn test_snappy_decompress() {
+        // "abcd" as a literal, then an overlapping copy of 8 from 4 back, then "!"
+        let data = [13, 3 << 2, b'a', b'b', b'c', b'd', 1 | (4 << 2), 4, 0, b'!'];
+        assert_eq!(snappy_decompress(&data).unwrap(), b"abcdabcdabcd!");
+        assert!(snappy_decompress(&[5, 1 | (1 << 2), 9]).is_err());
+    }
+
+    #[test]
+    fn test_log_records() {
+        let batch = |sequence: u64, key: &[u8], value: Option<&[u8]>| {
+            let mut batch = sequence.to_le_bytes().to_vec();
+            batch.extend(1u32.to_le_bytes());
+            batch.push(value.is_some() as u8);
+            batch.push(key.len() as u8);
+            batch.extend(key);
+            if let Some(value) = value {
+                batch.push(value.len() as u8);
+                batch.extend(value);
+            }
+            batch
+        };
+        let mut log = Vec::new();
+        for record in [batch(1, b"k", Some(b"old")), batch(2, b"k", Some(b"new"))] {
+            log.extend([0, 0, 0, 0]);
+            log.extend((record.len() as u16).to_le_bytes());
+            log.push(FULL);
+            log.extend(record);
+        }
+        // A record cut off mid-write
+        log.extend([0, 0, 0, 0, 50, 0, FULL, 1, 2]);
+
+        let mut store: Store = HashMap::new();
+        let mut newest = HashMap::new();
+        for record in log_records(&log) {
+            for_each_in_batch(&record, &mut |key, sequence, value| {
+                if newest.get(&key).is_none_or(|s| sequence > *s) {
+                    newest.insert(key.clone(), sequence);
+                    store.insert(key, value.unwrap_or_default());
+                }
+            })
+            .unwrap();
+        }
+        assert_eq!(store.get(b"k".as_slice()).map(Vec::as_slice), Some(b"new".as_slice()));
+    }
+}
@@ -0,0 +1,151 @@
+//! LLM, Simon Willison's command-line tool, logs every prompt and response to `logs.db`, a
+//! SQLite database in its user folder (where `click` keeps app folders, so under the
+//! platform's config folder on Linux). Each row of `responses` is a prompt and its response,
+//! tied to a row of `conversations` by `conversation_id`. Each conversation is a session,
+//! addressed as `logs.db/<conversation id>` (see [`super::entry_path`]).
+
+use crate::config;
+use crate::session::{Message, Role, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use super::sqlite::{self, Table};
+use super::{join_consecutive_messages, timeline::Timeline, SessionParser};
+
+/// LLM's user folder, under the home
+#[cfg(target_os = "macos")]
+pub const DATA_DIR: &str = "Library/Application Support/io.datasette.llm";
+#[cfg(windows)]
+pub const DATA_DIR: &str = "AppData/Roaming/io.datasette.llm";
+#[cfg(not(any(target_os = "macos", windows)))]
+pub const DATA_DIR: &str = ".config/io.datasette.llm";
+
+/// The database in the user folder
+pub const DATABASE_FILE: &str = "logs.db";
+
+const TABLE: &str = "responses";
+
+pub struct LlmParser;
+
+impl SessionParser for LlmParser {
+    fn can_parse(path: &Path) -> bool {
+        path.parent().is_some_and(|database| {
+            database.file_name().is_some_and(|name| name == DATABASE_FILE)
+                && database.parent().is_some_and(|dir| dir.ends_with("io.datasette.llm"))
+        })
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        let (database, conversation_id) =
+            super::split_entry(path).context("Not a conversation in a database")?;
+        let table = sqlite::read_table(database, TABLE)?;
+        let mut rows: Vec<_> = table
+            .rows
+            .iter()
+            .filter(|row| text(&table, row, "conversation_id") == Some(&conversation_id))
+            .collect();
+        anyhow::ensure!(!rows.is_empty(), "Conversation is no longer in the database");
+        // Response ids are ULIDs in newer versions, so they break ties in time
+        rows.sort_by_key(|row| (text(&table, row, "datetime_utc"), text(&table, row, "id")));
+
+        let mut timeline = Timeline::default();
+        let mut messages: Vec<Message> = Vec::new();
+        for row in rows {
+            timeline.observe(text(&table, row, "datetime_utc").and_then(parse_time));
+            for (role, column) in [(Role::User, "prompt"), (Role::Assistant, "response")] {
+                let content = text(&table, row, column).unwrap_or_default().trim();
+                if !content.is_empty() {
+                    let timestamp = timeline.stamp(&messages);
+                    messages.push(Message {
+                        role,
+                        content: content.to_string(),
+                        timestamp,
+                        tool_calls: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        let timestamp = timeline.finish(&mut messages, database);
+
+        Ok(Session {
+            id: conversation_id,
+            source: SessionSource::Llm,
+            file_path: path.to_path_buf(),
+            // Prompts aren't tied to a folder
+            cwd: ".".to_string(),
+            git_branch: None,
+            timestamp,
+            messages: join_consecutive_messages(messages, config::get().join_messages),
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: None,
+        })
+    }
+}
+
+/// A session for each conversation in the database in `dir`. A database that can't be read
+/// right now yields none this time.
+pub(super) fn discover(dir: &Path) -> Vec<PathBuf> {
+    let database = dir.join(DATABASE_FILE);
+    let Ok(table) = sqlite::read_table(&database, TABLE) else {
+        return Vec::new();
+    };
+    let conversations: BTreeSet<&str> = table
+        .rows
+        .iter()
+        .filter_map(|row| text(&table, row, "conversation_id"))
+        .collect();
+    conversations
+        .into_iter()
+        .map(|id| super::entry_path(&database, id))
+        .collect()
+}
+
+/// Whether the conversation at `path` is the one `llm -c` continues: the one with the latest
+/// response in its database
+pub fn is_latest_llm_conversation(path: &Path) -> bool {
+    let Some((database, conversation_id)) = super::split_entry(path) else {
+        return false;
+    };
+    let Ok(table) = sqlite::read_table(database, TABLE) else {
+        return false;
+    };
+    let latest = table
+        .rows
+        .iter()
+        .max_by_key(|row| (text(&table, row, "datetime_utc"), text(&table, row, "id")));
+    latest.and_then(|row| text(&table, row, "conversation_id")) == Some(&conversation_id)
+}
+
+fn text<'a>(table: &Table, row: &'a sqlite::Row, column: &str) -> Option<&'a str> {
+    table.get(row, column)?.as_text()
+}
+
+/// `datetime_utc`: ISO 8601 in UTC, without an offset
+fn parse_time(text: &str) -> Option<DateTime<Utc>> {
+    let naive = NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+    Some(naive.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time() {
+        let time = parse_time("2025-06-01T09:00:00.250000").unwrap();
+        assert_eq!(time.to_rfc3339(), "2025-06-01T09:00:00.250+00:00");
+        assert_eq!(parse_time("yesterday"), None);
+    }
+
+    #[test]
+    fn test_can_parse() {
+        let database = Path::new("/h/.config/io.datasette.llm/logs.db");
+        assert!(LlmParser::can_parse(&database.join("01j0abc")));
+        assert!(!LlmParser::can_parse(database));
+        assert!(!LlmParser::can_parse(Path::new("/h/other/logs.db/01j0abc")));
+    }
+}
@@ -1,26 +1,82 @@
+mod aider;
+mod amazon_q;
+mod amp;
+mod chatgpt;
 mod claude;
+mod claude_desktop;
+mod cline;
 mod codex;
+mod continue_dev;
+mod copilot;
+mod crush;
+mod cursor;
+mod exclude;
 mod factory;
+mod files;
+mod gemini;
+mod generic;
+mod git;
+mod goose;
+mod imported;
+mod leveldb;
+mod llm;
 mod opencode;
+mod openhands;
+mod plandex;
+mod roo_code;
+mod sqlite;
+mod timeline;
+mod tools;
+mod warp;
+mod windsurf;
+mod zed;
 
-pub use claude::ClaudeParser;
+pub use aider::AiderParser;
+pub use amazon_q::AmazonQParser;
+pub use amp::AmpParser;
+pub use chatgpt::read_chatgpt_export;
+pub use claude::{is_subagent_transcript, ClaudeParser};
+pub use claude_desktop::ClaudeDesktopParser;
+pub use cline::ClineParser;
 pub use codex::CodexParser;
+pub use continue_dev::ContinueParser;
+pub use copilot::CopilotParser;
+pub use crush::CrushParser;
+pub use cursor::CursorParser;
+pub use exclude::{is_excluded, is_excluded_by_project};
 pub use factory::FactoryParser;
+pub use gemini::GeminiParser;
+pub use generic::GenericParser;
+pub use goose::GooseParser;
+pub use imported::{extra_imported_dirs, imported_dir, ImportedParser};
+pub use llm::{is_latest_llm_conversation, LlmParser};
 pub use opencode::OpenCodeParser;
+pub use openhands::OpenHandsParser;
+pub use plandex::PlandexParser;
+pub use roo_code::RooCodeParser;
+pub(crate) use sqlite::wal_path;
+pub use warp::WarpParser;
+pub use windsurf::WindsurfParser;
+pub use zed::ZedParser;
 
-use crate::session::{Message, Session};
+use crate::config::MessageJoin;
+use crate::session::{Message, Session, SessionSource};
 use anyhow::Result;
-use std::path::Path;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
 
-/// Join consecutive messages from the same role into single messages.
-/// Uses the latest timestamp when joining.
-pub fn join_consecutive_messages(messages: Vec<Message>) -> Vec<Message> {
+/// Join consecutive messages from the same role into single messages, as far as `join`
+/// allows. Uses the latest timestamp when joining, and keeps the tool calls of both.
+pub fn join_consecutive_messages(messages: Vec<Message>, join: MessageJoin) -> Vec<Message> {
     messages.into_iter().fold(Vec::new(), |mut acc, msg| {
         if let Some(last) = acc.last_mut() {
-            if last.role == msg.role {
+            if joins(last, &msg, join) {
                 last.content.push_str("\n\n");
                 last.content.push_str(&msg.content);
                 last.timestamp = msg.timestamp; // use latest
+                last.tool_calls.extend(msg.tool_calls);
                 return acc;
             }
         }
@@ -29,6 +85,31 @@ pub fn join_consecutive_messages(messages: Vec<Message>) -> Vec<Message> {
     })
 }
 
+/// Whether `next` is joined onto `last`, the message before it, as far as `join` allows
+fn joins(last: &Message, next: &Message, join: MessageJoin) -> bool {
+    let close_enough = match join {
+        MessageJoin::Off => false,
+        MessageJoin::Always => true,
+        MessageJoin::Gap(minutes) => {
+            next.timestamp - last.timestamp <= chrono::Duration::minutes(minutes.into())
+        }
+    };
+    last.role == next.role && close_enough
+}
+
+/// The index each of `messages` will have once [`join_consecutive_messages`] joins them
+pub(crate) fn joined_indices(messages: &[Message], join: MessageJoin) -> Vec<usize> {
+    let mut index = 0;
+    let mut indices = Vec::with_capacity(messages.len());
+    for (i, message) in messages.iter().enumerate() {
+        if i > 0 && !joins(&messages[i - 1], message, join) {
+            index += 1;
+        }
+        indices.push(index);
+    }
+    indices
+}
+
 /// Trait for parsing session files
 pub trait SessionParser {
     /// Parse a session file into a Session
@@ -38,121 +119,508 @@ pub trait SessionParser {
     fn can_parse(path: &Path) -> bool;
 }
 
-/// Discover all session files from Claude Code, Codex CLI, and Factory
-pub fn discover_session_files() -> Vec<std::path::PathBuf> {
-    let mut files = Vec::new();
+/// A directory recall scans for session files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveryRoot {
+    /// Source whose files live here (None for the imported sessions directory)
+    pub source: Option<SessionSource>,
+    pub dir: PathBuf,
+    /// The extra home this root belongs to (None for the user's own home)
+    pub origin: Option<PathBuf>,
+}
 
-    // Allow override for testing
-    let home = std::env::var("RECALL_HOME_OVERRIDE")
-        .map(std::path::PathBuf::from)
-        .ok()
-        .or_else(dirs::home_dir);
-
-    if let Some(home) = home {
-        // Claude Code: ~/.claude/projects/*/*.jsonl
-        let claude_dir = home.join(".claude/projects");
-        if claude_dir.exists() {
-            if let Ok(projects) = std::fs::read_dir(&claude_dir) {
-                for project in projects.flatten() {
-                    if let Ok(sessions) = std::fs::read_dir(project.path()) {
-                        for session in sessions.flatten() {
-                            let path = session.path();
-                            if path.extension().map(|e| e == "jsonl").unwrap_or(false) {
-                                // Skip agent sidechain files (internal subagent conversations)
-                                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                                    if name.starts_with("agent-") {
+impl DiscoveryRoot {
+    /// Name shown to users: the source name, or "imported"
+    pub fn name(&self) -> &'static str {
+        self.source.map(|s| s.as_str()).unwrap_or("imported")
+    }
+
+    /// Whether this root's source is enabled in the config (the imported root always is)
+    pub fn enabled(&self) -> bool {
+        self.source.is_none_or(|s| crate::config::get().sources.enabled(s))
+    }
+
+    /// Whether `path` is a session file from this root (Aider's and Crush's are in project
+    /// folders all over its home, and Roo Code's in the editors' settings folder, which they
+    /// share with the other sources)
+    pub fn contains(&self, path: &Path) -> bool {
+        match self.source {
+            Some(SessionSource::Aider) => aider::is_history(path) && path.starts_with(&self.dir),
+            Some(SessionSource::Crush) => crush::is_session(path) && path.starts_with(&self.dir),
+            Some(SessionSource::RooCode) => roo_code::is_task(path) && path.starts_with(&self.dir),
+            _ => path.starts_with(&self.dir),
+        }
+    }
+
+    /// Find the session files under this root (none if its source is disabled)
+    pub fn discover_files(&self) -> Vec<PathBuf> {
+        self.discover_files_in(&DiscoveryPass::default())
+    }
+
+    /// Find the session files under this root, sharing the project folders found for its
+    /// home with the other roots discovered in the same `pass`
+    pub fn discover_files_in(&self, pass: &DiscoveryPass) -> Vec<PathBuf> {
+        if !self.dir.exists() || !self.enabled() {
+            return Vec::new();
+        }
+
+        match self.source {
+            // Claude Code: ~/.claude/projects/*/*.jsonl
+            Some(SessionSource::ClaudeCode) => {
+                let mut files = Vec::new();
+                if let Ok(projects) = std::fs::read_dir(&self.dir) {
+                    for project in projects.flatten() {
+                        if let Ok(sessions) = std::fs::read_dir(project.path()) {
+                            for session in sessions.flatten() {
+                                let path = session.path();
+                                if path.extension().map(|e| e == "jsonl").unwrap_or(false) {
+                                    // Agent sidechain files (subagent conversations) are opt-in
+                                    if is_subagent_transcript(&path)
+                                        && !crate::config::get().include_subagents
+                                    {
                                         continue;
                                     }
+                                    files.push(path);
                                 }
-                                files.push(path);
                             }
                         }
                     }
                 }
+                files
+            }
+            // Codex CLI and Factory: **/*.jsonl
+            Some(SessionSource::CodexCli) | Some(SessionSource::Factory) => {
+                walk_files(&self.dir, "jsonl", |_| true)
             }
+            // OpenCode: **/ses_*.json (messages and parts live in sibling trees)
+            Some(SessionSource::OpenCode) => {
+                walk_files(&self.dir, "json", |name| name.starts_with("ses_"))
+            }
+            // Aider: <project>/.aider.chat.history.md, in projects found through other sources
+            Some(SessionSource::Aider) => aider::discover(self, pass),
+            // Gemini CLI: <project hash>/checkpoint-<tag>.json and chats/session-*.json
+            Some(SessionSource::Gemini) => walk_files(&self.dir, "json", |name| {
+                name.starts_with("checkpoint-") || name.starts_with("session-")
+            }),
+            // Goose: <name>.jsonl
+            Some(SessionSource::Goose) => walk_files(&self.dir, "jsonl", |_| true),
+            // Cline: <task id>/api_conversation_history.json
+            Some(SessionSource::Cline) => {
+                walk_files(&self.dir, "json", |name| name == cline::HISTORY_FILE)
+            }
+            // Copilot CLI: <session>.json
+            Some(SessionSource::Copilot) => walk_files(&self.dir, "json", |_| true),
+            // Amp: T-<id>.json
+            Some(SessionSource::Amp) => {
+                walk_files(&self.dir, "json", |name| name.starts_with("T-"))
+            }
+            // Cursor: <workspace hash>/state.vscdb
+            Some(SessionSource::Cursor) => {
+                walk_files(&self.dir, "vscdb", |name| name == cursor::DATABASE_FILE)
+            }
+            // Continue: <session id>.json, beside the sessions.json index
+            Some(SessionSource::Continue) => {
+                walk_files(&self.dir, "json", |name| name != continue_dev::INDEX_FILE)
+            }
+            // OpenHands: <session id>/events/, a folder of events read as one session
+            Some(SessionSource::OpenHands) => openhands::discover(&self.dir),
+            // Amazon Q: data.sqlite3/<directory>, a conversation per row of its database
+            Some(SessionSource::AmazonQ) => amazon_q::discover(&self.dir),
+            // Crush: <project>/.crush/crush.db/<session id>, in projects found like Aider's
+            Some(SessionSource::Crush) => crush::discover(self, pass),
+            // ChatGPT: nothing on disk; its chats are only imported, with the imported sessions
+            Some(SessionSource::ChatGpt) => Vec::new(),
+            // LLM: logs.db/<conversation id>, a conversation per conversation_id in its log
+            Some(SessionSource::Llm) => llm::discover(&self.dir),
+            // Zed: <summary> - <n>.zed.json
+            Some(SessionSource::Zed) => walk_files(&self.dir, "json", |_| true),
+            // Windsurf: <workspace hash>/cascade.json/<trajectory id>, a chat per trajectory
+            Some(SessionSource::Windsurf) => windsurf::discover(&self.dir),
+            // Claude Desktop: <newest log>/<conversation uuid>, from its Local Storage
+            Some(SessionSource::ClaudeDesktop) => claude_desktop::discover(&self.dir),
+            // Plandex: <project>/<plan>/conversation/, a folder of messages read as one session
+            Some(SessionSource::Plandex) => plandex::discover(&self.dir),
+            // Roo Code: <editor>/User/globalStorage/<extension>/tasks/<task id>/, like Cline's
+            Some(SessionSource::RooCode) => roo_code::discover(&self.dir),
+            // Warp: warp.sqlite/<conversation id>, a conversation per conversation_id asked in
+            Some(SessionSource::Warp) => warp::discover(&self.dir),
+            // Custom sources: whatever their glob matches
+            Some(SessionSource::Custom(name)) => generic::discover(self, name),
+            // Imported sessions: <source>/*.json
+            None => {
+                let disabled = crate::config::get().sources.disabled();
+                walk_files(&self.dir, "json", |_| true)
+                    .into_iter()
+                    .filter(|path| !imported_source(path).is_some_and(|s| disabled.contains(&s)))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// The source of an imported session file, from the directory it's filed under
+pub(crate) fn imported_source(path: &Path) -> Option<SessionSource> {
+    path.parent()
+        .and_then(|p| p.file_name())
+        .and_then(|n| n.to_str())
+        .and_then(SessionSource::parse)
+}
+
+/// Recursively collect files with the given extension whose names pass `keep`
+fn walk_files(dir: &Path, extension: &str, keep: impl Fn(&str) -> bool) -> Vec<PathBuf> {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .flatten()
+        .map(|entry| entry.into_path())
+        .filter(|path| path.extension().map(|e| e == extension).unwrap_or(false))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .map(&keep)
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+/// One discovery of session files over many roots. Aider's and Crush's roots both look in
+/// the project folders found through the other sources, so those are found once per home.
+#[derive(Debug, Default)]
+pub struct DiscoveryPass {
+    projects: RefCell<HashMap<HomeKey, BTreeSet<PathBuf>>>,
+}
+
+/// A root's folder and the extra home it belongs to
+type HomeKey = (PathBuf, Option<PathBuf>);
+
+impl DiscoveryPass {
+    /// The project folders of `root`'s home, found on first use in this pass
+    pub(super) fn project_folders(&self, root: &DiscoveryRoot) -> BTreeSet<PathBuf> {
+        let key = (root.dir.clone(), root.origin.clone());
+        if let Some(projects) = self.projects.borrow().get(&key) {
+            return projects.clone();
         }
+        let projects = project_folders(root);
+        self.projects.borrow_mut().insert(key, projects.clone());
+        projects
+    }
+}
 
-        // Codex CLI: ~/.codex/sessions/**/*.jsonl
-        let codex_dir = home.join(".codex/sessions");
-        if codex_dir.exists() {
-            for entry in walkdir::WalkDir::new(&codex_dir)
-                .into_iter()
-                .flatten()
-            {
-                let path = entry.path();
-                if path.extension().map(|e| e == "jsonl").unwrap_or(false) {
-                    files.push(path.to_path_buf());
-                }
-            }
+/// Folders sessions from the other sources under `root`'s home ran in, and the configured
+/// `extra_roots`: where to look for histories kept in the project (Aider's, Crush's)
+fn project_folders(root: &DiscoveryRoot) -> BTreeSet<PathBuf> {
+    let origin = root.origin.as_deref();
+    let mut projects = crate::config::get().extra_roots();
+    for other in source_roots(&root.dir, origin) {
+        let in_projects = matches!(
+            other.source,
+            Some(SessionSource::Aider | SessionSource::Crush | SessionSource::Custom(_))
+        );
+        if in_projects {
+            continue;
         }
+        for file in other.discover_files() {
+            projects.extend(exclude::probe_cwd(&file).map(PathBuf::from));
+        }
+    }
+    projects
+}
 
-        // Factory: ~/.factory/sessions/**/*.jsonl
-        let factory_dir = home.join(".factory/sessions");
-        if factory_dir.exists() {
-            for entry in walkdir::WalkDir::new(&factory_dir)
-                .into_iter()
-                .flatten()
-            {
-                let path = entry.path();
-                if path.extension().map(|e| e == "jsonl").unwrap_or(false) {
-                    files.push(path.to_path_buf());
-                }
-            }
+/// Sessions kept many to a database are addressed as `<database>/<key>`, as though the
+/// database were a folder of them. The key is escaped to stay one path component.
+pub(crate) fn entry_path(database: &Path, key: &str) -> PathBuf {
+    let mut escaped = String::with_capacity(key.len());
+    for c in key.chars() {
+        match c {
+            '%' | '/' | '\\' | ':' => escaped.push_str(&format!("%{:02X}", c as u32)),
+            c => escaped.push(c),
         }
+    }
+    database.join(escaped)
+}
 
-        // OpenCode: ~/.local/share/opencode/storage/session/**/*.json
-        let opencode_dir = home.join(".local/share/opencode/storage/session");
-        if opencode_dir.exists() {
-            for entry in walkdir::WalkDir::new(&opencode_dir)
-                .into_iter()
-                .flatten()
-            {
-                let path = entry.path();
-                if path.extension().map(|e| e == "json").unwrap_or(false) {
-                    // Only include session files (ses_*.json)
-                    if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                        if name.starts_with("ses_") {
-                            files.push(path.to_path_buf());
-                        }
-                    }
-                }
+/// The database and key of a session addressed by [`entry_path`]
+pub(crate) fn split_entry(path: &Path) -> Option<(&Path, String)> {
+    let database = path.parent().filter(|parent| parent.is_file())?;
+    let key = path.file_name()?.to_str()?;
+    Some((database, percent_decode(key)))
+}
+
+/// The file on disk a session is read from: its own, or the database it's kept in
+pub fn backing_file(path: &Path) -> &Path {
+    split_entry(path).map_or(path, |(database, _)| database)
+}
+
+/// Undo `%20`-style escapes, as in a URI path or an [`entry_path`] key
+pub(crate) fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match hex.filter(|_| bytes[i] == b'%').and_then(|h| u8::from_str_radix(h, 16).ok()) {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
             }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Directories scanned for sessions: one per source plus the imported sessions directory,
+/// for the user's home and then each extra home from the config, then the sources in each
+/// remote's mirror
+pub fn discovery_roots() -> Vec<DiscoveryRoot> {
+    let mut roots = Vec::new();
+
+    let home = default_home();
+
+    if let Some(home) = &home {
+        roots.extend(source_roots(home, None));
+    }
+    roots.push(DiscoveryRoot {
+        source: None,
+        dir: imported_dir(),
+        origin: None,
+    });
+
+    for extra in crate::config::get().extra_homes() {
+        if home.as_ref() == Some(&extra) {
+            continue;
         }
+        roots.extend(source_roots(&extra, Some(&extra)));
+        roots.push(DiscoveryRoot {
+            source: None,
+            dir: extra.join(".local/share/recall/imported"),
+            origin: Some(extra),
+        });
+    }
+
+    for mirror in crate::remote::mirror_homes() {
+        roots.extend(source_roots(&mirror, Some(&mirror)));
     }
 
-    files
+    roots
+}
+
+/// The user's home directory (RECALL_HOME_OVERRIDE in tests)
+pub fn default_home() -> Option<PathBuf> {
+    std::env::var("RECALL_HOME_OVERRIDE")
+        .map(PathBuf::from)
+        .ok()
+        .or_else(dirs::home_dir)
+}
+
+/// The per-source roots under `home`, custom sources last
+pub(crate) fn source_roots(home: &Path, origin: Option<&Path>) -> Vec<DiscoveryRoot> {
+    let mut roots: Vec<DiscoveryRoot> = [
+        (SessionSource::ClaudeCode, ".claude/projects"),
+        (SessionSource::CodexCli, ".codex/sessions"),
+        (SessionSource::Factory, ".factory/sessions"),
+        (SessionSource::OpenCode, ".local/share/opencode/storage/session"),
+        (SessionSource::Aider, ""),
+        (SessionSource::Gemini, ".gemini/tmp"),
+        (SessionSource::Goose, ".local/share/goose/sessions"),
+        (SessionSource::Cline, cline::TASKS_DIR),
+        (SessionSource::Copilot, ".copilot/history-session-state"),
+        (SessionSource::Amp, ".local/share/amp/threads"),
+        (SessionSource::Cursor, cursor::WORKSPACES_DIR),
+        (SessionSource::Continue, ".continue/sessions"),
+        (SessionSource::OpenHands, ".openhands/sessions"),
+        (SessionSource::AmazonQ, amazon_q::DATA_DIR),
+        (SessionSource::Crush, ""),
+        (SessionSource::Llm, llm::DATA_DIR),
+        (SessionSource::Zed, zed::CONVERSATIONS_DIR),
+        (SessionSource::Windsurf, windsurf::WORKSPACES_DIR),
+        (SessionSource::ClaudeDesktop, claude_desktop::STORE_DIR),
+        (SessionSource::Plandex, plandex::HOME_DIR),
+        (SessionSource::RooCode, roo_code::EDITORS_DIR),
+        (SessionSource::Warp, warp::DATA_DIR),
+    ]
+    .into_iter()
+    .map(|(source, dir)| DiscoveryRoot {
+        source: Some(source),
+        // Aider's and Crush's root is the home itself
+        dir: if dir.is_empty() { home.to_path_buf() } else { home.join(dir) },
+        origin: origin.map(Path::to_path_buf),
+    })
+    .collect();
+    roots.extend(generic::roots(home, origin));
+    roots
+}
+
+/// The extra home a session file came from, if it isn't from the user's own home
+pub fn origin_of(path: &Path) -> Option<PathBuf> {
+    crate::config::get()
+        .extra_homes()
+        .into_iter()
+        .filter(|home| path.starts_with(home))
+        // The most specific home, in case one is nested in another
+        .max_by_key(|home| home.components().count())
+}
+
+/// Discover all session files from every discovery root, minus the excluded ones
+pub fn discover_session_files() -> Vec<PathBuf> {
+    let pass = DiscoveryPass::default();
+    discovery_roots()
+        .iter()
+        .flat_map(|root| root.discover_files_in(&pass))
+        .filter(|path| !is_excluded(path))
+        .collect()
 }
 
-/// Parse a session file, auto-detecting the format
+/// Parse a session file, auto-detecting the format. Sessions from disabled sources are an error.
+/// Secrets are masked when `redact` is on.
 pub fn parse_session_file(path: &Path) -> Result<Session> {
-    if ClaudeParser::can_parse(path) {
-        ClaudeParser::parse_file(path)
+    // Checked first: an imported directory could sit under any of the other roots
+    let mut session = if ImportedParser::can_parse(path) {
+        ImportedParser::parse_file(path)?
+    } else if ClaudeParser::can_parse(path) {
+        ensure_enabled(SessionSource::ClaudeCode)?;
+        ClaudeParser::parse_file(path)?
     } else if CodexParser::can_parse(path) {
-        CodexParser::parse_file(path)
+        ensure_enabled(SessionSource::CodexCli)?;
+        CodexParser::parse_file(path)?
     } else if FactoryParser::can_parse(path) {
-        FactoryParser::parse_file(path)
+        ensure_enabled(SessionSource::Factory)?;
+        FactoryParser::parse_file(path)?
     } else if OpenCodeParser::can_parse(path) {
-        OpenCodeParser::parse_file(path)
+        ensure_enabled(SessionSource::OpenCode)?;
+        OpenCodeParser::parse_file(path)?
+    } else if AiderParser::can_parse(path) {
+        ensure_enabled(SessionSource::Aider)?;
+        AiderParser::parse_file(path)?
+    } else if GeminiParser::can_parse(path) {
+        ensure_enabled(SessionSource::Gemini)?;
+        GeminiParser::parse_file(path)?
+    } else if GooseParser::can_parse(path) {
+        ensure_enabled(SessionSource::Goose)?;
+        GooseParser::parse_file(path)?
+    } else if ClineParser::can_parse(path) {
+        ensure_enabled(SessionSource::Cline)?;
+        ClineParser::parse_file(path)?
+    } else if CopilotParser::can_parse(path) {
+        ensure_enabled(SessionSource::Copilot)?;
+        CopilotParser::parse_file(path)?
+    } else if AmpParser::can_parse(path) {
+        ensure_enabled(SessionSource::Amp)?;
+        AmpParser::parse_file(path)?
+    } else if CursorParser::can_parse(path) {
+        ensure_enabled(SessionSource::Cursor)?;
+        CursorParser::parse_file(path)?
+    } else if ContinueParser::can_parse(path) {
+        ensure_enabled(SessionSource::Continue)?;
+        ContinueParser::parse_file(path)?
+    } else if OpenHandsParser::can_parse(path) {
+        ensure_enabled(SessionSource::OpenHands)?;
+        OpenHandsParser::parse_file(path)?
+    } else if AmazonQParser::can_parse(path) {
+        ensure_enabled(SessionSource::AmazonQ)?;
+        AmazonQParser::parse_file(path)?
+    } else if CrushParser::can_parse(path) {
+        ensure_enabled(SessionSource::Crush)?;
+        CrushParser::parse_file(path)?
+    } else if LlmParser::can_parse(path) {
+        ensure_enabled(SessionSource::Llm)?;
+        LlmParser::parse_file(path)?
+    } else if ZedParser::can_parse(path) {
+        ensure_enabled(SessionSource::Zed)?;
+        ZedParser::parse_file(path)?
+    } else if WindsurfParser::can_parse(path) {
+        ensure_enabled(SessionSource::Windsurf)?;
+        WindsurfParser::parse_file(path)?
+    } else if ClaudeDesktopParser::can_parse(path) {
+        ensure_enabled(SessionSource::ClaudeDesktop)?;
+        ClaudeDesktopParser::parse_file(path)?
+    } else if PlandexParser::can_parse(path) {
+        ensure_enabled(SessionSource::Plandex)?;
+        PlandexParser::parse_file(path)?
+    } else if RooCodeParser::can_parse(path) {
+        ensure_enabled(SessionSource::RooCode)?;
+        RooCodeParser::parse_file(path)?
+    } else if WarpParser::can_parse(path) {
+        ensure_enabled(SessionSource::Warp)?;
+        WarpParser::parse_file(path)?
+    } else if GenericParser::can_parse(path) {
+        // Enabled or not is checked below, once the session knows its source
+        GenericParser::parse_file(path)?
     } else {
         anyhow::bail!("Unknown session file format: {:?}", path)
+    };
+    ensure_enabled(session.source)?;
+    if let Some(redactor) = crate::redact::configured() {
+        let redact = |text: &mut String| {
+            if let Cow::Owned(redacted) = redactor.redact(text) {
+                *text = redacted;
+            }
+        };
+        if let Some(title) = &mut session.title {
+            redact(title);
+        }
+        for message in &mut session.messages {
+            redact(&mut message.content);
+            for call in &mut message.tool_calls {
+                redact(&mut call.input);
+                if let Some(output) = &mut call.output {
+                    redact(output);
+                }
+            }
+        }
     }
+    Ok(session)
+}
+
+fn ensure_enabled(source: SessionSource) -> Result<()> {
+    if !crate::config::get().sources.enabled(source) {
+        anyhow::bail!("{} sessions are disabled in the config", source.display_name());
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::session::Role;
-    use chrono::Utc;
+    use chrono::{DateTime, Utc};
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("/Users/me/My%20App"), "/Users/me/My App");
+        assert_eq!(percent_decode("/w/caf%C3%A9"), "/w/café");
+        assert_eq!(percent_decode("/w/100%"), "/w/100%");
+    }
+
+    #[test]
+    fn test_entry_path() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let database = temp.path().join("data.sqlite3");
+        std::fs::write(&database, "").unwrap();
+        let path = entry_path(&database, "/w/100% done");
+        assert_eq!(path.parent(), Some(database.as_path()));
+        assert_eq!(split_entry(&path), Some((database.as_path(), "/w/100% done".to_string())));
+        assert_eq!(backing_file(&path), database);
+        // A real file is its own backing file
+        assert_eq!(split_entry(&database), None);
+        assert_eq!(backing_file(&database), database);
+    }
+
+    fn message(role: Role, content: &str, timestamp: DateTime<Utc>) -> Message {
+        Message { role, content: content.to_string(), timestamp, tool_calls: Vec::new() }
+    }
 
     #[test]
     fn test_join_consecutive_messages_different_roles() {
         let now = Utc::now();
         let messages = vec![
-            Message { role: Role::User, content: "Hello".to_string(), timestamp: now },
-            Message { role: Role::Assistant, content: "Hi".to_string(), timestamp: now },
-            Message { role: Role::User, content: "Bye".to_string(), timestamp: now },
+            message(Role::User, "Hello", now),
+            message(Role::Assistant, "Hi", now),
+            message(Role::User, "Bye", now),
         ];
-        let joined = join_consecutive_messages(messages);
+        let joined = join_consecutive_messages(messages, MessageJoin::Always);
         assert_eq!(joined.len(), 3);
     }
 
@@ -161,11 +629,11 @@ mod tests {
         let t1 = Utc::now();
         let t2 = t1 + chrono::Duration::seconds(10);
         let messages = vec![
-            Message { role: Role::User, content: "Part 1".to_string(), timestamp: t1 },
-            Message { role: Role::User, content: "Part 2".to_string(), timestamp: t2 },
-            Message { role: Role::Assistant, content: "Response".to_string(), timestamp: t2 },
+            message(Role::User, "Part 1", t1),
+            message(Role::User, "Part 2", t2),
+            message(Role::Assistant, "Response", t2),
         ];
-        let joined = join_consecutive_messages(messages);
+        let joined = join_consecutive_messages(messages, MessageJoin::Always);
         assert_eq!(joined.len(), 2);
         assert_eq!(joined[0].content, "Part 1\n\nPart 2");
         assert_eq!(joined[0].timestamp, t2); // Uses latest timestamp
@@ -176,12 +644,50 @@ mod tests {
     fn test_join_consecutive_messages_multiple_same_role() {
         let now = Utc::now();
         let messages = vec![
-            Message { role: Role::Assistant, content: "A".to_string(), timestamp: now },
-            Message { role: Role::Assistant, content: "B".to_string(), timestamp: now },
-            Message { role: Role::Assistant, content: "C".to_string(), timestamp: now },
+            message(Role::Assistant, "A", now),
+            message(Role::Assistant, "B", now),
+            message(Role::Assistant, "C", now),
         ];
-        let joined = join_consecutive_messages(messages);
+        let joined = join_consecutive_messages(messages, MessageJoin::Always);
         assert_eq!(joined.len(), 1);
         assert_eq!(joined[0].content, "A\n\nB\n\nC");
     }
+
+    #[test]
+    fn test_join_consecutive_messages_off() {
+        let now = Utc::now();
+        let messages = vec![
+            message(Role::User, "A", now),
+            message(Role::User, "B", now),
+        ];
+        assert_eq!(join_consecutive_messages(messages, MessageJoin::Off).len(), 2);
+    }
+
+    #[test]
+    fn test_join_consecutive_messages_gap() {
+        let monday = Utc::now() - chrono::Duration::days(2);
+        let minutes = |n| monday + chrono::Duration::minutes(n);
+        let messages = vec![
+            message(Role::User, "Mon 1", monday),
+            message(Role::User, "Mon 2", minutes(10)),
+            // 15 minutes after the latest joined message is still close enough
+            message(Role::User, "Mon 3", minutes(25)),
+            message(Role::User, "Wed", minutes(2 * 24 * 60)),
+        ];
+        let joined = join_consecutive_messages(messages, MessageJoin::Gap(15));
+        assert_eq!(joined.len(), 2);
+        assert_eq!(joined[0].content, "Mon 1\n\nMon 2\n\nMon 3");
+        assert_eq!(joined[0].timestamp, minutes(25));
+        assert_eq!(joined[1].content, "Wed");
+    }
+
+    #[test]
+    fn test_join_consecutive_messages_gap_keeps_roles_apart() {
+        let now = Utc::now();
+        let messages = vec![
+            message(Role::User, "Q", now),
+            message(Role::Assistant, "A", now),
+        ];
+        assert_eq!(join_consecutive_messages(messages, MessageJoin::Gap(15)).len(), 2);
+    }
 }
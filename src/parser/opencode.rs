@@ -1,19 +1,20 @@
-use crate::session::{Message, Role, Session, SessionSource};
+use crate::config;
+use crate::session::{Message, ModelUsage, Role, Session, SessionSource, ToolCall, ToolStatus};
 use anyhow::{Context, Result};
 use chrono::{DateTime, TimeZone, Utc};
 use serde::Deserialize;
+use serde_json::Value;
 use std::fs::File;
 use std::io::BufReader;
 use std::path::{Path, PathBuf};
 
-use super::{join_consecutive_messages, SessionParser};
+use super::{join_consecutive_messages, timeline::Timeline, tools, SessionParser};
 
 /// OpenCode session metadata from session/<project_id>/ses_*.json
 #[derive(Debug, Deserialize)]
 struct OpenCodeSession {
     id: String,
     #[serde(rename = "projectID")]
-    #[allow(dead_code)]
     project_id: Option<String>,
     directory: Option<String>,
     #[allow(dead_code)]
@@ -21,6 +22,15 @@ struct OpenCodeSession {
     time: Option<TimeInfo>,
 }
 
+/// OpenCode project metadata from project/<project_id>.json
+#[derive(Debug, Default, Deserialize)]
+struct OpenCodeProject {
+    /// The root of the project's repository
+    worktree: Option<String>,
+    /// The branch checked out when the session ran
+    branch: Option<String>,
+}
+
 /// OpenCode message metadata from message/ses_*/msg_*.json
 #[derive(Debug, Deserialize)]
 struct OpenCodeMessage {
@@ -34,6 +44,30 @@ struct OpenCodeMessage {
     #[allow(dead_code)]
     parent_id: Option<String>,
     path: Option<PathInfo>,
+    #[serde(rename = "modelID")]
+    model_id: Option<String>,
+    tokens: Option<TokenInfo>,
+}
+
+/// Token counts from assistant messages
+#[derive(Debug, Deserialize)]
+struct TokenInfo {
+    #[serde(default)]
+    input: u64,
+    #[serde(default)]
+    output: u64,
+    #[serde(default)]
+    reasoning: u64,
+    #[serde(default)]
+    cache: CacheInfo,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CacheInfo {
+    #[serde(default)]
+    read: u64,
+    #[serde(default)]
+    write: u64,
 }
 
 /// Time information with millisecond timestamps
@@ -60,6 +94,23 @@ struct OpenCodePart {
     #[serde(rename = "type")]
     part_type: String,
     text: Option<String>,
+    /// The tool a `tool` part called
+    tool: Option<String>,
+    state: Option<ToolState>,
+}
+
+/// Where a `tool` part's call is: `pending`, `running`, `completed` or `error`, with what
+/// it was given and what came of it
+#[derive(Debug, Deserialize)]
+struct ToolState {
+    status: String,
+    #[serde(default)]
+    input: Value,
+    output: Option<String>,
+    error: Option<String>,
+    /// `exit` holds a shell command's exit code
+    #[serde(default)]
+    metadata: Value,
 }
 
 pub struct OpenCodeParser;
@@ -81,12 +132,20 @@ impl SessionParser for OpenCodeParser {
 
         // 2. Get storage root (go up from session/<project>/ses_*.json to storage/)
         let storage_root = get_storage_root(path).context("Failed to get storage root")?;
+        let project = session
+            .project_id
+            .as_deref()
+            .map(|id| read_project(&storage_root, id))
+            .unwrap_or_default();
 
         // 3. Find and read all messages for this session
         let message_dir = storage_root.join("message").join(&session.id);
         let mut messages: Vec<Message> = Vec::new();
-        let mut latest_timestamp: Option<DateTime<Utc>> = None;
+        let mut timeline = Timeline::default();
+        // The session's own creation time counts as known, for messages without one
+        timeline.observe(session.time.as_ref().and_then(|t| millis_to_datetime(t.created)));
         let mut cwd: Option<String> = session.directory.clone();
+        let mut usage: Vec<ModelUsage> = Vec::new();
 
         if message_dir.exists() {
             // Collect and sort message files by creation time
@@ -115,17 +174,7 @@ impl SessionParser for OpenCodeParser {
 
             // Process each message
             for (_msg_path, msg) in msg_entries {
-                // Get timestamp
-                let timestamp = msg
-                    .time
-                    .as_ref()
-                    .map(|t| millis_to_datetime(t.created))
-                    .unwrap_or_else(Utc::now);
-
-                // Update latest timestamp
-                if latest_timestamp.is_none() || timestamp > latest_timestamp.unwrap() {
-                    latest_timestamp = Some(timestamp);
-                }
+                timeline.observe(msg.time.as_ref().and_then(|t| millis_to_datetime(t.created)));
 
                 // Get cwd from message path info if available
                 if cwd.is_none() {
@@ -141,32 +190,51 @@ impl SessionParser for OpenCodeParser {
                     _ => continue, // Skip unknown roles
                 };
 
+                if let (Some(model), Some(tokens)) = (&msg.model_id, &msg.tokens) {
+                    ModelUsage::accumulate(
+                        &mut usage,
+                        ModelUsage {
+                            model: model.clone(),
+                            input_tokens: tokens.input,
+                            output_tokens: tokens.output + tokens.reasoning,
+                            cache_read_tokens: tokens.cache.read,
+                            cache_write_tokens: tokens.cache.write,
+                        },
+                    );
+                }
+
                 // Read parts for this message
-                let content = read_message_parts(&storage_root, &msg.id);
+                let (content, tool_calls) = read_message_parts(&storage_root, &msg.id);
                 if !content.is_empty() {
+                    let timestamp = timeline.stamp(&messages);
                     messages.push(Message {
                         role,
                         content,
                         timestamp,
+                        tool_calls,
                     });
+                } else if let Some(last) = messages.last_mut() {
+                    // A step that only called tools: they followed the latest message
+                    last.tool_calls.extend(tool_calls);
                 }
             }
         }
 
+        let timestamp = timeline.finish(&mut messages, path);
+        // The project's folder when neither the session nor its messages say where they ran
+        let cwd = cwd.or(project.worktree).unwrap_or_else(|| ".".to_string());
+
         Ok(Session {
             id: session.id,
             source: SessionSource::OpenCode,
             file_path: path.to_path_buf(),
-            cwd: cwd.unwrap_or_else(|| ".".to_string()),
-            git_branch: None, // OpenCode doesn't store git branch in session metadata
-            timestamp: latest_timestamp.unwrap_or_else(|| {
-                session
-                    .time
-                    .as_ref()
-                    .map(|t| millis_to_datetime(t.created))
-                    .unwrap_or_else(Utc::now)
-            }),
-            messages: join_consecutive_messages(messages),
+            cwd,
+            git_branch: project.branch.filter(|b| !b.is_empty()),
+            timestamp,
+            messages: join_consecutive_messages(messages, config::get().join_messages),
+            files_touched: Vec::new(),
+            usage,
+            title: None,
         })
     }
 }
@@ -182,18 +250,28 @@ fn get_storage_root(session_path: &Path) -> Option<PathBuf> {
         .map(|p| p.to_path_buf())
 }
 
-/// Convert milliseconds timestamp to DateTime<Utc>
-fn millis_to_datetime(millis: i64) -> DateTime<Utc> {
-    Utc.timestamp_millis_opt(millis).single().unwrap_or_else(Utc::now)
+/// The project a session belongs to, or nothing known about it when its record is missing
+fn read_project(storage_root: &Path, project_id: &str) -> OpenCodeProject {
+    let path = storage_root.join("project").join(format!("{}.json", project_id));
+    std::fs::read(path)
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default()
 }
 
-/// Read all text parts for a message and concatenate them
-fn read_message_parts(storage_root: &Path, message_id: &str) -> String {
+/// Convert milliseconds timestamp to DateTime<Utc> (None if out of range)
+fn millis_to_datetime(millis: i64) -> Option<DateTime<Utc>> {
+    Utc.timestamp_millis_opt(millis).single()
+}
+
+/// Read the parts of a message: its text parts concatenated, and its tool calls
+fn read_message_parts(storage_root: &Path, message_id: &str) -> (String, Vec<ToolCall>) {
     let parts_dir = storage_root.join("part").join(message_id);
     let mut texts: Vec<String> = Vec::new();
+    let mut tool_calls: Vec<ToolCall> = Vec::new();
 
     if !parts_dir.exists() {
-        return String::new();
+        return (String::new(), tool_calls);
     }
 
     // Read all part files
@@ -218,22 +296,48 @@ fn read_message_parts(storage_root: &Path, message_id: &str) -> String {
         }
     }
 
-    // Sort by filename to maintain order (prt_* IDs are sortable)
+    // Sort by filename to maintain order (prt_* IDs are sortable), so tool calls are in the
+    // order they ran
     part_entries.sort_by(|a, b| a.0.cmp(&b.0));
 
-    // Extract text from text parts only
     for (_filename, part) in part_entries {
-        if part.part_type == "text" {
-            if let Some(text) = part.text {
-                if !text.is_empty() {
-                    texts.push(text);
+        match part.part_type.as_str() {
+            "text" => {
+                if let Some(text) = part.text {
+                    if !text.is_empty() {
+                        texts.push(text);
+                    }
                 }
             }
+            "tool" => tool_calls.extend(tool_call(part)),
+            // step-start and step-finish are bookkeeping around each model call (snapshots,
+            // cost), with nothing to show
+            _ => {}
         }
-        // Skip step-start, step-finish, tool parts (per user preference)
     }
 
-    texts.join("\n")
+    (texts.join("\n"), tool_calls)
+}
+
+/// The call a `tool` part made. A command that exited non-zero failed, even if the tool
+/// completed.
+fn tool_call(part: OpenCodePart) -> Option<ToolCall> {
+    let state = part.state?;
+    let mut call = ToolCall::pending(part.tool.as_deref()?, tools::input_text(&state.input));
+    call.exit_code = state.metadata.get("exit").and_then(Value::as_i64);
+    match state.status.as_str() {
+        "completed" => {
+            let failed = call.exit_code.is_some_and(|code| code != 0);
+            call.status = if failed { ToolStatus::Error } else { ToolStatus::Ok };
+            call.output = state.output;
+        }
+        "error" => {
+            call.status = ToolStatus::Error;
+            call.output = state.error;
+        }
+        _ => {}
+    }
+    Some(call)
 }
 
 #[cfg(test)]
@@ -255,7 +359,7 @@ mod tests {
 
     #[test]
     fn test_millis_to_datetime() {
-        let dt = millis_to_datetime(1763499168814);
+        let dt = millis_to_datetime(1763499168814).unwrap();
         assert!(dt.timestamp_millis() == 1763499168814);
     }
 
@@ -0,0 +1,192 @@
+//! OpenHands (formerly OpenDevin) records each session as an event stream:
+//! `~/.openhands/sessions/<id>/events/<n>.json`, one file per action or observation. The
+//! session is the `events` folder, read whole; only messages between the user and the agent
+//! and the agent's closing summary (`finish`) become messages.
+
+use crate::config;
+use crate::session::{Message, Role, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use super::{join_consecutive_messages, timeline::Timeline, SessionParser};
+
+/// The folder of events in each session's folder
+pub const EVENTS_DIR: &str = "events";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Event {
+    id: Option<u64>,
+    /// ISO 8601, in local time without an offset
+    timestamp: Option<String>,
+    /// `user`, `agent`, or `environment`
+    source: Option<String>,
+    /// Set on actions; observations have `observation` instead
+    action: Option<String>,
+    args: Value,
+}
+
+pub struct OpenHandsParser;
+
+impl SessionParser for OpenHandsParser {
+    fn can_parse(path: &Path) -> bool {
+        let in_openhands = path.to_str().is_some_and(|s| {
+            s.contains(".openhands/sessions") || s.contains(".openhands\\sessions")
+        });
+        in_openhands && path.file_name().is_some_and(|name| name == EVENTS_DIR)
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        let mut events: Vec<(u64, Event)> = Vec::new();
+        for entry in std::fs::read_dir(path).context("Failed to read events")?.flatten() {
+            let event_path = entry.path();
+            if event_path.extension().is_none_or(|e| e != "json") {
+                continue;
+            }
+            // An event still being written is read when the next one makes the folder change
+            let Ok(file) = File::open(&event_path) else {
+                continue;
+            };
+            let Ok(event) = serde_json::from_reader::<_, Event>(BufReader::new(file)) else {
+                continue;
+            };
+            let number = event_path.file_stem().and_then(|s| s.to_str()?.parse().ok());
+            if let Some(id) = event.id.or(number) {
+                events.push((id, event));
+            }
+        }
+        events.sort_by_key(|(id, _)| *id);
+
+        let mut timeline = Timeline::default();
+        let mut messages: Vec<Message> = Vec::new();
+        for (_, event) in &events {
+            timeline.observe(event.timestamp.as_deref().and_then(parse_time));
+            let role = match event.source.as_deref() {
+                Some("user") => Role::User,
+                Some("agent") => Role::Assistant,
+                _ => continue,
+            };
+            let content = match event.action.as_deref() {
+                Some("message") => text_arg(&event.args, &["content"]),
+                Some("finish") => text_arg(&event.args, &["final_thought", "thought"]),
+                _ => continue,
+            };
+            if !content.is_empty() {
+                let timestamp = timeline.stamp(&messages);
+                messages.push(Message { role, content, timestamp, tool_calls: Vec::new() });
+            }
+        }
+
+        let session_id = path
+            .parent()
+            .and_then(|dir| dir.file_name())
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let timestamp = timeline.finish(&mut messages, path);
+
+        Ok(Session {
+            id: session_id,
+            source: SessionSource::OpenHands,
+            file_path: path.to_path_buf(),
+            // The agent works in a sandbox, so there's no folder on this machine to point at
+            cwd: ".".to_string(),
+            git_branch: None,
+            timestamp,
+            messages: join_consecutive_messages(messages, config::get().join_messages),
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: None,
+        })
+    }
+}
+
+/// The `events` folder of each session in `dir`
+pub(super) fn discover(dir: &Path) -> Vec<PathBuf> {
+    let Ok(sessions) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    sessions
+        .flatten()
+        .map(|session| session.path().join(EVENTS_DIR))
+        .filter(|events| events.is_dir())
+        .collect()
+}
+
+/// The first of `keys` in an action's args that holds some text, trimmed
+fn text_arg(args: &Value, keys: &[&str]) -> String {
+    keys.iter()
+        .filter_map(|key| args.get(key).and_then(Value::as_str))
+        .map(str::trim)
+        .find(|text| !text.is_empty())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn parse_time(text: &str) -> Option<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Some(time.with_timezone(&Utc));
+    }
+    let naive = NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+    naive.and_local_timezone(Local).earliest().map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_events() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let sessions = temp.path().join(".openhands/sessions");
+        let events = sessions.join("c0ffee12/events");
+        std::fs::create_dir_all(&events).unwrap();
+        let stream = [
+            json!({"id": 0, "timestamp": "2025-06-01T09:00:00+00:00", "source": "environment",
+                   "observation": "agent_state_changed", "extras": {"agent_state": "loading"}}),
+            json!({"id": 1, "timestamp": "2025-06-01T09:00:01+00:00", "source": "user",
+                   "action": "message",
+                   "args": {"content": "add a health check", "image_urls": []}}),
+            json!({"id": 2, "timestamp": "2025-06-01T09:00:05+00:00", "source": "agent",
+                   "action": "run", "args": {"command": "ls", "thought": "Looking around."}}),
+            json!({"id": 3, "timestamp": "2025-06-01T09:00:06+00:00", "source": "agent",
+                   "observation": "run", "content": "app.py"}),
+            json!({"id": 10, "timestamp": "2025-06-01T09:02:00+00:00", "source": "agent",
+                   "action": "finish", "args": {"outputs": {}, "thought": "",
+                   "final_thought": "Added /healthz."}}),
+        ];
+        for event in &stream {
+            let name = format!("{}.json", event["id"]);
+            std::fs::write(events.join(name), event.to_string()).unwrap();
+        }
+        assert!(OpenHandsParser::can_parse(&events));
+        assert_eq!(discover(&sessions), [sessions.join("c0ffee12/events")]);
+
+        let session = OpenHandsParser::parse_file(&events).unwrap();
+        assert_eq!(session.id, "c0ffee12");
+        assert_eq!(session.source, SessionSource::OpenHands);
+        let contents: Vec<_> =
+            session.messages.iter().map(|m| (m.role, m.content.as_str())).collect();
+        // Events go by id, not by file name (10.json would sort before 2.json)
+        assert_eq!(
+            contents,
+            [(Role::User, "add a health check"), (Role::Assistant, "Added /healthz.")]
+        );
+        assert_eq!(session.messages[1].timestamp, parse_time("2025-06-01T09:02:00Z").unwrap());
+        assert_eq!(session.timestamp, session.messages[1].timestamp);
+    }
+
+    #[test]
+    fn test_parse_local_time() {
+        let naive = parse_time("2025-06-01T09:00:00.123456").unwrap();
+        let later = parse_time("2025-06-01T09:00:01").unwrap();
+        assert!(naive < later);
+    }
+}
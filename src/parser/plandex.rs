@@ -0,0 +1,171 @@
+//! Plandex keeps its plans in `~/.plandex-home`, grouped by project. Each plan's folder has
+//! its conversation in `conversation/`, one Markdown file per message named
+//! `<n>-<role>.md`, and `plan.json` with the plan's name and the folder it was started in
+//! (`cwd`). The session is the `conversation` folder, read whole, in the order of the
+//! messages' numbers; each message is dated by its file, written as the message arrived.
+
+use crate::config;
+use crate::session::{Message, Role, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use super::{join_consecutive_messages, timeline::Timeline, SessionParser};
+
+/// Plandex's home, under the user's
+pub const HOME_DIR: &str = ".plandex-home";
+
+/// The folder of messages in each plan's folder
+pub const CONVERSATION_DIR: &str = "conversation";
+
+const PLAN_FILE: &str = "plan.json";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Plan {
+    /// The plan's name, as `plandex plans` lists it
+    #[allow(dead_code)]
+    name: String,
+    cwd: Option<String>,
+}
+
+pub struct PlandexParser;
+
+impl SessionParser for PlandexParser {
+    fn can_parse(path: &Path) -> bool {
+        path.file_name().is_some_and(|name| name == CONVERSATION_DIR)
+            && path.components().any(|c| c.as_os_str() == HOME_DIR)
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        let mut files: Vec<(u64, Role, PathBuf)> = Vec::new();
+        for entry in std::fs::read_dir(path).context("Failed to read conversation")?.flatten() {
+            let file = entry.path();
+            let Some((number, role)) = message_name(&file) else {
+                continue;
+            };
+            files.push((number, role, file));
+        }
+        // By number rather than by name, so message 10 comes after message 9
+        files.sort_by_key(|(number, _, _)| *number);
+
+        let mut timeline = Timeline::default();
+        let mut messages: Vec<Message> = Vec::new();
+        for (_, role, file) in files {
+            // A message still being written is read when the folder next changes
+            let Ok(text) = std::fs::read_to_string(&file) else {
+                continue;
+            };
+            let modified = std::fs::metadata(&file).and_then(|m| m.modified()).ok();
+            timeline.observe(modified.map(DateTime::<Utc>::from));
+            let content = text.trim();
+            if !content.is_empty() {
+                let timestamp = timeline.stamp(&messages);
+                messages.push(Message {
+                    role,
+                    content: content.to_string(),
+                    timestamp,
+                    tool_calls: Vec::new(),
+                });
+            }
+        }
+
+        let plan_dir = path.parent().unwrap_or(Path::new("."));
+        let plan = read_plan(&plan_dir.join(PLAN_FILE));
+        let session_id = plan_dir
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let timestamp = timeline.finish(&mut messages, path);
+
+        Ok(Session {
+            id: session_id,
+            source: SessionSource::Plandex,
+            file_path: path.to_path_buf(),
+            cwd: plan.cwd.unwrap_or_else(|| ".".to_string()),
+            git_branch: None,
+            timestamp,
+            messages: join_consecutive_messages(messages, config::get().join_messages),
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: None,
+        })
+    }
+}
+
+/// Every plan's conversation folder under `dir`, however its projects are nested
+pub(super) fn discover(dir: &Path) -> Vec<PathBuf> {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.file_type().is_dir() && entry.file_name() == CONVERSATION_DIR)
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// The number and role of a message file, from its `<n>-<role>.md` name. System prompts and
+/// anything else that isn't between the user and the model are left out.
+fn message_name(path: &Path) -> Option<(u64, Role)> {
+    let stem = path.file_name()?.to_str()?.strip_suffix(".md")?;
+    let (number, role) = stem.split_once('-')?;
+    let role = match role {
+        "user" => Role::User,
+        "assistant" => Role::Assistant,
+        _ => return None,
+    };
+    Some((number.parse().ok()?, role))
+}
+
+/// The plan's settings, or none if they're missing or unreadable
+fn read_plan(path: &Path) -> Plan {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plan() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let plan = temp.path().join(".plandex-home/projects/p1/plans/7d1e0c2a");
+        let conversation = plan.join(CONVERSATION_DIR);
+        std::fs::create_dir_all(&conversation).unwrap();
+        std::fs::write(plan.join(PLAN_FILE), r#"{"name": "add-auth", "cwd": "/w/api"}"#)
+            .unwrap();
+        let files = [
+            ("1-user.md", "Add JWT auth to the API"),
+            ("2-assistant.md", "I'll add a middleware."),
+            ("3-system.md", "Context loaded."),
+            ("10-assistant.md", "Done: auth.go and routes.go."),
+            ("9-user.md", "Also protect /admin"),
+            ("notes.txt", "not a message"),
+        ];
+        for (name, text) in files {
+            std::fs::write(conversation.join(name), text).unwrap();
+        }
+        assert!(PlandexParser::can_parse(&conversation));
+
+        let session = PlandexParser::parse_file(&conversation).unwrap();
+        assert_eq!(session.id, "7d1e0c2a");
+        assert_eq!(session.source, SessionSource::Plandex);
+        assert_eq!(session.cwd, "/w/api");
+        let contents: Vec<_> =
+            session.messages.iter().map(|m| (m.role, m.content.as_str())).collect();
+        assert_eq!(
+            contents,
+            [
+                (Role::User, "Add JWT auth to the API"),
+                (Role::Assistant, "I'll add a middleware."),
+                (Role::User, "Also protect /admin"),
+                (Role::Assistant, "Done: auth.go and routes.go."),
+            ]
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Roo Code, a fork of Cline, keeps its tasks just as Cline does (see [`super::cline`]), in
+//! the global storage of whichever editor it's installed in:
+//! `<editor>/User/globalStorage/rooveterinaryinc.roo-cline/tasks/<id>/`. The editors' shared
+//! settings folder is its root, and VS Code's, VSCodium's and Cursor's are searched.
+
+use crate::session::{Session, SessionSource};
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use super::{cline, SessionParser};
+
+/// The folder the editors keep their settings in, under the home
+#[cfg(target_os = "macos")]
+pub const EDITORS_DIR: &str = "Library/Application Support";
+#[cfg(windows)]
+pub const EDITORS_DIR: &str = "AppData/Roaming";
+#[cfg(not(any(target_os = "macos", windows)))]
+pub const EDITORS_DIR: &str = ".config";
+
+/// Editors Roo Code is installed in, by their folder in [`EDITORS_DIR`]
+const EDITORS: [&str; 3] = ["Code", "VSCodium", "Cursor"];
+
+const EXTENSION_ID: &str = "rooveterinaryinc.roo-cline";
+
+pub struct RooCodeParser;
+
+impl SessionParser for RooCodeParser {
+    fn can_parse(path: &Path) -> bool {
+        is_task(path)
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        cline::parse_task(path, SessionSource::RooCode)
+    }
+}
+
+/// Whether `path` is a Roo Code task's conversation
+pub(super) fn is_task(path: &Path) -> bool {
+    path.to_str().is_some_and(|s| s.contains(EXTENSION_ID))
+        && path.file_name().is_some_and(|name| name == cline::HISTORY_FILE)
+}
+
+/// The conversation of every task, in each editor's global storage under `dir`
+pub(super) fn discover(dir: &Path) -> Vec<PathBuf> {
+    EDITORS
+        .iter()
+        .map(|editor| dir.join(editor).join("User/globalStorage").join(EXTENSION_ID).join("tasks"))
+        .filter(|tasks| tasks.is_dir())
+        .flat_map(|tasks| super::walk_files(&tasks, "json", |name| name == cline::HISTORY_FILE))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discover_in_each_editor() {
+        let temp = tempfile::TempDir::new().unwrap();
+        for editor in ["Code", "VSCodium", "Cursor", "Windsurf"] {
+            let task = temp.path().join(editor).join("User/globalStorage").join(EXTENSION_ID);
+            let task = task.join("tasks/1748772000000");
+            std::fs::create_dir_all(&task).unwrap();
+            std::fs::write(task.join(cline::HISTORY_FILE), "[]").unwrap();
+            std::fs::write(task.join("ui_messages.json"), "[]").unwrap();
+        }
+
+        let mut editors: Vec<_> = discover(temp.path())
+            .iter()
+            .inspect(|path| assert!(RooCodeParser::can_parse(path)))
+            .map(|path| path.strip_prefix(temp.path()).unwrap().iter().next().unwrap().to_owned())
+            .collect();
+        editors.sort();
+        assert_eq!(editors, ["Code", "Cursor", "VSCodium"]);
+        let cline = Path::new("/h/saoudrizwan.claude-dev/tasks/1/api_conversation_history.json");
+        assert!(!RooCodeParser::can_parse(cline));
+    }
+}
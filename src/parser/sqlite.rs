@@ -0,0 +1,529 @@
+//! Just enough of the SQLite file format to read whole tables out of a database some other
+//! program keeps its history in (Cursor's `state.vscdb`, Amazon Q's `data.sqlite3`). It walks
+//! table b-trees page by page and decodes records; indexes and SQL are out of scope. Writes
+//! not yet checkpointed into the file are read from its WAL, up to the last commit in it. A
+//! database with a hot rollback journal is mid-write, so it's refused rather than read
+//! half-updated.
+
+use anyhow::{bail, Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+const HEADER_MAGIC: &[u8] = b"SQLite format 3\0";
+
+/// B-tree page types
+const INTERIOR_TABLE: u8 = 0x05;
+const LEAF_TABLE: u8 = 0x0d;
+
+/// How deep a table b-tree may go before the file is taken to be corrupt
+const MAX_DEPTH: usize = 64;
+
+/// A WAL's magic number, its low bit set when its checksums are big-endian
+const WAL_MAGIC: u32 = 0x377f0682;
+const WAL_HEADER: usize = 32;
+const FRAME_HEADER: usize = 24;
+
+/// A column's value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl Value {
+    /// Text, or a blob that holds UTF-8 (apps store JSON as either)
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Value::Text(text) => Some(text),
+            Value::Blob(bytes) => std::str::from_utf8(bytes).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// A row's rowid and columns
+pub type Row = (i64, Vec<Value>);
+
+/// A table's column names and rows
+#[derive(Debug)]
+pub struct Table {
+    pub columns: Vec<String>,
+    pub rows: Vec<Row>,
+}
+
+impl Table {
+    /// The value of the column named `name` in `row`, or None if the row was written before
+    /// the column was added
+    pub fn get<'a>(&self, row: &'a Row, name: &str) -> Option<&'a Value> {
+        let i = self.columns.iter().position(|c| c.eq_ignore_ascii_case(name))?;
+        row.1.get(i)
+    }
+}
+
+/// A table as read from a database file, as of its and its WAL's modification times
+struct ReadTable {
+    path: PathBuf,
+    modified: (SystemTime, Option<SystemTime>),
+    name: String,
+    table: Arc<Table>,
+}
+
+/// Tables kept from the last few reads
+const READS_KEPT: usize = 4;
+
+/// The tables read last, most recent first. Sessions kept many to a database are parsed one
+/// after another from the same tables, which are read once rather than once each.
+static LAST_READS: Mutex<Vec<ReadTable>> = Mutex::new(Vec::new());
+
+/// The table `name` in the database at `path`, reusing a recent read while the file is
+/// unchanged
+pub fn read_table(path: &Path, name: &str) -> Result<Arc<Table>> {
+    let database = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .context("Failed to read database")?;
+    let wal = std::fs::metadata(wal_path(path)).and_then(|m| m.modified()).ok();
+    let modified = (database, wal);
+    let mut reads = LAST_READS.lock().unwrap_or_else(|e| e.into_inner());
+    let cached = reads
+        .iter()
+        .find(|read| read.path == path && read.modified == modified && read.name == name);
+    if let Some(read) = cached {
+        return Ok(Arc::clone(&read.table));
+    }
+    let table = Arc::new(Database::open(path)?.table(name)?);
+    reads.retain(|read| !(read.path == path && read.name == name));
+    reads.insert(
+        0,
+        ReadTable {
+            path: path.to_path_buf(),
+            modified,
+            name: name.to_string(),
+            table: Arc::clone(&table),
+        },
+    );
+    reads.truncate(READS_KEPT);
+    Ok(table)
+}
+
+/// The write-ahead log beside the database at `path`
+pub(crate) fn wal_path(path: &Path) -> PathBuf {
+    let mut wal = path.as_os_str().to_owned();
+    wal.push("-wal");
+    PathBuf::from(wal)
+}
+
+/// A database file, read into memory
+pub struct Database {
+    data: Vec<u8>,
+    page_size: usize,
+    /// Page size less the bytes reserved at the end of each page
+    usable: usize,
+    /// The WAL, and where in it the newest committed copy of each page it holds starts
+    wal: Vec<u8>,
+    frames: HashMap<usize, usize>,
+}
+
+impl Database {
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut journal = path.as_os_str().to_owned();
+        journal.push("-journal");
+        if std::fs::metadata(&journal).is_ok_and(|m| m.len() > 0) {
+            bail!("Database is being written to; it will be read once it's idle");
+        }
+        let data = std::fs::read(path).context("Failed to read database")?;
+        let wal = match std::fs::read(wal_path(path)) {
+            Ok(wal) => wal,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e).context("Failed to read the database's WAL"),
+        };
+        Ok(Self::from_bytes(data)?.with_wal(wal))
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self> {
+        if data.len() < 100 || !data.starts_with(HEADER_MAGIC) {
+            bail!("Not a SQLite database");
+        }
+        let page_size = match u16::from_be_bytes([data[16], data[17]]) {
+            1 => 65536,
+            size => size as usize,
+        };
+        if page_size < 512 || !page_size.is_power_of_two() {
+            bail!("Unsupported page size {}", page_size);
+        }
+        // 1 is UTF-8; UTF-16 databases are rare enough not to bother with
+        if u32::from_be_bytes([data[56], data[57], data[58], data[59]]) > 1 {
+            bail!("Only UTF-8 databases are supported");
+        }
+        let usable = page_size - data[20] as usize;
+        Ok(Self { data, page_size, usable, wal: Vec::new(), frames: HashMap::new() })
+    }
+
+    /// The database with the pages committed to `wal` read over its own. A log left from
+    /// before the last checkpoint, or for another page size, holds none; one torn mid-write
+    /// holds those up to the last commit whose checksums still add up.
+    pub fn with_wal(mut self, wal: Vec<u8>) -> Self {
+        let Some(header) = wal.get(..WAL_HEADER) else {
+            return self;
+        };
+        let magic = be_u32(&header[0..4]);
+        if magic & !1 != WAL_MAGIC || be_u32(&header[8..12]) as usize != self.page_size {
+            return self;
+        }
+        let big_endian = magic & 1 == 1;
+        let mut sum = wal_checksum((0, 0), &header[..24], big_endian);
+        if sum != (be_u32(&header[24..28]), be_u32(&header[28..32])) {
+            return self;
+        }
+
+        let mut pending = Vec::new();
+        let mut at = WAL_HEADER;
+        while let Some(frame) = wal.get(at..at + FRAME_HEADER + self.page_size) {
+            let (frame_header, page) = frame.split_at(FRAME_HEADER);
+            // Frames from before the log was restarted carry the old salt
+            if frame_header[8..16] != header[16..24] {
+                break;
+            }
+            sum = wal_checksum(sum, &frame_header[..8], big_endian);
+            sum = wal_checksum(sum, page, big_endian);
+            if sum != (be_u32(&frame_header[16..20]), be_u32(&frame_header[20..24])) {
+                break;
+            }
+            pending.push((be_u32(&frame_header[0..4]) as usize, at + FRAME_HEADER));
+            // A commit frame records the database's size after it
+            if be_u32(&frame_header[4..8]) > 0 {
+                self.frames.extend(pending.drain(..));
+            }
+            at += FRAME_HEADER + self.page_size;
+        }
+        self.wal = wal;
+        self
+    }
+
+    /// The table `name`: its columns, and every row as its rowid and values. An `INTEGER
+    /// PRIMARY KEY` column reads as NULL; its value is the rowid.
+    pub fn table(&self, name: &str) -> Result<Table> {
+        let schema = self.scan(1)?;
+        let entry = schema
+            .iter()
+            .map(|(_, columns)| columns)
+            .find(|columns| {
+                columns.first().and_then(Value::as_text) == Some("table")
+                    && columns.get(1).and_then(Value::as_text) == Some(name)
+            })
+            .with_context(|| format!("No table named {}", name))?;
+        let root = match entry.get(3) {
+            Some(Value::Integer(page)) => *page as usize,
+            _ => bail!("Table {} has no root page", name),
+        };
+        let sql = entry.get(4).and_then(Value::as_text).unwrap_or_default();
+        Ok(Table { columns: column_names(sql), rows: self.scan(root)? })
+    }
+
+    /// The rows of the table b-tree rooted at `root`, in rowid order
+    fn scan(&self, root: usize) -> Result<Vec<Row>> {
+        let mut rows = Vec::new();
+        self.scan_page(root, 0, &mut HashSet::new(), &mut rows)?;
+        Ok(rows)
+    }
+
+    fn scan_page(
+        &self,
+        number: usize,
+        depth: usize,
+        visited: &mut HashSet<usize>,
+        rows: &mut Vec<Row>,
+    ) -> Result<()> {
+        if depth > MAX_DEPTH {
+            bail!("Table b-tree is too deep");
+        }
+        // A page linked twice makes a loop, or a cycle through the tree
+        if !visited.insert(number) {
+            bail!("Page {} is linked more than once", number);
+        }
+        let page = self.page(number)?;
+        // Page 1 starts with the database header
+        let header = if number == 1 { 100 } else { 0 };
+        let kind = *page.get(header).context("Truncated page")?;
+        let cells = read_u16(page, header + 3)? as usize;
+        let pointers = header + if kind == INTERIOR_TABLE { 12 } else { 8 };
+        for i in 0..cells {
+            let offset = read_u16(page, pointers + 2 * i)? as usize;
+            match kind {
+                INTERIOR_TABLE => {
+                    let child = read_u32(page, offset)? as usize;
+                    self.scan_page(child, depth + 1, visited, rows)?;
+                }
+                LEAF_TABLE => rows.push(self.leaf_cell(page, offset)?),
+                _ => bail!("Page {} isn't part of a table", number),
+            }
+        }
+        if kind == INTERIOR_TABLE {
+            let right = read_u32(page, header + 8)? as usize;
+            self.scan_page(right, depth + 1, visited, rows)?;
+        }
+        Ok(())
+    }
+
+    /// A leaf cell's rowid and record, following its overflow pages
+    fn leaf_cell(&self, page: &[u8], offset: usize) -> Result<Row> {
+        let (size, n) = read_varint(page, offset)?;
+        let (rowid, m) = read_varint(page, offset + n)?;
+        let start = offset + n + m;
+        let size = size as usize;
+
+        let local = self.local_payload(size);
+        let mut payload = slice(page, start, local)?.to_vec();
+        if local < size {
+            let mut next = read_u32(page, start + local)? as usize;
+            let mut visited = HashSet::new();
+            while payload.len() < size {
+                if next == 0 {
+                    bail!("Overflow chain ends early");
+                }
+                if !visited.insert(next) {
+                    bail!("Overflow chain loops back to page {}", next);
+                }
+                let overflow = self.page(next)?;
+                next = read_u32(overflow, 0)? as usize;
+                let take = (size - payload.len()).min(self.usable - 4);
+                payload.extend_from_slice(slice(overflow, 4, take)?);
+            }
+        }
+        Ok((rowid as i64, decode_record(&payload)?))
+    }
+
+    /// How much of a table leaf cell's payload of `size` bytes is stored on its page
+    fn local_payload(&self, size: usize) -> usize {
+        let max_local = self.usable - 35;
+        if size <= max_local {
+            return size;
+        }
+        let min_local = (self.usable - 12) * 32 / 255 - 23;
+        let local = min_local + (size - min_local) % (self.usable - 4);
+        if local <= max_local {
+            local
+        } else {
+            min_local
+        }
+    }
+
+    fn page(&self, number: usize) -> Result<&[u8]> {
+        if number == 0 {
+            bail!("Page 0 doesn't exist");
+        }
+        if let Some(&at) = self.frames.get(&number) {
+            return slice(&self.wal, at, self.page_size);
+        }
+        slice(&self.data, (number - 1) * self.page_size, self.page_size)
+            .with_context(|| format!("Page {} is past the end of the file", number))
+    }
+}
+
+/// Keywords that start a table constraint rather than a column definition
+const CONSTRAINTS: [&str; 5] = ["CONSTRAINT", "PRIMARY", "UNIQUE", "CHECK", "FOREIGN"];
+
+/// The column names in a `CREATE TABLE` statement, in order
+fn column_names(sql: &str) -> Vec<String> {
+    let (Some(start), Some(end)) = (sql.find('('), sql.rfind(')')) else {
+        return Vec::new();
+    };
+    // Split the definitions on the commas outside parentheses and quotes
+    let mut definitions = vec![String::new()];
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    for c in sql[start + 1..end].chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (None, '[') => quote = Some(']'),
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                definitions.push(String::new());
+                continue;
+            }
+            _ => {}
+        }
+        definitions.last_mut().unwrap().push(c);
+    }
+    definitions
+        .iter()
+        .filter_map(|definition| {
+            let definition = definition.trim_start();
+            let name = match definition.chars().next()? {
+                open @ ('"' | '`' | '[') => {
+                    let close = if open == '[' { ']' } else { open };
+                    definition[1..].split(close).next()?
+                }
+                _ => definition.split_whitespace().next()?,
+            };
+            let constraint = CONSTRAINTS.iter().any(|k| k.eq_ignore_ascii_case(name));
+            (!constraint || definition.starts_with(['"', '`', '['])).then(|| name.to_string())
+        })
+        .collect()
+}
+
+/// A record's columns
+fn decode_record(payload: &[u8]) -> Result<Vec<Value>> {
+    let (header_size, n) = read_varint(payload, 0)?;
+    let header_size = header_size as usize;
+    let mut types = Vec::new();
+    let mut at = n;
+    while at < header_size {
+        let (serial, n) = read_varint(payload, at)?;
+        types.push(serial);
+        at += n;
+    }
+
+    let mut values = Vec::with_capacity(types.len());
+    let mut at = header_size;
+    for serial in types {
+        let int = |bytes: &[u8]| {
+            // Big-endian two's complement, sign-extended from the first byte
+            let first = if bytes[0] & 0x80 != 0 { -1i64 } else { 0 };
+            bytes.iter().fold(first, |acc, b| (acc << 8) | *b as i64)
+        };
+        let (value, len) = match serial {
+            0 => (Value::Null, 0),
+            1..=4 => {
+                let len = serial as usize;
+                (Value::Integer(int(slice(payload, at, len)?)), len)
+            }
+            5 => (Value::Integer(int(slice(payload, at, 6)?)), 6),
+            6 => (Value::Integer(int(slice(payload, at, 8)?)), 8),
+            7 => {
+                let bytes: [u8; 8] = slice(payload, at, 8)?.try_into()?;
+                (Value::Real(f64::from_be_bytes(bytes)), 8)
+            }
+            8 => (Value::Integer(0), 0),
+            9 => (Value::Integer(1), 0),
+            10 | 11 => bail!("Reserved serial type {}", serial),
+            _ if serial % 2 == 0 => {
+                let len = (serial as usize - 12) / 2;
+                (Value::Blob(slice(payload, at, len)?.to_vec()), len)
+            }
+            _ => {
+                let len = (serial as usize - 13) / 2;
+                let text = String::from_utf8_lossy(slice(payload, at, len)?).into_owned();
+                (Value::Text(text), len)
+            }
+        };
+        values.push(value);
+        at += len;
+    }
+    Ok(values)
+}
+
+fn slice(data: &[u8], start: usize, len: usize) -> Result<&[u8]> {
+    start
+        .checked_add(len)
+        .and_then(|end| data.get(start..end))
+        .context("Read past the end of a page")
+}
+
+/// A WAL header field, or a word of a frame header
+fn be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// The WAL's running checksum, continued over `data`: pairs of words in the log's byte order
+fn wal_checksum((mut s0, mut s1): (u32, u32), data: &[u8], big_endian: bool) -> (u32, u32) {
+    let word = |bytes: &[u8]| {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        if big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    };
+    for pair in data.chunks_exact(8) {
+        s0 = s0.wrapping_add(word(&pair[..4])).wrapping_add(s1);
+        s1 = s1.wrapping_add(word(&pair[4..])).wrapping_add(s0);
+    }
+    (s0, s1)
+}
+
+fn read_u16(data: &[u8], at: usize) -> Result<u16> {
+    Ok(u16::from_be_bytes(slice(data, at, 2)?.try_into()?))
+}
+
+fn read_u32(data: &[u8], at: usize) -> Result<u32> {
+    Ok(u32::from_be_bytes(slice(data, at, 4)?.try_into()?))
+}
+
+/// A SQLite varint and its length: big-endian 7-bit groups, the 9th byte all 8 bits
+fn read_varint(data: &[u8], at: usize) -> Result<(u64, usize)> {
+    let mut value = 0u64;
+    for i in 0..9 {
+        let byte = *data.get(at + i).context("Truncated varint")?;
+        if i == 8 {
+            return Ok(((value << 8) | byte as u64, 9));
+        }
+        value = (value << 7) | (byte & 0x7f) as u64;
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varint() {
+        assert_eq!(read_varint(&[0x05], 0).unwrap(), (5, 1));
+        assert_eq!(read_varint(&[0x81, 0x00], 0).unwrap(), (128, 2));
+        assert_eq!(read_varint(&[0xff; 9], 0).unwrap(), (u64::MAX, 9));
+        assert!(read_varint(&[0x81], 0).is_err());
+    }
+
+    #[test]
+    fn test_decode_record() {
+        // Header: size 5, then NULL, an 8-bit int, 3 bytes of text, a 2-byte blob
+        let payload = [5, 0, 1, 19, 16, 0xfe, b'a', b'b', b'c', 0xca, 0xfe];
+        assert_eq!(
+            decode_record(&payload).unwrap(),
+            [
+                Value::Null,
+                Value::Integer(-2),
+                Value::Text("abc".to_string()),
+                Value::Blob(vec![0xca, 0xfe]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_column_names() {
+        let sql = "CREATE TABLE messages (id TEXT PRIMARY KEY, session_id TEXT NOT NULL,
+                   \"role\" TEXT CHECK (role IN ('user', 'assistant')), `parts` TEXT DEFAULT '[]',
+                   created_at INTEGER, FOREIGN KEY (session_id) REFERENCES sessions (id))";
+        assert_eq!(column_names(sql), ["id", "session_id", "role", "parts", "created_at"]);
+        assert!(column_names("").is_empty());
+    }
+
+    #[test]
+    fn test_rejects_other_files() {
+        assert!(Database::from_bytes(b"{\"tabs\": []}".to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_rejects_loops() {
+        // Page 1 is an interior page whose right child is itself
+        let mut data = vec![0; 512];
+        data[..16].copy_from_slice(HEADER_MAGIC);
+        data[16..18].copy_from_slice(&512u16.to_be_bytes());
+        data[100] = INTERIOR_TABLE;
+        data[108..112].copy_from_slice(&1u32.to_be_bytes());
+        let error = Database::from_bytes(data).unwrap().scan(1).unwrap_err();
+        assert_eq!(error.to_string(), "Page 1 is linked more than once");
+        assert!(slice(&[0; 4], usize::MAX, 2).is_err());
+    }
+}
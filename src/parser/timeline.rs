@@ -0,0 +1,102 @@
+//! Timestamps for messages whose log entries don't carry one (shared by every parser)
+
+use crate::session::Message;
+use chrono::{DateTime, Utc};
+use std::path::Path;
+
+/// Dates messages without falling back to the wall clock, which would make an old session
+/// with one malformed entry sort as "just now". An undated message takes the previous
+/// entry's time; messages before the first dated entry take the session's earliest time,
+/// then the file's mtime. Only a session with no time information at all is dated now.
+#[derive(Debug, Default)]
+pub(crate) struct Timeline {
+    previous: Option<DateTime<Utc>>,
+    earliest: Option<DateTime<Utc>>,
+    latest: Option<DateTime<Utc>>,
+    /// Indexes of messages stamped before any entry had a time
+    undated: Vec<usize>,
+}
+
+impl Timeline {
+    /// Record the time of an entry, if it has one
+    pub fn observe(&mut self, timestamp: Option<DateTime<Utc>>) {
+        let Some(timestamp) = timestamp else {
+            return;
+        };
+        self.previous = Some(timestamp);
+        self.earliest = Some(self.earliest.map_or(timestamp, |t| t.min(timestamp)));
+        self.latest = Some(self.latest.map_or(timestamp, |t| t.max(timestamp)));
+    }
+
+    /// Time for the message about to be pushed onto `messages`
+    pub fn stamp(&mut self, messages: &[Message]) -> DateTime<Utc> {
+        self.previous.unwrap_or_else(|| {
+            self.undated.push(messages.len());
+            DateTime::UNIX_EPOCH
+        })
+    }
+
+    /// Date the messages that came before any time was known, and return the session's
+    /// timestamp: its latest known time, else the file's mtime
+    pub fn finish(self, messages: &mut [Message], path: &Path) -> DateTime<Utc> {
+        let mtime = || {
+            std::fs::metadata(path)
+                .and_then(|m| m.modified())
+                .ok()
+                .map(DateTime::<Utc>::from)
+        };
+        if !self.undated.is_empty() {
+            let fallback = self.earliest.or_else(mtime).unwrap_or_else(Utc::now);
+            for i in self.undated {
+                messages[i].timestamp = fallback;
+            }
+        }
+        self.latest.or_else(mtime).unwrap_or_else(Utc::now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::Role;
+    use chrono::TimeZone;
+
+    fn push(timeline: &mut Timeline, messages: &mut Vec<Message>, time: Option<DateTime<Utc>>) {
+        timeline.observe(time);
+        let timestamp = timeline.stamp(messages);
+        messages.push(Message {
+            role: Role::User,
+            content: "x".to_string(),
+            timestamp,
+            tool_calls: Vec::new(),
+        });
+    }
+
+    #[test]
+    fn test_undated_messages_borrow_nearby_times() {
+        let t1 = Utc.with_ymd_and_hms(2024, 2, 1, 9, 0, 0).unwrap();
+        let t2 = Utc.with_ymd_and_hms(2024, 2, 1, 10, 0, 0).unwrap();
+        let mut timeline = Timeline::default();
+        let mut messages = Vec::new();
+        for time in [None, Some(t1), None, Some(t2), None] {
+            push(&mut timeline, &mut messages, time);
+        }
+
+        let session_time = timeline.finish(&mut messages, Path::new("/nonexistent"));
+        let times: Vec<_> = messages.iter().map(|m| m.timestamp).collect();
+        assert_eq!(times, [t1, t1, t1, t2, t2]);
+        assert_eq!(session_time, t2);
+    }
+
+    #[test]
+    fn test_undated_session_uses_file_mtime() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let mtime: DateTime<Utc> = file.as_file().metadata().unwrap().modified().unwrap().into();
+        let mut timeline = Timeline::default();
+        let mut messages = Vec::new();
+        push(&mut timeline, &mut messages, None);
+
+        assert_eq!(timeline.finish(&mut messages, file.path()), mtime);
+        assert_eq!(messages[0].timestamp, mtime);
+    }
+}
@@ -0,0 +1,139 @@
+//! Tool calls paired with their results (shared by parsers whose logs record them, as
+//! tool_use and tool_result blocks or otherwise)
+
+use crate::session::{Message, ToolCall, ToolStatus};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Where each call waiting for its result is, by call ID: a message, and the call in it
+#[derive(Debug, Default)]
+pub(crate) struct OpenCalls(HashMap<String, (usize, usize)>);
+
+impl OpenCalls {
+    /// Add `call` to the latest message, the one it followed. A call before any message is
+    /// dropped.
+    pub fn push(&mut self, id: &str, call: ToolCall, messages: &mut [Message]) {
+        let Some(last) = messages.len().checked_sub(1) else {
+            return;
+        };
+        self.0.insert(id.to_string(), (last, messages[last].tool_calls.len()));
+        messages[last].tool_calls.push(call);
+    }
+
+    /// The call `id`, to record its result on
+    pub fn get<'m>(&self, id: &str, messages: &'m mut [Message]) -> Option<&'m mut ToolCall> {
+        let &(message, call) = self.0.get(id)?;
+        messages.get_mut(message)?.tool_calls.get_mut(call)
+    }
+
+    /// Add the calls of the tool_use blocks in a message content array
+    pub fn push_tool_uses(&mut self, content: &Value, messages: &mut [Message]) {
+        for block in blocks(content, "tool_use") {
+            let (Some(id), Some(name)) = (str_field(block, "id"), str_field(block, "name")) else {
+                continue;
+            };
+            let input = block.get("input").map(input_text).unwrap_or_default();
+            self.push(id, ToolCall::pending(name, input), messages);
+        }
+    }
+
+    /// Record the results in the tool_result blocks of a message content array on their calls
+    pub fn record_tool_results(&self, content: &Value, messages: &mut [Message]) {
+        for block in blocks(content, "tool_result") {
+            let Some(call) = str_field(block, "tool_use_id").and_then(|id| self.get(id, messages))
+            else {
+                continue;
+            };
+            let failed = block.get("is_error").and_then(Value::as_bool) == Some(true);
+            call.status = if failed { ToolStatus::Error } else { ToolStatus::Ok };
+            call.output = Some(block.get("content").map(result_text).unwrap_or_default());
+        }
+    }
+}
+
+fn blocks<'c>(content: &'c Value, kind: &'c str) -> impl Iterator<Item = &'c Value> {
+    content
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(move |block| str_field(block, "type") == Some(kind))
+}
+
+fn str_field<'v>(value: &'v Value, key: &str) -> Option<&'v str> {
+    value.get(key)?.as_str()
+}
+
+/// What a tool was given, shortly: the command run, the file or pattern worked on, or else
+/// the whole input as JSON
+pub(crate) fn input_text(input: &Value) -> String {
+    ["command", "file_path", "filePath", "notebook_path", "path", "pattern", "url", "query"]
+        .iter()
+        .find_map(|key| str_field(input, key))
+        .map(str::to_string)
+        .unwrap_or_else(|| input.to_string())
+}
+
+/// A tool_result's content: text, or an array whose text blocks are kept
+fn result_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        _ => content
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|block| str_field(block, "text"))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::Role;
+    use chrono::Utc;
+    use serde_json::json;
+
+    #[test]
+    fn test_tool_uses_paired_with_results() {
+        let mut messages = vec![Message {
+            role: Role::Assistant,
+            content: "Checking.".to_string(),
+            timestamp: Utc::now(),
+            tool_calls: Vec::new(),
+        }];
+        let mut calls = OpenCalls::default();
+        calls.push_tool_uses(
+            &json!([
+                {"type": "text", "text": "Checking."},
+                {"type": "tool_use", "id": "t1", "name": "Bash", "input": {"command": "ls"}},
+                {"type": "tool_use", "id": "t2", "name": "Grep", "input": {"pattern": "TODO"}},
+                {"type": "tool_use", "id": "t3", "name": "Think", "input": {"thought": "x"}}
+            ]),
+            &mut messages,
+        );
+        calls.record_tool_results(
+            &json!([
+                {"type": "tool_result", "tool_use_id": "t1", "content": "src\ntests"},
+                {"type": "tool_result", "tool_use_id": "t2", "is_error": true,
+                 "content": [{"type": "text", "text": "regex parse error"}]},
+                {"type": "tool_result", "tool_use_id": "unknown", "content": "ignored"}
+            ]),
+            &mut messages,
+        );
+
+        let calls: Vec<_> = messages[0]
+            .tool_calls
+            .iter()
+            .map(|c| (c.name.as_str(), c.input.as_str(), c.status, c.output.as_deref()))
+            .collect();
+        assert_eq!(
+            calls,
+            [
+                ("Bash", "ls", ToolStatus::Ok, Some("src\ntests")),
+                ("Grep", "TODO", ToolStatus::Error, Some("regex parse error")),
+                ("Think", r#"{"thought":"x"}"#, ToolStatus::Pending, None),
+            ]
+        );
+    }
+}
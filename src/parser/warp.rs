@@ -0,0 +1,242 @@
+//! Warp, the terminal, keeps its agent mode conversations in `warp.sqlite`, a SQLite database
+//! in its state folder. Each row of `ai_queries` is one exchange: `input` is a JSON list of
+//! blocks, of which `Query` holds what was asked, `working_directory` is the folder of the
+//! shell block it was asked from, and `output_status` says how it ended, with the agent's
+//! reply when it `Finished`. Each `conversation_id` is a session, addressed as
+//! `warp.sqlite/<conversation id>` (see [`super::entry_path`]).
+//!
+//! The format is Warp's own and changes as it does, so reading it is best effort. A database
+//! without the columns below isn't guessed at: it's reported as a file that couldn't be indexed.
+
+use crate::config;
+use crate::session::{Message, Role, Session, SessionSource};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::sqlite::{self, Table};
+use super::{join_consecutive_messages, timeline::Timeline, SessionParser};
+
+/// Warp's state folder, under the home
+#[cfg(target_os = "macos")]
+pub const DATA_DIR: &str = concat!(
+    "Library/Group Containers/2BBY89MBSN.dev.warp/",
+    "Library/Application Support/dev.warp.Warp-Stable"
+);
+#[cfg(windows)]
+pub const DATA_DIR: &str = "AppData/Local/warp/Warp/data";
+#[cfg(not(any(target_os = "macos", windows)))]
+pub const DATA_DIR: &str = ".local/state/warp-terminal";
+
+/// The database in the state folder
+pub const DATABASE_FILE: &str = "warp.sqlite";
+
+const TABLE: &str = "ai_queries";
+
+/// The columns read, without which the schema is taken to be one this doesn't know
+const COLUMNS: [&str; 5] =
+    ["conversation_id", "exchange_id", "start_ts", "input", "working_directory"];
+
+pub struct WarpParser;
+
+impl SessionParser for WarpParser {
+    fn can_parse(path: &Path) -> bool {
+        // The database itself stands in for its sessions when it can't be read
+        let database = match path.file_name().is_some_and(|name| name == DATABASE_FILE) {
+            true => Some(path),
+            false => path.parent(),
+        };
+        database.is_some_and(|database| {
+            database.file_name().is_some_and(|name| name == DATABASE_FILE)
+                && database.parent().is_some_and(|dir| dir.ends_with(DATA_DIR))
+        })
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        if path.is_file() {
+            read_queries(path)?;
+            bail!("Warp's database can be read again; indexed on the next pass");
+        }
+        let (database, conversation_id) =
+            super::split_entry(path).context("Not a conversation in a database")?;
+        let table = read_queries(database)?;
+        let mut rows: Vec<_> = table
+            .rows
+            .iter()
+            .filter(|row| text(&table, row, "conversation_id") == Some(&conversation_id))
+            .collect();
+        anyhow::ensure!(!rows.is_empty(), "Conversation is no longer in the database");
+        rows.sort_by_key(|row| (text(&table, row, "start_ts"), text(&table, row, "exchange_id")));
+
+        let mut timeline = Timeline::default();
+        let mut messages: Vec<Message> = Vec::new();
+        let mut cwd: Option<String> = None;
+        for row in rows {
+            timeline.observe(text(&table, row, "start_ts").and_then(parse_time));
+            let input = json(&table, row, "input");
+            if cwd.is_none() {
+                cwd = text(&table, row, "working_directory")
+                    .filter(|dir| !dir.is_empty())
+                    .map(str::to_string)
+                    .or_else(|| context_dir(&input));
+            }
+            let exchange = [
+                (Role::User, query_text(&input)),
+                (Role::Assistant, reply(&json(&table, row, "output_status"))),
+            ];
+            for (role, content) in exchange {
+                if !content.is_empty() {
+                    let timestamp = timeline.stamp(&messages);
+                    messages.push(Message { role, content, timestamp, tool_calls: Vec::new() });
+                }
+            }
+        }
+
+        let timestamp = timeline.finish(&mut messages, database);
+
+        Ok(Session {
+            id: conversation_id,
+            source: SessionSource::Warp,
+            file_path: path.to_path_buf(),
+            cwd: cwd.unwrap_or_else(|| ".".to_string()),
+            git_branch: None,
+            timestamp,
+            messages: join_consecutive_messages(messages, config::get().join_messages),
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: None,
+        })
+    }
+}
+
+/// A session for each conversation in the database in `dir`. A database that can't be read,
+/// or whose schema isn't known, is returned itself, so that indexing reports it.
+pub(super) fn discover(dir: &Path) -> Vec<PathBuf> {
+    let database = dir.join(DATABASE_FILE);
+    if !database.is_file() {
+        return Vec::new();
+    }
+    let Ok(table) = read_queries(&database) else {
+        return vec![database];
+    };
+    let conversations: BTreeSet<&str> = table
+        .rows
+        .iter()
+        .filter_map(|row| text(&table, row, "conversation_id"))
+        .collect();
+    conversations
+        .into_iter()
+        .map(|id| super::entry_path(&database, id))
+        .collect()
+}
+
+/// The exchanges in the database, once it's known to have the columns they're read from
+fn read_queries(database: &Path) -> Result<Arc<Table>> {
+    let table = sqlite::read_table(database, TABLE).with_context(|| {
+        format!("Unknown Warp database schema, skipped (no {} table)", TABLE)
+    })?;
+    let missing = COLUMNS
+        .iter()
+        .find(|column| !table.columns.iter().any(|c| c.eq_ignore_ascii_case(column)));
+    if let Some(column) = missing {
+        bail!("Unknown Warp database schema, skipped (no {} in {})", column, TABLE);
+    }
+    Ok(table)
+}
+
+fn text<'a>(table: &Table, row: &'a sqlite::Row, column: &str) -> Option<&'a str> {
+    table.get(row, column)?.as_text()
+}
+
+fn json(table: &Table, row: &sqlite::Row, column: &str) -> Value {
+    text(table, row, column)
+        .and_then(|text| serde_json::from_str(text).ok())
+        .unwrap_or_default()
+}
+
+/// What was asked: the text of the `Query` blocks of an exchange's input
+fn query_text(input: &Value) -> String {
+    blocks(input)
+        .filter_map(|block| block.get("Query")?.get("text")?.as_str())
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The folder of the shell block a query was asked from, from the context sent with it
+fn context_dir(input: &Value) -> Option<String> {
+    blocks(input)
+        .filter_map(|block| block.get("Query")?.get("context")?.as_array())
+        .flatten()
+        .filter_map(|context| context.get("Directory")?.get("pwd")?.as_str())
+        .find(|dir| !dir.is_empty())
+        .map(str::to_string)
+}
+
+/// The agent's reply, once it finished: a string, or a list of `Text` blocks
+fn reply(output: &Value) -> String {
+    let Some(output) = output.get("Finished").and_then(|finished| finished.get("output")) else {
+        return String::new();
+    };
+    match output {
+        Value::String(text) => text.trim().to_string(),
+        _ => blocks(output)
+            .filter_map(|block| block.get("Text")?.get("text")?.as_str())
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+fn blocks(value: &Value) -> impl Iterator<Item = &Value> {
+    value.as_array().into_iter().flatten()
+}
+
+/// `start_ts`: UTC, without an offset
+fn parse_time(text: &str) -> Option<DateTime<Utc>> {
+    let naive = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f"))
+        .ok()?;
+    Some(naive.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_time() {
+        let time = parse_time("2025-06-01 09:00:00.250000").unwrap();
+        assert_eq!(time.to_rfc3339(), "2025-06-01T09:00:00.250+00:00");
+        assert!(parse_time("2025-06-01T09:00:00").is_some());
+        assert_eq!(parse_time("yesterday"), None);
+    }
+
+    #[test]
+    fn test_exchange_blocks() {
+        let input = json!([{"Query": {"text": " why is the build slow? ", "context": [
+            {"SelectedText": "cargo build"}, {"Directory": {"pwd": "/w/api", "home_dir": "/h"}}
+        ]}}]);
+        assert_eq!(query_text(&input), "why is the build slow?");
+        assert_eq!(context_dir(&input).as_deref(), Some("/w/api"));
+
+        let finished = json!({"Finished": {"output": [{"Text": {"text": "Enable sccache."}}]}});
+        assert_eq!(reply(&finished), "Enable sccache.");
+        assert_eq!(reply(&json!({"Finished": {"output": "Done."}})), "Done.");
+        assert_eq!(reply(&json!("Cancelled")), "");
+    }
+
+    #[test]
+    fn test_can_parse() {
+        let database = Path::new("/h").join(DATA_DIR).join(DATABASE_FILE);
+        assert!(WarpParser::can_parse(&database.join("c-1")));
+        assert!(WarpParser::can_parse(&database));
+        assert!(!WarpParser::can_parse(Path::new("/h/other/warp.sqlite/c-1")));
+    }
+}
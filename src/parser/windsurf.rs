@@ -0,0 +1,153 @@
+//! Windsurf, a VS Code fork, keeps Cascade's chats with each workspace's state:
+//! `User/workspaceStorage/<hash>/cascade.json` in its data folder, next to the
+//! `workspace.json` naming the folder. Each of its `trajectories` is a chat with a list of
+//! `messages`. Each chat is a session, addressed as `cascade.json/<trajectory id>` (see
+//! [`super::entry_path`]); Windsurf has no way to reopen one from outside.
+
+use crate::config;
+use crate::session::{Message, Role, Session, SessionSource};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use super::{join_consecutive_messages, timeline::Timeline, SessionParser};
+
+/// The workspace folders, under the home
+#[cfg(target_os = "macos")]
+pub const WORKSPACES_DIR: &str = "Library/Application Support/Windsurf/User/workspaceStorage";
+#[cfg(windows)]
+pub const WORKSPACES_DIR: &str = "AppData/Roaming/Windsurf/User/workspaceStorage";
+#[cfg(not(any(target_os = "macos", windows)))]
+pub const WORKSPACES_DIR: &str = ".config/Windsurf/User/workspaceStorage";
+
+/// Each workspace's Cascade state
+pub const STATE_FILE: &str = "cascade.json";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct CascadeState {
+    trajectories: Vec<Trajectory>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Trajectory {
+    trajectory_id: String,
+    messages: Vec<CascadeMessage>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct CascadeMessage {
+    role: String,
+    text: String,
+    /// RFC 3339
+    created_at: Option<String>,
+}
+
+pub struct WindsurfParser;
+
+impl SessionParser for WindsurfParser {
+    fn can_parse(path: &Path) -> bool {
+        path.parent().is_some_and(|state| {
+            state.file_name().is_some_and(|name| name == STATE_FILE)
+                && state.to_str().is_some_and(|s| {
+                    s.contains("Windsurf/User/workspaceStorage")
+                        || s.contains("Windsurf\\User\\workspaceStorage")
+                })
+        })
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        let (state_file, trajectory_id) =
+            super::split_entry(path).context("Not a chat in a workspace's state")?;
+        let trajectory = read_state(state_file)?
+            .trajectories
+            .into_iter()
+            .find(|t| t.trajectory_id == trajectory_id)
+            .context("Chat is no longer in the workspace's state")?;
+
+        let mut timeline = Timeline::default();
+        let mut messages: Vec<Message> = Vec::new();
+        for message in &trajectory.messages {
+            timeline.observe(message.created_at.as_deref().and_then(parse_time));
+            // Tool calls and their output aren't part of the conversation
+            let role = match message.role.as_str() {
+                "user" => Role::User,
+                "assistant" => Role::Assistant,
+                _ => continue,
+            };
+            let content = message.text.trim();
+            if !content.is_empty() {
+                let timestamp = timeline.stamp(&messages);
+                messages.push(Message {
+                    role,
+                    content: content.to_string(),
+                    timestamp,
+                    tool_calls: Vec::new(),
+                });
+            }
+        }
+
+        let dir = state_file.parent().unwrap_or(Path::new("."));
+        let timestamp = timeline.finish(&mut messages, state_file);
+
+        Ok(Session {
+            id: trajectory_id,
+            source: SessionSource::Windsurf,
+            file_path: path.to_path_buf(),
+            cwd: super::cursor::workspace_folder(dir).unwrap_or_else(|| ".".to_string()),
+            git_branch: None,
+            timestamp,
+            messages: join_consecutive_messages(messages, config::get().join_messages),
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: None,
+        })
+    }
+}
+
+/// A session for each chat in each workspace's state under `dir`. A state file that can't
+/// be read right now (Windsurf may be rewriting it) yields none this time.
+pub(super) fn discover(dir: &Path) -> Vec<PathBuf> {
+    let Ok(workspaces) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files = Vec::new();
+    for workspace in workspaces.flatten() {
+        let state_file = workspace.path().join(STATE_FILE);
+        let Ok(state) = read_state(&state_file) else {
+            continue;
+        };
+        for trajectory in state.trajectories.iter().filter(|t| !t.trajectory_id.is_empty()) {
+            files.push(super::entry_path(&state_file, &trajectory.trajectory_id));
+        }
+    }
+    files
+}
+
+fn read_state(path: &Path) -> Result<CascadeState> {
+    let file = File::open(path).context("Failed to open file")?;
+    serde_json::from_reader(BufReader::new(file)).context("Failed to parse Cascade state")
+}
+
+fn parse_time(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text).ok().map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_can_parse() {
+        let state = Path::new("/h/.config/Windsurf/User/workspaceStorage/ab12/cascade.json");
+        assert!(WindsurfParser::can_parse(&state.join("7c0e")));
+        assert!(!WindsurfParser::can_parse(state));
+        let cursor = Path::new("/h/.config/Cursor/User/workspaceStorage/ab12/cascade.json");
+        assert!(!WindsurfParser::can_parse(&cursor.join("7c0e")));
+    }
+}
@@ -0,0 +1,188 @@
+//! Zed saves each assistant panel conversation as `<summary> - <n>.zed.json` in its
+//! `conversations` folder. The conversation is one buffer, `text`; each of `messages` starts at
+//! a byte offset into it and runs to where the next begins. Its role is in the message's
+//! `metadata`, or in a `message_metadata` map by message id in older versions. Messages carry
+//! Lamport clocks rather than times, so they're dated by the file.
+
+use crate::config;
+use crate::session::{Message, Role, Session, SessionSource};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use super::{join_consecutive_messages, timeline::Timeline, SessionParser};
+
+/// The conversations folder, under the home
+#[cfg(target_os = "macos")]
+pub const CONVERSATIONS_DIR: &str = "Library/Application Support/Zed/conversations";
+#[cfg(windows)]
+pub const CONVERSATIONS_DIR: &str = "AppData/Roaming/Zed/conversations";
+#[cfg(not(any(target_os = "macos", windows)))]
+pub const CONVERSATIONS_DIR: &str = ".config/zed/conversations";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Conversation {
+    id: Option<String>,
+    text: String,
+    messages: Vec<ZedMessage>,
+    /// Older versions: message id -> metadata
+    message_metadata: HashMap<String, Metadata>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ZedMessage {
+    /// `{replica_id, value}`, or a plain number in older versions
+    id: Value,
+    /// Byte offset into the text
+    start: usize,
+    metadata: Option<Metadata>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Metadata {
+    role: String,
+}
+
+pub struct ZedParser;
+
+impl SessionParser for ZedParser {
+    fn can_parse(path: &Path) -> bool {
+        let in_zed = path.to_str().is_some_and(|s| {
+            let s = s.to_lowercase();
+            s.contains("zed/conversations") || s.contains("zed\\conversations")
+        });
+        in_zed && path.extension().is_some_and(|e| e == "json")
+    }
+
+    fn parse_file(path: &Path) -> Result<Session> {
+        let file = File::open(path).context("Failed to open file")?;
+        let conversation: Conversation = serde_json::from_reader(BufReader::new(file))
+            .context("Failed to parse Zed conversation")?;
+
+        let mut timeline = Timeline::default();
+        let mut messages: Vec<Message> = Vec::new();
+        for (i, message) in conversation.messages.iter().enumerate() {
+            let metadata = message.metadata.as_ref().or_else(|| {
+                conversation.message_metadata.get(&message_key(&message.id))
+            });
+            let role = match metadata.map(|m| m.role.as_str()) {
+                Some("user") => Role::User,
+                Some("assistant") => Role::Assistant,
+                _ => continue,
+            };
+            let end = conversation
+                .messages
+                .get(i + 1)
+                .map_or(conversation.text.len(), |next| next.start);
+            let content = conversation.text.get(message.start..end).unwrap_or_default().trim();
+            if !content.is_empty() {
+                let timestamp = timeline.stamp(&messages);
+                messages.push(Message {
+                    role,
+                    content: content.to_string(),
+                    timestamp,
+                    tool_calls: Vec::new(),
+                });
+            }
+        }
+
+        let session_id = conversation.id.unwrap_or_else(|| {
+            let name = path.file_name().and_then(|s| s.to_str()).unwrap_or("unknown");
+            name.strip_suffix(".zed.json").unwrap_or(name).to_string()
+        });
+
+        let timestamp = timeline.finish(&mut messages, path);
+
+        Ok(Session {
+            id: session_id,
+            source: SessionSource::Zed,
+            file_path: path.to_path_buf(),
+            // Conversations aren't tied to a project
+            cwd: ".".to_string(),
+            git_branch: None,
+            timestamp,
+            messages: join_consecutive_messages(messages, config::get().join_messages),
+            files_touched: Vec::new(),
+            usage: Vec::new(),
+            title: None,
+        })
+    }
+}
+
+/// A message id as the key of `message_metadata`
+fn message_key(id: &Value) -> String {
+    match id.get("value").unwrap_or(id) {
+        Value::String(id) => id.clone(),
+        id => id.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_conversation() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let dir = temp.path().join(".config/zed/conversations");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Flaky test - 1.zed.json");
+        let text = "You are an assistant.\nwhy is this test flaky?\nIt sleeps — fake time.\n";
+        let user = text.find("why").unwrap();
+        let assistant = text.find("It sleeps").unwrap();
+        let conversation = json!({
+            "id": "9f1c2b7e-ctx",
+            "zed": "context",
+            "version": "0.4.0",
+            "text": text,
+            "summary": "Flaky test",
+            "messages": [
+                {"id": {"replica_id": 0, "value": 0}, "start": 0,
+                 "metadata": {"role": "system", "status": "Done"}},
+                {"id": {"replica_id": 0, "value": 1}, "start": user,
+                 "metadata": {"role": "user", "status": "Done"}},
+                {"id": {"replica_id": 0, "value": 2}, "start": assistant,
+                 "metadata": {"role": "assistant", "status": "Done"}}
+            ]
+        });
+        std::fs::write(&path, conversation.to_string()).unwrap();
+        assert!(ZedParser::can_parse(&path));
+
+        let session = ZedParser::parse_file(&path).unwrap();
+        assert_eq!(session.id, "9f1c2b7e-ctx");
+        assert_eq!(session.source, SessionSource::Zed);
+        let contents: Vec<_> =
+            session.messages.iter().map(|m| (m.role, m.content.as_str())).collect();
+        assert_eq!(
+            contents,
+            [(Role::User, "why is this test flaky?"), (Role::Assistant, "It sleeps — fake time.")]
+        );
+    }
+
+    #[test]
+    fn test_parse_older_conversation() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let dir = temp.path().join(".config/zed/conversations");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Regex - 2.zed.json");
+        let conversation = json!({
+            "text": "match digits\n\\d+\n",
+            "messages": [{"id": 0, "start": 0}, {"id": 1, "start": 13}],
+            "message_metadata": {"0": {"role": "user"}, "1": {"role": "assistant"}}
+        });
+        std::fs::write(&path, conversation.to_string()).unwrap();
+
+        let session = ZedParser::parse_file(&path).unwrap();
+        assert_eq!(session.id, "Regex - 2");
+        let contents: Vec<_> = session.messages.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, ["match digits", "\\d+"]);
+    }
+}
@@ -0,0 +1,135 @@
+//! Model prices for estimating what sessions cost
+
+use crate::session::ModelUsage;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Price {
+    pub input: f64,
+    pub output: f64,
+    #[serde(default)]
+    pub cache_read: f64,
+    #[serde(default)]
+    pub cache_write: f64,
+}
+
+impl Price {
+    const fn new(input: f64, output: f64, cache_read: f64, cache_write: f64) -> Self {
+        Self { input, output, cache_read, cache_write }
+    }
+
+    /// Cost of `usage` in USD
+    pub fn cost(&self, usage: &ModelUsage) -> f64 {
+        (usage.input_tokens as f64 * self.input
+            + usage.output_tokens as f64 * self.output
+            + usage.cache_read_tokens as f64 * self.cache_read
+            + usage.cache_write_tokens as f64 * self.cache_write)
+            / 1_000_000.0
+    }
+}
+
+/// Built-in list prices, matched by longest model-name prefix
+const PRICES: &[(&str, Price)] = &[
+    ("claude-opus-4-5", Price::new(5.0, 25.0, 0.5, 6.25)),
+    ("claude-opus-4", Price::new(15.0, 75.0, 1.5, 18.75)),
+    ("claude-sonnet-4", Price::new(3.0, 15.0, 0.3, 3.75)),
+    ("claude-haiku-4-5", Price::new(1.0, 5.0, 0.1, 1.25)),
+    ("claude-3-opus", Price::new(15.0, 75.0, 1.5, 18.75)),
+    ("claude-3-7-sonnet", Price::new(3.0, 15.0, 0.3, 3.75)),
+    ("claude-3-5-sonnet", Price::new(3.0, 15.0, 0.3, 3.75)),
+    ("claude-3-5-haiku", Price::new(0.8, 4.0, 0.08, 1.0)),
+    ("gpt-5-nano", Price::new(0.05, 0.4, 0.005, 0.0)),
+    ("gpt-5-mini", Price::new(0.25, 2.0, 0.025, 0.0)),
+    ("gpt-5", Price::new(1.25, 10.0, 0.125, 0.0)),
+    ("gpt-4.1-mini", Price::new(0.4, 1.6, 0.1, 0.0)),
+    ("gpt-4.1", Price::new(2.0, 8.0, 0.5, 0.0)),
+    ("o4-mini", Price::new(1.1, 4.4, 0.275, 0.0)),
+    ("o3", Price::new(2.0, 8.0, 0.5, 0.0)),
+];
+
+/// Price for a model, if known: from `[pricing]` in the config, or else the built-in list.
+/// Provider prefixes ("anthropic/claude-…") are ignored.
+pub fn price(model: &str) -> Option<Price> {
+    price_with(&crate::config::get().pricing, model)
+}
+
+/// Price for a model from `overrides`, or else the built-in list
+fn price_with(overrides: &BTreeMap<String, Price>, model: &str) -> Option<Price> {
+    let model = model.rsplit('/').next().unwrap_or(model);
+    let overrides = overrides.iter().map(|(prefix, price)| (prefix.as_str(), *price));
+    longest_prefix(overrides, model).or_else(|| longest_prefix(PRICES.iter().copied(), model))
+}
+
+/// The price whose prefix is the longest one `model` starts with
+fn longest_prefix<'a>(
+    prices: impl Iterator<Item = (&'a str, Price)>,
+    model: &str,
+) -> Option<Price> {
+    prices
+        .filter(|(prefix, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, price)| price)
+}
+
+/// Total cost of a session's usage, or None if there is no usage or a model has no known price
+pub fn cost(usage: &[ModelUsage]) -> Option<f64> {
+    if usage.is_empty() {
+        return None;
+    }
+    usage
+        .iter()
+        .map(|u| price(&u.model).map(|p| p.cost(u)))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(model: &str, input: u64, output: u64, cache_read: u64, cache_write: u64) -> ModelUsage {
+        ModelUsage {
+            model: model.to_string(),
+            input_tokens: input,
+            output_tokens: output,
+            cache_read_tokens: cache_read,
+            cache_write_tokens: cache_write,
+        }
+    }
+
+    #[test]
+    fn test_price_longest_prefix() {
+        assert_eq!(price("claude-opus-4-5-20251101").unwrap().input, 5.0);
+        assert_eq!(price("claude-opus-4-1-20250805").unwrap().input, 15.0);
+        assert_eq!(price("gpt-5-mini").unwrap().input, 0.25);
+        assert_eq!(price("gpt-5-codex").unwrap().input, 1.25);
+        assert_eq!(price("anthropic/claude-sonnet-4-20250514").unwrap().output, 15.0);
+        assert!(price("llama-3").is_none());
+    }
+
+    #[test]
+    fn test_price_overrides() {
+        let sonnet = Price::new(2.0, 10.0, 0.0, 0.0);
+        let overrides = BTreeMap::from([
+            ("claude-sonnet-4-5".to_string(), sonnet),
+            ("llama".to_string(), Price::new(0.1, 0.1, 0.0, 0.0)),
+        ]);
+        assert_eq!(price_with(&overrides, "claude-sonnet-4-5-20250929"), Some(sonnet));
+        assert_eq!(price_with(&overrides, "llama-3").unwrap().input, 0.1);
+        // Models no override matches keep the built-in price
+        assert_eq!(price_with(&overrides, "claude-sonnet-4-20250514").unwrap().input, 3.0);
+        assert_eq!(price_with(&overrides, "gpt-5").unwrap().input, 1.25);
+    }
+
+    #[test]
+    fn test_cost() {
+        // 1M input at $3 + 100k output at $15 + 2M cache reads at $0.30 + 200k writes at $3.75
+        let sonnet = usage("claude-sonnet-4-5", 1_000_000, 100_000, 2_000_000, 200_000);
+        let total = cost(&[sonnet]).unwrap();
+        assert!((total - (3.0 + 1.5 + 0.6 + 0.75)).abs() < 1e-9);
+
+        assert_eq!(cost(&[]), None);
+        assert_eq!(cost(&[usage("mystery-model", 1, 1, 0, 0)]), None);
+    }
+}
@@ -0,0 +1,317 @@
+//! Sessions on other machines (`[[remote]]` in the config), mirrored over SSH. Each host's
+//! mirror in the cache is laid out like its home directory, so discovery scans it like an
+//! extra home, and the sessions found there resume with `ssh -t <host>`. Syncing is
+//! incremental: a file is fetched again only when its size or modification time changed.
+
+use crate::index::cache;
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Files fetched per round trip
+const FETCH_BATCH: usize = 100;
+
+/// A file on the remote, as [`Transport::list`] reports it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteFile {
+    /// Path relative to the remote home
+    pub path: String,
+    pub size: u64,
+    /// Modification time in Unix seconds
+    pub mtime: i64,
+}
+
+/// How a mirror reads the remote: [`Ssh`], or a plain directory in tests
+pub trait Transport {
+    /// The files under `dir` (relative to the remote home), none if it doesn't exist
+    fn list(&self, dir: &str) -> Result<Vec<RemoteFile>>;
+    /// The contents of these files, in the same order
+    fn fetch(&self, paths: &[String]) -> Result<Vec<Vec<u8>>>;
+}
+
+/// A remote reached with the `ssh` binary, so `~/.ssh/config`, keys, and agents apply. It
+/// never prompts: a host that needs a password fails to sync.
+pub struct Ssh {
+    host: String,
+}
+
+impl Ssh {
+    pub fn new(host: &str) -> Self {
+        Self { host: host.to_string() }
+    }
+
+    /// Run `command` in the remote's home and return its output
+    fn run(&self, command: &str) -> Result<Vec<u8>> {
+        let output = Command::new("ssh")
+            .args(["-o", "BatchMode=yes", "-o", "ConnectTimeout=5", &self.host, command])
+            .stdin(Stdio::null())
+            .output()
+            .context("Couldn't run ssh")?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("ssh {} failed: {}", self.host, stderr.trim());
+        }
+        Ok(output.stdout)
+    }
+}
+
+impl Transport for Ssh {
+    fn list(&self, dir: &str) -> Result<Vec<RemoteFile>> {
+        // GNU find on the remote: size, mtime, and path relative to the home on each line
+        let dir = shell_words::quote(dir);
+        let command =
+            format!("if [ -d {dir} ]; then find {dir} -type f -printf '%s %T@ %p\\n'; fi");
+        let output = String::from_utf8_lossy(&self.run(&command)?).into_owned();
+        output.lines().map(parse_listing).collect()
+    }
+
+    fn fetch(&self, paths: &[String]) -> Result<Vec<Vec<u8>>> {
+        let command = format!("tar cf - -- {}", shell_words::join(paths));
+        let archive = self.run(&command)?;
+        let mut files = std::collections::HashMap::new();
+        for entry in tar::Archive::new(archive.as_slice()).entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.to_string_lossy().into_owned();
+            let mut contents = Vec::new();
+            std::io::Read::read_to_end(&mut entry, &mut contents)?;
+            files.insert(path, contents);
+        }
+        paths
+            .iter()
+            .map(|path| files.remove(path).with_context(|| format!("{} wasn't sent", path)))
+            .collect()
+    }
+}
+
+/// One line of the remote's `find` output: `<size> <mtime> <path>`
+fn parse_listing(line: &str) -> Result<RemoteFile> {
+    let mut parts = line.splitn(3, ' ');
+    let (Some(size), Some(mtime), Some(path)) = (parts.next(), parts.next(), parts.next()) else {
+        anyhow::bail!("Unexpected file listing: {}", line);
+    };
+    let mtime: f64 = mtime.parse().with_context(|| format!("Bad mtime in: {}", line))?;
+    Ok(RemoteFile {
+        path: path.to_string(),
+        size: size.parse().with_context(|| format!("Bad size in: {}", line))?,
+        mtime: mtime.floor() as i64,
+    })
+}
+
+/// What a sync changed in the mirror
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub fetched: usize,
+    pub removed: usize,
+}
+
+/// Bring `dir` in `mirror` up to date with `dir` on the remote: fetch new and changed files,
+/// and delete the mirrored ones the remote no longer has
+pub fn sync(transport: &dyn Transport, dir: &str, mirror: &Path) -> Result<SyncReport> {
+    let listed = transport.list(dir)?;
+    for file in &listed {
+        // The listing decides where files are written, so keep them inside `dir`
+        let relative = Path::new(&file.path);
+        let inside = relative.components().all(|c| matches!(c, Component::Normal(_)));
+        if !inside || !relative.starts_with(dir) {
+            anyhow::bail!("Remote listed a file outside {}: {}", dir, file.path);
+        }
+    }
+
+    let changed: Vec<&RemoteFile> =
+        listed.iter().filter(|file| !is_current(&mirror.join(&file.path), file)).collect();
+    let mut report = SyncReport::default();
+    for batch in changed.chunks(FETCH_BATCH) {
+        let paths: Vec<String> = batch.iter().map(|file| file.path.clone()).collect();
+        let contents = transport.fetch(&paths)?;
+        for (file, contents) in batch.iter().zip(contents) {
+            let local = mirror.join(&file.path);
+            if let Some(parent) = local.parent() {
+                cache::create_private_dir(parent)?;
+            }
+            cache::write_private(&local, &contents)?;
+            // The remote's mtime, so the next sync (and the index) can tell it's unchanged
+            let mtime = UNIX_EPOCH + Duration::from_secs(file.mtime.max(0) as u64);
+            std::fs::File::options().write(true).open(&local)?.set_modified(mtime)?;
+            report.fetched += 1;
+        }
+    }
+
+    let kept: HashSet<PathBuf> = listed.iter().map(|file| mirror.join(&file.path)).collect();
+    let mirrored = walkdir::WalkDir::new(mirror.join(dir)).into_iter().flatten();
+    for entry in mirrored.filter(|e| e.file_type().is_file()) {
+        if !kept.contains(entry.path()) {
+            std::fs::remove_file(entry.path())?;
+            report.removed += 1;
+        }
+    }
+    Ok(report)
+}
+
+/// Whether the mirrored copy at `local` has `file`'s size and modification time
+fn is_current(local: &Path, file: &RemoteFile) -> bool {
+    let Ok(metadata) = std::fs::metadata(local) else {
+        return false;
+    };
+    let mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64);
+    metadata.len() == file.size && mtime == Some(file.mtime)
+}
+
+/// Sync every `[[remote]]` over SSH, returning the hosts that failed and why. A failed host
+/// keeps its last mirror. Nothing is synced with `privacy.ephemeral`, which writes nothing.
+pub fn sync_all() -> Vec<(String, String)> {
+    if cache::ephemeral() {
+        return Vec::new();
+    }
+    let mut failures = Vec::new();
+    for remote in &crate::config::get().remotes {
+        let mirror = mirror_home(&remote.host);
+        if let Err(e) = sync(&Ssh::new(&remote.host), remote.dir(), &mirror) {
+            failures.push((remote.host.clone(), format!("{:#}", e)));
+        }
+    }
+    failures
+}
+
+/// Where `host`'s sessions are mirrored, laid out like its home directory
+pub fn mirror_home(host: &str) -> PathBuf {
+    cache::dir().join("remotes").join(host)
+}
+
+/// The mirror of each configured host
+pub fn mirror_homes() -> Vec<PathBuf> {
+    let mut hosts: Vec<&str> = Vec::new();
+    for remote in &crate::config::get().remotes {
+        if !hosts.contains(&remote.host.as_str()) {
+            hosts.push(&remote.host);
+        }
+    }
+    hosts.into_iter().map(mirror_home).collect()
+}
+
+/// The host whose mirror `path` is in, if any
+pub fn host_of(path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(cache::dir().join("remotes")).ok()?;
+    match relative.components().next()? {
+        Component::Normal(host) => Some(host.to_string_lossy().into_owned()),
+        _ => None,
+    }
+}
+
+/// `program args` run in `cwd` on `host`, through a terminal so the agent is interactive
+pub fn ssh_command(
+    host: &str,
+    cwd: &str,
+    program: String,
+    args: Vec<String>,
+) -> (String, Vec<String>) {
+    let command = shell_words::join(std::iter::once(program).chain(args));
+    let command = if cwd.is_empty() {
+        command
+    } else {
+        format!("cd {} && {}", shell_words::quote(cwd), command)
+    };
+    ("ssh".to_string(), vec!["-t".to_string(), host.to_string(), command])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A remote home that's a local directory
+    struct DirTransport(PathBuf);
+
+    impl Transport for DirTransport {
+        fn list(&self, dir: &str) -> Result<Vec<RemoteFile>> {
+            let files = walkdir::WalkDir::new(self.0.join(dir)).into_iter().flatten();
+            files
+                .filter(|e| e.file_type().is_file())
+                .map(|entry| {
+                    let metadata = entry.metadata()?;
+                    let mtime = metadata.modified()?.duration_since(UNIX_EPOCH)?.as_secs();
+                    let path = entry.path().strip_prefix(&self.0)?;
+                    Ok(RemoteFile {
+                        path: path.to_string_lossy().into_owned(),
+                        size: metadata.len(),
+                        mtime: mtime as i64,
+                    })
+                })
+                .collect()
+        }
+
+        fn fetch(&self, paths: &[String]) -> Result<Vec<Vec<u8>>> {
+            paths.iter().map(|path| Ok(std::fs::read(self.0.join(path))?)).collect()
+        }
+    }
+
+    #[test]
+    fn test_sync_is_incremental() {
+        let temp = TempDir::new().unwrap();
+        let remote = temp.path().join("remote");
+        let mirror = temp.path().join("mirror");
+        let dir = ".claude/projects";
+        std::fs::create_dir_all(remote.join(dir).join("app")).unwrap();
+        std::fs::write(remote.join(dir).join("app/a.jsonl"), "a").unwrap();
+        std::fs::write(remote.join(dir).join("app/b.jsonl"), "b").unwrap();
+        let transport = DirTransport(remote.clone());
+
+        let report = sync(&transport, dir, &mirror).unwrap();
+        assert_eq!(report, SyncReport { fetched: 2, removed: 0 });
+        assert_eq!(std::fs::read_to_string(mirror.join(dir).join("app/a.jsonl")).unwrap(), "a");
+
+        // Nothing changed
+        assert_eq!(sync(&transport, dir, &mirror).unwrap(), SyncReport::default());
+
+        // One file grew, one was deleted
+        std::fs::write(remote.join(dir).join("app/a.jsonl"), "a, longer").unwrap();
+        std::fs::remove_file(remote.join(dir).join("app/b.jsonl")).unwrap();
+        let report = sync(&transport, dir, &mirror).unwrap();
+        assert_eq!(report, SyncReport { fetched: 1, removed: 1 });
+        assert!(!mirror.join(dir).join("app/b.jsonl").exists());
+
+        // A missing directory is an empty one
+        assert_eq!(sync(&transport, ".codex/sessions", &mirror).unwrap(), SyncReport::default());
+    }
+
+    #[test]
+    fn test_sync_stays_inside_mirror() {
+        struct Hostile;
+        impl Transport for Hostile {
+            fn list(&self, _: &str) -> Result<Vec<RemoteFile>> {
+                let path = ".claude/projects/../../escape".to_string();
+                Ok(vec![RemoteFile { path, size: 1, mtime: 0 }])
+            }
+            fn fetch(&self, paths: &[String]) -> Result<Vec<Vec<u8>>> {
+                Ok(paths.iter().map(|_| b"x".to_vec()).collect())
+            }
+        }
+        let temp = TempDir::new().unwrap();
+        assert!(sync(&Hostile, ".claude/projects", &temp.path().join("mirror")).is_err());
+        assert!(!temp.path().join("escape").exists());
+    }
+
+    #[test]
+    fn test_parse_listing() {
+        let file = parse_listing("120 1700000000.5000000000 .claude/projects/my app/s.jsonl");
+        let path = ".claude/projects/my app/s.jsonl".to_string();
+        let expected = RemoteFile { path, size: 120, mtime: 1700000000 };
+        assert_eq!(file.unwrap(), expected);
+        assert!(parse_listing("garbage").is_err());
+    }
+
+    #[test]
+    fn test_ssh_command() {
+        let args = vec!["--resume".to_string(), "s1".to_string()];
+        let (program, args) = ssh_command("dev", "/home/me/my app", "claude".into(), args);
+        assert_eq!(program, "ssh");
+        assert_eq!(args, ["-t", "dev", "cd '/home/me/my app' && claude --resume s1"]);
+        let (_, args) = ssh_command("dev", "", "claude".into(), vec![]);
+        assert_eq!(args, ["-t", "dev", "claude"]);
+    }
+}
@@ -1,36 +1,140 @@
+use crate::config::{CommandTemplate, OpenConfig, PathMap, ResumeConfig};
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
 use std::path::PathBuf;
+use std::sync::Mutex;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+/// Serialized as its name (`as_str`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SessionSource {
-    #[serde(rename = "claude")]
     ClaudeCode,
-    #[serde(rename = "codex")]
     CodexCli,
-    #[serde(rename = "factory")]
     Factory,
-    #[serde(rename = "opencode")]
     OpenCode,
+    Aider,
+    Gemini,
+    Goose,
+    Cline,
+    Copilot,
+    Amp,
+    Cursor,
+    Continue,
+    OpenHands,
+    AmazonQ,
+    Crush,
+    ChatGpt,
+    Llm,
+    Zed,
+    Windsurf,
+    ClaudeDesktop,
+    Plandex,
+    RooCode,
+    Warp,
+    /// A format described by a `[[custom_source]]` in the config, by its name
+    Custom(&'static str),
 }
 
+/// Names of the custom sources seen so far, leaked once each so `SessionSource` stays `Copy`
+static CUSTOM_NAMES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
 impl SessionSource {
+    pub const ALL: [SessionSource; 23] = [
+        SessionSource::ClaudeCode,
+        SessionSource::CodexCli,
+        SessionSource::Factory,
+        SessionSource::OpenCode,
+        SessionSource::Aider,
+        SessionSource::Gemini,
+        SessionSource::Goose,
+        SessionSource::Cline,
+        SessionSource::Copilot,
+        SessionSource::Amp,
+        SessionSource::Cursor,
+        SessionSource::Continue,
+        SessionSource::OpenHands,
+        SessionSource::AmazonQ,
+        SessionSource::Crush,
+        SessionSource::ChatGpt,
+        SessionSource::Llm,
+        SessionSource::Zed,
+        SessionSource::Windsurf,
+        SessionSource::ClaudeDesktop,
+        SessionSource::Plandex,
+        SessionSource::RooCode,
+        SessionSource::Warp,
+    ];
+
+    /// The custom source named `name` (see [`crate::config::CustomSource`])
+    pub fn custom(name: &str) -> Self {
+        let mut names = CUSTOM_NAMES.lock().unwrap_or_else(|e| e.into_inner());
+        let name = match names.iter().find(|known| **known == name) {
+            Some(known) => *known,
+            None => {
+                let leaked: &'static str = Box::leak(name.to_string().into_boxed_str());
+                names.push(leaked);
+                leaked
+            }
+        };
+        SessionSource::Custom(name)
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             SessionSource::ClaudeCode => "claude",
             SessionSource::CodexCli => "codex",
             SessionSource::Factory => "factory",
             SessionSource::OpenCode => "opencode",
+            SessionSource::Aider => "aider",
+            SessionSource::Gemini => "gemini",
+            SessionSource::Goose => "goose",
+            SessionSource::Cline => "cline",
+            SessionSource::Copilot => "copilot",
+            SessionSource::Amp => "amp",
+            SessionSource::Cursor => "cursor",
+            SessionSource::Continue => "continue",
+            SessionSource::OpenHands => "openhands",
+            SessionSource::AmazonQ => "amazonq",
+            SessionSource::Crush => "crush",
+            SessionSource::ChatGpt => "chatgpt",
+            SessionSource::Llm => "llm",
+            SessionSource::Zed => "zed",
+            SessionSource::Windsurf => "windsurf",
+            SessionSource::ClaudeDesktop => "claudedesktop",
+            SessionSource::Plandex => "plandex",
+            SessionSource::RooCode => "roocode",
+            SessionSource::Warp => "warp",
+            SessionSource::Custom(name) => name,
         }
     }
 
+    /// A built-in source, or a custom one from the config, by name
     pub fn parse(s: &str) -> Option<Self> {
         match s {
             "claude" => Some(SessionSource::ClaudeCode),
             "codex" => Some(SessionSource::CodexCli),
             "factory" => Some(SessionSource::Factory),
             "opencode" => Some(SessionSource::OpenCode),
-            _ => None,
+            "aider" => Some(SessionSource::Aider),
+            "gemini" => Some(SessionSource::Gemini),
+            "goose" => Some(SessionSource::Goose),
+            "cline" => Some(SessionSource::Cline),
+            "copilot" => Some(SessionSource::Copilot),
+            "amp" => Some(SessionSource::Amp),
+            "cursor" => Some(SessionSource::Cursor),
+            "continue" => Some(SessionSource::Continue),
+            "openhands" => Some(SessionSource::OpenHands),
+            "amazonq" => Some(SessionSource::AmazonQ),
+            "crush" => Some(SessionSource::Crush),
+            "chatgpt" => Some(SessionSource::ChatGpt),
+            "llm" => Some(SessionSource::Llm),
+            "zed" => Some(SessionSource::Zed),
+            "windsurf" => Some(SessionSource::Windsurf),
+            "claudedesktop" => Some(SessionSource::ClaudeDesktop),
+            "plandex" => Some(SessionSource::Plandex),
+            "roocode" => Some(SessionSource::RooCode),
+            "warp" => Some(SessionSource::Warp),
+            _ => crate::config::get().custom_source(s).map(|c| Self::custom(&c.name)),
         }
     }
 
@@ -40,6 +144,56 @@ impl SessionSource {
             SessionSource::CodexCli => "Codex",
             SessionSource::Factory => "Factory",
             SessionSource::OpenCode => "OpenCode",
+            SessionSource::Aider => "Aider",
+            SessionSource::Gemini => "Gemini",
+            SessionSource::Goose => "Goose",
+            SessionSource::Cline => "Cline",
+            SessionSource::Copilot => "Copilot",
+            SessionSource::Amp => "Amp",
+            SessionSource::Cursor => "Cursor",
+            SessionSource::Continue => "Continue",
+            SessionSource::OpenHands => "OpenHands",
+            SessionSource::AmazonQ => "Amazon Q",
+            SessionSource::Crush => "Crush",
+            SessionSource::ChatGpt => "ChatGPT",
+            SessionSource::Llm => "LLM",
+            SessionSource::Zed => "Zed",
+            SessionSource::Windsurf => "Windsurf",
+            SessionSource::ClaudeDesktop => "Claude Desktop",
+            SessionSource::Plandex => "Plandex",
+            SessionSource::RooCode => "Roo Code",
+            SessionSource::Warp => "Warp",
+            SessionSource::Custom(name) => name,
+        }
+    }
+
+    /// The agent's program, as its default resume command runs it
+    pub fn program(&self) -> &'static str {
+        match self {
+            SessionSource::ClaudeCode => "claude",
+            SessionSource::CodexCli => "codex",
+            SessionSource::Factory => "droid",
+            SessionSource::OpenCode => "opencode",
+            SessionSource::Aider => "aider",
+            SessionSource::Gemini => "gemini",
+            SessionSource::Goose => "goose",
+            SessionSource::Cline => "code",
+            SessionSource::Copilot => "copilot",
+            SessionSource::Amp => "amp",
+            SessionSource::Cursor => "cursor",
+            SessionSource::Continue => "code",
+            SessionSource::OpenHands => "openhands",
+            SessionSource::AmazonQ => "q",
+            SessionSource::Crush => "crush",
+            SessionSource::ChatGpt => "chatgpt",
+            SessionSource::Llm => "llm",
+            SessionSource::Zed => "zed",
+            SessionSource::Windsurf => "windsurf",
+            SessionSource::ClaudeDesktop => "claude-desktop",
+            SessionSource::Plandex => "plandex",
+            SessionSource::RooCode => "code",
+            SessionSource::Warp => "warp-terminal",
+            SessionSource::Custom(name) => name,
         }
     }
 
@@ -49,15 +203,97 @@ impl SessionSource {
             SessionSource::CodexCli => "■",
             SessionSource::Factory => "◆",
             SessionSource::OpenCode => "○",
+            SessionSource::Aider => "▲",
+            SessionSource::Gemini => "✦",
+            SessionSource::Goose => "◎",
+            SessionSource::Cline => "◉",
+            SessionSource::Copilot => "◈",
+            SessionSource::Amp => "▸",
+            SessionSource::Cursor => "□",
+            SessionSource::Continue => "▷",
+            SessionSource::OpenHands => "◐",
+            SessionSource::AmazonQ => "▼",
+            SessionSource::Crush => "▣",
+            SessionSource::ChatGpt => "◌",
+            SessionSource::Llm => "λ",
+            SessionSource::Zed => "◢",
+            SessionSource::Windsurf => "≋",
+            SessionSource::ClaudeDesktop => "✻",
+            SessionSource::Plandex => "◧",
+            SessionSource::RooCode => "◍",
+            SessionSource::Warp => "◤",
+            SessionSource::Custom(_) => "◇",
+        }
+    }
+
+    /// Whether Enter and `--last` can take you back into its sessions. Cursor, Windsurf,
+    /// Claude Desktop and Warp can't reopen a chat from outside, and ChatGPT chats are imported
+    /// from an export, so their sessions are only searched and copied.
+    pub fn resumable(&self) -> bool {
+        !matches!(
+            self,
+            SessionSource::Cursor
+                | SessionSource::ChatGpt
+                | SessionSource::Windsurf
+                | SessionSource::ClaudeDesktop
+                | SessionSource::Warp
+        )
+    }
+
+    /// `icon` for terminals without color or Unicode
+    pub fn ascii_icon(&self) -> &'static str {
+        match self {
+            SessionSource::ClaudeCode => "*",
+            SessionSource::CodexCli => "#",
+            SessionSource::Factory => "+",
+            SessionSource::OpenCode => "o",
+            SessionSource::Aider => "^",
+            SessionSource::Gemini => "g",
+            SessionSource::Goose => "@",
+            SessionSource::Cline => "=",
+            SessionSource::Copilot => "%",
+            SessionSource::Amp => ">",
+            SessionSource::Cursor => "]",
+            SessionSource::Continue => "}",
+            SessionSource::OpenHands => "&",
+            SessionSource::AmazonQ => "v",
+            SessionSource::Crush => "x",
+            SessionSource::ChatGpt => "c",
+            SessionSource::Llm => "l",
+            SessionSource::Zed => "z",
+            SessionSource::Windsurf => "w",
+            SessionSource::ClaudeDesktop => "d",
+            SessionSource::Plandex => "p",
+            SessionSource::RooCode => "r",
+            SessionSource::Warp => "$",
+            SessionSource::Custom(_) => "~",
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+impl Serialize for SessionSource {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Names that aren't built in are taken as custom sources even if the config no longer has
+/// them, so exported sessions from one still load
+impl<'de> Deserialize<'de> for SessionSource {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(Self::parse(&name).unwrap_or_else(|| Self::custom(&name)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Role {
     User,
     Assistant,
+    /// What came before a compacted conversation, as the agent summed it up to carry on from:
+    /// shown collapsed, and not searched
+    Context,
 }
 
 impl Role {
@@ -65,82 +301,569 @@ impl Role {
         match self {
             Role::User => "user",
             Role::Assistant => "assistant",
+            Role::Context => "context",
+        }
+    }
+
+    /// Speaker name shown above a message
+    pub fn label(&self, source: SessionSource) -> &'static str {
+        match (self, source) {
+            (Role::User, _) => "You",
+            (Role::Context, _) => "Compacted context",
+            (Role::Assistant, SessionSource::ClaudeCode) => "Claude",
+            (Role::Assistant, SessionSource::CodexCli) => "Codex",
+            (Role::Assistant, SessionSource::Factory) => "Droid",
+            (Role::Assistant, SessionSource::OpenCode) => "OpenCode",
+            (Role::Assistant, SessionSource::Aider) => "Aider",
+            (Role::Assistant, SessionSource::Gemini) => "Gemini",
+            (Role::Assistant, SessionSource::Goose) => "Goose",
+            (Role::Assistant, SessionSource::Cline) => "Cline",
+            (Role::Assistant, SessionSource::Copilot) => "Copilot",
+            (Role::Assistant, SessionSource::Amp) => "Amp",
+            (Role::Assistant, SessionSource::Cursor) => "Cursor",
+            (Role::Assistant, SessionSource::Continue) => "Continue",
+            (Role::Assistant, SessionSource::OpenHands) => "OpenHands",
+            (Role::Assistant, SessionSource::AmazonQ) => "Amazon Q",
+            (Role::Assistant, SessionSource::Crush) => "Crush",
+            (Role::Assistant, SessionSource::ChatGpt) => "ChatGPT",
+            (Role::Assistant, SessionSource::Llm) => "LLM",
+            (Role::Assistant, SessionSource::Zed) => "Zed",
+            (Role::Assistant, SessionSource::Windsurf) => "Windsurf",
+            (Role::Assistant, SessionSource::ClaudeDesktop) => "Claude",
+            (Role::Assistant, SessionSource::Plandex) => "Plandex",
+            (Role::Assistant, SessionSource::RooCode) => "Roo",
+            (Role::Assistant, SessionSource::Warp) => "Warp",
+            (Role::Assistant, SessionSource::Custom(_)) => "Assistant",
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Message {
     pub role: Role,
     pub content: String,
     pub timestamp: DateTime<Utc>,
+    /// Tools called after the message, before the next one, in the order they were called.
+    /// Kept out of `content`, and so out of search unless `index.tool_output` is on.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
 }
 
+/// How a tool call ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolStatus {
+    Ok,
+    /// The tool failed or the command exited non-zero
+    Error,
+    /// No result was recorded: the call was interrupted, or is still running
+    Pending,
+}
+
+/// A tool the assistant called, and what it returned
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolCall {
+    pub name: String,
+    /// The command run, or the arguments the tool was given
+    pub input: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i64>,
+    pub status: ToolStatus,
+}
+
+impl ToolCall {
+    /// A call whose result isn't known yet
+    pub fn pending(name: &str, input: String) -> Self {
+        Self {
+            name: name.to_string(),
+            input,
+            output: None,
+            exit_code: None,
+            status: ToolStatus::Pending,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.status == ToolStatus::Error
+    }
+}
+
+/// How a tool call touched a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileOp {
+    Read,
+    Edit,
+    Create,
+}
+
+impl FileOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileOp::Read => "read",
+            FileOp::Edit => "edit",
+            FileOp::Create => "create",
+        }
+    }
+
+    /// Whether the operation wrote to the file
+    pub fn is_write(&self) -> bool {
+        matches!(self, FileOp::Edit | FileOp::Create)
+    }
+}
+
+/// A file read or written by a tool call during a session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileTouch {
+    /// Absolute path (relative paths are resolved against the session's cwd)
+    pub path: String,
+    pub op: FileOp,
+    /// Index of the message the tool call followed
+    pub message_index: usize,
+}
+
+/// Tokens one model used during a session
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelUsage {
+    pub model: String,
+    /// Input tokens not served from the prompt cache
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+}
+
+impl ModelUsage {
+    /// Add `usage` to the per-model totals in `totals`
+    pub fn accumulate(totals: &mut Vec<ModelUsage>, usage: ModelUsage) {
+        match totals.iter_mut().find(|u| u.model == usage.model) {
+            Some(total) => {
+                total.input_tokens += usage.input_tokens;
+                total.output_tokens += usage.output_tokens;
+                total.cache_read_tokens += usage.cache_read_tokens;
+                total.cache_write_tokens += usage.cache_write_tokens;
+            }
+            None => totals.push(usage),
+        }
+    }
+}
+
+/// A session's token usage, as stored in the index
 #[derive(Debug, Clone)]
+pub struct SessionUsage {
+    pub session_id: String,
+    pub source: SessionSource,
+    pub cwd: String,
+    pub timestamp: DateTime<Utc>,
+    /// Empty when the source didn't record usage
+    pub usage: Vec<ModelUsage>,
+}
+
+/// A parsed session. Serializes with the source as its `as_str()` name and timestamps as
+/// RFC 3339, and deserializes back to an equal value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Session {
     pub id: String,
     pub source: SessionSource,
     pub file_path: PathBuf,
     pub cwd: String,
+    #[serde(default)]
     pub git_branch: Option<String>,
     pub timestamp: DateTime<Utc>,
     pub messages: Vec<Message>,
+    /// Files read or written by tool calls, in session order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files_touched: Vec<FileTouch>,
+    /// Token usage per model (empty when the source doesn't record it)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub usage: Vec<ModelUsage>,
+    /// The title the agent gave the session, like Claude's summary lines. In search results,
+    /// the title it was indexed with, derived when the agent gave none (see [`Session::title`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
 }
 
 impl Session {
-    /// Get the project name from cwd (last path component)
-    pub fn project_name(&self) -> &str {
-        std::path::Path::new(&self.cwd)
+    /// Get the project name from cwd (last path component, after `[path_map]`)
+    pub fn project_name(&self) -> String {
+        let cwd = self.local_cwd();
+        std::path::Path::new(&*cwd)
             .file_name()
             .and_then(|s| s.to_str())
-            .unwrap_or(&self.cwd)
+            .unwrap_or(&cwd)
+            .to_string()
+    }
+
+    /// Where the session's folder is on this machine: its cwd, rewritten by `[path_map]`
+    pub fn local_cwd(&self) -> Cow<'_, str> {
+        crate::config::get().path_map.apply(&self.cwd)
+    }
+
+    /// The extra home (from the config) this session was found in, if not the user's own
+    pub fn origin(&self) -> Option<PathBuf> {
+        crate::parser::origin_of(&self.file_path)
+    }
+
+    /// The `[[remote]]` host this session was mirrored from, if it's from another machine
+    pub fn remote_host(&self) -> Option<String> {
+        crate::remote::host_of(&self.file_path)
+    }
+
+    /// Whether this is the transcript of a subagent a Claude session ran (`include_subagents`)
+    pub fn is_subagent(&self) -> bool {
+        self.source == SessionSource::ClaudeCode
+            && crate::parser::is_subagent_transcript(&self.file_path)
+    }
+
+    /// Whether Enter and `--last` can take you back into it: its source can reopen sessions
+    /// (see [`SessionSource::resumable`]), and it isn't a subagent's transcript
+    pub fn resumable(&self) -> bool {
+        self.source.resumable() && !self.is_subagent()
+    }
+
+    /// Short title: what the user renamed the session to, or else the title the agent gave
+    /// it, or else the first line of the first user message, truncated
+    pub fn title(&self) -> String {
+        self.title_renamed(crate::titles::get(&self.id))
+    }
+
+    /// [`Session::title`], with `rename` as what the user renamed the session to
+    pub(crate) fn title_renamed(&self, rename: Option<String>) -> String {
+        const MAX_CHARS: usize = 80;
+
+        if let Some(title) = rename {
+            return title;
+        }
+
+        let first_line = self.title.as_deref().map(str::trim).filter(|t| !t.is_empty());
+        let first_line = first_line
+            .or_else(|| {
+                self.messages
+                    .iter()
+                    .find(|m| m.role == Role::User)
+                    .and_then(|m| m.content.lines().map(str::trim).find(|l| !l.is_empty()))
+            })
+            .unwrap_or("");
+
+        if first_line.chars().count() > MAX_CHARS {
+            let truncated: String = first_line.chars().take(MAX_CHARS - 1).collect();
+            format!("{}…", truncated.trim_end())
+        } else {
+            first_line.to_string()
+        }
     }
 
     /// Get the resume command for this session
-    /// Checks RECALL_CLAUDE_CMD / RECALL_CODEX_CMD / RECALL_FACTORY_CMD env vars first, falls back to defaults
-    /// Env var format: "program arg1 arg2 {id}" where {id} is replaced with session ID
+    /// Checks the source's RECALL_<SOURCE>_CMD env var first (RECALL_CLAUDE_CMD, RECALL_CODEX_CMD,
+    /// ...; custom sources have none), then the `[resume]` config, and falls back to defaults
     pub fn resume_command(&self) -> (String, Vec<String>) {
+        self.resume_command_with(&crate::config::get().resume)
+    }
+
+    /// The resume command as one line, quoted so a shell splits it back into the same
+    /// arguments
+    pub fn resume_command_line(&self) -> String {
+        let (program, args) = self.resume_command();
+        shell_words::join(std::iter::once(program).chain(args))
+    }
+
+    /// Command that opens the session's folder: RECALL_OPEN_CMD, then `[open]` in the config,
+    /// then the platform's opener
+    pub fn open_folder_command(&self) -> (String, Vec<String>) {
+        let config = crate::config::get();
+        self.open_folder_command_with(&config.open, &config.path_map)
+    }
+
+    /// Command that opens the session's folder (as `path_map` rewrites it), using `open` and
+    /// `path_map` instead of the global config
+    pub fn open_folder_command_with(
+        &self,
+        open: &OpenConfig,
+        path_map: &PathMap,
+    ) -> (String, Vec<String>) {
+        let cwd = path_map.apply(&self.cwd);
+        let from_env = std::env::var("RECALL_OPEN_CMD").ok().map(CommandTemplate::Line);
+        for template in from_env.iter().chain(open.command.as_ref()) {
+            if let Ok(mut args) = template.expand(&self.id, &cwd) {
+                if !args.is_empty() {
+                    let program = args.remove(0);
+                    return (program, args);
+                }
+            }
+        }
+
+        let opener = if cfg!(target_os = "macos") {
+            "open"
+        } else if cfg!(windows) {
+            "explorer"
+        } else {
+            "xdg-open"
+        };
+        (opener.to_string(), vec![cwd.into_owned()])
+    }
+
+    /// Get the resume command using the given templates instead of the global config.
+    /// Env var format: a shell-quoted command line with {id} and {cwd} placeholders;
+    /// an empty or unparsable one is ignored. A remote session's command runs over ssh.
+    pub fn resume_command_with(&self, resume: &ResumeConfig) -> (String, Vec<String>) {
+        let (program, args) = self.local_resume_command(resume);
+        match self.remote_host() {
+            Some(host) => crate::remote::ssh_command(&host, &self.cwd, program, args),
+            None => (program, args),
+        }
+    }
+
+    /// The resume command as it runs on the machine the session is from
+    fn local_resume_command(&self, resume: &ResumeConfig) -> (String, Vec<String>) {
         let env_var = match self.source {
-            SessionSource::ClaudeCode => "RECALL_CLAUDE_CMD",
-            SessionSource::CodexCli => "RECALL_CODEX_CMD",
-            SessionSource::Factory => "RECALL_FACTORY_CMD",
-            SessionSource::OpenCode => "RECALL_OPENCODE_CMD",
+            SessionSource::ClaudeCode => Some("RECALL_CLAUDE_CMD"),
+            SessionSource::CodexCli => Some("RECALL_CODEX_CMD"),
+            SessionSource::Factory => Some("RECALL_FACTORY_CMD"),
+            SessionSource::OpenCode => Some("RECALL_OPENCODE_CMD"),
+            SessionSource::Aider => Some("RECALL_AIDER_CMD"),
+            SessionSource::Gemini => Some("RECALL_GEMINI_CMD"),
+            SessionSource::Goose => Some("RECALL_GOOSE_CMD"),
+            SessionSource::Cline => Some("RECALL_CLINE_CMD"),
+            SessionSource::Copilot => Some("RECALL_COPILOT_CMD"),
+            SessionSource::Amp => Some("RECALL_AMP_CMD"),
+            SessionSource::Cursor => Some("RECALL_CURSOR_CMD"),
+            SessionSource::Continue => Some("RECALL_CONTINUE_CMD"),
+            SessionSource::OpenHands => Some("RECALL_OPENHANDS_CMD"),
+            SessionSource::AmazonQ => Some("RECALL_AMAZONQ_CMD"),
+            SessionSource::Crush => Some("RECALL_CRUSH_CMD"),
+            SessionSource::ChatGpt => Some("RECALL_CHATGPT_CMD"),
+            SessionSource::Llm => Some("RECALL_LLM_CMD"),
+            SessionSource::Zed => Some("RECALL_ZED_CMD"),
+            SessionSource::Windsurf => Some("RECALL_WINDSURF_CMD"),
+            SessionSource::ClaudeDesktop => Some("RECALL_CLAUDEDESKTOP_CMD"),
+            SessionSource::Plandex => Some("RECALL_PLANDEX_CMD"),
+            SessionSource::RooCode => Some("RECALL_ROOCODE_CMD"),
+            SessionSource::Warp => Some("RECALL_WARP_CMD"),
+            SessionSource::Custom(_) => None,
         };
 
-        if let Ok(cmd) = std::env::var(env_var) {
-            let cmd = cmd.replace("{id}", &self.id);
-            let parts: Vec<&str> = cmd.split_whitespace().collect();
-            if !parts.is_empty() {
-                return (
-                    parts[0].to_string(),
-                    parts[1..].iter().map(|s| s.to_string()).collect(),
-                );
+        let from_env = env_var.and_then(|var| std::env::var(var).ok()).map(CommandTemplate::Line);
+        let custom = match self.source {
+            SessionSource::Custom(name) => crate::config::get().custom_source(name),
+            _ => None,
+        };
+        let from_config = resume
+            .template(self.source)
+            .or_else(|| custom.and_then(|c| c.resume.as_ref()));
+        for template in from_env.iter().chain(from_config) {
+            if let Ok(mut args) = template.expand(&self.id, &self.cwd) {
+                if !args.is_empty() {
+                    let program = args.remove(0);
+                    return (program, args);
+                }
             }
         }
 
         // Default commands
-        match self.source {
-            SessionSource::ClaudeCode => (
-                "claude".to_string(),
-                vec!["--resume".to_string(), self.id.clone()],
-            ),
-            SessionSource::CodexCli => (
-                "codex".to_string(),
-                vec!["resume".to_string(), self.id.clone()],
-            ),
-            SessionSource::Factory => (
-                "droid".to_string(),
-                vec!["--resume".to_string(), self.id.clone()],
-            ),
-            SessionSource::OpenCode => (
-                "opencode".to_string(),
-                vec!["--session".to_string(), self.id.clone()],
-            ),
+        let id = self.id.as_str();
+        let args = match self.source {
+            SessionSource::ClaudeCode | SessionSource::Factory => vec!["--resume", id],
+            SessionSource::CodexCli => vec!["resume", id],
+            SessionSource::OpenCode => vec!["--session", id],
+            // One history per folder, which the command runs in
+            SessionSource::Aider => vec!["--restore-chat-history"],
+            SessionSource::Gemini => vec!["--resume", id],
+            SessionSource::Goose => vec!["session", "-r", "--name", id],
+            // A VS Code extension, with no command line to resume from: open its workspace
+            SessionSource::Cline => vec![self.cwd.as_str()],
+            SessionSource::Copilot => vec!["--resume", id],
+            SessionSource::Amp => vec!["threads", "continue", id],
+            // Not run by Enter (see `resumable`), but where the chats can be found
+            SessionSource::Cursor => vec![self.cwd.as_str()],
+            // An IDE extension too; VS Code is where most people run it
+            SessionSource::Continue => vec![self.cwd.as_str()],
+            SessionSource::OpenHands => vec!["--resume", id],
+            // Resumes the conversation of the folder it's run in, the session's cwd
+            SessionSource::AmazonQ => vec!["chat", "--resume"],
+            // Run in the session's cwd, the project the database is in
+            SessionSource::Crush => vec!["--resume", id],
+            // Only imported, from an export; not run by Enter (see `resumable`)
+            SessionSource::ChatGpt => vec![id],
+            // `-c` continues the latest conversation; an older one is named
+            SessionSource::Llm if crate::parser::is_latest_llm_conversation(&self.file_path) => {
+                vec!["chat", "-c"]
+            }
+            SessionSource::Llm => vec!["chat", "--cid", id],
+            // No way back into a conversation but to open its file in the editor
+            SessionSource::Zed => vec![self.file_path.to_str().unwrap_or(id)],
+            // Not run by Enter (see `resumable`), but where the chats can be found
+            SessionSource::Windsurf => vec![self.cwd.as_str()],
+            // The app has no command line; not run by Enter (see `resumable`)
+            SessionSource::ClaudeDesktop => vec![id],
+            // Continues the current plan of the folder it's run in, the plan's cwd
+            SessionSource::Plandex => vec!["continue"],
+            // Cline's fork, so likewise: open its workspace
+            SessionSource::RooCode => vec![self.cwd.as_str()],
+            // Not run by Enter (see `resumable`), but where the conversation was had
+            SessionSource::Warp => vec![self.cwd.as_str()],
+            // A custom source without a `resume` template: its name as the program
+            SessionSource::Custom(_) => vec![id],
+        };
+        (self.source.program().to_string(), args.into_iter().map(String::from).collect())
+    }
+}
+
+/// Mechanical summary of a session: what it was about, how it ended, and what it touched
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionDigest {
+    pub session_id: String,
+    pub source: SessionSource,
+    pub project: String,
+    pub cwd: String,
+    pub title: String,
+    /// Opening lines of the last substantive assistant message
+    pub outcome: Option<String>,
+    pub message_count: usize,
+    pub started: Option<DateTime<Utc>>,
+    pub ended: Option<DateTime<Utc>>,
+    pub duration_secs: i64,
+    /// Recognized tool calls by kind (currently file reads, edits, and creations)
+    pub tool_calls: std::collections::BTreeMap<String, usize>,
+    /// Distinct files touched, in first-touch order
+    pub files_touched: Vec<String>,
+}
+
+impl Session {
+    /// Summarize the session without reading anything beyond its messages
+    pub fn digest(&self) -> SessionDigest {
+        // Short acknowledgements ("Done!") say little about the outcome
+        const SUBSTANTIVE_CHARS: usize = 40;
+        const OUTCOME_LINES: usize = 2;
+        const OUTCOME_CHARS: usize = 240;
+
+        let assistant = || self.messages.iter().rev().filter(|m| m.role == Role::Assistant);
+        let outcome = assistant()
+            .find(|m| m.content.trim().chars().count() >= SUBSTANTIVE_CHARS)
+            .or_else(|| assistant().next())
+            .map(|m| {
+                let lines: Vec<&str> = m
+                    .content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty())
+                    .take(OUTCOME_LINES)
+                    .collect();
+                let joined = lines.join(" ");
+                if joined.chars().count() > OUTCOME_CHARS {
+                    let cut: String = joined.chars().take(OUTCOME_CHARS - 1).collect();
+                    format!("{}…", cut.trim_end())
+                } else {
+                    joined
+                }
+            });
+
+        let started = self.messages.iter().map(|m| m.timestamp).min();
+        let ended = self.messages.iter().map(|m| m.timestamp).max();
+        let duration_secs = match (started, ended) {
+            (Some(start), Some(end)) => (end - start).num_seconds(),
+            _ => 0,
+        };
+
+        let mut tool_calls = std::collections::BTreeMap::new();
+        let mut files_touched: Vec<String> = Vec::new();
+        for touch in &self.files_touched {
+            *tool_calls.entry(touch.op.as_str().to_string()).or_insert(0) += 1;
+            if !files_touched.contains(&touch.path) {
+                files_touched.push(touch.path.clone());
+            }
+        }
+
+        SessionDigest {
+            session_id: self.id.clone(),
+            source: self.source,
+            project: self.project_name(),
+            cwd: self.cwd.clone(),
+            title: self.title(),
+            outcome,
+            message_count: self.messages.len(),
+            started,
+            ended,
+            duration_secs,
+            tool_calls,
+            files_touched,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+impl SessionDigest {
+    /// The digest as one readable paragraph
+    pub fn paragraph(&self) -> String {
+        const LISTED_FILES: usize = 3;
+
+        let mut parts = Vec::new();
+        let title = if self.title.is_empty() { "Untitled session" } else { &self.title };
+        parts.push(format!("{}.", title.trim_end_matches('.')));
+
+        if let Some(outcome) = &self.outcome {
+            parts.push(format!("Ended with: \"{}\"", outcome));
+        }
+
+        let mut stats = format!(
+            "{} message{} over {}",
+            self.message_count,
+            if self.message_count == 1 { "" } else { "s" },
+            format_duration(self.duration_secs)
+        );
+        if !self.tool_calls.is_empty() {
+            let calls: Vec<String> = self
+                .tool_calls
+                .iter()
+                .map(|(kind, n)| format!("{} {}", n, kind))
+                .collect();
+            stats.push_str(&format!("; tool calls: {}", calls.join(", ")));
+        }
+        if !self.files_touched.is_empty() {
+            let names: Vec<&str> = self
+                .files_touched
+                .iter()
+                .take(LISTED_FILES)
+                .map(|p| {
+                    std::path::Path::new(p)
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or(p)
+                })
+                .collect();
+            let more = self.files_touched.len().saturating_sub(LISTED_FILES);
+            stats.push_str(&format!(
+                "; touched {} file{} ({}{})",
+                self.files_touched.len(),
+                if self.files_touched.len() == 1 { "" } else { "s" },
+                names.join(", "),
+                if more > 0 { format!(", +{} more", more) } else { String::new() }
+            ));
+        }
+        parts.push(format!("{}.", stats));
+
+        parts.join(" ")
+    }
+}
+
+/// Format a duration compactly: "45s", "12m", "3h 5m", "2d 4h"
+pub fn format_duration(secs: i64) -> String {
+    let secs = secs.max(0);
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m", s / 60),
+        s if s < 86400 => match (s / 3600, (s % 3600) / 60) {
+            (h, 0) => format!("{}h", h),
+            (h, m) => format!("{}h {}m", h, m),
+        },
+        s => match (s / 86400, (s % 86400) / 3600) {
+            (d, 0) => format!("{}d", d),
+            (d, h) => format!("{}d {}h", d, h),
+        },
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SearchResult {
     pub session: Session,
     pub score: f32,
@@ -152,6 +875,30 @@ pub struct SearchResult {
     pub match_spans: Vec<(usize, usize)>,
     /// Original fragment from Tantivy (for finding match in wrapped text)
     pub match_fragment: String,
+    /// Other messages that matched, best first (near-duplicates of these and of the best
+    /// match left out)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub more_matches: Vec<MatchHit>,
+}
+
+/// A message in a search result's session that matched besides the one it shows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MatchHit {
+    pub message_index: usize,
+    pub snippet: String,
+    pub match_spans: Vec<(usize, usize)>,
+    pub match_fragment: String,
+}
+
+impl From<SearchResult> for MatchHit {
+    fn from(result: SearchResult) -> Self {
+        Self {
+            message_index: result.matched_message_index,
+            snippet: result.snippet,
+            match_spans: result.match_spans,
+            match_fragment: result.match_fragment,
+        }
+    }
 }
 
 // ============================================================================
@@ -162,6 +909,10 @@ pub struct SearchResult {
 #[derive(Debug, Serialize)]
 pub struct SearchOutput {
     pub query: String,
+    /// Number of matching sessions found so far; more than `offset + limit` means another page exists
+    pub total_estimate: usize,
+    pub offset: usize,
+    pub limit: usize,
     pub results: Vec<SearchResultOutput>,
 }
 
@@ -179,63 +930,262 @@ pub struct SearchResultOutput {
 /// Output format for `recall list`
 #[derive(Debug, Serialize)]
 pub struct ListOutput {
+    /// Number of matching sessions found so far; more than `offset + limit` means another page exists
+    pub total_estimate: usize,
+    pub offset: usize,
+    pub limit: usize,
     pub sessions: Vec<SessionSummary>,
 }
 
-/// Session summary for list output (no messages)
+/// Session summary for list output (no message bodies)
 #[derive(Debug, Serialize)]
 pub struct SessionSummary {
     pub session_id: String,
     pub source: SessionSource,
+    pub project: String,
     pub cwd: String,
+    pub git_branch: Option<String>,
     pub timestamp: DateTime<Utc>,
+    pub message_count: usize,
+    pub title: String,
     pub resume_command: String,
+    /// Extra home the session was found in (absent for the user's own home)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin: Option<PathBuf>,
+    /// `[[remote]]` host the session was mirrored from (absent for this machine's sessions)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote: Option<String>,
+    /// The user's note on the session (see [`crate::notes`])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
 }
 
-/// Output format for `recall read`
-#[derive(Debug, Serialize)]
+/// Output format for `recall read` (also the export format read back by `recall import`)
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ReadOutput {
     pub session_id: String,
     pub source: SessionSource,
     pub cwd: String,
+    #[serde(default)]
+    pub git_branch: Option<String>,
     pub timestamp: DateTime<Utc>,
     pub messages: Vec<Message>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files_touched: Vec<FileTouch>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub usage: Vec<ModelUsage>,
+    /// The title the agent gave the session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default)]
     pub resume_command: String,
+    /// The user's note on the session (see [`crate::notes`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl ReadOutput {
+    /// Convert an exported session back into a Session stored at `file_path`
+    pub fn into_session(self, file_path: PathBuf) -> Session {
+        Session {
+            id: self.session_id,
+            source: self.source,
+            file_path,
+            cwd: self.cwd,
+            git_branch: self.git_branch,
+            timestamp: self.timestamp,
+            messages: self.messages,
+            files_touched: self.files_touched,
+            usage: self.usage,
+            title: self.title,
+        }
+    }
 }
 
 impl Session {
     /// Convert to ReadOutput for JSON serialization
     pub fn to_read_output(&self) -> ReadOutput {
-        let (cmd, args) = self.resume_command();
-        let resume_str = std::iter::once(cmd)
-            .chain(args)
-            .collect::<Vec<_>>()
-            .join(" ");
-
         ReadOutput {
             session_id: self.id.clone(),
             source: self.source,
             cwd: self.cwd.clone(),
+            git_branch: self.git_branch.clone(),
             timestamp: self.timestamp,
             messages: self.messages.clone(),
-            resume_command: resume_str,
+            files_touched: self.files_touched.clone(),
+            usage: self.usage.clone(),
+            title: self.title.clone(),
+            resume_command: self.resume_command_line(),
+            note: crate::notes::get(&self.id),
         }
     }
 
     /// Convert to SessionSummary for list output
     pub fn to_summary(&self) -> SessionSummary {
-        let (cmd, args) = self.resume_command();
-        let resume_str = std::iter::once(cmd)
-            .chain(args)
-            .collect::<Vec<_>>()
-            .join(" ");
-
         SessionSummary {
             session_id: self.id.clone(),
             source: self.source,
+            project: self.project_name(),
             cwd: self.cwd.clone(),
+            git_branch: self.git_branch.clone(),
             timestamp: self.timestamp,
-            resume_command: resume_str,
+            message_count: self.messages.len(),
+            title: self.title(),
+            resume_command: self.resume_command_line(),
+            origin: self.origin(),
+            remote: self.remote_host(),
+            note: crate::notes::get(&self.id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(12 * 60 + 5), "12m");
+        assert_eq!(format_duration(3 * 3600), "3h");
+        assert_eq!(format_duration(3 * 3600 + 5 * 60), "3h 5m");
+        assert_eq!(format_duration(2 * 86400 + 4 * 3600), "2d 4h");
+        assert_eq!(format_duration(-5), "0s");
+    }
+
+    #[test]
+    fn test_resume_command_with_templates() {
+        // OpenCode, since tests may run with RECALL_CLAUDE_CMD and friends set
+        let session = Session {
+            id: "ses_1".to_string(),
+            source: SessionSource::OpenCode,
+            file_path: PathBuf::from("/tmp/ses_1.json"),
+            cwd: "/w/app".to_string(),
+            git_branch: None,
+            timestamp: Utc::now(),
+            messages: vec![],
+            files_touched: vec![],
+            usage: vec![],
+            title: None,
+        };
+
+        let default = session.resume_command_with(&ResumeConfig::default());
+        assert_eq!(default, ("opencode".to_string(), vec!["--session".to_string(), "ses_1".to_string()]));
+
+        let resume = ResumeConfig {
+            opencode: Some(CommandTemplate::Line("oc --session={id} --dir '{cwd}'".to_string())),
+            ..Default::default()
+        };
+        let (program, args) = session.resume_command_with(&resume);
+        assert_eq!(program, "oc");
+        assert_eq!(args, ["--session=ses_1", "--dir", "/w/app"]);
+
+        // An empty template falls back to the default
+        let resume = ResumeConfig {
+            opencode: Some(CommandTemplate::Args(vec![])),
+            ..Default::default()
+        };
+        assert_eq!(session.resume_command_with(&resume), default);
+    }
+
+    #[test]
+    fn test_open_folder_command_with_template() {
+        let session = Session {
+            id: "ses_1".to_string(),
+            source: SessionSource::OpenCode,
+            file_path: PathBuf::from("/tmp/ses_1.json"),
+            cwd: "/w/my app".to_string(),
+            git_branch: None,
+            timestamp: Utc::now(),
+            messages: vec![],
+            files_touched: vec![],
+            usage: vec![],
+            title: None,
+        };
+
+        let open = OpenConfig {
+            command: Some(CommandTemplate::Args(
+                ["wezterm", "start", "--cwd", "{cwd}"].map(String::from).to_vec(),
+            )),
+            terminal: false,
+        };
+        let (program, args) = session.open_folder_command_with(&open, &PathMap::default());
+        assert_eq!(program, "wezterm");
+        assert_eq!(args, ["start", "--cwd", "/w/my app"]);
+
+        // Opened where `[path_map]` says the folder is
+        let (config, _) = crate::config::Config::parse("[path_map]\n'/w' = '/home/me'").unwrap();
+        let (_, args) = session.open_folder_command_with(&open, &config.path_map);
+        assert_eq!(args, ["start", "--cwd", "/home/me/my app"]);
+    }
+
+    #[test]
+    fn test_source_serializes_as_str() {
+        for source in SessionSource::ALL.into_iter().chain([SessionSource::custom("toybot")]) {
+            let json = serde_json::to_string(&source).unwrap();
+            assert_eq!(json, format!("\"{}\"", source.as_str()));
+            assert_eq!(serde_json::from_str::<SessionSource>(&json).unwrap(), source);
         }
+        assert_eq!(SessionSource::custom("toybot"), SessionSource::Custom("toybot"));
+        // Only configured custom sources parse from user input
+        assert_eq!(SessionSource::parse("toybot"), None);
+    }
+
+    #[test]
+    fn test_session_round_trip() {
+        let timestamp = "2025-03-04T05:06:07.250Z".parse::<DateTime<Utc>>().unwrap();
+        let session = Session {
+            id: "abc".to_string(),
+            source: SessionSource::CodexCli,
+            file_path: PathBuf::from("/home/u/.codex/sessions/abc.jsonl"),
+            cwd: "/w/app".to_string(),
+            git_branch: Some("main".to_string()),
+            timestamp,
+            messages: vec![
+                Message {
+                    role: Role::User,
+                    content: "fix the build".to_string(),
+                    timestamp,
+                    tool_calls: Vec::new(),
+                },
+                Message {
+                    role: Role::Assistant,
+                    content: "Done.".to_string(),
+                    timestamp,
+                    tool_calls: Vec::new(),
+                },
+            ],
+            files_touched: vec![FileTouch {
+                path: "/w/app/src/main.rs".to_string(),
+                op: FileOp::Edit,
+                message_index: 1,
+            }],
+            usage: vec![ModelUsage {
+                model: "gpt-5".to_string(),
+                output_tokens: 12,
+                ..Default::default()
+            }],
+            title: Some("Fix the build".to_string()),
+        };
+
+        let json: serde_json::Value = serde_json::to_value(&session).unwrap();
+        assert_eq!(json["source"], "codex");
+        assert_eq!(json["timestamp"], "2025-03-04T05:06:07.250Z");
+        assert_eq!(json["messages"][0]["role"], "user");
+        assert_eq!(json["title"], "Fix the build");
+        assert_eq!(serde_json::from_value::<Session>(json).unwrap(), session);
+
+        let result = SearchResult {
+            session: Session { files_touched: vec![], usage: vec![], ..session },
+            score: 1.5,
+            matched_message_index: 0,
+            snippet: "fix the build".to_string(),
+            match_spans: vec![(8, 13)],
+            match_fragment: "<b>build</b>".to_string(),
+            more_matches: Vec::new(),
+        };
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(!json.contains("files_touched"));
+        assert_eq!(serde_json::from_str::<SearchResult>(&json).unwrap(), result);
     }
 }
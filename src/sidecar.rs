@@ -0,0 +1,130 @@
+//! Things the user adds to sessions (tags, notes), each kind in its own JSON file next to the
+//! index, keyed by session ID. Entries remember the session's file so the index state can
+//! tell which files to reindex when they change.
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A session's value, with the file it was given to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry<V> {
+    value: V,
+    path: PathBuf,
+}
+
+/// Session ID -> value, as stored in the file
+type Store<V> = BTreeMap<String, Entry<V>>;
+
+/// One sidecar file, loaded on first use and again whenever the cache dir changes (as it does
+/// between tests)
+pub struct Sidecar<V> {
+    file: &'static str,
+    loaded: Mutex<Option<(PathBuf, Store<V>)>>,
+}
+
+impl<V: Clone + PartialEq + Serialize + DeserializeOwned> Sidecar<V> {
+    /// A sidecar stored as `file` in the cache dir
+    pub const fn new(file: &'static str) -> Self {
+        Self { file, loaded: Mutex::new(None) }
+    }
+
+    /// The file, in the cache dir
+    pub fn path(&self) -> PathBuf {
+        self.path_in(&crate::index::cache::dir())
+    }
+
+    /// The file, in `dir` rather than the cache dir (a library index keeps its own)
+    pub fn path_in(&self, dir: &Path) -> PathBuf {
+        dir.join(self.file)
+    }
+
+    fn with_store<T>(&self, f: impl FnOnce(&mut Store<V>) -> T) -> T {
+        self.with_store_in(&crate::index::cache::dir(), f)
+    }
+
+    fn with_store_in<T>(&self, dir: &Path, f: impl FnOnce(&mut Store<V>) -> T) -> T {
+        let path = self.path_in(dir);
+        let mut guard = self.loaded.lock().unwrap_or_else(|e| e.into_inner());
+        if guard.as_ref().is_none_or(|(loaded, _)| *loaded != path) {
+            // A missing or unreadable file just means nothing was added
+            let store = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|text| serde_json::from_str(&text).ok())
+                .unwrap_or_default();
+            *guard = Some((path, store));
+        }
+        let (_, store) = guard.as_mut().expect("loaded above");
+        f(store)
+    }
+
+    /// The value of the session with this ID
+    pub fn get(&self, session_id: &str) -> Option<V> {
+        self.get_in(&crate::index::cache::dir(), session_id)
+    }
+
+    /// The value of the session with this ID, from the sidecar in `dir`
+    pub fn get_in(&self, dir: &Path, session_id: &str) -> Option<V> {
+        self.with_store_in(dir, |store| store.get(session_id).map(|e| e.value.clone()))
+    }
+
+    /// The value given to the session stored in `path`
+    pub fn for_file(&self, path: &Path) -> Option<V> {
+        self.for_file_in(&crate::index::cache::dir(), path)
+    }
+
+    /// The value given to the session stored in `path`, from the sidecar in `dir`
+    pub fn for_file_in(&self, dir: &Path, path: &Path) -> Option<V> {
+        self.with_store_in(dir, |store| {
+            store.values().find(|e| e.path == path).map(|e| e.value.clone())
+        })
+    }
+
+    /// Every session's value, with its ID and file
+    pub fn all(&self) -> Vec<(String, PathBuf, V)> {
+        self.with_store(|store| {
+            store
+                .iter()
+                .map(|(id, e)| (id.clone(), e.path.clone(), e.value.clone()))
+                .collect()
+        })
+    }
+
+    /// Replace a session's value (None removes it) and save the file, unless the index is
+    /// kept in memory. Returns whether anything changed.
+    pub fn set(&self, session_id: &str, path: &Path, value: Option<V>) -> Result<bool> {
+        let text = self
+            .with_store(|store| {
+                if store.get(session_id).map(|e| &e.value) == value.as_ref() {
+                    return Ok(None);
+                }
+                match value {
+                    Some(value) => {
+                        let entry = Entry { value, path: path.to_path_buf() };
+                        store.insert(session_id.to_string(), entry);
+                    }
+                    None => {
+                        store.remove(session_id);
+                    }
+                }
+                serde_json::to_string_pretty(store).map(Some)
+            })
+            .with_context(|| format!("Failed to serialize {}", self.file))?;
+
+        let Some(text) = text else {
+            return Ok(false);
+        };
+        if !crate::index::cache::ephemeral() {
+            let file = self.path();
+            if let Some(dir) = file.parent() {
+                crate::index::cache::create_private_dir(dir)?;
+            }
+            crate::index::cache::write_private(&file, text.as_bytes())
+                .with_context(|| format!("Failed to write {}", file.display()))?;
+        }
+        Ok(true)
+    }
+}
@@ -0,0 +1,69 @@
+//! Labels the user gives sessions ("billing-bug", "research"), kept in a sidecar file next to
+//! the index. They're indexed with each session's messages so `tag:billing-bug` finds them,
+//! and the index state remembers which tags a file was indexed with, so changing them
+//! reindexes it.
+
+use crate::sidecar::Sidecar;
+use anyhow::Result;
+use std::path::Path;
+
+static TAGS: Sidecar<Vec<String>> = Sidecar::new("tags.json");
+
+/// Tags of the session with this ID
+pub fn get(session_id: &str) -> Vec<String> {
+    TAGS.get(session_id).unwrap_or_default()
+}
+
+/// Tags of the session with this ID, from the sidecar in `dir` rather than the cache dir
+pub fn get_in(dir: &Path, session_id: &str) -> Vec<String> {
+    TAGS.get_in(dir, session_id).unwrap_or_default()
+}
+
+/// Tags given to the session stored in `path`
+pub fn for_file(path: &Path) -> Vec<String> {
+    TAGS.for_file(path).unwrap_or_default()
+}
+
+/// Tags given to the session stored in `path`, from the sidecar in `dir`
+pub fn for_file_in(dir: &Path, path: &Path) -> Vec<String> {
+    TAGS.for_file_in(dir, path).unwrap_or_default()
+}
+
+/// Replace a session's tags (none removes it) and save the sidecar, unless the index is kept
+/// in memory. Returns whether anything changed.
+pub fn set(session_id: &str, path: &Path, tags: Vec<String>) -> Result<bool> {
+    TAGS.set(session_id, path, (!tags.is_empty()).then_some(tags))
+}
+
+/// Tags from comma-separated input: lowercase, with spaces and anything a query couldn't
+/// name turned into dashes, deduplicated in the order given
+pub fn parse(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input.split(',') {
+        let tag: String = tag
+            .trim()
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_alphanumeric() || "-_./".contains(c) { c } else { '-' })
+            .collect();
+        let tag = tag.trim_matches('-').to_string();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let tags = parse("billing-bug, Research ,demo prep");
+        assert_eq!(tags, ["billing-bug", "research", "demo-prep"]);
+        assert_eq!(parse(" , research, research,"), ["research"]);
+        assert_eq!(parse("v2.1, \"quoted\", a:b"), ["v2.1", "quoted", "a-b"]);
+        assert!(parse("").is_empty());
+    }
+}
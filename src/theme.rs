@@ -1,4 +1,35 @@
-use ratatui::style::Color;
+use crate::session::SessionSource;
+use anyhow::Result;
+use ratatui::style::{Color, Modifier, Style};
+use std::time::Duration;
+
+/// A named theme constructor
+type Preset = (&'static str, fn() -> Theme);
+
+/// Built-in themes, selectable by name with `theme = "…"` in the config or RECALL_THEME
+const PRESETS: &[Preset] = &[
+    ("dark", Theme::dark),
+    ("light", Theme::light),
+    ("gruvbox", Theme::gruvbox),
+    ("nord", Theme::nord),
+    ("solarized-dark", Theme::solarized_dark),
+    ("solarized-light", Theme::solarized_light),
+    ("mono", Theme::monochrome),
+];
+
+/// How long to wait for the terminal to report its background color. Some terminals and
+/// multiplexers never answer, and first paint waits on this.
+const BG_QUERY_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Names of the built-in themes
+pub fn preset_names() -> Vec<&'static str> {
+    PRESETS.iter().map(|(name, _)| *name).collect()
+}
+
+/// Whether `name` is "auto" or a built-in theme
+pub fn is_known(name: &str) -> bool {
+    name == "auto" || PRESETS.iter().any(|(preset, _)| *preset == name)
+}
 
 /// Terminal theme colors, adapts to light/dark mode
 pub struct Theme {
@@ -44,6 +75,38 @@ pub struct Theme {
     pub opencode_bubble_bg: Color,
     /// OpenCode source indicator color
     pub opencode_source: Color,
+    /// Goose message bubble background
+    pub goose_bubble_bg: Color,
+    /// Goose source indicator color
+    pub goose_source: Color,
+    /// Copilot message bubble background
+    pub copilot_bubble_bg: Color,
+    /// Copilot source indicator color
+    pub copilot_source: Color,
+    /// Amp message bubble background
+    pub amp_bubble_bg: Color,
+    /// Amp source indicator color
+    pub amp_source: Color,
+    /// OpenHands message bubble background
+    pub openhands_bubble_bg: Color,
+    /// OpenHands source indicator color
+    pub openhands_source: Color,
+    /// Zed message bubble background
+    pub zed_bubble_bg: Color,
+    /// Zed source indicator color
+    pub zed_source: Color,
+    /// Windsurf message bubble background
+    pub windsurf_bubble_bg: Color,
+    /// Windsurf source indicator color
+    pub windsurf_source: Color,
+    /// Cline message bubble background
+    pub cline_bubble_bg: Color,
+    /// Cline source indicator color
+    pub cline_source: Color,
+    /// Roo Code message bubble background
+    pub roocode_bubble_bg: Color,
+    /// Roo Code source indicator color
+    pub roocode_source: Color,
     /// Scope indicator background (slightly different from search_bg)
     pub scope_bg: Color,
     /// Scope keycap background (for "/" key)
@@ -54,9 +117,136 @@ pub struct Theme {
     pub scope_label_fg: Color,
     /// Focus indicator color (left border on focused message in preview)
     pub focus_indicator: Color,
+    /// No colors at all: emphasis comes from bold and dim, icons are ASCII
+    pub monochrome: bool,
 }
 
 impl Theme {
+    /// Indicator and label color for a source
+    pub fn source_color(&self, source: SessionSource) -> Color {
+        match source {
+            SessionSource::ClaudeCode | SessionSource::ClaudeDesktop => self.claude_source,
+            SessionSource::CodexCli => self.codex_source,
+            SessionSource::Factory => self.factory_source,
+            SessionSource::OpenCode => self.opencode_source,
+            SessionSource::Goose => self.goose_source,
+            SessionSource::Copilot => self.copilot_source,
+            SessionSource::Amp => self.amp_source,
+            SessionSource::OpenHands => self.openhands_source,
+            SessionSource::Zed => self.zed_source,
+            SessionSource::Windsurf => self.windsurf_source,
+            SessionSource::Cline => self.cline_source,
+            SessionSource::RooCode => self.roocode_source,
+            // Sources without colors of their own
+            SessionSource::Aider
+            | SessionSource::Gemini
+            | SessionSource::Cursor
+            | SessionSource::Continue
+            | SessionSource::AmazonQ
+            | SessionSource::Crush
+            | SessionSource::ChatGpt
+            | SessionSource::Llm
+            | SessionSource::Plandex
+            | SessionSource::Warp
+            | SessionSource::Custom(_) => self.accent_secondary,
+        }
+    }
+
+    /// Assistant message bubble background for a source
+    pub fn bubble_bg(&self, source: SessionSource) -> Color {
+        match source {
+            SessionSource::ClaudeCode | SessionSource::ClaudeDesktop => self.claude_bubble_bg,
+            SessionSource::CodexCli => self.codex_bubble_bg,
+            SessionSource::Factory => self.factory_bubble_bg,
+            SessionSource::OpenCode => self.opencode_bubble_bg,
+            SessionSource::Goose => self.goose_bubble_bg,
+            SessionSource::Copilot => self.copilot_bubble_bg,
+            SessionSource::Amp => self.amp_bubble_bg,
+            SessionSource::OpenHands => self.openhands_bubble_bg,
+            SessionSource::Zed => self.zed_bubble_bg,
+            SessionSource::Windsurf => self.windsurf_bubble_bg,
+            SessionSource::Cline => self.cline_bubble_bg,
+            SessionSource::RooCode => self.roocode_bubble_bg,
+            SessionSource::Aider
+            | SessionSource::Gemini
+            | SessionSource::Cursor
+            | SessionSource::Continue
+            | SessionSource::AmazonQ
+            | SessionSource::Crush
+            | SessionSource::ChatGpt
+            | SessionSource::Llm
+            | SessionSource::Plandex
+            | SessionSource::Warp
+            | SessionSource::Custom(_) => self.claude_bubble_bg,
+        }
+    }
+
+    /// Icon for a source, ASCII when monochrome
+    pub fn icon(&self, source: SessionSource) -> &'static str {
+        if self.monochrome {
+            source.ascii_icon()
+        } else {
+            source.icon()
+        }
+    }
+
+    /// Style for secondary text: the dim color, or the dim modifier when monochrome
+    pub fn dim(&self) -> Style {
+        if self.monochrome {
+            Style::default().add_modifier(Modifier::DIM)
+        } else {
+            Style::default().fg(self.dim_fg)
+        }
+    }
+
+    /// Style for a key hint like " / ", bold instead of boxed when monochrome
+    pub fn keycap(&self) -> Style {
+        if self.monochrome {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().bg(self.keycap_bg)
+        }
+    }
+
+    /// Style for a tag chip in a result header, reversed when monochrome
+    pub fn chip(&self) -> Style {
+        if self.monochrome {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default().fg(self.accent).bg(self.keycap_bg)
+        }
+    }
+
+    /// A theme by name, detecting light or dark for "auto"
+    pub fn named(name: &str) -> Option<Self> {
+        if name == "auto" {
+            return Some(Self::detect());
+        }
+        PRESETS
+            .iter()
+            .find(|(preset, _)| *preset == name)
+            .map(|(_, theme)| theme())
+    }
+
+    /// The theme to use: monochrome under NO_COLOR or `--no-color`, else RECALL_THEME if set,
+    /// otherwise `configured` (the config's `theme`)
+    pub fn select(configured: &str) -> Result<Self> {
+        if no_color() {
+            return Ok(Self::monochrome());
+        }
+        let name = std::env::var("RECALL_THEME")
+            .ok()
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| configured.to_string());
+        Self::named(&name).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unknown theme '{}'. Valid: auto, {}",
+                name,
+                preset_names().join(", ")
+            )
+        })
+    }
+
     pub fn detect() -> Self {
         let is_light = detect_light_theme();
         if is_light {
@@ -89,11 +279,28 @@ impl Theme {
             factory_source: Color::Rgb(150, 120, 200), // Google purple
             opencode_bubble_bg: Color::Rgb(30, 40, 55), // subtle blue tint
             opencode_source: Color::Rgb(100, 150, 255), // sky blue
+            goose_bubble_bg: Color::Rgb(45, 42, 28), // subtle yellow tint
+            goose_source: Color::Rgb(230, 190, 60), // goose-beak yellow
+            copilot_bubble_bg: Color::Rgb(35, 35, 55), // subtle indigo tint
+            copilot_source: Color::Rgb(130, 140, 255), // Copilot indigo
+            amp_bubble_bg: Color::Rgb(50, 32, 36), // subtle red tint
+            amp_source: Color::Rgb(240, 90, 110), // Amp red
+            openhands_bubble_bg: Color::Rgb(45, 32, 50), // subtle magenta tint
+            openhands_source: Color::Rgb(200, 110, 220), // OpenHands magenta
+            zed_bubble_bg: Color::Rgb(28, 44, 44), // subtle teal tint
+            zed_source: Color::Rgb(70, 200, 190), // Zed teal
+            windsurf_bubble_bg: Color::Rgb(26, 42, 40), // subtle sea-green tint
+            windsurf_source: Color::Rgb(60, 210, 150), // Windsurf sea green
+            cline_bubble_bg: Color::Rgb(34, 36, 52), // subtle indigo tint
+            cline_source: Color::Rgb(140, 150, 255), // Cline indigo
+            roocode_bubble_bg: Color::Rgb(48, 34, 30), // subtle coral tint
+            roocode_source: Color::Rgb(240, 125, 95), // Roo coral
             scope_bg: Color::Rgb(45, 45, 50),         // slightly lighter than search_bg
             scope_key_bg: Color::Rgb(60, 60, 65),     // keycap style
             separator_fg: Color::Rgb(60, 60, 65),     // subtle separator
             scope_label_fg: Color::Rgb(140, 140, 140), // readable but not bright
             focus_indicator: Color::Rgb(100, 180, 255), // bright blue
+            monochrome: false,
         }
     }
 
@@ -120,28 +327,297 @@ impl Theme {
             factory_source: Color::Rgb(100, 80, 160),  // Google purple (darker for light bg)
             opencode_bubble_bg: Color::Rgb(225, 235, 250), // subtle blue tint
             opencode_source: Color::Rgb(50, 100, 200), // sky blue (darker for light bg)
+            goose_bubble_bg: Color::Rgb(248, 242, 215), // subtle yellow tint
+            goose_source: Color::Rgb(160, 120, 0), // goose-beak yellow (darker for light bg)
+            copilot_bubble_bg: Color::Rgb(232, 232, 250), // subtle indigo tint
+            copilot_source: Color::Rgb(80, 80, 200), // Copilot indigo (darker for light bg)
+            amp_bubble_bg: Color::Rgb(250, 228, 232), // subtle red tint
+            amp_source: Color::Rgb(190, 40, 70), // Amp red (darker for light bg)
+            openhands_bubble_bg: Color::Rgb(244, 230, 248), // subtle magenta tint
+            openhands_source: Color::Rgb(150, 50, 170), // OpenHands magenta (darker for light bg)
+            zed_bubble_bg: Color::Rgb(222, 242, 240), // subtle teal tint
+            zed_source: Color::Rgb(0, 130, 125), // Zed teal (darker for light bg)
+            windsurf_bubble_bg: Color::Rgb(222, 245, 235), // subtle sea-green tint
+            windsurf_source: Color::Rgb(10, 140, 95), // Windsurf sea green (darker for light bg)
+            cline_bubble_bg: Color::Rgb(232, 234, 252), // subtle indigo tint
+            cline_source: Color::Rgb(70, 80, 200), // Cline indigo (darker for light bg)
+            roocode_bubble_bg: Color::Rgb(252, 236, 230), // subtle coral tint
+            roocode_source: Color::Rgb(190, 75, 45), // Roo coral (darker for light bg)
             scope_bg: Color::Rgb(215, 215, 220),      // slightly darker than search_bg
             scope_key_bg: Color::Rgb(200, 200, 205),  // keycap style
             separator_fg: Color::Rgb(195, 195, 200),  // visible on light bg
             scope_label_fg: Color::Rgb(100, 100, 100), // readable on light bg
             focus_indicator: Color::Rgb(50, 120, 200), // darker blue for light bg
+            monochrome: false,
+        }
+    }
+}
+
+impl Theme {
+    fn gruvbox() -> Self {
+        Self {
+            selection_bg: Color::Rgb(60, 56, 54),            // bg1
+            selection_header_fg: Color::Rgb(250, 189, 47),   // yellow
+            selection_snippet_fg: Color::Rgb(235, 219, 178), // fg
+            snippet_fg: Color::Rgb(168, 153, 132),           // fg4
+            match_fg: Color::Rgb(254, 128, 25),              // orange
+            search_bg: Color::Rgb(29, 32, 33),               // bg0_h
+            placeholder_fg: Color::Rgb(146, 131, 116),       // gray
+            accent: Color::Rgb(142, 192, 124),               // aqua
+            accent_secondary: Color::Rgb(184, 187, 38),      // green
+            dim_fg: Color::Rgb(146, 131, 116),
+            keycap_bg: Color::Rgb(80, 73, 69), // bg2
+            user_bubble_bg: Color::Rgb(40, 48, 45),
+            user_label: Color::Rgb(131, 165, 152), // blue
+            claude_bubble_bg: Color::Rgb(50, 40, 32),
+            codex_bubble_bg: Color::Rgb(42, 46, 32),
+            claude_source: Color::Rgb(254, 128, 25), // orange
+            codex_source: Color::Rgb(184, 187, 38),  // green
+            factory_bubble_bg: Color::Rgb(48, 38, 44),
+            factory_source: Color::Rgb(211, 134, 155), // purple
+            opencode_bubble_bg: Color::Rgb(36, 44, 46),
+            opencode_source: Color::Rgb(131, 165, 152), // blue
+            goose_bubble_bg: Color::Rgb(50, 46, 30),
+            goose_source: Color::Rgb(215, 153, 33), // yellow
+            copilot_bubble_bg: Color::Rgb(38, 44, 40),
+            copilot_source: Color::Rgb(104, 157, 106), // aqua
+            amp_bubble_bg: Color::Rgb(52, 36, 34),
+            amp_source: Color::Rgb(251, 73, 52), // red
+            openhands_bubble_bg: Color::Rgb(48, 38, 44),
+            openhands_source: Color::Rgb(211, 134, 155), // purple
+            zed_bubble_bg: Color::Rgb(38, 46, 40),
+            zed_source: Color::Rgb(142, 192, 124), // aqua
+            windsurf_bubble_bg: Color::Rgb(40, 46, 36),
+            windsurf_source: Color::Rgb(184, 187, 38), // green
+            cline_bubble_bg: Color::Rgb(44, 40, 46),
+            cline_source: Color::Rgb(211, 134, 155), // purple
+            roocode_bubble_bg: Color::Rgb(50, 38, 32),
+            roocode_source: Color::Rgb(254, 128, 25), // orange
+            scope_bg: Color::Rgb(50, 48, 47),           // bg0_s
+            scope_key_bg: Color::Rgb(80, 73, 69),
+            separator_fg: Color::Rgb(80, 73, 69),
+            scope_label_fg: Color::Rgb(189, 174, 147), // fg3
+            focus_indicator: Color::Rgb(250, 189, 47), // yellow
+            monochrome: false,
+        }
+    }
+
+    fn nord() -> Self {
+        Self {
+            selection_bg: Color::Rgb(59, 66, 82),            // nord1
+            selection_header_fg: Color::Rgb(136, 192, 208),  // nord8
+            selection_snippet_fg: Color::Rgb(216, 222, 233), // nord4
+            snippet_fg: Color::Rgb(129, 140, 160),
+            match_fg: Color::Rgb(235, 203, 139),     // nord13
+            search_bg: Color::Rgb(46, 52, 64),       // nord0
+            placeholder_fg: Color::Rgb(76, 86, 106), // nord3
+            accent: Color::Rgb(136, 192, 208),
+            accent_secondary: Color::Rgb(163, 190, 140), // nord14
+            dim_fg: Color::Rgb(110, 120, 140),
+            keycap_bg: Color::Rgb(67, 76, 94), // nord2
+            user_bubble_bg: Color::Rgb(48, 60, 74),
+            user_label: Color::Rgb(129, 161, 193), // nord9
+            claude_bubble_bg: Color::Rgb(60, 56, 62),
+            codex_bubble_bg: Color::Rgb(52, 62, 62),
+            claude_source: Color::Rgb(208, 135, 112), // nord12
+            codex_source: Color::Rgb(163, 190, 140),
+            factory_bubble_bg: Color::Rgb(58, 56, 72),
+            factory_source: Color::Rgb(180, 142, 173), // nord15
+            opencode_bubble_bg: Color::Rgb(48, 58, 78),
+            opencode_source: Color::Rgb(94, 129, 172), // nord10
+            goose_bubble_bg: Color::Rgb(58, 60, 62),
+            goose_source: Color::Rgb(235, 203, 139), // nord13
+            copilot_bubble_bg: Color::Rgb(50, 56, 72),
+            copilot_source: Color::Rgb(143, 188, 187), // nord7
+            amp_bubble_bg: Color::Rgb(60, 52, 62),
+            amp_source: Color::Rgb(191, 97, 106), // nord11
+            openhands_bubble_bg: Color::Rgb(58, 52, 66),
+            openhands_source: Color::Rgb(180, 142, 173), // nord15
+            zed_bubble_bg: Color::Rgb(48, 60, 70),
+            zed_source: Color::Rgb(136, 192, 208), // nord8
+            windsurf_bubble_bg: Color::Rgb(50, 60, 60),
+            windsurf_source: Color::Rgb(163, 190, 140), // nord14
+            cline_bubble_bg: Color::Rgb(52, 56, 72),
+            cline_source: Color::Rgb(180, 142, 173), // nord15
+            roocode_bubble_bg: Color::Rgb(62, 56, 60),
+            roocode_source: Color::Rgb(208, 135, 112), // nord12
+            scope_bg: Color::Rgb(59, 66, 82),
+            scope_key_bg: Color::Rgb(67, 76, 94),
+            separator_fg: Color::Rgb(67, 76, 94),
+            scope_label_fg: Color::Rgb(216, 222, 233),
+            focus_indicator: Color::Rgb(136, 192, 208),
+            monochrome: false,
+        }
+    }
+
+    fn solarized_dark() -> Self {
+        Self {
+            selection_bg: Color::Rgb(7, 54, 66),             // base02
+            selection_header_fg: Color::Rgb(42, 161, 152),   // cyan
+            selection_snippet_fg: Color::Rgb(147, 161, 161), // base1
+            snippet_fg: Color::Rgb(101, 123, 131),           // base00
+            match_fg: Color::Rgb(181, 137, 0),               // yellow
+            search_bg: Color::Rgb(0, 43, 54),                // base03
+            placeholder_fg: Color::Rgb(88, 110, 117),        // base01
+            accent: Color::Rgb(42, 161, 152),
+            accent_secondary: Color::Rgb(133, 153, 0), // green
+            dim_fg: Color::Rgb(88, 110, 117),
+            keycap_bg: Color::Rgb(7, 54, 66),
+            user_bubble_bg: Color::Rgb(4, 52, 70),
+            user_label: Color::Rgb(38, 139, 210), // blue
+            claude_bubble_bg: Color::Rgb(24, 48, 50),
+            codex_bubble_bg: Color::Rgb(10, 52, 52),
+            claude_source: Color::Rgb(203, 75, 22), // orange
+            codex_source: Color::Rgb(133, 153, 0),
+            factory_bubble_bg: Color::Rgb(16, 46, 66),
+            factory_source: Color::Rgb(108, 113, 196), // violet
+            opencode_bubble_bg: Color::Rgb(4, 48, 68),
+            opencode_source: Color::Rgb(38, 139, 210),
+            goose_bubble_bg: Color::Rgb(20, 50, 46),
+            goose_source: Color::Rgb(181, 137, 0), // yellow
+            copilot_bubble_bg: Color::Rgb(4, 46, 58),
+            copilot_source: Color::Rgb(42, 161, 152), // cyan
+            amp_bubble_bg: Color::Rgb(20, 40, 50),
+            amp_source: Color::Rgb(220, 50, 47), // red
+            openhands_bubble_bg: Color::Rgb(30, 40, 56),
+            openhands_source: Color::Rgb(211, 54, 130), // magenta
+            zed_bubble_bg: Color::Rgb(4, 46, 52),
+            zed_source: Color::Rgb(42, 161, 152), // cyan
+            windsurf_bubble_bg: Color::Rgb(10, 48, 44),
+            windsurf_source: Color::Rgb(133, 153, 0), // green
+            cline_bubble_bg: Color::Rgb(14, 40, 58),
+            cline_source: Color::Rgb(108, 113, 196), // violet
+            roocode_bubble_bg: Color::Rgb(40, 36, 30),
+            roocode_source: Color::Rgb(203, 75, 22), // orange
+            scope_bg: Color::Rgb(7, 54, 66),
+            scope_key_bg: Color::Rgb(20, 70, 82),
+            separator_fg: Color::Rgb(20, 70, 82),
+            scope_label_fg: Color::Rgb(131, 148, 150), // base0
+            focus_indicator: Color::Rgb(38, 139, 210),
+            monochrome: false,
+        }
+    }
+
+    fn solarized_light() -> Self {
+        Self {
+            selection_bg: Color::Rgb(238, 232, 213),        // base2
+            selection_header_fg: Color::Rgb(38, 139, 210),  // blue
+            selection_snippet_fg: Color::Rgb(88, 110, 117), // base01
+            snippet_fg: Color::Rgb(131, 148, 150),          // base0
+            match_fg: Color::Rgb(203, 75, 22),              // orange
+            search_bg: Color::Rgb(253, 246, 227),           // base3
+            placeholder_fg: Color::Rgb(147, 161, 161),      // base1
+            accent: Color::Rgb(42, 161, 152),               // cyan
+            accent_secondary: Color::Rgb(133, 153, 0),      // green
+            dim_fg: Color::Rgb(147, 161, 161),
+            keycap_bg: Color::Rgb(238, 232, 213),
+            user_bubble_bg: Color::Rgb(230, 236, 228),
+            user_label: Color::Rgb(38, 139, 210),
+            claude_bubble_bg: Color::Rgb(246, 230, 210),
+            codex_bubble_bg: Color::Rgb(236, 238, 208),
+            claude_source: Color::Rgb(203, 75, 22),
+            codex_source: Color::Rgb(133, 153, 0),
+            factory_bubble_bg: Color::Rgb(236, 230, 236),
+            factory_source: Color::Rgb(108, 113, 196), // violet
+            opencode_bubble_bg: Color::Rgb(228, 234, 236),
+            opencode_source: Color::Rgb(38, 139, 210),
+            goose_bubble_bg: Color::Rgb(238, 232, 213),
+            goose_source: Color::Rgb(181, 137, 0),
+            copilot_bubble_bg: Color::Rgb(232, 236, 230),
+            copilot_source: Color::Rgb(42, 161, 152),
+            amp_bubble_bg: Color::Rgb(242, 230, 222),
+            amp_source: Color::Rgb(220, 50, 47),
+            openhands_bubble_bg: Color::Rgb(244, 230, 232),
+            openhands_source: Color::Rgb(211, 54, 130),
+            zed_bubble_bg: Color::Rgb(226, 240, 236),
+            zed_source: Color::Rgb(42, 161, 152),
+            windsurf_bubble_bg: Color::Rgb(236, 240, 222),
+            windsurf_source: Color::Rgb(133, 153, 0),
+            cline_bubble_bg: Color::Rgb(236, 234, 244),
+            cline_source: Color::Rgb(108, 113, 196),
+            roocode_bubble_bg: Color::Rgb(248, 236, 222),
+            roocode_source: Color::Rgb(203, 75, 22),
+            scope_bg: Color::Rgb(238, 232, 213),
+            scope_key_bg: Color::Rgb(225, 219, 200),
+            separator_fg: Color::Rgb(225, 219, 200),
+            scope_label_fg: Color::Rgb(101, 123, 131), // base00
+            focus_indicator: Color::Rgb(38, 139, 210),
+            monochrome: false,
         }
     }
 }
 
+impl Theme {
+    /// Terminal default colors only, for NO_COLOR and monochrome terminals
+    pub fn monochrome() -> Self {
+        Self {
+            selection_bg: Color::Reset,
+            selection_header_fg: Color::Reset,
+            selection_snippet_fg: Color::Reset,
+            snippet_fg: Color::Reset,
+            match_fg: Color::Reset,
+            search_bg: Color::Reset,
+            placeholder_fg: Color::Reset,
+            accent: Color::Reset,
+            accent_secondary: Color::Reset,
+            dim_fg: Color::Reset,
+            keycap_bg: Color::Reset,
+            user_bubble_bg: Color::Reset,
+            user_label: Color::Reset,
+            claude_bubble_bg: Color::Reset,
+            codex_bubble_bg: Color::Reset,
+            claude_source: Color::Reset,
+            codex_source: Color::Reset,
+            factory_bubble_bg: Color::Reset,
+            factory_source: Color::Reset,
+            opencode_bubble_bg: Color::Reset,
+            opencode_source: Color::Reset,
+            goose_bubble_bg: Color::Reset,
+            goose_source: Color::Reset,
+            copilot_bubble_bg: Color::Reset,
+            copilot_source: Color::Reset,
+            amp_bubble_bg: Color::Reset,
+            amp_source: Color::Reset,
+            openhands_bubble_bg: Color::Reset,
+            openhands_source: Color::Reset,
+            zed_bubble_bg: Color::Reset,
+            zed_source: Color::Reset,
+            windsurf_bubble_bg: Color::Reset,
+            windsurf_source: Color::Reset,
+            cline_bubble_bg: Color::Reset,
+            cline_source: Color::Reset,
+            roocode_bubble_bg: Color::Reset,
+            roocode_source: Color::Reset,
+            scope_bg: Color::Reset,
+            scope_key_bg: Color::Reset,
+            separator_fg: Color::Reset,
+            scope_label_fg: Color::Reset,
+            focus_indicator: Color::Reset,
+            monochrome: true,
+        }
+    }
+}
+
+/// Whether colors are turned off, by a non-empty NO_COLOR (https://no-color.org) or
+/// `--no-color`
+pub fn no_color() -> bool {
+    crate::config::get().no_color || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
 /// Detect if terminal has a light background
 fn detect_light_theme() -> bool {
-    // Try to query terminal's actual background color
-    if let Some(bg) = query_terminal_bg() {
+    is_light_background(query_terminal_bg(), std::env::var("COLORFGBG").ok().as_deref())
+}
+
+/// Light or dark from the queried background color, falling back to COLORFGBG
+/// (format: "fg;bg" where 15=white, 0=black), then to dark
+fn is_light_background(queried: Option<(u8, u8, u8)>, colorfgbg: Option<&str>) -> bool {
+    if let Some(bg) = queried {
         return is_light(bg);
     }
-
-    // Fallback: Check COLORFGBG env var (format: "fg;bg" where 15=white, 0=black)
-    if let Ok(val) = std::env::var("COLORFGBG") {
-        if let Some(bg) = val.split(';').next_back() {
-            if let Ok(bg_num) = bg.parse::<u8>() {
-                return bg_num >= 7;
-            }
+    if let Some(bg) = colorfgbg.and_then(|val| val.split(';').next_back()) {
+        if let Ok(bg_num) = bg.parse::<u8>() {
+            return bg_num >= 7;
         }
     }
 
@@ -161,6 +637,7 @@ fn is_light(color: (u8, u8, u8)) -> bool {
 #[cfg(unix)]
 fn query_terminal_bg() -> Option<(u8, u8, u8)> {
     use crossterm::style::{query_background_color, Color as CtColor};
+    use crossterm::terminal::{disable_raw_mode, enable_raw_mode, is_raw_mode_enabled};
     use std::io::IsTerminal;
     use std::sync::OnceLock;
 
@@ -174,13 +651,22 @@ fn query_terminal_bg() -> Option<(u8, u8, u8)> {
     static CACHED: OnceLock<Option<(u8, u8, u8)>> = OnceLock::new();
 
     *CACHED.get_or_init(|| {
-        query_background_color()
-            .ok()
-            .flatten()
-            .and_then(|c| match c {
+        // Hold raw mode ourselves, so a query still waiting after the timeout doesn't
+        // switch it off again underneath the TUI
+        let was_raw = is_raw_mode_enabled().unwrap_or(false);
+        if !was_raw && enable_raw_mode().is_err() {
+            return None;
+        }
+        let color = with_timeout(BG_QUERY_TIMEOUT, || {
+            query_background_color().ok().flatten().and_then(|c| match c {
                 CtColor::Rgb { r, g, b } => Some((r, g, b)),
                 _ => None,
             })
+        });
+        if !was_raw {
+            let _ = disable_raw_mode();
+        }
+        color.flatten()
     })
 }
 
@@ -188,3 +674,88 @@ fn query_terminal_bg() -> Option<(u8, u8, u8)> {
 fn query_terminal_bg() -> Option<(u8, u8, u8)> {
     None
 }
+
+/// Run `f` on its own thread and give up on it after `timeout`. A call that never returns
+/// is left running in the background.
+fn with_timeout<T: Send + 'static>(
+    timeout: Duration,
+    f: impl FnOnce() -> T + Send + 'static,
+) -> Option<T> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(f());
+    });
+    rx.recv_timeout(timeout).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_named_presets() {
+        for name in preset_names() {
+            assert!(is_known(name));
+            assert!(Theme::named(name).is_some(), "{}", name);
+        }
+        assert_eq!(Theme::named("light").unwrap().selection_bg, Theme::light().selection_bg);
+        assert_eq!(Theme::named("gruvbox").unwrap().search_bg, Color::Rgb(29, 32, 33));
+        assert!(is_known("auto"));
+        assert!(!is_known("purple"));
+        assert!(Theme::named("purple").is_none());
+    }
+
+    #[test]
+    fn test_monochrome_has_no_colors() {
+        let theme = Theme::monochrome();
+        assert!(theme.monochrome);
+        for source in SessionSource::ALL {
+            assert_eq!(theme.source_color(source), Color::Reset);
+            assert_eq!(theme.bubble_bg(source), Color::Reset);
+            assert!(theme.icon(source).is_ascii());
+        }
+        assert_eq!(theme.dim().fg, None);
+        assert!(!Theme::dark().icon(SessionSource::ClaudeCode).is_ascii());
+    }
+
+    #[test]
+    fn test_recall_theme_overrides_config() {
+        // The only test in this crate that sets RECALL_THEME
+        std::env::set_var("RECALL_THEME", "mono");
+        let mono = Theme::select("auto");
+        std::env::set_var("RECALL_THEME", "light");
+        let light = Theme::select("gruvbox");
+        std::env::set_var("RECALL_THEME", "purple");
+        let unknown = Theme::select("dark");
+        std::env::remove_var("RECALL_THEME");
+
+        assert!(mono.unwrap().monochrome);
+        assert_eq!(light.unwrap().selection_bg, Theme::light().selection_bg);
+        assert!(unknown.is_err());
+    }
+
+    #[test]
+    fn test_background_fallbacks() {
+        assert!(is_light_background(Some((250, 250, 245)), Some("0;0")));
+        assert!(!is_light_background(Some((30, 30, 30)), Some("0;15")));
+        assert!(is_light_background(None, Some("0;15")));
+        assert!(is_light_background(None, Some("0;default;15")));
+        assert!(!is_light_background(None, Some("15;0")));
+        assert!(!is_light_background(None, Some("garbage")));
+        assert!(!is_light_background(None, None));
+    }
+
+    #[test]
+    fn test_query_timeout() {
+        let start = std::time::Instant::now();
+        let hung = with_timeout(Duration::from_millis(50), || {
+            std::thread::sleep(Duration::from_secs(5));
+            Some((255, 255, 255))
+        });
+        assert_eq!(hung, None);
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        let answered = with_timeout(BG_QUERY_TIMEOUT, || Some((1, 2, 3)));
+        assert_eq!(answered, Some(Some((1, 2, 3))));
+    }
+}
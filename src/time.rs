@@ -0,0 +1,337 @@
+//! Human-friendly time parsing for `--since` and `--until`, and timestamps for display
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Local, TimeZone, Utc};
+use std::fmt::Display;
+
+/// Parse a human-friendly time string into a DateTime
+/// Supports: "1 week ago", "2 days ago", "yesterday", "2025-12-01", ISO 8601
+pub fn parse_time(s: &str) -> Result<DateTime<Utc>> {
+    let s = s.trim().to_lowercase();
+
+    // Handle relative times
+    if s == "yesterday" {
+        return Ok(Utc::now() - Duration::days(1));
+    }
+    if s == "today" {
+        return Ok(Utc::now());
+    }
+
+    // Handle "N unit ago" patterns (also "N unit" and compact "1day", "2h")
+    let relative = s.strip_suffix(" ago").unwrap_or(&s);
+    if let Some((n, unit)) = split_amount(relative) {
+        let unit = unit.trim_end_matches('s'); // "weeks" -> "week"
+
+        let duration = match unit {
+            "minute" | "min" => Duration::minutes(n),
+            "hour" | "hr" | "h" => Duration::hours(n),
+            "day" | "d" => Duration::days(n),
+            "week" | "wk" | "w" => Duration::weeks(n),
+            "month" | "mo" => Duration::days(n * 30), // Approximate
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Unknown time unit: {}. Use minutes, hours, days, weeks, months",
+                    unit
+                ))
+            }
+        };
+
+        return Ok(Utc::now() - duration);
+    }
+
+    // Try parsing as ISO 8601 or date
+    if let Ok(dt) = DateTime::parse_from_rfc3339(&s) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+
+    // Try parsing as simple date (YYYY-MM-DD)
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(&s, "%Y-%m-%d") {
+        return Ok(date
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc());
+    }
+
+    Err(anyhow::anyhow!(
+        "Invalid time format: {}. Try '1 week ago', 'yesterday', or '2025-12-01'",
+        s
+    ))
+}
+
+/// Split "3 days" or "3days" into (3, "days"). The unit must be alphabetic.
+fn split_amount(s: &str) -> Option<(i64, &str)> {
+    let digits = s.find(|c: char| !c.is_ascii_digit())?;
+    let n = s[..digits].parse().ok()?;
+    let unit = s[digits..].trim();
+    if unit.is_empty() || !unit.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some((n, unit))
+}
+
+/// Shows timestamps as "5m ago" while recent, then as a date in `tz`. The clock and the
+/// timezone are fixed at construction so the output doesn't depend on where tests run.
+pub struct TimeFormat<Tz: TimeZone> {
+    now: DateTime<Utc>,
+    tz: Tz,
+    /// strftime format for dates; default "%b %d", plus the year when it isn't this year's
+    date_format: Option<String>,
+}
+
+impl TimeFormat<Local> {
+    /// The system clock and timezone
+    pub fn local(date_format: Option<&str>) -> Self {
+        Self::new(Utc::now(), Local, date_format)
+    }
+}
+
+impl<Tz: TimeZone> TimeFormat<Tz>
+where
+    Tz::Offset: Display,
+{
+    pub fn new(now: DateTime<Utc>, tz: Tz, date_format: Option<&str>) -> Self {
+        Self { now, tz, date_format: date_format.map(str::to_string) }
+    }
+
+    /// Format a timestamp as a human-readable "time ago" string
+    pub fn ago(&self, timestamp: DateTime<Utc>) -> String {
+        self.relative(timestamp).unwrap_or_else(|| self.date(timestamp))
+    }
+
+    /// [`TimeFormat::ago`] for narrow columns, with [`TimeFormat::short_date`] for dates
+    pub fn short_ago(&self, timestamp: DateTime<Utc>) -> String {
+        self.relative(timestamp).unwrap_or_else(|| self.short_date(timestamp))
+    }
+
+    /// "5m ago" and the like, or None once the timestamp is old enough to show as a date
+    fn relative(&self, timestamp: DateTime<Utc>) -> Option<String> {
+        let duration = self.now.signed_duration_since(timestamp);
+
+        // Clock skew between machines can put timestamps slightly in the future. Past a
+        // minute ahead, a relative time would be nonsense, so show the date.
+        if duration < -Duration::minutes(1) {
+            None
+        } else if duration.num_minutes() < 1 {
+            Some("just now".to_string())
+        } else if duration.num_minutes() < 60 {
+            Some(format!("{}m ago", duration.num_minutes()))
+        } else if duration.num_hours() < 24 {
+            Some(format!("{}h ago", duration.num_hours()))
+        } else if duration.num_days() < 7 {
+            Some(format!("{}d ago", duration.num_days()))
+        } else if duration.num_weeks() < 4 {
+            Some(format!("{}w ago", duration.num_weeks()))
+        } else {
+            None
+        }
+    }
+
+    /// The timestamp's date in this timezone
+    pub fn date(&self, timestamp: DateTime<Utc>) -> String {
+        let local = timestamp.with_timezone(&self.tz);
+        match &self.date_format {
+            Some(format) => local.format(format).to_string(),
+            None if local.year() == self.now.with_timezone(&self.tz).year() => {
+                local.format("%b %d").to_string()
+            }
+            None => local.format("%b %d %Y").to_string(),
+        }
+    }
+
+    /// The timestamp's date in this timezone, leaving out the day rather than the year when
+    /// it isn't this year's ("Jan 2024"). A configured `date_format` is used as it is.
+    pub fn short_date(&self, timestamp: DateTime<Utc>) -> String {
+        let local = timestamp.with_timezone(&self.tz);
+        match &self.date_format {
+            None if local.year() != self.now.with_timezone(&self.tz).year() => {
+                local.format("%b %Y").to_string()
+            }
+            _ => self.date(timestamp),
+        }
+    }
+}
+
+/// Check a strftime format string, e.g. `date_format` from the config
+pub fn validate_format(format: &str) -> Result<()> {
+    use chrono::format::{Item, StrftimeItems};
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        anyhow::bail!("Invalid date format '{}'", format);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+
+    #[test]
+    fn test_parse_time_yesterday() {
+        let result = parse_time("yesterday").unwrap();
+        let expected = Utc::now() - Duration::days(1);
+        // Allow 1 second tolerance for test execution time
+        assert!((result - expected).num_seconds().abs() < 2);
+    }
+
+    #[test]
+    fn test_parse_time_today() {
+        let result = parse_time("today").unwrap();
+        let expected = Utc::now();
+        assert!((result - expected).num_seconds().abs() < 2);
+    }
+
+    #[test]
+    fn test_parse_time_relative_days() {
+        let result = parse_time("3 days ago").unwrap();
+        let expected = Utc::now() - Duration::days(3);
+        assert!((result - expected).num_seconds().abs() < 2);
+    }
+
+    #[test]
+    fn test_parse_time_relative_weeks() {
+        let result = parse_time("2 weeks ago").unwrap();
+        let expected = Utc::now() - Duration::weeks(2);
+        assert!((result - expected).num_seconds().abs() < 2);
+    }
+
+    #[test]
+    fn test_parse_time_relative_hours() {
+        let result = parse_time("5 hours ago").unwrap();
+        let expected = Utc::now() - Duration::hours(5);
+        assert!((result - expected).num_seconds().abs() < 2);
+    }
+
+    #[test]
+    fn test_parse_time_relative_minutes() {
+        let result = parse_time("30 minutes ago").unwrap();
+        let expected = Utc::now() - Duration::minutes(30);
+        assert!((result - expected).num_seconds().abs() < 2);
+    }
+
+    #[test]
+    fn test_parse_time_relative_months() {
+        let result = parse_time("2 months ago").unwrap();
+        let expected = Utc::now() - Duration::days(60); // 2 * 30
+        assert!((result - expected).num_seconds().abs() < 2);
+    }
+
+    #[test]
+    fn test_parse_time_short_units() {
+        // Test abbreviated units
+        assert!(parse_time("1 hr ago").is_ok());
+        assert!(parse_time("5 min ago").is_ok());
+        assert!(parse_time("1 wk ago").is_ok());
+        assert!(parse_time("1 mo ago").is_ok());
+    }
+
+    #[test]
+    fn test_parse_time_without_ago() {
+        let expected = Utc::now() - Duration::days(1);
+        for input in ["1day", "1 day", "1d", "1 day ago"] {
+            let result = parse_time(input).unwrap();
+            assert!((result - expected).num_seconds().abs() < 2, "{}", input);
+        }
+        assert!(parse_time("2h").is_ok());
+        assert!(parse_time("3 fortnights").is_err());
+    }
+
+    #[test]
+    fn test_parse_time_date() {
+        let result = parse_time("2025-12-01").unwrap();
+        assert_eq!(result.year(), 2025);
+        assert_eq!(result.month(), 12);
+        assert_eq!(result.day(), 1);
+    }
+
+    #[test]
+    fn test_parse_time_iso8601() {
+        let result = parse_time("2025-12-01T14:30:00Z").unwrap();
+        assert_eq!(result.year(), 2025);
+        assert_eq!(result.month(), 12);
+        assert_eq!(result.day(), 1);
+        assert_eq!(result.hour(), 14);
+        assert_eq!(result.minute(), 30);
+    }
+
+    #[test]
+    fn test_parse_time_case_insensitive() {
+        assert!(parse_time("YESTERDAY").is_ok());
+        assert!(parse_time("Today").is_ok());
+        assert!(parse_time("3 DAYS AGO").is_ok());
+    }
+
+    #[test]
+    fn test_parse_time_whitespace() {
+        assert!(parse_time("  yesterday  ").is_ok());
+        assert!(parse_time("\tyesterday\n").is_ok());
+    }
+
+    #[test]
+    fn test_parse_time_invalid() {
+        assert!(parse_time("invalid").is_err());
+        assert!(parse_time("a week ago").is_err()); // "a" is not a number
+        assert!(parse_time("5 fortnights ago").is_err()); // unknown unit
+    }
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_time_ago_relative() {
+        let now = utc(2025, 6, 15, 12, 0);
+        let format = TimeFormat::new(now, Utc, None);
+        assert_eq!(format.ago(now - Duration::seconds(30)), "just now");
+        assert_eq!(format.ago(now - Duration::minutes(5)), "5m ago");
+        assert_eq!(format.ago(now - Duration::hours(3)), "3h ago");
+        assert_eq!(format.ago(now - Duration::days(2)), "2d ago");
+        assert_eq!(format.ago(now - Duration::weeks(3)), "3w ago");
+    }
+
+    #[test]
+    fn test_time_ago_future() {
+        let now = utc(2025, 6, 15, 12, 0);
+        let format = TimeFormat::new(now, Utc, None);
+        assert_eq!(format.ago(now + Duration::seconds(5)), "just now");
+        assert_eq!(format.ago(now + Duration::hours(2)), "Jun 15");
+        assert_eq!(format.ago(now + Duration::days(3)), "Jun 18");
+    }
+
+    #[test]
+    fn test_time_ago_date_adds_year_when_different() {
+        let format = TimeFormat::new(utc(2025, 6, 15, 12, 0), Utc, None);
+        assert_eq!(format.ago(utc(2025, 1, 12, 9, 0)), "Jan 12");
+        assert_eq!(format.ago(utc(2024, 1, 12, 9, 0)), "Jan 12 2024");
+
+        // Narrow columns keep the year and drop the day
+        assert_eq!(format.short_ago(utc(2024, 1, 12, 9, 0)), "Jan 2024");
+        assert_eq!(format.short_ago(utc(2025, 1, 12, 9, 0)), "Jan 12");
+        assert_eq!(format.short_ago(utc(2025, 6, 14, 9, 0)), "1d ago");
+    }
+
+    #[test]
+    fn test_time_ago_uses_timezone() {
+        let tokyo = chrono_tz::Asia::Tokyo;
+        let now = utc(2025, 6, 15, 12, 0);
+        // 20:00 UTC on New Year's Eve is already New Year's Day in Tokyo
+        assert_eq!(TimeFormat::new(now, tokyo, None).ago(utc(2024, 12, 31, 20, 0)), "Jan 01");
+        assert_eq!(TimeFormat::new(now, Utc, None).ago(utc(2024, 12, 31, 20, 0)), "Dec 31 2024");
+    }
+
+    #[test]
+    fn test_date_format_across_dst() {
+        let new_york = chrono_tz::America::New_York;
+        let format = TimeFormat::new(utc(2025, 6, 15, 12, 0), new_york, Some("%Y-%m-%d %H:%M %Z"));
+        // Clocks went forward at 2:00 local on March 9 and back at 2:00 on November 2
+        assert_eq!(format.ago(utc(2025, 3, 9, 6, 30)), "2025-03-09 01:30 EST");
+        assert_eq!(format.ago(utc(2025, 3, 9, 7, 30)), "2025-03-09 03:30 EDT");
+        assert_eq!(format.date(utc(2025, 11, 2, 5, 30)), "2025-11-02 01:30 EDT");
+        assert_eq!(format.date(utc(2025, 11, 2, 6, 30)), "2025-11-02 01:30 EST");
+    }
+
+    #[test]
+    fn test_validate_format() {
+        assert!(validate_format("%d.%m.%Y").is_ok());
+        assert!(validate_format("%Q").is_err());
+    }
+}
@@ -0,0 +1,37 @@
+//! Titles the user gives sessions in place of the derived ones (a first message of "hi"
+//! makes a poor title), kept in a sidecar file like [`crate::tags`]. The session files are
+//! never touched, so renames survive reindexing; they're indexed too so searching finds them.
+
+use crate::sidecar::Sidecar;
+use anyhow::Result;
+use std::path::Path;
+
+static TITLES: Sidecar<String> = Sidecar::new("titles.json");
+
+/// The title the session with this ID was renamed to
+pub fn get(session_id: &str) -> Option<String> {
+    TITLES.get(session_id)
+}
+
+/// The title the session with this ID was renamed to, from the sidecar in `dir` rather than
+/// the cache dir
+pub fn get_in(dir: &Path, session_id: &str) -> Option<String> {
+    TITLES.get_in(dir, session_id)
+}
+
+/// The title the session stored in `path` was renamed to
+pub fn for_file(path: &Path) -> Option<String> {
+    TITLES.for_file(path)
+}
+
+/// The title the session stored in `path` was renamed to, from the sidecar in `dir`
+pub fn for_file_in(dir: &Path, path: &Path) -> Option<String> {
+    TITLES.for_file_in(dir, path)
+}
+
+/// Rename a session (a blank title goes back to the derived one) and save the sidecar, unless
+/// the index is kept in memory. Returns whether anything changed.
+pub fn set(session_id: &str, path: &Path, title: &str) -> Result<bool> {
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    TITLES.set(session_id, path, (!title.is_empty()).then_some(title))
+}
@@ -1,27 +1,189 @@
 use anyhow::Result;
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
+    cursor::Show,
+    event::{
+        DisableMouseCapture, EnableMouseCapture, KeyboardEnhancementFlags,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use ratatui::{backend::CrosstermBackend, Terminal};
-use std::io::{self, Stdout};
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::{widgets::Clear, Terminal, TerminalOptions, Viewport};
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-pub type Tui = Terminal<CrosstermBackend<Stdout>>;
+pub type Tui = Terminal<CrosstermBackend<Box<dyn Write>>>;
+
+/// Fewest rows an inline UI gets, whatever `--height` asks for
+const MIN_INLINE_ROWS: u16 = 8;
+
+/// Whether the running UI is drawn inline rather than on the alternate screen
+static INLINE: AtomicBool = AtomicBool::new(false);
+
+/// Whether the terminal was asked for kitty protocol key events (see [`crate::keys`])
+static ENHANCED_KEYS: AtomicBool = AtomicBool::new(false);
+
+/// Stream the UI is drawn on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    Stdout,
+    /// Keeps stdout free for a result (e.g. `--select`)
+    Stderr,
+}
+
+impl Output {
+    fn writer(self) -> Box<dyn Write> {
+        match self {
+            Output::Stdout => Box::new(io::stdout()),
+            Output::Stderr => Box::new(io::stderr()),
+        }
+    }
+}
+
+/// How much of the screen `--height` gives the UI: rows, or a percentage like `40%`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Height {
+    Rows(u16),
+    Percent(u16),
+}
+
+impl Height {
+    /// Rows to draw in on a terminal this many rows tall
+    pub fn rows(self, total: u16) -> u16 {
+        let rows = match self {
+            Height::Rows(rows) => rows,
+            Height::Percent(percent) => (total as u32 * percent as u32 / 100) as u16,
+        };
+        rows.max(MIN_INLINE_ROWS)
+    }
+}
+
+impl FromStr for Height {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid height '{}': use rows (20) or a percentage (40%)", s);
+        match s.strip_suffix('%') {
+            Some(percent) => match percent.parse() {
+                Ok(percent @ 1..=100) => Ok(Height::Percent(percent)),
+                _ => Err(invalid()),
+            },
+            None => match s.parse() {
+                Ok(rows @ 1..) => Ok(Height::Rows(rows)),
+                _ => Err(invalid()),
+            },
+        }
+    }
+}
+
+/// Initialize the terminal: the alternate screen, or with `height` the bottom of the current
+/// one, leaving what's above it alone. A terminal that can't report its size, or isn't tall
+/// enough to spare any rows, gets the whole screen.
+pub fn init(output: Output, height: Option<Height>) -> Result<Tui> {
+    let inline = height.and_then(|height| {
+        let (_, total) = crossterm::terminal::size().ok()?;
+        let rows = height.rows(total);
+        (rows < total).then_some(rows)
+    });
 
-/// Initialize the terminal
-pub fn init() -> Result<Tui> {
     enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let terminal = Terminal::new(backend)?;
+    let mut writer = output.writer();
+    let viewport = match inline {
+        Some(rows) => {
+            execute!(writer, EnableMouseCapture)?;
+            Viewport::Inline(rows)
+        }
+        None => {
+            execute!(writer, EnterAlternateScreen, EnableMouseCapture)?;
+            Viewport::Fullscreen
+        }
+    };
+    INLINE.store(inline.is_some(), Ordering::Relaxed);
+
+    // Chords like Ctrl+Enter, where the terminal can report them
+    let enhanced = crossterm::terminal::supports_keyboard_enhancement().unwrap_or(false);
+    if enhanced {
+        let flags = KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+            | KeyboardEnhancementFlags::REPORT_EVENT_TYPES;
+        execute!(writer, PushKeyboardEnhancementFlags(flags))?;
+    }
+    ENHANCED_KEYS.store(enhanced, Ordering::Relaxed);
+
+    let backend = CrosstermBackend::new(writer);
+    let terminal = Terminal::with_options(backend, TerminalOptions { viewport })?;
     Ok(terminal)
 }
 
 /// Restore the terminal to its original state
-pub fn restore() -> Result<()> {
+pub fn restore(terminal: &mut Tui, output: Output) -> Result<()> {
+    if ENHANCED_KEYS.swap(false, Ordering::Relaxed) {
+        execute!(output.writer(), PopKeyboardEnhancementFlags)?;
+    }
     disable_raw_mode()?;
-    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
+    if INLINE.load(Ordering::Relaxed) {
+        clear_inline(terminal)?;
+        execute!(output.writer(), DisableMouseCapture, Show)?;
+    } else {
+        execute!(output.writer(), LeaveAlternateScreen, DisableMouseCapture)?;
+    }
+    Ok(())
+}
+
+/// Erase an inline UI and put the cursor where it started, so the shell prompt (or whatever
+/// runs next) appears there instead of below leftover rows
+pub fn clear_inline<B: Backend>(terminal: &mut Terminal<B>) -> Result<()> {
+    let top = terminal.get_frame().area().y;
+    terminal.draw(|frame| frame.render_widget(Clear, frame.area()))?;
+    terminal.set_cursor_position((0, top))?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::widgets::Paragraph;
+
+    #[test]
+    fn test_parse_height() {
+        assert_eq!("40%".parse(), Ok(Height::Percent(40)));
+        assert_eq!("20".parse(), Ok(Height::Rows(20)));
+        for bad in ["0", "0%", "101%", "-5", "half", ""] {
+            assert!(bad.parse::<Height>().is_err(), "{}", bad);
+        }
+        assert_eq!(Height::Percent(40).rows(50), 20);
+        assert_eq!(Height::Percent(10).rows(50), MIN_INLINE_ROWS);
+        assert_eq!(Height::Rows(3).rows(50), MIN_INLINE_ROWS);
+    }
+
+    #[test]
+    fn test_clear_inline_leaves_scrollback() {
+        // A shell prompt above where the UI opens
+        let mut backend = TestBackend::new(20, 10);
+        let prompt: Vec<_> = "$ recall"
+            .chars()
+            .enumerate()
+            .map(|(x, c)| {
+                let mut cell = ratatui::buffer::Cell::default();
+                cell.set_char(c);
+                (x as u16, 3, cell)
+            })
+            .collect();
+        backend.draw(prompt.iter().map(|(x, y, cell)| (*x, *y, cell))).unwrap();
+        backend.set_cursor_position((0, 4)).unwrap();
+        let mut terminal =
+            Terminal::with_options(backend, TerminalOptions { viewport: Viewport::Inline(4) })
+                .unwrap();
+        terminal.draw(|f| f.render_widget(Paragraph::new("recall ui"), f.area())).unwrap();
+        assert_eq!(terminal.get_frame().area().y, 4);
+
+        clear_inline(&mut terminal).unwrap();
+        let buffer = terminal.backend().buffer();
+        let row = |y: u16| (0..20).map(|x| buffer[(x, y)].symbol()).collect::<String>();
+        assert_eq!(row(3).trim_end(), "$ recall");
+        assert!((4..10).all(|y| row(y).trim().is_empty()));
+        assert_eq!(terminal.get_cursor_position().unwrap(), (0, 4).into());
+    }
+}
@@ -1,33 +1,62 @@
 use crate::app::{App, SearchScope};
-use crate::session::{Role, SessionSource};
+use crate::fold;
+use crate::session::Role;
 use crate::theme::Theme;
+use crate::time::TimeFormat;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{List, ListItem, ListState, Paragraph},
+    widgets::{Block, Clear, List, ListItem, ListState, Paragraph},
     Frame,
 };
-use std::sync::OnceLock;
+use std::sync::{Arc, RwLock};
 
-fn theme() -> &'static Theme {
-    static THEME: OnceLock<Theme> = OnceLock::new();
-    THEME.get_or_init(Theme::detect)
+static THEME: RwLock<Option<Arc<Theme>>> = RwLock::new(None);
+
+/// List panes at least this wide show two lines of snippet per result
+const SNIPPET_WRAP_WIDTH: usize = 60;
+
+/// Result headers with less room than this for the title leave it out
+const MIN_TITLE_WIDTH: usize = 12;
+
+/// Screens shorter than this (an inline `--height`) get a compact layout
+const COMPACT_HEIGHT: u16 = 15;
+
+fn theme() -> Arc<Theme> {
+    if let Some(theme) = THEME.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        return theme.clone();
+    }
+    // Detection queries the terminal, so it happens before the TUI starts, not mid-render
+    let theme = Arc::new(Theme::named("dark").unwrap());
+    *THEME.write().unwrap_or_else(|e| e.into_inner()) = Some(theme.clone());
+    theme
+}
+
+/// Render with this theme; until it is called, rendering uses the dark theme
+pub fn set_theme(theme: Theme) {
+    *THEME.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(theme));
 }
 
 /// Main UI rendering
 pub fn render(frame: &mut Frame, app: &mut App) {
+    if app.accessible {
+        return render_accessible(frame, app);
+    }
     let area = frame.area();
 
+    // Short viewports (`--height`) drop the search bar's padding and the spacing rows
+    let (search_height, spacing) = if area.height < COMPACT_HEIGHT { (1, 0) } else { (3, 1) };
+
     // Main layout: search bar (3 lines with padding), spacing, content, spacing, status bar
     let main_layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3), // Search bar with top/bottom padding
-            Constraint::Length(1), // Spacing
-            Constraint::Min(0),    // Content area
-            Constraint::Length(1), // Spacing before status bar
-            Constraint::Length(1), // Status bar
+            Constraint::Length(search_height), // Search bar with top/bottom padding
+            Constraint::Length(spacing),       // Spacing
+            Constraint::Min(0),                // Content area
+            Constraint::Length(spacing),       // Spacing before status bar
+            Constraint::Length(1),             // Status bar
         ])
         .split(area);
 
@@ -84,6 +113,34 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         .split(main_layout[4]);
 
     render_status_bar(frame, app, status_with_padding[1]);
+
+    if let Some(input) = &app.note_input {
+        render_note_overlay(frame, input, main_layout[2]);
+    }
+}
+
+/// The note editor (Ctrl+N), centered over the content area
+fn render_note_overlay(frame: &mut Frame, input: &str, area: Rect) {
+    let t = theme();
+    let width = area.width.saturating_sub(4).min(72);
+    let height = (crate::notes::MAX_LINES as u16 + 2).min(area.height);
+    let overlay = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 3,
+        width,
+        height,
+    };
+
+    let mut lines: Vec<Line> = input.split('\n').map(|line| Line::from(line.to_string())).collect();
+    if let Some(last) = lines.last_mut() {
+        last.push_span(Span::styled("█", Style::default().fg(t.accent)));
+    }
+    let block = Block::bordered()
+        .title(" Note ")
+        .title_bottom(Line::from(" Enter new line · ^S save · Esc cancel ").right_aligned())
+        .border_style(Style::default().fg(t.accent));
+    frame.render_widget(Clear, overlay);
+    frame.render_widget(Paragraph::new(lines).block(block), overlay);
 }
 
 fn render_search_bar(frame: &mut Frame, app: &App, area: Rect) {
@@ -100,10 +157,11 @@ fn render_search_bar(frame: &mut Frame, app: &App, area: Rect) {
     let label_color = t.scope_label_fg;
     let scope_widget = vec![
         Span::styled(" │ ", Style::default().fg(separator_color)),  // separator
-        Span::styled(" / ", Style::default().bg(t.keycap_bg)),  // keycap like status bar
+        Span::styled(" / ", t.keycap()),  // keycap like status bar
         Span::styled(format!(" {} ", scope_label), Style::default().fg(label_color)),  // label
     ];
-    let scope_width: usize = 3 + 3 + 1 + scope_label.len() + 1; // " │ " + " / " + " label "
+    // " │ " + " / " + " label "
+    let scope_width: usize = 3 + 3 + 1 + Span::raw(scope_label.as_str()).width() + 1;
 
     // Calculate how much space for search text (leave room for scope widget + left margin)
     let search_width = (area.width as usize).saturating_sub(scope_width + 1); // +1 for left margin before widget
@@ -113,7 +171,7 @@ fn render_search_bar(frame: &mut Frame, app: &App, area: Rect) {
         let placeholder = " Search...";
         let padding = search_width.saturating_sub(placeholder.len());
         let mut spans = vec![
-            Span::styled(placeholder, Style::default().fg(t.placeholder_fg)),
+            Span::styled(placeholder, t.dim().fg(t.placeholder_fg)),
             Span::styled(" ".repeat(padding), Style::default()), // fill to push scope right
             Span::styled(" ", Style::default()), // margin before widget
         ];
@@ -160,7 +218,11 @@ fn render_search_bar(frame: &mut Frame, app: &App, area: Rect) {
         Span::raw(" ".repeat(separator_pos)),
         Span::styled(" │ ", Style::default().fg(separator_color)),
     ]);
-    let lines = vec![top_line, middle_line, bottom_line];
+    let lines = if area.height >= 3 {
+        vec![top_line, middle_line, bottom_line]
+    } else {
+        vec![middle_line]
+    };
 
     let paragraph = Paragraph::new(lines)
         .style(Style::default().bg(t.search_bg));
@@ -170,17 +232,21 @@ fn render_search_bar(frame: &mut Frame, app: &App, area: Rect) {
 
 fn render_results_list(frame: &mut Frame, app: &mut App, area: Rect) {
     let t = theme();
+    let times = TimeFormat::local(app.date_format.as_deref());
     // Available width for snippet text
     let available_width = area.width.saturating_sub(2) as usize;
+    // Wide list panes have room for a second line of snippet
+    let snippet_line_count = if available_width >= SNIPPET_WRAP_WIDTH { 2 } else { 1 };
 
     if app.results.is_empty() {
+        app.more_matches_line = None;
         // Show hint to search everywhere if scoped and no results
         let is_scoped = !matches!(app.search_scope, SearchScope::Everything);
         if is_scoped {
             let prefix = if app.query.is_empty() { "Nothing here." } else { "No results." };
             let hint = Line::from(vec![
                 Span::styled(format!(" {} Press ", prefix), Style::default().fg(t.snippet_fg)),
-                Span::styled(" / ", Style::default().bg(t.keycap_bg)),
+                Span::styled(" / ", t.keycap()),
                 Span::styled(" to search everywhere.", Style::default().fg(t.snippet_fg)),
             ]);
             frame.render_widget(Paragraph::new(hint), area);
@@ -194,6 +260,8 @@ fn render_results_list(frame: &mut Frame, app: &mut App, area: Rect) {
         return;
     }
 
+    // Line of the selected item showing "+N more matches", if it has one
+    let mut more_matches_y = None;
     let items: Vec<ListItem> = app
         .results
         .iter()
@@ -201,16 +269,8 @@ fn render_results_list(frame: &mut Frame, app: &mut App, area: Rect) {
         .map(|(i, result)| {
             let is_selected = i == app.selected;
 
-            // Format time ago
-            let time_ago = format_time_ago(result.session.timestamp);
-
             // Source-specific color
-            let source_color = match result.session.source {
-                SessionSource::ClaudeCode => t.claude_source,
-                SessionSource::CodexCli => t.codex_source,
-                SessionSource::Factory => t.factory_source,
-                SessionSource::OpenCode => t.opencode_source,
-            };
+            let source_color = t.source_color(result.session.source);
 
             // Build header with colored source indicator
             let header_style = if is_selected {
@@ -219,56 +279,118 @@ fn render_results_list(frame: &mut Frame, app: &mut App, area: Rect) {
                 Style::default()
             };
 
-            let header_spans = vec![
+            let mut header_spans = vec![
                 Span::styled("📁 ", header_style),
                 Span::styled(result.session.project_name(), header_style),
                 Span::styled("  ", header_style),
+            ];
+            header_spans.extend([
                 Span::styled(
-                    format!("{} {}", result.session.source.icon(), result.session.source.display_name()),
+                    format!("{} {}", t.icon(result.session.source), result.session.source.display_name()),
                     Style::default().fg(source_color),
                 ),
-                Span::styled(format!("  {}", time_ago), header_style),
-            ];
+                Span::styled(format!("  {}", times.ago(result.session.timestamp)), header_style),
+            ]);
+            // A date running past the pane is shortened, and then the project name, rather
+            // than the date being cut off
+            let header_width = |spans: &[Span]| spans.iter().map(Span::width).sum::<usize>();
+            let room = area.width as usize;
+            if header_width(&header_spans) > room {
+                let time_ago = times.short_ago(result.session.timestamp);
+                header_spans[4] = Span::styled(format!("  {}", time_ago), header_style);
+            }
+            let over = header_width(&header_spans).saturating_sub(room);
+            if over > 0 {
+                let project = result.session.project_name();
+                let keep = project.chars().count().saturating_sub(over).max(1);
+                header_spans[1] = Span::styled(truncate(&project, keep), header_style);
+            }
+            if let Some(origin) = result.session.origin() {
+                header_spans.push(Span::styled(
+                    format!("  from {}", origin.display()),
+                    t.dim(),
+                ));
+            }
+            if let Some(host) = result.session.remote_host() {
+                header_spans.push(Span::styled(format!("  on {}", host), t.dim()));
+            }
+            if result.session.is_subagent() {
+                header_spans.push(Span::styled("  subagent", Style::default().fg(source_color)));
+            }
+            for tag in crate::tags::get(&result.session.id) {
+                header_spans.push(Span::raw(" "));
+                header_spans.push(Span::styled(format!(" {} ", tag), t.chip()));
+            }
+            // The title (renamed, the agent's, or the first thing asked) after the project, in
+            // the room the rest of the header leaves
+            let title = crate::titles::get(&result.session.id).or(result.session.title.clone());
+            let used: usize = header_spans.iter().map(Span::width).sum();
+            let room = available_width.saturating_sub(used + 2);
+            if let Some(title) = title.filter(|_| room >= MIN_TITLE_WIDTH) {
+                let title_style = header_style.add_modifier(Modifier::BOLD);
+                header_spans.splice(
+                    3..3,
+                    [
+                        Span::styled(truncate(&title, room), title_style),
+                        Span::styled("  ", header_style),
+                    ],
+                );
+            }
 
-            // Truncate snippet to fit available width (Tantivy already centered it)
-            let snippet: String = result.snippet.chars().take(available_width).collect();
-            let truncated = snippet.len() < result.snippet.len();
-            let snippet = if truncated {
-                format!("{}...", snippet.trim_end())
-            } else {
-                snippet
-            };
+            let mut lines = vec![Line::from(header_spans)];
 
-            // Use pre-computed match spans from Tantivy for highlighting
-            // Adjust spans if we truncated
-            let adjusted_spans: Vec<(usize, usize)> = result
-                .match_spans
-                .iter()
-                .filter(|&&(start, _)| start < snippet.len())
-                .map(|&(start, end)| (start, end.min(snippet.len())))
-                .collect();
-            let snippet_spans = highlight_with_spans(&snippet, &adjusted_spans);
-
-            let lines = vec![
-                Line::from(header_spans),
-                Line::from(
-                    snippet_spans
-                        .into_iter()
-                        .map(|s| {
-                            if s.style.add_modifier.contains(Modifier::BOLD) {
-                                // Highlight for matches
-                                Span::styled(s.content, Style::default().fg(t.match_fg).add_modifier(Modifier::BOLD))
-                            } else {
-                                let fg = if is_selected { t.selection_snippet_fg } else { t.snippet_fg };
-                                Span::styled(s.content, Style::default().fg(fg))
-                            }
-                        })
-                        .collect::<Vec<_>>(),
-                ),
-                Line::from(""), // Empty line between conversations
-            ];
+            // Fit the snippet to the available width (the index already centered it)
+            lines.extend(result_snippet_lines(
+                &result.snippet,
+                &result.match_spans,
+                available_width,
+                snippet_line_count,
+                is_selected,
+            ));
+            // Keep every item the same height so scrolling stays simple
+            while lines.len() < 1 + snippet_line_count {
+                lines.push(Line::from(""));
+            }
 
-            if is_selected {
+            let expanded = app.expanded_result.as_ref() == Some(&result.session.id);
+            if expanded && !result.more_matches.is_empty() {
+                // The other matches, one line each, the selected one marked
+                for (m, hit) in result.more_matches.iter().enumerate() {
+                    let is_match_selected = is_selected && app.selected_match == Some(m);
+                    let marker = if is_match_selected { "  ▸ " } else { "  · " };
+                    let width = available_width.saturating_sub(marker.chars().count());
+                    let mut line = result_snippet_lines(
+                        &hit.snippet,
+                        &hit.match_spans,
+                        width,
+                        1,
+                        is_match_selected,
+                    )
+                    .pop()
+                    .unwrap_or_default();
+                    let marker_style = if is_match_selected {
+                        Style::default().fg(t.accent).add_modifier(Modifier::BOLD)
+                    } else {
+                        t.dim()
+                    };
+                    line.spans.insert(0, Span::styled(marker, marker_style));
+                    lines.push(line);
+                }
+                lines.push(Line::from(""));
+            } else if is_selected && !result.more_matches.is_empty() {
+                // In place of the empty line, so the item keeps its height
+                let count = result.more_matches.len();
+                let plural = if count == 1 { "" } else { "es" };
+                let hint = format!("  +{} more match{} (→)", count, plural);
+                more_matches_y = Some(lines.len());
+                lines.push(Line::from(Span::styled(hint, t.dim())));
+            } else {
+                lines.push(Line::from("")); // Empty line between conversations
+            }
+
+            if is_selected && t.monochrome {
+                ListItem::new(lines).style(Style::default().add_modifier(Modifier::BOLD))
+            } else if is_selected {
                 ListItem::new(lines).style(Style::default().bg(t.selection_bg))
             } else {
                 ListItem::new(lines)
@@ -276,21 +398,28 @@ fn render_results_list(frame: &mut Frame, app: &mut App, area: Rect) {
         })
         .collect();
 
+    // Items are header, snippet lines, empty, plus the other matches of an expanded one
+    let heights: Vec<usize> = items.iter().map(ListItem::height).collect();
     let list = List::new(items);
 
-    // Calculate visible items (each item is 3 lines: header, snippet, empty)
-    let lines_per_item = 3;
-    let visible_items = (area.height as usize) / lines_per_item;
-
     // Update scroll offset to keep selected item visible
     if app.selected < app.list_scroll {
         // Selected above visible area - scroll up
         app.list_scroll = app.selected;
-    } else if app.selected >= app.list_scroll + visible_items && visible_items > 0 {
+    }
+    while app.list_scroll < app.selected
+        && heights[app.list_scroll..=app.selected].iter().sum::<usize>() > area.height as usize
+    {
         // Selected below visible area - scroll down
-        app.list_scroll = app.selected - visible_items + 1;
+        app.list_scroll += 1;
     }
 
+    // Where the "+N more matches" line lands on screen, for clicks
+    app.more_matches_line = more_matches_y.and_then(|line| {
+        let y = heights[app.list_scroll..app.selected].iter().sum::<usize>() + line;
+        (y < area.height as usize).then_some((area.x, area.y + y as u16, area.width))
+    });
+
     // Use ListState with our tracked scroll offset
     let mut list_state = ListState::default();
     list_state.select(Some(app.selected));
@@ -301,6 +430,7 @@ fn render_results_list(frame: &mut Frame, app: &mut App, area: Rect) {
 
 fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
     let t = theme();
+    let times = TimeFormat::local(app.date_format.as_deref());
 
     // Store preview area for mouse click detection
     app.preview_area = (area.x, area.y, area.width, area.height);
@@ -312,8 +442,10 @@ fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
 
     // Extract values we need before mutating app
     let file_path = result.session.file_path.clone();
-    let matched_message_index = result.matched_message_index;
-    let match_fragment = result.match_fragment.clone();
+    let (matched_message_index, match_fragment) = match app.selected_match() {
+        Some((index, fragment)) => (index, fragment.to_string()),
+        None => (result.matched_message_index, result.match_fragment.clone()),
+    };
 
     // Load the full session for preview
     let session = match crate::parser::parse_session_file(&file_path) {
@@ -340,21 +472,42 @@ fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
     // Track line index where each message starts (for scrolling)
     let mut message_start_lines: Vec<usize> = Vec::new();
 
+    // Header: the title the user gave the session, where it lives on disk, and whether
+    // secrets were masked
+    if let Some(title) = crate::titles::get(&session.id) {
+        lines.push(Line::from(Span::styled(
+            format!(" {}", title),
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+    }
+    let mut header = vec![Span::styled(format!(" {}", home_relative(&file_path)), t.dim())];
+    if session.messages.iter().any(|m| m.content.contains(crate::redact::REDACTED)) {
+        header.push(Span::styled("  contains redacted content", Style::default().fg(t.match_fg)));
+    }
+    lines.push(Line::from(header));
+    // The user's note, above the conversation
+    if let Some(note) = crate::notes::get(&session.id) {
+        let style = Style::default().fg(t.accent).add_modifier(Modifier::ITALIC);
+        for line in note.lines() {
+            lines.push(Line::from(Span::styled(format!(" {}", line), style)));
+        }
+    }
+    lines.push(Line::from(""));
+
+    let bookmarked = crate::bookmarks::bookmarked(&session);
+    app.preview_max_hscroll = 0;
     for (i, message) in session.messages.iter().enumerate() {
         // Track where this message starts
         message_start_lines.push(lines.len());
 
         let is_focused = i == focused_idx;
         let is_expanded = app.expanded_messages.contains(&i);
+        let is_unwrapped = app.unwrapped_messages.contains(&i);
 
         let (accent_color, msg_bg) = match message.role {
             Role::User => (t.user_label, t.user_bubble_bg),
-            Role::Assistant => match session.source {
-                crate::session::SessionSource::ClaudeCode => (t.claude_source, t.claude_bubble_bg),
-                crate::session::SessionSource::CodexCli => (t.codex_source, t.codex_bubble_bg),
-                crate::session::SessionSource::Factory => (t.factory_source, t.factory_bubble_bg),
-                crate::session::SessionSource::OpenCode => (t.opencode_source, t.opencode_bubble_bg),
-            },
+            Role::Assistant => (t.source_color(session.source), t.bubble_bg(session.source)),
+            Role::Context => (t.dim_fg, t.bubble_bg(session.source)),
         };
 
         // Focus indicator - ▎ for focused, space for unfocused (same width)
@@ -367,20 +520,12 @@ fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
         }
 
         // Role label
-        let role_label = match message.role {
-            Role::User => "You",
-            Role::Assistant => match session.source {
-                crate::session::SessionSource::ClaudeCode => "Claude",
-                crate::session::SessionSource::CodexCli => "Codex",
-                crate::session::SessionSource::Factory => "Droid",
-                crate::session::SessionSource::OpenCode => "OpenCode",
-            },
-        };
+        let role_label = message.role.label(session.source);
 
-        let time_str = format_time_ago(message.timestamp);
+        let time_str = times.ago(message.timestamp);
 
-        // Role header with timestamp and focus indicator
-        lines.push(Line::from(vec![
+        // Role header with timestamp, focus indicator, and bookmark
+        let mut role_header = vec![
             if is_focused { focus_prefix.clone() } else { unfocused_prefix.clone() },
             Span::styled(
                 role_label,
@@ -388,14 +533,30 @@ fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
             ),
             Span::styled(
                 format!("  {}", time_str),
-                Style::default().fg(t.dim_fg),
+                t.dim(),
             ),
-        ]));
+        ];
+        if bookmarked.contains(&i) {
+            let mark = if t.monochrome { "  *" } else { "  ★" };
+            role_header.push(Span::styled(mark, Style::default().fg(t.match_fg)));
+        }
+        lines.push(Line::from(role_header));
 
-        // Message content with word wrapping
-        let wrapped_lines = wrap_text(&message.content, bubble_width);
+        // Message content with word wrapping, or cut off at the edge when unwrapped (Ctrl+W)
+        let wrapped_lines = if is_unwrapped {
+            unwrapped_text(&message.content)
+        } else {
+            wrap_text(&message.content, bubble_width)
+        };
         let is_matched = i == matched_message_index;
-        let max_lines = if is_expanded { usize::MAX } else { 12 };
+        let collapse_lines = app.preview.collapse_lines;
+        let max_lines = match is_expanded {
+            true => usize::MAX,
+            // Compacted context stays folded until expanded
+            false if message.role == Role::Context => 0,
+            false if collapse_lines == 0 => usize::MAX,
+            false => collapse_lines,
+        };
 
         // Determine which line indices to show (use Tantivy's fragment for centering)
         let line_indices = select_lines_to_show(
@@ -403,6 +564,8 @@ fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
             is_matched,
             &match_fragment,
             max_lines,
+            app.preview.head_lines,
+            app.preview.tail_lines,
         );
         let lines_to_show: Vec<(usize, &str)> = line_indices
             .iter()
@@ -415,12 +578,22 @@ fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
             })
             .collect();
 
-        let hidden_count = wrapped_lines.len().saturating_sub(12);
+        let shown_count = lines_to_show.iter().filter(|(idx, _)| *idx != usize::MAX).count();
+        let hidden_count = wrapped_lines.len() - shown_count;
 
         // Track if focused message can be expanded/collapsed
         if is_focused {
-            app.focused_message_expandable = wrapped_lines.len() > 12 || is_expanded;
+            app.focused_message_expandable = wrapped_lines.len() > max_lines || is_expanded;
         }
+        // Only the focused message scrolls sideways; other unwrapped ones show their start
+        let hscroll = if is_focused && is_unwrapped {
+            let longest = lines_to_show.iter().map(|(_, line)| line.chars().count()).max();
+            app.preview_max_hscroll = longest.unwrap_or(0).saturating_sub(bubble_width);
+            app.preview_hscroll = app.preview_hscroll.min(app.preview_max_hscroll);
+            app.preview_hscroll
+        } else {
+            0
+        };
 
         for (line_idx, display_line) in &lines_to_show {
             let prefix = if is_focused { focus_prefix.clone() } else { unfocused_prefix.clone() };
@@ -432,26 +605,30 @@ fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
                     prefix,
                     Span::styled(
                         format!(" {:<width$}", trunc_msg, width = bubble_width + 1),
-                        Style::default().fg(t.dim_fg).bg(msg_bg),
+                        t.dim().bg(msg_bg),
                     ),
                 ]));
                 continue;
             }
 
-            let content_len = display_line.chars().count();
-            let right_pad = bubble_width.saturating_sub(content_len);
-
             // Build line: [focus indicator] [1 space padding] [content] [right padding to fill width]
             let mut spans = vec![
                 prefix,
                 Span::styled(" ", Style::default().bg(msg_bg)),
             ];
 
-            if !display_line.is_empty() {
-                let highlighted = highlight_matches_owned(display_line, &app.query);
-                for span in highlighted {
-                    spans.push(Span::styled(span.content, span.style.bg(msg_bg)));
-                }
+            let mut highlighted = if display_line.is_empty() {
+                Vec::new()
+            } else {
+                highlight_matches_owned(display_line, &app.query)
+            };
+            if is_unwrapped {
+                highlighted = visible_window(highlighted, hscroll, bubble_width);
+            }
+            let content_len: usize = highlighted.iter().map(|s| s.content.chars().count()).sum();
+            let right_pad = bubble_width.saturating_sub(content_len);
+            for span in highlighted {
+                spans.push(Span::styled(span.content, span.style.bg(msg_bg)));
             }
 
             spans.push(Span::styled(" ".repeat(right_pad + 1), Style::default().bg(msg_bg)));
@@ -464,22 +641,8 @@ fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
 
     // Store message line ranges for mouse click detection
     app.message_line_ranges = message_line_ranges;
-
-    // Clamp scroll to valid range (leave at least one screen of content)
-    let visible_height = area.height as usize;
-    let max_scroll = lines.len().saturating_sub(visible_height.min(lines.len()));
-    app.preview_scrollable = max_scroll > 0;
-
-    // Auto-scroll to focused message when pending (triggered by selection change or navigation)
-    if app.pending_auto_scroll {
-        if let Some(&start_line) = message_start_lines.get(focused_idx) {
-            // Scroll to show focused message with some context above
-            app.preview_scroll = start_line.saturating_sub(2).min(max_scroll);
-        }
-        app.pending_auto_scroll = false;
-    }
-
-    app.preview_scroll = app.preview_scroll.min(max_scroll);
+    let focus_line = message_start_lines.get(focused_idx).copied();
+    settle_preview_scroll(app, lines.len(), area.height as usize, focus_line);
 
     // Use app's preview_scroll for manual scrolling
     let visible_lines: Vec<Line> = lines
@@ -492,13 +655,52 @@ fn render_preview(frame: &mut Frame, app: &mut App, area: Rect) {
     frame.render_widget(paragraph, area);
 }
 
+/// Clamp the preview's scroll to its `line_count` lines in `height` rows (leaving at least
+/// one screen of content), first scrolling to the focused message at `focus_line` when a
+/// selection change or navigation asked for it
+fn settle_preview_scroll(
+    app: &mut App,
+    line_count: usize,
+    height: usize,
+    focus_line: Option<usize>,
+) {
+    let max_scroll = line_count.saturating_sub(height.min(line_count));
+    app.preview_scrollable = max_scroll > 0;
+
+    if app.pending_auto_scroll {
+        if let Some(start_line) = focus_line {
+            // Show the focused message with some context above
+            app.preview_scroll = start_line.saturating_sub(2).min(max_scroll);
+        }
+        app.pending_auto_scroll = false;
+    }
+
+    app.preview_scroll = app.preview_scroll.min(max_scroll);
+}
+
 fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
     let t = theme();
-    let keycap = Style::default().bg(t.keycap_bg);
+    let keycap = t.keycap();
     let label = Style::default();
-    let dim = Style::default().fg(t.dim_fg);
-
-    let hints: Line = if let Some(ref msg) = app.status {
+    let dim = t.dim();
+
+    let hints: Line = if let Some(prompt) = &app.prompt {
+        Line::from(vec![
+            Span::styled(format!(" {}: ", prompt.kind.label()), keycap),
+            Span::styled(format!(" {}", prompt.input), label),
+            Span::styled("█", Style::default().fg(t.accent)),
+            Span::styled(format!("  {}", prompt.kind.hint()), dim),
+        ])
+    } else if app.confirm_resume.is_some() {
+        Line::from(vec![
+            Span::styled(" This session looks active in another terminal. Resume anyway? ", label),
+            Span::styled(" y ", keycap),
+            Span::styled(" / ", dim),
+            Span::styled(" n ", keycap),
+        ])
+    } else if let Some(toast) = app.toast() {
+        Line::from(Span::styled(format!(" {}", toast), Style::default().fg(t.match_fg)))
+    } else if let Some(ref msg) = app.status {
         Line::from(Span::styled(msg, Style::default().fg(t.match_fg)))
     } else {
         let has_selection = !app.results.is_empty();
@@ -541,6 +743,27 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
                 Span::styled(action, label),
             ]);
         }
+        if app.focused_unwrapped() {
+            spans.extend([
+                Span::styled(" │ ", dim),
+                Span::styled(" ←→ ", keycap),
+                Span::styled(" scroll ", label),
+            ]);
+        }
+        if area.width > 130 && has_selection {
+            spans.extend([
+                Span::styled(" │ ", dim),
+                Span::styled(" ^X ", keycap),
+                Span::styled(" copy path ", label),
+            ]);
+        }
+        if area.width > 150 && has_selection {
+            spans.extend([
+                Span::styled(" │ ", dim),
+                Span::styled(" ^T ", keycap),
+                Span::styled(" tags ", label),
+            ]);
+        }
         spans.extend([
             Span::styled(" │ ", dim),
             Span::styled(" Esc ", keycap),
@@ -549,8 +772,20 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
         Line::from(spans)
     };
 
+    let mut notes = Vec::new();
+    if app.skipped_sessions > 0 {
+        notes.push(format!("{} skipped", app.skipped_sessions));
+    }
+    if app.pruned_sessions > 0 {
+        notes.push(format!("{} pruned", app.pruned_sessions));
+    }
+    let skipped = if notes.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", notes.join(", "))
+    };
     let sessions_count = Span::styled(
-        format!(" {} sessions", app.total_sessions),
+        format!(" {} sessions{}", app.total_sessions, skipped),
         dim,
     );
 
@@ -563,6 +798,287 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(Paragraph::new(sessions_count), layout[1]);
 }
 
+/// The accessible layout (`--accessible`): the same state as labeled lines of plain text, read
+/// top to bottom (scope and query, results, the selected session, status) with no panes,
+/// bubbles, icons, or meaning carried by color alone
+fn render_accessible(frame: &mut Frame, app: &mut App) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),      // Scope and query
+            Constraint::Percentage(40), // Results
+            Constraint::Min(0),         // Preview or note
+            Constraint::Length(1),      // Status
+        ])
+        .split(frame.area());
+
+    let scope = app.scope_display_path().unwrap_or_else(|| "everywhere".to_string());
+    let search = format!("Scope: {}. Search: ", scope);
+    let before_cursor: String = app.query.chars().take(app.cursor).collect();
+    let mut cursor = (Span::raw(format!("{}{}", search, before_cursor)).width(), layout[0].y);
+    frame.render_widget(Paragraph::new(format!("{}{}", search, app.query)), layout[0]);
+
+    render_accessible_results(frame, app, layout[1]);
+    render_accessible_preview(frame, app, layout[2]);
+    if let Some(input) = &app.note_input {
+        let mut lines = vec![
+            Line::from("Note editor. Enter adds a line, Ctrl+S saves, Esc cancels."),
+            Line::from(""),
+        ];
+        lines.extend(input.split('\n').map(|line| Line::from(line.to_string())));
+        let last = lines.last().map_or(0, Line::width);
+        let area = layout[2];
+        cursor = (last, area.y + (lines.len() as u16 - 1).min(area.height.saturating_sub(1)));
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+
+    let status = accessible_status(app);
+    if let Some(prompt) = &app.prompt {
+        let typed = format!("{}: {}", prompt.kind.label(), prompt.input);
+        cursor = (Span::raw(typed).width(), layout[3].y);
+    }
+    frame.render_widget(Paragraph::new(status), layout[3]);
+
+    // Screen readers follow the cursor, so it sits where typing goes
+    let (x, y) = cursor;
+    frame.set_cursor_position((x.min(frame.area().width.saturating_sub(1) as usize) as u16, y));
+}
+
+/// Results as "Result 3 of 12: project recall, Claude, 2 days ago. Snippet: …", wrapped
+fn render_accessible_results(frame: &mut Frame, app: &mut App, area: Rect) {
+    let times = TimeFormat::local(app.date_format.as_deref());
+    let width = area.width as usize;
+    app.more_matches_line = None;
+
+    if app.results.is_empty() {
+        let scoped = !matches!(app.search_scope, SearchScope::Everything);
+        let text = match (scoped, app.query.is_empty()) {
+            (true, true) => "Nothing here. Press / to search everywhere.",
+            (true, false) => "No results. Press / to search everywhere.",
+            (false, false) => "No results.",
+            (false, true) => "",
+        };
+        frame.render_widget(Paragraph::new(text), area);
+        return;
+    }
+
+    let count = app.results.len();
+    let items: Vec<Vec<String>> = app
+        .results
+        .iter()
+        .enumerate()
+        .map(|(i, result)| {
+            let session = &result.session;
+            let selected = if i == app.selected { ", selected" } else { "" };
+            let mut text = format!(
+                "Result {} of {}{}: project {}",
+                i + 1,
+                count,
+                selected,
+                session.project_name()
+            );
+            if let Some(title) = crate::titles::get(&session.id) {
+                text.push_str(&format!(", titled {}", title));
+            }
+            let ago = times.ago(session.timestamp);
+            text.push_str(&format!(", {}, {}", session.source.display_name(), ago));
+            if let Some(origin) = session.origin() {
+                text.push_str(&format!(", from {}", origin.display()));
+            }
+            if let Some(host) = session.remote_host() {
+                text.push_str(&format!(", on {}", host));
+            }
+            if session.is_subagent() {
+                text.push_str(", a subagent's transcript");
+            }
+            let tags = crate::tags::get(&session.id);
+            if !tags.is_empty() {
+                text.push_str(&format!(", tagged {}", tags.join(", ")));
+            }
+            let snippet = result.snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+            text.push_str(&format!(". Snippet: {}", snippet));
+
+            let others = result.more_matches.len();
+            let expanded = app.expanded_result.as_ref() == Some(&session.id);
+            if i == app.selected && others > 0 && !expanded {
+                let plural = if others == 1 { "" } else { "es" };
+                text.push_str(&format!(". {} more match{}, Right shows them.", others, plural));
+            }
+            let mut lines = wrap_text(&text, width);
+            if expanded {
+                for (m, hit) in result.more_matches.iter().enumerate() {
+                    let selected = if app.selected_match == Some(m) { ", selected" } else { "" };
+                    let snippet = hit.snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+                    let text =
+                        format!("Other match {} of {}{}: {}", m + 1, others, selected, snippet);
+                    lines.extend(wrap_text(&text, width));
+                }
+            }
+            lines
+        })
+        .collect();
+
+    // Keep the selected result on screen, as the regular list does
+    let heights: Vec<usize> = items.iter().map(Vec::len).collect();
+    app.list_scroll = app.list_scroll.min(app.selected);
+    while app.list_scroll < app.selected
+        && heights[app.list_scroll..=app.selected].iter().sum::<usize>() > area.height as usize
+    {
+        app.list_scroll += 1;
+    }
+
+    let lines: Vec<Line> = items
+        .into_iter()
+        .enumerate()
+        .skip(app.list_scroll)
+        .flat_map(|(i, lines)| {
+            let style = if i == app.selected {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            lines.into_iter().map(move |line| Line::styled(line, style))
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), area);
+}
+
+/// The selected session as plain text, each message headed by who said it ("You said:")
+fn render_accessible_preview(frame: &mut Frame, app: &mut App, area: Rect) {
+    app.preview_area = (area.x, area.y, area.width, area.height);
+
+    let Some(result) = app.selected_result() else {
+        app.message_line_ranges.clear();
+        return;
+    };
+    let number = app.selected + 1;
+    let file_path = result.session.file_path.clone();
+    let (matched_message_index, match_fragment) = match app.selected_match() {
+        Some((index, fragment)) => (index, fragment.to_string()),
+        None => (result.matched_message_index, result.match_fragment.clone()),
+    };
+    let Ok(session) = crate::parser::parse_session_file(&file_path) else {
+        app.message_line_ranges.clear();
+        return;
+    };
+    app.preview_message_count = session.messages.len();
+    let focused_idx = app.focused_message.unwrap_or(matched_message_index);
+    let width = area.width as usize;
+
+    let mut lines: Vec<Line> = Vec::new();
+    let mut header = format!("Preview of result {}", number);
+    if let Some(title) = crate::titles::get(&session.id) {
+        header.push_str(&format!(", titled {}", title));
+    }
+    header.push_str(&format!(". File: {}.", home_relative(&file_path)));
+    if session.messages.iter().any(|m| m.content.contains(crate::redact::REDACTED)) {
+        header.push_str(" Contains redacted content.");
+    }
+    if let Some(note) = crate::notes::get(&session.id) {
+        header.push_str(&format!(" Note: {}", note.lines().collect::<Vec<_>>().join(" ")));
+    }
+    lines.extend(wrap_text(&header, width).into_iter().map(Line::from));
+    lines.push(Line::from(""));
+
+    let bookmarked = crate::bookmarks::bookmarked(&session);
+    let mut message_line_ranges = Vec::new();
+    for (i, message) in session.messages.iter().enumerate() {
+        let start = lines.len();
+        let is_focused = i == focused_idx;
+        let is_expanded = app.expanded_messages.contains(&i);
+        let style = if is_focused {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+
+        let marked = if bookmarked.contains(&i) { ", bookmarked" } else { "" };
+        let said = format!("{} said{}:", message.role.label(session.source), marked);
+        lines.push(Line::styled(said, style));
+
+        let wrapped_lines = wrap_text(&message.content, width);
+        let collapse_lines = app.preview.collapse_lines;
+        let max_lines = match is_expanded {
+            true => usize::MAX,
+            false if message.role == Role::Context => 0,
+            false if collapse_lines == 0 => usize::MAX,
+            false => collapse_lines,
+        };
+        let line_indices = select_lines_to_show(
+            &wrapped_lines,
+            i == matched_message_index,
+            &match_fragment,
+            max_lines,
+            app.preview.head_lines,
+            app.preview.tail_lines,
+        );
+        let shown = line_indices.iter().filter(|&&idx| idx != usize::MAX).count();
+        let hidden = wrapped_lines.len() - shown;
+        if is_focused {
+            app.focused_message_expandable = wrapped_lines.len() > max_lines || is_expanded;
+        }
+        for idx in line_indices {
+            let line = match idx {
+                usize::MAX => format!("{} more lines hidden, Ctrl+E shows them.", hidden),
+                idx => wrapped_lines[idx].clone(),
+            };
+            lines.push(Line::styled(line, style));
+        }
+        lines.push(Line::from(""));
+        message_line_ranges.push((start, lines.len() - 1));
+    }
+
+    app.message_line_ranges = message_line_ranges;
+    let focus_line = app.message_line_ranges.get(focused_idx).map(|&(start, _)| start);
+    settle_preview_scroll(app, lines.len(), area.height as usize, focus_line);
+    let visible: Vec<Line> = lines.into_iter().skip(app.preview_scroll).collect();
+    frame.render_widget(Paragraph::new(visible), area);
+}
+
+/// The status line in words: an open prompt, a question, the latest notice, or the keys
+fn accessible_status(app: &App) -> String {
+    if let Some(prompt) = &app.prompt {
+        return format!("{}: {}  ({})", prompt.kind.label(), prompt.input, prompt.kind.hint());
+    }
+    if app.confirm_resume.is_some() {
+        return "This session looks active in another terminal. Resume anyway? Press y or n."
+            .to_string();
+    }
+    if let Some(toast) = app.toast() {
+        return toast.to_string();
+    }
+    if let Some(msg) = &app.status {
+        return msg.trim().to_string();
+    }
+
+    let mut status = format!("{} sessions", app.total_sessions);
+    if app.skipped_sessions > 0 {
+        status.push_str(&format!(", {} skipped", app.skipped_sessions));
+    }
+    if app.pruned_sessions > 0 {
+        status.push_str(&format!(", {} pruned", app.pruned_sessions));
+    }
+    status.push_str(". Up and Down move");
+    if !app.results.is_empty() {
+        status.push_str(", Enter opens, Tab copies the ID");
+    }
+    status.push_str(", Esc quits.");
+    status
+}
+
+/// `path` with the home directory shown as `~`
+fn home_relative(path: &std::path::Path) -> String {
+    let home = std::env::var("RECALL_HOME_OVERRIDE")
+        .map(std::path::PathBuf::from)
+        .ok()
+        .or_else(dirs::home_dir);
+    match home.as_deref().and_then(|home| path.strip_prefix(home).ok()) {
+        Some(rest) => std::path::Path::new("~").join(rest).display().to_string(),
+        None => path.display().to_string(),
+    }
+}
+
 /// Find the wrapped line index that contains the given fragment.
 /// Searches for the fragment in the joined wrapped text.
 fn find_fragment_line(wrapped_lines: &[String], fragment: &str) -> usize {
@@ -614,66 +1130,134 @@ fn find_fragment_line(wrapped_lines: &[String], fragment: &str) -> usize {
     0
 }
 
-/// Word-wrap text to fit within max_width characters
-fn wrap_text(text: &str, max_width: usize) -> Vec<String> {
+/// Word-wrap text to fit within max_width characters. Prose keeps its leading indent, which
+/// continuation lines repeat; lines in fenced code blocks and table rows keep their spacing
+/// and are broken at the width instead of between words.
+pub fn wrap_text(text: &str, max_width: usize) -> Vec<String> {
+    let max_width = max_width.max(1);
     let mut result = Vec::new();
+    let mut in_code_block = false;
 
     for line in text.lines() {
+        let line = line.replace('\t', "    ");
+        let trimmed = line.trim_start();
+        let is_fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+
         // Empty or whitespace-only lines become blank lines
-        if line.trim().is_empty() {
+        if trimmed.is_empty() {
             result.push(String::new());
-            continue;
+        } else if in_code_block || is_fence || trimmed.starts_with('|') {
+            wrap_verbatim(&line, max_width, &mut result);
+        } else {
+            wrap_words(&line, max_width, &mut result);
         }
 
-        let mut current_line = String::new();
-        let mut current_width = 0;
+        if is_fence {
+            in_code_block = !in_code_block;
+        }
+    }
 
-        for word in line.split_whitespace() {
-            let word_width = word.chars().count();
+    if result.is_empty() {
+        result.push(String::new());
+    }
 
-            if current_width == 0 {
-                // First word on line
-                if word_width > max_width {
-                    // Word too long, force break it
-                    for chunk in word.chars().collect::<Vec<_>>().chunks(max_width) {
-                        result.push(chunk.iter().collect());
-                    }
-                } else {
-                    current_line = word.to_string();
-                    current_width = word_width;
-                }
-            } else if current_width + 1 + word_width <= max_width {
-                // Word fits on current line
-                current_line.push(' ');
-                current_line.push_str(word);
-                current_width += 1 + word_width;
-            } else {
-                // Word doesn't fit, start new line
-                result.push(current_line);
-                if word_width > max_width {
-                    // Word too long, force break it
-                    for chunk in word.chars().collect::<Vec<_>>().chunks(max_width) {
-                        result.push(chunk.iter().collect());
-                    }
-                    current_line = String::new();
-                    current_width = 0;
-                } else {
-                    current_line = word.to_string();
-                    current_width = word_width;
-                }
-            }
+    result
+}
+
+/// `text`'s lines as they are (tabs expanded, as `wrap_text` does), for messages shown with
+/// wrapping off
+pub fn unwrapped_text(text: &str) -> Vec<String> {
+    let lines: Vec<String> = text.lines().map(|line| line.replace('\t', "    ")).collect();
+    if lines.is_empty() {
+        vec![String::new()]
+    } else {
+        lines
+    }
+}
+
+/// The part of a highlighted line from column `start` that fits in `width` columns, styles
+/// kept, with a `→` in the last column when the line goes on past it
+fn visible_window(spans: Vec<Span<'static>>, start: usize, width: usize) -> Vec<Span<'static>> {
+    let total: usize = spans.iter().map(|s| s.content.chars().count()).sum();
+    let overflows = total > start + width;
+    let end = if overflows { start + width.saturating_sub(1) } else { start + width };
+
+    let mut window = Vec::new();
+    let mut column = 0;
+    for span in spans {
+        let len = span.content.chars().count();
+        let (from, to) = (start.max(column), end.min(column + len));
+        if from < to {
+            let text: String = span.content.chars().skip(from - column).take(to - from).collect();
+            window.push(Span::styled(text, span.style));
+        }
+        column += len;
+    }
+    if overflows && width > 0 {
+        window.push(Span::styled("→", theme().dim()));
+    }
+    window
+}
+
+/// Indent repeated on continuation lines, unless it would leave too little room for text
+fn carried_indent(line: &str, max_width: usize) -> usize {
+    let indent = line.len() - line.trim_start().len();
+    if indent * 2 <= max_width {
+        indent
+    } else {
+        0
+    }
+}
+
+/// Wrap a prose line between words, collapsing runs of spaces
+fn wrap_words(line: &str, max_width: usize, result: &mut Vec<String>) {
+    let indent = " ".repeat(carried_indent(line, max_width));
+    let max_width = max_width - indent.len();
+    let mut current_line = String::new();
+    let mut current_width = 0;
+    let mut push = |text: String| result.push(format!("{}{}", indent, text));
+
+    for word in line.split_whitespace() {
+        let word_width = word.chars().count();
+
+        if current_width > 0 && current_width + 1 + word_width <= max_width {
+            // Word fits on current line
+            current_line.push(' ');
+            current_line.push_str(word);
+            current_width += 1 + word_width;
+            continue;
         }
 
-        if !current_line.is_empty() {
-            result.push(current_line);
+        // Word doesn't fit (or is the first), start new line
+        if current_width > 0 {
+            push(std::mem::take(&mut current_line));
+            current_width = 0;
+        }
+        if word_width > max_width {
+            // Word too long, force break it
+            for chunk in word.chars().collect::<Vec<_>>().chunks(max_width) {
+                push(chunk.iter().collect());
+            }
+        } else {
+            current_line = word.to_string();
+            current_width = word_width;
         }
     }
 
-    if result.is_empty() {
-        result.push(String::new());
+    if !current_line.is_empty() {
+        push(current_line);
     }
+}
 
-    result
+/// Break a code or table line every `max_width` characters, keeping its spacing
+fn wrap_verbatim(line: &str, max_width: usize, result: &mut Vec<String>) {
+    let indent = carried_indent(line, max_width);
+    let chars: Vec<char> = line.trim_end().chars().collect();
+    let (first, rest) = chars.split_at(max_width.min(chars.len()));
+    result.push(first.iter().collect());
+    for chunk in rest.chunks(max_width - indent) {
+        result.push(format!("{}{}", " ".repeat(indent), chunk.iter().collect::<String>()));
+    }
 }
 
 /// Highlight text using pre-computed byte spans (from Tantivy)
@@ -687,9 +1271,9 @@ fn highlight_with_spans(text: &str, spans: &[(usize, usize)]) -> Vec<Span<'stati
     let mut last_end = 0;
 
     for &(start, end) in spans {
-        // Ensure spans are within bounds
-        let start = start.min(text.len());
-        let end = end.min(text.len());
+        // Keep spans within bounds, on char boundaries, and clear of the previous one
+        let start = floor_char_boundary(text, start.max(last_end));
+        let end = ceil_char_boundary(text, end);
         if start >= end {
             continue;
         }
@@ -717,6 +1301,24 @@ fn highlight_with_spans(text: &str, spans: &[(usize, usize)]) -> Vec<Span<'stati
     result
 }
 
+/// The nearest char boundary at or before `i`, clamped to the text
+fn floor_char_boundary(text: &str, i: usize) -> usize {
+    let mut i = i.min(text.len());
+    while !text.is_char_boundary(i) {
+        i -= 1;
+    }
+    i
+}
+
+/// The nearest char boundary at or after `i`, clamped to the text
+fn ceil_char_boundary(text: &str, i: usize) -> usize {
+    let mut i = i.min(text.len());
+    while !text.is_char_boundary(i) {
+        i += 1;
+    }
+    i
+}
+
 /// Highlight query matches, returning owned Spans (for use with local variables)
 /// Splits query into words and highlights each word separately
 fn highlight_matches_owned(text: &str, query: &str) -> Vec<Span<'static>> {
@@ -725,30 +1327,16 @@ fn highlight_matches_owned(text: &str, query: &str) -> Vec<Span<'static>> {
         return vec![Span::raw(text.to_owned())];
     }
 
-    let lower_text = text.to_lowercase();
-
     // Split query into words and find all match positions
     let query_words: Vec<&str> = query.split_whitespace().filter(|w| !w.is_empty()).collect();
     if query_words.is_empty() {
         return vec![Span::raw(text.to_owned())];
     }
 
-    // Collect all match ranges (byte positions in original text)
+    // Collect all match ranges (byte positions in original text), folding case like the CLI
     let mut matches: Vec<(usize, usize)> = Vec::new();
     for word in &query_words {
-        let lower_word = word.to_lowercase();
-        for (match_start_lower, matched_str) in lower_text.match_indices(&lower_word) {
-            let char_offset = lower_text[..match_start_lower].chars().count();
-            let start = text.char_indices().nth(char_offset).map(|(i, _)| i).unwrap_or(text.len());
-
-            let match_char_len = matched_str.chars().count();
-            let end = text[start..].char_indices()
-                .nth(match_char_len)
-                .map(|(i, _)| start + i)
-                .unwrap_or(text.len());
-
-            matches.push((start, end));
-        }
+        matches.extend(fold::find_all(text, word).into_iter().map(|r| (r.start, r.end)));
     }
 
     // Sort by start position and merge overlapping ranges
@@ -794,39 +1382,111 @@ fn highlight_matches_owned(text: &str, query: &str) -> Vec<Span<'static>> {
 
 
 
-/// Format a timestamp as a human-readable "time ago" string
-fn format_time_ago(timestamp: chrono::DateTime<chrono::Utc>) -> String {
-    let now = chrono::Utc::now();
-    let duration = now.signed_duration_since(timestamp);
+/// A result's snippet on up to `max_lines` lines of `width` characters, with its matches
+/// highlighted and "..." where it's cut
+/// `text` cut to `width` characters, ending in "…" when it was cut
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(width.saturating_sub(1)).collect();
+    format!("{}…", cut.trim_end())
+}
 
-    if duration.num_minutes() < 1 {
-        "just now".to_string()
-    } else if duration.num_minutes() < 60 {
-        format!("{}m ago", duration.num_minutes())
-    } else if duration.num_hours() < 24 {
-        format!("{}h ago", duration.num_hours())
-    } else if duration.num_days() < 7 {
-        format!("{}d ago", duration.num_days())
-    } else if duration.num_weeks() < 4 {
-        format!("{}w ago", duration.num_weeks())
-    } else {
-        timestamp.format("%b %d").to_string()
+fn result_snippet_lines(
+    snippet: &str,
+    match_spans: &[(usize, usize)],
+    width: usize,
+    max_lines: usize,
+    is_selected: bool,
+) -> Vec<Line<'static>> {
+    let t = theme();
+    let ranges = snippet_lines(snippet, width, max_lines);
+    let mut lines = Vec::with_capacity(ranges.len());
+    for (i, &(line_start, line_end)) in ranges.iter().enumerate() {
+        let cut = i + 1 == ranges.len() && line_end < snippet.len();
+        let text = &snippet[line_start..line_end];
+        // Trim before shifting spans so none of them reaches into the ellipsis
+        let text = if cut { text.trim_end() } else { text };
+        let line_end = line_start + text.len();
+
+        // Use pre-computed match spans from Tantivy for highlighting, shifted to this line
+        let adjusted_spans: Vec<(usize, usize)> = match_spans
+            .iter()
+            .filter(|&&(start, end)| start < line_end && end > line_start)
+            .map(|&(start, end)| {
+                (start.max(line_start) - line_start, end.min(line_end) - line_start)
+            })
+            .collect();
+        let mut snippet_spans = highlight_with_spans(text, &adjusted_spans);
+        if cut {
+            snippet_spans.push(Span::raw("..."));
+        }
+
+        lines.push(Line::from(
+            snippet_spans
+                .into_iter()
+                .map(|s| {
+                    if s.style.add_modifier.contains(Modifier::BOLD) {
+                        // Highlight for matches
+                        Span::styled(s.content, Style::default().fg(t.match_fg).add_modifier(Modifier::BOLD))
+                    } else {
+                        let fg = if is_selected { t.selection_snippet_fg } else { t.snippet_fg };
+                        let style = if t.monochrome && !is_selected { t.dim() } else { Style::default() };
+                        Span::styled(s.content, style.fg(fg))
+                    }
+                })
+                .collect::<Vec<_>>(),
+        ));
     }
+    lines
+}
+
+/// Byte ranges of `snippet` that fit on up to `max_lines` lines of `width` characters,
+/// breaking at spaces where possible. Text past the last range doesn't fit.
+fn snippet_lines(snippet: &str, width: usize, max_lines: usize) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while ranges.len() < max_lines && start < snippet.len() {
+        let rest = &snippet[start..];
+        let end = match rest.char_indices().nth(width) {
+            None => snippet.len(),
+            // Last line: cut anywhere, the caller adds an ellipsis
+            Some((cut, _)) if ranges.len() + 1 == max_lines => start + cut,
+            Some((cut, _)) => match rest[..cut].rfind(' ') {
+                Some(space) if space > 0 => start + space + 1,
+                _ => start + cut,
+            },
+        };
+        ranges.push((start, end));
+        start = end;
+    }
+    if ranges.is_empty() {
+        ranges.push((0, 0));
+    }
+    ranges
 }
 
 /// Select which line indices to show from a long message.
 /// Returns a Vec of (original_line_index, is_truncation_marker).
 /// The truncation marker uses usize::MAX as a sentinel value.
+/// Messages over `max_lines` keep `head_lines` + `tail_lines`, or a window around the match.
+/// A `max_lines` of 0 folds a message down to the marker.
 fn select_lines_to_show(
     wrapped_lines: &[String],
     is_matched: bool,
     match_fragment: &str,
     max_lines: usize,
+    head_lines: usize,
+    tail_lines: usize,
 ) -> Vec<usize> {
     if wrapped_lines.len() <= max_lines {
         // Short message - show all
         return (0..wrapped_lines.len()).collect();
     }
+    if max_lines == 0 {
+        return vec![usize::MAX];
+    }
 
     if is_matched && !match_fragment.is_empty() {
         // Matched message - center around the match by finding fragment in wrapped text
@@ -839,8 +1499,8 @@ fn select_lines_to_show(
     }
 
     // Non-matched long message - show first N + last N
-    let head_count = 6.min(wrapped_lines.len());
-    let tail_count = 5.min(wrapped_lines.len().saturating_sub(head_count));
+    let head_count = head_lines.min(wrapped_lines.len());
+    let tail_count = tail_lines.min(wrapped_lines.len().saturating_sub(head_count));
     let tail_start = wrapped_lines.len().saturating_sub(tail_count);
 
     let mut result: Vec<usize> = (0..head_count).collect();
@@ -853,6 +1513,39 @@ fn select_lines_to_show(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_unwrapped_text_keeps_long_lines() {
+        let url = format!("https://example.com/{}", "a".repeat(300));
+        let text = format!("see\n{}\n\tindented", url);
+        assert_eq!(unwrapped_text(&text), ["see".to_string(), url, "    indented".to_string()]);
+        assert_eq!(unwrapped_text(""), [""]);
+    }
+
+    #[test]
+    fn test_visible_window_keeps_highlights() {
+        // A long base64-ish line with a match far to the right
+        let line = format!("{}needle{}", "x".repeat(200), "y".repeat(200));
+        let spans = highlight_matches_owned(&line, "needle");
+        let text = |spans: &[Span]| spans.iter().map(|s| s.content.to_string()).collect::<String>();
+        let is_match = |span: &Span| span.style.add_modifier.contains(Modifier::BOLD);
+
+        // At the start, cut with an arrow and nothing highlighted
+        let window = visible_window(spans.clone(), 0, 40);
+        assert_eq!(text(&window), format!("{}→", "x".repeat(39)));
+        assert!(!window.iter().any(is_match));
+
+        // Scrolled over the match, which straddles the left edge
+        let window = visible_window(spans.clone(), 203, 10);
+        assert_eq!(text(&window), "dleyyyyyy→");
+        let matched: Vec<_> = window.iter().filter(|s| is_match(s)).map(|s| &s.content).collect();
+        assert_eq!(matched, ["dle"]);
+
+        // Scrolled to the end, no arrow
+        let end = line.chars().count() - 10;
+        let window = visible_window(spans, end, 10);
+        assert_eq!(text(&window), "y".repeat(10));
+    }
+
     #[test]
     fn test_wrap_text_short_line() {
         let lines = wrap_text("Hello world", 80);
@@ -889,17 +1582,72 @@ mod tests {
         assert_eq!(lines, vec!["supercalif", "ragilistic", "expialidoc", "ious"]);
     }
 
+    #[test]
+    fn test_wrap_text_keeps_indent() {
+        let text = "def greet(name):\n    if name:\n        return f\"hello {name}\"";
+        assert_eq!(
+            wrap_text(text, 80),
+            vec!["def greet(name):", "    if name:", "        return f\"hello {name}\""]
+        );
+
+        // Continuation lines repeat the indent
+        let lines = wrap_text("    - a list item that goes on for a while", 20);
+        assert_eq!(lines, vec!["    - a list item", "    that goes on for", "    a while"]);
+
+        // Tabs count as four spaces
+        assert_eq!(wrap_text("\tindented", 80), vec!["    indented"]);
+    }
+
+    #[test]
+    fn test_wrap_text_code_block_is_verbatim() {
+        let text = "Here's the fix:\n```diff\n@@ -1,3 +1,3 @@\n-    x  =  1\n+    x  =  2\n     y = 3\n```\n\
+                    Done  now.";
+        assert_eq!(
+            wrap_text(text, 80),
+            vec![
+                "Here's the fix:",
+                "```diff",
+                "@@ -1,3 +1,3 @@",
+                "-    x  =  1",
+                "+    x  =  2",
+                "     y = 3",
+                "```",
+                "Done now.",
+            ]
+        );
+
+        // Long code lines break at the width, continuing at their indent
+        let lines = wrap_text("```\n    call(first_argument, second)\n```", 20);
+        assert_eq!(lines, vec!["```", "    call(first_argum", "    ent, second)", "```"]);
+    }
+
+    #[test]
+    fn test_wrap_text_table_row_keeps_alignment() {
+        let text = "| name   | size |\n|--------|------|\n| a.rs   |   12 |";
+        assert_eq!(
+            wrap_text(text, 80),
+            vec!["| name   | size |", "|--------|------|", "| a.rs   |   12 |"]
+        );
+    }
+
+    #[test]
+    fn test_find_fragment_line_with_indented_code() {
+        let text = "Intro line\n```\nfn main() {\n    let  answer = 42;\n}\n```";
+        let lines = wrap_text(text, 80);
+        assert_eq!(find_fragment_line(&lines, "let answer = 42"), 3);
+    }
+
     #[test]
     fn test_select_lines_short_message() {
         let lines: Vec<String> = (0..5).map(|i| format!("Line {}", i)).collect();
-        let result = select_lines_to_show(&lines, false, "", 12);
+        let result = select_lines_to_show(&lines, false, "", 12, 6, 5);
         assert_eq!(result, vec![0, 1, 2, 3, 4]);
     }
 
     #[test]
     fn test_select_lines_long_unmatched_message() {
         let lines: Vec<String> = (0..30).map(|i| format!("Line {}", i)).collect();
-        let result = select_lines_to_show(&lines, false, "", 12);
+        let result = select_lines_to_show(&lines, false, "", 12, 6, 5);
 
         // Should have: 6 head + 1 truncation marker + 5 tail = 12 entries
         // But after blank trimming, might be slightly different
@@ -927,7 +1675,7 @@ mod tests {
         // Use a fragment that would come from Tantivy
         let fragment = "contains the MATCH keyword";
 
-        let result = select_lines_to_show(&lines, true, fragment, 12);
+        let result = select_lines_to_show(&lines, true, fragment, 12, 6, 5);
 
         // Should NOT contain truncation marker for matched messages
         assert!(!result.contains(&usize::MAX), "Matched message shouldn't have truncation marker");
@@ -944,13 +1692,13 @@ mod tests {
         let lines: Vec<String> = (0..27).map(|i| format!("Content line {}", i)).collect();
 
         // Test unmatched case
-        let result = select_lines_to_show(&lines, false, "", 12);
+        let result = select_lines_to_show(&lines, false, "", 12, 6, 5);
         let line_count = result.iter().filter(|&&i| i != usize::MAX).count();
         assert!(line_count <= 11, "Unmatched 27-line msg should show at most 11 lines, got {}", line_count);
 
         // Test matched case - use fragment from line 13
         let fragment = "Content line 13";
-        let result = select_lines_to_show(&lines, true, fragment, 12);
+        let result = select_lines_to_show(&lines, true, fragment, 12, 6, 5);
         assert_eq!(result.len(), 12, "Matched 27-line msg should show exactly 12 lines");
         assert!(!result.contains(&usize::MAX), "Matched message shouldn't have truncation marker");
     }
@@ -964,7 +1712,7 @@ mod tests {
         lines[14] = String::new();
         lines[15] = String::new();
 
-        let result = select_lines_to_show(&lines, false, "", 12);
+        let result = select_lines_to_show(&lines, false, "", 12, 6, 5);
         let line_count = result.iter().filter(|&&i| i != usize::MAX).count();
         // Simplified algorithm: always shows exactly 6 head + 5 tail (no blank trimming)
         assert_eq!(line_count, 11, "Should show exactly 11 lines");
@@ -1055,7 +1803,7 @@ mod tests {
         ].into_iter().map(String::from).collect();
 
         // Non-matched case - should get head + truncation + tail
-        let result = select_lines_to_show(&lines, false, "", 12);
+        let result = select_lines_to_show(&lines, false, "", 12, 6, 5);
 
         // Count actual lines (excluding truncation marker)
         let line_count = result.iter().filter(|&&i| i != usize::MAX).count();
@@ -1078,4 +1826,91 @@ mod tests {
         assert_eq!(head_count, 6, "Head should be exactly 6 lines");
         assert_eq!(tail_count, 5, "Tail should be exactly 5 lines");
     }
+
+    #[test]
+    fn test_highlight_spans_inside_multibyte_chars() {
+        let text = "ab🎉cd検索";
+        // The emoji is bytes 2..6 and 検 is 8..11; overlapping and out-of-range spans too
+        let cases = [vec![(3, 5)], vec![(0, 3)], vec![(5, 9)], vec![(9, 100)], vec![(1, 4), (3, 8)]];
+        for spans in cases {
+            let rendered: String = highlight_with_spans(text, &spans)
+                .iter()
+                .map(|s| s.content.as_ref())
+                .collect();
+            assert_eq!(rendered, text, "{:?}", spans);
+        }
+
+        // A span that starts mid-emoji highlights the whole emoji
+        let spans = highlight_with_spans(text, &[(3, 6)]);
+        assert_eq!(spans[1].content, "🎉");
+        assert!(spans[1].style.add_modifier.contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn test_highlight_matches_folds_case() {
+        let bold = |text: &str, query: &str| -> Vec<String> {
+            highlight_matches_owned(text, query)
+                .into_iter()
+                .filter(|s| s.style.add_modifier.contains(Modifier::BOLD))
+                .map(|s| s.content.into_owned())
+                .collect()
+        };
+        assert_eq!(bold("Die Straße ist lang", "STRASSE"), ["Straße"]);
+        assert_eq!(bold("STRASSE und Straße", "straße"), ["STRASSE", "Straße"]);
+        assert_eq!(bold("İstanbul ve istanbul", "istanbul"), ["İstanbul", "istanbul"]);
+    }
+
+    #[test]
+    fn test_snippet_lines_multibyte() {
+        let text = "🚀 launch ✨ sparkle 日本語のテキスト";
+        for width in 1..text.chars().count() + 2 {
+            for (start, end) in snippet_lines(text, width, 2) {
+                assert!(text.is_char_boundary(start) && text.is_char_boundary(end));
+            }
+        }
+    }
+
+    #[test]
+    fn test_snippet_lines() {
+        let text = "alpha beta gamma delta";
+        // Fits on one line
+        assert_eq!(snippet_lines(text, 40, 2), vec![(0, 22)]);
+        // Breaks after a space, the last line is cut anywhere
+        assert_eq!(snippet_lines(text, 12, 2), vec![(0, 11), (11, 22)]);
+        assert_eq!(snippet_lines(text, 8, 2), vec![(0, 6), (6, 14)]);
+        // One line keeps the old hard cut
+        assert_eq!(snippet_lines(text, 8, 1), vec![(0, 8)]);
+        assert_eq!(snippet_lines("", 8, 2), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn test_select_lines_configured_split() {
+        let lines: Vec<String> = (0..30).map(|i| format!("Line {}", i)).collect();
+
+        // Taller collapse with a different head/tail split
+        let result = select_lines_to_show(&lines, false, "", 20, 12, 7);
+        let marker_pos = result.iter().position(|&i| i == usize::MAX).unwrap();
+        assert_eq!(&result[..marker_pos], (0..12).collect::<Vec<_>>());
+        assert_eq!(&result[marker_pos + 1..], (23..30).collect::<Vec<_>>());
+
+        // Under the limit nothing is cut
+        assert_eq!(select_lines_to_show(&lines, false, "", 30, 12, 7), (0..30).collect::<Vec<_>>());
+
+        // No tail: head then the marker
+        let result = select_lines_to_show(&lines, false, "", 4, 3, 0);
+        assert_eq!(result, vec![0, 1, 2, usize::MAX]);
+
+        // Matched messages get a window the size of the limit
+        let result = select_lines_to_show(&lines, true, "Line 25", 8, 3, 3);
+        assert_eq!(result, (21..29).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_select_lines_never_truncate() {
+        // collapse_lines = 0 renders with no limit
+        let lines: Vec<String> = (0..500).map(|i| format!("Line {}", i)).collect();
+        let result = select_lines_to_show(&lines, false, "", usize::MAX, 6, 5);
+        assert_eq!(result.len(), 500);
+        assert!(!result.contains(&usize::MAX));
+    }
 }
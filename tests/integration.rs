@@ -1,5 +1,5 @@
 use insta::assert_snapshot;
-use ratatui::{backend::TestBackend, Terminal};
+use ratatui::{backend::TestBackend, style::Color, Terminal};
 use std::path::PathBuf;
 use std::sync::Mutex;
 use tempfile::TempDir;
@@ -108,6 +108,32 @@ fn buffer_to_string(terminal: &Terminal<TestBackend>) -> String {
     result
 }
 
+/// Convert terminal buffer to text plus the colors of each run of cells, for theme snapshots
+fn styled_buffer_to_string(terminal: &Terminal<TestBackend>) -> String {
+    let buffer = terminal.backend().buffer();
+    let mut result = String::new();
+    for y in 0..buffer.area.height {
+        let mut runs: Vec<(String, Color, Color)> = Vec::new();
+        for x in 0..buffer.area.width {
+            let cell = buffer.cell((x, y)).unwrap();
+            match runs.last_mut() {
+                Some((text, fg, bg)) if *fg == cell.fg && *bg == cell.bg => {
+                    text.push_str(cell.symbol())
+                }
+                _ => runs.push((cell.symbol().to_string(), cell.fg, cell.bg)),
+            }
+        }
+        for (text, fg, bg) in runs {
+            // Skip unstyled blank space
+            if fg == Color::Reset && bg == Color::Reset && text.trim().is_empty() {
+                continue;
+            }
+            result.push_str(&format!("{:>2} {:?} on {:?}: {:?}\n", y, fg, bg, text));
+        }
+    }
+    result
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -228,6 +254,186 @@ fn test_navigation_up_down() {
     }
 }
 
+#[test]
+fn test_jump_back_and_forward() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let options = recall::AppOptions { everywhere: true, ..Default::default() };
+    let mut app = app_with_options(temp_dir.path(), options);
+    let select = |app: &mut recall::App, id: &str| {
+        app.selected = app.results.iter().position(|r| r.session.id == id).unwrap();
+    };
+    let selected = |app: &recall::App| app.selected_result().unwrap().session.id.clone();
+
+    // Scrolling a preview records the session
+    select(&mut app, "test-claude-123");
+    let claude_path = app.selected_result().unwrap().session.file_path.clone();
+    app.scroll_preview_down(4);
+
+    // Then another session, found with a query
+    "database".chars().for_each(|c| app.on_char(c));
+    app.flush_pending_search();
+    select(&mut app, "test-codex-456");
+    app.scroll_preview_down(1);
+
+    app.jump_back();
+    assert_eq!(selected(&app), "test-claude-123");
+    assert_eq!(app.query, "");
+    assert_eq!(app.preview_scroll, 4);
+
+    app.jump_forward();
+    assert_eq!(selected(&app), "test-codex-456");
+    assert_eq!(app.query, "database");
+    assert_eq!(app.preview_scroll, 1);
+
+    // Nothing further ahead
+    app.jump_forward();
+    assert_eq!(selected(&app), "test-codex-456");
+
+    // A deleted session is skipped
+    std::fs::remove_file(claude_path).unwrap();
+    app.jump_back();
+    assert_eq!(selected(&app), "test-codex-456");
+}
+
+#[test]
+fn test_tag_session() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let options = recall::AppOptions { everywhere: true, ..Default::default() };
+    let mut app = app_with_options(temp_dir.path(), options);
+    // The sidecar lives in the cache dir
+    std::env::set_var("RECALL_HOME_OVERRIDE", temp_dir.path());
+
+    app.selected = app.results.iter().position(|r| r.session.id == "test-claude-123").unwrap();
+    let path = app.selected_result().unwrap().session.file_path.clone();
+    app.on_edit_tags();
+    assert_eq!(app.prompt.as_ref().map(|p| p.input.as_str()), Some(""));
+    "Billing Bug, research".chars().for_each(|c| app.on_prompt_char(c));
+    app.submit_prompt();
+    assert!(app.prompt.is_none());
+
+    // The session is reindexed, so the query operator finds it right away
+    "tag:billing-bug".chars().for_each(|c| app.on_char(c));
+    app.flush_pending_search();
+    assert_eq!(result_ids(&app), ["test-claude-123"]);
+
+    // Saved, and the prompt starts from the saved tags
+    let cache = temp_dir.path().join(".cache/recall");
+    let sidecar = std::fs::read_to_string(cache.join("tags.json")).unwrap();
+    assert!(sidecar.contains("billing-bug"), "{}", sidecar);
+    app.on_edit_tags();
+    let prompt = app.prompt.as_ref().map(|p| p.input.as_str());
+    assert_eq!(prompt, Some("billing-bug, research"));
+    app.cancel_prompt();
+
+    // The state remembers the indexed tags; changing them means reindexing
+    let state = recall::index::IndexState::load(&cache.join("state.json")).unwrap();
+    assert!(!state.needs_reindex(&path));
+    recall::tags::set("test-claude-123", &path, vec!["research".to_string()]).unwrap();
+    assert!(state.needs_reindex(&path));
+
+    // And the CLI filters by tag
+    let (stdout, _, success) = run_cli(&["list", "--tag", "research"], temp_dir.path());
+    assert!(success);
+    assert!(stdout.contains("test-claude-123"), "{}", stdout);
+    assert!(!stdout.contains("test-codex-456"), "{}", stdout);
+    let (stdout, _, _) = run_cli(&["search", "tag:research"], temp_dir.path());
+    assert!(stdout.contains("test-claude-123"), "{}", stdout);
+    std::env::remove_var("RECALL_HOME_OVERRIDE");
+}
+
+#[test]
+fn test_note_session() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let options = recall::AppOptions { everywhere: true, ..Default::default() };
+    let mut app = app_with_options(temp_dir.path(), options);
+    std::env::set_var("RECALL_HOME_OVERRIDE", temp_dir.path());
+
+    app.selected = app.results.iter().position(|r| r.session.id == "test-codex-456").unwrap();
+    app.on_edit_note();
+    assert_eq!(app.note_input.as_deref(), Some(""));
+    "chose sqlite".chars().for_each(|c| app.on_note_char(c));
+    app.on_note_newline();
+    "over sled".chars().for_each(|c| app.on_note_char(c));
+    assert!(buffer_contains(&render_app(&mut app), "Note"));
+    app.save_note();
+    assert!(app.note_input.is_none());
+    let sidecar = temp_dir.path().join(".cache/recall/notes.json");
+    assert!(std::fs::read_to_string(sidecar).unwrap().contains("chose sqlite\\nover sled"));
+
+    // With no query, the note stands in for the snippet and heads the preview
+    let result = app.results.iter().find(|r| r.session.id == "test-codex-456").unwrap();
+    assert!(result.snippet.starts_with("chose sqlite"), "{}", result.snippet);
+    app.selected = app.results.iter().position(|r| r.session.id == "test-codex-456").unwrap();
+    assert!(buffer_contains(&render_app(&mut app), " over sled"));
+
+    // The note is searchable
+    "sled".chars().for_each(|c| app.on_char(c));
+    app.flush_pending_search();
+    assert_eq!(result_ids(&app), ["test-codex-456"]);
+    assert!(app.results[0].snippet.contains("over sled"), "{}", app.results[0].snippet);
+
+    // Only a few lines fit
+    app.on_edit_note();
+    (0..5).for_each(|_| app.on_note_newline());
+    assert_eq!(app.note_input.as_deref(), Some("chose sqlite\nover sled\n"));
+    app.cancel_note();
+
+    // And JSON output carries it
+    let (stdout, _, success) = run_cli(&["read", "test-codex-456"], temp_dir.path());
+    assert!(success);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["note"], "chose sqlite\nover sled");
+    let (stdout, _, _) = run_cli(&["list"], temp_dir.path());
+    assert!(stdout.contains("over sled"), "{}", stdout);
+    std::env::remove_var("RECALL_HOME_OVERRIDE");
+}
+
+#[test]
+fn test_rename_session() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let options = recall::AppOptions { everywhere: true, ..Default::default() };
+    let mut app = app_with_options(temp_dir.path(), options);
+    std::env::set_var("RECALL_HOME_OVERRIDE", temp_dir.path());
+    let title = |home: &std::path::Path| {
+        let (stdout, _, _) = run_cli(&["list", "--reindex"], home);
+        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let sessions = json["sessions"].as_array().unwrap().clone();
+        let session = sessions.into_iter().find(|s| s["session_id"] == "test-claude-123");
+        session.unwrap()["title"].as_str().unwrap().to_string()
+    };
+    assert_eq!(title(temp_dir.path()), "hello world");
+
+    app.selected = app.results.iter().position(|r| r.session.id == "test-claude-123").unwrap();
+    app.on_rename();
+    assert_eq!(app.prompt.as_ref().map(|p| p.input.as_str()), Some(""));
+    " Login  flake hunt ".chars().for_each(|c| app.on_prompt_char(c));
+    app.submit_prompt();
+
+    // The new title wins over the derived one, shows in the list, and is searchable
+    assert!(buffer_contains(&render_app(&mut app), "Login flake hunt"));
+    "flake hunt".chars().for_each(|c| app.on_char(c));
+    app.flush_pending_search();
+    assert_eq!(result_ids(&app), ["test-claude-123"]);
+
+    // It's kept through a full reindex, which leaves the session file alone
+    assert_eq!(title(temp_dir.path()), "Login flake hunt");
+    let (stdout, _, _) = run_cli(&["search", "flake hunt"], temp_dir.path());
+    assert!(stdout.contains("test-claude-123"), "{}", stdout);
+
+    // Clearing it goes back to the derived title
+    app.on_rename();
+    assert_eq!(app.prompt.as_ref().map(|p| p.input.as_str()), Some("Login flake hunt"));
+    (0.."Login flake hunt".len()).for_each(|_| app.on_prompt_backspace());
+    app.submit_prompt();
+    assert_eq!(recall::titles::get("test-claude-123"), None);
+    assert_eq!(title(temp_dir.path()), "hello world");
+    std::env::remove_var("RECALL_HOME_OVERRIDE");
+}
+
 #[test]
 fn test_toggle_scope() {
     let _lock = lock_test();
@@ -358,6 +564,218 @@ fn test_initial_query() {
     assert_eq!(app.query, "initial", "Should have initial query");
 }
 
+/// Start the TUI app with the given options and wait for the index
+fn app_with_options(home: &std::path::Path, options: recall::AppOptions) -> recall::App {
+    std::env::set_var("RECALL_HOME_OVERRIDE", home);
+    std::env::set_var("RECALL_CWD_OVERRIDE", "/test/project");
+    let mut app = recall::App::with_options(options).unwrap();
+    wait_for_indexing(&mut app, 100);
+    std::env::remove_var("RECALL_HOME_OVERRIDE");
+    std::env::remove_var("RECALL_CWD_OVERRIDE");
+    app
+}
+
+fn result_ids(app: &recall::App) -> Vec<&str> {
+    let mut ids: Vec<&str> = app.results.iter().map(|r| r.session.id.as_str()).collect();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn test_app_options_default_scope() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let app = app_with_options(temp_dir.path(), recall::AppOptions::default());
+
+    assert_eq!(app.search_scope, recall::SearchScope::Folder("/test/project".to_string()));
+    assert_eq!(result_ids(&app), ["test-claude-123"]);
+}
+
+#[test]
+fn test_app_options_everywhere() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let options = recall::AppOptions { everywhere: true, ..Default::default() };
+    let app = app_with_options(temp_dir.path(), options);
+
+    assert_eq!(app.search_scope, recall::SearchScope::Everything);
+    assert_eq!(result_ids(&app), ["test-claude-123", "test-codex-456"]);
+}
+
+#[test]
+fn test_app_options_cwd() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let options = recall::AppOptions {
+        cwd: Some("/projects/webapp".to_string()),
+        ..Default::default()
+    };
+    let app = app_with_options(temp_dir.path(), options);
+
+    assert_eq!(app.search_scope, recall::SearchScope::Folder("/projects/webapp".to_string()));
+    assert_eq!(result_ids(&app), ["test-codex-456"]);
+
+    // A folder that isn't here just loses its trailing slash
+    let options = recall::AppOptions {
+        cwd: Some("/projects/webapp/".to_string()),
+        ..Default::default()
+    };
+    let app = app_with_options(temp_dir.path(), options);
+    assert_eq!(app.search_scope, recall::SearchScope::Folder("/projects/webapp".to_string()));
+
+    // A relative one is made absolute
+    let options =
+        recall::AppOptions { cwd: Some("tests/fixtures/".to_string()), ..Default::default() };
+    let app = app_with_options(temp_dir.path(), options);
+    let fixtures = std::fs::canonicalize("tests/fixtures").unwrap();
+    assert_eq!(app.search_scope, recall::SearchScope::Folder(fixtures.display().to_string()));
+}
+
+#[test]
+fn test_default_scope_config() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    write_config(temp_dir.path(), "default_scope = \"folder\"\n");
+    let app = app_with_options(temp_dir.path(), recall::AppOptions::default());
+    assert_eq!(app.search_scope, recall::SearchScope::Folder("/test/project".to_string()));
+
+    write_config(temp_dir.path(), "default_scope = \"everywhere\"\n");
+    let mut app = app_with_options(temp_dir.path(), recall::AppOptions::default());
+    assert_eq!(app.search_scope, recall::SearchScope::Everything);
+    assert_eq!(result_ids(&app), ["test-claude-123", "test-codex-456"]);
+    // `/` still narrows to the launch folder
+    app.toggle_scope();
+    assert_eq!(app.search_scope, recall::SearchScope::Folder("/test/project".to_string()));
+
+    // --cwd wins over the configured default
+    let options = recall::AppOptions {
+        cwd: Some("/projects/webapp".to_string()),
+        ..Default::default()
+    };
+    let app = app_with_options(temp_dir.path(), options);
+    assert_eq!(app.search_scope, recall::SearchScope::Folder("/projects/webapp".to_string()));
+}
+
+#[test]
+fn test_default_scope_repo() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let repo = TempDir::new().unwrap();
+    std::fs::create_dir(repo.path().join(".git")).unwrap();
+    let root = repo.path().display().to_string();
+    write_session_in(temp_dir.path(), "repo-root", &root, 2);
+    write_session_in(temp_dir.path(), "repo-nested", &format!("{}/crates/core", root), 3);
+    write_config(temp_dir.path(), "default_scope = \"repo\"\n");
+
+    std::env::set_var("RECALL_HOME_OVERRIDE", temp_dir.path());
+    std::env::set_var("RECALL_CWD_OVERRIDE", format!("{}/crates", root));
+    let mut app = recall::App::with_options(recall::AppOptions::default()).unwrap();
+    wait_for_indexing(&mut app, 100);
+    std::env::remove_var("RECALL_HOME_OVERRIDE");
+    std::env::remove_var("RECALL_CWD_OVERRIDE");
+
+    assert_eq!(app.search_scope, recall::SearchScope::Repo(root.clone()));
+    assert_eq!(result_ids(&app), ["repo-nested", "repo-root"]);
+
+    app.toggle_scope();
+    assert_eq!(app.search_scope, recall::SearchScope::Everything);
+    app.toggle_scope();
+    assert_eq!(app.search_scope, recall::SearchScope::Repo(root));
+
+    // --everywhere wins over the configured default
+    let options = recall::AppOptions { everywhere: true, ..Default::default() };
+    let app = app_with_options(temp_dir.path(), options);
+    assert_eq!(app.search_scope, recall::SearchScope::Everything);
+}
+
+#[test]
+fn test_app_options_source_and_since() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let options = recall::AppOptions {
+        everywhere: true,
+        source: Some(recall::SessionSource::CodexCli),
+        ..Default::default()
+    };
+    let app = app_with_options(temp_dir.path(), options);
+    assert_eq!(app.source_filter, Some(recall::SessionSource::CodexCli));
+    assert_eq!(result_ids(&app), ["test-codex-456"]);
+
+    // Between the Claude fixture (Jan 15) and the Codex one (Jan 16)
+    let since = recall::time::parse_time("2025-01-16").unwrap();
+    let options = recall::AppOptions { everywhere: true, since: Some(since), ..Default::default() };
+    let app = app_with_options(temp_dir.path(), options);
+    assert_eq!(app.since_filter, Some(since));
+    assert_eq!(result_ids(&app), ["test-codex-456"]);
+}
+
+#[test]
+fn test_app_options_with_query() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let options = recall::AppOptions {
+        query: "hello".to_string(),
+        everywhere: true,
+        source: Some(recall::SessionSource::ClaudeCode),
+        ..Default::default()
+    };
+    let app = app_with_options(temp_dir.path(), options);
+
+    assert_eq!(app.query, "hello");
+    assert_eq!(app.cursor, 5);
+    assert_eq!(result_ids(&app), ["test-claude-123"]);
+}
+
+#[test]
+fn test_recent_not_crowded_out_by_long_session() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+
+    // Ten small sessions, all older than one 600-message session
+    for day in 1..=10 {
+        let messages = [("user", format!("question {}", day)), ("assistant", "answer".into())];
+        write_claude_session(home, &format!("small-{:02}", day), day, &messages);
+    }
+    let lines: Vec<String> = (0..600)
+        .map(|i| {
+            let role = if i % 2 == 0 { "user" } else { "assistant" };
+            serde_json::json!({
+                "cwd": "/test/context",
+                "sessionId": "big",
+                "type": role,
+                "message": {"role": role, "content": [{"type": "text", "text": "more"}]},
+                "timestamp": format!("2025-04-20T{:02}:{:02}:00.000Z", i / 60, i % 60),
+            })
+            .to_string()
+        })
+        .collect();
+    let big = home.join(".claude/projects/context/big.jsonl");
+    std::fs::write(big, lines.join("\n") + "\n").unwrap();
+
+    let options = recall::AppOptions { everywhere: true, ..Default::default() };
+    let app = app_with_options(home, options);
+    let mut expected: Vec<String> = (1..=10).map(|day| format!("small-{:02}", day)).collect();
+    expected.insert(0, "big".to_string());
+    assert_eq!(result_ids(&app).len(), 11);
+
+    let index = recall::index::SessionIndex::open_or_create(&home.join(".cache/recall/index"))
+        .unwrap();
+    let recent: Vec<String> = index.recent(11).unwrap().into_iter().map(|r| r.session.id).collect();
+    expected[1..].reverse();
+    assert_eq!(recent, expected);
+
+    // A small limit still gets distinct sessions rather than the big session's messages
+    let recent: Vec<String> = index.recent(3).unwrap().into_iter().map(|r| r.session.id).collect();
+    assert_eq!(recent, ["big", "small-10", "small-09"]);
+}
+
 // =============================================================================
 // UI Snapshot Tests
 // =============================================================================
@@ -462,135 +880,663 @@ fn test_ui_with_query_everywhere_scope_no_results() {
     assert_snapshot!(buffer_to_string(&terminal));
 }
 
-// =============================================================================
-// CLI Integration Tests
-// =============================================================================
-
-use std::process::Command;
+#[test]
+fn test_ui_theme_presets_no_results() {
+    let _lock = lock_test();
+    let _temp_dir = setup_ui_test();
 
-fn recall_bin() -> PathBuf {
-    PathBuf::from(env!("CARGO_BIN_EXE_recall"))
-}
+    let mut app = recall::App::new(String::new()).unwrap();
+    wait_for_indexing(&mut app, 100);
+    for c in "zzzznotfound".chars() {
+        app.on_char(c);
+    }
+    app.flush_pending_search();
 
-fn run_cli(args: &[&str], home_override: &std::path::Path) -> (String, String, bool) {
-    let output = Command::new(recall_bin())
-        .args(args)
-        .env("RECALL_HOME_OVERRIDE", home_override)
-        .output()
-        .expect("Failed to run recall");
+    for name in ["gruvbox", "solarized-light"] {
+        recall::ui::set_theme(recall::theme::Theme::named(name).unwrap());
+        let terminal = render_app(&mut app);
+        assert_snapshot!(format!("ui_theme_{}", name), styled_buffer_to_string(&terminal));
+    }
+    // The theme is global; put back the one the other UI tests expect
+    recall::ui::set_theme(recall::theme::Theme::named("dark").unwrap());
 
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-    (stdout, stderr, output.status.success())
+    cleanup_ui_test();
 }
 
 #[test]
-fn test_cli_search_returns_json() {
+fn test_ui_long_snippet_wraps_when_wide() {
     let _lock = lock_test();
-    let temp_dir = setup_test_env();
-
-    let (stdout, _stderr, success) = run_cli(
-        &["search", "hello", "--limit", "5"],
-        temp_dir.path(),
-    );
+    let temp_dir = TempDir::new().unwrap();
+    let filler = "the quick brown fox jumps over the lazy dog and keeps running through the field";
+    let message = format!("{} the needle is here {} {} {}", filler, filler, filler, filler);
+    write_claude_session(temp_dir.path(), "long-snippet", 3, &[("user", message)]);
 
-    assert!(success, "CLI search should succeed");
+    let mut config = recall::config::Config::default();
+    config.search.snippet_chars = 400;
+    std::env::set_var("RECALL_HOME_OVERRIDE", temp_dir.path());
+    std::env::set_var("RECALL_CWD_OVERRIDE", "/test/context");
+    let options = recall::AppOptions { query: "needle".to_string(), ..Default::default() };
+    let mut app = recall::App::with_config(options, &config).unwrap();
+    wait_for_indexing(&mut app, 100);
+    app.flush_pending_search();
 
-    // Parse as JSON
-    let json: serde_json::Value = serde_json::from_str(&stdout)
-        .expect("Output should be valid JSON");
+    let mut terminal = Terminal::new(TestBackend::new(180, 16)).unwrap();
+    terminal.draw(|f| recall::ui::render(f, &mut app)).unwrap();
+    std::env::remove_var("RECALL_HOME_OVERRIDE");
+    std::env::remove_var("RECALL_CWD_OVERRIDE");
 
-    assert_eq!(json["query"], "hello");
-    assert!(json["results"].is_array());
+    assert!(app.results[0].snippet.len() > 200);
+    assert_snapshot!(buffer_to_string(&terminal));
 }
 
 #[test]
-fn test_cli_search_finds_fixture_content() {
+fn test_ui_inline_height() {
     let _lock = lock_test();
-    let temp_dir = setup_test_env();
-
-    let (stdout, _stderr, success) = run_cli(
-        &["search", "hello", "--limit", "10"],
-        temp_dir.path(),
-    );
+    let temp_dir = TempDir::new().unwrap();
+    let message = "the needle is in the inline viewport".to_string();
+    write_claude_session(temp_dir.path(), "inline", 3, &[("user", message)]);
 
-    assert!(success);
+    std::env::set_var("RECALL_HOME_OVERRIDE", temp_dir.path());
+    std::env::set_var("RECALL_CWD_OVERRIDE", "/test/context");
+    let options = recall::AppOptions { query: "needle".to_string(), ..Default::default() };
+    let mut app = recall::App::with_options(options).unwrap();
+    wait_for_indexing(&mut app, 100);
+    app.flush_pending_search();
 
-    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
-    let results = json["results"].as_array().unwrap();
+    // `--height 10` on a taller terminal: the UI gets ten rows and a compact layout
+    let mut terminal = Terminal::new(TestBackend::new(100, 10)).unwrap();
+    terminal.draw(|f| recall::ui::render(f, &mut app)).unwrap();
+    std::env::remove_var("RECALL_HOME_OVERRIDE");
+    std::env::remove_var("RECALL_CWD_OVERRIDE");
 
-    // Should find the Claude fixture session
-    assert!(
-        results.iter().any(|r| r["session_id"] == "test-claude-123"),
-        "Should find Claude fixture session"
-    );
+    assert_snapshot!(buffer_to_string(&terminal));
 }
 
 #[test]
-fn test_cli_search_with_source_filter() {
+fn test_ui_accessible_list() {
     let _lock = lock_test();
-    let temp_dir = setup_test_env();
-
-    let (stdout, _stderr, success) = run_cli(
-        &["search", "hello", "--source", "claude", "--limit", "10"],
-        temp_dir.path(),
-    );
+    let temp_dir = TempDir::new().unwrap();
+    let first = "deploy the needle service to staging".to_string();
+    write_claude_session(temp_dir.path(), "a11y-first", 3, &[("user", first)]);
+    let second = "where did the needle go after the refactor".to_string();
+    let reply = "It moved to the parser module.".to_string();
+    let messages = [("user", second), ("assistant", reply)];
+    write_claude_session(temp_dir.path(), "a11y-second", 4, &messages);
 
-    assert!(success);
+    std::env::set_var("RECALL_HOME_OVERRIDE", temp_dir.path());
+    std::env::set_var("RECALL_CWD_OVERRIDE", "/test/context");
+    let options = recall::AppOptions { query: "needle".to_string(), ..Default::default() };
+    let config = recall::config::Config { accessible: true, ..Default::default() };
+    let mut app = recall::App::with_config(options, &config).unwrap();
+    wait_for_indexing(&mut app, 100);
+    app.flush_pending_search();
+    app.on_down();
 
-    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
-    let results = json["results"].as_array().unwrap();
+    let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+    terminal.draw(|f| recall::ui::render(f, &mut app)).unwrap();
+    std::env::remove_var("RECALL_HOME_OVERRIDE");
+    std::env::remove_var("RECALL_CWD_OVERRIDE");
 
-    // All results should be Claude
-    for result in results {
-        assert_eq!(result["source"], "claude");
-    }
+    assert_snapshot!(buffer_to_string(&terminal));
 }
 
 #[test]
-fn test_cli_search_no_results() {
+fn test_ui_accessible_preview() {
     let _lock = lock_test();
-    let temp_dir = setup_test_env();
-
-    let (stdout, _stderr, success) = run_cli(
-        &["search", "xyznonexistent12345"],
-        temp_dir.path(),
-    );
+    let temp_dir = TempDir::new().unwrap();
+    let question = "why does the needle test flake".to_string();
+    let answer = (1..=20).map(|i| format!("step {}", i)).collect::<Vec<_>>().join("\n");
+    let thanks = "thanks".to_string();
+    let messages = [("user", question), ("assistant", answer), ("user", thanks)];
+    write_claude_session(temp_dir.path(), "a11y-preview", 3, &messages);
 
-    assert!(success);
+    std::env::set_var("RECALL_HOME_OVERRIDE", temp_dir.path());
+    std::env::set_var("RECALL_CWD_OVERRIDE", "/test/context");
+    let options = recall::AppOptions { query: "needle".to_string(), ..Default::default() };
+    let config = recall::config::Config { accessible: true, ..Default::default() };
+    let mut app = recall::App::with_config(options, &config).unwrap();
+    wait_for_indexing(&mut app, 100);
+    app.flush_pending_search();
 
-    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
-    let results = json["results"].as_array().unwrap();
+    let mut terminal = Terminal::new(TestBackend::new(80, 40)).unwrap();
+    terminal.draw(|f| recall::ui::render(f, &mut app)).unwrap();
+    std::env::remove_var("RECALL_HOME_OVERRIDE");
+    std::env::remove_var("RECALL_CWD_OVERRIDE");
 
-    assert!(results.is_empty(), "Should have no results for nonexistent query");
+    // Every message is headed by its speaker, and the long one is collapsed in words
+    let screen = buffer_to_string(&terminal);
+    assert!(!screen.contains('▎') && !screen.contains('📁'));
+    assert_snapshot!(screen);
 }
 
 #[test]
-fn test_cli_list_returns_json() {
+fn test_ui_multibyte_snippet_at_every_width() {
     let _lock = lock_test();
-    let temp_dir = setup_test_env();
-
-    let (stdout, _stderr, success) = run_cli(
-        &["list", "--limit", "5"],
-        temp_dir.path(),
+    let temp_dir = TempDir::new().unwrap();
+    let message = format!(
+        "{} needle🎉needle {} 検索のneedle テスト {}",
+        "🚀 launch ✨ sparkle ".repeat(4),
+        "日本語のテキスト".repeat(6),
+        "🎉".repeat(40)
     );
+    write_claude_session(temp_dir.path(), "emoji-snippet", 3, &[("user", message)]);
 
-    assert!(success, "CLI list should succeed");
-
-    let json: serde_json::Value = serde_json::from_str(&stdout)
-        .expect("Output should be valid JSON");
+    std::env::set_var("RECALL_HOME_OVERRIDE", temp_dir.path());
+    std::env::set_var("RECALL_CWD_OVERRIDE", "/test/context");
+    let options = recall::AppOptions { query: "needle".to_string(), ..Default::default() };
+    let mut app = recall::App::with_options(options).unwrap();
+    wait_for_indexing(&mut app, 100);
+    app.flush_pending_search();
+    std::env::remove_var("RECALL_HOME_OVERRIDE");
+    std::env::remove_var("RECALL_CWD_OVERRIDE");
+    assert_eq!(app.results.len(), 1);
 
-    assert!(json["sessions"].is_array());
+    // Somewhere in this range the cut lands inside an emoji and inside a match
+    for width in 40..140 {
+        let mut terminal = Terminal::new(TestBackend::new(width, 12)).unwrap();
+        terminal.draw(|f| recall::ui::render(f, &mut app)).unwrap();
+        assert!(buffer_to_string(&terminal).contains("needle"), "width {}", width);
+    }
 }
 
 #[test]
-fn test_cli_list_with_source_filter() {
+fn test_undated_messages_stay_historical() {
     let _lock = lock_test();
-    let temp_dir = setup_test_env();
-
-    let (stdout, _stderr, success) = run_cli(
-        &["list", "--source", "codex", "--limit", "10"],
-        temp_dir.path(),
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let write = |path: &str, lines: &[serde_json::Value]| {
+        let path = home.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        std::fs::write(&path, lines.join("\n") + "\n").unwrap();
+        path
+    };
+    use serde_json::json;
+
+    // Each session has an undated message first, a dated one, then a malformed timestamp
+    let claude = write(
+        ".claude/projects/p/undated.jsonl",
+        &[
+            json!({"type": "user", "sessionId": "c", "message": {"role": "user", "content": "a"}}),
+            json!({"type": "assistant", "timestamp": "2025-01-10T10:00:00Z",
+                   "message": {"role": "assistant", "content": "b"}}),
+            json!({"type": "user", "timestamp": "yesterday-ish",
+                   "message": {"role": "user", "content": "c"}}),
+        ],
+    );
+    let codex = write(
+        ".codex/sessions/undated.jsonl",
+        &[
+            json!({"type": "response_item",
+                   "payload": {"role": "user", "content": [{"type": "input_text", "text": "a"}]}}),
+            json!({"type": "response_item", "timestamp": "2025-01-10T10:00:00Z",
+                   "payload": {"role": "assistant",
+                               "content": [{"type": "output_text", "text": "b"}]}}),
+            json!({"type": "response_item", "timestamp": "not a time",
+                   "payload": {"role": "user", "content": [{"type": "input_text", "text": "c"}]}}),
+        ],
+    );
+    let factory = write(
+        ".factory/sessions/p/undated.jsonl",
+        &[
+            json!({"type": "message", "message": {"role": "user", "content": "a"}}),
+            json!({"type": "message", "timestamp": "2025-01-10T10:00:00Z",
+                   "message": {"role": "assistant", "content": "b"}}),
+            json!({"type": "message", "timestamp": "",
+                   "message": {"role": "user", "content": "c"}}),
+        ],
+    );
+    let storage = home.join(".local/share/opencode/storage");
+    for (path, contents) in [
+        ("message/ses_u/msg_a.json", json!({"id": "msg_a", "sessionID": "ses_u", "role": "user"})),
+        ("part/msg_a/prt_1.json", json!({"id": "prt_1", "type": "text", "text": "a"})),
+        ("message/ses_u/msg_b.json", json!({"id": "msg_b", "sessionID": "ses_u",
+                                            "role": "assistant",
+                                            "time": {"created": 1736503200000i64}})),
+        ("part/msg_b/prt_2.json", json!({"id": "prt_2", "type": "text", "text": "b"})),
+    ] {
+        let path = storage.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents.to_string()).unwrap();
+    }
+    let opencode = write(
+        ".local/share/opencode/storage/session/p/ses_u.json",
+        &[json!({"id": "ses_u", "directory": "/test/oc"})],
+    );
+
+    let dated: chrono::DateTime<chrono::Utc> = "2025-01-10T10:00:00Z".parse().unwrap();
+    for path in [claude, codex, factory, opencode] {
+        let session = recall::parser::parse_session_file(&path).unwrap();
+        assert_eq!(session.timestamp, dated, "{}", path.display());
+        for message in &session.messages {
+            assert_eq!(message.timestamp, dated, "{}: {:?}", path.display(), message);
+        }
+    }
+}
+
+#[test]
+fn test_index_reports_skipped_files() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let project = temp_dir.path().join(".claude/projects/broken");
+    std::fs::create_dir_all(&project).unwrap();
+    // Invalid UTF-8 can't be read as lines
+    let corrupt: &[u8] = b"{\"type\": \"user\", \"x\": \"\xff\xfe\"}\n";
+    std::fs::write(project.join("corrupt.jsonl"), corrupt).unwrap();
+    std::fs::write(project.join("empty.jsonl"), "").unwrap();
+
+    let mut app = app_with_options(temp_dir.path(), recall::AppOptions::default());
+    assert_eq!(app.total_sessions, 2);
+    assert_eq!(app.skipped_sessions, 2);
+    assert_eq!(app.index_failures.len(), 1);
+    assert!(app.index_failures[0].0.ends_with("broken/corrupt.jsonl"));
+
+    let terminal = render_app(&mut app);
+    assert!(buffer_contains(&terminal, "2 sessions (2 skipped)"));
+
+    let diagnostics = app.exit_diagnostics();
+    assert!(diagnostics.contains("Skipped 1 session file that couldn't be read:"));
+    assert!(diagnostics.contains("corrupt.jsonl: Failed to read line"), "{}", diagnostics);
+
+    // Next run: the empty file is remembered, the corrupt one is retried
+    let app = app_with_options(temp_dir.path(), recall::AppOptions::default());
+    assert_eq!((app.total_sessions, app.skipped_sessions), (2, 2));
+    assert_eq!(app.index_failures.len(), 1);
+
+    // The CLI reports them too
+    std::fs::write(project.join("empty.jsonl"), "\n").unwrap();
+    let (_, stderr, success) = run_cli(&["list"], temp_dir.path());
+    assert!(success);
+    assert!(stderr.contains("Indexed 0 sessions (2 skipped)."), "stderr: {}", stderr);
+    assert!(stderr.contains("Couldn't index"), "stderr: {}", stderr);
+}
+
+#[test]
+fn test_ui_monochrome() {
+    let _lock = lock_test();
+    let _temp_dir = setup_ui_test();
+
+    let mut app = recall::App::new(String::new()).unwrap();
+    wait_for_indexing(&mut app, 100);
+    app.toggle_scope();
+
+    recall::ui::set_theme(recall::theme::Theme::monochrome());
+    let terminal = render_app(&mut app);
+    recall::ui::set_theme(recall::theme::Theme::named("dark").unwrap());
+
+    cleanup_ui_test();
+
+    let styled = styled_buffer_to_string(&terminal);
+    assert!(!styled.contains("Rgb("), "monochrome rendered a color");
+    assert_snapshot!(buffer_to_string(&terminal));
+}
+
+#[test]
+fn test_copy_session_file_path() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+
+    let storage = home.join(".local/share/opencode/storage");
+    for (path, contents) in [
+        ("session/proj/ses_copy1.json", r#"{"id":"ses_copy1","directory":"/test/oc"}"#),
+        ("message/ses_copy1/msg_a.json", r#"{"id":"msg_a","sessionID":"ses_copy1","role":"user"}"#),
+        ("part/msg_a/prt_1.json", r#"{"id":"prt_1","type":"text","text":"opencode copy text"}"#),
+    ] {
+        let path = storage.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    let options = recall::AppOptions { everywhere: true, ..Default::default() };
+    let mut app = app_with_options(home, options);
+
+    let expected = [
+        ("test-claude-123", home.join(".claude/projects/test-project/session.jsonl")),
+        ("test-codex-456", home.join(".codex/sessions/test-codex.jsonl")),
+        ("ses_copy1", storage.join("session/proj/ses_copy1.json")),
+    ];
+    for (id, path) in expected {
+        app.selected = app
+            .results
+            .iter()
+            .position(|r| r.session.id == id)
+            .unwrap_or_else(|| panic!("{} should be listed", id));
+        app.on_copy_path();
+        assert_eq!(app.clipboard.take(), Some(path.to_string_lossy().into_owned()), "{}", id);
+    }
+
+    // The preview header shows the path too, with home as ~
+    let mut terminal = Terminal::new(TestBackend::new(160, 24)).unwrap();
+    std::env::set_var("RECALL_HOME_OVERRIDE", home);
+    terminal.draw(|f| recall::ui::render(f, &mut app)).unwrap();
+    std::env::remove_var("RECALL_HOME_OVERRIDE");
+    let header = "~/.local/share/opencode/storage/session/proj/ses_copy1.json";
+    assert!(buffer_contains(&terminal, header));
+}
+
+#[test]
+fn test_open_session_folder() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    let project = home.join("work/app");
+    std::fs::create_dir_all(&project).unwrap();
+    let session = serde_json::json!({
+        "type": "user",
+        "sessionId": "open-me",
+        "cwd": project,
+        "timestamp": "2025-01-16T10:00:00.000Z",
+        "message": {"role": "user", "content": "open this folder"},
+    });
+    let dir = home.join(".claude/projects/work-app");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("open-me.jsonl"), session.to_string()).unwrap();
+
+    let options = recall::AppOptions { everywhere: true, ..Default::default() };
+    let mut app = app_with_options(home, options);
+    let select = |app: &mut recall::App, id: &str| {
+        app.selected = app.results.iter().position(|r| r.session.id == id).unwrap();
+    };
+
+    std::env::set_var("RECALL_OPEN_CMD", "stub-opener --new-window '{cwd}'");
+    select(&mut app, "open-me");
+    app.on_open_folder();
+    std::env::remove_var("RECALL_OPEN_CMD");
+    let cwd = project.to_string_lossy().into_owned();
+    let expected = recall::app::OpenFolder {
+        cwd: cwd.clone(),
+        program: "stub-opener".to_string(),
+        args: vec!["--new-window".to_string(), cwd],
+        terminal: false,
+    };
+    assert_eq!(app.should_open.take(), Some(expected));
+
+    // A folder that's gone is reported, not opened
+    select(&mut app, "test-claude-123");
+    app.on_open_folder();
+    assert!(app.should_open.is_none());
+    assert_eq!(app.toast(), Some("Folder not found: /test/project"));
+}
+
+#[test]
+fn test_bookmark_messages() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let options = recall::AppOptions { everywhere: true, ..Default::default() };
+    let mut app = app_with_options(temp_dir.path(), options);
+    std::env::set_var("RECALL_HOME_OVERRIDE", temp_dir.path());
+
+    app.selected = app.results.iter().position(|r| r.session.id == "test-claude-123").unwrap();
+    let path = app.selected_result().unwrap().session.file_path.clone();
+    app.focused_message = Some(2);
+    app.toggle_bookmark();
+    assert_eq!(app.toast(), Some("Bookmarked message 3"));
+    assert!(temp_dir.path().join(".cache/recall/bookmarks.json").exists());
+
+    // The bookmarks view lists it like a search result
+    recall::app::BOOKMARKS_QUERY.chars().for_each(|c| app.on_char(c));
+    app.flush_pending_search();
+    assert_eq!(result_ids(&app), ["test-claude-123"]);
+    assert_eq!(app.results[0].matched_message_index, 2);
+    assert_eq!(app.results[0].snippet, "fix the authentication bug in login.rs");
+    let mut terminal = Terminal::new(TestBackend::new(160, 40)).unwrap();
+    terminal.draw(|f| recall::ui::render(f, &mut app)).unwrap();
+    assert!(buffer_contains(&terminal, "★"));
+
+    // Enter goes to the message, then resumes
+    app.on_enter();
+    assert_eq!(app.focused_message, Some(2));
+    assert!(app.should_resume.is_none());
+    app.on_enter();
+    assert!(app.should_resume.take().is_some());
+
+    // Messages added before it move it; the bookmark follows
+    let original = std::fs::read_to_string(&path).unwrap();
+    let first = original.lines().next().unwrap();
+    let first = first.replace("hello world", "an earlier message").replace("T10:", "T09:");
+    std::fs::write(&path, format!("{}\n{}", first, original)).unwrap();
+    app.on_backspace();
+    app.on_char('d');
+    app.flush_pending_search();
+    assert_eq!(app.results[0].matched_message_index, 3);
+
+    let (stdout, _, success) = run_cli(&["bookmarks"], temp_dir.path());
+    assert!(success);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json[0]["session_id"], "test-claude-123");
+    assert_eq!(json[0]["message_index"], 3);
+    assert_eq!(json[0]["role"], "user");
+
+    // Toggling again removes it
+    app.focused_message = Some(3);
+    app.toggle_bookmark();
+    assert_eq!(app.toast(), Some("Bookmark removed"));
+    assert!(app.results.is_empty());
+    std::env::remove_var("RECALL_HOME_OVERRIDE");
+}
+
+#[test]
+fn test_expand_more_matches() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    let messages = [
+        ("user", "the cache eviction policy drops old entries"),
+        ("assistant", "It evicts the least recently used ones."),
+        ("user", "why does the cache miss right after a restart"),
+        ("assistant", "It's only kept in memory."),
+        ("user", "add cache hit rates to the dashboard"),
+    ];
+    let lines: Vec<String> = messages
+        .iter()
+        .enumerate()
+        .map(|(i, (role, text))| {
+            serde_json::json!({
+                "cwd": "/test/project",
+                "sessionId": "many-matches",
+                "type": role,
+                "message": {"role": role, "content": text},
+                "timestamp": format!("2025-01-16T1{}:00:00.000Z", i),
+            })
+            .to_string()
+        })
+        .collect();
+    let dir = home.join(".claude/projects/test-project");
+    std::fs::write(dir.join("many-matches.jsonl"), lines.join("\n") + "\n").unwrap();
+
+    let options = recall::AppOptions { query: "cache".to_string(), ..Default::default() };
+    let mut app = app_with_options(home, options);
+    app.flush_pending_search();
+    app.selected = app.results.iter().position(|r| r.session.id == "many-matches").unwrap();
+    let result = app.selected_result().unwrap().clone();
+    assert_eq!(result.more_matches.len(), 2);
+    let mut matched: Vec<usize> = result.more_matches.iter().map(|m| m.message_index).collect();
+    matched.push(result.matched_message_index);
+    matched.sort();
+    assert_eq!(matched, [0, 2, 4]);
+
+    let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+    terminal.draw(|f| recall::ui::render(f, &mut app)).unwrap();
+    assert!(buffer_contains(&terminal, "+2 more matches"));
+
+    // Right at the end of the query expands the result; Down walks its other matches
+    app.on_right();
+    assert_eq!(app.expanded_result.as_deref(), Some("many-matches"));
+    assert_eq!(app.selected_match(), Some((result.matched_message_index, &*result.match_fragment)));
+    app.on_down();
+    assert_eq!(app.selected_match, Some(0));
+    let first = &result.more_matches[0];
+    assert_eq!(app.selected_match(), Some((first.message_index, &*first.match_fragment)));
+    app.focus_next_message();
+    assert_eq!(app.focused_message, Some(first.message_index + 1));
+    app.on_down();
+    assert_eq!(app.selected_match, Some(1));
+    assert_eq!(app.focused_message, None);
+    terminal.draw(|f| recall::ui::render(f, &mut app)).unwrap();
+    assert!(buffer_contains(&terminal, "▸"));
+    assert!(!buffer_contains(&terminal, "more matches"));
+
+    // Past the last match is the next result; coming back up lands on that last match
+    let last_result = app.selected + 1 == app.results.len();
+    app.on_down();
+    if !last_result {
+        assert_eq!(app.selected_match, None);
+        app.on_up();
+    }
+    assert_eq!(app.selected_match, Some(1));
+    app.on_up();
+    app.on_up();
+    assert_eq!(app.selected_match, None);
+
+    // Left folds it back up, and a click on the hint opens it again
+    app.on_left();
+    assert_eq!(app.expanded_result, None);
+    assert_eq!(app.cursor, 5);
+    terminal.draw(|f| recall::ui::render(f, &mut app)).unwrap();
+    let (x, y, _) = app.more_matches_line.unwrap();
+    assert!(app.click_results_list(x + 2, y));
+    assert_eq!(app.expanded_result.as_deref(), Some("many-matches"));
+
+    // Left inside the query moves the cursor as usual
+    app.on_home();
+    app.on_right();
+    assert_eq!(app.cursor, 1);
+    app.on_left();
+    assert_eq!(app.cursor, 0);
+    assert!(app.expanded_result.is_some());
+}
+
+// =============================================================================
+// CLI Integration Tests
+// =============================================================================
+
+use std::process::Command;
+
+fn recall_bin() -> PathBuf {
+    PathBuf::from(env!("CARGO_BIN_EXE_recall"))
+}
+
+fn run_cli(args: &[&str], home_override: &std::path::Path) -> (String, String, bool) {
+    let output = Command::new(recall_bin())
+        .args(args)
+        .env("RECALL_HOME_OVERRIDE", home_override)
+        .env_remove("NO_COLOR")
+        .output()
+        .expect("Failed to run recall");
+
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    (stdout, stderr, output.status.success())
+}
+
+#[test]
+fn test_cli_search_returns_json() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let (stdout, _stderr, success) = run_cli(
+        &["search", "hello", "--limit", "5"],
+        temp_dir.path(),
+    );
+
+    assert!(success, "CLI search should succeed");
+
+    // Parse as JSON
+    let json: serde_json::Value = serde_json::from_str(&stdout)
+        .expect("Output should be valid JSON");
+
+    assert_eq!(json["query"], "hello");
+    assert!(json["results"].is_array());
+}
+
+#[test]
+fn test_cli_search_finds_fixture_content() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let (stdout, _stderr, success) = run_cli(
+        &["search", "hello", "--limit", "10"],
+        temp_dir.path(),
+    );
+
+    assert!(success);
+
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let results = json["results"].as_array().unwrap();
+
+    // Should find the Claude fixture session
+    assert!(
+        results.iter().any(|r| r["session_id"] == "test-claude-123"),
+        "Should find Claude fixture session"
+    );
+}
+
+#[test]
+fn test_cli_search_with_source_filter() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let (stdout, _stderr, success) = run_cli(
+        &["search", "hello", "--source", "claude", "--limit", "10"],
+        temp_dir.path(),
+    );
+
+    assert!(success);
+
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let results = json["results"].as_array().unwrap();
+
+    // All results should be Claude
+    for result in results {
+        assert_eq!(result["source"], "claude");
+    }
+}
+
+#[test]
+fn test_cli_search_no_results() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let (stdout, _stderr, success) = run_cli(
+        &["search", "xyznonexistent12345"],
+        temp_dir.path(),
+    );
+
+    assert!(success);
+
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let results = json["results"].as_array().unwrap();
+
+    assert!(results.is_empty(), "Should have no results for nonexistent query");
+}
+
+#[test]
+fn test_cli_list_returns_json() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let (stdout, _stderr, success) = run_cli(
+        &["list", "--limit", "5"],
+        temp_dir.path(),
+    );
+
+    assert!(success, "CLI list should succeed");
+
+    let json: serde_json::Value = serde_json::from_str(&stdout)
+        .expect("Output should be valid JSON");
+
+    assert!(json["sessions"].is_array());
+}
+
+#[test]
+fn test_cli_list_with_source_filter() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let (stdout, _stderr, success) = run_cli(
+        &["list", "--source", "codex", "--limit", "10"],
+        temp_dir.path(),
     );
 
     assert!(success);
@@ -732,3 +1678,3420 @@ fn test_cli_list_with_cwd_filter() {
         assert_eq!(session["cwd"], "/test/project");
     }
 }
+
+#[test]
+fn test_cli_import_restores_deleted_sessions() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+
+    // Export both fixture sessions, then delete the originals
+    let export_dir = home.join("export");
+    std::fs::create_dir_all(&export_dir).unwrap();
+    for id in ["test-claude-123", "test-codex-456"] {
+        let (stdout, _stderr, success) = run_cli(&["read", id], home);
+        assert!(success, "read {} should succeed", id);
+        std::fs::write(export_dir.join(format!("{}.json", id)), stdout).unwrap();
+    }
+    std::fs::remove_dir_all(home.join(".claude")).unwrap();
+    std::fs::remove_dir_all(home.join(".codex")).unwrap();
+
+    let (stdout, _stderr, success) =
+        run_cli(&["import", export_dir.to_str().unwrap()], home);
+    assert!(success, "import should succeed");
+    assert!(stdout.contains("Imported 2 sessions"), "got: {}", stdout);
+
+    // Imported sessions are searchable with their original metadata
+    let (stdout, _stderr, success) = run_cli(&["search", "hello", "--limit", "10"], home);
+    assert!(success);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let result = json["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["session_id"] == "test-claude-123")
+        .expect("imported Claude session should be searchable");
+    assert_eq!(result["source"], "claude");
+    assert_eq!(result["cwd"], "/test/project");
+
+    let (stdout, _stderr, success) = run_cli(&["read", "test-codex-456"], home);
+    assert!(success, "imported session should be readable");
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["source"], "codex");
+    assert!(!json["messages"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_cli_import_skips_conflicts_unless_overwrite() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+
+    let (stdout, _stderr, _) = run_cli(&["read", "test-claude-123"], home);
+    let export = home.join("test-claude-123.json");
+    std::fs::write(&export, stdout).unwrap();
+
+    // Original is still present
+    let (stdout, _stderr, success) = run_cli(&["import", export.to_str().unwrap()], home);
+    assert!(success);
+    assert!(stdout.contains("Imported 0 sessions"));
+    assert!(stdout.contains("test-claude-123: original still present"));
+
+    std::fs::remove_dir_all(home.join(".claude")).unwrap();
+    let (stdout, _stderr, _) = run_cli(&["import", export.to_str().unwrap()], home);
+    assert!(stdout.contains("Imported 1 session "), "got: {}", stdout);
+
+    // Already imported
+    let (stdout, _stderr, _) = run_cli(&["import", export.to_str().unwrap()], home);
+    assert!(stdout.contains("Imported 0 sessions"));
+    assert!(stdout.contains("already imported"));
+
+    let (stdout, _stderr, _) =
+        run_cli(&["import", export.to_str().unwrap(), "--overwrite"], home);
+    assert!(stdout.contains("Imported 1 session "), "got: {}", stdout);
+}
+
+#[test]
+fn test_cli_import_reports_invalid_files() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+
+    let bogus = home.join("bogus.json");
+    std::fs::write(&bogus, r#"{"not": "a session"}"#).unwrap();
+
+    let (stdout, _stderr, success) = run_cli(&["import", bogus.to_str().unwrap()], home);
+    assert!(success);
+    assert!(stdout.contains("Skipped 1:"));
+    assert!(stdout.contains("Not a recall session export"));
+}
+
+#[test]
+fn test_cli_import_chatgpt_export() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    let id = "6842a1b0-77c4-8000-9e1d-2f3c4b5a6d7e";
+    let node = |parent: &str, role: &str, time: f64, text: &str| {
+        serde_json::json!({"parent": parent, "message": {"author": {"role": role},
+            "create_time": time, "content": {"content_type": "text", "parts": [text]}}})
+    };
+    let mut conversation = serde_json::json!({
+        "title": "Sourdough", "conversation_id": id, "create_time": 1748772000.0,
+        "current_node": "a1",
+        "mapping": {
+            "u1": node("", "user", 1748772000.0, "how long should sourdough proof?"),
+            "a1": node("u1", "assistant", 1748772030.0, "Four to six hours at room temperature."),
+        }
+    });
+    let export = home.join("conversations.json");
+    std::fs::write(&export, serde_json::json!([conversation]).to_string()).unwrap();
+
+    let args = ["import", "chatgpt", export.to_str().unwrap()];
+    let (stdout, stderr, success) = run_cli(&args, home);
+    assert!(success, "{}", stderr);
+    assert!(stdout.starts_with("Imported 1 conversation into"), "got: {}", stdout);
+
+    let search = || {
+        let (stdout, _stderr, success) = run_cli(&["search", "sourdough", "--limit", "10"], home);
+        assert!(success);
+        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        json["results"].as_array().unwrap().clone()
+    };
+    let results = search();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["session_id"], id);
+    assert_eq!(results[0]["source"], "chatgpt");
+
+    // A later export of the same conversation replaces the earlier import
+    conversation["mapping"]["u2"] = node("a1", "user", 1748775600.0, "and in the fridge?");
+    conversation["current_node"] = "u2".into();
+    std::fs::write(&export, serde_json::json!([conversation]).to_string()).unwrap();
+    let (stdout, _stderr, _) = run_cli(&args, home);
+    assert!(stdout.contains("(1 updated, 0 unchanged)"), "got: {}", stdout);
+    let (stdout, _stderr, _) = run_cli(&args, home);
+    assert!(stdout.contains("(0 updated, 1 unchanged)"), "got: {}", stdout);
+
+    let results = search();
+    assert_eq!(results.len(), 1);
+    let (stdout, _stderr, success) = run_cli(&["read", id], home);
+    assert!(success);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["messages"].as_array().unwrap().len(), 3);
+}
+
+/// Read a backup archive into (entry path -> contents)
+fn read_archive(path: &std::path::Path) -> std::collections::BTreeMap<String, String> {
+    use std::io::Read;
+
+    let file = std::fs::File::open(path).unwrap();
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+    archive
+        .entries()
+        .unwrap()
+        .map(|entry| {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().to_string_lossy().to_string();
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents).unwrap();
+            (name, contents)
+        })
+        .collect()
+}
+
+#[test]
+fn test_cli_backup_writes_manifest_and_files() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+
+    // OpenCode keeps messages and parts in sibling trees
+    let storage = home.join(".local/share/opencode/storage");
+    for (path, contents) in [
+        ("session/proj/ses_backup1.json", r#"{"id":"ses_backup1","directory":"/test/oc"}"#),
+        ("message/ses_backup1/msg_a.json", r#"{"id":"msg_a","sessionID":"ses_backup1","role":"user"}"#),
+        ("part/msg_a/prt_1.json", r#"{"id":"prt_1","type":"text","text":"opencode backup text"}"#),
+    ] {
+        let path = storage.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    let archive = home.join("backup.tar.gz");
+    let (stdout, _stderr, success) = run_cli(&["backup", archive.to_str().unwrap()], home);
+    assert!(success, "backup should succeed");
+    assert!(stdout.contains("Backed up 3 sessions"), "got: {}", stdout);
+
+    let entries = read_archive(&archive);
+    assert!(entries.contains_key(".claude/projects/test-project/session.jsonl"));
+    assert!(entries.contains_key(".codex/sessions/test-codex.jsonl"));
+    assert!(entries.contains_key(".local/share/opencode/storage/message/ses_backup1/msg_a.json"));
+    assert!(entries.contains_key(".local/share/opencode/storage/part/msg_a/prt_1.json"));
+
+    let manifest: serde_json::Value = serde_json::from_str(&entries["manifest.json"]).unwrap();
+    let sessions = manifest["sessions"].as_array().unwrap();
+    let claude = sessions
+        .iter()
+        .find(|s| s["id"] == "test-claude-123")
+        .expect("manifest should list the Claude session");
+    assert_eq!(claude["source"], "claude");
+    assert_eq!(claude["archive_path"], ".claude/projects/test-project/session.jsonl");
+    assert!(claude["original_path"].as_str().unwrap().ends_with("session.jsonl"));
+    assert!(claude["mtime"].is_string());
+
+    let opencode = sessions.iter().find(|s| s["id"] == "ses_backup1").unwrap();
+    assert_eq!(opencode["source"], "opencode");
+    assert_eq!(opencode["extra_files"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_cli_backup_incremental_skips_unchanged_files() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    let archive = home.join("backup.tar.gz");
+
+    let (stdout, _stderr, _) = run_cli(&["backup", archive.to_str().unwrap()], home);
+    assert!(stdout.contains("2 files added, 0 unchanged"), "got: {}", stdout);
+
+    let (stdout, _stderr, _) =
+        run_cli(&["backup", archive.to_str().unwrap(), "--incremental"], home);
+    assert!(stdout.contains("0 files added, 2 unchanged"), "got: {}", stdout);
+
+    // Growing a session file makes it count as changed
+    let codex = home.join(".codex/sessions/test-codex.jsonl");
+    let mut contents = std::fs::read_to_string(&codex).unwrap();
+    contents.push('\n');
+    std::fs::write(&codex, contents).unwrap();
+
+    let (stdout, _stderr, _) =
+        run_cli(&["backup", archive.to_str().unwrap(), "--incremental"], home);
+    assert!(stdout.contains("1 files added, 1 unchanged"), "got: {}", stdout);
+
+    let entries = read_archive(&archive);
+    assert_eq!(entries.len(), 3, "two session files plus the manifest");
+    let manifest: serde_json::Value = serde_json::from_str(&entries["manifest.json"]).unwrap();
+    assert_eq!(manifest["sessions"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_cli_backup_incremental_keeps_deleted_sessions() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    let archive = home.join("backup.tar.gz");
+    let (_stdout, _stderr, success) = run_cli(&["backup", archive.to_str().unwrap()], home);
+    assert!(success);
+
+    // The agent pruned a session: the backup still has it
+    std::fs::remove_dir_all(home.join(".codex")).unwrap();
+    let (stdout, stderr, success) =
+        run_cli(&["backup", archive.to_str().unwrap(), "--incremental"], home);
+    assert!(success, "stderr: {}", stderr);
+    assert!(stdout.contains("Backed up 2 sessions"), "got: {}", stdout);
+    assert!(stdout.contains("1 kept though no longer on disk"), "got: {}", stdout);
+    let entries = read_archive(&archive);
+    assert!(entries.contains_key(".codex/sessions/test-codex.jsonl"));
+    let manifest: serde_json::Value = serde_json::from_str(&entries["manifest.json"]).unwrap();
+    let ids: Vec<_> = manifest["sessions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, ["test-claude-123", "test-codex-456"]);
+
+    // And again, with nothing changed
+    let (stdout, _, _) = run_cli(&["backup", archive.to_str().unwrap(), "--incremental"], home);
+    assert!(stdout.contains("Backed up 2 sessions"), "got: {}", stdout);
+
+    // A failed run leaves no temp file behind
+    std::fs::write(&archive, "not an archive").unwrap();
+    let (_stdout, _stderr, success) =
+        run_cli(&["backup", archive.to_str().unwrap(), "--incremental"], home);
+    assert!(!success);
+    assert!(!archive.with_extension("tmp").exists());
+}
+
+#[test]
+fn test_cli_import_restores_backup_archive() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    let archive = home.join("backup.tar.gz");
+
+    let (_stdout, _stderr, success) = run_cli(&["backup", archive.to_str().unwrap()], home);
+    assert!(success);
+    std::fs::remove_dir_all(home.join(".claude")).unwrap();
+    std::fs::remove_dir_all(home.join(".codex")).unwrap();
+
+    let (stdout, _stderr, success) = run_cli(&["import", archive.to_str().unwrap()], home);
+    assert!(success, "import should succeed");
+    assert!(stdout.contains("Imported 2 sessions"), "got: {}", stdout);
+
+    let (stdout, _stderr, success) = run_cli(&["read", "test-codex-456"], home);
+    assert!(success, "restored session should be readable");
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["source"], "codex");
+    assert_eq!(json["cwd"], "/projects/webapp");
+    assert_eq!(json["git_branch"], "main");
+}
+
+#[test]
+fn test_cli_search_fields_selects_keys() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let (stdout, _stderr, success) = run_cli(
+        &["search", "hello", "--fields", "id,timestamp"],
+        temp_dir.path(),
+    );
+    assert!(success);
+
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["query"], "hello");
+    let results = json["results"].as_array().unwrap();
+    assert!(!results.is_empty());
+    for result in results {
+        let keys: Vec<_> = result.as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["session_id", "timestamp"]);
+    }
+}
+
+#[test]
+fn test_cli_search_fields_messages_opt_in() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let (stdout, _stderr, _) = run_cli(
+        &["search", "hello", "--fields", "id,messages"],
+        temp_dir.path(),
+    );
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let result = &json["results"][0];
+    assert!(result["relevant_messages"].is_array());
+    assert!(result.get("cwd").is_none());
+}
+
+#[test]
+fn test_cli_list_fields_selects_keys() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let (stdout, _stderr, success) = run_cli(
+        &["list", "--fields", "id,cwd,resume_command"],
+        temp_dir.path(),
+    );
+    assert!(success);
+
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    for session in json["sessions"].as_array().unwrap() {
+        let session = session.as_object().unwrap();
+        assert_eq!(session.len(), 3);
+        assert!(session.contains_key("resume_command"));
+        assert!(!session.contains_key("timestamp"));
+    }
+}
+
+#[test]
+fn test_cli_fields_unknown_field_errors() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let (_stdout, stderr, success) = run_cli(
+        &["list", "--fields", "id,nope"],
+        temp_dir.path(),
+    );
+    assert!(!success);
+    assert!(stderr.contains("Unknown field 'nope'"), "got: {}", stderr);
+    assert!(stderr.contains("Valid fields: id, source"), "got: {}", stderr);
+}
+
+#[test]
+fn test_cli_list_csv() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let (stdout, _stderr, success) = run_cli(&["list", "--format", "csv"], temp_dir.path());
+    assert!(success);
+
+    assert_snapshot!("cli_list_csv", stdout);
+}
+
+#[test]
+fn test_cli_list_csv_respects_fields() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let (stdout, _stderr, success) = run_cli(
+        &["list", "--format", "csv", "--fields", "timestamp,id"],
+        temp_dir.path(),
+    );
+    assert!(success);
+
+    let mut lines = stdout.lines();
+    assert_eq!(lines.next(), Some("timestamp,id"));
+    assert!(lines.any(|l| l == "2025-01-15T10:00:03Z,test-claude-123"));
+}
+
+#[test]
+fn test_cli_list_csv_quotes_titles() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let project = temp_dir.path().join(".claude/projects/csv");
+    std::fs::create_dir_all(&project).unwrap();
+    let line = serde_json::json!({
+        "cwd": "/test/csv",
+        "sessionId": "csv-1",
+        "type": "user",
+        "message": {"role": "user", "content": "fix \"parse\", then test"},
+        "timestamp": "2025-03-01T10:00:00.000Z",
+    });
+    std::fs::write(project.join("csv-1.jsonl"), format!("{}\n", line)).unwrap();
+
+    let (stdout, _stderr, success) = run_cli(
+        &["list", "--format", "csv", "--fields", "id,title"],
+        temp_dir.path(),
+    );
+    assert!(success);
+    assert_eq!(stdout, "id,title\ncsv-1,\"fix \"\"parse\"\", then test\"\n");
+}
+
+#[test]
+fn test_cli_sources_json() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    // Build the index so indexed counts are populated
+    let (_stdout, _stderr, success) = run_cli(&["list"], temp_dir.path());
+    assert!(success);
+
+    let (stdout, _stderr, success) = run_cli(&["sources", "--json"], temp_dir.path());
+    assert!(success);
+
+    let home = temp_dir.path().to_string_lossy().to_string();
+    assert_snapshot!("cli_sources_json", stdout.replace(&home, "[HOME]"));
+}
+
+#[test]
+fn test_cli_sources_text() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let (stdout, _stderr, success) = run_cli(&["sources"], temp_dir.path());
+    assert!(success);
+
+    let mut lines = stdout.lines();
+    assert!(lines.next().unwrap().starts_with("SOURCE"));
+    let claude = lines.next().unwrap();
+    assert!(claude.starts_with("claude"), "got: {}", claude);
+    assert!(claude.contains(".claude/projects"), "got: {}", claude);
+    // Nothing indexed yet: sources reports the index as it is
+    assert!(claude.split_whitespace().take(3).eq(["claude", "1", "0"]), "got: {}", claude);
+    assert!(stdout.contains("(not found)"));
+}
+
+#[test]
+fn test_cli_print_plain() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    // Piped output: plain text, no ANSI escapes
+    let (stdout, stderr, success) = run_cli(&["print", "test-claude-123"], temp_dir.path());
+    assert!(success, "stderr: {}", stderr);
+    assert!(!stdout.contains('\x1b'));
+
+    assert_snapshot!("cli_print_plain", stdout);
+}
+
+#[test]
+fn test_cli_print_slice() {
+    let _lock = lock_test();
+    let temp_dir = setup_tools_env();
+
+    let (stdout, stderr, success) = run_cli(&["print", "tools-claude-1:1..3"], temp_dir.path());
+    assert!(success, "stderr: {}", stderr);
+
+    assert_snapshot!("cli_print_slice", stdout);
+}
+
+#[test]
+fn test_cli_print_unknown_session() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let (_stdout, stderr, success) = run_cli(&["print", "nope:1"], temp_dir.path());
+    assert!(!success);
+    assert!(stderr.contains("Session not found: nope"), "got: {}", stderr);
+}
+
+#[test]
+fn test_cli_summary_text() {
+    let _lock = lock_test();
+    let temp_dir = setup_tools_env();
+
+    let (stdout, stderr, success) = run_cli(&["summary", "tools-claude-1"], temp_dir.path());
+    assert!(success, "stderr: {}", stderr);
+
+    assert_snapshot!("cli_summary_tools", stdout);
+}
+
+#[test]
+fn test_cli_no_color_uses_ascii_icons() {
+    let _lock = lock_test();
+    let temp_dir = setup_tools_env();
+
+    let (colored, _, _) = run_cli(&["summary", "tools-claude-1"], temp_dir.path());
+    let (plain, stderr, success) =
+        run_cli(&["summary", "tools-claude-1", "--no-color"], temp_dir.path());
+    assert!(success, "stderr: {}", stderr);
+    let output = Command::new(recall_bin())
+        .args(["summary", "tools-claude-1"])
+        .env("RECALL_HOME_OVERRIDE", temp_dir.path())
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    let from_env = String::from_utf8_lossy(&output.stdout).to_string();
+
+    let first = |out: &str| out.lines().next().unwrap_or_default().to_string();
+    assert_snapshot!(
+        "cli_summary_no_color",
+        format!("colored: {}\nplain:   {}", first(&colored), first(&plain))
+    );
+    assert_eq!(plain, from_env);
+}
+
+#[test]
+fn test_cli_summary_json() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let (stdout, stderr, success) =
+        run_cli(&["summary", "test-codex-456", "--json"], temp_dir.path());
+    assert!(success, "stderr: {}", stderr);
+
+    assert_snapshot!("cli_summary_codex_json", stdout);
+}
+
+#[test]
+fn test_cli_summary_all_since() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    // Fixture sessions are from January 2025
+    let (stdout, _stderr, success) = run_cli(
+        &["summary", "--all", "--since", "2025-01-16", "--json"],
+        temp_dir.path(),
+    );
+    assert!(success);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let ids: Vec<_> = json["sessions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["session_id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, ["test-codex-456"]);
+
+    let (stdout, _stderr, success) =
+        run_cli(&["summary", "--all", "--since", "1day"], temp_dir.path());
+    assert!(success);
+    assert_eq!(stdout, "");
+}
+
+/// Write a Claude session with the given (role, text) messages
+fn write_claude_session(home: &std::path::Path, id: &str, day: u32, messages: &[(&str, String)]) {
+    let project = home.join(".claude/projects/context");
+    std::fs::create_dir_all(&project).unwrap();
+    let lines: Vec<String> = messages
+        .iter()
+        .enumerate()
+        .map(|(i, (role, text))| {
+            let content = if *role == "user" {
+                serde_json::json!(text)
+            } else {
+                serde_json::json!([{"type": "text", "text": text}])
+            };
+            serde_json::json!({
+                "cwd": "/test/context",
+                "sessionId": id,
+                "type": role,
+                "message": {"role": role, "content": content},
+                "timestamp": format!("2025-04-{:02}T10:00:{:02}.000Z", day, i),
+            })
+            .to_string()
+        })
+        .collect();
+    std::fs::write(project.join(format!("{}.jsonl", id)), lines.join("\n") + "\n").unwrap();
+}
+
+#[test]
+fn test_cli_context_respects_budget_and_keeps_best_match() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let filler = "unrelated filler text. ".repeat(60);
+
+    write_claude_session(
+        temp_dir.path(),
+        "context-strong",
+        1,
+        &[
+            ("user", "how should we do connection pooling?".to_string()),
+            (
+                "assistant",
+                format!(
+                    "{}DECISION: use pooling with bb8; pooling size 16, pooling timeout 5s.{}",
+                    filler, filler
+                ),
+            ),
+        ],
+    );
+    write_claude_session(
+        temp_dir.path(),
+        "context-weak",
+        2,
+        &[
+            ("user", format!("{}mentions pooling once", filler)),
+            ("assistant", filler.clone()),
+        ],
+    );
+
+    for budget in [600usize, 1500] {
+        let (stdout, stderr, success) = run_cli(
+            &["context", "pooling", "--budget", &budget.to_string()],
+            temp_dir.path(),
+        );
+        assert!(success, "stderr: {}", stderr);
+        assert!(
+            stdout.chars().count() <= budget,
+            "{} chars over budget {}",
+            stdout.chars().count(),
+            budget
+        );
+        assert!(stdout.contains("DECISION: use pooling"), "got: {}", stdout);
+        assert!(stdout.contains("…"), "long messages should be trimmed");
+        assert!(stdout.contains("`claude --resume context-strong` in /test/context"));
+    }
+}
+
+#[test]
+fn test_cli_context_tiny_budget_is_empty() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let (stdout, _stderr, success) =
+        run_cli(&["context", "hello", "--budget", "10"], temp_dir.path());
+    assert!(success);
+    assert_eq!(stdout, "");
+}
+
+/// Create a home with one Claude session that reads, edits, and creates files
+fn setup_tools_env() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let project = temp_dir.path().join(".claude/projects/-work-shop");
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::copy(
+        fixtures_path().join("tools/claude-tools.jsonl"),
+        project.join("tools-claude-1.jsonl"),
+    )
+    .unwrap();
+    temp_dir
+}
+
+#[test]
+fn test_codex_tool_calls() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let sessions = home.join(".codex/sessions/2025/02/03");
+    std::fs::create_dir_all(&sessions).unwrap();
+    std::fs::copy(
+        fixtures_path().join("tools/codex-tools.jsonl"),
+        sessions.join("rollout-2025-02-03T09-00-00-tools-codex-1.jsonl"),
+    )
+    .unwrap();
+
+    let (stdout, stderr, success) = run_cli(&["read", "tools-codex-1"], home);
+    assert!(success, "stderr: {}", stderr);
+    let read: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let calls = &read["messages"][0]["tool_calls"];
+    assert_eq!(calls[0]["input"], "ls tests");
+    assert_eq!(calls[0]["status"], "ok");
+    assert_eq!(calls[1]["name"], "shell");
+    assert_eq!(calls[1]["input"], "cargo test checkout");
+    assert_eq!(calls[1]["exit_code"], 101);
+    assert_eq!(calls[1]["status"], "error");
+    // Never answered
+    assert_eq!(read["messages"][1]["tool_calls"][0]["status"], "pending");
+
+    let (stdout, stderr, success) = run_cli(&["print", "tools-codex-1:errors"], home);
+    assert!(success, "stderr: {}", stderr);
+    assert_snapshot!("cli_print_codex_errors", stdout);
+
+    // Tool output is searched only once it's opted into
+    let results = |args: &[&str]| {
+        let (stdout, stderr, success) = run_cli(args, home);
+        assert!(success, "stderr: {}", stderr);
+        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        json["results"].as_array().unwrap().len()
+    };
+    assert_eq!(results(&["search", "panicked"]), 0);
+    write_config(home, "[index]\ntool_output = true\n");
+    assert_eq!(results(&["--reindex", "search", "panicked"]), 1);
+}
+
+#[test]
+fn test_claude_summary_titles() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let ask = |text: &str| [("user", text.to_string()), ("assistant", "On it.".to_string())];
+    write_claude_session(home, "titled", 3, &ask("the cart total is off by a cent"));
+    write_claude_session(home, "untitled", 4, &ask("  \nbump the lockfile\nand rebuild"));
+    let file = home.join(".claude/projects/context/titled.jsonl");
+    let summary = r#"{"type":"summary","summary":"Checkout rounding fix","leafUuid":"u1"}"#;
+    let lines = std::fs::read_to_string(&file).unwrap();
+    std::fs::write(&file, format!("{}\n{}", summary, lines)).unwrap();
+
+    let (stdout, stderr, success) = run_cli(&["list"], home);
+    assert!(success, "stderr: {}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let titles: Vec<_> = json["sessions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| (s["session_id"].as_str().unwrap(), s["title"].as_str().unwrap()))
+        .collect();
+    assert_eq!(titles, [("untitled", "bump the lockfile"), ("titled", "Checkout rounding fix")]);
+
+    // The title is searched, and shown in the results list
+    let (stdout, _, _) = run_cli(&["search", "rounding"], home);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["results"][0]["session_id"], "titled");
+    std::env::set_var("RECALL_HOME_OVERRIDE", home);
+    std::env::set_var("RECALL_CWD_OVERRIDE", "/test/context");
+    let mut app = recall::App::new(String::new()).unwrap();
+    wait_for_indexing(&mut app, 100);
+    let mut terminal = Terminal::new(TestBackend::new(180, 16)).unwrap();
+    terminal.draw(|f| recall::ui::render(f, &mut app)).unwrap();
+    std::env::remove_var("RECALL_HOME_OVERRIDE");
+    std::env::remove_var("RECALL_CWD_OVERRIDE");
+    assert!(buffer_contains(&terminal, "Checkout rounding fix"));
+    assert!(buffer_contains(&terminal, "bump the lockfile"));
+}
+
+#[test]
+fn test_claude_command_wrappers_filtered() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let user = |text: &str| ("user", text.to_string());
+    let messages = [
+        user("<command-name>/model</command-name>\n<command-args>opus</command-args>"),
+        user("<local-command-stdout>Set model to opus</local-command-stdout>"),
+        user("<command-name>/clear</command-name>\nnow fix the login redirect"),
+        ("assistant", "Fixed.".to_string()),
+        user("why does <local-command-stdout> show up in my transcript?"),
+    ];
+    write_claude_session(home, "commands", 5, &messages);
+
+    let (stdout, stderr, success) = run_cli(&["read", "commands"], home);
+    assert!(success, "stderr: {}", stderr);
+    let read: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let contents: Vec<_> = read["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["content"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        contents,
+        [
+            "now fix the login redirect",
+            "Fixed.",
+            "why does <local-command-stdout> show up in my transcript?",
+        ]
+    );
+    let (stdout, _, _) = run_cli(&["search", "opus"], home);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["results"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn test_claude_tool_results_and_follow_ups() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let entry = |second: u32, role: &str, content: serde_json::Value| {
+        serde_json::json!({
+            "type": role, "sessionId": "results-1", "cwd": "/work/api",
+            "timestamp": format!("2025-03-02T10:00:{:02}.000Z", second),
+            "message": {"role": role, "content": content},
+        })
+        .to_string()
+    };
+    let lines = [
+        entry(0, "user", serde_json::json!("run the tests")),
+        entry(1, "assistant", serde_json::json!([{"type": "text", "text": "Running them."}])),
+        entry(2, "assistant", serde_json::json!([
+            {"type": "tool_use", "id": "t1", "name": "Bash", "input": {"command": "cargo test"}}
+        ])),
+        entry(3, "user", serde_json::json!([
+            {"type": "tool_result", "tool_use_id": "t1", "is_error": true,
+             "content": "test db::migrate ... FAILED"},
+            {"type": "text", "text": "also check the migrations"}
+        ])),
+        entry(4, "user", serde_json::json!([
+            {"type": "tool_result", "tool_use_id": "t0", "content": "unpaired"}
+        ])),
+        entry(5, "assistant", serde_json::json!([{"type": "text", "text": "It's stale."}])),
+    ];
+    let dir = home.join(".claude/projects/-work-api");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("results-1.jsonl"), lines.join("\n")).unwrap();
+
+    let (stdout, stderr, success) = run_cli(&["read", "results-1"], home);
+    assert!(success, "stderr: {}", stderr);
+    let read: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let messages = read["messages"].as_array().unwrap();
+    let contents: Vec<_> = messages.iter().map(|m| m["content"].as_str().unwrap()).collect();
+    assert_eq!(
+        contents,
+        [
+            "run the tests",
+            "Running them.",
+            "also check the migrations",
+            "It's stale."
+        ]
+    );
+    let call = &messages[1]["tool_calls"][0];
+    assert_eq!(call["input"], "cargo test");
+    assert_eq!(call["status"], "error");
+    assert_eq!(call["output"], "test db::migrate ... FAILED");
+}
+
+#[test]
+fn test_claude_subagents_opt_in() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let messages = [
+        ("user", "where is the retry policy configured?".to_string()),
+        ("assistant", "An explore agent will look.".to_string()),
+    ];
+    write_claude_session(home, "parent-1", 7, &messages);
+    // The subagent's transcript carries its parent's session ID
+    let subagent = serde_json::json!({
+        "cwd": "/test/context", "sessionId": "parent-1", "type": "assistant",
+        "isSidechain": true, "agentId": "a1b2c3", "timestamp": "2025-04-07T10:00:05.000Z",
+        "message": {"role": "assistant", "content": [
+            {"type": "text", "text": "Found it in backoff.toml under [retries]."}
+        ]},
+    });
+    let project = home.join(".claude/projects/context");
+    std::fs::write(project.join("agent-a1b2c3.jsonl"), format!("{}\n", subagent)).unwrap();
+
+    let search = |args: &[&str], envs: &[(&str, &str)]| -> Vec<String> {
+        let mut command = Command::new(recall_bin());
+        command.args(args).env("RECALL_HOME_OVERRIDE", home);
+        command.envs(envs.iter().copied());
+        let output = command.output().unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(output.status.success(), "stderr: {}", stderr);
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        json["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["session_id"].as_str().unwrap().to_string())
+            .collect()
+    };
+    assert!(search(&["search", "backoff"], &[]).is_empty());
+    // Included, it's a session of its own
+    assert_eq!(search(&["--include-subagents", "search", "backoff"], &[]), ["agent-a1b2c3"]);
+    assert_eq!(search(&["--include-subagents", "search", "policy"], &[]), ["parent-1"]);
+    // The environment overrides the config, and dropping it again drops the transcript
+    write_config(home, "include_subagents = true\n");
+    let off = [("RECALL_INCLUDE_SUBAGENTS", "0")];
+    assert!(search(&["search", "backoff"], &off).is_empty());
+    assert_eq!(search(&["search", "backoff"], &[]), ["agent-a1b2c3"]);
+}
+
+#[test]
+fn test_claude_resumed_copies_deduplicated() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let messages = [
+        ("user", "why does the invoice total drift?".to_string()),
+        ("assistant", "Rounding per line item.".to_string()),
+    ];
+    write_claude_session(home, "resumed", 5, &messages);
+    // Resuming copied the session into a new file, under the same ID, and carried on
+    let project = home.join(".claude/projects/context");
+    let original = std::fs::read_to_string(project.join("resumed.jsonl")).unwrap();
+    let follow_up = serde_json::json!({
+        "cwd": "/test/context", "sessionId": "resumed", "type": "user",
+        "message": {"role": "user", "content": "round the invoice once, at the end"},
+        "timestamp": "2025-04-06T10:00:00.000Z",
+    });
+    std::fs::write(project.join("fork.jsonl"), format!("{}{}\n", original, follow_up)).unwrap();
+
+    let search = |query: &str| -> Vec<String> {
+        let (stdout, stderr, success) = run_cli(&["search", query], home);
+        assert!(success, "stderr: {}", stderr);
+        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        json["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["session_id"].as_str().unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(search("invoice"), ["resumed"]);
+    assert_eq!(search("once"), ["resumed"]);
+    let (stdout, _, _) = run_cli(&["read", "resumed"], home);
+    let read: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(read["messages"].as_array().unwrap().len(), 3);
+    // The state remembers which copy the other lost to
+    let state = home.join(".cache/recall/state.json");
+    let state = recall::index::IndexState::load(&state).unwrap();
+    let duplicate = &state.indexed_files[&project.join("resumed.jsonl")];
+    assert_eq!(duplicate.duplicate_of.as_deref(), Some(project.join("fork.jsonl").as_path()));
+
+    // With the fuller copy gone, the other one is indexed again
+    std::fs::remove_file(project.join("fork.jsonl")).unwrap();
+    assert_eq!(search("invoice"), ["resumed"]);
+    assert!(search("once").is_empty());
+}
+
+#[test]
+fn test_claude_compacted_context() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let summary = "This session is being continued from a previous conversation.\n\
+        Summary:\n1. The user asked to migrate the ledger tables.\n2. Pending: backfill.";
+    let lines = [
+        serde_json::json!({
+            "type": "user", "sessionId": "compacted-1", "cwd": "/work/api",
+            "timestamp": "2025-03-03T10:00:00.000Z", "isCompactSummary": true,
+            "isVisibleInTranscriptOnly": true,
+            "message": {"role": "user", "content": summary},
+        }),
+        serde_json::json!({
+            "type": "user", "sessionId": "compacted-1", "cwd": "/work/api",
+            "timestamp": "2025-03-03T10:00:05.000Z",
+            "message": {"role": "user", "content": "carry on with the backfill"},
+        }),
+    ];
+    let dir = home.join(".claude/projects/-work-api");
+    std::fs::create_dir_all(&dir).unwrap();
+    let lines: Vec<_> = lines.iter().map(|line| line.to_string()).collect();
+    std::fs::write(dir.join("compacted-1.jsonl"), lines.join("\n")).unwrap();
+
+    let (stdout, stderr, success) = run_cli(&["read", "compacted-1"], home);
+    assert!(success, "stderr: {}", stderr);
+    let read: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let roles: Vec<_> = read["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["role"].as_str().unwrap())
+        .collect();
+    assert_eq!(roles, ["context", "user"]);
+    assert_eq!(read["messages"][0]["content"].as_str().unwrap(), summary);
+
+    // Not searched, and the title comes from what the user asked after it
+    let (stdout, _, _) = run_cli(&["search", "ledger"], home);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["results"].as_array().unwrap().len(), 0);
+    let (stdout, _, _) = run_cli(&["list"], home);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["sessions"][0]["title"], "carry on with the backfill");
+
+    // Shown folded in the preview
+    std::env::set_var("RECALL_HOME_OVERRIDE", home);
+    std::env::set_var("RECALL_CWD_OVERRIDE", "/work/api");
+    let mut app = recall::App::new(String::new()).unwrap();
+    wait_for_indexing(&mut app, 100);
+    let mut terminal = Terminal::new(TestBackend::new(180, 16)).unwrap();
+    terminal.draw(|f| recall::ui::render(f, &mut app)).unwrap();
+    std::env::remove_var("RECALL_HOME_OVERRIDE");
+    std::env::remove_var("RECALL_CWD_OVERRIDE");
+    assert!(buffer_contains(&terminal, "Compacted context"));
+    assert!(buffer_contains(&terminal, "... (4 more lines)"));
+    assert!(!buffer_contains(&terminal, "migrate the ledger"));
+
+    // Searched once opted in
+    write_config(home, "[index]\ncompacted_context = true\n");
+    let (stdout, _, _) = run_cli(&["--reindex", "search", "ledger"], home);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["results"][0]["session_id"], "compacted-1");
+}
+
+#[test]
+fn test_include_thinking() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let lines = [
+        serde_json::json!({
+            "type": "user", "sessionId": "thinking-1", "cwd": "/work/api",
+            "timestamp": "2025-03-01T10:00:00.000Z",
+            "message": {"role": "user", "content": "why is checkout slow?"},
+        }),
+        serde_json::json!({
+            "type": "assistant", "sessionId": "thinking-1", "cwd": "/work/api",
+            "timestamp": "2025-03-01T10:00:05.000Z",
+            "message": {"role": "assistant", "content": [
+                {"type": "thinking", "thinking": "Probably the inventory lookup."},
+                {"type": "text", "text": "Each item is fetched on its own."}
+            ]},
+        }),
+    ];
+    let lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+    let dir = home.join(".claude/projects/-work-api");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("thinking-1.jsonl"), lines.join("\n")).unwrap();
+
+    let search = |args: &[&str]| {
+        let (stdout, stderr, success) = run_cli(args, home);
+        assert!(success, "stderr: {}", stderr);
+        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        (json["results"].as_array().unwrap().len(), stderr)
+    };
+    let (found, stderr) = search(&["search", "inventory"]);
+    assert_eq!(found, 0);
+    assert!(!stderr.contains("--reindex"), "stderr: {}", stderr);
+
+    // Turning it on suggests reindexing, which brings the thinking in
+    let (found, stderr) = search(&["--include-thinking", "search", "inventory"]);
+    assert_eq!(found, 0);
+    assert!(stderr.contains("include_thinking changed"), "stderr: {}", stderr);
+    let (found, stderr) = search(&["--include-thinking", "--reindex", "search", "inventory"]);
+    assert_eq!(found, 1);
+    assert!(!stderr.contains("include_thinking changed"), "stderr: {}", stderr);
+
+    let (stdout, _, _) = run_cli(&["--include-thinking", "read", "thinking-1"], home);
+    let read: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(
+        read["messages"][1]["content"],
+        "[thinking] Probably the inventory lookup.\nEach item is fetched on its own."
+    );
+}
+
+#[test]
+fn test_codex_chunks_joined_and_retries_deduped() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let sessions = home.join(".codex/sessions/2025/02/05");
+    std::fs::create_dir_all(&sessions).unwrap();
+    std::fs::copy(
+        fixtures_path().join("codex/chunks-and-retry.jsonl"),
+        sessions.join("rollout-2025-02-05T11-00-00-chunks-codex-1.jsonl"),
+    )
+    .unwrap();
+
+    let (stdout, stderr, success) = run_cli(&["read", "chunks-codex-1"], home);
+    assert!(success, "stderr: {}", stderr);
+    let read: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let messages: Vec<_> = read["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| (m["role"].as_str().unwrap(), m["content"].as_str().unwrap()))
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            ("user", "add a test for the discount code"),
+            (
+                "assistant",
+                "Adding it to tests/discount.rs.\n\nIt covers expired codes.\n\nThe suite passes."
+            ),
+            ("user", "thanks"),
+        ]
+    );
+}
+
+#[test]
+fn test_codex_reasoning() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let sessions = home.join(".codex/sessions/2025/02/04");
+    std::fs::create_dir_all(&sessions).unwrap();
+    std::fs::copy(
+        fixtures_path().join("thinking/codex-reasoning.jsonl"),
+        sessions.join("rollout-2025-02-04T10-00-00-reasoning-codex-1.jsonl"),
+    )
+    .unwrap();
+    write_config(home, "join_messages = \"off\"\n");
+
+    let contents = |args: &[&str]| {
+        let (stdout, stderr, success) = run_cli(args, home);
+        assert!(success, "stderr: {}", stderr);
+        let read: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let messages = read["messages"].as_array().unwrap().clone();
+        messages.iter().map(|m| m["content"].as_str().unwrap().to_string()).collect::<Vec<_>>()
+    };
+    let reply = "Each line is rounded before summing; round the total instead.";
+    assert_eq!(contents(&["read", "reasoning-codex-1"])[1], reply);
+
+    // The reasoning and the reply are one turn, even with joining off
+    let messages = contents(&["--include-thinking", "read", "reasoning-codex-1"]);
+    assert_eq!(messages.len(), 4);
+    let turn = "[thinking] **Looking for rounding**\n\nThe total sums rounded line prices.\n\
+        [thinking] Rounding each line loses half-cents.\n";
+    assert_eq!(messages[1], format!("{}{}", turn, reply));
+    assert_eq!(messages[3], "Done.");
+}
+
+#[test]
+fn test_factory_git_branch() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let repo = home.join("work/shop");
+    std::fs::create_dir_all(repo.join(".git")).unwrap();
+    std::fs::write(repo.join(".git/HEAD"), "ref: refs/heads/checkout-v2\n").unwrap();
+    let sessions = home.join(".factory/sessions/-work-shop");
+    std::fs::create_dir_all(&sessions).unwrap();
+    let cwd = repo.to_string_lossy();
+    let session = |id: &str, branch: Option<&str>| {
+        let mut start = serde_json::json!({"type": "session_start", "id": id, "cwd": cwd});
+        if let Some(branch) = branch {
+            start["gitBranch"] = branch.into();
+        }
+        let message = serde_json::json!({
+            "type": "message", "id": "m1", "timestamp": "2025-02-06T09:00:00.000Z",
+            "message": {"role": "user", "content": [{"type": "text", "text": "hi"}]},
+        });
+        let file = sessions.join(format!("{}.jsonl", id));
+        std::fs::write(file, format!("{}\n{}\n", start, message)).unwrap();
+    };
+    session("recorded", Some("main"));
+    session("from-head", None);
+
+    // Branches are indexed, so they're in the list
+    let (stdout, stderr, success) = run_cli(&["list"], home);
+    assert!(success, "stderr: {}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let mut branches: Vec<_> = json["sessions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| (s["session_id"].as_str().unwrap(), s["git_branch"].as_str().unwrap()))
+        .collect();
+    branches.sort();
+    assert_eq!(branches, [("from-head", "checkout-v2"), ("recorded", "main")]);
+}
+
+#[test]
+fn test_factory_tool_calls() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let sessions = home.join(".factory/sessions/-work-shop");
+    std::fs::create_dir_all(&sessions).unwrap();
+    std::fs::copy(
+        fixtures_path().join("tools/factory-tools.jsonl"),
+        sessions.join("tools-factory-1.jsonl"),
+    )
+    .unwrap();
+
+    let (stdout, stderr, success) = run_cli(&["read", "tools-factory-1"], home);
+    assert!(success, "stderr: {}", stderr);
+    let read: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(read["messages"].as_array().unwrap().len(), 2);
+    let calls = &read["messages"][1]["tool_calls"];
+    assert_eq!(calls[0]["name"], "Execute");
+    assert_eq!(calls[0]["input"], "npm test -- upload --repeat 5");
+    assert_eq!(calls[0]["status"], "error");
+    assert_eq!(calls[1]["name"], "Read");
+    assert_eq!(calls[1]["status"], "ok");
+    assert_eq!(calls[1]["output"], "it('retries on timeout', async () => {");
+
+    let (stdout, stderr, success) = run_cli(&["print", "tools-factory-1:errors"], home);
+    assert!(success, "stderr: {}", stderr);
+    assert_snapshot!("cli_print_factory_errors", stdout);
+}
+
+#[test]
+fn test_opencode_project_branch() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let storage = home.join(".local/share/opencode/storage");
+    copy_dir_recursive(&fixtures_path().join("opencode-project"), &storage);
+
+    // The session and its messages don't say where they ran: the project does
+    let (stdout, stderr, success) = run_cli(&["list"], home);
+    assert!(success, "stderr: {}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let session = &json["sessions"][0];
+    assert_eq!(session["session_id"], "ses_branch1");
+    assert_eq!(session["cwd"], "/work/shop");
+    assert_eq!(session["git_branch"], "lint-fix");
+
+    // Without its project record, the session is still read
+    std::fs::remove_file(storage.join("project/prj_shop.json")).unwrap();
+    let (stdout, stderr, success) = run_cli(&["read", "ses_branch1"], home);
+    assert!(success, "stderr: {}", stderr);
+    let read: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(read["cwd"], ".");
+    assert!(read["git_branch"].is_null());
+}
+
+#[test]
+fn test_opencode_tool_parts() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    copy_dir_recursive(
+        &fixtures_path().join("tools/opencode"),
+        &home.join(".local/share/opencode/storage"),
+    );
+
+    let (stdout, stderr, success) = run_cli(&["read", "ses_tools1"], home);
+    assert!(success, "stderr: {}", stderr);
+    let read: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let messages = read["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 2);
+    // Step bookkeeping and tool output stay out of the text
+    assert!(!messages[1]["content"].as_str().unwrap().contains("prefer-const"));
+    // In the order they ran, the calls of the step without text included
+    let calls: Vec<_> = messages[1]["tool_calls"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| (c["name"].as_str().unwrap(), c["status"].as_str().unwrap()))
+        .collect();
+    assert_eq!(calls, [("bash", "error"), ("read", "error"), ("edit", "ok")]);
+    assert_eq!(messages[1]["tool_calls"][0]["input"], "npm run lint");
+    assert_eq!(messages[1]["tool_calls"][0]["exit_code"], 1);
+    assert_eq!(messages[1]["tool_calls"][2]["input"], "/work/shop/src/cart.ts");
+
+    // Selectors pick messages out of the session, as for print
+    let (stdout, stderr, success) = run_cli(&["read", "ses_tools1:errors"], home);
+    assert!(success, "stderr: {}", stderr);
+    let read: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let errors = read["messages"].as_array().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["content"], messages[1]["content"]);
+    let (stdout, _, success) = run_cli(&["read", "ses_tools1:0"], home);
+    assert!(success);
+    let read: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(read["messages"][0]["content"], "why is the lint job red?");
+    assert_eq!(read["messages"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_cli_files_json() {
+    let _lock = lock_test();
+    let temp_dir = setup_tools_env();
+
+    let (stdout, stderr, success) =
+        run_cli(&["files", "tools-claude-1", "--json"], temp_dir.path());
+    assert!(success, "stderr: {}", stderr);
+
+    assert_snapshot!("cli_files_json", stdout);
+}
+
+#[test]
+fn test_cli_files_edited_only() {
+    let _lock = lock_test();
+    let temp_dir = setup_tools_env();
+
+    let (stdout, _stderr, success) =
+        run_cli(&["files", "tools-claude-1", "--edited-only"], temp_dir.path());
+    assert!(success);
+
+    assert_eq!(
+        stdout,
+        "/work/shop/src/cart.rs  [edit]  messages 3\n/work/shop/CHANGELOG.md  [create]  messages 3\n"
+    );
+}
+
+#[test]
+fn test_cli_files_reverse_lookup() {
+    let _lock = lock_test();
+    let temp_dir = setup_tools_env();
+
+    let (stdout, _stderr, success) = run_cli(
+        &["files", "--path", "/work/shop/src/lib.rs", "--json"],
+        temp_dir.path(),
+    );
+    assert!(success);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["path"], "/work/shop/src/lib.rs");
+    assert_eq!(json["sessions"][0]["session_id"], "tools-claude-1");
+
+    // lib.rs was only read
+    let (stdout, _stderr, success) = run_cli(
+        &["files", "--path", "/work/shop/src/lib.rs", "--edited-only", "--json"],
+        temp_dir.path(),
+    );
+    assert!(success);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["sessions"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn test_cli_files_requires_session_or_path() {
+    let _lock = lock_test();
+    let temp_dir = setup_tools_env();
+
+    let (_stdout, _stderr, success) = run_cli(&["files"], temp_dir.path());
+    assert!(!success);
+}
+
+/// Create a home with `count` Claude sessions, each mentioning "pagination"
+fn setup_paging_env(count: usize) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let project = temp_dir.path().join(".claude/projects/paging");
+    std::fs::create_dir_all(&project).unwrap();
+
+    for i in 0..count {
+        let id = format!("paging-{:02}", i);
+        let ts = format!("2025-02-{:02}T10:00:00.000Z", i + 1);
+        let line = serde_json::json!({
+            "cwd": "/test/paging",
+            "sessionId": id,
+            "type": "user",
+            "message": {"role": "user", "content": format!("pagination test number {}", i)},
+            "timestamp": ts,
+        });
+        std::fs::write(project.join(format!("{}.jsonl", id)), format!("{}\n", line)).unwrap();
+    }
+
+    temp_dir
+}
+
+/// Page through `args` with --limit 5, returning every session ID seen and each page's JSON
+fn collect_pages(args: &[&str], key: &str, home: &std::path::Path) -> (Vec<String>, Vec<serde_json::Value>) {
+    let mut ids = Vec::new();
+    let mut pages = Vec::new();
+    for offset in [0, 5, 10] {
+        let offset = offset.to_string();
+        let mut full_args = args.to_vec();
+        full_args.extend(["--limit", "5", "--offset", &offset]);
+        let (stdout, _stderr, success) = run_cli(&full_args, home);
+        assert!(success);
+
+        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        for item in json[key].as_array().unwrap() {
+            ids.push(item["session_id"].as_str().unwrap().to_string());
+        }
+        pages.push(json);
+    }
+    (ids, pages)
+}
+
+#[test]
+fn test_cli_list_pagination() {
+    let _lock = lock_test();
+    let temp_dir = setup_paging_env(12);
+
+    let (ids, pages) = collect_pages(&["list"], "sessions", temp_dir.path());
+
+    let unique: std::collections::HashSet<_> = ids.iter().collect();
+    assert_eq!(ids.len(), 12, "pages should cover every session: {:?}", ids);
+    assert_eq!(unique.len(), 12, "pages should not overlap: {:?}", ids);
+
+    // Most recent first across pages
+    assert_eq!(ids.first().unwrap(), "paging-11");
+    assert_eq!(ids.last().unwrap(), "paging-00");
+
+    assert_eq!(pages[0]["offset"], 0);
+    assert_eq!(pages[0]["limit"], 5);
+    assert!(pages[0]["total_estimate"].as_u64().unwrap() > 5);
+    assert_eq!(pages[2]["total_estimate"], 12);
+    assert_eq!(pages[2]["sessions"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_cli_search_pagination() {
+    let _lock = lock_test();
+    let temp_dir = setup_paging_env(12);
+
+    let (ids, pages) = collect_pages(&["search", "pagination"], "results", temp_dir.path());
+
+    let unique: std::collections::HashSet<_> = ids.iter().collect();
+    assert_eq!(ids.len(), 12, "pages should cover every session: {:?}", ids);
+    assert_eq!(unique.len(), 12, "pages should not overlap: {:?}", ids);
+
+    assert!(pages[1]["total_estimate"].as_u64().unwrap() > 10);
+    assert_eq!(pages[2]["total_estimate"], 12);
+    assert_eq!(pages[2]["offset"], 10);
+}
+
+#[test]
+fn test_cli_list_offset_past_end() {
+    let _lock = lock_test();
+    let temp_dir = setup_paging_env(3);
+
+    let (stdout, _stderr, success) =
+        run_cli(&["list", "--offset", "10"], temp_dir.path());
+    assert!(success);
+
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert!(json["sessions"].as_array().unwrap().is_empty());
+    assert_eq!(json["total_estimate"], 3);
+}
+
+// =============================================================================
+// Serve Tests
+// =============================================================================
+
+/// A `recall serve` child process bound to an ephemeral port
+#[cfg(feature = "serve")]
+struct ServeProcess {
+    child: std::process::Child,
+    addr: String,
+}
+
+#[cfg(feature = "serve")]
+impl ServeProcess {
+    fn start(home_override: &std::path::Path, extra_args: &[&str]) -> Self {
+        use std::io::BufRead;
+
+        let mut child = Command::new(recall_bin())
+            .args(["serve", "--addr", "127.0.0.1:0"])
+            .args(extra_args)
+            .env("RECALL_HOME_OVERRIDE", home_override)
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .expect("Failed to run recall serve");
+
+        // Indexing progress goes to stderr too; wait for the listening line
+        let stderr = child.stderr.take().unwrap();
+        let mut lines = std::io::BufReader::new(stderr).lines();
+        let addr = lines
+            .by_ref()
+            .map_while(Result::ok)
+            .find_map(|line| line.strip_prefix("Listening on http://").map(str::to_string))
+            .expect("recall serve should print its address");
+        // Keep draining it, so the server never blocks on a full pipe or writes to a closed one
+        std::thread::spawn(move || lines.for_each(drop));
+
+        Self { child, addr }
+    }
+
+    /// Send a GET request, returning (status, headers, body)
+    fn get(&self, path: &str) -> (u16, String, String) {
+        self.get_with_host(path, &self.addr)
+    }
+
+    /// Send a GET request with this Host header
+    fn get_with_host(&self, path: &str, host: &str) -> (u16, String, String) {
+        use std::io::{Read, Write};
+
+        let mut stream = std::net::TcpStream::connect(&self.addr).unwrap();
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+            path, host
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        (status, head.to_string(), body.to_string())
+    }
+}
+
+#[cfg(feature = "serve")]
+impl Drop for ServeProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+#[cfg(feature = "serve")]
+fn test_serve_search_recent_and_read() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let server = ServeProcess::start(temp_dir.path(), &[]);
+
+    let (status, head, body) = server.get("/search?q=hello&limit=10");
+    assert_eq!(status, 200);
+    assert!(!head.contains("Access-Control-Allow-Origin"));
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["query"], "hello");
+    assert!(json["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|r| r["session_id"] == "test-claude-123"));
+
+    let (status, _, body) = server.get("/recent?source=codex");
+    assert_eq!(status, 200);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    for session in json["sessions"].as_array().unwrap() {
+        assert_eq!(session["source"], "codex");
+    }
+
+    let (status, _, body) = server.get("/sessions/test-claude-123");
+    assert_eq!(status, 200);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(json["session_id"], "test-claude-123");
+}
+
+#[test]
+#[cfg(feature = "serve")]
+fn test_serve_errors_are_json() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let server = ServeProcess::start(temp_dir.path(), &[]);
+
+    let (status, _, body) = server.get("/search");
+    assert_eq!(status, 400);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert!(json["error"].is_string());
+
+    let (status, _, body) = server.get("/sessions/nonexistent-id");
+    assert_eq!(status, 404);
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert!(json["error"].is_string());
+
+    let (status, _, _) = server.get("/search?q=hello&source=bogus");
+    assert_eq!(status, 400);
+}
+
+#[test]
+#[cfg(feature = "serve")]
+fn test_serve_allow_origin() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let server = ServeProcess::start(temp_dir.path(), &["--allow-origin", "http://localhost:3000"]);
+
+    let (status, head, _) = server.get("/recent");
+    assert_eq!(status, 200);
+    assert!(head.contains("Access-Control-Allow-Origin: http://localhost:3000"));
+}
+
+#[test]
+#[cfg(feature = "serve")]
+fn test_serve_rejects_foreign_host() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let server = ServeProcess::start(temp_dir.path(), &[]);
+    let port = server.addr.rsplit_once(':').unwrap().1.to_string();
+
+    // A rebound hostname reaches the same socket but names itself in Host
+    let (status, _, body) =
+        server.get_with_host("/recent", &format!("attacker.example:{}", port));
+    assert_eq!(status, 403);
+    assert!(!body.contains("test-claude-123"));
+
+    let (status, _, _) = server.get_with_host("/recent", "localhost:1");
+    assert_eq!(status, 403);
+    let (status, _, _) = server.get_with_host("/recent", &format!("localhost:{}", port));
+    assert_eq!(status, 200);
+}
+
+#[test]
+#[cfg(feature = "serve")]
+fn test_serve_rejects_non_loopback_addr() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let (_stdout, stderr, success) =
+        run_cli(&["serve", "--addr", "0.0.0.0:0"], temp_dir.path());
+
+    assert!(!success);
+    assert!(stderr.contains("localhost"));
+}
+
+/// Write a one-exchange Claude session in `cwd`, started on the given April day
+fn write_session_in(home: &std::path::Path, id: &str, cwd: &str, day: u32) {
+    let project = home.join(".claude/projects/last");
+    std::fs::create_dir_all(&project).unwrap();
+    let lines: Vec<String> = [("user", "resume me"), ("assistant", "ok")]
+        .iter()
+        .enumerate()
+        .map(|(i, (role, text))| {
+            serde_json::json!({
+                "cwd": cwd,
+                "sessionId": id,
+                "type": role,
+                "message": {"role": role, "content": text},
+                "timestamp": format!("2025-04-{:02}T10:00:{:02}.000Z", day, i),
+            })
+            .to_string()
+        })
+        .collect();
+    std::fs::write(project.join(format!("{}.jsonl", id)), lines.join("\n") + "\n").unwrap();
+}
+
+/// Run `recall` from `cwd`, with resume commands that just echo what they'd resume
+fn run_last(args: &[&str], home: &std::path::Path, cwd: &str) -> (String, String, bool) {
+    let output = Command::new(recall_bin())
+        .args(args)
+        .env("RECALL_HOME_OVERRIDE", home)
+        .env("RECALL_CWD_OVERRIDE", cwd)
+        .env("RECALL_CLAUDE_CMD", "echo claude {id}")
+        .env("RECALL_CODEX_CMD", "echo codex {id}")
+        .output()
+        .expect("Failed to run recall");
+
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    (stdout, stderr, output.status.success())
+}
+
+#[test]
+fn test_cli_last_resumes_most_recent_in_directory() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    write_session_in(temp_dir.path(), "last-old", "/work/app", 1);
+    write_session_in(temp_dir.path(), "last-new", "/work/app/src", 3);
+    write_session_in(temp_dir.path(), "last-other", "/work/app2", 5);
+
+    let (stdout, stderr, success) = run_last(&["--last"], temp_dir.path(), "/work/app");
+    assert!(success, "stderr: {}", stderr);
+    assert_eq!(stdout.trim(), "claude last-new");
+    assert!(stderr.contains("Resuming last-new"));
+
+    let (stdout, _, success) = run_last(&["last", "--pick", "2"], temp_dir.path(), "/work/app");
+    assert!(success);
+    assert_eq!(stdout.trim(), "claude last-old");
+
+    let (stdout, _, success) = run_last(&["--last"], temp_dir.path(), "/projects/webapp");
+    assert!(success);
+    assert_eq!(stdout.trim(), "codex test-codex-456");
+}
+
+#[test]
+fn test_cli_last_source_filter() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    write_session_in(temp_dir.path(), "last-claude", "/projects/webapp", 28);
+
+    let (stdout, _, success) = run_last(&["--last"], temp_dir.path(), "/projects/webapp");
+    assert!(success);
+    assert_eq!(stdout.trim(), "claude last-claude");
+
+    let (stdout, _, success) =
+        run_last(&["--last", "--source", "codex"], temp_dir.path(), "/projects/webapp");
+    assert!(success);
+    assert_eq!(stdout.trim(), "codex test-codex-456");
+}
+
+#[test]
+fn test_cli_last_not_found() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    write_session_in(temp_dir.path(), "last-only", "/work/app", 1);
+
+    let (stdout, stderr, success) = run_last(&["--last"], temp_dir.path(), "/nowhere");
+    assert!(!success);
+    assert!(stdout.is_empty());
+    assert!(stderr.contains("Run `recall`"));
+
+    let (_, _, success) = run_last(&["last", "--pick", "2"], temp_dir.path(), "/work/app");
+    assert!(!success);
+}
+
+/// Base fixtures plus a Claude and a Codex session that record token usage
+fn setup_usage_env() -> TempDir {
+    let temp_dir = setup_test_env();
+    let claude = temp_dir.path().join(".claude/projects/-work-shop");
+    let codex = temp_dir.path().join(".codex/sessions/2025/03/11");
+    std::fs::create_dir_all(&claude).unwrap();
+    std::fs::create_dir_all(&codex).unwrap();
+    std::fs::copy(
+        fixtures_path().join("usage/claude-usage.jsonl"),
+        claude.join("usage-claude-1.jsonl"),
+    )
+    .unwrap();
+    std::fs::copy(
+        fixtures_path().join("usage/codex-usage.jsonl"),
+        codex.join("rollout-usage-codex-1.jsonl"),
+    )
+    .unwrap();
+    temp_dir
+}
+
+#[test]
+fn test_cli_cost_by_source_json() {
+    let _lock = lock_test();
+    let temp_dir = setup_usage_env();
+
+    let (stdout, stderr, success) =
+        run_cli(&["cost", "--by", "source", "--format", "json"], temp_dir.path());
+    assert!(success, "stderr: {}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+
+    let group = |key: &str| {
+        json["groups"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|g| g["key"] == key)
+            .unwrap()
+            .clone()
+    };
+    let tokens = |g: &serde_json::Value| {
+        [
+            g["input_tokens"].as_u64().unwrap(),
+            g["output_tokens"].as_u64().unwrap(),
+            g["cache_read_tokens"].as_u64().unwrap(),
+            g["cache_write_tokens"].as_u64().unwrap(),
+        ]
+    };
+
+    // Repeated usage on a multi-block response is counted once
+    let claude = group("claude");
+    assert_eq!(tokens(&claude), [1500, 500, 20000, 5000]);
+    assert_eq!((claude["sessions"].as_u64(), claude["priced_sessions"].as_u64()), (Some(2), Some(1)));
+    let cost = claude["cost_usd"].as_f64().unwrap();
+    assert!((cost - 0.03675).abs() < 1e-9, "claude cost {}", cost);
+
+    // Codex reports running totals; cached input is split out of input
+    let codex = group("codex");
+    assert_eq!(tokens(&codex), [13000, 3000, 12000, 0]);
+    let cost = codex["cost_usd"].as_f64().unwrap();
+    assert!((cost - 0.04775).abs() < 1e-9, "codex cost {}", cost);
+
+    assert_eq!(json["total"]["sessions"], 4);
+    assert_eq!(json["total"]["priced_sessions"], 2);
+}
+
+#[test]
+fn test_cli_cost_config_pricing() {
+    let _lock = lock_test();
+    let temp_dir = setup_usage_env();
+    let pricing = "\"gpt-5\" = { input = 2.5, output = 20.0, cache_read = 0.25 }";
+    write_config(temp_dir.path(), &format!("[pricing]\n{}\n", pricing));
+
+    let (stdout, stderr, success) =
+        run_cli(&["cost", "--by", "source", "--format", "json"], temp_dir.path());
+    assert!(success, "stderr: {}", stderr);
+    assert!(!stderr.contains("unknown config key"), "stderr: {}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let group = |key: &str| {
+        json["groups"].as_array().unwrap().iter().find(|g| g["key"] == key).unwrap().clone()
+    };
+
+    // Twice the built-in gpt-5 price, and Claude's sessions keep theirs
+    let cost = group("codex")["cost_usd"].as_f64().unwrap();
+    assert!((cost - 0.0955).abs() < 1e-9, "codex cost {}", cost);
+    let cost = group("claude")["cost_usd"].as_f64().unwrap();
+    assert!((cost - 0.03675).abs() < 1e-9, "claude cost {}", cost);
+}
+
+#[test]
+fn test_cli_cost_table_marks_missing_usage() {
+    let _lock = lock_test();
+    let temp_dir = setup_usage_env();
+
+    let (stdout, _, success) = run_cli(&["cost"], temp_dir.path());
+    assert!(success);
+    assert_snapshot!("cli_cost_by_project", stdout);
+}
+
+#[test]
+fn test_cli_cost_since_and_csv() {
+    let _lock = lock_test();
+    let temp_dir = setup_usage_env();
+
+    let (stdout, _, success) = run_cli(
+        &["cost", "--since", "2025-03-11", "--by", "day", "--format", "csv"],
+        temp_dir.path(),
+    );
+    assert!(success);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "key,sessions,priced_sessions,input_tokens,output_tokens,cache_read_tokens,cache_write_tokens,cost_usd");
+    assert_eq!(lines[1], "2025-03-11,1,1,13000,3000,12000,0,0.0478");
+    assert_eq!(lines[2], "total,1,1,13000,3000,12000,0,0.0478");
+    assert_eq!(lines.len(), 3);
+}
+
+#[test]
+fn test_cli_list_null_delimited() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    write_session_in(temp_dir.path(), "null-old", "/work/my shop", 1);
+    write_session_in(temp_dir.path(), "null-new", "/work/my shop", 2);
+
+    let output = Command::new(recall_bin())
+        .args(["list", "-0", "--fields", "id,project,cwd"])
+        .env("RECALL_HOME_OVERRIDE", temp_dir.path())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        output.stdout,
+        b"null-new\tmy shop\t/work/my shop\0null-old\tmy shop\t/work/my shop\0"
+    );
+}
+
+#[test]
+fn test_cli_files_null_delimited() {
+    let _lock = lock_test();
+    let temp_dir = setup_tools_env();
+
+    let output = Command::new(recall_bin())
+        .args(["files", "tools-claude-1", "--null"])
+        .env("RECALL_HOME_OVERRIDE", temp_dir.path())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "/work/shop/src/cart.rs\tread,edit\t1,3\0\
+         /work/shop/src/lib.rs\tread\t1\0\
+         /work/shop/CHANGELOG.md\tcreate\t3\0"
+    );
+
+    let (_, stderr, success) =
+        run_cli(&["files", "tools-claude-1", "--null", "--json"], temp_dir.path());
+    assert!(!success);
+    assert!(stderr.contains("cannot be used with"));
+}
+
+/// A running `recall watch`, with its event lines collected on a background thread
+struct WatchProcess {
+    child: std::process::Child,
+    events: std::sync::mpsc::Receiver<serde_json::Value>,
+}
+
+impl WatchProcess {
+    fn start(home_override: &std::path::Path, extra_args: &[&str]) -> Self {
+        use std::io::BufRead;
+
+        let mut child = Command::new(recall_bin())
+            .args(["watch", "--interval-ms", "50"])
+            .args(extra_args)
+            .env("RECALL_HOME_OVERRIDE", home_override)
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .expect("Failed to run recall watch");
+
+        // Existing sessions are taken stock of before this line
+        let stderr = child.stderr.take().unwrap();
+        std::io::BufReader::new(stderr)
+            .lines()
+            .map_while(Result::ok)
+            .find(|line| line.starts_with("Watching"))
+            .expect("recall watch should say it's watching");
+
+        let stdout = child.stdout.take().unwrap();
+        let (tx, events) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for line in std::io::BufReader::new(stdout).lines().map_while(Result::ok) {
+                let _ = tx.send(serde_json::from_str(&line).unwrap());
+            }
+        });
+
+        Self { child, events }
+    }
+
+    fn next_event(&self) -> serde_json::Value {
+        self.events
+            .recv_timeout(std::time::Duration::from_secs(10))
+            .expect("recall watch should emit an event")
+    }
+}
+
+impl Drop for WatchProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn test_cli_watch_streams_events() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let watch = WatchProcess::start(temp_dir.path(), &["--include-messages"]);
+
+    write_session_in(temp_dir.path(), "watch-1", "/work/app", 1);
+    let created = watch.next_event();
+    assert_eq!(created["event"], "session_created");
+    assert_eq!(created["session_id"], "watch-1");
+    assert_eq!(created["message_count"], 2);
+    assert_eq!(created["messages"].as_array().unwrap().len(), 2);
+
+    // Appending a message sends only that message
+    let file = temp_dir.path().join(".claude/projects/last/watch-1.jsonl");
+    let line = serde_json::json!({
+        "cwd": "/work/app",
+        "sessionId": "watch-1",
+        "type": "user",
+        "message": {"role": "user", "content": "one more thing"},
+        "timestamp": "2025-04-01T10:05:00.000Z",
+    });
+    let mut contents = std::fs::read_to_string(&file).unwrap();
+    contents.push_str(&format!("{}\n", line));
+    std::fs::write(&file, contents).unwrap();
+
+    let updated = watch.next_event();
+    assert_eq!(updated["event"], "session_updated");
+    assert_eq!(updated["message_count"], 3);
+    assert_eq!(updated["first_message_index"], 2);
+    let messages = updated["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0]["content"], "one more thing");
+
+    std::fs::remove_file(&file).unwrap();
+    let deleted = watch.next_event();
+    assert_eq!(deleted["event"], "session_deleted");
+    assert_eq!(deleted["session_id"], "watch-1");
+    assert!(deleted.get("messages").is_none());
+}
+
+#[test]
+fn test_cli_watch_filters() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let watch = WatchProcess::start(temp_dir.path(), &["--cwd", "/work/wanted"]);
+
+    write_session_in(temp_dir.path(), "watch-other", "/work/other", 1);
+    write_session_in(temp_dir.path(), "watch-wanted", "/work/wanted", 1);
+
+    let event = watch.next_event();
+    assert_eq!(event["session_id"], "watch-wanted");
+    assert!(event.get("messages").is_none());
+    assert!(watch.events.recv_timeout(std::time::Duration::from_millis(300)).is_err());
+}
+
+fn setup_diff_env() -> TempDir {
+    let temp_dir = setup_test_env();
+    let shared = [
+        ("user", "Add a --verbose flag".to_string()),
+        ("assistant", "Added the flag to the parser.\nWired it into logging.".to_string()),
+        ("user", "Now write a test".to_string()),
+    ];
+    write_claude_session(temp_dir.path(), "diff-base", 1, &shared);
+
+    let mut fork = shared.to_vec();
+    fork[1].1 = "Added the flag to the parser.\nWired it into tracing.".to_string();
+    fork.push(("assistant", "Test added in cli.rs".to_string()));
+    write_claude_session(temp_dir.path(), "diff-fork", 2, &fork);
+    temp_dir
+}
+
+#[test]
+fn test_cli_diff_text() {
+    let _lock = lock_test();
+    let temp_dir = setup_diff_env();
+
+    let (stdout, stderr, success) = run_cli(&["diff", "diff-base", "diff-fork"], temp_dir.path());
+    assert!(success, "diff failed: {}", stderr);
+    assert_snapshot!("cli_diff_text", stdout);
+}
+
+#[test]
+fn test_cli_diff_json() {
+    let _lock = lock_test();
+    let temp_dir = setup_diff_env();
+
+    let (stdout, stderr, success) = run_cli(
+        &["diff", "diff-base", "diff-fork", "--format", "json"],
+        temp_dir.path(),
+    );
+    assert!(success, "diff failed: {}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["left"]["session_id"], "diff-base");
+    assert_eq!(json["right"]["message_count"], 4);
+    assert_eq!(json["summary"]["added"], 1);
+    assert_eq!(json["summary"]["changed"], 1);
+    assert_eq!(json["summary"]["unchanged"], 2);
+
+    let kinds: Vec<&str> = json["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["kind"].as_str().unwrap())
+        .collect();
+    assert_eq!(kinds, ["same", "changed", "same", "added"]);
+    assert_eq!(json["entries"][1]["lines"][1]["op"], "-");
+
+    let (_, stderr, success) = run_cli(&["diff", "diff-base", "nope"], temp_dir.path());
+    assert!(!success);
+    assert!(stderr.contains("Session not found: nope"));
+}
+
+fn write_config(home: &std::path::Path, text: &str) -> std::path::PathBuf {
+    let dir = home.join(".config/recall");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("config.toml");
+    std::fs::write(&path, text).unwrap();
+    path
+}
+
+#[test]
+fn test_config_limits_tui_results() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    write_config(temp_dir.path(), "[search]\nmax_results = 1\n");
+
+    let options = recall::AppOptions { everywhere: true, ..Default::default() };
+    let app = app_with_options(temp_dir.path(), options);
+
+    assert_eq!(result_ids(&app), ["test-codex-456"]);
+}
+
+#[test]
+fn test_config_redacts_secrets() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    let secret = "sk-proj-0123456789abcdefghijkl";
+    let messages = [
+        ("user", format!("deploy with OPENAI_API_KEY={}", secret)),
+        ("assistant", "Deployed using postgres://app:hunter2@db/shop".to_string()),
+    ];
+    write_claude_session(home, "secretive", 2, &messages);
+    write_config(home, "redact = true\n");
+
+    let (stdout, _, success) = run_cli(&["search", "deploy"], home);
+    assert!(success);
+    assert!(!stdout.contains(secret) && !stdout.contains("hunter2"), "{}", stdout);
+    assert!(stdout.contains("OPENAI_API_KEY=…REDACTED…"), "{}", stdout);
+    assert!(stdout.contains("postgres://app:…REDACTED…@db/shop"), "{}", stdout);
+    let (stdout, _, _) = run_cli(&["search", "0123456789abcdefghijkl"], home);
+    assert!(!stdout.contains("secretive"), "the secret was indexed: {}", stdout);
+
+    // The session file is untouched, and turning redaction off reindexes
+    let file = home.join(".claude/projects/context/secretive.jsonl");
+    assert!(std::fs::read_to_string(file).unwrap().contains(secret));
+    write_config(home, "redact = false\n");
+    let (stdout, _, _) = run_cli(&["search", "deploy"], home);
+    assert!(stdout.contains(secret), "{}", stdout);
+}
+
+#[test]
+fn test_aider_history_in_known_project() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+
+    // A project another agent ran in, with an Aider history in its root
+    let project = home.join("code/webapp");
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::write(
+        project.join(".aider.chat.history.md"),
+        "\n# aider chat started at 2025-05-01 10:00:00\n\n#### make the navbar sticky\n\n\
+         Add `position: sticky` to `.navbar`.\n\n> Applied edit to style.css\n",
+    )
+    .unwrap();
+    write_session_in(home, "claude-webapp", &project.display().to_string(), 3);
+
+    let (stdout, stderr, success) = run_cli(&["search", "navbar", "--source", "aider"], home);
+    assert!(success, "{}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let result = &json["results"][0];
+    assert_eq!(result["source"], "aider");
+    assert_eq!(result["cwd"], project.display().to_string());
+    assert_eq!(result["resume_command"], "aider --restore-chat-history");
+
+    let id = result["session_id"].as_str().unwrap();
+    let (stdout, _, success) = run_cli(&["read", id], home);
+    assert!(success);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let messages = json["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0]["content"], "make the navbar sticky");
+    assert_eq!(messages[1]["content"], "Add `position: sticky` to `.navbar`.");
+}
+
+#[test]
+fn test_crush_sessions_in_projects() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    let id = "b7c2e9d4-1f30-4a8e-9c55-3d2a6f1e0b87";
+    let search = |home| {
+        let args = ["search", "paginate", "--source", "crush", "--limit", "10"];
+        let (stdout, stderr, success) = run_cli(&args, home);
+        assert!(success, "{}", stderr);
+        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        json["results"].as_array().unwrap().clone()
+    };
+
+    // A project another agent ran in
+    let known = home.join("code/orders");
+    copy_dir_recursive(&fixtures_path().join("crush/.crush"), &known.join(".crush"));
+    write_session_in(home, "claude-orders", &known.display().to_string(), 3);
+    let results = search(home);
+    // The session Crush started to title the other isn't one of the user's
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["session_id"], id);
+    assert_eq!(results[0]["cwd"], known.display().to_string());
+    assert_eq!(results[0]["resume_command"], format!("crush --resume {}", id));
+
+    let database = known.join(".crush/crush.db");
+    let session = recall::parser::parse_session_file(&database.join(id)).unwrap();
+    let contents: Vec<_> = session.messages.iter().map(|m| m.content.as_str()).collect();
+    assert_eq!(contents[0], "Paginate the orders endpoint with cursors");
+    assert!(contents[1].starts_with("I'll start with the handler."));
+    assert!(contents[1].ends_with("returns next_cursor."));
+    assert_eq!(contents.len(), 2);
+
+    // Moved to a project only listed in `extra_roots`
+    let listed = home.join("lab/orders");
+    copy_dir_recursive(&known.join(".crush"), &listed.join(".crush"));
+    std::fs::remove_dir_all(known.join(".crush")).unwrap();
+    let config_dir = home.join(".config/recall");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    let config = format!("extra_roots = [{:?}]\n", listed.display().to_string());
+    std::fs::write(config_dir.join("config.toml"), config).unwrap();
+    let results = search(home);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["cwd"], listed.display().to_string());
+}
+
+#[test]
+fn test_gemini_checkpoint() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    copy_dir_recursive(&fixtures_path().join(".gemini"), &home.join(".gemini"));
+
+    let args = ["search", "token refresh", "--source", "gemini"];
+    let (stdout, stderr, success) = run_cli(&args, home);
+    assert!(success, "{}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let result = &json["results"][0];
+    assert_eq!(result["session_id"], "login-hang");
+    assert_eq!(result["cwd"], "/test/gemini-app");
+    assert_eq!(result["resume_command"], "gemini --resume login-hang");
+
+    // The checkpoint's project is the folder scope, and its messages show as Gemini's
+    std::env::set_var("RECALL_HOME_OVERRIDE", home);
+    std::env::set_var("RECALL_CWD_OVERRIDE", "/test/gemini-app");
+    let mut app = recall::App::new(String::new()).unwrap();
+    wait_for_indexing(&mut app, 100);
+    let terminal = render_app(&mut app);
+    cleanup_ui_test();
+
+    let screen = buffer_to_string(&terminal);
+    assert!(screen.contains("The login form hangs after submitting."), "{}", screen);
+    assert!(screen.contains("Gemini  just now"));
+    assert!(screen.contains("The token refresh in `src/auth.ts` never"));
+    assert!(!screen.contains("Thanks for the context"));
+    assert!(!screen.contains("Reading the auth flow"));
+}
+
+#[test]
+fn test_goose_session() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    let sessions = home.join(".local/share/goose/sessions");
+    std::fs::create_dir_all(&sessions).unwrap();
+    std::fs::write(
+        sessions.join("release-notes.jsonl"),
+        "{\"working_dir\":\"/w/docs\",\"description\":\"Release notes\"}\n\
+         {\"role\":\"user\",\"created\":1748772000,\
+         \"content\":[{\"type\":\"text\",\"text\":\"draft the changelog\"}]}\n",
+    )
+    .unwrap();
+
+    let (stdout, stderr, success) = run_cli(&["search", "changelog", "--source", "goose"], home);
+    assert!(success, "{}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let result = &json["results"][0];
+    assert_eq!(result["source"], "goose");
+    assert_eq!(result["cwd"], "/w/docs");
+    assert_eq!(result["resume_command"], "goose session -r --name release-notes");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_cline_task() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    let tasks = ".config/Code/User/globalStorage/saoudrizwan.claude-dev/tasks";
+    let task = home.join(tasks).join("1748772000000");
+    std::fs::create_dir_all(&task).unwrap();
+    let history = serde_json::json!([
+        {"role": "user", "content": [
+            {"type": "text", "text": "<task>\nspeed up the image resizer\n</task>"},
+            {"type": "text", "text": "<environment_details>\n\
+                                      # Current Working Directory (/w/thumbs) Files\n\
+                                      </environment_details>"}
+        ]},
+        {"role": "assistant", "content": "It decodes every frame twice."}
+    ]);
+    std::fs::write(task.join("api_conversation_history.json"), history.to_string()).unwrap();
+
+    let (stdout, stderr, success) = run_cli(&["search", "resizer", "--source", "cline"], home);
+    assert!(success, "{}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let result = &json["results"][0];
+    assert_eq!(result["session_id"], "1748772000000");
+    assert_eq!(result["cwd"], "/w/thumbs");
+    // Nothing to resume a VS Code task with, so it opens the workspace
+    assert_eq!(result["resume_command"], "code /w/thumbs");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_roo_code_task() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    // Installed in VSCodium rather than VS Code
+    let tasks = ".config/VSCodium/User/globalStorage/rooveterinaryinc.roo-cline/tasks";
+    let task = home.join(tasks).join("1750000000000");
+    std::fs::create_dir_all(&task).unwrap();
+    let history = serde_json::json!([
+        {"role": "user", "content": [
+            {"type": "text", "text": "<task>\nport the scraper to async\n</task>"},
+            {"type": "text", "text": "<environment_details>\n\
+                                      # Current Working Directory (/w/scraper) Files\n\
+                                      </environment_details>"}
+        ]},
+        {"role": "assistant", "content": "Switching requests to httpx.AsyncClient."}
+    ]);
+    std::fs::write(task.join("api_conversation_history.json"), history.to_string()).unwrap();
+
+    let (stdout, stderr, success) = run_cli(&["search", "scraper", "--source", "roocode"], home);
+    assert!(success, "{}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let result = &json["results"][0];
+    assert_eq!(result["session_id"], "1750000000000");
+    assert_eq!(result["source"], "roocode");
+    assert_eq!(result["cwd"], "/w/scraper");
+    assert_eq!(result["resume_command"], "code /w/scraper");
+
+    // Not a Cline task
+    let (stdout, _, _) = run_cli(&["search", "scraper", "--source", "cline"], home);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["results"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn test_copilot_session() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    copy_dir_recursive(&fixtures_path().join(".copilot"), &home.join(".copilot"));
+
+    std::env::set_var("RECALL_HOME_OVERRIDE", home);
+    std::env::set_var("RECALL_CWD_OVERRIDE", "/test/copilot-repo");
+    let options = recall::AppOptions { query: "flaky".to_string(), ..Default::default() };
+    let mut app = recall::App::with_config(options, &recall::config::Config::default()).unwrap();
+    wait_for_indexing(&mut app, 100);
+    app.flush_pending_search();
+    let terminal = render_app(&mut app);
+    cleanup_ui_test();
+
+    let result = &app.results[0];
+    assert_eq!(result.session.id, "0f3c9a2e-copilot");
+    assert_eq!(result.session.source.display_name(), "Copilot");
+    assert_eq!(result.session.resume_command().0, "copilot");
+    assert!(buffer_contains(&terminal, "◈ Copilot"));
+}
+
+#[test]
+fn test_amp_thread() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    let threads = home.join(".local/share/amp/threads");
+    std::fs::create_dir_all(&threads).unwrap();
+    let thread = serde_json::json!({
+        "id": "T-42c0",
+        "created": 1748772000000i64,
+        "env": {"initial": {"trees": [{"uri": "file:///w/billing"}]}},
+        "messages": [
+            {"role": "user", "content": [{"type": "text", "text": "why do invoices round down?"}]},
+            {"role": "assistant", "content": "They're cast to cents early."}
+        ]
+    });
+    std::fs::write(threads.join("T-42c0.json"), thread.to_string()).unwrap();
+
+    let (stdout, stderr, success) = run_cli(&["search", "invoices", "--source", "amp"], home);
+    assert!(success, "{}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let result = &json["results"][0];
+    assert_eq!(result["session_id"], "T-42c0");
+    assert_eq!(result["cwd"], "/w/billing");
+    assert_eq!(result["resume_command"], "amp threads continue T-42c0");
+}
+
+#[test]
+fn test_continue_session() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    let sessions = home.join(".continue/sessions");
+    std::fs::create_dir_all(&sessions).unwrap();
+    let session = serde_json::json!({
+        "sessionId": "9d2c",
+        "history": [
+            {"message": {"role": "user", "content": "which regions does the CDN serve?"}},
+            {"message": {"role": "assistant", "content": "Only eu-west so far."}}
+        ]
+    });
+    let index = serde_json::json!([
+        {"sessionId": "9d2c", "title": "CDN regions", "workspaceDirectory": "/w/edge"}
+    ]);
+    std::fs::write(sessions.join("9d2c.json"), session.to_string()).unwrap();
+    std::fs::write(sessions.join("sessions.json"), index.to_string()).unwrap();
+
+    let (stdout, stderr, success) = run_cli(&["search", "CDN", "--source", "continue"], home);
+    assert!(success, "{}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let results = json["results"].as_array().unwrap();
+    // The index isn't a session of its own
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["session_id"], "9d2c");
+    assert_eq!(results[0]["cwd"], "/w/edge");
+    assert_eq!(results[0]["resume_command"], "code /w/edge");
+}
+
+#[test]
+fn test_openhands_session() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    let events = home.join(".openhands/sessions/5ab1e7/events");
+    std::fs::create_dir_all(&events).unwrap();
+    let stream = [
+        serde_json::json!({"id": 0, "timestamp": "2025-06-01T09:00:00+00:00", "source": "user",
+            "action": "message", "args": {"content": "upgrade the flask version"}}),
+        serde_json::json!({"id": 1, "timestamp": "2025-06-01T09:05:00+00:00", "source": "agent",
+            "action": "finish", "args": {"final_thought": "Flask is on 3.1 now."}}),
+    ];
+    for (i, event) in stream.iter().enumerate() {
+        std::fs::write(events.join(format!("{}.json", i)), event.to_string()).unwrap();
+    }
+
+    let (stdout, stderr, success) = run_cli(&["search", "flask", "--source", "openhands"], home);
+    assert!(success, "{}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let result = &json["results"][0];
+    assert_eq!(result["session_id"], "5ab1e7");
+    assert_eq!(result["resume_command"], "openhands --resume 5ab1e7");
+}
+
+#[test]
+fn test_plandex_plan() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    let plan = home.join(".plandex-home/projects/9c2e/plans/4b7f1a");
+    let conversation = plan.join("conversation");
+    std::fs::create_dir_all(&conversation).unwrap();
+    std::fs::write(plan.join("plan.json"), r#"{"name": "csv-export", "cwd": "/test/shop"}"#)
+        .unwrap();
+    std::fs::write(conversation.join("1-user.md"), "Add a CSV export for orders").unwrap();
+    std::fs::write(conversation.join("2-assistant.md"), "Adding `export_orders`.").unwrap();
+
+    let (stdout, stderr, success) = run_cli(&["search", "csv", "--source", "plandex"], home);
+    assert!(success, "{}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let result = &json["results"][0];
+    assert_eq!(result["session_id"], "4b7f1a");
+    assert_eq!(result["cwd"], "/test/shop");
+    assert_eq!(result["resume_command"], "plandex continue");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_zed_conversation() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    let conversations = home.join(".config/zed/conversations");
+    std::fs::create_dir_all(&conversations).unwrap();
+    let text = "how do I bind a key to toggle the terminal?\nAdd it to keymap.json.\n";
+    let conversation = serde_json::json!({
+        "id": "c4d1-zed", "zed": "context", "version": "0.4.0", "text": text,
+        "messages": [
+            {"id": {"replica_id": 0, "value": 0}, "start": 0, "metadata": {"role": "user"}},
+            {"id": {"replica_id": 0, "value": 1}, "start": text.find("Add").unwrap(),
+             "metadata": {"role": "assistant"}}
+        ]
+    });
+    let path = conversations.join("Terminal keybinding - 1.zed.json");
+    std::fs::write(&path, conversation.to_string()).unwrap();
+
+    let (stdout, stderr, success) = run_cli(&["search", "keymap", "--source", "zed"], home);
+    assert!(success, "{}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let result = &json["results"][0];
+    assert_eq!(result["session_id"], "c4d1-zed");
+    // Reopened in the editor, the path quoted for its spaces
+    assert_eq!(result["resume_command"], format!("zed '{}'", path.display()));
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_warp_agent_conversations() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    copy_dir_recursive(&fixtures_path().join(".local"), &home.join(".local"));
+    let search = |query| {
+        let (stdout, stderr, success) = run_cli(&["search", query, "--source", "warp"], home);
+        assert!(success, "{}", stderr);
+        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        json["results"][0].clone()
+    };
+
+    let result = search("compose");
+    assert_eq!(result["session_id"], "conv-docker");
+    assert_eq!(result["cwd"], "/test/warp app");
+    // The folder comes from the query's context when the row has none
+    assert_eq!(search("commit")["cwd"], "/test/notes");
+
+    let database = home.join(".local/state/warp-terminal/warp.sqlite");
+    let session = recall::parser::parse_session_file(&database.join("conv-docker")).unwrap();
+    assert!(!session.source.resumable());
+    let contents: Vec<_> = session.messages.iter().map(|m| m.content.as_str()).collect();
+    assert_eq!(
+        contents,
+        [
+            "Why does docker compose rebuild every time?",
+            "The COPY before pip install busts the layer cache.",
+            "Move it after the install then",
+            "Moved COPY . . below RUN pip install.",
+            // Cancelled, so no reply
+            "And pin the base image",
+        ]
+    );
+    assert_eq!(session.timestamp.to_rfc3339(), "2025-07-03T10:03:30+00:00");
+
+    // A schema it doesn't know is skipped and reported, and indexing goes on
+    let mut bytes = std::fs::read(&database).unwrap();
+    let column = b"working_directory";
+    let at = bytes.windows(column.len()).position(|w| w == column).unwrap();
+    bytes[at..at + column.len()].copy_from_slice(b"cwd              ");
+    std::fs::write(&database, bytes).unwrap();
+    let (stdout, stderr, success) = run_cli(&["search", "compose"], home);
+    assert!(success, "{}", stderr);
+    assert!(stderr.contains("Unknown Warp database schema"), "stderr: {}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert!(json["results"].as_array().unwrap().iter().all(|r| r["source"] != "warp"));
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_amazon_q_conversations() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    copy_dir_recursive(&fixtures_path().join(".local"), &home.join(".local"));
+
+    let (stdout, stderr, success) =
+        run_cli(&["search", "double-charge", "--source", "amazonq"], home);
+    assert!(success, "{}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let result = &json["results"][0];
+    assert_eq!(result["session_id"], "8e3f0c1a-52d4-4b7e-9a61-0c2f7d9e4b15");
+    assert_eq!(result["cwd"], "/test/q shop");
+    assert_eq!(result["resume_command"], "q chat --resume");
+
+    // Each directory's conversation is a session of its own, older formats included
+    let (stdout, stderr, success) = run_cli(&["search", "cron", "--source", "amazonq"], home);
+    assert!(success, "{}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["results"][0]["cwd"], "/test/ops");
+
+    // The tool call's text is kept; the results sent back for the user aren't messages
+    let database = home.join(".local/share/amazon-q/data.sqlite3");
+    let session = recall::parser::parse_session_file(&database.join("%2Ftest%2Fq shop")).unwrap();
+    let contents: Vec<_> = session.messages.iter().map(|m| m.content.as_str()).collect();
+    assert_eq!(contents[0], "Why does checkout double-charge on retry?");
+    assert!(contents[1].starts_with("Let me look at the payment handler."));
+    assert!(contents[1].ends_with("so each attempt charges again."));
+    assert_eq!(contents.len(), 2);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_llm_logged_conversations() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    copy_dir_recursive(&fixtures_path().join(".config"), &home.join(".config"));
+    let search = |query| {
+        let (stdout, stderr, success) = run_cli(&["search", query, "--source", "llm"], home);
+        assert!(success, "{}", stderr);
+        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        json["results"][0].clone()
+    };
+
+    // Continued by id, since it isn't the latest conversation
+    let nginx = "01jx4kq7m2v9d3r8c5t1w6y0ab";
+    let result = search("burst");
+    assert_eq!(result["session_id"], nginx);
+    assert_eq!(result["resume_command"], format!("llm chat --cid {}", nginx));
+    let result = search("digit");
+    assert_eq!(result["session_id"], "01jx9zt3p8e1h6n4b2k7s5q0cd");
+    assert_eq!(result["resume_command"], "llm chat -c");
+
+    let database = home.join(".config/io.datasette.llm/logs.db");
+    let session = recall::parser::parse_session_file(&database.join(nginx)).unwrap();
+    let contents: Vec<_> = session.messages.iter().map(|m| m.content.as_str()).collect();
+    assert_eq!(
+        contents,
+        [
+            "How do I rate limit an endpoint in nginx?",
+            "Use limit_req_zone in the http block and limit_req in the location.",
+            "And allow short bursts?",
+            "Add burst=20 nodelay to limit_req.",
+        ]
+    );
+    assert_eq!(session.messages[1].role, recall::session::Role::Assistant);
+    assert_eq!(session.timestamp.to_rfc3339(), "2025-06-01T09:01:30.500+00:00");
+
+    // Commits not yet checkpointed are read from the WAL, once it's there
+    let wal = fixtures_path().join("wal");
+    std::fs::copy(wal.join("logs.db"), &database).unwrap();
+    assert_eq!(search("binary_remote_addr"), serde_json::Value::Null);
+    std::fs::copy(wal.join("logs.db-wal"), database.with_file_name("logs.db-wal")).unwrap();
+    assert_eq!(search("binary_remote_addr")["session_id"], nginx);
+    let session = recall::parser::parse_session_file(&database.join(nginx)).unwrap();
+    assert_eq!(session.messages.len(), 6);
+    assert_eq!(session.messages[4].content, "And per client IP?");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_claude_desktop_local_storage() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    copy_dir_recursive(&fixtures_path().join(".config"), &home.join(".config"));
+    let search = |query| {
+        let args = ["search", query, "--source", "claudedesktop"];
+        let (stdout, stderr, success) = run_cli(&args, home);
+        assert!(success, "{}", stderr);
+        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        json["results"].as_array().unwrap().clone()
+    };
+
+    // One conversation from a compressed table, one from the log; the deleted one is gone
+    let results = search("autovacuum");
+    assert_eq!(results[0]["session_id"], "4f1d2c3b-8a9e-4b7c-9d0e-1a2b3c4d5e6f");
+    assert_eq!(search("evict")[0]["session_id"], "9b8a7c6d-5e4f-4a3b-8c2d-1e0f9a8b7c6d");
+    assert!(search("deleted").is_empty());
+
+    let store = home.join(".config/Claude/Local Storage/leveldb");
+    let path = store.join("000006.log/9b8a7c6d-5e4f-4a3b-8c2d-1e0f9a8b7c6d");
+    let session = recall::parser::parse_session_file(&path).unwrap();
+    assert_eq!(session.source, recall::session::SessionSource::ClaudeDesktop);
+    assert_eq!(session.cwd, "");
+    assert_eq!(session.title.as_deref(), Some("Haiku about caches"));
+    assert!(!session.source.resumable());
+    assert_eq!(session.messages.len(), 2);
+    assert!(session.messages[1].content.contains("the café clock"));
+    assert_eq!(session.timestamp.to_rfc3339(), "2025-06-20T08:31:00+00:00");
+
+    // A store that can't be read is reported, not fatal
+    std::fs::create_dir(store.join("000007.ldb")).unwrap();
+    let (_, stderr, success) = run_cli(&["list"], home);
+    assert!(success, "{}", stderr);
+    assert!(stderr.contains("Couldn't index"), "stderr: {}", stderr);
+    assert!(stderr.contains("is the app holding it locked?"), "stderr: {}", stderr);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_cursor_workspace_chats() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    copy_dir_recursive(&fixtures_path().join(".config"), &home.join(".config"));
+
+    std::env::set_var("RECALL_HOME_OVERRIDE", home);
+    std::env::set_var("RECALL_CWD_OVERRIDE", "/test/cursor shop");
+    let options = recall::AppOptions { query: "stale prices".to_string(), ..Default::default() };
+    let mut app = recall::App::with_config(options, &recall::config::Config::default()).unwrap();
+    wait_for_indexing(&mut app, 100);
+    app.flush_pending_search();
+    cleanup_ui_test();
+
+    let session = app.results[0].session.clone();
+    assert_eq!(session.source.display_name(), "Cursor");
+    assert_eq!(session.id, "4f1d0c7be2a94b3e");
+    assert_eq!(session.cwd, "/test/cursor shop");
+
+    // Both tabs, oldest first, with the reply that spills onto overflow pages in full
+    let session = recall::parser::parse_session_file(&session.file_path).unwrap();
+    assert_eq!(session.messages.len(), 4);
+    assert_eq!(session.messages[0].content, "How do I run the storefront locally?");
+    assert!(session.messages[3].content.ends_with("fixes the stale product pages."));
+    assert!(session.messages[3].content.len() > 4096);
+
+    // Enter can't resume it, but Tab still copies its ID
+    app.on_enter();
+    assert!(app.should_resume.is_none());
+    assert_eq!(app.toast(), Some("Cursor chats can't be resumed; Tab copies the ID"));
+    app.on_tab();
+    assert_eq!(app.should_copy.as_deref(), Some("4f1d0c7be2a94b3e"));
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_windsurf_cascade_chats() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    copy_dir_recursive(&fixtures_path().join(".config"), &home.join(".config"));
+
+    std::env::set_var("RECALL_HOME_OVERRIDE", home);
+    std::env::set_var("RECALL_CWD_OVERRIDE", "/test/windsurf app");
+    let options = recall::AppOptions { query: "dark mode".to_string(), ..Default::default() };
+    let mut app = recall::App::with_config(options, &recall::config::Config::default()).unwrap();
+    wait_for_indexing(&mut app, 100);
+    app.flush_pending_search();
+    let terminal = render_app(&mut app);
+    cleanup_ui_test();
+
+    // Each chat is a session of its own, in the workspace's folder
+    assert_eq!(app.results.len(), 1);
+    let session = app.results[0].session.clone();
+    assert_eq!(session.source.display_name(), "Windsurf");
+    assert_eq!(session.id, "d41f7a2c-6b3e-4c19-8e5a-0f2b9c7d1e64");
+    assert_eq!(session.cwd, "/test/windsurf app");
+    let session = recall::parser::parse_session_file(&session.file_path).unwrap();
+    // The replies around the tool call are one message
+    assert_eq!(session.messages.len(), 2);
+    assert!(session.messages[1].content.ends_with("flips the theme class on <html>."));
+
+    // Listed in a color of its own
+    let dark = recall::theme::Theme::named("dark").unwrap();
+    let buffer = terminal.backend().buffer();
+    let label = buffer
+        .content
+        .iter()
+        .position(|cell| cell.symbol() == "≋")
+        .expect("the Windsurf icon is in the list");
+    assert_eq!(buffer.content[label].fg, dark.windsurf_source);
+    assert_ne!(dark.windsurf_source, dark.accent_secondary);
+
+    // Only its ID can be copied
+    app.on_enter();
+    assert!(app.should_resume.is_none());
+    assert_eq!(app.toast(), Some("Windsurf chats can't be resumed; Tab copies the ID"));
+    app.on_tab();
+    assert_eq!(app.should_copy.as_deref(), Some("d41f7a2c-6b3e-4c19-8e5a-0f2b9c7d1e64"));
+}
+
+#[test]
+fn test_custom_source_from_config() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    copy_dir_recursive(&fixtures_path().join(".toybot"), &home.join(".toybot"));
+    write_config(
+        home,
+        r#"
+[[custom_source]]
+name = "toybot"
+glob = ".toybot/logs/*.jsonl"
+role = "/speaker"
+content = "/text"
+timestamp = "/ts"
+session_id = "/conv"
+cwd = "/workdir"
+user_roles = ["human"]
+assistant_roles = ["bot"]
+resume = "toybot --continue {id}"
+"#,
+    );
+
+    let (stdout, stderr, success) = run_cli(&["search", "tunneling"], home);
+    assert!(success, "{}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let result = &json["results"][0];
+    assert_eq!(result["session_id"], "toy-conv-1");
+    assert_eq!(result["source"], "toybot");
+    assert_eq!(result["cwd"], "/test/project");
+    assert_eq!(result["resume_command"], "toybot --continue toy-conv-1");
+
+    // Content parts are joined; records with other roles are skipped
+    let (stdout, _, success) = run_cli(&["read", "toy-conv-1"], home);
+    assert!(success);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let messages = json["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[1]["role"], "assistant");
+    assert_eq!(
+        messages[1]["content"],
+        "Imagine rolling a ball at a hill.\nSometimes it shows up on the other side."
+    );
+    let (stdout, _, success) = run_cli(&["print", "toy-conv-1"], home);
+    assert!(success);
+    assert!(stdout.contains("Sometimes it shows up on the other side."), "{}", stdout);
+
+    // It's a source like any other
+    let (stdout, _, success) = run_cli(&["list", "--source", "toybot"], home);
+    assert!(success);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let sessions = json["sessions"].as_array().unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0]["session_id"], "toy-conv-1");
+    let (stdout, _, _) = run_cli(&["--disable-source", "toybot", "search", "tunneling"], home);
+    assert!(!stdout.contains("toy-conv-1"), "{}", stdout);
+    let (_, stderr, success) = run_cli(&["list", "--source", "nope"], home);
+    assert!(!success);
+    let valid = "Valid: claude, codex, factory, opencode, aider, gemini, goose, cline, copilot, \
+                 amp, cursor, continue, openhands, amazonq, crush, chatgpt, llm, zed, windsurf, \
+                 claudedesktop, plandex, roocode, warp, toybot";
+    assert!(stderr.contains(valid), "{}", stderr);
+}
+
+#[test]
+fn test_path_map_rewrites_container_cwds() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    write_session_in(home, "in-container", "/workspaces/recall", 2);
+    write_session_in(home, "container-src", "/workspaces/recall/src", 3);
+    write_session_in(home, "other-container", "/workspaces/other", 4);
+    let map = "[path_map]\n'/workspaces' = '/srv/ws'\n'/workspaces/recall' = '/host/code/recall'\n";
+    write_config(home, map);
+
+    // The host checkout's scope finds the sessions recorded in its container
+    let (config, _) = recall::config::Config::parse(map).unwrap();
+    std::env::set_var("RECALL_HOME_OVERRIDE", home);
+    std::env::set_var("RECALL_CWD_OVERRIDE", "/host/code/recall");
+    let mut app = recall::App::with_config(recall::AppOptions::default(), &config).unwrap();
+    wait_for_indexing(&mut app, 100);
+    std::env::remove_var("RECALL_HOME_OVERRIDE");
+    std::env::remove_var("RECALL_CWD_OVERRIDE");
+    assert_eq!(result_ids(&app), ["in-container"]);
+
+    let (stdout, _, success) = run_last(&["--last"], home, "/host/code/recall");
+    assert!(success);
+    assert_eq!(stdout.trim(), "claude container-src");
+
+    // Shown where it is on this machine, but the data keeps the recorded cwd
+    let (stdout, _, success) = run_cli(&["print", "in-container"], home);
+    assert!(success);
+    assert!(stdout.contains("/host/code/recall ·"), "{}", stdout);
+    let (stdout, _, _) = run_cli(&["read", "other-container"], home);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["cwd"], "/workspaces/other");
+    let (stdout, _, _) = run_cli(&["list", "--cwd", "/srv/ws/other"], home);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["sessions"][0]["session_id"], "other-container");
+}
+
+#[test]
+fn test_remote_mirror_resumes_over_ssh() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    // A mirror left by an earlier sync; the host can't be reached now
+    write_session_in(home, "on-remote", "/home/me/app", 2);
+    let mirror = home.join(".cache/recall/remotes/dev.invalid/.claude/projects");
+    std::fs::create_dir_all(&mirror).unwrap();
+    std::fs::rename(home.join(".claude/projects/last"), mirror.join("last")).unwrap();
+    write_config(home, "[[remote]]\nhost = \"dev.invalid\"\n");
+
+    let (stdout, stderr, success) = run_cli(&["list", "--cwd", "/home/me/app"], home);
+    assert!(success, "{}", stderr);
+    assert!(stderr.contains("Couldn't sync remote dev.invalid"), "{}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let session = &json["sessions"][0];
+    assert_eq!(session["session_id"], "on-remote");
+    assert_eq!(session["remote"], "dev.invalid");
+    let resume = "ssh -t dev.invalid 'cd /home/me/app && claude --resume on-remote'";
+    assert_eq!(session["resume_command"], resume);
+}
+
+/// A stand-in for `claude` left running in `cwd`, killed when dropped
+#[cfg(target_os = "linux")]
+struct FakeAgent(std::process::Child);
+
+#[cfg(target_os = "linux")]
+impl FakeAgent {
+    fn start(bin: &std::path::Path, cwd: &std::path::Path) -> Self {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::create_dir_all(bin).unwrap();
+        let script = bin.join("claude");
+        std::fs::write(&script, "#!/bin/sh\nwhile true; do sleep 1; done\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        Self(Command::new(&script).current_dir(cwd).spawn().unwrap())
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for FakeAgent {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_resume_asks_when_session_looks_active() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let home = temp_dir.path();
+    let work = home.join("work");
+    std::fs::create_dir_all(&work).unwrap();
+    let cwd = work.to_string_lossy().to_string();
+    // Just written, so it changed recently
+    write_session_in(home, "busy", &cwd, 2);
+
+    // Closed: nothing runs in its folder
+    let (stdout, stderr, success) = run_last(&["--last"], home, &cwd);
+    assert!(success, "{}", stderr);
+    assert_eq!(stdout.trim(), "claude busy");
+
+    let _agent = FakeAgent::start(&home.join("bin"), &work);
+    let (stdout, stderr, success) = run_last(&["--last"], home, &cwd);
+    assert!(!success);
+    assert!(stdout.is_empty());
+    assert!(stderr.contains("looks active") && stderr.contains("--force"), "{}", stderr);
+    let (stdout, _, success) = run_last(&["last", "--force"], home, &cwd);
+    assert!(success);
+    assert_eq!(stdout.trim(), "claude busy");
+
+    let options = recall::AppOptions { cwd: Some(cwd), ..Default::default() };
+    let mut app = app_with_options(home, options);
+    assert_eq!(result_ids(&app), ["busy"]);
+    app.on_enter();
+    assert!(app.should_resume.is_none());
+    assert!(app.confirm_resume.is_some());
+    let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+    terminal.draw(|f| recall::ui::render(f, &mut app)).unwrap();
+    assert!(buffer_contains(&terminal, "Resume anyway?"));
+
+    app.confirm_resume(false);
+    assert!(app.confirm_resume.is_none() && app.should_resume.is_none());
+    app.on_enter();
+    app.confirm_resume(true);
+    assert_eq!(app.should_resume.as_ref().map(|s| s.id.as_str()), Some("busy"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_index_cache_is_private() {
+    use std::os::unix::fs::PermissionsExt;
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let cache = temp_dir.path().join(".cache/recall");
+    let mode = |path: &std::path::Path| {
+        std::fs::metadata(path).unwrap().permissions().mode() & 0o777
+    };
+
+    let (_, _, success) = run_cli(&["list"], temp_dir.path());
+    assert!(success);
+    assert_eq!(mode(&cache), 0o700);
+    assert_eq!(mode(&cache.join("index")), 0o700);
+    assert_eq!(mode(&cache.join("state.json")), 0o600);
+    for entry in std::fs::read_dir(cache.join("index")).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_file() {
+            assert_eq!(mode(&path), 0o600, "{}", path.display());
+        }
+    }
+
+    let (stdout, _, _) = run_cli(&["sources", "--json"], temp_dir.path());
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["index"]["mode"], "0700");
+    let (_, stderr, _) = run_cli(&["list", "--reindex"], temp_dir.path());
+    assert!(stderr.contains("(mode 0700)"), "{}", stderr);
+}
+
+#[test]
+fn test_ephemeral_index_leaves_no_cache() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let cache = temp_dir.path().join(".cache/recall");
+
+    let (stdout, _, success) = run_cli(&["search", "hello", "--no-persist"], temp_dir.path());
+    assert!(success);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert!(!json["results"].as_array().unwrap().is_empty(), "{}", stdout);
+    assert!(!cache.exists());
+
+    write_config(temp_dir.path(), "[privacy]\nephemeral = true\n");
+    let (stdout, _, success) = run_cli(&["list"], temp_dir.path());
+    assert!(success);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["sessions"].as_array().unwrap().len(), 2);
+    let (stdout, _, _) = run_cli(&["sources", "--json"], temp_dir.path());
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert!(json["index"]["dir"].is_null());
+    assert!(!cache.exists());
+}
+
+/// A one-message Claude session from `days_ago`, with the file's mtime set to `mtime_days_ago`
+fn write_aged_session(home: &std::path::Path, id: &str, days_ago: i64, mtime_days_ago: i64) {
+    let project = home.join(".claude/projects/aged");
+    std::fs::create_dir_all(&project).unwrap();
+    let timestamp = chrono::Utc::now() - chrono::Duration::days(days_ago);
+    let line = serde_json::json!({
+        "cwd": "/test/aged",
+        "sessionId": id,
+        "type": "user",
+        "message": {"role": "user", "content": format!("harvest notes from {}", id)},
+        "timestamp": timestamp.to_rfc3339(),
+    });
+    let path = project.join(format!("{}.jsonl", id));
+    std::fs::write(&path, format!("{}\n", line)).unwrap();
+    let mtime = std::time::SystemTime::now()
+        - std::time::Duration::from_secs(mtime_days_ago as u64 * 24 * 3600);
+    let file = std::fs::File::options().write(true).open(&path).unwrap();
+    file.set_modified(mtime).unwrap();
+}
+
+#[test]
+fn test_retention_prunes_old_sessions() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    write_aged_session(home, "fresh", 3, 3);
+    write_aged_session(home, "ancient", 900, 900);
+    // Recently touched, but its messages are old: caught by the parsed timestamp
+    write_aged_session(home, "touched", 400, 1);
+    let listed = |args: &[&str]| -> (Vec<String>, String) {
+        let (stdout, stderr, success) = run_cli(args, home);
+        assert!(success, "{}", stderr);
+        let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        let mut ids: Vec<String> = json["sessions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s["session_id"].as_str().unwrap().to_string())
+            .collect();
+        ids.sort();
+        (ids, stderr)
+    };
+
+    assert_eq!(listed(&["list"]).0, ["ancient", "fresh", "touched"]);
+
+    // Turning retention on prunes what's already indexed, and says so
+    write_config(home, "retention = \"180d\"\n");
+    let (ids, stderr) = listed(&["list"]);
+    assert_eq!(ids, ["fresh"]);
+    assert!(stderr.contains("Pruned 2 sessions older than 180d."), "{}", stderr);
+    let (_, stderr) = listed(&["list"]);
+    assert!(!stderr.contains("Pruned"), "{}", stderr);
+
+    // --no-retention searches everything without touching the cache
+    let (ids, _) = listed(&["list", "--no-retention"]);
+    assert_eq!(ids, ["ancient", "fresh", "touched"]);
+    assert_eq!(listed(&["list"]).0, ["fresh"]);
+
+    // Rebuilding with --max-age leaves old sessions out without indexing them
+    std::fs::remove_file(home.join(".config/recall/config.toml")).unwrap();
+    let (ids, stderr) = listed(&["list", "--reindex", "--max-age", "180d"]);
+    assert_eq!(ids, ["fresh"]);
+    assert!(stderr.contains("Indexed 1 session (1 skipped)."), "{}", stderr);
+
+    // And without retention, sessions left out come back
+    assert_eq!(listed(&["list"]).0, ["ancient", "fresh", "touched"]);
+}
+
+#[test]
+fn test_low_memory_indexing_matches_default() {
+    let _lock = lock_test();
+    let temp_dir = TempDir::new().unwrap();
+    let home = temp_dir.path();
+    // Enough sessions for several low-memory batches, and messages past the content cap
+    let project = home.join(".claude/projects/bulk");
+    std::fs::create_dir_all(&project).unwrap();
+    let filler = "lorem ipsum dolor sit amet ".repeat(2600);
+    for i in 0..60 {
+        let id = format!("bulk-{:03}", i);
+        let lines = [
+            ("user", format!("migrate shard {} of the ledger", i)),
+            ("assistant", format!("ledger shard {} migrated. {}", i, filler)),
+        ]
+        .iter()
+        .enumerate()
+        .map(|(j, (role, text))| {
+            serde_json::json!({
+                "cwd": "/test/bulk",
+                "sessionId": id,
+                "type": role,
+                "message": {"role": role, "content": text},
+                "timestamp": format!("2025-03-{:02}T{:02}:00:{:02}Z", 1 + i / 24, i % 24, j),
+            })
+            .to_string()
+        })
+        .collect::<Vec<_>>();
+        std::fs::write(project.join(format!("{}.jsonl", id)), lines.join("\n")).unwrap();
+    }
+
+    // Equally relevant results come back in segment order, which batching changes, so
+    // compare them by session
+    let queries: [&[&str]; 3] = [
+        &["search", "ledger", "--limit", "200"],
+        &["search", "migrate", "--limit", "200"],
+        &["list", "--limit", "200"],
+    ];
+    let run = |extra: &[&str]| -> Vec<serde_json::Value> {
+        let mut outputs = Vec::new();
+        for query in queries {
+            let args: Vec<&str> = query.iter().chain(extra).copied().collect();
+            let (stdout, stderr, success) = run_cli(&args, home);
+            assert!(success, "{}", stderr);
+            let mut json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+            let key = if query[0] == "list" { "sessions" } else { "results" };
+            let sessions = json[key].as_array_mut().unwrap();
+            sessions.sort_by_key(|s| s["session_id"].as_str().unwrap().to_string());
+            outputs.push(json);
+        }
+        outputs
+    };
+
+    let default = run(&[]);
+    assert_eq!(default[0]["results"].as_array().unwrap().len(), 60);
+    let low_memory = run(&["--reindex", "--low-memory"]);
+    assert!(default == low_memory, "low-memory indexing changed the results");
+}
+
+#[test]
+fn test_library_api_without_env() {
+    // Explicit paths only: no lock, no RECALL_HOME_OVERRIDE
+    let home = setup_test_env();
+    let cache = TempDir::new().unwrap();
+    let config = recall::RecallConfig::new(home.path(), cache.path());
+    let engine = recall::Recall::open(config.clone()).unwrap();
+
+    let report = engine.index().unwrap();
+    assert_eq!(report.indexed, 2);
+    assert!(cache.path().join("index/meta.json").exists());
+    assert_eq!(engine.index().unwrap().indexed, 0, "nothing changed");
+
+    let results = engine.search("database", &recall::SearchOptions::default()).unwrap();
+    assert_eq!(results[0].session.id, "test-codex-456");
+    let options = recall::SearchOptions {
+        source: Some(recall::SessionSource::ClaudeCode),
+        ..Default::default()
+    };
+    assert!(engine.search("database", &options).unwrap().is_empty());
+
+    let recent = engine.recent(&recall::SearchOptions { limit: 1, ..Default::default() }).unwrap();
+    assert_eq!(recent.len(), 1);
+    assert_eq!(recent[0].session.id, "test-codex-456");
+
+    let session = engine.get_session("test-claude-123").unwrap().unwrap();
+    assert_eq!(session.messages[0].content, "hello world");
+    assert!(engine.get_session("missing").unwrap().is_none());
+
+    // Limiting sources leaves the others out of a fresh index
+    let claude_only = recall::RecallConfig {
+        sources: vec![recall::SessionSource::ClaudeCode],
+        cache_dir: cache.path().join("claude-only"),
+        ..config
+    };
+    let engine = recall::Recall::open(claude_only).unwrap();
+    assert_eq!(engine.index().unwrap().indexed, 1);
+    assert!(engine.get_session("test-codex-456").unwrap().is_none());
+}
+
+#[test]
+fn test_library_api_reads_its_own_sidecars() {
+    // Tags, notes, and titles come from the engine's cache dir, not the user's
+    let home = setup_test_env();
+    let cache = TempDir::new().unwrap();
+    let file = home.path().join(".codex/sessions/test-codex.jsonl");
+    let sidecar = |name: &str, value: serde_json::Value| {
+        let store = serde_json::json!({ "test-codex-456": { "value": value, "path": file } });
+        std::fs::write(cache.path().join(name), store.to_string()).unwrap();
+    };
+    sidecar("tags.json", serde_json::json!(["billing"]));
+    sidecar("notes.json", serde_json::json!("picked postgres"));
+    sidecar("titles.json", serde_json::json!("Zanzibar migration"));
+
+    let engine =
+        recall::Recall::open(recall::RecallConfig::new(home.path(), cache.path())).unwrap();
+    assert_eq!(engine.index().unwrap().indexed, 2);
+
+    let tagged = recall::SearchOptions { tags: vec!["billing".to_string()], ..Default::default() };
+    let recent = engine.recent(&tagged).unwrap();
+    assert_eq!(recent.len(), 1);
+    assert_eq!(recent[0].session.id, "test-codex-456");
+    assert_eq!(recent[0].snippet, "picked postgres");
+    assert_eq!(recent[0].session.title.as_deref(), Some("Zanzibar migration"));
+
+    let results = engine.search("zanzibar", &recall::SearchOptions::default()).unwrap();
+    assert_eq!(results[0].session.id, "test-codex-456");
+    let results = engine.search("tag:billing", &recall::SearchOptions::default()).unwrap();
+    assert_eq!(results[0].session.id, "test-codex-456");
+
+    // The index state remembers the tags from the engine's sidecar, so changing them there
+    // reindexes the session
+    let state = std::fs::read_to_string(cache.path().join("state.json")).unwrap();
+    let state: serde_json::Value = serde_json::from_str(&state).unwrap();
+    let indexed = &state["indexed_files"][file.to_str().unwrap()];
+    assert_eq!(indexed["tags"], serde_json::json!(["billing"]));
+    assert_eq!(indexed["title"], "Zanzibar migration");
+}
+
+#[test]
+fn test_config_sets_cli_limits() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    write_config(temp_dir.path(), "[cli]\nlist_limit = 1\n");
+
+    let (stdout, _, success) = run_cli(&["list"], temp_dir.path());
+    assert!(success);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["sessions"].as_array().unwrap().len(), 1);
+
+    // An explicit --limit still wins
+    let (stdout, _, _) = run_cli(&["list", "--limit", "5"], temp_dir.path());
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["sessions"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_config_unknown_keys_warn() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    write_config(temp_dir.path(), "colour = \"blue\"\n\n[cli]\nlist_limit = 1\n");
+
+    let (stdout, stderr, success) = run_cli(&["list"], temp_dir.path());
+    assert!(success);
+    assert!(stderr.contains("Ignoring unknown config key 'colour'"), "stderr: {}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["sessions"].as_array().unwrap().len(), 1);
+
+    // Invalid values are an error rather than silently ignored
+    write_config(temp_dir.path(), "theme = \"purple\"\n");
+    let (_, stderr, success) = run_cli(&["list"], temp_dir.path());
+    assert!(!success);
+    assert!(stderr.contains("Invalid config"), "stderr: {}", stderr);
+}
+
+#[test]
+fn test_config_env_override() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    write_config(temp_dir.path(), "[cli]\nlist_limit = 1\n");
+    let other = temp_dir.path().join("other.toml");
+    std::fs::write(&other, "[cli]\nlist_limit = 2\n").unwrap();
+
+    let output = Command::new(recall_bin())
+        .args(["list"])
+        .env("RECALL_HOME_OVERRIDE", temp_dir.path())
+        .env("RECALL_CONFIG", &other)
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["sessions"].as_array().unwrap().len(), 2);
+
+    // A missing RECALL_CONFIG file is an error, unlike a missing default config
+    let output = Command::new(recall_bin())
+        .args(["list"])
+        .env("RECALL_HOME_OVERRIDE", temp_dir.path())
+        .env("RECALL_CONFIG", temp_dir.path().join("missing.toml"))
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_resume_templates_keep_quoted_arguments() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    write_session_in(temp_dir.path(), "quoted", "/work/app", 1);
+
+    // Env var templates are split like a shell would
+    let output = Command::new(recall_bin())
+        .args(["--last"])
+        .env("RECALL_HOME_OVERRIDE", temp_dir.path())
+        .env("RECALL_CWD_OVERRIDE", "/work/app")
+        .env("RECALL_CLAUDE_CMD", r#"printf "[%s]" --resume {id} "be brief""#)
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "[--resume][quoted][be brief]");
+
+    // Config templates, with the env var taking precedence when set
+    write_config(
+        temp_dir.path(),
+        "[resume]\nclaude = [\"printf\", \"[%s]\", \"--cwd={cwd}\", \"{id}\"]\n",
+    );
+    let output = Command::new(recall_bin())
+        .args(["--last"])
+        .env("RECALL_HOME_OVERRIDE", temp_dir.path())
+        .env("RECALL_CWD_OVERRIDE", "/work/app")
+        .env_remove("RECALL_CLAUDE_CMD")
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "[--cwd=/work/app][quoted]");
+
+    let (stdout, _, _) = run_last(&["--last"], temp_dir.path(), "/work/app");
+    assert_eq!(stdout.trim(), "claude quoted");
+}
+
+#[test]
+fn test_resume_command_output_quotes_arguments() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    write_config(
+        temp_dir.path(),
+        "[resume]\nclaude = 'claude --append-system-prompt \"be brief\" --resume {id}'\n",
+    );
+    let expected = ["claude", "--append-system-prompt", "be brief", "--resume", "test-claude-123"];
+    let split = |line: &serde_json::Value| shell_words::split(line.as_str().unwrap()).unwrap();
+
+    let (stdout, stderr, success) = run_cli(&["read", "test-claude-123"], temp_dir.path());
+    assert!(success, "stderr: {}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(split(&json["resume_command"]), expected);
+
+    let (stdout, _, _) = run_cli(&["search", "hello"], temp_dir.path());
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(split(&json["results"][0]["resume_command"]), expected);
+
+    let (stdout, _, _) = run_cli(&["list", "--source", "claude"], temp_dir.path());
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(split(&json["sessions"][0]["resume_command"]), expected);
+}
+
+fn listed_ids(stdout: &str) -> Vec<String> {
+    let json: serde_json::Value = serde_json::from_str(stdout).unwrap();
+    json["sessions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["session_id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_disabled_source_hidden_from_existing_index() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    // Index both sources first
+    let (stdout, _, _) = run_cli(&["list"], temp_dir.path());
+    assert_eq!(listed_ids(&stdout).len(), 2);
+
+    write_config(temp_dir.path(), "[sources]\ncodex = false\n");
+    let (stdout, _, success) = run_cli(&["list"], temp_dir.path());
+    assert!(success);
+    assert_eq!(listed_ids(&stdout), ["test-claude-123"]);
+
+    let (stdout, _, _) = run_cli(&["search", "hello"], temp_dir.path());
+    assert!(!stdout.contains("test-codex-456"));
+
+    let (_, stderr, success) = run_cli(&["read", "test-codex-456"], temp_dir.path());
+    assert!(!success);
+    assert!(stderr.contains("Codex sessions are disabled"), "stderr: {}", stderr);
+
+    let (stdout, _, _) = run_cli(&["sources"], temp_dir.path());
+    assert!(stdout.lines().any(|l| l.starts_with("codex") && l.ends_with("(disabled)")));
+
+    // The session files themselves are left alone
+    assert!(temp_dir.path().join(".codex/sessions").read_dir().unwrap().next().is_some());
+}
+
+#[test]
+fn test_disable_source_flag() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    let (stdout, _, success) = run_cli(&["list", "--disable-source", "claude"], temp_dir.path());
+    assert!(success);
+    assert_eq!(listed_ids(&stdout), ["test-codex-456"]);
+
+    // Never discovered, so never indexed
+    let (stdout, _, _) = run_cli(&["sources", "--json", "--disable-source", "claude"], temp_dir.path());
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let claude = &json["sources"][0];
+    assert_eq!(claude["source"], "claude");
+    assert_eq!(claude["enabled"], false);
+    assert_eq!(claude["indexed"], 0);
+
+    let (_, stderr, success) = run_cli(&["list", "--disable-source", "nope"], temp_dir.path());
+    assert!(!success);
+    assert!(stderr.contains("Invalid source 'nope'"));
+}
+
+#[test]
+fn test_exclude_patterns() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+
+    // Indexed before the pattern existed
+    let (stdout, _, _) = run_cli(&["list"], temp_dir.path());
+    assert_eq!(listed_ids(&stdout).len(), 2);
+
+    // Matches the Codex session's cwd (/projects/webapp), not its file path
+    write_config(temp_dir.path(), "exclude = [\"**/webapp/**\"]\n");
+    let (stdout, stderr, success) = run_cli(&["list"], temp_dir.path());
+    assert!(success, "stderr: {}", stderr);
+    assert_eq!(listed_ids(&stdout), ["test-claude-123"]);
+
+    let (_, stderr, success) = run_cli(&["read", "test-codex-456"], temp_dir.path());
+    assert!(!success);
+    assert!(stderr.contains("Session not found"), "stderr: {}", stderr);
+
+    // Patterns also match the session file's path
+    write_config(temp_dir.path(), "exclude = [\"**/.claude/**\"]\n");
+    let (stdout, _, _) = run_cli(&["list"], temp_dir.path());
+    assert_eq!(listed_ids(&stdout), ["test-codex-456"]);
+
+    write_config(temp_dir.path(), "exclude = [\"[\"]\n");
+    let (_, stderr, success) = run_cli(&["list"], temp_dir.path());
+    assert!(!success);
+    assert!(stderr.contains("Invalid exclude pattern"), "stderr: {}", stderr);
+}
+
+#[test]
+fn test_project_config_overrides() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let repo = TempDir::new().unwrap();
+    let repo_dir = repo.path().display().to_string();
+    std::fs::create_dir_all(repo.path().join("src")).unwrap();
+    std::fs::write(
+        repo.path().join(".recall.toml"),
+        "[cli]\nlist_limit = 1\n\n[resume]\nclaude = \"echo project {id}\"\n",
+    )
+    .unwrap();
+    write_session_in(temp_dir.path(), "in-repo", &repo_dir, 3);
+
+    let run = |args: &[&str], cwd: &std::path::Path| {
+        let output = Command::new(recall_bin())
+            .args(args)
+            .env("RECALL_HOME_OVERRIDE", temp_dir.path())
+            .env("RECALL_CWD_OVERRIDE", cwd)
+            .output()
+            .expect("Failed to run recall");
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        assert!(output.status.success(), "stderr: {}", stderr);
+        stdout
+    };
+
+    // Found from a subdirectory, on top of the global config
+    write_config(temp_dir.path(), "[cli]\nlist_limit = 2\n");
+    assert_eq!(listed_ids(&run(&["list"], &repo.path().join("src"))).len(), 1);
+    assert_eq!(listed_ids(&run(&["list"], temp_dir.path())).len(), 2);
+
+    let stdout = run(&["--last"], repo.path());
+    assert_eq!(stdout.trim(), "project in-repo");
+}
+
+#[test]
+fn test_project_config_exclude_applies_to_its_sessions() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let repo = TempDir::new().unwrap();
+    let repo_dir = repo.path().display().to_string();
+    std::fs::write(repo.path().join(".recall.toml"), "exclude = [\"**\"]\n").unwrap();
+    write_session_in(temp_dir.path(), "secret", &format!("{}/api", repo_dir), 3);
+
+    // Launched elsewhere, the project's sessions still stay out of the shared index
+    let (stdout, stderr, success) = run_cli(&["list"], temp_dir.path());
+    assert!(success, "stderr: {}", stderr);
+    let mut ids = listed_ids(&stdout);
+    ids.sort();
+    assert_eq!(ids, ["test-claude-123", "test-codex-456"]);
+
+    // ...and launched inside it, its exclude doesn't hide everyone else's
+    let (stdout, _, success) = run_last(&["list"], temp_dir.path(), &repo_dir);
+    assert!(success);
+    assert_eq!(listed_ids(&stdout).len(), 2);
+
+    // Dropping the exclude brings the session back once reindexed
+    std::fs::write(repo.path().join(".recall.toml"), "").unwrap();
+    let (stdout, _, success) = run_cli(&["list", "--reindex"], temp_dir.path());
+    assert!(success);
+    assert!(listed_ids(&stdout).contains(&"secret".to_string()));
+}
+
+#[test]
+fn test_extra_homes_share_one_index() {
+    let _lock = lock_test();
+    let temp_dir = setup_test_env();
+    let old_home = TempDir::new().unwrap();
+    write_claude_session(
+        old_home.path(),
+        "old-machine-1",
+        2,
+        &[("user", "from the old laptop".to_string())],
+    );
+    write_config(
+        temp_dir.path(),
+        &format!("extra_homes = [{:?}]\n", old_home.path().display().to_string()),
+    );
+
+    let (stdout, stderr, success) = run_cli(&["list"], temp_dir.path());
+    assert!(success, "stderr: {}", stderr);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let sessions = json["sessions"].as_array().unwrap();
+    let mut ids: Vec<&str> = sessions.iter().map(|s| s["session_id"].as_str().unwrap()).collect();
+    ids.sort();
+    assert_eq!(ids, ["old-machine-1", "test-claude-123", "test-codex-456"]);
+
+    // Sessions from the extra home say where they came from
+    for session in sessions {
+        let origin = session.get("origin").and_then(|o| o.as_str());
+        if session["session_id"] == "old-machine-1" {
+            assert_eq!(origin, Some(old_home.path().to_str().unwrap()));
+        } else {
+            assert_eq!(origin, None);
+        }
+    }
+
+    let (stdout, _, _) = run_cli(&["read", "old-machine-1"], temp_dir.path());
+    assert!(stdout.contains("from the old laptop"));
+
+    let (stdout, _, _) = run_cli(&["sources"], temp_dir.path());
+    assert!(stdout.contains(&old_home.path().join(".claude/projects").display().to_string()));
+}